@@ -0,0 +1,47 @@
+#[path = "../tests/test_spec.rs"]
+mod test_spec;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use ebml_iterable::nonblocking::TagIteratorAsync;
+use ebml_iterable::specs::Master;
+use ebml_iterable::TagWriter;
+use futures::executor::block_on;
+
+use test_spec::TestSpec;
+
+fn document() -> Vec<u8> {
+    let clusters = (0..200u64).map(|cluster| {
+        let mut children = vec![TestSpec::Count(cluster * 1000)];
+        children.extend((0..20u8).map(|block| TestSpec::Block(vec![block; 1024])));
+        TestSpec::Cluster(Master::Full(children))
+    }).collect();
+
+    let mut writer = TagWriter::new(Vec::new());
+    writer.write(&TestSpec::Segment(Master::Full(clusters))).unwrap();
+    writer.into_inner().unwrap()
+}
+
+fn async_read(c: &mut Criterion) {
+    let data = document();
+
+    let mut group = c.benchmark_group("async_read");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("start_end", |b| b.iter(|| block_on(async {
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(black_box(&data[..]), &[]);
+        while let Some(tag) = iter.next().await {
+            black_box(tag.unwrap());
+        }
+    })));
+    group.bench_function("buffered_clusters", |b| b.iter(|| block_on(async {
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(black_box(&data[..]), &[TestSpec::Cluster(Master::Start)]);
+        while let Some(tag) = iter.next().await {
+            black_box(tag.unwrap());
+        }
+    })));
+    group.finish();
+}
+
+criterion_group!(benches, async_read);
+criterion_main!(benches);
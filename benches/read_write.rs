@@ -0,0 +1,117 @@
+#[path = "../tests/test_spec.rs"]
+mod test_spec;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use ebml_iterable::specs::Master;
+use ebml_iterable::tools::{self, Vint};
+use ebml_iterable::{TagIterator, TagWriter};
+
+use test_spec::TestSpec;
+
+const CLUSTER_COUNT: u64 = 200;
+const BLOCKS_PER_CLUSTER: u64 = 20;
+const BLOCK_LEN: usize = 1024;
+
+///
+/// A Matroska-like layout: a header followed by a segment of clusters, each holding a timestamp and a run of blocks.
+///
+fn clusters() -> Vec<TestSpec> {
+    (0..CLUSTER_COUNT).map(|cluster| {
+        let mut children = vec![TestSpec::Count(cluster * 1000)];
+        children.extend((0..BLOCKS_PER_CLUSTER).map(|block| TestSpec::Block(vec![block as u8; BLOCK_LEN])));
+        children.push(TestSpec::CueRefCluster(cluster));
+        TestSpec::Cluster(Master::Full(children))
+    }).collect()
+}
+
+fn document() -> Vec<u8> {
+    let mut writer = TagWriter::new(Vec::new());
+    writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+    writer.write(&TestSpec::Segment(Master::Full(clusters()))).unwrap();
+    writer.into_inner().unwrap()
+}
+
+fn vint_codec(c: &mut Criterion) {
+    let values: Vec<u64> = (0..8).flat_map(|length| [(1u64 << (7 * length)) - 1, 1u64 << (7 * length)]).filter(|v| *v < (1 << 56) - 1).collect();
+    let encoded: Vec<Vec<u8>> = values.iter().map(|v| v.as_vint().unwrap()).collect();
+    let padded: Vec<Vec<u8>> = encoded.iter().map(|e| { let mut e = e.clone(); e.resize(16, 0); e }).collect();
+
+    let mut group = c.benchmark_group("vint");
+    group.throughput(Throughput::Elements(values.len() as u64));
+    group.bench_function("encode", |b| b.iter(|| {
+        for v in values.iter() {
+            black_box(black_box(*v).as_vint().unwrap());
+        }
+    }));
+    group.bench_function("decode_exact", |b| b.iter(|| {
+        for e in encoded.iter() {
+            black_box(tools::read_vint(black_box(e)).unwrap());
+        }
+    }));
+    group.bench_function("decode_padded", |b| b.iter(|| {
+        for e in padded.iter() {
+            black_box(tools::read_vint(black_box(e)).unwrap());
+        }
+    }));
+    group.finish();
+}
+
+fn read(c: &mut Criterion) {
+    let data = document();
+
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("start_end", |b| b.iter(|| {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(black_box(&data[..]), &[]);
+        for tag in iter {
+            black_box(tag.unwrap());
+        }
+    }));
+    group.bench_function("buffered_clusters", |b| b.iter(|| {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(black_box(&data[..]), &[TestSpec::Cluster(Master::Start)]);
+        for tag in iter {
+            black_box(tag.unwrap());
+        }
+    }));
+    group.bench_function("buffered_segment", |b| b.iter(|| {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(black_box(&data[..]), &[TestSpec::Segment(Master::Start), TestSpec::Cluster(Master::Start)]);
+        for tag in iter {
+            black_box(tag.unwrap());
+        }
+    }));
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let clusters = clusters();
+    let data_len = document().len();
+
+    let mut group = c.benchmark_group("write");
+    group.throughput(Throughput::Bytes(data_len as u64));
+    group.bench_function("start_end", |b| b.iter_batched(|| Vec::with_capacity(data_len), |dest| {
+        let mut writer = TagWriter::new(dest);
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        for cluster in clusters.iter() {
+            if let TestSpec::Cluster(Master::Full(children)) = cluster {
+                writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+                for child in children {
+                    writer.write(child).unwrap();
+                }
+                writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+            }
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        black_box(writer.into_inner().unwrap())
+    }, BatchSize::SmallInput));
+    group.bench_function("full_tree", |b| b.iter_batched(|| Vec::with_capacity(data_len), |dest| {
+        let mut writer = TagWriter::new(dest);
+        writer.write(&TestSpec::Segment(Master::Full(clusters.clone()))).unwrap();
+        black_box(writer.into_inner().unwrap())
+    }, BatchSize::SmallInput));
+    group.finish();
+}
+
+criterion_group!(benches, vint_codec, read, write);
+criterion_main!(benches);
@@ -0,0 +1,16 @@
+// Runs the smoke test built by `cargo build --release --target wasm32-unknown-unknown`
+const fs = require('fs');
+const path = require('path');
+
+const wasm = path.join(__dirname, 'target', 'wasm32-unknown-unknown', 'release', 'ebml_iterable_wasm_smoke.wasm');
+WebAssembly.instantiate(fs.readFileSync(wasm), {}).then(({ instance }) => {
+    const count = instance.exports.run();
+    if (count !== 2) {
+        console.error(`Expected 2 tags, read ${count}`);
+        process.exit(1);
+    }
+    console.log(`Read ${count} tags`);
+}).catch((err) => {
+    console.error(err);
+    process.exit(1);
+});
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use ebml_iterable::utils::decode_slice;
+use ebml_iterable::{PushDecoder, TagIterator, TagWriter};
+use ebml_iterable_specification::empty_spec::EmptySpec;
+
+///
+/// Writes a small document (recording an audit log and cues), reads it back in a few different ways, and returns the number of tags read (or 0 if the results don't match).
+///
+#[no_mangle]
+pub extern "C" fn run() -> u32 {
+    let tags = vec![EmptySpec::with_data(0x4286, &[0x01]), EmptySpec::with_data(0x4287, &[0x02, 0x03])];
+    let mut writer = TagWriter::new(Vec::new());
+    writer.set_metrics_callback(Duration::from_secs(1), |_| {});
+    writer.record_audit_log(true);
+    let cues = writer.build_cues(&[0x4287], |element| Some(element.offset));
+    for tag in &tags {
+        writer.write(tag).unwrap();
+    }
+    writer.flush().unwrap();
+    let logged = writer.take_audit_log().len();
+    let data = writer.into_inner().unwrap();
+
+    let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(&data[..], &[]);
+    iterator.set_metrics_callback(Duration::from_secs(1), |_| {});
+    let read: Vec<EmptySpec> = iterator.map(|t| t.unwrap()).collect();
+
+    let mut decoder: PushDecoder<EmptySpec> = PushDecoder::new(&[]);
+    let mut pushed = Vec::new();
+    for byte in &data {
+        decoder.push(&[*byte]);
+        while let Some(tag) = decoder.next_tag() {
+            pushed.push(tag.unwrap());
+        }
+    }
+    decoder.finish();
+    pushed.extend(std::iter::from_fn(|| decoder.next_tag()).map(|t| t.unwrap()));
+
+    if logged != tags.len() || cues.take_entries() != vec![4] {
+        return 0;
+    }
+    if read != tags || pushed != tags || decode_slice::<EmptySpec>(&data, &[]).unwrap() != tags {
+        return 0;
+    }
+    read.len() as u32
+}
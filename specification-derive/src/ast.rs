@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 use proc_macro2::TokenStream;
-use syn::{ItemEnum, Error, Generics, Ident, Result, LitInt, Path, spanned::Spanned};
+use syn::{ItemEnum, Error, Generics, Ident, Result, LitInt, Path, Token, punctuated::Punctuated, spanned::Spanned};
 
 use ebml_iterable_specification::TagDataType;
 use quote::ToTokens;
@@ -20,6 +20,17 @@ pub struct Variant<'a> {
     pub id_attr: (u64, Attribute<'a>),
     pub data_type_attr: (TagDataType, Path, Attribute<'a>),
     pub path_attr: Option<(EBMLPath, Attribute<'a>)>,
+    pub meta: Meta,
+}
+
+#[derive(Default)]
+pub struct Meta {
+    pub default: Option<String>,
+    pub min_occurs: Option<u64>,
+    pub max_occurs: Option<u64>,
+    pub min_version: Option<u64>,
+    pub max_version: Option<u64>,
+    pub restricted_values: Option<Vec<u64>>,
 }
 
 pub struct Attribute<'a> {
@@ -50,6 +61,7 @@ impl<'a> Variant<'a> {
         let mut id_attr: Option<(u64, Attribute<'a>)> = None;
         let mut data_type_attr: Option<(TagDataType, Path, Attribute<'a>)> = None;
         let mut path_attr: Option<(EBMLPath, Attribute<'a>)> = None;
+        let mut meta = Meta::default();
 
         for attr in &node.attrs {
             if attr.path.is_ident("id") {
@@ -103,6 +115,9 @@ impl<'a> Variant<'a> {
                             last_was_global = false;
                             variant_names.get(id).ok_or(Error::new(id.span(), format!("Unknown variant [{id}] in path")))?;
                         },
+                        PathPart::External(_) => {
+                            last_was_global = false;
+                        },
                         PathPart::Global((_, max)) => {
                             if matches!(max, Some(0)) {
                                 return Err(Error::new(attr.span(), "Global maximum cannot be 0".to_string()));
@@ -118,9 +133,46 @@ impl<'a> Variant<'a> {
                     original: attr,
                     tokens: &attr.tokens,
                 }))
+            } else if attr.path.is_ident("default_value") {
+                if meta.default.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                let val = attr.parse_args::<syn::Expr>().map_err(|err| Error::new(err.span(), format!("{} requires a literal value", attr.to_token_stream())))?;
+                meta.default = Some(match &val {
+                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => lit.value(),
+                    syn::Expr::Lit(_) | syn::Expr::Unary(syn::ExprUnary { op: syn::UnOp::Neg(_), .. }) => val.to_token_stream().to_string().replace(' ', ""),
+                    _ => return Err(Error::new_spanned(attr, format!("{} requires a literal value", attr.to_token_stream()))),
+                });
+            } else if attr.path.is_ident("restricted_values") {
+                if meta.restricted_values.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                let values = attr.parse_args_with(Punctuated::<LitInt, Token![,]>::parse_separated_nonempty)
+                    .map_err(|err| Error::new(err.span(), format!("{} requires a list of unsigned integers", attr.to_token_stream())))?;
+                meta.restricted_values = Some(values.iter().map(|v| v.base10_parse::<u64>()).collect::<Result<_>>()?);
+            } else if let Some(field) = meta_number_field(&mut meta, attr) {
+                if field.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                *field = Some(attr.parse_args::<LitInt>()?.base10_parse::<u64>()?);
             }
         }
 
+        if let (Some(min), Some(max)) = (meta.min_occurs, meta.max_occurs) {
+            if min > max {
+                return Err(Error::new_spanned(node, "#[min_occurs] cannot be greater than #[max_occurs]"));
+            }
+        }
+        if let (Some(min), Some(max)) = (meta.min_version, meta.max_version) {
+            if min > max {
+                return Err(Error::new_spanned(node, "#[min_version] cannot be greater than #[max_version]"));
+            }
+        }
+
+        if meta.restricted_values.is_some() && !matches!(data_type_attr, Some((TagDataType::UnsignedInt, _, _))) {
+            return Err(Error::new_spanned(node, "#[restricted_values] can only be used on UnsignedInt elements"));
+        }
+
         let id_attr = if let Some(id_attr) = id_attr { id_attr } else {
             return Err(Error::new_spanned(node, "#[id] attribute is required when using #[ebml_specification] attribute"));
         };
@@ -134,7 +186,24 @@ impl<'a> Variant<'a> {
             ident: node.ident.clone(),
             id_attr,
             data_type_attr,
-            path_attr
+            path_attr,
+            meta,
         })
     }
 }
+
+pub const META_ATTRIBUTES: [&str; 6] = ["default_value", "restricted_values", "min_occurs", "max_occurs", "min_version", "max_version"];
+
+fn meta_number_field<'m>(meta: &'m mut Meta, attr: &syn::Attribute) -> Option<&'m mut Option<u64>> {
+    if attr.path.is_ident("min_occurs") {
+        Some(&mut meta.min_occurs)
+    } else if attr.path.is_ident("max_occurs") {
+        Some(&mut meta.max_occurs)
+    } else if attr.path.is_ident("min_version") {
+        Some(&mut meta.min_version)
+    } else if attr.path.is_ident("max_version") {
+        Some(&mut meta.max_version)
+    } else {
+        None
+    }
+}
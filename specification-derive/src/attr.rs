@@ -2,28 +2,67 @@ use proc_macro2::TokenStream;
 use std::str::FromStr;
 use std::collections::HashMap;
 use syn::spanned::Spanned;
-use syn::{Attribute, ItemEnum, Result, Error, Visibility, Fields, FieldsUnnamed, Path, Ident, Variant};
-use quote::{quote, quote_spanned, ToTokens};
+use syn::{Attribute, ItemEnum, Result, Error, Visibility, Fields, FieldsUnnamed, Path, Ident, Variant, LitInt, Token};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use ebml_iterable_specification::TagDataType;
 use ebml_iterable_specification::TagDataType::Master;
 
-use super::ast::Enum;
+use super::ast::{Enum, META_ATTRIBUTES};
 use super::pathing::PathPart;
 
-pub fn impl_ebml_specification(original: &mut ItemEnum) -> Result<TokenStream> {
+// the arguments of the #[ebml_specification()] attribute
+#[derive(Default)]
+pub struct SpecOptions {
+    emit_metadata: bool,
+    // set for #[non_exhaustive] specs, which name the variant that holds unknown elements
+    extension_variant: Option<Ident>,
+}
+
+impl Parse for SpecOptions {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut options = SpecOptions::default();
+        while !input.is_empty() {
+            let arg: Ident = input.parse()?;
+            if arg == "metadata" && !options.emit_metadata {
+                options.emit_metadata = true;
+            } else if arg == "non_exhaustive" && options.extension_variant.is_none() {
+                let content;
+                syn::parenthesized!(content in input);
+                options.extension_variant = Some(content.parse::<Ident>().map_err(|err| Error::new(err.span(), "`non_exhaustive` requires the name of the extension variant, e.g. `non_exhaustive(Extension)`"))?);
+            } else {
+                return Err(Error::new(arg.span(), "unknown or duplicate #[ebml_specification] argument, expected `metadata` or `non_exhaustive(VariantName)`"));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(options)
+    }
+}
+
+pub fn impl_ebml_specification(original: &mut ItemEnum, options: &SpecOptions) -> Result<TokenStream> {
     let tag_data_type = spanned_tag_data_type(original);
-    original.variants.push(syn::parse2::<Variant>(quote!{
-        #[id(0xbf)]
-        #[data_type(#tag_data_type::Binary)]
-        #[doc_path((1-))]
-        Crc32
-    })?);
-    original.variants.push(syn::parse2::<Variant>(quote!{
-        #[id(0xec)]
-        #[data_type(#tag_data_type::Binary)]
-        #[doc_path((-))]
-        Void
-    })?);
+    inject_global_elements(original, &tag_data_type)?;
+
+    // The global EBML elements are only added if the spec doesn't declare them itself
+    if !original.variants.iter().any(|v| v.ident == "Crc32") {
+        original.variants.push(syn::parse2::<Variant>(quote!{
+            #[id(0xbf)]
+            #[data_type(#tag_data_type::Binary)]
+            #[doc_path((1-))]
+            Crc32
+        })?);
+    }
+    if !original.variants.iter().any(|v| v.ident == "Void") {
+        original.variants.push(syn::parse2::<Variant>(quote!{
+            #[id(0xec)]
+            #[data_type(#tag_data_type::Binary)]
+            #[doc_path((-))]
+            Void
+        })?);
+    }
 
     let input = Enum::from_syn(original)?;
 
@@ -43,48 +82,107 @@ pub fn impl_ebml_specification(original: &mut ItemEnum) -> Result<TokenStream> {
         }
     }
 
-    let ebml_specification_impl = get_impl(input)?;
-    let modified_orig = modify_orig(original)?;
+    let raw_variant = options.extension_variant.clone().unwrap_or_else(|| format_ident!("RawTag"));
+    if let Some(variant) = input.variants.iter().find(|v| v.ident == raw_variant) {
+        return Err(Error::new_spanned(variant.original, format!("[{raw_variant}] is reserved for elements that aren't in the spec")));
+    }
+
+    let spec_metadata = if options.emit_metadata {
+        get_spec_metadata(&input)
+    } else {
+        TokenStream::new()
+    };
+    let path_constants = get_path_constants(&input);
+    let ebml_specification_impl = get_impl(input, &raw_variant)?;
+    let modified_orig = modify_orig(original, &raw_variant, options.extension_variant.is_some())?;
 
     Ok(quote!(
         #modified_orig
 
         #ebml_specification_impl
+
+        #path_constants
+
+        #spec_metadata
     ))
 }
 
+// an entry of the enum level #[global_elements()] attribute, e.g. `(1-)/Checksum: Binary = 0x4dbb`
+struct GlobalElement {
+    range: PathPart,
+    ident: Ident,
+    ty: Ident,
+    id: LitInt,
+}
+
+impl Parse for GlobalElement {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let span = input.span();
+        let range: PathPart = input.parse()?;
+        if !matches!(range, PathPart::Global(_)) {
+            return Err(Error::new(span, "global elements must start with their occurrence range, e.g. `(1-)/Name: Type = id`"));
+        }
+        input.parse::<Token![/]>()?;
+        let ident: Ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let id: LitInt = input.parse()?;
+        Ok(Self { range, ident, ty, id })
+    }
+}
+
+// turns the entries of #[global_elements()] attributes into variants with global paths
+fn inject_global_elements(original: &mut ItemEnum, tag_data_type: &TokenStream) -> Result<()> {
+    let (global_attrs, attrs): (Vec<Attribute>, Vec<Attribute>) = original.attrs.drain(..).partition(|a| a.path.is_ident("global_elements"));
+    original.attrs = attrs;
+
+    for attr in global_attrs {
+        let elements = attr.parse_args_with(Punctuated::<GlobalElement, Token![,]>::parse_terminated)?;
+        for GlobalElement { range, ident, ty, id } in elements {
+            if original.variants.iter().any(|v| v.ident == ident) {
+                return Err(Error::new(ident.span(), format!("global element [{ident}] has the same name as another variant")));
+            }
+            original.variants.push(syn::parse2::<Variant>(quote_spanned!{ ident.span() =>
+                #[id(#id)]
+                #[data_type(#tag_data_type::#ty)]
+                #[doc_path(#range)]
+                #ident
+            })?);
+        }
+    }
+
+    Ok(())
+}
+
 // verify all parents are Master type elements and their path lines up with this item's path
 fn validate_path(origin: &crate::ast::Variant, variants_map: &HashMap<&Ident, &crate::ast::Variant>) -> Result<()> {
     // Only validate the element if it has a path attribute
     if let Some(path_parts) = origin.path_attr.as_ref().map(|(path, _)| &path.parts) {
-        // Only validate if there is a specific parent element
-        if let Some(parent) = path_parts.iter().rev().filter_map(|p| {
-            if let PathPart::Ident(ident) = p {
-                Some(ident)
-            } else {
-                None
-            }
-        }).next() {
+        // Only validate if there is a specific parent element in this spec (external parents are declared elsewhere)
+        if let Some((index, PathPart::Ident(parent))) = path_parts.iter().enumerate().rev().find(|(_, p)| !matches!(p, PathPart::Global(_))) {
             let parent = *variants_map.get(parent).unwrap();
             if parent.data_type_attr.0 != Master {
                 return Err(Error::new_spanned(parent.original, "Parents must be of Master type"))
             }
 
-            if let Some((parent_path, _)) = parent.path_attr.as_ref() {
-                for i in 0..parent_path.parts.len() {
-                    if parent_path.parts[i] != path_parts[i] {
-                        return Err(Error::new_spanned(origin.original, format!("Path segment [{}] did not align with parent [{}] path.", path_parts[i], parent.ident)));
-                    }
+            let parent_path: Vec<&PathPart> = parent.path_attr.iter().flat_map(|(path, _)| path.parts.iter()).collect();
+            if parent_path.len() != index {
+                return Err(Error::new_spanned(origin.original, format!("Path did not align with parent [{}] path.", parent.ident)));
+            }
+            for (i, part) in parent_path.into_iter().enumerate() {
+                if *part != path_parts[i] {
+                    return Err(Error::new_spanned(origin.original, format!("Path segment [{}] did not align with parent [{}] path.", path_parts[i], parent.ident)));
                 }
-                validate_path(parent, variants_map)?;
             }
+            validate_path(parent, variants_map)?;
         }
     }
 
     Ok(())
 }
 
-fn modify_orig(original: &mut ItemEnum) -> Result<TokenStream> {
+fn modify_orig(original: &mut ItemEnum, raw_variant: &Ident, non_exhaustive: bool) -> Result<TokenStream> {
     let spanned_master_enum = spanned_master_enum(original);
     for var in original.variants.iter_mut() {
         let data_type_attribute: &Attribute = var
@@ -96,6 +194,7 @@ fn modify_orig(original: &mut ItemEnum) -> Result<TokenStream> {
         let data_type_path = data_type_attribute.parse_args::<Path>().map_err(|err| Error::new(err.span(), format!("{} requires `ebml_iterable::TagDataType`", data_type_attribute.to_token_stream())))?;
         let data_type = get_last_path_ident(&data_type_path).ok_or_else(|| Error::new_spanned(data_type_attribute.clone(), format!("{} requires `ebml_iterable::TagDataType`", data_type_attribute.to_token_stream())))?;
 
+        let data_type_declares_storage = data_type == "Binary" || data_type == "Utf8";
         let data_type = if data_type == "Master" {
             let orig_ident = &original.ident;
             quote!( (#spanned_master_enum<#orig_ident>) )
@@ -113,15 +212,23 @@ fn modify_orig(original: &mut ItemEnum) -> Result<TokenStream> {
             return Err(Error::new_spanned(data_type_attribute.clone(), format!("unknown data_type \"{data_type}\"")));
         };
 
-        var.attrs.retain(|a| !(a.path.is_ident("id") || a.path.is_ident("data_type") || a.path.is_ident("doc_path")));
-        var.fields = Fields::Unnamed(syn::parse2::<FieldsUnnamed>(data_type)?);
+        var.attrs.retain(|a| !(a.path.is_ident("id") || a.path.is_ident("data_type") || a.path.is_ident("doc_path") || META_ATTRIBUTES.iter().any(|m| a.path.is_ident(m))));
+
+        // Binary and Utf8 variants may declare their own storage type (like `bytes::Bytes` or `LazyUtf8`)
+        let declares_storage = data_type_declares_storage && matches!(&var.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1);
+        if !declares_storage {
+            var.fields = Fields::Unnamed(syn::parse2::<FieldsUnnamed>(data_type)?);
+        }
+    }
+    original.variants.push(syn::parse2::<Variant>(quote!(#raw_variant(u64, ::std::vec::Vec<u8>)))?);
+    if non_exhaustive {
+        original.attrs.push(syn::parse_quote!(#[non_exhaustive]));
     }
-    original.variants.push(syn::parse_str::<Variant>("RawTag(u64, ::std::vec::Vec<u8>)")?);
 
     Ok(quote!(#original))
 }
 
-fn get_impl(input: Enum) -> Result<TokenStream> {
+fn get_impl(input: Enum, raw_variant: &Ident) -> Result<TokenStream> {
     let ty = &input.ident;
     let spanned_master_enum = spanned_master_enum(input.original);
 
@@ -168,6 +275,9 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                         let id = variant_map.get(&ident).map(|v| v.id_attr.0).unwrap();
                         quote_spanned! { path.1.original.span() => #path_part::Id(#id) }
                     },
+                    PathPart::External((_, id)) => {
+                        quote_spanned! { path.1.original.span() => #path_part::Id(#id) }
+                    },
                     PathPart::Global((min, max)) => {
                         let min_tokens = if let Some(min) = min {
                             quote!{Some(#min)}
@@ -191,6 +301,38 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         }
     });
 
+    let get_name_by_id = input.variants.iter().map(|var: &crate::ast::Variant| {
+        let id = &var.id_attr.0;
+        let name = var.ident.to_string();
+
+        quote_spanned! { var.original.span() =>
+            #id => Some(#name),
+        }
+    });
+
+    let get_id_by_name = input.variants.iter().map(|var: &crate::ast::Variant| {
+        let id = &var.id_attr.0;
+        let name = var.ident.to_string();
+
+        quote_spanned! { var.original.span() =>
+            #name => Some(#id),
+        }
+    });
+
+    let get_restricted_values = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let id = &var.id_attr.0;
+        var.meta.restricted_values.as_ref().map(|values| quote_spanned! { var.original.span() =>
+            #id => Some(&[#(#values),*]),
+        })
+    });
+
+    let get_default_value = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let id = &var.id_attr.0;
+        var.meta.default.as_ref().map(|default| quote_spanned! { var.original.span() =>
+            #id => Some(#default),
+        })
+    });
+
     let get_unsigned_int_tag = input.variants.iter()
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::UnsignedInt))
         .map(get_tag(String::from("data")));
@@ -199,13 +341,69 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Integer))
         .map(get_tag(String::from("data")));
 
-    let get_utf8_tag = input.variants.iter()
-    .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Utf8))
-        .map(get_tag(String::from("data")));
+    let declares_type = |v: &crate::ast::Variant, name: &str| matches!(v.original.fields.iter().next().map(|f| &f.ty), Some(syn::Type::Path(p)) if p.path.segments.last().is_some_and(|s| s.ident == name));
 
-    let get_binary_tag = input.variants.iter()
-        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Binary))
-        .map(get_tag(String::from("data.to_vec()")));
+    // Utf8 variants can hold either a `String` or a `LazyUtf8`
+    let is_lazy_utf8 = |v: &&crate::ast::Variant| declares_type(v, "LazyUtf8");
+    let utf8_variants = || input.variants.iter().filter(|v| matches!(&v.data_type_attr.0, TagDataType::Utf8));
+
+    let get_utf8_tag = utf8_variants().filter(|v| !is_lazy_utf8(v))
+        .map(get_tag(String::from("data")))
+        .chain(utf8_variants().filter(is_lazy_utf8).map(get_tag(String::from("data.into()"))));
+
+    let get_utf8_tag_bytes = if utf8_variants().any(|v| is_lazy_utf8(&v)) {
+        let arms = utf8_variants().map(|var| {
+            let name = &var.ident;
+            let id = &var.id_attr.0;
+            if is_lazy_utf8(&var) {
+                quote_spanned! { var.original.span() =>
+                    #id => Some(Ok(#ty::#name(data.into()))),
+                }
+            } else {
+                quote_spanned! { var.original.span() =>
+                    #id => Some(String::from_utf8(data).map(#ty::#name)),
+                }
+            }
+        });
+        quote! {
+            fn get_utf8_tag_bytes(id: u64, data: Vec<u8>) -> Option<Result<#ty, ::std::string::FromUtf8Error>> {
+                match id {
+                    #(#arms)*
+                    _ => None
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    // Binary variants can hold either a `Vec<u8>` or a `bytes::Bytes`
+    let is_bytes = |v: &&crate::ast::Variant| declares_type(v, "Bytes");
+    let binary_variants = || input.variants.iter().filter(|v| matches!(&v.data_type_attr.0, TagDataType::Binary));
+
+    let get_binary_tag = binary_variants().filter(|v| !is_bytes(v))
+        .map(get_tag(String::from("data.to_vec()")))
+        .chain(binary_variants().filter(is_bytes).map(get_tag(String::from("data.to_vec().into()"))));
+
+    let get_binary_tag_owned = binary_variants().filter(|v| !is_bytes(v))
+        .map(get_tag(String::from("data")))
+        .chain(binary_variants().filter(is_bytes).map(get_tag(String::from("data.into()"))));
+
+    let get_binary_tag_bytes = if binary_variants().any(|v| is_bytes(&v)) {
+        let arms = binary_variants().filter(|v| !is_bytes(v))
+            .map(get_tag(String::from("data.into()")))
+            .chain(binary_variants().filter(is_bytes).map(get_tag(String::from("data"))));
+        quote! {
+            fn get_binary_tag_bytes(id: u64, data: ::bytes::Bytes) -> Option<#ty> {
+                match id {
+                    #(#arms)*
+                    _ => None
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
 
     let get_float_tag = input.variants.iter()
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Float))
@@ -231,9 +429,33 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Integer))
         .map(as_data);
 
-    let as_utf8 = input.variants.iter()
-        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Utf8))
-        .map(as_data);
+    let as_utf8 = utf8_variants().filter(|v| !is_lazy_utf8(v))
+        .map(as_data)
+        .chain(utf8_variants().filter(is_lazy_utf8).map(|var| {
+            let name = &var.ident;
+            quote! {
+                #ty::#name(val) => val.to_str().ok(),
+            }
+        }));
+
+    let as_utf8_bytes = if utf8_variants().any(|v| is_lazy_utf8(&v)) {
+        let arms = utf8_variants().map(|var| {
+            let name = &var.ident;
+            quote! {
+                #ty::#name(val) => Some(val.as_bytes()),
+            }
+        });
+        quote! {
+            fn as_utf8_bytes(&self) -> Option<&[u8]> {
+                match self {
+                    #(#arms)*
+                    _ => None,
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
 
     let as_binary = input.variants.iter()
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Binary))
@@ -268,6 +490,34 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            fn get_name_by_id(id: u64) -> Option<&'static str> {
+                match id {
+                    #(#get_name_by_id)*
+                    _ => None
+                }
+            }
+
+            fn get_id_by_name(name: &str) -> Option<u64> {
+                match name {
+                    #(#get_id_by_name)*
+                    _ => None
+                }
+            }
+
+            fn get_restricted_values(id: u64) -> Option<&'static [u64]> {
+                match id {
+                    #(#get_restricted_values)*
+                    _ => None
+                }
+            }
+
+            fn get_default_value(id: u64) -> Option<&'static str> {
+                match id {
+                    #(#get_default_value)*
+                    _ => None
+                }
+            }
+
             fn get_unsigned_int_tag(id: u64, data: u64) -> Option<#ty> {
                 match id {
                     #(#get_unsigned_int_tag)*
@@ -289,6 +539,8 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            #get_utf8_tag_bytes
+
             fn get_binary_tag(id: u64, data: &[u8]) -> Option<#ty> {
                 match id {
                     #(#get_binary_tag)*
@@ -296,6 +548,15 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            fn get_binary_tag_owned(id: u64, data: Vec<u8>) -> Option<#ty> {
+                match id {
+                    #(#get_binary_tag_owned)*
+                    _ => None
+                }
+            }
+
+            #get_binary_tag_bytes
+
             fn get_float_tag(id: u64, data: f64) -> Option<#ty> {
                 match id {
                     #(#get_float_tag)*
@@ -311,7 +572,7 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
             }
 
             fn get_raw_tag(id: u64, data: &[u8]) -> #ty {
-                #ty::RawTag(id, data.to_vec())
+                #ty::#raw_variant(id, data.to_vec())
             }
         }
 
@@ -320,7 +581,7 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
             fn get_id(&self) -> u64 {
                 match self {
                     #(#get_id)*
-                    #ty::RawTag(id, _data) => *id,
+                    #ty::#raw_variant(id, _data) => *id,
                 }
             }
 
@@ -345,10 +606,12 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            #as_utf8_bytes
+
             fn as_binary(&self) -> Option<&[u8]> {
                 match self {
                     #(#as_binary)*
-                    #ty::RawTag(_id, data) => Some(data),
+                    #ty::#raw_variant(_id, data) => Some(data),
                     _ => None,
                 }
             }
@@ -370,6 +633,142 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
     })
 }
 
+fn get_spec_metadata(input: &Enum) -> TokenStream {
+    let ty = &input.ident;
+    let element_meta = spanned_element_meta(input.original);
+    let tag_data_type = spanned_tag_data_type(input.original);
+    let option = |val: &Option<u64>| match val {
+        Some(val) => quote!(Some(#val)),
+        None => quote!(None),
+    };
+
+    let entries = input.variants.iter().map(|var: &crate::ast::Variant| {
+        let id = &var.id_attr.0;
+        let name = var.ident.to_string();
+        let data_type = format_ident!("{}", format!("{:?}", var.data_type_attr.0));
+        let path = schema_path(var);
+        let default = match &var.meta.default {
+            Some(val) => quote!(Some(#val)),
+            None => quote!(None),
+        };
+        let min_occurs = option(&var.meta.min_occurs);
+        let max_occurs = option(&var.meta.max_occurs);
+        let min_version = option(&var.meta.min_version);
+        let max_version = option(&var.meta.max_version);
+
+        quote_spanned! { var.original.span() =>
+            #element_meta {
+                id: #id,
+                name: #name,
+                data_type: #tag_data_type::#data_type,
+                path: #path,
+                default: #default,
+                min_occurs: #min_occurs,
+                max_occurs: #max_occurs,
+                min_version: #min_version,
+                max_version: #max_version,
+            },
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #ty #ty_generics #where_clause {
+            /// Metadata for every element in this specification, in declaration order.
+            pub const SPEC_METADATA: &'static [#element_meta] = &[
+                #(#entries)*
+            ];
+        }
+    }
+}
+
+// emits a `PATH_*` constant holding the document path string of each element, e.g. `PATH_ROOT_PARENT_CHILD = "Root/Parent/Child"`
+// elements under global wildcards don't have a single path, so they're left out
+fn get_path_constants(input: &Enum) -> TokenStream {
+    let ty = &input.ident;
+    let constants = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let parts: Vec<&PathPart> = var.path_attr.iter().flat_map(|(path, _)| path.parts.iter()).collect();
+        let mut segments = Vec::new();
+        let mut names = Vec::new();
+        for part in parts {
+            match part {
+                PathPart::Ident(ident) => {
+                    segments.push(ident.to_string());
+                    names.push(upper_snake_case(&ident.to_string()));
+                },
+                // names of external elements aren't known to this spec, so they're given as ids
+                PathPart::External((ident, id)) => {
+                    segments.push(format!("0x{id:x}"));
+                    names.push(upper_snake_case(&ident.to_string()));
+                },
+                PathPart::Global(_) => return None,
+            }
+        }
+        segments.push(var.ident.to_string());
+        names.push(upper_snake_case(&var.ident.to_string()));
+
+        let name = format_ident!("PATH_{}", names.join("_"), span = var.ident.span());
+        let path = segments.join("/");
+        let doc = format!(" The document path of [`{}::{}`].", ty, var.ident);
+        Some(quote_spanned! { var.original.span() =>
+            #[doc = #doc]
+            pub const #name: &'static str = #path;
+        })
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #ty #ty_generics #where_clause {
+            #(#constants)*
+        }
+    }
+}
+
+// converts a variant name to the upper snake case used for constants, e.g. `EBMLMaxIDLength` -> `EBML_MAX_ID_LENGTH`
+fn upper_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_uppercase());
+    }
+    result
+}
+
+// builds the EBML schema style path for an element, e.g. `\Root\Parent\Child` or `\(1-\)Crc32`
+fn schema_path(var: &crate::ast::Variant) -> String {
+    let mut path = String::new();
+    let mut last_was_global = false;
+    for part in var.path_attr.iter().flat_map(|(path, _)| path.parts.iter()) {
+        match part {
+            PathPart::Ident(ident) | PathPart::External((ident, _)) => {
+                if !last_was_global {
+                    path.push('\\');
+                }
+                path.push_str(&ident.to_string());
+                last_was_global = false;
+            },
+            PathPart::Global((min, max)) => {
+                let min = min.map(|v| v.to_string()).unwrap_or_default();
+                let max = max.map(|v| v.to_string()).unwrap_or_default();
+                path.push_str(&format!("\\({min}-{max}\\)"));
+                last_was_global = true;
+            }
+        }
+    }
+    if !last_was_global {
+        path.push('\\');
+    }
+    path.push_str(&var.ident.to_string());
+    path
+}
+
 fn spanned_ebml_iterable_specs(input: &ItemEnum) -> TokenStream {
     let vis_span = match &input.vis {
         Visibility::Public(vis) => Some(vis.pub_token.span()),
@@ -417,6 +816,13 @@ fn spanned_path_part(input: &ItemEnum) -> TokenStream {
     quote!(#path #r#type)
 }
 
+fn spanned_element_meta(input: &ItemEnum) -> TokenStream {
+    let path = spanned_ebml_iterable_specs(input);
+    let last_span = input.ident.span();
+    let r#type = quote_spanned!(last_span=> ElementMeta);
+    quote!(#path #r#type)
+}
+
 fn get_last_path_ident(path: &Path) -> Option<&Ident> {
     let seg = path.segments.iter().last();
     seg.map(|seg| &seg.ident)
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use proc_macro2::TokenStream;
 use syn::{Attribute, AttrStyle, Ident, LitInt, parse::Parse, Token, Variant, Visibility};
 use syn::parse::{ParseBuffer, ParseStream};
@@ -38,7 +40,20 @@ impl EasyEBML {
     pub fn implement(self) -> Result<TokenStream> {
         let EasyEBML { attrs, visibility, ident, variants } = self;
 
-        let variants: Vec<_> = variants.into_iter().map(EasyEBMLVariant::into_variant).collect::<Result<_>>()?;
+        // Elements of another spec only need their id once; later paths can refer to them by name
+        let mut externals: HashMap<Ident, u64> = HashMap::new();
+        for part in variants.iter().flat_map(|v| v.path.parts.iter()) {
+            if let PathPart::External((name, id)) = part {
+                if matches!(externals.insert(name.clone(), *id), Some(existing) if existing != *id) {
+                    return Err(Error::new(name.span(), format!("external element [{name}] declared with different ids")));
+                }
+            }
+        }
+        if let Some(variant) = variants.iter().find(|v| v.path.parts.last().is_some_and(|p| matches!(p, PathPart::Ident(name) if externals.contains_key(name)))) {
+            return Err(Error::new(variant.path.span, "easy_ebml enum variant has the same name as an external element"));
+        }
+
+        let variants: Vec<_> = variants.into_iter().map(|v| v.into_variant(&externals)).collect::<Result<_>>()?;
 
         Ok(quote!(
             #[ebml_iterable::specs::ebml_specification]
@@ -57,13 +72,20 @@ pub struct EasyEBMLVariant {
 }
 
 impl EasyEBMLVariant {
-    pub fn into_variant(self) -> Result<Variant> {
+    pub fn into_variant(self, externals: &HashMap<Ident, u64>) -> Result<Variant> {
         let EasyEBMLVariant { path, ty, id } = self;
         let span = path.span;
-        let mut path: Vec<PathPart> = path.parts.into_iter().collect();
+        let mut path: Vec<PathPart> = path.parts.into_iter().map(|part| match part {
+            PathPart::Ident(name) if externals.contains_key(&name) => {
+                let id = externals[&name];
+                PathPart::External((name, id))
+            },
+            part => part,
+        }).collect();
         let ident: Ident = match path.pop().ok_or_else(|| Error::new(span, "easy_ebml enum variant must be at least: `Name: Type = id`"))? {
             PathPart::Ident(id) => Ok(id),
             PathPart::Global(_) => Err(Error::new(span, "easy_ebml enum variant cannot end in global path")),
+            PathPart::External(_) => Err(Error::new(span, "easy_ebml enum variant cannot end in external element")),
         }?;
         let mut attrs = vec![];
         attrs.push(Attribute {
@@ -19,20 +19,51 @@ use crate::easy_ebml::EasyEBML;
 ///   * __#[data_type(`TagDataType`)]__ - This attribute specifies the type of data contained in the tag. e.g. `TagDataType::UnsignedInt`
 ///
 /// The following attribute is optional for each variant:
-///   * __#[doc_path(Path/To/Element)]__ - This attribute specifies the document path of the current element.  If this attribute is not present, the variant is treated as a Root element.  Global elements can be defined with wildcard paths, e.g. #[doc_path(Segment/(1-)/)].
+///   * __#[doc_path(Path/To/Element)]__ - This attribute specifies the document path of the current element.  If this attribute is not present, the variant is treated as a Root element.  Global elements can be defined with wildcard paths, e.g. #[doc_path(Segment/(1-)/)].  Elements declared in another spec (e.g. a base spec this one extends) can be used in the path by giving their id, e.g. #[doc_path(Segment(0x18538067)/Chapters)].
+///
+/// Global elements that the spec defines itself (beyond the `Crc32` and `Void` elements of the EBML spec) can be declared with an attribute on the enum, placed after `#[ebml_specification]`.  Each entry gives the occurrence range of the element, its name, its data type, and its id:
+///   * __#[global_elements((1-)/Checksum: Binary = 0x4dbb, (-)/Padding: Binary = 0x6dbb)]__ - Adds a `Checksum` variant that may occur in any element below the root level, and a `Padding` variant that may occur anywhere.  Child elements of a global "Master" element can name it in their paths, e.g. #[doc_path((1-)/Container)].
+///
+/// Unsigned integer variants can also declare the only values they accept:
+///   * __#[restricted_values(`u64`, ...)]__ - e.g. `#[restricted_values(1, 2, 17)]`.  Readers and writers can be configured to reject other values.
+///
+/// # Metadata
+///
+/// Writing `#[ebml_specification(metadata)]` additionally generates a `pub const SPEC_METADATA: &'static [ElementMeta]` on the enum, describing every element (id, name, type, path, and the optional details below) so applications can introspect the spec at runtime.  These optional variant attributes only feed that table:
+///   * __#[default_value(`literal`)]__ - The default value of the element, e.g. `#[default_value(1)]` or `#[default_value("eng")]`.  The default is also returned by `EbmlSpecification::get_default_value()` whether or not metadata is generated, so readers can use it for elements that have no data.
+///   * __#[min_occurs(`u64`)]__ / __#[max_occurs(`u64`)]__ - How many times the element may appear in its parent.
+///   * __#[min_version(`u64`)]__ / __#[max_version(`u64`)]__ - The range of spec versions the element belongs to.
+///
+/// # Path constants
+///
+/// A `pub const PATH_*: &'static str` is generated on the enum for each element with a fixed document path, named after the elements along it, e.g. `PATH_SEGMENT_INFO_TITLE = "Segment/Info/Title"`.  These can be passed to the path-based APIs of ebml-iterable in place of handwritten strings, so typos are caught by the compiler, and can be converted to [`PathPart`](ebml_iterable_specification::PathPart)s with `ebml_iterable::utils::parse_doc_path()`.  Elements below a global wildcard don't have a single path, so no constant is generated for them.
+///
+/// # Non-exhaustive specs
+///
+/// Writing `#[ebml_specification(non_exhaustive(Extension))]` marks the enum `#[non_exhaustive]`, so crates publishing a spec can add elements in later versions without breaking the `match` statements of their users.  Elements that aren't in the spec are held by the named extension variant (`Extension(u64, Vec<u8>)` here) in place of `RawTag`, giving users a stable place to handle elements their version doesn't know about.  Both arguments can be combined, e.g. `#[ebml_specification(metadata, non_exhaustive(Extension))]`.
+///
+/// `Binary` variants hold a `Vec<u8>` by default, but can instead declare a `bytes::Bytes` field (e.g. `Block(bytes::Bytes)`).  This requires the `"bytes"` feature of ebml-iterable-specification, and lets the iterator hand out binary data without copying it when its `"bytes"` feature is enabled.
+///
+/// Similarly, `Utf8` variants hold a `String` by default, but can instead declare a `LazyUtf8` field (e.g. `Title(ebml_iterable::specs::LazyUtf8)`).  The iterator then skips UTF-8 validation for these tags; it only happens if the text is accessed (through `as_utf8()` or [`LazyUtf8::to_str()`](ebml_iterable_specification::LazyUtf8::to_str)).
 /// 
 /// # Note
 ///
 /// This attribute modifies the variants in the enumeration by adding fields to them.  It also will add the following variants to the enum:
-/// - `Crc32(Vec<u8>)` - global tag defined in the EBML spec
-/// - `Void(Vec<u8>)` - global tag defined in the EBML spec
-/// - `RawTag(u64, Vec<u8>)` - used to support reading "unknown" tags that aren't in the spec
+/// - `Crc32(Vec<u8>)` - global tag defined in the EBML spec (unless the enum already has a `Crc32` variant)
+/// - `Void(Vec<u8>)` - global tag defined in the EBML spec (unless the enum already has a `Void` variant)
+/// - a variant for each element declared with `#[global_elements()]`
+/// - `RawTag(u64, Vec<u8>)` - used to support reading "unknown" tags that aren't in the spec (named by `non_exhaustive()` instead, if given)
 ///
 /// [spec]: ebml_iterable_specification::EbmlSpecification
 /// [tag]: ebml_iterable_specification::EbmlTag
 
 #[proc_macro_attribute]
-pub fn ebml_specification(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn ebml_specification(args: TokenStream, input: TokenStream) -> TokenStream {
+    let options = match syn::parse::<attr::SpecOptions>(args) {
+        Ok(options) => options,
+        Err(err) => return TokenStream::from(err.to_compile_error()),
+    };
+
     let mut input = match syn::parse::<ItemEnum>(input) {
         Ok(syntax_tree) => syntax_tree,
         Err(err) => {
@@ -40,7 +71,7 @@ pub fn ebml_specification(_args: TokenStream, input: TokenStream) -> TokenStream
         },
     };
 
-    attr::impl_ebml_specification(&mut input)
+    attr::impl_ebml_specification(&mut input, &options)
         .unwrap_or_else(|err| err.to_compile_error())
         .into()
 }
@@ -103,6 +134,29 @@ pub fn ebml_specification(_args: TokenStream, input: TokenStream) -> TokenStream
 /// }
 /// ```
 /// 
+/// Paths can also start from elements that aren't part of the macro invocation, such as the root elements of a base spec.  Give the id of such an element the first time it is used, and refer to it by name afterwards:
+/// ```
+/// # use ebml_iterable_specification_derive::easy_ebml;
+/// # use ebml_iterable_specification::TagDataType;
+/// # pub mod ebml_iterable { pub mod specs { 
+/// #    pub use ebml_iterable_specification_derive::ebml_specification as ebml_specification; 
+/// #    pub use ebml_iterable_specification::EbmlSpecification as EbmlSpecification;
+/// #    pub use ebml_iterable_specification::EbmlTag as EbmlTag;
+/// #    pub use ebml_iterable_specification::TagDataType as TagDataType;
+/// #    pub use ebml_iterable_specification::Master as Master;
+/// #    pub use ebml_iterable_specification::PathPart as PathPart;
+/// # }}
+/// easy_ebml! {
+///   #[derive(Clone)]
+///   enum Extension {
+///     Segment(0x18538067)/Chapters    : Master = 0x1043a770,
+///     Segment/Chapters/EditionEntry   : Master = 0x45b9,
+///     (1-)/Tags                       : Master = 0x1254c367,
+///     (1-)/Tags/Tag                   : Master = 0x7373,
+///   }
+/// }
+/// ```
+///
 /// Behind the scenes `easy_ebml!` still uses the existing [`[#ebml_specification]`][macro] attribute macro, so the final output of this macro will remain identical.
 /// 
 /// [spec]: ebml_iterable_specification::EbmlSpecification
@@ -18,6 +18,7 @@ impl Parse for EBMLPath {
 pub enum PathPart {
     Ident(Ident),
     Global((Option<u64>,Option<u64>)),
+    External((Ident, u64)),
 }
 
 impl Parse for PathPart {
@@ -45,7 +46,14 @@ impl Parse for PathPart {
             Ok(PathPart::Global((min, max)))
         } else {
             let id: Ident = input.parse()?;
-            Ok(PathPart::Ident(id))
+            if input.lookahead1().peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in input);
+                let tag_id: u64 = content.parse::<LitInt>()?.base10_parse()?;
+                Ok(PathPart::External((id, tag_id)))
+            } else {
+                Ok(PathPart::Ident(id))
+            }
         }
     }
 }
@@ -54,6 +62,7 @@ impl std::fmt::Display for PathPart {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             PathPart::Ident(id) => write!(f, "{id}"),
+            PathPart::External((id, tag_id)) => write!(f, "{id}(0x{tag_id:x})"),
             PathPart::Global((min, max)) => {
                 let min = if let Some(min) = min {
                     min.to_string()
@@ -75,6 +84,7 @@ impl quote::ToTokens for PathPart {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
             PathPart::Ident(id) => tokens.append(id.clone()),
+            PathPart::External((id, tag_id)) => tokens.extend(quote!{#id(#tag_id)}),
             PathPart::Global((min, max)) => tokens.extend(quote!{(#min-#max)}),
         }
     }
@@ -0,0 +1,210 @@
+use super::{ElementMeta, EbmlSpecification, EbmlTag, Master, TagDataType, PathPart};
+
+pub const EBML_ID: u64 = 0x1a45dfa3;
+pub const EBML_VERSION_ID: u64 = 0x4286;
+pub const EBML_READ_VERSION_ID: u64 = 0x42f7;
+pub const EBML_MAX_ID_LENGTH_ID: u64 = 0x42f2;
+pub const EBML_MAX_SIZE_LENGTH_ID: u64 = 0x42f3;
+pub const DOC_TYPE_ID: u64 = 0x4282;
+pub const DOC_TYPE_VERSION_ID: u64 = 0x4287;
+pub const DOC_TYPE_READ_VERSION_ID: u64 = 0x4285;
+pub const DOC_TYPE_EXTENSION_ID: u64 = 0x4281;
+pub const DOC_TYPE_EXTENSION_NAME_ID: u64 = 0x4283;
+pub const DOC_TYPE_EXTENSION_VERSION_ID: u64 = 0x4284;
+pub const CRC32_ID: u64 = 0xbf;
+pub const VOID_ID: u64 = 0xec;
+
+const HEADER_PATH: &[PathPart] = &[PathPart::Id(EBML_ID)];
+const EXTENSION_PATH: &[PathPart] = &[PathPart::Id(EBML_ID), PathPart::Id(DOC_TYPE_EXTENSION_ID)];
+const CRC32_PATH: &[PathPart] = &[PathPart::Global((Some(1), None))];
+const VOID_PATH: &[PathPart] = &[PathPart::Global((None, None))];
+
+const fn meta(id: u64, name: &'static str, data_type: TagDataType, path: &'static str, default: Option<&'static str>, min_occurs: Option<u64>, max_occurs: Option<u64>) -> ElementMeta {
+    ElementMeta { id, name, data_type, path, default, min_occurs, max_occurs, min_version: None, max_version: None }
+}
+
+///
+/// The EBML header elements defined by [RFC 8794](https://www.rfc-editor.org/rfc/rfc8794.html#section-11.2), along with the global `Crc32` and `Void` elements.
+///
+/// Every EBML document starts with this header, so specifications for specific DocTypes (Matroska, WebM, etc.) can use the ids in this module rather than redefining them.  This spec can also be used on its own, e.g. to read just the header of a document whose DocType isn't known yet.  Element paths in [`Self::SPEC_METADATA`] use the variant names, like the tables generated by the derive macro.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum EbmlHeader {
+    Ebml(Master<EbmlHeader>),
+    EbmlVersion(u64),
+    EbmlReadVersion(u64),
+    EbmlMaxIdLength(u64),
+    EbmlMaxSizeLength(u64),
+    DocType(String),
+    DocTypeVersion(u64),
+    DocTypeReadVersion(u64),
+    DocTypeExtension(Master<EbmlHeader>),
+    DocTypeExtensionName(String),
+    DocTypeExtensionVersion(u64),
+    Crc32(Vec<u8>),
+    Void(Vec<u8>),
+    RawTag(u64, Vec<u8>),
+}
+
+impl EbmlHeader {
+
+    ///
+    /// Metadata for every element in this specification, matching the table generated by `#[ebml_specification(metadata)]`.
+    ///
+    pub const SPEC_METADATA: &'static [ElementMeta] = &[
+        meta(EBML_ID, "Ebml", TagDataType::Master, "\\Ebml", None, Some(1), Some(1)),
+        meta(EBML_VERSION_ID, "EbmlVersion", TagDataType::UnsignedInt, "\\Ebml\\EbmlVersion", Some("1"), Some(1), Some(1)),
+        meta(EBML_READ_VERSION_ID, "EbmlReadVersion", TagDataType::UnsignedInt, "\\Ebml\\EbmlReadVersion", Some("1"), Some(1), Some(1)),
+        meta(EBML_MAX_ID_LENGTH_ID, "EbmlMaxIdLength", TagDataType::UnsignedInt, "\\Ebml\\EbmlMaxIdLength", Some("4"), Some(1), Some(1)),
+        meta(EBML_MAX_SIZE_LENGTH_ID, "EbmlMaxSizeLength", TagDataType::UnsignedInt, "\\Ebml\\EbmlMaxSizeLength", Some("8"), Some(1), Some(1)),
+        meta(DOC_TYPE_ID, "DocType", TagDataType::Utf8, "\\Ebml\\DocType", None, Some(1), Some(1)),
+        meta(DOC_TYPE_VERSION_ID, "DocTypeVersion", TagDataType::UnsignedInt, "\\Ebml\\DocTypeVersion", Some("1"), Some(1), Some(1)),
+        meta(DOC_TYPE_READ_VERSION_ID, "DocTypeReadVersion", TagDataType::UnsignedInt, "\\Ebml\\DocTypeReadVersion", Some("1"), Some(1), Some(1)),
+        meta(DOC_TYPE_EXTENSION_ID, "DocTypeExtension", TagDataType::Master, "\\Ebml\\DocTypeExtension", None, Some(0), None),
+        meta(DOC_TYPE_EXTENSION_NAME_ID, "DocTypeExtensionName", TagDataType::Utf8, "\\Ebml\\DocTypeExtension\\DocTypeExtensionName", None, Some(1), Some(1)),
+        meta(DOC_TYPE_EXTENSION_VERSION_ID, "DocTypeExtensionVersion", TagDataType::UnsignedInt, "\\Ebml\\DocTypeExtension\\DocTypeExtensionVersion", None, Some(1), Some(1)),
+        meta(CRC32_ID, "Crc32", TagDataType::Binary, "\\(1-\\)Crc32", None, Some(0), Some(1)),
+        meta(VOID_ID, "Void", TagDataType::Binary, "\\(-\\)Void", None, Some(0), None),
+    ];
+}
+
+impl EbmlSpecification<EbmlHeader> for EbmlHeader {
+    fn get_tag_data_type(id: u64) -> Option<TagDataType> {
+        Self::SPEC_METADATA.iter().find(|m| m.id == id).map(|m| m.data_type)
+    }
+
+    fn get_path_by_id(id: u64) -> &'static [PathPart] {
+        match id {
+            EBML_ID => &[],
+            DOC_TYPE_EXTENSION_NAME_ID | DOC_TYPE_EXTENSION_VERSION_ID => EXTENSION_PATH,
+            CRC32_ID => CRC32_PATH,
+            VOID_ID => VOID_PATH,
+            _ if Self::get_tag_data_type(id).is_some() => HEADER_PATH,
+            _ => &[],
+        }
+    }
+
+    fn get_name_by_id(id: u64) -> Option<&'static str> {
+        Self::SPEC_METADATA.iter().find(|m| m.id == id).map(|m| m.name)
+    }
+
+    fn get_id_by_name(name: &str) -> Option<u64> {
+        Self::SPEC_METADATA.iter().find(|m| m.name == name).map(|m| m.id)
+    }
+
+    fn get_default_value(id: u64) -> Option<&'static str> {
+        Self::SPEC_METADATA.iter().find(|m| m.id == id).and_then(|m| m.default)
+    }
+
+    fn get_unsigned_int_tag(id: u64, data: u64) -> Option<EbmlHeader> {
+        match id {
+            EBML_VERSION_ID => Some(EbmlHeader::EbmlVersion(data)),
+            EBML_READ_VERSION_ID => Some(EbmlHeader::EbmlReadVersion(data)),
+            EBML_MAX_ID_LENGTH_ID => Some(EbmlHeader::EbmlMaxIdLength(data)),
+            EBML_MAX_SIZE_LENGTH_ID => Some(EbmlHeader::EbmlMaxSizeLength(data)),
+            DOC_TYPE_VERSION_ID => Some(EbmlHeader::DocTypeVersion(data)),
+            DOC_TYPE_READ_VERSION_ID => Some(EbmlHeader::DocTypeReadVersion(data)),
+            DOC_TYPE_EXTENSION_VERSION_ID => Some(EbmlHeader::DocTypeExtensionVersion(data)),
+            _ => None,
+        }
+    }
+
+    fn get_signed_int_tag(_id: u64, _data: i64) -> Option<EbmlHeader> {
+        None
+    }
+
+    fn get_utf8_tag(id: u64, data: String) -> Option<EbmlHeader> {
+        match id {
+            DOC_TYPE_ID => Some(EbmlHeader::DocType(data)),
+            DOC_TYPE_EXTENSION_NAME_ID => Some(EbmlHeader::DocTypeExtensionName(data)),
+            _ => None,
+        }
+    }
+
+    fn get_binary_tag(id: u64, data: &[u8]) -> Option<EbmlHeader> {
+        match id {
+            CRC32_ID => Some(EbmlHeader::Crc32(data.to_vec())),
+            VOID_ID => Some(EbmlHeader::Void(data.to_vec())),
+            _ => None,
+        }
+    }
+
+    fn get_float_tag(_id: u64, _data: f64) -> Option<EbmlHeader> {
+        None
+    }
+
+    fn get_master_tag(id: u64, data: Master<EbmlHeader>) -> Option<EbmlHeader> {
+        match id {
+            EBML_ID => Some(EbmlHeader::Ebml(data)),
+            DOC_TYPE_EXTENSION_ID => Some(EbmlHeader::DocTypeExtension(data)),
+            _ => None,
+        }
+    }
+
+    fn get_raw_tag(id: u64, data: &[u8]) -> EbmlHeader {
+        EbmlHeader::RawTag(id, data.to_vec())
+    }
+}
+
+impl EbmlTag<EbmlHeader> for EbmlHeader {
+    fn get_id(&self) -> u64 {
+        match self {
+            EbmlHeader::Ebml(_) => EBML_ID,
+            EbmlHeader::EbmlVersion(_) => EBML_VERSION_ID,
+            EbmlHeader::EbmlReadVersion(_) => EBML_READ_VERSION_ID,
+            EbmlHeader::EbmlMaxIdLength(_) => EBML_MAX_ID_LENGTH_ID,
+            EbmlHeader::EbmlMaxSizeLength(_) => EBML_MAX_SIZE_LENGTH_ID,
+            EbmlHeader::DocType(_) => DOC_TYPE_ID,
+            EbmlHeader::DocTypeVersion(_) => DOC_TYPE_VERSION_ID,
+            EbmlHeader::DocTypeReadVersion(_) => DOC_TYPE_READ_VERSION_ID,
+            EbmlHeader::DocTypeExtension(_) => DOC_TYPE_EXTENSION_ID,
+            EbmlHeader::DocTypeExtensionName(_) => DOC_TYPE_EXTENSION_NAME_ID,
+            EbmlHeader::DocTypeExtensionVersion(_) => DOC_TYPE_EXTENSION_VERSION_ID,
+            EbmlHeader::Crc32(_) => CRC32_ID,
+            EbmlHeader::Void(_) => VOID_ID,
+            EbmlHeader::RawTag(id, _) => *id,
+        }
+    }
+
+    fn as_unsigned_int(&self) -> Option<&u64> {
+        match self {
+            EbmlHeader::EbmlVersion(val) |
+            EbmlHeader::EbmlReadVersion(val) |
+            EbmlHeader::EbmlMaxIdLength(val) |
+            EbmlHeader::EbmlMaxSizeLength(val) |
+            EbmlHeader::DocTypeVersion(val) |
+            EbmlHeader::DocTypeReadVersion(val) |
+            EbmlHeader::DocTypeExtensionVersion(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_signed_int(&self) -> Option<&i64> {
+        None
+    }
+
+    fn as_utf8(&self) -> Option<&str> {
+        match self {
+            EbmlHeader::DocType(val) | EbmlHeader::DocTypeExtensionName(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_binary(&self) -> Option<&[u8]> {
+        match self {
+            EbmlHeader::Crc32(val) | EbmlHeader::Void(val) | EbmlHeader::RawTag(_, val) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn as_float(&self) -> Option<&f64> {
+        None
+    }
+
+    fn as_master(&self) -> Option<&Master<EbmlHeader>> {
+        match self {
+            EbmlHeader::Ebml(val) | EbmlHeader::DocTypeExtension(val) => Some(val),
+            _ => None,
+        }
+    }
+}
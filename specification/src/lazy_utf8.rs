@@ -0,0 +1,82 @@
+use std::borrow::Cow;
+use std::string::FromUtf8Error;
+use std::str::Utf8Error;
+
+///
+/// Text data that is stored as it was read and only checked for valid UTF-8 when it is accessed.
+///
+/// Specifications can use this in place of `String` for [`TagDataType::Utf8`][crate::TagDataType::Utf8] tags so that reading a file doesn't pay to validate strings that are never looked at.  Invalid data is kept as-is (and written back out unchanged), and is only reported when the text is requested through [`Self::to_str()`].
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LazyUtf8(Vec<u8>);
+
+impl LazyUtf8 {
+
+    ///
+    /// Wraps `bytes` without checking that they are valid UTF-8.
+    ///
+    pub fn new(bytes: Vec<u8>) -> Self {
+        LazyUtf8(bytes)
+    }
+
+    ///
+    /// Returns the raw data.
+    ///
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    ///
+    /// Consumes self and returns the raw data.
+    ///
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    ///
+    /// Returns the data as a string slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is not valid UTF-8.
+    ///
+    pub fn to_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(&self.0)
+    }
+
+    ///
+    /// Returns the data as a string, replacing any invalid sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.0)
+    }
+
+    ///
+    /// Consumes self and returns the data as a `String`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is not valid UTF-8.
+    ///
+    pub fn into_string(self) -> Result<String, FromUtf8Error> {
+        String::from_utf8(self.0)
+    }
+}
+
+impl From<String> for LazyUtf8 {
+    fn from(val: String) -> Self {
+        LazyUtf8(val.into_bytes())
+    }
+}
+
+impl From<&str> for LazyUtf8 {
+    fn from(val: &str) -> Self {
+        LazyUtf8(val.as_bytes().to_vec())
+    }
+}
+
+impl From<Vec<u8>> for LazyUtf8 {
+    fn from(bytes: Vec<u8>) -> Self {
+        LazyUtf8(bytes)
+    }
+}
@@ -8,6 +8,14 @@
 ///
 pub mod empty_spec;
 
+///
+/// Contains the EBML header specification shared by every EBML document.
+///
+pub mod ebml_header;
+
+mod lazy_utf8;
+pub use lazy_utf8::LazyUtf8;
+
 ///
 /// Different data types defined in the EBML specification.
 ///
@@ -33,6 +41,26 @@ pub enum PathPart {
     Global((Option<u64>,Option<u64>)),
 }
 
+///
+/// Describes a single element of a specification for runtime introspection.
+///
+/// Tables of these are generated by `#[ebml_specification(metadata)]` as a `SPEC_METADATA` associated constant, which is handy for building element pickers, validation reports, or documentation without duplicating the spec.
+///
+/// The `path` follows the EBML schema path notation (e.g. `\Segment\Cluster\Timestamp`, or `\(1-\)Crc32` for global elements).  `default` holds the default value exactly as it was written in the spec, since its type depends on `data_type`.
+///
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ElementMeta {
+    pub id: u64,
+    pub name: &'static str,
+    pub data_type: TagDataType,
+    pub path: &'static str,
+    pub default: Option<&'static str>,
+    pub min_occurs: Option<u64>,
+    pub max_occurs: Option<u64>,
+    pub min_version: Option<u64>,
+    pub max_version: Option<u64>,
+}
+
 ///
 /// This trait, along with [`EbmlTag`], should be implemented to define a specification so that EBML can be parsed correctly.  Typically implemented on an Enum of tag variants.
 ///
@@ -74,6 +102,42 @@ pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
         Self::get_path_by_id(item.get_id())
     }
 
+    ///
+    /// Gets the name of a tag from the spec, based on the tag id.
+    ///
+    /// Names are used to resolve human readable document paths (e.g. `"Segment/Tracks"`).  Default implementation returns [`None`] for every id, meaning paths must be supplied as ids.
+    ///
+    fn get_name_by_id(_id: u64) -> Option<&'static str> {
+        None
+    }
+
+    ///
+    /// Gets the id of a tag from the spec, based on the tag name.
+    ///
+    /// This is the inverse of [`Self::get_name_by_id`].  Default implementation returns [`None`] for every name.
+    ///
+    fn get_id_by_name(_name: &str) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Gets the values an unsigned integer tag is restricted to, based on the tag id.
+    ///
+    /// Returns [`None`] if the tag accepts any value.  Readers and writers can be configured to reject values outside of this list (see [`TagIterator::validate_restricted_values()`](https://docs.rs/ebml-iterable/latest/ebml_iterable/struct.TagIterator.html#method.validate_restricted_values)).  Default implementation returns [`None`] for every id.
+    ///
+    fn get_restricted_values(_id: u64) -> Option<&'static [u64]> {
+        None
+    }
+
+    ///
+    /// Gets the default value of a tag from the spec, exactly as it was written in the spec, based on the tag id.
+    ///
+    /// Returns [`None`] if the tag has no default.  Readers use the default for unsigned integer, signed integer, float, and utf8 elements that have no data, as required by [RFC 8794](https://www.rfc-editor.org/rfc/rfc8794.html#section-6.3).  Default implementation returns [`None`] for every id.
+    ///
+    fn get_default_value(_id: u64) -> Option<&'static str> {
+        None
+    }
+
     ///
     /// Creates an unsigned integer type tag from the spec.
     ///
@@ -95,6 +159,18 @@ pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
     ///
     fn get_utf8_tag(id: u64, data: String) -> Option<T>;
 
+    ///
+    /// Creates a utf8 type tag from the spec using data that hasn't been checked for valid UTF-8.
+    ///
+    /// This has the same requirements as [`Self::get_utf8_tag()`], but returns `Some(Err(..))` if the tag requires valid UTF-8 and `data` isn't.  The default implementation validates `data` and calls [`Self::get_utf8_tag()`].  Specifications that store text as [`LazyUtf8`] should override it to keep `data` unvalidated.
+    ///
+    fn get_utf8_tag_bytes(id: u64, data: Vec<u8>) -> Option<Result<T, std::string::FromUtf8Error>> {
+        match String::from_utf8(data) {
+            Ok(val) => Self::get_utf8_tag(id, val).map(Ok),
+            Err(err) => Self::get_tag_data_type(id).filter(|t| matches!(t, TagDataType::Utf8)).map(|_| Err(err)),
+        }
+    }
+
     ///
     /// Creates a binary type tag from the spec.
     ///
@@ -102,6 +178,25 @@ pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
     ///
     fn get_binary_tag(id: u64, data: &[u8]) -> Option<T>;
 
+    ///
+    /// Creates a binary type tag from the spec, taking ownership of the data.
+    ///
+    /// This has the same requirements as [`Self::get_binary_tag()`].  The default implementation calls [`Self::get_binary_tag()`], which copies the data.  Implementors should override it to move `data` into the tag instead, which allows buffers recycled through [`TagIterator::recycle_payload()`](https://docs.rs/ebml-iterable/latest/ebml_iterable/struct.TagIterator.html#method.recycle_payload) to be reused.
+    ///
+    fn get_binary_tag_owned(id: u64, data: Vec<u8>) -> Option<T> {
+        Self::get_binary_tag(id, &data)
+    }
+
+    ///
+    /// Creates a binary type tag from the spec using a reference counted [`bytes::Bytes`] buffer.
+    ///
+    /// This has the same requirements as [`Self::get_binary_tag()`].  The default implementation calls [`Self::get_binary_tag()`], which copies the data.  Specifications that store binary data as [`bytes::Bytes`] should override it to keep `data` as-is, so that payloads can be handed off without being copied.
+    ///
+    #[cfg(feature = "bytes")]
+    fn get_binary_tag_bytes(id: u64, data: bytes::Bytes) -> Option<T> {
+        Self::get_binary_tag(id, &data)
+    }
+
     ///
     /// Creates a float type tag from the spec.
     ///
@@ -162,6 +257,15 @@ pub trait EbmlTag<T: Clone> {
     ///
     fn as_utf8(&self) -> Option<&str>;
 
+    ///
+    /// Gets a reference to the data contained in `self` as utf8 encoded bytes, which may not be valid UTF-8 if the tag stores its text as a [`LazyUtf8`].
+    ///
+    /// This function *must* return `None` if the associated data type of `self` is not [`TagDataType::Utf8`].  The default implementation returns the bytes of [`Self::as_utf8()`].
+    ///
+    fn as_utf8_bytes(&self) -> Option<&[u8]> {
+        self.as_utf8().map(str::as_bytes)
+    }
+
     ///
     /// Gets a reference to the data contained in `self` as binary data.
     ///
@@ -238,3 +342,60 @@ impl<T: Clone> Master<T> {
         }
     }
 }
+
+#[cfg(feature = "rayon")]
+impl<T: Clone + Send + Sync> Master<T> {
+
+    ///
+    /// Returns a parallel iterator over the direct children of a `Full` variant, so that CPU-heavy work on each child can be spread across threads.
+    ///
+    /// `Start` and `End` variants have no children, so the iterator is empty for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    /// use ebml_iterable_specification::{EbmlTag, Master};
+    /// use rayon::prelude::*;
+    ///
+    /// let tag = Master::Full(vec![EmptySpec::with_data(0x1253, &[1]), EmptySpec::with_data(0x1234, &[2])]);
+    /// let total: u32 = tag.par_children().map(|child| child.as_binary().unwrap()[0] as u32).sum();
+    /// assert_eq!(3, total);
+    /// ```
+    ///
+    pub fn par_children(&self) -> rayon::slice::Iter<'_, T> {
+        use rayon::prelude::*;
+        match self {
+            Master::Full(children) => children.par_iter(),
+            Master::Start | Master::End => [].par_iter(),
+        }
+    }
+
+    ///
+    /// Returns a parallel iterator over every tag nested within a `Full` variant (children, their children, and so on).
+    ///
+    /// "Master" descendants are included along with their own children.  The tree is walked up front to collect references in document order, and the work done on each of them is what runs in parallel.
+    ///
+    pub fn par_descendants(&self) -> rayon::vec::IntoIter<&T>
+        where T: EbmlTag<T>
+    {
+        use rayon::prelude::*;
+        let mut descendants = Vec::new();
+        let mut stack: Vec<std::slice::Iter<'_, T>> = match self {
+            Master::Full(children) => vec![children.iter()],
+            Master::Start | Master::End => Vec::new(),
+        };
+        while let Some(children) = stack.last_mut() {
+            match children.next() {
+                Some(child) => {
+                    descendants.push(child);
+                    if let Some(Master::Full(grandchildren)) = child.as_master() {
+                        stack.push(grandchildren.iter());
+                    }
+                },
+                None => { stack.pop(); },
+            }
+        }
+        descendants.into_par_iter()
+    }
+}
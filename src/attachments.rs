@@ -0,0 +1,266 @@
+use std::io::{ErrorKind, Read, Write};
+
+use crate::header_walker::HeaderWalker;
+use crate::tag_iterator_util::ElementHeader;
+
+use super::tag_writer::TagWriter;
+use super::tools;
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::attachments::AttachmentError;
+use super::errors::tag_writer::TagWriterError;
+
+const COPY_CHUNK_SIZE: usize = 8192;
+
+///
+/// The ids of the elements that make up an attachment in a particular specification.
+///
+/// For Matroska, these are `AttachedFile` (`0x61a7`), `FileName` (`0x466e`), `FileMimeType` (`0x4660`) and `FileData` (`0x465c`).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttachmentIds {
+
+    ///
+    /// The id of the "Master" element holding a single attachment.
+    ///
+    pub attachment: u64,
+
+    ///
+    /// The id of the "Utf8" child holding the file name.
+    ///
+    pub name: u64,
+
+    ///
+    /// The id of the "Utf8" (or "String") child holding the media type.
+    ///
+    pub mime_type: u64,
+
+    ///
+    /// The id of the "Binary" child holding the file contents.
+    ///
+    pub data: u64,
+}
+
+///
+/// An attachment found by [`list_attachments()`] or [`extract_attachments()`].
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AttachmentInfo {
+
+    ///
+    /// The file name, if the attachment has one.
+    ///
+    pub name: Option<String>,
+
+    ///
+    /// The media type, if the attachment has one.
+    ///
+    pub mime_type: Option<String>,
+
+    ///
+    /// The offset of the attachment element's header in the source.
+    ///
+    pub offset: usize,
+
+    ///
+    /// The offset of the file contents in the source, if the attachment has any.
+    ///
+    pub data_offset: Option<usize>,
+
+    ///
+    /// The length of the file contents, if the attachment has any.
+    ///
+    pub data_len: Option<usize>,
+}
+
+///
+/// Lists the attachments in `source`, without reading their contents into memory.
+///
+/// Elements with the `ids.attachment` id are found at any depth in the document.  The offsets in each [`AttachmentInfo`] can be used to read an attachment's contents later using a seekable source.
+///
+/// ## Errors
+///
+/// Returns [`AttachmentError::UnknownSize`] if an attachment's data has an unknown size.  The other possible error states are enumerated in [`AttachmentError`].
+///
+pub fn list_attachments<TSpec, R: Read>(source: R, ids: &AttachmentIds) -> Result<Vec<AttachmentInfo>, AttachmentError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut attachments = Vec::new();
+    walk_attachments::<TSpec, _>(source, ids, |walker, header, _| walker.skip_data(header).map_err(AttachmentError::from), |info| attachments.push(info))?;
+    Ok(attachments)
+}
+
+///
+/// Streams the contents of the attachments in `source` to the sinks returned by `open_sink`.
+///
+/// `open_sink` is called when an attachment's data is reached, and the data is copied to the returned sink in small chunks (so it never needs to fit in memory), or skipped if `None` is returned.  Since the source is only read once, the [`AttachmentInfo`] passed to `open_sink` only has the name and media type if those elements come before the data, which is the order used by Matroska.  Returns the number of attachments that were extracted.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::utils::{extract_attachments, AttachmentIds};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ids = AttachmentIds { attachment: 0x61a7, name: 0x466e, mime_type: 0x4660, data: 0x465c };
+/// let source = File::open("my_ebml_file.mkv")?;
+/// let count = extract_attachments::<EmptySpec, _, _>(source, &ids, |info| {
+///     info.name.as_ref().map(File::create).transpose()
+/// })?;
+/// println!("Extracted {} attachments", count);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Errors
+///
+/// Returns [`AttachmentError::SinkError`] if `open_sink` returns an error or a sink can't be written to.  The other possible error states are enumerated in [`AttachmentError`].
+///
+pub fn extract_attachments<TSpec, R: Read, W: Write>(source: R, ids: &AttachmentIds, mut open_sink: impl FnMut(&AttachmentInfo) -> std::io::Result<Option<W>>) -> Result<usize, AttachmentError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut count = 0;
+    walk_attachments::<TSpec, _>(source, ids, |walker, header, info| {
+        match open_sink(info).map_err(|source| AttachmentError::SinkError { source })? {
+            Some(mut sink) => {
+                walker.copy_data(header.size.known().unwrap_or(0), &mut sink, |source| AttachmentError::SinkError { source })?;
+                sink.flush().map_err(|source| AttachmentError::SinkError { source })?;
+                count += 1;
+                Ok(())
+            },
+            None => walker.skip_data(header).map_err(AttachmentError::from),
+        }
+    }, |_| {})?;
+    Ok(count)
+}
+
+///
+/// Writes an attachment to `dest`, streaming its `data_len` bytes of contents from `data`.
+///
+/// The attachment is written as an `ids.attachment` element holding the name, media type and data elements, in that order.  It is validated like a tag passed to [`TagWriter::write()`], so it must be allowed below the tags open in `dest`.  The contents are passed to `dest` in small chunks, and are written straight through unless `dest` is buffering a "Master" element with a known size around the attachment.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagWriter;
+/// use ebml_iterable::utils::{inject_attachment, list_attachments, AttachmentIds};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let ids = AttachmentIds { attachment: 0x61a7, name: 0x466e, mime_type: 0x4660, data: 0x465c };
+/// let mut writer = TagWriter::new(Vec::new());
+/// inject_attachment::<EmptySpec, _, _>(&mut writer, &ids, "notes.txt", "text/plain", &b"hello"[..], 5).unwrap();
+/// let output = writer.into_inner().unwrap();
+///
+/// let attachments = list_attachments::<EmptySpec, _>(&output[..], &ids).unwrap();
+/// assert_eq!(Some("notes.txt".to_string()), attachments[0].name);
+/// ```
+///
+/// ## Errors
+///
+/// Returns [`AttachmentError::DataError`] if `data` can't be read or ends before `data_len` bytes, and [`AttachmentError::WriteError`] if the attachment can't be written to `dest`.  `dest` should not be used after an error, since the attachment may have been partially written.
+///
+pub fn inject_attachment<TSpec, R: Read, W: Write>(dest: &mut TagWriter<W>, ids: &AttachmentIds, name: &str, mime_type: &str, mut data: R, data_len: usize) -> Result<(), AttachmentError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut children = Vec::new();
+    children.extend(encode_header(ids.name, name.len())?);
+    children.extend_from_slice(name.as_bytes());
+    children.extend(encode_header(ids.mime_type, mime_type.len())?);
+    children.extend_from_slice(mime_type.as_bytes());
+    children.extend(encode_header(ids.data, data_len)?);
+
+    dest.begin_preencoded::<TSpec>(&encode_header(ids.attachment, children.len() + data_len)?, children.len() + data_len)?;
+    dest.write_preencoded_data(&children)?;
+
+    let mut buffer = [0u8; COPY_CHUNK_SIZE];
+    let mut remaining = data_len;
+    while remaining > 0 {
+        let chunk = &mut buffer[..remaining.min(COPY_CHUNK_SIZE)];
+        let read = match data.read(chunk) {
+            Ok(0) => return Err(AttachmentError::DataError { source: ErrorKind::UnexpectedEof.into() }),
+            Ok(read) => read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(source) => return Err(AttachmentError::DataError { source }),
+        };
+        dest.write_preencoded_data(&chunk[..read])?;
+        remaining -= read;
+    }
+    Ok(())
+}
+
+fn encode_header(id: u64, size: usize) -> Result<Vec<u8>, AttachmentError> {
+    if !tools::is_vint(id) {
+        return Err(AttachmentError::WriteError { source: TagWriterError::TagIdError(id) });
+    }
+    let size = tools::size_as_vint(size as u64).map_err(|err| AttachmentError::WriteError { source: TagWriterError::TagSizeError(err.to_string()) })?;
+    Ok(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size.iter()).copied().collect())
+}
+
+///
+/// Walks `source`, calling `on_data` with the header of each attachment's data element (which must consume the data) and `on_end` with each attachment once all of its children have been read.
+///
+fn walk_attachments<TSpec, R: Read>(source: R, ids: &AttachmentIds, mut on_data: impl FnMut(&mut HeaderWalker<R, TSpec>, &ElementHeader, &AttachmentInfo) -> Result<(), AttachmentError>, mut on_end: impl FnMut(AttachmentInfo)) -> Result<(), AttachmentError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut walker: HeaderWalker<R, TSpec> = HeaderWalker::new(source);
+
+    // The depth of the attachment being read, and what is known about it so far
+    let mut current: Option<(usize, AttachmentInfo)> = None;
+    while let Some(header) = walker.next_header()? {
+        let header_start = walker.position() - header.header_len;
+        if matches!(&current, Some((depth, _)) if walker.depth() <= *depth) {
+            on_end(current.take().unwrap().1);
+        }
+
+        match &mut current {
+            Some((depth, info)) if walker.depth() == *depth + 1 => {
+                if header.id == ids.name || header.id == ids.mime_type {
+                    let value = read_string(&mut walker, &header)?;
+                    if header.id == ids.name {
+                        info.name = Some(value);
+                    } else {
+                        info.mime_type = Some(value);
+                    }
+                } else if header.id == ids.data {
+                    let len = header.size.known().ok_or(AttachmentError::UnknownSize { tag_id: header.id, position: header_start })?;
+                    info.data_offset = Some(walker.position());
+                    info.data_len = Some(len);
+                    on_data(&mut walker, &header, info)?;
+                } else {
+                    walker.skip_data(&header)?;
+                }
+            },
+            Some(_) => walker.skip_data(&header)?,
+            None if header.id == ids.attachment => {
+                current = Some((walker.depth(), AttachmentInfo { offset: header_start, ..AttachmentInfo::default() }));
+                walker.descend(&header);
+            },
+            None if HeaderWalker::<R, TSpec>::is_master(&header) => walker.descend(&header),
+            None => walker.skip_data(&header)?,
+        }
+    }
+
+    if let Some((_, info)) = current {
+        on_end(info);
+    }
+    Ok(())
+}
+
+fn read_string<TSpec, R: Read>(walker: &mut HeaderWalker<R, TSpec>, header: &ElementHeader) -> Result<String, AttachmentError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let position = walker.position() - header.header_len;
+    let len = header.size.known().ok_or(AttachmentError::UnknownSize { tag_id: header.id, position })?;
+    let mut value = Vec::with_capacity(len.min(COPY_CHUNK_SIZE));
+    walker.copy_data(len, &mut value, |source| AttachmentError::SinkError { source })?;
+    // Strings may be padded with trailing zero bytes
+    while value.last() == Some(&0) {
+        value.pop();
+    }
+    Ok(String::from_utf8_lossy(&value).into_owned())
+}
@@ -0,0 +1,234 @@
+//! `ebml-inspect` prints the structure of any EBML document without needing its specification.
+//!
+//! Elements are read with [`RawFrames`], and named using the EBML header specification where it knows them.  Since nothing else is known about the document, an element is shown as a "Master" element whenever its data parses exactly into a sequence of child elements with known sizes.  This is a heuristic - binary data can occasionally look like valid children - but it works well for inspecting unfamiliar files.  An element with an unknown size is assumed to contain everything after it.
+//!
+//! `check` reads the document with a [`TagIterator`] using the EBML header specification, recovering from each error and reporting the ranges it skipped (see [`TagIterator::corrupt_ranges()`]).  Elements that the header specification doesn't know are read without being decoded, so corruption is only found where the iterator reads headers: at the top level and inside elements with an unknown size.
+//!
+//! ```text
+//! ebml-inspect tree <file>        Print the element tree
+//! ebml-inspect histogram <file>   Count elements by id
+//! ebml-inspect check <file>       Report corrupted ranges
+//! ```
+//!
+//! Use `-` as the file to read from stdin.  Exits with 1 if the document is corrupted, or if `tree` or `histogram` couldn't read all of it.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::process::ExitCode;
+
+use ebml_iterable::TagIterator;
+use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+use ebml_iterable::iterator::{AllowableErrors, RawFrame, RawFrames, SizePastEnd};
+use ebml_iterable::specs::ebml_header::EbmlHeader;
+use ebml_iterable::specs::EbmlSpecification;
+
+/// Children aren't probed beyond this depth, so deeply nested binary data can't cause a stack overflow
+const MAX_DEPTH: usize = 32;
+
+/// How many bytes of a binary element's data are shown in the tree
+const PREVIEW_LEN: usize = 16;
+
+struct Element {
+    offset: usize,
+    frame: RawFrame,
+}
+
+impl Element {
+    fn len(&self, parent_end: usize) -> usize {
+        self.frame.size.known().map(|size| self.frame.header.len() + size).unwrap_or(parent_end - self.offset)
+    }
+}
+
+fn name(id: u64) -> &'static str {
+    EbmlHeader::get_name_by_id(id).unwrap_or("")
+}
+
+///
+/// Reads the elements of `data` (which starts at `offset` in the document), stopping at the first error.
+///
+fn read_elements(data: &[u8], offset: usize) -> (Vec<Element>, Option<TagIteratorError>) {
+    let mut frames = RawFrames::new(data);
+    let mut elements = Vec::new();
+    loop {
+        let position = offset + frames.position();
+        match frames.next() {
+            Some(Ok(frame)) => elements.push(Element { offset: position, frame }),
+            Some(Err(err)) => return (elements, Some(err)),
+            None => return (elements, None),
+        }
+    }
+}
+
+///
+/// Parses the data of `element` as a sequence of children, returning `None` unless it parses exactly.  Children with an unknown size are rejected, since they would make almost any data parse.
+///
+fn probe_children(element: &Element, depth: usize) -> Option<Vec<Element>> {
+    if depth >= MAX_DEPTH || element.frame.payload.len() < 2 {
+        return None;
+    }
+    match read_elements(&element.frame.payload, element.offset + element.frame.header.len()) {
+        (children, None) if children.iter().all(|child| child.frame.size.is_known()) => Some(children),
+        _ => None,
+    }
+}
+
+fn describe_data(data: &[u8]) -> String {
+    if !data.is_empty() && data.len() <= 64 && data.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        return format!("\"{}\"", String::from_utf8_lossy(data));
+    }
+    if !data.is_empty() && data.len() <= 8 {
+        let value = data.iter().fold(0u64, |v, b| (v << 8) + *b as u64);
+        return format!("{} (0x{})", value, hex(data));
+    }
+    let preview = &data[..data.len().min(PREVIEW_LEN)];
+    format!("{}{}", hex(preview), if data.len() > PREVIEW_LEN { "..." } else { "" })
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+///
+/// Pairs each element with its depth, treating everything after an element with an unknown size as its children.
+///
+fn with_depths(elements: &[Element], depth: usize) -> impl Iterator<Item = (&Element, usize)> {
+    elements.iter().scan(depth, |depth, element| {
+        let element_depth = *depth;
+        if element.frame.size.is_unknown() {
+            *depth += 1;
+        }
+        Some((element, element_depth))
+    })
+}
+
+fn print_tree(elements: &[Element], depth: usize) {
+    for (element, depth) in with_depths(elements, depth) {
+        let indent = "  ".repeat(depth);
+        let frame = &element.frame;
+        let size = frame.size.known().map(|s| s.to_string()).unwrap_or_else(|| String::from("unknown"));
+        if frame.size.is_unknown() {
+            println!("{}0x{:x} {} @{} size={}", indent, frame.id, name(frame.id), element.offset, size);
+            continue;
+        }
+        match probe_children(element, depth) {
+            Some(children) => {
+                println!("{}0x{:x} {} @{} size={}", indent, frame.id, name(frame.id), element.offset, size);
+                print_tree(&children, depth + 1);
+            },
+            None => println!("{}0x{:x} {} @{} size={}: {}", indent, frame.id, name(frame.id), element.offset, size, describe_data(&frame.payload)),
+        }
+    }
+}
+
+fn count_ids(elements: &[Element], depth: usize, parent_end: usize, counts: &mut BTreeMap<u64, (usize, usize)>) {
+    for (element, depth) in with_depths(elements, depth) {
+        let entry = counts.entry(element.frame.id).or_default();
+        entry.0 += 1;
+        entry.1 += element.len(parent_end);
+        if let Some(children) = probe_children(element, depth) {
+            count_ids(&children, depth + 1, parent_end, counts);
+        }
+    }
+}
+
+///
+/// Returns whether the element at `position` has an unknown size.  The iterator can't read those unless the specification knows they are "Master" elements, but their children can still be read.
+///
+fn has_unknown_size(data: &[u8], position: usize, tag_id: u64) -> bool {
+    matches!(RawFrames::new(&data[position..]).next(), Some(Ok(frame)) if frame.id == tag_id && frame.size.is_unknown())
+}
+
+fn check(data: &[u8]) -> bool {
+    let mut iter: TagIterator<&[u8], EbmlHeader> = TagIterator::new(data, &[]);
+    iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+    iter.set_source_len(Some(data.len()));
+    iter.set_size_past_end(SizePastEnd::Error);
+    iter.set_max_allowable_tag_size(Some(data.len()));
+
+    let mut problems = 0;
+    while let Some(tag) = iter.next() {
+        let err = match tag {
+            Ok(_) => continue,
+            Err(err) => err,
+        };
+        if let TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id, position }) = &err {
+            if has_unknown_size(data, *position, *tag_id) {
+                continue;
+            }
+        }
+
+        // Errors about a value are returned after the tag is read, but the reader is stuck on a bad header until it recovers
+        let needs_recovery = matches!(&err, TagIteratorError::CorruptedFileData(corruption) if !matches!(corruption, CorruptedFileError::RestrictedValue { .. } | CorruptedFileError::ProfileViolation { .. } | CorruptedFileError::ZeroLengthValue { .. } | CorruptedFileError::TrailingData { .. }));
+        let is_fatal = matches!(&err, TagIteratorError::UnexpectedEOF(_) | TagIteratorError::ReadError { .. });
+        println!("{}", err);
+        problems += 1;
+        if is_fatal || (needs_recovery && iter.try_recover().is_err()) {
+            break;
+        }
+    }
+
+    for range in iter.corrupt_ranges() {
+        println!("corrupted bytes {}..{}", range.start, range.end);
+    }
+    if problems == 0 {
+        println!("no corruption found in {} bytes", data.len());
+    }
+    problems == 0
+}
+
+fn run(command: &str, data: &[u8]) -> Result<bool, String> {
+    let (elements, err) = match command {
+        "tree" | "histogram" => read_elements(data, 0),
+        "check" => return Ok(check(data)),
+        _ => return Err(format!("unknown command `{}`", command)),
+    };
+
+    if command == "tree" {
+        print_tree(&elements, 0);
+    } else {
+        let mut counts = BTreeMap::new();
+        count_ids(&elements, 0, data.len(), &mut counts);
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|a, b| (b.1).0.cmp(&(a.1).0).then(a.0.cmp(&b.0)));
+        println!("{:>12} {:>8} {:>12}  name", "id", "count", "bytes");
+        for (id, (count, bytes)) in counts {
+            println!("{:>12} {:>8} {:>12}  {}", format!("0x{:x}", id), count, bytes, name(id));
+        }
+    }
+
+    if let Some(err) = &err {
+        eprintln!("stopped reading: {} (use `check` to find the corrupted ranges)", err);
+    }
+    Ok(err.is_none())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, path) = match &args[..] {
+        [command, path] => (command, path),
+        _ => {
+            eprintln!("usage: ebml-inspect <tree|histogram|check> <file|->");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut data = Vec::new();
+    let read = if path == "-" {
+        std::io::stdin().read_to_end(&mut data).map(|_| ())
+    } else {
+        std::fs::File::open(path).and_then(|mut file| file.read_to_end(&mut data)).map(|_| ())
+    };
+    if let Err(err) = read {
+        eprintln!("error reading {}: {}", path, err);
+        return ExitCode::from(2);
+    }
+
+    match run(command, &data) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(2)
+        }
+    }
+}
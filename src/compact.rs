@@ -0,0 +1,225 @@
+use std::cell::RefCell;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::header_walker::HeaderWalker;
+use crate::spec_util::VOID_ID;
+use crate::tag_iterator_util::ElementHeader;
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::compact::CompactError;
+use super::errors::tag_iterator::TagIteratorError;
+
+///
+/// How [`compact_voids()`] and [`compact_voids_in_place()`] clean up `Void` elements.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VoidCompaction {
+
+    ///
+    /// Merges each run of adjacent `Void` elements into a single `Void` of the same total length.  No element moves, so offsets into the document (like cues) stay valid.
+    ///
+    Merge,
+
+    ///
+    /// Removes every `Void` element, shrinking the master elements that contained them.  This changes the offsets of all data following a removed element.
+    ///
+    Remove,
+}
+
+///
+/// The outcome of compacting a document with [`compact_voids()`] or [`compact_voids_in_place()`].
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CompactionSummary {
+
+    ///
+    /// The number of `Void` elements that were removed or merged into a preceding `Void`.
+    ///
+    pub voids_removed: usize,
+
+    ///
+    /// The number of bytes the document shrank by.
+    ///
+    pub bytes_removed: usize,
+
+    ///
+    /// The length of the compacted document.
+    ///
+    pub len: usize,
+}
+
+///
+/// Copies a document from `source` to `dest`, merging or removing its `Void` elements as configured by `compaction`.
+///
+/// This is the cleanup step after repeated in-place edits (such as with an [`EbmlEditor`][crate::EbmlEditor]), which leave `Void` padding behind.  Only element headers are parsed and everything other than `Void` elements is copied byte for byte.  The sizes of known-size master elements that shrink are updated by seeking back in `dest` once the master has been written (the size vint keeps its original length).  `CRC-32` elements aren't recalculated, so they no longer match masters whose `Void` elements were changed.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::utils::{compact_voids, VoidCompaction};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let source = File::open("my_ebml_file.ebml")?;
+/// let dest = File::create("compacted.ebml")?;
+/// let summary = compact_voids::<EmptySpec, _, _>(source, dest, VoidCompaction::Remove)?;
+/// println!("Removed {} bytes of padding", summary.bytes_removed);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Errors
+///
+/// Returns [`CompactError::UnknownSizeVoid`] if a `Void` element has an unknown size.  The other possible error states are enumerated in [`CompactError`].
+///
+pub fn compact_voids<TSpec, R: Read, W: Write + Seek>(source: R, mut dest: W, compaction: VoidCompaction) -> Result<CompactionSummary, CompactError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    copy_compacted::<TSpec, _, _>(source, &mut dest, compaction)
+}
+
+///
+/// Merges or removes the `Void` elements of the document in `file`, rewriting it in place.
+///
+/// This works the same as [`compact_voids()`], with the document starting at the current position of `file`.  The compacted document is never longer than the original, so the data is shifted towards the start of the file as it is read.  The file isn't truncated: when elements are removed, the caller should cut it to the start position plus [`CompactionSummary::len`] afterwards (e.g. using [`std::fs::File::set_len()`]).
+///
+/// ## Errors
+///
+/// The possible error states are enumerated in [`CompactError`].  The file is left partially compacted if an error occurs.
+///
+pub fn compact_voids_in_place<TSpec, S: Read + Write + Seek>(mut file: S, compaction: VoidCompaction) -> Result<CompactionSummary, CompactError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let start = file.stream_position().map_err(|source| CompactError::ReadError { source: TagIteratorError::ReadError { source } })?;
+    let file = RefCell::new(file);
+    let reader = SharedCursor { file: &file, position: start };
+    let mut writer = SharedCursor { file: &file, position: start };
+    copy_compacted::<TSpec, _, _>(reader, &mut writer, compaction)
+}
+
+///
+/// Reads or writes a file shared with another cursor, seeking to its own position before each operation.
+///
+struct SharedCursor<'a, S> {
+    file: &'a RefCell<S>,
+    position: u64,
+}
+
+impl<S: Read + Seek> Read for SharedCursor<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(self.position))?;
+        let read = file.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<S: Write + Seek> Write for SharedCursor<'_, S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(self.position))?;
+        let written = file.write(buf)?;
+        self.position += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.borrow_mut().flush()
+    }
+}
+
+impl<S: Seek> Seek for SharedCursor<'_, S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = match pos {
+            SeekFrom::Start(position) => position,
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset).ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek to a negative position"))?,
+            SeekFrom::End(_) => self.file.borrow_mut().seek(pos)?,
+        };
+        Ok(self.position)
+    }
+}
+
+fn copy_compacted<TSpec, R: Read, W: Write + Seek>(source: R, dest: &mut W, compaction: VoidCompaction) -> Result<CompactionSummary, CompactError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let write_err = |source| CompactError::WriteError { source };
+    let base = dest.stream_position().map_err(write_err)?;
+    let mut walker: HeaderWalker<R, TSpec> = HeaderWalker::new(source);
+    let mut written = 0;
+    let mut summary = CompactionSummary::default();
+
+    // Output position and original header of each master we're inside of
+    let mut open: Vec<(usize, ElementHeader)> = Vec::new();
+
+    // Depth and total length of the run of Voids being merged
+    let mut pending_void: Option<(usize, usize)> = None;
+
+    loop {
+        let next = walker.next_header()?;
+
+        // A run of Voids ends at any other element, or when the walker leaves the master containing it
+        if let Some((depth, len)) = pending_void {
+            if !matches!(&next, Some(header) if header.id == VOID_ID) || walker.depth() != depth {
+                let void = ElementHeader::void(len).encode();
+                dest.write_all(&void).map_err(write_err)?;
+                std::io::copy(&mut std::io::repeat(0).take((len - void.len()) as u64), dest).map_err(write_err)?;
+                written += len;
+                pending_void = None;
+            }
+        }
+
+        while open.len() > walker.depth() {
+            let (position, header) = open.pop().expect("open should not be empty");
+            let size = written - position - header.header_len;
+            if matches!(header.size, Known(original) if original != size) {
+                dest.seek(SeekFrom::Start(base + position as u64)).map_err(write_err)?;
+                dest.write_all(&ElementHeader { size: Known(size), ..header }.encode()).map_err(write_err)?;
+                dest.seek(SeekFrom::Start(base + written as u64)).map_err(write_err)?;
+            }
+        }
+
+        let header = match next {
+            Some(header) => header,
+            None => break,
+        };
+
+        if header.id == VOID_ID {
+            let total_len = header.total_len().ok_or(CompactError::UnknownSizeVoid { position: walker.position() - header.header_len })?;
+            walker.skip_data(&header)?;
+            match (compaction, pending_void.as_mut()) {
+                (VoidCompaction::Merge, Some((_, len))) => {
+                    *len += total_len;
+                    summary.voids_removed += 1;
+                },
+                (VoidCompaction::Merge, None) => pending_void = Some((walker.depth(), total_len)),
+                (VoidCompaction::Remove, _) => summary.voids_removed += 1,
+            }
+            continue;
+        }
+
+        let encoded = header.encode();
+        dest.write_all(&encoded).map_err(write_err)?;
+        written += encoded.len();
+        match header.size {
+            Known(size) if !HeaderWalker::<R, TSpec>::is_master(&header) => {
+                walker.copy_data(size, dest, write_err)?;
+                written += size;
+            },
+            Known(_) | Unknown => {
+                open.push((written - encoded.len(), header));
+                walker.descend(&header);
+            },
+        }
+    }
+
+    dest.flush().map_err(write_err)?;
+    summary.bytes_removed = walker.position() - written;
+    summary.len = written;
+    Ok(summary)
+}
@@ -0,0 +1,52 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::tag_writer::WrittenElement;
+
+///
+/// The index entries collected by [`TagWriter::build_cues()`](crate::TagWriter::build_cues).
+///
+/// This is a handle to entries that are added by the writer as it writes "anchor" elements, so it can be kept while the writer is in use (and even sent to another thread).  Once the anchored elements have been written, the entries can be turned into whatever index the caller's specification uses (such as Matroska's `Cues`).
+///
+pub struct CueBuilder<E> {
+    entries: Arc<Mutex<Vec<E>>>,
+}
+
+impl<E> CueBuilder<E> {
+    pub(crate) fn new() -> Self {
+        CueBuilder { entries: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    pub(crate) fn recorder<F>(&self, mut callback: F) -> impl FnMut(&WrittenElement) + Send + 'static
+        where
+        E: Send + 'static,
+        F: FnMut(&WrittenElement) -> Option<E> + Send + 'static
+    {
+        let entries = Arc::clone(&self.entries);
+        move |element| {
+            if let Some(entry) = callback(element) {
+                entries.lock().unwrap_or_else(PoisonError::into_inner).push(entry);
+            }
+        }
+    }
+
+    ///
+    /// Returns the number of entries collected so far.
+    ///
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    ///
+    /// Returns `true` if no entries have been collected so far.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Returns the entries collected so far, in the order their elements were written, and clears them.  Entries continue to be collected while the writer is in use.
+    ///
+    pub fn take_entries(&self) -> Vec<E> {
+        std::mem::take(&mut *self.entries.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
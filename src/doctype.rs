@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::io::{Chain, Cursor, Read};
+
+use crate::tag_iterator_util::read_element_header;
+use crate::tag_iterator_util::ElementSize::Known;
+
+use super::specs::ebml_header::{EBML_ID, DOC_TYPE_ID};
+use super::errors::doctype::DocTypeError;
+use super::errors::tag_iterator::{PartialTag, TagIteratorError};
+
+// EBML headers are tiny; anything this large is not a real header
+const MAX_HEADER_LEN: usize = 64 * 1024;
+
+type DocTypeHandler<R, T> = Box<dyn Fn(SniffedDocType<R>) -> T>;
+
+///
+/// A source whose EBML header has been read by [`sniff_doctype()`].
+///
+pub struct SniffedDocType<R: Read> {
+    doctype: String,
+    header: Vec<u8>,
+    source: R,
+}
+
+impl<R: Read> SniffedDocType<R> {
+
+    ///
+    /// The DocType declared in the EBML header (e.g. `"matroska"` or `"webm"`).
+    ///
+    pub fn doctype(&self) -> &str {
+        &self.doctype
+    }
+
+    ///
+    /// The encoded EBML header element that was read from the source.
+    ///
+    pub fn header(&self) -> &[u8] {
+        &self.header
+    }
+
+    ///
+    /// Returns a reader over the whole document.
+    ///
+    /// The header is replayed from memory before continuing with the rest of the source, so a [`TagIterator`](crate::TagIterator) created from this reader sees the document from the start without the header being read twice.
+    ///
+    pub fn into_reader(self) -> Chain<Cursor<Vec<u8>>, R> {
+        Cursor::new(self.header).chain(self.source)
+    }
+
+    ///
+    /// Consumes self and returns the DocType, the encoded EBML header, and the source (positioned right after the header).
+    ///
+    pub fn into_parts(self) -> (String, Vec<u8>, R) {
+        (self.doctype, self.header, self.source)
+    }
+}
+
+///
+/// Reads the EBML header from the start of `source` to determine the document's DocType.
+///
+/// Only the header element is read from `source`.  Use [`SniffedDocType::into_reader()`] to iterate over the document with whichever specification matches the DocType, or a [`DocTypeRegistry`] to make that choice automatically.
+///
+/// ## Errors
+///
+/// Returns [`DocTypeError::InvalidHeader`] if the source doesn't start with an EBML header of a known size, and [`DocTypeError::MissingDocType`] if the header has no DocType.
+///
+pub fn sniff_doctype<R: Read>(mut source: R) -> Result<SniffedDocType<R>, DocTypeError> {
+    let header = read_element_header(&mut source, 0)?.ok_or(DocTypeError::InvalidHeader)?;
+    let size = match header.size {
+        Known(size) if header.id == EBML_ID && size <= MAX_HEADER_LEN => size,
+        _ => return Err(DocTypeError::InvalidHeader),
+    };
+
+    let mut encoded = header.encode();
+    let data_start = encoded.len();
+    let obtained = (&mut source).take(size as u64).read_to_end(&mut encoded).map_err(|source| TagIteratorError::ReadError { source })?;
+    if obtained < size {
+        let data = encoded.split_off(data_start);
+        return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start: 0, id: Some(EBML_ID), size: Some(size), header_len: Some(data_start), obtained, data: Some(data) }).into());
+    }
+
+    let doctype = find_doctype(&encoded[data_start..])?.ok_or(DocTypeError::MissingDocType)?;
+    Ok(SniffedDocType {
+        doctype,
+        header: encoded,
+        source,
+    })
+}
+
+fn find_doctype(mut data: &[u8]) -> Result<Option<String>, DocTypeError> {
+    let mut position = 0;
+    while let Some(child) = read_element_header(&mut data, position)? {
+        let size = match child.size {
+            Known(size) if size <= data.len() => size,
+            _ => return Err(DocTypeError::InvalidHeader),
+        };
+        if child.id == DOC_TYPE_ID {
+            // Strings can be padded with trailing nulls
+            let value = data[..size].split(|b| *b == 0).next().unwrap_or_default();
+            return String::from_utf8(value.to_vec()).map(Some).map_err(|_| DocTypeError::InvalidHeader);
+        }
+        data = &data[size..];
+        position += child.header_len + size;
+    }
+    Ok(None)
+}
+
+///
+/// Picks how to handle a document based on its DocType.
+///
+/// Applications supporting several DocTypes register a handler for each of them.  Each handler receives the [`SniffedDocType`] and typically creates a [`TagIterator`](crate::TagIterator) with the matching specification, returning it as some type `T` that is common to all handlers (such as an application defined enum).
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagIterator;
+/// use ebml_iterable::utils::DocTypeRegistry;
+/// use ebml_iterable::specs::EbmlTag;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let data = [0x1a, 0x45, 0xdf, 0xa3, 0x87, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'];
+///
+/// let mut registry: DocTypeRegistry<&[u8], TagIterator<_, EmptySpec>> = DocTypeRegistry::new();
+/// registry.register("webm", |sniffed| TagIterator::new(sniffed.into_reader(), &[]));
+///
+/// let mut tags = registry.open(&data[..]).unwrap();
+/// assert_eq!(0x1a45dfa3, tags.next().unwrap().unwrap().get_id());
+/// ```
+///
+pub struct DocTypeRegistry<R: Read, T> {
+    handlers: HashMap<String, DocTypeHandler<R, T>>,
+}
+
+impl<R: Read, T> DocTypeRegistry<R, T> {
+
+    ///
+    /// Returns a new registry without any handlers.
+    ///
+    pub fn new() -> Self {
+        DocTypeRegistry { handlers: HashMap::new() }
+    }
+
+    ///
+    /// Registers the handler for documents with the given `doctype`.  Registering a handler for a DocType that already has one replaces it.
+    ///
+    pub fn register(&mut self, doctype: &str, handler: impl Fn(SniffedDocType<R>) -> T + 'static) {
+        self.handlers.insert(doctype.to_string(), Box::new(handler));
+    }
+
+    ///
+    /// Reads the EBML header of `source` and passes it to the handler registered for its DocType.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`DocTypeError::UnknownDocType`] if no handler is registered for the DocType, along with any errors from [`sniff_doctype()`].
+    ///
+    pub fn open(&self, source: R) -> Result<T, DocTypeError> {
+        let sniffed = sniff_doctype(source)?;
+        match self.handlers.get(sniffed.doctype()) {
+            Some(handler) => Ok(handler(sniffed)),
+            None => Err(DocTypeError::UnknownDocType(sniffed.doctype)),
+        }
+    }
+}
+
+impl<R: Read, T> Default for DocTypeRegistry<R, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,210 @@
+use std::io::Write;
+use std::marker::PhantomData;
+
+use super::tag_writer::{TagWriter, WriteOptions};
+use super::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use super::specs::ebml_header::EBML_ID;
+use super::errors::tag_writer::TagWriterError;
+
+///
+/// The phase of a [`DocumentWriter`] that hasn't written the EBML header yet.
+///
+pub enum NeedsHeader {}
+
+///
+/// The phase of a [`DocumentWriter`] that has written the EBML header and is waiting for the body's root element.
+///
+pub enum NeedsRoot {}
+
+///
+/// The phase of a [`DocumentWriter`] with an open root element, which accepts the children of the root.
+///
+pub enum Body {}
+
+///
+/// The phase of a [`DocumentWriter`] whose root element has been closed.
+///
+pub enum Finished {}
+
+///
+/// Wraps a [`TagWriter`] to enforce the structure of a whole document at compile time.
+///
+/// An EBML document is an EBML header followed by exactly one root element (e.g. a Matroska `Segment`).  The phase of the document is part of the writer's type, and each method consumes the writer and returns it in the next phase: the header is written by [`DocumentWriter::write_header()`], the root is opened by [`DocumentWriter::open_root()`], its children are written using [`DocumentWriter::write()`], and the root is closed by [`DocumentWriter::finish()`].  Writing a child before the root is open, or a second root after the document is finished, doesn't compile.
+///
+/// The tags passed to each phase are still checked when they are written: the header must be a complete [`Master::Full`] tag with the EBML header id, the root must be a top-level "Master" element, and the body can't contain top-level elements.  All other validation is done by the wrapped [`TagWriter`].
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::{DocumentWriter, TagWriter};
+/// use ebml_iterable::error::TagWriterError;
+/// use ebml_iterable::specs::{EbmlSpecification, EbmlTag};
+///
+/// fn write_document<TSpec>(header: &TSpec, root: &TSpec, children: &[TSpec]) -> Result<Vec<u8>, TagWriterError>
+///     where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+/// {
+///     let writer: DocumentWriter<_, TSpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+///     let mut body = writer.write_header(header)?.open_root(root)?;
+///     for child in children {
+///         body.write(child)?;
+///     }
+///     body.finish()?.into_inner()
+/// }
+/// ```
+///
+/// Skipping a phase is a compile error:
+///
+/// ```compile_fail
+/// use ebml_iterable::{DocumentWriter, TagWriter};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut writer: DocumentWriter<_, EmptySpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+/// writer.write(&EmptySpec::with_data(0x83, &[0x01]));
+/// ```
+///
+pub struct DocumentWriter<W: Write, TSpec, Phase> {
+    writer: TagWriter<W>,
+    root_id: u64,
+    _phase: PhantomData<(TSpec, Phase)>,
+}
+
+impl<W: Write, TSpec, Phase> DocumentWriter<W, TSpec, Phase>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn into_phase<Next>(self) -> DocumentWriter<W, TSpec, Next> {
+        DocumentWriter { writer: self.writer, root_id: self.root_id, _phase: PhantomData }
+    }
+
+    ///
+    /// Returns a reference to the wrapped [`TagWriter`], e.g. to read its [`metrics()`](TagWriter::metrics) or [`audit_log()`](TagWriter::audit_log).
+    ///
+    pub fn tag_writer(&self) -> &TagWriter<W> {
+        &self.writer
+    }
+}
+
+impl<W: Write, TSpec> DocumentWriter<W, TSpec, NeedsHeader>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a [`DocumentWriter`] that starts a new document on `writer`.
+    ///
+    /// The [`TagWriter`] should not have written anything yet, but can be configured (transforms, profiles, etc.) beforehand.
+    ///
+    pub fn new(writer: TagWriter<W>) -> Self {
+        DocumentWriter { writer, root_id: 0, _phase: PhantomData }
+    }
+
+    ///
+    /// Writes the EBML header of the document.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`TagWriterError::UnexpectedTag`] if `header` isn't a [`Master::Full`] tag with the EBML header id (`0x1a45dfa3`), or any error returned by [`TagWriter::write()`].
+    ///
+    pub fn write_header(mut self, header: &TSpec) -> Result<DocumentWriter<W, TSpec, NeedsRoot>, TagWriterError> {
+        let tag_id = header.get_id();
+        if tag_id != EBML_ID || !matches!(header.as_master(), Some(Master::Full(_))) {
+            return Err(TagWriterError::UnexpectedTag { tag_id, current_path: vec![] });
+        }
+        self.writer.write(header)?;
+        Ok(self.into_phase())
+    }
+}
+
+impl<W: Write, TSpec> DocumentWriter<W, TSpec, NeedsRoot>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Opens the root element of the document's body.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`TagWriterError::UnexpectedTag`] if `root` isn't a [`Master::Start`] tag for a top-level element of `<TSpec>`, or any error returned by [`TagWriter::write()`].
+    ///
+    pub fn open_root(self, root: &TSpec) -> Result<DocumentWriter<W, TSpec, Body>, TagWriterError> {
+        self.open_root_advanced(root, WriteOptions::default())
+    }
+
+    ///
+    /// Opens the root element of the document's body using advanced options, e.g. to give the root an unknown size when streaming.  See [`TagWriter::write_advanced()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::open_root()`].
+    ///
+    pub fn open_root_advanced(mut self, root: &TSpec, options: WriteOptions) -> Result<DocumentWriter<W, TSpec, Body>, TagWriterError> {
+        let tag_id = root.get_id();
+        if tag_id == EBML_ID || !TSpec::get_path_by_id(tag_id).is_empty() || !matches!(root.as_master(), Some(Master::Start)) {
+            return Err(TagWriterError::UnexpectedTag { tag_id, current_path: vec![] });
+        }
+        self.writer.write_advanced(root, options)?;
+        self.root_id = tag_id;
+        Ok(self.into_phase())
+    }
+}
+
+impl<W: Write, TSpec> DocumentWriter<W, TSpec, Body>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Writes a tag inside the root element.  See [`TagWriter::write()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`TagWriterError::UnexpectedTag`] if `tag` is a top-level element (the root is closed by [`Self::finish()`]), or any error returned by [`TagWriter::write()`].
+    ///
+    pub fn write(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        self.write_advanced(tag, WriteOptions::default())
+    }
+
+    ///
+    /// Writes a tag inside the root element using advanced options.  See [`TagWriter::write_advanced()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::write()`].
+    ///
+    pub fn write_advanced(&mut self, tag: &TSpec, options: WriteOptions) -> Result<(), TagWriterError> {
+        let tag_id = tag.get_id();
+        if tag_id == EBML_ID || tag_id == self.root_id || is_top_level::<TSpec>(tag_id) {
+            return Err(TagWriterError::UnexpectedTag { tag_id, current_path: vec![self.root_id] });
+        }
+        self.writer.write_advanced(tag, options)
+    }
+
+    ///
+    /// Closes the root element, completing the document.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`TagWriterError::UnexpectedClosingTag`] if a child of the root is still open, or any other error returned by [`TagWriter::write()`].
+    ///
+    pub fn finish(mut self) -> Result<DocumentWriter<W, TSpec, Finished>, TagWriterError> {
+        let end = TSpec::get_master_tag(self.root_id, Master::End).ok_or(TagWriterError::UnexpectedTag { tag_id: self.root_id, current_path: vec![] })?;
+        self.writer.write(&end)?;
+        Ok(self.into_phase())
+    }
+}
+
+impl<W: Write, TSpec> DocumentWriter<W, TSpec, Finished>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Flushes the document and returns the underlying destination.  See [`TagWriter::into_inner()`].
+    ///
+    pub fn into_inner(self) -> Result<W, TagWriterError> {
+        self.writer.into_inner()
+    }
+
+    ///
+    /// Returns the wrapped [`TagWriter`], e.g. to append data after the document.
+    ///
+    pub fn into_tag_writer(self) -> TagWriter<W> {
+        self.writer
+    }
+}
+
+fn is_top_level<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(tag_id: u64) -> bool {
+    matches!(TSpec::get_tag_data_type(tag_id), Some(TagDataType::Master)) && TSpec::get_path_by_id(tag_id).is_empty()
+}
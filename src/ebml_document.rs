@@ -0,0 +1,537 @@
+use std::io::{Read, Write};
+
+use crate::spec_util::parse_path;
+use crate::{TagIterator, TagWriter};
+
+use super::specs::{EbmlSpecification, EbmlTag, Master};
+use super::errors::ebml_document::EbmlDocumentError;
+
+///
+/// An in-memory tree of an entire EBML document.
+///
+/// This is a higher level alternative to the [`TagIterator`] and [`TagWriter`] for tools that don't need streaming.  The whole document is read into [`EbmlNode`]s which can be queried with [`Self::select()`], modified, and written back out with [`Self::write()`].
+///
+/// ## Queries
+///
+/// Queries are a `/` separated list of steps, each of which selects children of the nodes matched by the previous step (the first step selects top level nodes).  A step is one of:
+///
+///  - A tag name (if the spec provides them) or a hex id, like `Segment` or `0x18538067`.
+///  - `*`, which matches any tag.
+///
+/// Each step can be followed by any number of predicates in square brackets:
+///
+///  - `[Name=value]` keeps nodes with a child `Name` whose value equals `value`.  Unsigned/signed integers and floats are parsed from `value`, utf-8 values are compared as strings (surrounding quotes are optional), and binary values are compared to hex strings like `0x0a0b`.
+///  - `[Name]` keeps nodes that have a child `Name`.
+///  - `[n]` keeps the `n`th (starting at 1) node matched by the step under each parent.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::EbmlDocument;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let file = File::open("my_ebml_file.ebml")?;
+/// let mut document: EbmlDocument<EmptySpec> = EbmlDocument::read(file)?;
+/// // Find video tracks
+/// for track in document.select("0x18538067/0x1654ae6b/0xae[0x83=1]")? {
+///   println!("{:?}", track.to_tag());
+/// }
+/// // Drop all tags
+/// document.remove("0x18538067/0x1254c367")?;
+/// document.write(File::create("updated.ebml")?)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct EbmlDocument<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    nodes: Vec<EbmlNode<TSpec>>,
+}
+
+///
+/// A single element in an [`EbmlDocument`].
+///
+/// "Master" elements hold their children as nodes rather than in a [`Master::Full`] tag, so that they can be modified in place.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct EbmlNode<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    tag: TSpec,
+    children: Vec<EbmlNode<TSpec>>,
+}
+
+impl<TSpec> EbmlDocument<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new, empty [`EbmlDocument<TSpec>`].
+    ///
+    pub fn new() -> Self {
+        EbmlDocument { nodes: Vec::new() }
+    }
+
+    ///
+    /// Reads an entire document from `source`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlDocumentError::ReadError`] if the source can't be parsed.
+    ///
+    pub fn read<R: Read>(source: R) -> Result<Self, EbmlDocumentError> {
+        Self::from_tag_iterator(TagIterator::<R, TSpec>::new(source, &[]))
+    }
+
+    ///
+    /// Builds a document from the tags emitted by a [`TagIterator`].
+    ///
+    /// Masters can be emitted either as [`Master::Start`]/[`Master::End`] pairs or as [`Master::Full`] tags, so the iterator can be configured with any tags to buffer.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlDocumentError::ReadError`] if the iterator returns an error.
+    ///
+    pub fn from_tag_iterator<R: Read>(iter: TagIterator<R, TSpec>) -> Result<Self, EbmlDocumentError> {
+        let mut stack: Vec<EbmlNode<TSpec>> = Vec::new();
+        let mut nodes = Vec::new();
+        for tag in iter {
+            let tag = tag?;
+            let node = match tag.as_master() {
+                Some(Master::Start) => {
+                    stack.push(EbmlNode { tag, children: Vec::new() });
+                    continue;
+                },
+                Some(Master::End) => match stack.pop() {
+                    Some(node) => node,
+                    None => continue,
+                },
+                _ => EbmlNode::new(tag),
+            };
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => nodes.push(node),
+            }
+        }
+
+        // The iterator closes any open masters at the end of the source, but don't lose data if it didn't
+        while let Some(node) = stack.pop() {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => nodes.push(node),
+            }
+        }
+
+        Ok(EbmlDocument { nodes })
+    }
+
+    ///
+    /// Builds a document from a list of top level tags (with "Master" tags being [`Master::Full`] variants).
+    ///
+    pub fn from_tags(tags: Vec<TSpec>) -> Self {
+        EbmlDocument { nodes: tags.into_iter().map(EbmlNode::new).collect() }
+    }
+
+    ///
+    /// Returns the top level nodes of the document.
+    ///
+    pub fn nodes(&self) -> &[EbmlNode<TSpec>] {
+        &self.nodes
+    }
+
+    ///
+    /// Returns a mutable list of the top level nodes of the document.
+    ///
+    pub fn nodes_mut(&mut self) -> &mut Vec<EbmlNode<TSpec>> {
+        &mut self.nodes
+    }
+
+    ///
+    /// Returns all nodes matching `query`, in document order.  See [`EbmlDocument`] for the query syntax.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlDocumentError::InvalidQuery`] if `query` can't be parsed.
+    ///
+    pub fn select(&self, query: &str) -> Result<Vec<&EbmlNode<TSpec>>, EbmlDocumentError> {
+        let steps = parse_query::<TSpec>(query)?;
+        let mut found = Vec::new();
+        select_in(&self.nodes, &steps, &mut found);
+        Ok(found)
+    }
+
+    ///
+    /// Returns the first node matching `query`, if any.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlDocumentError::InvalidQuery`] if `query` can't be parsed.
+    ///
+    pub fn select_first(&self, query: &str) -> Result<Option<&EbmlNode<TSpec>>, EbmlDocumentError> {
+        Ok(self.select(query)?.into_iter().next())
+    }
+
+    ///
+    /// Returns mutable references to all nodes matching `query`, in document order.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlDocumentError::InvalidQuery`] if `query` can't be parsed.
+    ///
+    pub fn select_mut(&mut self, query: &str) -> Result<Vec<&mut EbmlNode<TSpec>>, EbmlDocumentError> {
+        let steps = parse_query::<TSpec>(query)?;
+        let mut found = Vec::new();
+        select_in_mut(&mut self.nodes, &steps, &mut found);
+        Ok(found)
+    }
+
+    ///
+    /// Removes all nodes matching `query` (along with their children) and returns how many were removed.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlDocumentError::InvalidQuery`] if `query` can't be parsed.
+    ///
+    pub fn remove(&mut self, query: &str) -> Result<usize, EbmlDocumentError> {
+        let mut steps = parse_query::<TSpec>(query)?;
+        let last = match steps.pop() {
+            Some(last) => last,
+            None => return Ok(0),
+        };
+
+        let mut parents = Vec::new();
+        if steps.is_empty() {
+            return Ok(remove_matching(&mut self.nodes, &last));
+        }
+        select_in_mut(&mut self.nodes, &steps, &mut parents);
+        Ok(parents.into_iter().map(|parent| remove_matching(&mut parent.children, &last)).sum())
+    }
+
+    ///
+    /// Converts the document into a list of top level tags, with "Master" tags as [`Master::Full`] variants.
+    ///
+    pub fn to_tags(&self) -> Vec<TSpec> {
+        self.nodes.iter().map(|node| node.to_tag()).collect()
+    }
+
+    ///
+    /// Writes the document to `dest`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlDocumentError::WriteError`] if there is a problem writing a tag.
+    ///
+    pub fn write<W: Write>(&self, dest: W) -> Result<(), EbmlDocumentError> {
+        let mut writer = TagWriter::new(dest);
+        for node in self.nodes.iter() {
+            node.write_to(&mut writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<TSpec> Default for EbmlDocument<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TSpec> EbmlNode<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new node for `tag`.  The children of [`Master::Full`] tags become child nodes.
+    ///
+    pub fn new(tag: TSpec) -> Self {
+        let children = match tag.as_master() {
+            Some(Master::Full(children)) => children.clone(),
+            _ => return EbmlNode { tag, children: Vec::new() },
+        };
+        let id = tag.get_id();
+        EbmlNode {
+            tag: TSpec::get_master_tag(id, Master::Start).unwrap_or(tag),
+            children: children.into_iter().map(EbmlNode::new).collect(),
+        }
+    }
+
+    ///
+    /// Returns the id of the element.
+    ///
+    pub fn id(&self) -> u64 {
+        self.tag.get_id()
+    }
+
+    ///
+    /// Returns the tag for this node.  For "Master" elements this is a [`Master::Start`] tag; use [`Self::to_tag()`] to get the tag with its children.
+    ///
+    pub fn tag(&self) -> &TSpec {
+        &self.tag
+    }
+
+    ///
+    /// Replaces the tag for this node.  If `tag` is a [`Master::Full`] tag, the children of this node are replaced as well.
+    ///
+    pub fn set_tag(&mut self, tag: TSpec) {
+        let keep_children = matches!(tag.as_master(), Some(Master::Start));
+        let node = EbmlNode::new(tag);
+        self.tag = node.tag;
+        if !keep_children {
+            self.children = node.children;
+        }
+    }
+
+    ///
+    /// Returns whether this node is a "Master" element.
+    ///
+    pub fn is_master(&self) -> bool {
+        self.tag.as_master().is_some()
+    }
+
+    ///
+    /// Returns the children of this node.
+    ///
+    pub fn children(&self) -> &[EbmlNode<TSpec>] {
+        &self.children
+    }
+
+    ///
+    /// Returns a mutable list of the children of this node.
+    ///
+    pub fn children_mut(&mut self) -> &mut Vec<EbmlNode<TSpec>> {
+        &mut self.children
+    }
+
+    ///
+    /// Returns the first child with id `id`, if any.
+    ///
+    pub fn child(&self, id: u64) -> Option<&EbmlNode<TSpec>> {
+        self.children.iter().find(|child| child.id() == id)
+    }
+
+    ///
+    /// Converts the node into a single tag, with "Master" elements as [`Master::Full`] variants.
+    ///
+    pub fn to_tag(&self) -> TSpec {
+        if self.is_master() {
+            TSpec::get_master_tag(self.id(), Master::Full(self.children.iter().map(|child| child.to_tag()).collect()))
+                .unwrap_or_else(|| self.tag.clone())
+        } else {
+            self.tag.clone()
+        }
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut TagWriter<W>) -> Result<(), EbmlDocumentError> {
+        writer.write(&self.tag)?;
+        if !self.is_master() {
+            return Ok(());
+        }
+
+        for child in self.children.iter() {
+            child.write_to(writer)?;
+        }
+        if let Some(end) = TSpec::get_master_tag(self.id(), Master::End) {
+            writer.write(&end)?;
+        }
+        Ok(())
+    }
+}
+
+enum StepMatch {
+    Any,
+    Id(u64),
+}
+
+enum Predicate {
+    Position(usize),
+    HasChild(u64),
+    ChildValue(u64, String),
+}
+
+struct Step {
+    matcher: StepMatch,
+    predicates: Vec<Predicate>,
+}
+
+fn parse_id<TSpec>(name: &str, query: &str) -> Result<u64, EbmlDocumentError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    parse_path::<TSpec>(name.trim())
+        .filter(|ids| ids.len() == 1)
+        .map(|ids| ids[0])
+        .ok_or_else(|| EbmlDocumentError::InvalidQuery(query.to_string()))
+}
+
+fn parse_query<TSpec>(query: &str) -> Result<Vec<Step>, EbmlDocumentError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let invalid = || EbmlDocumentError::InvalidQuery(query.to_string());
+    let mut steps = Vec::new();
+
+    // Split on '/' outside of brackets, since predicate values may contain slashes
+    let mut depth = 0;
+    let mut start = 0;
+    let mut segments = Vec::new();
+    for (index, c) in query.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '/' if depth == 0 => {
+                segments.push(&query[start..index]);
+                start = index + 1;
+            },
+            _ => {},
+        }
+    }
+    segments.push(&query[start..]);
+
+    for segment in segments.into_iter().filter(|s| !s.is_empty()) {
+        let name_end = segment.find('[').unwrap_or(segment.len());
+        let matcher = match segment[..name_end].trim() {
+            "*" => StepMatch::Any,
+            name => StepMatch::Id(parse_id::<TSpec>(name, query)?),
+        };
+
+        let mut predicates = Vec::new();
+        let mut rest = &segment[name_end..];
+        while !rest.is_empty() {
+            let end = rest.find(']').ok_or_else(invalid)?;
+            if !rest.starts_with('[') {
+                return Err(invalid());
+            }
+            let predicate = rest[1..end].trim();
+            rest = &rest[(end + 1)..];
+
+            predicates.push(match predicate.split_once('=') {
+                Some((name, value)) => {
+                    let value = value.trim();
+                    let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"'))
+                        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                        .unwrap_or(value);
+                    Predicate::ChildValue(parse_id::<TSpec>(name, query)?, value.to_string())
+                },
+                None => match predicate.parse::<usize>() {
+                    Ok(0) => return Err(invalid()),
+                    Ok(position) => Predicate::Position(position),
+                    Err(_) => Predicate::HasChild(parse_id::<TSpec>(predicate, query)?),
+                },
+            });
+        }
+
+        steps.push(Step { matcher, predicates });
+    }
+
+    if steps.is_empty() {
+        return Err(invalid());
+    }
+    Ok(steps)
+}
+
+fn value_matches<TSpec>(tag: &TSpec, value: &str) -> bool
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    if let Some(data) = tag.as_unsigned_int() {
+        let parsed = match value.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => value.parse::<u64>().ok(),
+        };
+        parsed == Some(*data)
+    } else if let Some(data) = tag.as_signed_int() {
+        value.parse::<i64>().ok() == Some(*data)
+    } else if let Some(data) = tag.as_float() {
+        value.parse::<f64>().ok() == Some(*data)
+    } else if let Some(data) = tag.as_utf8() {
+        data == value
+    } else if let Some(data) = tag.as_binary() {
+        let hex: String = data.iter().map(|b| format!("{b:02x}")).collect();
+        value.strip_prefix("0x").is_some_and(|v| v.eq_ignore_ascii_case(&hex))
+    } else {
+        false
+    }
+}
+
+///
+/// Returns the indexes of `nodes` matched by `step`.
+///
+fn matching_indexes<TSpec>(nodes: &[EbmlNode<TSpec>], step: &Step) -> Vec<usize>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut indexes: Vec<usize> = nodes.iter().enumerate()
+        .filter(|(_, node)| match step.matcher {
+            StepMatch::Any => true,
+            StepMatch::Id(id) => node.id() == id,
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    // Predicates apply in order, so positions are relative to the nodes kept by earlier predicates
+    for predicate in step.predicates.iter() {
+        indexes = match predicate {
+            Predicate::Position(position) => indexes.get(position - 1).copied().into_iter().collect(),
+            Predicate::HasChild(id) => indexes.into_iter().filter(|i| nodes[*i].child(*id).is_some()).collect(),
+            Predicate::ChildValue(id, value) => indexes.into_iter()
+                .filter(|i| nodes[*i].children.iter().any(|child| child.id() == *id && value_matches(&child.tag, value)))
+                .collect(),
+        };
+    }
+    indexes
+}
+
+fn select_in<'a, TSpec>(nodes: &'a [EbmlNode<TSpec>], steps: &[Step], found: &mut Vec<&'a EbmlNode<TSpec>>)
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    for index in matching_indexes(nodes, &steps[0]) {
+        if steps.len() == 1 {
+            found.push(&nodes[index]);
+        } else {
+            select_in(&nodes[index].children, &steps[1..], found);
+        }
+    }
+}
+
+fn select_in_mut<'a, TSpec>(nodes: &'a mut [EbmlNode<TSpec>], steps: &[Step], found: &mut Vec<&'a mut EbmlNode<TSpec>>)
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let indexes = matching_indexes(nodes, &steps[0]);
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if !indexes.contains(&index) {
+            continue;
+        }
+        if steps.len() == 1 {
+            found.push(node);
+        } else {
+            select_in_mut(&mut node.children, &steps[1..], found);
+        }
+    }
+}
+
+fn remove_matching<TSpec>(nodes: &mut Vec<EbmlNode<TSpec>>, step: &Step) -> usize
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let indexes = matching_indexes(nodes, step);
+    let mut index = 0;
+    nodes.retain(|_| {
+        index += 1;
+        !indexes.contains(&(index - 1))
+    });
+    indexes.len()
+}
@@ -0,0 +1,220 @@
+use std::io::{Read, Seek, Write};
+
+use crate::ebml_reader::{EbmlReader, LocatedElement};
+use crate::spec_util::{parse_path, CRC32_ID, VOID_ID};
+use crate::tag_iterator_util::{ElementHeader, read_element_header};
+use crate::tag_iterator_util::ElementSize::Known;
+use crate::tools::Crc32Hasher;
+use crate::{TagWriter, WriteOptions};
+
+use super::specs::{EbmlSpecification, EbmlTag, Master};
+use super::errors::ebml_editor::EbmlEditorError;
+use super::errors::tag_iterator::TagIteratorError;
+use super::errors::tag_writer::TagWriterError;
+
+///
+/// Modifies elements of an existing EBML document in place (on a source implementing [`std::io::Read`], [`std::io::Write`], and [`std::io::Seek`]).
+///
+/// Elements are located the same way as in an [`EbmlReader`], and are only rewritten when the new encoding fits in the space the old element occupied (plus any `Void` element immediately following it).  Leftover space is filled with a `Void` element so the sizes of enclosing elements never change.  After an edit, any `CRC-32` elements in the enclosing master elements are recalculated.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::OpenOptions;
+/// use ebml_iterable::EbmlEditor;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let file = OpenOptions::new().read(true).write(true).open("my_ebml_file.ebml")?;
+/// let mut editor: EbmlEditor<_, EmptySpec> = EbmlEditor::new(file);
+/// editor.replace("0x18538067/0x1549a966/0x7ba9", &EmptySpec::with_data(0x7ba9, b"New Title"))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct EbmlEditor<S: Read + Write + Seek, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    reader: EbmlReader<S, TSpec>,
+}
+
+impl<S: Read + Write + Seek, TSpec> EbmlEditor<S, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`EbmlEditor<S, TSpec>`] instance.
+    ///
+    /// The `source` parameter must implement [`std::io::Read`], [`std::io::Write`], and [`std::io::Seek`].  The start of the document is assumed to be at position 0 of the source.
+    ///
+    pub fn new(source: S) -> Self {
+        EbmlEditor {
+            reader: EbmlReader::new(source),
+        }
+    }
+
+    ///
+    /// Replaces the element at `path` with `tag`.
+    ///
+    /// The path follows the same rules as [`EbmlReader::open()`].  `tag` must have the same id as the element being replaced, and "Master" elements must be replaced using [`Master::Full`] tags.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlEditorError::InsufficientSpace`] if the encoded tag doesn't fit where the existing element is, in which case the document is left unchanged.  The other possible error states are enumerated in [`EbmlEditorError`].
+    ///
+    pub fn replace(&mut self, path: &str, tag: &TSpec) -> Result<(), EbmlEditorError> {
+        let ids = parse_path::<TSpec>(path)
+            .filter(|ids| !ids.is_empty())
+            .ok_or_else(|| EbmlEditorError::InvalidPath(path.to_string()))?;
+
+        self.replace_path(&ids, tag, || path.to_string())
+    }
+
+    ///
+    /// Replaces the element at a path of tag ids with `tag`.
+    ///
+    /// This is identical to [`Self::replace()`], but takes the path as a list of ids rather than a string.
+    ///
+    pub fn replace_by_ids(&mut self, ids: &[u64], tag: &TSpec) -> Result<(), EbmlEditorError> {
+        if ids.is_empty() {
+            return Err(EbmlEditorError::InvalidPath(String::new()));
+        }
+
+        self.replace_path(ids, tag, || ids.iter().map(|id| format!("0x{id:x}")).collect::<Vec<_>>().join("/"))
+    }
+
+    ///
+    /// Consumes self and returns the underlying stream.
+    ///
+    pub fn into_inner(self) -> S {
+        self.reader.into_inner()
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying stream.
+    ///
+    pub fn get_mut(&mut self) -> &mut S {
+        self.reader.get_mut()
+    }
+
+    ///
+    /// Gets a reference to the underlying stream.
+    ///
+    pub fn get_ref(&self) -> &S {
+        self.reader.get_ref()
+    }
+
+    fn replace_path(&mut self, ids: &[u64], tag: &TSpec, describe_path: impl FnOnce() -> String) -> Result<(), EbmlEditorError> {
+        let element_id = *ids.last().expect("path should not be empty");
+        let tag_id = tag.get_id();
+        if element_id != tag_id {
+            return Err(EbmlEditorError::TagIdMismatch { element_id, tag_id });
+        }
+        if matches!(tag.as_master(), Some(Master::Start) | Some(Master::End)) {
+            return Err(EbmlEditorError::IncompleteMaster(tag_id));
+        }
+
+        let mut located = self.reader.find_path(ids)?;
+        let target = located.pop().ok_or_else(|| EbmlEditorError::ElementNotFound(describe_path()))?;
+        let mut available = target.header.total_len().ok_or(EbmlEditorError::UnknownSize(element_id))?;
+
+        // A Void directly after the element is free space we can grow into, as long as it ends within the parent
+        let end = target.position + available;
+        if !matches!(target.limit, Some(limit) if end >= limit) {
+            if let Some(next) = self.reader.read_header_at(end)? {
+                if next.id == VOID_ID {
+                    let void_len = next.total_len().unwrap_or(0);
+                    if !matches!(target.limit, Some(limit) if end + void_len > limit) {
+                        available += void_len;
+                    }
+                }
+            }
+        }
+
+        let parents = &ids[..ids.len() - 1];
+        let mut encoded = encode(parents, tag, None)?;
+        if encoded.len() + 1 == available {
+            // A Void element needs at least two bytes, so take up the extra byte by widening the size vint instead
+            let size_len = encoded_size_len(&encoded);
+            if size_len < 8 {
+                encoded = encode(parents, tag, Some(size_len + 1))?;
+            }
+        }
+        if encoded.len() > available || encoded.len() + 1 == available {
+            return Err(EbmlEditorError::InsufficientSpace { required: encoded.len(), available });
+        }
+
+        self.reader.seek_to(target.position)?;
+        let remaining = available - encoded.len();
+        let dest = self.reader.get_mut();
+        dest.write_all(&encoded).map_err(|source| EbmlEditorError::WriteError { source })?;
+        if remaining > 0 {
+            dest.write_all(&void_element(remaining)).map_err(|source| EbmlEditorError::WriteError { source })?;
+        }
+
+        // Inner checksums have to be updated first since they are covered by the outer ones
+        for parent in located.iter().rev() {
+            self.update_crc(parent)?;
+        }
+
+        self.reader.get_mut().flush().map_err(|source| EbmlEditorError::WriteError { source })
+    }
+
+    fn update_crc(&mut self, parent: &LocatedElement) -> Result<(), EbmlEditorError> {
+        let data_start = parent.data_start();
+        let end = self.reader.element_end(parent.position, &parent.header, parent.limit)?;
+        if data_start >= end {
+            return Ok(());
+        }
+
+        // The CRC-32 element must be the first child of the element it covers
+        let crc_header = match self.reader.read_header_at(data_start)? {
+            Some(header) if header.id == CRC32_ID && matches!(header.size, Known(4)) => header,
+            _ => return Ok(()),
+        };
+        let crc_position = data_start + crc_header.header_len;
+
+        let mut hasher = Crc32Hasher::new();
+        let mut position = crc_position + 4;
+        let mut buffer = [0u8; 8192];
+        self.reader.seek_to(position)?;
+        while position < end {
+            let len = buffer.len().min(end - position);
+            self.reader.get_mut().read_exact(&mut buffer[..len]).map_err(|source| TagIteratorError::ReadError { source })?;
+            hasher.update(&buffer[..len]);
+            position += len;
+        }
+
+        self.reader.seek_to(crc_position)?;
+        self.reader.get_mut().write_all(&hasher.finish().to_le_bytes()).map_err(|source| EbmlEditorError::WriteError { source })
+    }
+}
+
+fn encode<TSpec>(parents: &[u64], tag: &TSpec, size_len: Option<usize>) -> Result<Vec<u8>, TagWriterError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut writer = TagWriter::new(Vec::new());
+    writer.assume_open_parents(parents);
+    match size_len {
+        Some(len) => writer.write_advanced(tag, WriteOptions::set_size_byte_count(len))?,
+        None => writer.write(tag)?,
+    }
+    writer.into_inner()
+}
+
+fn encoded_size_len(encoded: &[u8]) -> usize {
+    let header = read_element_header(&mut &encoded[..], 0)
+        .ok()
+        .flatten()
+        .expect("encoded tag should have a valid header");
+    header.header_len - header.id_len()
+}
+
+fn void_element(len: usize) -> Vec<u8> {
+    let mut element = ElementHeader::void(len).encode();
+    element.resize(len, 0);
+    element
+}
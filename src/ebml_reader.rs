@@ -0,0 +1,559 @@
+use std::io::{Read, Seek, SeekFrom, Take};
+use std::marker::PhantomData;
+
+use crate::spec_util::{is_ended_by, is_valid_path, parse_path};
+use crate::tag_iterator_util::{ElementHeader, ElementLayout, read_element_header};
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+use crate::TagIterator;
+
+use super::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use super::errors::ebml_reader::EbmlReaderError;
+use super::errors::tag_iterator::{PartialTag, TagIteratorError};
+
+///
+/// Provides random access to elements in an EBML document (read from a source implementing both [`std::io::Read`] and [`std::io::Seek`]).
+///
+/// Unlike the [`TagIterator`], which decodes every tag in the order they appear, this reader only parses element headers and seeks over any element that isn't part of the requested path.  This makes it well suited for file based workloads where only a small part of a (potentially huge) document is needed.
+///
+/// Elements are located using [`Self::open()`], which returns an [`ElementHandle`] that can be iterated, buffered into a single tag, or skipped.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::EbmlReader;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let file = File::open("my_ebml_file.ebml")?;
+/// let mut reader: EbmlReader<_, EmptySpec> = EbmlReader::new(file);
+/// // Paths can use tag names (if the spec provides them) or hex ids
+/// for tag in reader.open("0x18538067/0x1654ae6b")?.iter()? {
+///   println!("{:?}", tag?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct EbmlReader<R: Read + Seek, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    source: R,
+    _spec: PhantomData<TSpec>,
+}
+
+impl<R: Read + Seek, TSpec> EbmlReader<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`EbmlReader<R, TSpec>`] instance.
+    ///
+    /// The `source` parameter must implement [`std::io::Read`] and [`std::io::Seek`].  The start of the document is assumed to be at position 0 of the source.
+    ///
+    pub fn new(source: R) -> Self {
+        EbmlReader {
+            source,
+            _spec: PhantomData,
+        }
+    }
+
+    ///
+    /// Locates the element at `path` and returns a handle to it.
+    ///
+    /// The path is a `/` delimited list of tag names starting at a root element, e.g. `"Segment/Tracks"`.  Names are resolved using [`EbmlSpecification::get_id_by_name`]; hex ids (e.g. `"0x18538067"`) may also be used.  If an element appears more than once at a level, the first occurrence is used.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlReaderError::InvalidPath`] if the path cannot be resolved using `TSpec`, [`EbmlReaderError::ElementNotFound`] if the document doesn't contain the element, or [`EbmlReaderError::ReadError`] if the source couldn't be read.
+    ///
+    pub fn open(&mut self, path: &str) -> Result<ElementHandle<'_, R, TSpec>, EbmlReaderError> {
+        let ids = parse_path::<TSpec>(path)
+            .filter(|ids| !ids.is_empty())
+            .ok_or_else(|| EbmlReaderError::InvalidPath(path.to_string()))?;
+
+        match self.find_path(&ids)?.pop() {
+            Some(found) => Ok(ElementHandle { reader: self, found }),
+            None => Err(EbmlReaderError::ElementNotFound(path.to_string())),
+        }
+    }
+
+    ///
+    /// Locates the element at a path of tag ids and returns a handle to it.
+    ///
+    /// This is identical to [`Self::open()`], but takes the path as a list of ids rather than a string.
+    ///
+    pub fn open_by_ids(&mut self, ids: &[u64]) -> Result<ElementHandle<'_, R, TSpec>, EbmlReaderError> {
+        if ids.is_empty() {
+            return Err(EbmlReaderError::InvalidPath(String::new()));
+        }
+
+        match self.find_path(ids)?.pop() {
+            Some(found) => Ok(ElementHandle { reader: self, found }),
+            None => Err(EbmlReaderError::ElementNotFound(ids.iter().map(|id| format!("0x{id:x}")).collect::<Vec<_>>().join("/"))),
+        }
+    }
+
+    ///
+    /// Returns a handle to the element whose header starts at `offset`, such as one found by [`Self::rev_top_level()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlReaderError::ElementNotFound`] if `offset` is at the end of the source, or [`EbmlReaderError::ReadError`] if no header could be read there.
+    ///
+    pub fn open_at(&mut self, offset: usize) -> Result<ElementHandle<'_, R, TSpec>, EbmlReaderError> {
+        match self.read_header_at(offset)? {
+            Some(header) => Ok(ElementHandle { reader: self, found: LocatedElement { position: offset, header, limit: None } }),
+            None => Err(EbmlReaderError::ElementNotFound(format!("offset {offset}"))),
+        }
+    }
+
+    ///
+    /// Returns an iterator over the top-level elements of the document, starting from the end of the source and working backwards.
+    ///
+    /// See [`RevElements`] for how elements are found.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlReaderError::ReadError`] if the end of the source couldn't be found.
+    ///
+    pub fn rev_top_level(&mut self) -> Result<RevElements<'_, R, TSpec>, EbmlReaderError> {
+        let end = self.source.seek(SeekFrom::End(0)).map_err(|source| TagIteratorError::ReadError { source })? as usize;
+        Ok(RevElements::new(self, Vec::new(), 0, end))
+    }
+
+    ///
+    /// Returns an iterator over the children of the element at `path` (see [`Self::open()`]), starting from its last child and working backwards.
+    ///
+    /// This makes finding the last of many elements, like the last `Cluster` in a `Segment`, much cheaper than reading forward through the whole element.  See [`RevElements`] for how elements are found.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::open()`].
+    ///
+    pub fn rev_children(&mut self, path: &str) -> Result<RevElements<'_, R, TSpec>, EbmlReaderError> {
+        let ids = parse_path::<TSpec>(path)
+            .filter(|ids| !ids.is_empty())
+            .ok_or_else(|| EbmlReaderError::InvalidPath(path.to_string()))?;
+
+        let found = self.find_path(&ids)?.pop().ok_or_else(|| EbmlReaderError::ElementNotFound(path.to_string()))?;
+        let end = self.element_end(found.position, &found.header, found.limit)?;
+        Ok(RevElements::new(self, ids, found.data_start(), end))
+    }
+
+    ///
+    /// Consumes self and returns the underlying read stream.
+    ///
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying read stream.
+    ///
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.source
+    }
+
+    ///
+    /// Gets a reference to the underlying read stream.
+    ///
+    pub fn get_ref(&self) -> &R {
+        &self.source
+    }
+
+    ///
+    /// Locates every element along `ids`.  Returns an empty list if the full path doesn't exist.
+    ///
+    pub(crate) fn find_path(&mut self, ids: &[u64]) -> Result<Vec<LocatedElement>, TagIteratorError> {
+        let mut located: Vec<LocatedElement> = Vec::with_capacity(ids.len());
+        let mut position = 0;
+        let mut limit = None;
+        let mut unknown_parent = None;
+        for id in ids {
+            let (found_position, header) = match self.find_child(position, limit, unknown_parent, *id)? {
+                Some(found) => found,
+                None => return Ok(Vec::new()),
+            };
+            located.push(LocatedElement { position: found_position, header, limit });
+
+            position = found_position + header.header_len;
+            match header.size {
+                Known(size) => {
+                    limit = Some(position + size);
+                    unknown_parent = None;
+                },
+                Unknown => {
+                    unknown_parent = Some(header.id);
+                }
+            }
+        }
+        Ok(located)
+    }
+
+    fn find_child(&mut self, mut position: usize, limit: Option<usize>, unknown_parent: Option<u64>, id: u64) -> Result<Option<(usize, ElementHeader)>, TagIteratorError> {
+        loop {
+            if matches!(limit, Some(limit) if position >= limit) {
+                return Ok(None);
+            }
+
+            let header = match self.read_header_at(position)? {
+                Some(header) => header,
+                None => return Ok(None),
+            };
+
+            if matches!(unknown_parent, Some(parent) if is_ended_by::<TSpec>(parent, header.id)) {
+                return Ok(None);
+            }
+
+            if header.id == id {
+                return Ok(Some((position, header)));
+            }
+
+            position = self.element_end(position, &header, limit)?;
+        }
+    }
+
+    pub(crate) fn read_header_at(&mut self, position: usize) -> Result<Option<ElementHeader>, TagIteratorError> {
+        self.seek_to(position)?;
+        read_element_header(&mut self.source, position)
+    }
+
+    pub(crate) fn seek_to(&mut self, position: usize) -> Result<(), TagIteratorError> {
+        self.source.seek(SeekFrom::Start(position as u64)).map_err(|source| TagIteratorError::ReadError { source })?;
+        Ok(())
+    }
+
+    pub(crate) fn element_end(&mut self, position: usize, header: &ElementHeader, limit: Option<usize>) -> Result<usize, TagIteratorError> {
+        if let Some(len) = header.total_len() {
+            return Ok(position + len);
+        }
+
+        // Unknown sized elements have to be walked until we find an element that isn't one of their children
+        let mut child_position = position + header.header_len;
+        loop {
+            if matches!(limit, Some(limit) if child_position >= limit) {
+                return Ok(child_position);
+            }
+
+            let child = match self.read_header_at(child_position)? {
+                Some(child) => child,
+                None => return Ok(child_position),
+            };
+
+            if is_ended_by::<TSpec>(header.id, child.id) {
+                return Ok(child_position);
+            }
+
+            child_position = self.element_end(child_position, &child, limit)?;
+        }
+    }
+}
+
+///
+/// Location details of an element found by an [`EbmlReader`].
+///
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct LocatedElement {
+    pub position: usize,
+    pub header: ElementHeader,
+
+    /// End of the nearest known-size parent, if any
+    pub limit: Option<usize>,
+}
+
+impl LocatedElement {
+    pub fn data_start(&self) -> usize {
+        self.position + self.header.header_len
+    }
+}
+
+///
+/// A handle to a single element located by an [`EbmlReader`].
+///
+/// Handles borrow the reader, so only one can be active at a time.  A handle can be consumed to [iterate](Self::iter) over the element's tags, [buffer](Self::buffer) the element into a single tag, or [skip](Self::skip) over it.
+///
+pub struct ElementHandle<'a, R: Read + Seek, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    reader: &'a mut EbmlReader<R, TSpec>,
+    found: LocatedElement,
+}
+
+impl<'a, R: Read + Seek, TSpec> ElementHandle<'a, R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns the id of the element.
+    ///
+    pub fn id(&self) -> u64 {
+        self.found.header.id
+    }
+
+    ///
+    /// Returns the byte offset of the start of the element (including its header) in the source.
+    ///
+    pub fn offset(&self) -> usize {
+        self.found.position
+    }
+
+    ///
+    /// Returns the byte length of the element header (id + size).
+    ///
+    pub fn header_len(&self) -> usize {
+        self.found.header.header_len
+    }
+
+    ///
+    /// Returns the declared size of the element data, or `None` if the element has an unknown size.
+    ///
+    pub fn data_size(&self) -> Option<usize> {
+        match self.found.header.size {
+            Known(size) => Some(size),
+            Unknown => None,
+        }
+    }
+
+    ///
+    /// Returns the byte offset just past the end of the element.
+    ///
+    /// For elements of unknown size this requires walking the headers of the element's children.
+    ///
+    pub fn end_offset(&mut self) -> Result<usize, EbmlReaderError> {
+        Ok(self.reader.element_end(self.found.position, &self.found.header, self.found.limit)?)
+    }
+
+    ///
+    /// Consumes the handle and returns a [`TagIterator`] over this element and all of its children.
+    ///
+    /// The returned iterator is limited to the bytes of this element.  Note that offsets reported by the iterator (like [`TagIterator::last_emitted_tag_offset()`]) are relative to [`Self::offset()`].
+    ///
+    pub fn iter(self) -> Result<TagIterator<Take<&'a mut R>, TSpec>, EbmlReaderError> {
+        self.iter_buffered(&[])
+    }
+
+    ///
+    /// Consumes the handle and reads the element as a single tag.
+    ///
+    /// "Master" elements are returned as [`Master::Full`] variants containing all children.
+    ///
+    pub fn buffer(self) -> Result<TSpec, EbmlReaderError> {
+        let tag_id = self.found.header.id;
+        let start = self.found.position;
+        let to_buffer: Vec<TSpec> = TSpec::get_master_tag(tag_id, Master::Start).into_iter().collect();
+        let mut iter = self.iter_buffered(&to_buffer)?;
+        match iter.next() {
+            Some(tag) => Ok(tag?),
+            None => Err(EbmlReaderError::ReadError { source: TagIteratorError::UnexpectedEOF(PartialTag::in_header(start, Some(tag_id))) }),
+        }
+    }
+
+    ///
+    /// Consumes the handle and positions the underlying source just past the end of the element.
+    ///
+    /// Returns the offset of the end of the element.
+    ///
+    pub fn skip(mut self) -> Result<usize, EbmlReaderError> {
+        let end = self.end_offset()?;
+        self.reader.seek_to(end)?;
+        Ok(end)
+    }
+
+    fn iter_buffered(mut self, tags_to_buffer: &[TSpec]) -> Result<TagIterator<Take<&'a mut R>, TSpec>, EbmlReaderError> {
+        let end = self.end_offset()?;
+        self.reader.seek_to(self.found.position)?;
+        let len = (end - self.found.position) as u64;
+        Ok(TagIterator::new(self.reader.source.by_ref().take(len), tags_to_buffer))
+    }
+}
+
+const REVERSE_CHUNK_LEN: usize = 64 * 1024;
+
+// An 8 byte id followed by an 8 byte size
+const MAX_HEADER_LEN: usize = 16;
+
+///
+/// An iterator over sibling elements in reverse order, returned by [`EbmlReader::rev_top_level()`] and [`EbmlReader::rev_children()`].
+///
+/// Elements are found by reading the source backwards in chunks and checking each offset for the header of an element that the spec allows at this level and that ends exactly where the previously found element starts.  "Master" elements are also checked by walking the headers of their children, and elements of unknown size are accepted if their children end where the previous element starts.  Since the data of a binary element could hold something that looks like such a header, an element is only returned once the element before it has also been found (or it is the first one).  The iterator only reads a little further back than the elements it returns, but the whole of each of those elements is read.
+///
+/// Each item is an [`ElementLayout`] (without children) describing where the element is; use [`EbmlReader::open_at()`] to read it.
+///
+/// ## Errors
+///
+/// If no element ends at the start of the previously found element (for example, because the document is truncated or corrupt), an [`EbmlReaderError::UnrecognizedData`] is returned and iteration stops.
+///
+pub struct RevElements<'a, R: Read + Seek, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    reader: &'a mut EbmlReader<R, TSpec>,
+    parents: Vec<u64>,
+
+    /// Start and end of the data holding the elements
+    start: usize,
+    limit: usize,
+
+    /// End of the next element to be found
+    end: usize,
+
+    /// An element found ending at `end` that hasn't been checked yet
+    pending: Option<ElementLayout>,
+
+    buffer: Vec<u8>,
+    buffer_start: usize,
+    done: bool,
+}
+
+impl<'a, R: Read + Seek, TSpec> RevElements<'a, R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn new(reader: &'a mut EbmlReader<R, TSpec>, parents: Vec<u64>, start: usize, limit: usize) -> Self {
+        RevElements {
+            reader,
+            parents,
+            start,
+            limit,
+            end: limit,
+            pending: None,
+            buffer: Vec::new(),
+            buffer_start: limit,
+            done: false,
+        }
+    }
+
+    ///
+    /// Finds the element ending at `self.end` that is preceded by another element (or the start of the data).
+    ///
+    fn find_checked(&mut self) -> Result<Option<ElementLayout>, TagIteratorError> {
+        let mut candidate = match self.pending.take() {
+            Some(candidate) => Some(candidate),
+            None => self.find_previous(self.end, self.end)?,
+        };
+        while let Some(found) = candidate {
+            if found.offset == self.start {
+                return Ok(Some(found));
+            }
+            self.pending = self.find_previous(found.offset, found.offset)?;
+            if self.pending.is_some() {
+                return Ok(Some(found));
+            }
+            candidate = self.find_previous(self.end, found.offset)?;
+        }
+        Ok(None)
+    }
+
+    ///
+    /// Finds the closest element before `from` that ends at `end`.
+    ///
+    fn find_previous(&mut self, end: usize, from: usize) -> Result<Option<ElementLayout>, TagIteratorError> {
+        let mut position = from;
+        while position > self.start {
+            position -= 1;
+            let header_end = (position + MAX_HEADER_LEN).min(self.limit);
+            if position < self.buffer_start || header_end > self.buffer_start + self.buffer.len() {
+                self.fill_buffer(position, header_end)?;
+            }
+
+            let header = match read_element_header(&mut &self.buffer[(position - self.buffer_start)..], position) {
+                Ok(Some(header)) => header,
+                _ => continue,
+            };
+            if is_valid_path::<TSpec>(header.id, &self.parents) && self.ends_at(position, &header, end)? {
+                return Ok(Some(ElementLayout {
+                    id: header.id,
+                    offset: position,
+                    header_len: header.header_len,
+                    size: header.size,
+                    children: Vec::new(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn fill_buffer(&mut self, position: usize, header_end: usize) -> Result<(), TagIteratorError> {
+        let start = (position + 1).saturating_sub(REVERSE_CHUNK_LEN).max(self.start);
+        self.buffer.resize(header_end - start, 0);
+        self.reader.seek_to(start)?;
+        self.reader.source.read_exact(&mut self.buffer).map_err(|source| TagIteratorError::ReadError { source })?;
+        self.buffer_start = start;
+        Ok(())
+    }
+
+    ///
+    /// Checks whether the element with `header` at `position` ends at `end`.
+    ///
+    fn ends_at(&mut self, position: usize, header: &ElementHeader, end: usize) -> Result<bool, TagIteratorError> {
+        if let Some(len) = header.total_len() {
+            if position + len != end {
+                return Ok(false);
+            }
+            if TSpec::get_tag_data_type(header.id) != Some(TagDataType::Master) {
+                return Ok(true);
+            }
+        }
+
+        // A header found in the middle of some binary data is unlikely to be followed by a valid list of children
+        let data_end = header.total_len().map_or(self.limit, |len| position + len);
+        let mut child_position = position + header.header_len;
+        while child_position < data_end {
+            let child = match self.reader.read_header_at(child_position) {
+                Ok(Some(child)) => child,
+                Ok(None) => break,
+                Err(err) => return not_found(err),
+            };
+            if header.size == Unknown && is_ended_by::<TSpec>(header.id, child.id) {
+                break;
+            }
+            child_position = match self.reader.element_end(child_position, &child, Some(data_end)) {
+                Ok(end) => end,
+                Err(err) => return not_found(err),
+            };
+        }
+        Ok(child_position == end)
+    }
+}
+
+///
+/// Treats data that can't be parsed as not being the element being looked for, while still reporting read errors.
+///
+fn not_found(err: TagIteratorError) -> Result<bool, TagIteratorError> {
+    match err {
+        TagIteratorError::ReadError { .. } => Err(err),
+        _ => Ok(false),
+    }
+}
+
+impl<R: Read + Seek, TSpec> Iterator for RevElements<'_, R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<ElementLayout, EbmlReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.end <= self.start {
+            return None;
+        }
+        match self.find_checked() {
+            Ok(Some(layout)) => {
+                self.end = layout.offset;
+                Some(Ok(layout))
+            },
+            Ok(None) => {
+                self.done = true;
+                Some(Err(EbmlReaderError::UnrecognizedData(self.end)))
+            },
+            Err(err) => {
+                self.done = true;
+                Some(Err(err.into()))
+            },
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use std::io::{ErrorKind, SeekFrom};
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use futures::io::Take;
+use futures::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::spec_util::{is_ended_by, parse_path};
+use crate::tag_iterator_util::{ElementHeader, read_element_header};
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+use crate::ebml_reader::LocatedElement;
+use crate::nonblocking::TagIteratorAsync;
+
+use super::specs::{EbmlSpecification, EbmlTag, Master};
+use super::errors::ebml_reader::EbmlReaderError;
+use super::errors::tag_iterator::{PartialTag, TagIteratorError};
+
+///
+/// Provides random access to elements in an EBML document read from a source implementing both [`futures::AsyncRead`] and [`futures::AsyncSeek`].
+///
+/// This is the async counterpart of [`EbmlReader`][crate::EbmlReader].  Only element headers are parsed while locating an element, and any element that isn't part of the requested path is seeked over, so only a small part of a remote document (such as one behind an object storage range reader) needs to be fetched.
+///
+/// Elements are located using [`Self::open()`], which returns an [`ElementHandleAsync`] that can be iterated, buffered into a single tag, skipped, or have a range of its payload streamed.
+///
+/// ## Example
+///
+/// ```no_run
+/// use futures::io::Cursor;
+/// use ebml_iterable::nonblocking::EbmlReaderAsync;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # async fn read(data: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+/// let mut reader: EbmlReaderAsync<_, EmptySpec> = EbmlReaderAsync::new(Cursor::new(data));
+/// let mut tags = reader.open("0x18538067/0x1654ae6b").await?.iter().await?;
+/// while let Some(tag) = tags.next().await {
+///   println!("{:?}", tag?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct EbmlReaderAsync<R: AsyncRead + AsyncSeek + Unpin, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    source: R,
+    _spec: PhantomData<TSpec>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin, TSpec> EbmlReaderAsync<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`EbmlReaderAsync<R, TSpec>`] instance.
+    ///
+    /// The start of the document is assumed to be at position 0 of the source.
+    ///
+    pub fn new(source: R) -> Self {
+        EbmlReaderAsync {
+            source,
+            _spec: PhantomData,
+        }
+    }
+
+    ///
+    /// Locates the element at `path` and returns a handle to it.
+    ///
+    /// Paths work the same as in [`EbmlReader::open()`][crate::EbmlReader::open].
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`EbmlReaderError::InvalidPath`] if the path cannot be resolved using `TSpec`, [`EbmlReaderError::ElementNotFound`] if the document doesn't contain the element, or [`EbmlReaderError::ReadError`] if the source couldn't be read.
+    ///
+    pub async fn open(&mut self, path: &str) -> Result<ElementHandleAsync<'_, R, TSpec>, EbmlReaderError> {
+        let ids = parse_path::<TSpec>(path)
+            .filter(|ids| !ids.is_empty())
+            .ok_or_else(|| EbmlReaderError::InvalidPath(path.to_string()))?;
+
+        match self.find_path(&ids).await?.pop() {
+            Some(found) => Ok(ElementHandleAsync { reader: self, found }),
+            None => Err(EbmlReaderError::ElementNotFound(path.to_string())),
+        }
+    }
+
+    ///
+    /// Locates the element at a path of tag ids and returns a handle to it.
+    ///
+    /// This is identical to [`Self::open()`], but takes the path as a list of ids rather than a string.
+    ///
+    pub async fn open_by_ids(&mut self, ids: &[u64]) -> Result<ElementHandleAsync<'_, R, TSpec>, EbmlReaderError> {
+        if ids.is_empty() {
+            return Err(EbmlReaderError::InvalidPath(String::new()));
+        }
+
+        match self.find_path(ids).await?.pop() {
+            Some(found) => Ok(ElementHandleAsync { reader: self, found }),
+            None => Err(EbmlReaderError::ElementNotFound(ids.iter().map(|id| format!("0x{id:x}")).collect::<Vec<_>>().join("/"))),
+        }
+    }
+
+    ///
+    /// Consumes self and returns the underlying read stream.
+    ///
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying read stream.
+    ///
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.source
+    }
+
+    ///
+    /// Gets a reference to the underlying read stream.
+    ///
+    pub fn get_ref(&self) -> &R {
+        &self.source
+    }
+
+    async fn find_path(&mut self, ids: &[u64]) -> Result<Vec<LocatedElement>, TagIteratorError> {
+        let mut located: Vec<LocatedElement> = Vec::with_capacity(ids.len());
+        let mut position = 0;
+        let mut limit = None;
+        let mut unknown_parent = None;
+        for id in ids {
+            let (found_position, header) = match self.find_child(position, limit, unknown_parent, *id).await? {
+                Some(found) => found,
+                None => return Ok(Vec::new()),
+            };
+            located.push(LocatedElement { position: found_position, header, limit });
+
+            position = found_position + header.header_len;
+            match header.size {
+                Known(size) => {
+                    limit = Some(position + size);
+                    unknown_parent = None;
+                },
+                Unknown => {
+                    unknown_parent = Some(header.id);
+                }
+            }
+        }
+        Ok(located)
+    }
+
+    async fn find_child(&mut self, mut position: usize, limit: Option<usize>, unknown_parent: Option<u64>, id: u64) -> Result<Option<(usize, ElementHeader)>, TagIteratorError> {
+        loop {
+            if matches!(limit, Some(limit) if position >= limit) {
+                return Ok(None);
+            }
+
+            let header = match self.read_header_at(position).await? {
+                Some(header) => header,
+                None => return Ok(None),
+            };
+
+            if matches!(unknown_parent, Some(parent) if is_ended_by::<TSpec>(parent, header.id)) {
+                return Ok(None);
+            }
+
+            if header.id == id {
+                return Ok(Some((position, header)));
+            }
+
+            position = self.element_end(position, &header, limit).await?;
+        }
+    }
+
+    async fn read_header_at(&mut self, position: usize) -> Result<Option<ElementHeader>, TagIteratorError> {
+        self.seek_to(position).await?;
+
+        // Read just the bytes of the id and size vints, then let the sync parser handle them (including any that are missing)
+        let mut bytes = [0u8; 16];
+        let mut len = 0;
+        for _ in 0..2 {
+            if !self.read_into(&mut bytes, len, len + 1).await? {
+                break;
+            }
+            len += 1;
+            let vint_len = bytes[len - 1].leading_zeros() as usize + 1;
+            if vint_len > 8 {
+                break;
+            }
+            let end = len + vint_len - 1;
+            if !self.read_into(&mut bytes, len, end).await? {
+                break;
+            }
+            len = end;
+        }
+        read_element_header(&mut &bytes[..len], position)
+    }
+
+    ///
+    /// Fills `bytes[start..end]` from the source, returning `false` if it ends first.
+    ///
+    async fn read_into(&mut self, bytes: &mut [u8], mut start: usize, end: usize) -> Result<bool, TagIteratorError> {
+        while start < end {
+            match self.source.read(&mut bytes[start..end]).await {
+                Ok(0) => return Ok(false),
+                Ok(read) => start += read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {},
+                Err(source) => return Err(TagIteratorError::ReadError { source }),
+            }
+        }
+        Ok(true)
+    }
+
+    async fn seek_to(&mut self, position: usize) -> Result<(), TagIteratorError> {
+        self.source.seek(SeekFrom::Start(position as u64)).await.map_err(|source| TagIteratorError::ReadError { source })?;
+        Ok(())
+    }
+
+    async fn element_end(&mut self, position: usize, header: &ElementHeader, limit: Option<usize>) -> Result<usize, TagIteratorError> {
+        if let Some(len) = header.total_len() {
+            return Ok(position + len);
+        }
+
+        // Unknown sized elements have to be walked until we find an element that isn't one of their children.  Unknown sized children are walked in the same loop, so this keeps a stack of the ones that are open.
+        let mut open = vec![header.id];
+        let mut child_position = position + header.header_len;
+        loop {
+            if matches!(limit, Some(limit) if child_position >= limit) {
+                return Ok(child_position);
+            }
+
+            let child = match self.read_header_at(child_position).await? {
+                Some(child) => child,
+                None => return Ok(child_position),
+            };
+
+            while matches!(open.last(), Some(parent) if is_ended_by::<TSpec>(*parent, child.id)) {
+                open.pop();
+            }
+            if open.is_empty() {
+                return Ok(child_position);
+            }
+
+            match child.total_len() {
+                Some(len) => child_position += len,
+                None => {
+                    open.push(child.id);
+                    child_position += child.header_len;
+                },
+            }
+        }
+    }
+}
+
+///
+/// A handle to a single element located by an [`EbmlReaderAsync`].
+///
+/// Handles borrow the reader, so only one can be active at a time.  A handle can be consumed to [iterate](Self::iter) over the element's tags, [buffer](Self::buffer) the element into a single tag, [skip](Self::skip) over it, or [stream part of its payload](Self::payload_range).
+///
+pub struct ElementHandleAsync<'a, R: AsyncRead + AsyncSeek + Unpin, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    reader: &'a mut EbmlReaderAsync<R, TSpec>,
+    found: LocatedElement,
+}
+
+impl<'a, R: AsyncRead + AsyncSeek + Unpin, TSpec> ElementHandleAsync<'a, R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns the id of the element.
+    ///
+    pub fn id(&self) -> u64 {
+        self.found.header.id
+    }
+
+    ///
+    /// Returns the byte offset of the start of the element (including its header) in the source.
+    ///
+    pub fn offset(&self) -> usize {
+        self.found.position
+    }
+
+    ///
+    /// Returns the byte length of the element header (id + size).
+    ///
+    pub fn header_len(&self) -> usize {
+        self.found.header.header_len
+    }
+
+    ///
+    /// Returns the declared size of the element data, or `None` if the element has an unknown size.
+    ///
+    pub fn data_size(&self) -> Option<usize> {
+        match self.found.header.size {
+            Known(size) => Some(size),
+            Unknown => None,
+        }
+    }
+
+    ///
+    /// Returns the byte offset just past the end of the element.
+    ///
+    /// For elements of unknown size this requires walking the headers of the element's children.
+    ///
+    pub async fn end_offset(&mut self) -> Result<usize, EbmlReaderError> {
+        Ok(self.reader.element_end(self.found.position, &self.found.header, self.found.limit).await?)
+    }
+
+    ///
+    /// Consumes the handle and returns a [`TagIteratorAsync`] over this element and all of its children.
+    ///
+    /// The returned iterator is limited to the bytes of this element.
+    ///
+    pub async fn iter(self) -> Result<TagIteratorAsync<Take<&'a mut R>, TSpec>, EbmlReaderError> {
+        self.iter_buffered(&[]).await
+    }
+
+    ///
+    /// Consumes the handle and reads the element as a single tag.
+    ///
+    /// "Master" elements are returned as [`Master::Full`] variants containing all children.
+    ///
+    pub async fn buffer(self) -> Result<TSpec, EbmlReaderError> {
+        let tag_id = self.found.header.id;
+        let start = self.found.position;
+        let to_buffer: Vec<TSpec> = TSpec::get_master_tag(tag_id, Master::Start).into_iter().collect();
+        let mut iter = self.iter_buffered(&to_buffer).await?;
+        match iter.next().await {
+            Some(tag) => Ok(tag?),
+            None => Err(EbmlReaderError::ReadError { source: TagIteratorError::UnexpectedEOF(PartialTag::in_header(start, Some(tag_id))) }),
+        }
+    }
+
+    ///
+    /// Consumes the handle and positions the underlying source just past the end of the element.
+    ///
+    /// Returns the offset of the end of the element.
+    ///
+    pub async fn skip(mut self) -> Result<usize, EbmlReaderError> {
+        let end = self.end_offset().await?;
+        self.reader.seek_to(end).await?;
+        Ok(end)
+    }
+
+    ///
+    /// Consumes the handle and returns a reader over `range` of the element's payload, without reading anything before it.
+    ///
+    /// The range is relative to the start of the payload, and is cut short at the end of the element.  This suits fetching part of a large binary element (e.g. a frame inside a block) from a remote source.
+    ///
+    pub async fn payload_range(mut self, range: Range<usize>) -> Result<Take<&'a mut R>, EbmlReaderError> {
+        let data_start = self.found.data_start();
+        let end = self.end_offset().await?;
+        let start = data_start.saturating_add(range.start).min(end);
+        let len = range.end.saturating_sub(range.start).min(end - start);
+        self.reader.seek_to(start).await?;
+        Ok((&mut self.reader.source).take(len as u64))
+    }
+
+    async fn iter_buffered(mut self, tags_to_buffer: &[TSpec]) -> Result<TagIteratorAsync<Take<&'a mut R>, TSpec>, EbmlReaderError> {
+        let end = self.end_offset().await?;
+        self.reader.seek_to(self.found.position).await?;
+        let len = (end - self.found.position) as u64;
+        Ok(TagIteratorAsync::new((&mut self.reader.source).take(len), tags_to_buffer))
+    }
+}
@@ -0,0 +1,435 @@
+use std::io::{Read, Write};
+use std::ops::Range;
+
+use digest::{Digest, Output};
+
+use crate::header_walker::HeaderWalker;
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::element_digest::DigestError;
+
+///
+/// The hash of a single element, as computed by [`digest_elements()`].
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementDigest<D: Digest> {
+
+    ///
+    /// The id of the element.
+    ///
+    pub id: u64,
+
+    ///
+    /// The position of the start of the element (including its header) in the source.
+    ///
+    pub position: usize,
+
+    ///
+    /// The total length of the element (including its header).
+    ///
+    pub len: usize,
+
+    ///
+    /// The hash of every byte of the element, including its header.
+    ///
+    pub hash: Output<D>,
+}
+
+///
+/// Copies a document from `source` to `sink` while hashing every element with an id in `ids`.
+///
+/// Each hash covers an element's header and data, exactly as they appear in the source.  Selected elements can be nested inside each other.  This lets integrity manifests or signatures be computed while a document is being stored or uploaded, without reading it a second time; pass [`std::io::sink()`] as `sink` when only the hashes are needed.  Returns the hashes in the order the elements appear.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::utils::digest_elements;
+/// use sha2::Sha256;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let source = File::open("my_ebml_file.ebml")?;
+/// let backup = File::create("backup.ebml")?;
+/// // Hash every "Cluster" while making a backup copy
+/// for digest in digest_elements::<EmptySpec, Sha256, _, _>(source, backup, &[0x1f43b675])? {
+///     println!("0x{:x} @ {}: {:x?}", digest.id, digest.position, digest.hash);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Errors
+///
+/// This method can error if there is a problem reading the source or writing to the sink.  The different possible error states are enumerated in [`DigestError`].
+///
+pub fn digest_elements<TSpec, D, R, W>(source: R, mut sink: W, ids: &[u64]) -> Result<Vec<ElementDigest<D>>, DigestError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    D: Digest,
+    R: Read,
+    W: Write,
+{
+    let write_err = |source| DigestError::WriteError { source };
+    let mut walker: HeaderWalker<R, TSpec> = HeaderWalker::new(source);
+    let mut hashing = HashingSink { inner: &mut sink, active: Vec::new(), done: Vec::new() };
+
+    loop {
+        let next = walker.next_header()?;
+
+        // Unknown-size elements end once we're no longer inside them
+        let depth = if next.is_some() { walker.depth() } else { 0 };
+        hashing.finish_unknown_sized(depth, walker.position() - next.map_or(0, |h| h.header_len));
+
+        let header = match next {
+            Some(header) => header,
+            None => break,
+        };
+
+        if ids.contains(&header.id) {
+            hashing.active.push(ActiveDigest {
+                id: header.id,
+                position: walker.position() - header.header_len,
+                depth,
+                len: header.total_len(),
+                remaining: header.total_len(),
+                hasher: D::new(),
+            });
+        }
+
+        hashing.write_all(&header.encode()).map_err(write_err)?;
+        match header.size {
+            Known(size) if !HeaderWalker::<R, TSpec>::is_master(&header) => walker.copy_data(size, &mut hashing, write_err)?,
+            Known(_) | Unknown => walker.descend(&header),
+        }
+    }
+
+    hashing.flush().map_err(write_err)?;
+    let mut done = hashing.done;
+    done.sort_by_key(|d| d.position);
+    Ok(done)
+}
+
+struct ActiveDigest<D: Digest> {
+    id: u64,
+    position: usize,
+    depth: usize,
+    len: Option<usize>,
+    remaining: Option<usize>,
+    hasher: D,
+}
+
+impl<D: Digest> ActiveDigest<D> {
+    fn finish(self, end: usize) -> ElementDigest<D> {
+        ElementDigest { id: self.id, position: self.position, len: end - self.position, hash: self.hasher.finalize() }
+    }
+}
+
+///
+/// Writes through to `inner`, feeding written bytes to every element currently being hashed.
+///
+struct HashingSink<'a, W: Write, D: Digest> {
+    inner: &'a mut W,
+    active: Vec<ActiveDigest<D>>,
+    done: Vec<ElementDigest<D>>,
+}
+
+impl<W: Write, D: Digest> HashingSink<'_, W, D> {
+    fn finish_unknown_sized(&mut self, depth: usize, end: usize) {
+        let mut index = 0;
+        while index < self.active.len() {
+            if self.active[index].remaining.is_none() && self.active[index].depth >= depth {
+                let finished = self.active.remove(index);
+                self.done.push(finished.finish(end));
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+impl<W: Write, D: Digest> Write for HashingSink<'_, W, D> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        let mut index = 0;
+        while index < self.active.len() {
+            let active = &mut self.active[index];
+            let take = active.remaining.map_or(len, |remaining| remaining.min(len));
+            active.hasher.update(&buf[..take]);
+            if let Some(remaining) = active.remaining.as_mut() {
+                *remaining -= take;
+                if *remaining == 0 {
+                    let finished = self.active.remove(index);
+                    let end = finished.position + finished.len.unwrap_or_default();
+                    self.done.push(finished.finish(end));
+                    continue;
+                }
+            }
+            index += 1;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+///
+/// Wraps a reader or writer and hashes the bytes that pass through it within specific byte ranges.
+///
+/// Ranges are positions in the stream, starting at 0 with the first byte read or written.  This can wrap the source of a [`TagIterator`][`crate::TagIterator`] or the destination of a [`TagWriter`][`crate::TagWriter`] (e.g. using positions reported by [`TagIterator::last_emitted_tag_offset()`][`crate::TagIterator::last_emitted_tag_offset`] from an earlier pass, or a manifest) so hashes are computed without reading the data again.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::utils::DigestStream;
+/// use sha2::{Digest, Sha256};
+///
+/// let data = [1u8, 2, 3, 4, 5];
+/// let mut stream: DigestStream<_, Sha256> = DigestStream::new(&data[..], &[1..3]);
+/// std::io::copy(&mut stream, &mut std::io::sink()).unwrap();
+/// let (_, hashes) = stream.finalize();
+/// assert_eq!(Sha256::digest([2u8, 3]), hashes[0]);
+/// ```
+///
+pub struct DigestStream<T, D: Digest> {
+    inner: T,
+    position: u64,
+    ranges: Vec<(Range<u64>, D)>,
+}
+
+impl<T, D: Digest> DigestStream<T, D> {
+
+    ///
+    /// Returns a new [`DigestStream`] around `inner` that hashes each of `ranges` separately.
+    ///
+    pub fn new(inner: T, ranges: &[Range<u64>]) -> Self {
+        DigestStream {
+            inner,
+            position: 0,
+            ranges: ranges.iter().map(|range| (range.clone(), D::new())).collect(),
+        }
+    }
+
+    ///
+    /// Returns the number of bytes that have passed through the stream.
+    ///
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    ///
+    /// Consumes self and returns the underlying stream along with the hash of each range (in the order they were given).
+    ///
+    /// Ranges that extend past the data that passed through the stream only cover the part that did.
+    ///
+    pub fn finalize(self) -> (T, Vec<Output<D>>) {
+        (self.inner, self.ranges.into_iter().map(|(_, hasher)| hasher.finalize()).collect())
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying stream.
+    ///
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    ///
+    /// Gets a reference to the underlying stream.
+    ///
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    fn hash(&mut self, data: &[u8]) {
+        let start = self.position;
+        let end = start + data.len() as u64;
+        for (range, hasher) in self.ranges.iter_mut() {
+            if range.start < end && range.end > start {
+                let from = (range.start.max(start) - start) as usize;
+                let to = (range.end.min(end) - start) as usize;
+                hasher.update(&data[from..to]);
+            }
+        }
+        self.position = end;
+    }
+}
+
+impl<T: Read, D: Digest> Read for DigestStream<T, D> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hash(&buf[..len]);
+        Ok(len)
+    }
+}
+
+impl<T: Write, D: Digest> Write for DigestStream<T, D> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hash(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+///
+/// Wraps a reader and hashes every byte read through it, for storing a document by its content hash while it is parsed.
+///
+/// When used as the source of a [`TagIterator`][`crate::TagIterator`], the iterator reads ahead of the tags it has emitted, so the hash covers exactly the document once the iterator has reached the end of the source.  Use [`DigestStream`] to hash specific byte ranges instead.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagIterator;
+/// use ebml_iterable::utils::HashingReader;
+/// use sha2::{Digest, Sha256};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let data = [0x42, 0x86, 0x81, 0x01];
+/// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(HashingReader::<_, Sha256>::new(&data[..]), &[]);
+/// let tags: Vec<EmptySpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+/// let reader = iter.into_inner();
+/// assert_eq!(4, reader.len());
+/// let (_, hash) = reader.finalize();
+/// assert_eq!(Sha256::digest(data), hash);
+/// ```
+///
+pub struct HashingReader<R: Read, D: Digest> {
+    inner: R,
+    hasher: D,
+    len: u64,
+}
+
+impl<R: Read, D: Digest> HashingReader<R, D> {
+
+    ///
+    /// Returns a new [`HashingReader`] around `inner`.
+    ///
+    pub fn new(inner: R) -> Self {
+        HashingReader { inner, hasher: D::new(), len: 0 }
+    }
+
+    ///
+    /// Returns the number of bytes that have been read (and hashed).
+    ///
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    ///
+    /// Consumes self and returns the underlying reader along with the hash of every byte read.
+    ///
+    pub fn finalize(self) -> (R, Output<D>) {
+        (self.inner, self.hasher.finalize())
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Data read directly from the underlying reader is not hashed.
+    ///
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    ///
+    /// Gets a reference to the underlying reader.
+    ///
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: Read, D: Digest> Read for HashingReader<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        self.len += len as u64;
+        Ok(len)
+    }
+}
+
+///
+/// Wraps a writer and hashes every byte written through it, so the content hash of a document is known as soon as it has been written.
+///
+/// When used as the destination of a [`TagWriter`][`crate::TagWriter`], flush the writer (or use [`TagWriter::into_inner()`][`crate::TagWriter::into_inner`]) first so that every tag has been written out.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagWriter;
+/// use ebml_iterable::utils::HashingWriter;
+/// use sha2::{Digest, Sha256};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut writer = TagWriter::new(HashingWriter::<_, Sha256>::new(Vec::new()));
+/// writer.write(&EmptySpec::with_data(0x4286, &[0x01])).unwrap();
+/// let (data, hash) = writer.into_inner().unwrap().finalize();
+/// assert_eq!(Sha256::digest(&data), hash);
+/// ```
+///
+pub struct HashingWriter<W: Write, D: Digest> {
+    inner: W,
+    hasher: D,
+    len: u64,
+}
+
+impl<W: Write, D: Digest> HashingWriter<W, D> {
+
+    ///
+    /// Returns a new [`HashingWriter`] around `inner`.
+    ///
+    pub fn new(inner: W) -> Self {
+        HashingWriter { inner, hasher: D::new(), len: 0 }
+    }
+
+    ///
+    /// Returns the number of bytes that have been written (and hashed).
+    ///
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    ///
+    /// Consumes self and returns the underlying writer along with the hash of every byte written.
+    ///
+    pub fn finalize(self) -> (W, Output<D>) {
+        (self.inner, self.hasher.finalize())
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// Data written directly to the underlying writer is not hashed.
+    ///
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    ///
+    /// Gets a reference to the underlying writer.
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+}
+
+impl<W: Write, D: Digest> Write for HashingWriter<W, D> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hasher.update(&buf[..len]);
+        self.len += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
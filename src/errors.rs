@@ -46,7 +46,10 @@ pub mod tag_iterator {
     use super::fmt;
     use super::Error;
     use super::tool::ToolError;
+    use super::profile::ProfileViolation;
     use std::io;
+    use std::ops::Range;
+    use std::time::Duration;
 
     ///
     /// Errors that indicate file data is corrupted.
@@ -141,6 +144,198 @@ pub mod tag_iterator {
             /// 
             size: usize 
         },
+
+        ///
+        /// An error indicating the reader found a tag id that is longer than the document's `EBMLMaxIDLength` (4 bytes unless the EBML header declares otherwise).
+        ///
+        /// Ids defined by the specification are accepted regardless of their length.
+        ///
+        OversizedTagId {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the tag that was found.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The length of the tag id, in bytes.
+            ///
+            length: usize,
+        },
+
+        ///
+        /// An error indicating the reader found an unsigned integer tag with a value the specification doesn't allow.
+        ///
+        /// Only reported if enabled through [`TagIterator::validate_restricted_values()`][`crate::TagIterator::validate_restricted_values`].
+        ///
+        RestrictedValue {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the tag that was found.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The value of the tag that was found.
+            ///
+            value: u64,
+        },
+
+        ///
+        /// An error indicating the reader found an element that breaks the restrictions of the configured [`Profile`][`crate::Profile`].
+        ///
+        ProfileViolation {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the tag that was found.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The restriction that was broken.
+            ///
+            violation: ProfileViolation,
+        },
+
+        ///
+        /// An error indicating the reader found a number or string element with no data.
+        ///
+        /// Only reported if enabled through [`TagIterator::set_zero_length_values()`][`crate::TagIterator::set_zero_length_values`].
+        ///
+        ZeroLengthValue {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the tag that was found.
+            ///
+            tag_id: u64,
+        },
+
+        ///
+        /// An error indicating the reader found data after the last top-level element of the document.
+        ///
+        /// Only reported if enabled through [`TagIterator::set_trailing_data()`][`crate::TagIterator::set_trailing_data`].
+        ///
+        TrailingData {
+
+            ///
+            /// The position of the trailing data.
+            ///
+            position: usize,
+        },
+
+        ///
+        /// An error indicating the reader found an element whose size puts its end past the end of the source.  Only reported if enabled through [`TagIterator::set_size_past_end()`](crate::TagIterator::set_size_past_end).
+        ///
+        SizePastEnd {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the tag that was found.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The size of the element's data, as read from the file.
+            ///
+            size: usize,
+
+            ///
+            /// The number of bytes left in the source after the element's header.
+            ///
+            available: usize,
+        },
+
+        ///
+        /// An error indicating the reader found an element whose size puts its end past the largest offset that can be represented on this platform.
+        ///
+        SizeOverflow {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the tag that was found.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The size of the element's data, as read from the file.
+            ///
+            size: u64,
+        },
+
+        ///
+        /// An error indicating the data of a "Master" element doesn't match the checksum in its `CRC-32` child.  Only checked if enabled through [`TagIterator::set_crc_mismatch()`][`crate::TagIterator::set_crc_mismatch`].
+        ///
+        CrcMismatch {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The checksum stored in the `CRC-32` child.
+            ///
+            expected: u32,
+
+            ///
+            /// The checksum of the element's data.
+            ///
+            computed: u32,
+        },
+
+        ///
+        /// An error indicating a "Master" element would be opened inside too many others.  The limit is set through [`TagIterator::set_max_nesting_depth()`][`crate::TagIterator::set_max_nesting_depth`].
+        ///
+        NestingTooDeep {
+
+            ///
+            /// The position of the element.
+            ///
+            position: usize,
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The maximum number of "Master" elements that can be open at once.
+            ///
+            max_depth: usize,
+        },
     }
 
     impl fmt::Display for CorruptedFileError {
@@ -168,10 +363,138 @@ pub mod tag_iterator {
                     tag_id, 
                     size,
                 } => write!(f, "Found an oversized tag [0x{tag_id:x?}] at position {position} with size {size}.  Max supported size is 8GB."),
+                CorruptedFileError::OversizedTagId {
+                    position,
+                    tag_id,
+                    length,
+                } => write!(f, "Found tag id [0x{tag_id:x?}] at position {position} that is {length} bytes long, which is longer than the document allows"),
+                CorruptedFileError::RestrictedValue {
+                    position,
+                    tag_id,
+                    value,
+                } => write!(f, "Found value {value} for tag [0x{tag_id:x?}] at position {position}, which is not one of the values allowed by the specification"),
+                CorruptedFileError::ProfileViolation {
+                    position,
+                    tag_id,
+                    violation,
+                } => write!(f, "Found tag [0x{tag_id:x?}] at position {position} that breaks the profile: {violation}"),
+                CorruptedFileError::ZeroLengthValue {
+                    position,
+                    tag_id,
+                } => write!(f, "Found tag [0x{tag_id:x?}] at position {position} with no data"),
+                CorruptedFileError::TrailingData {
+                    position,
+                } => write!(f, "Found data after the end of the document at position {position}"),
+                CorruptedFileError::SizePastEnd {
+                    position,
+                    tag_id,
+                    size,
+                    available,
+                } => write!(f, "Found tag [0x{tag_id:x?}] at position {position} with size {size}, but only {available} bytes remain in the source"),
+                CorruptedFileError::SizeOverflow {
+                    position,
+                    tag_id,
+                    size,
+                } => write!(f, "Found tag [0x{tag_id:x?}] at position {position} with size {size}, which ends past the largest supported offset"),
+                CorruptedFileError::CrcMismatch {
+                    position,
+                    tag_id,
+                    expected,
+                    computed,
+                } => write!(f, "Data of tag [0x{tag_id:x?}] at position {position} has checksum 0x{computed:08x}, but its CRC-32 element holds 0x{expected:08x}"),
+                CorruptedFileError::NestingTooDeep {
+                    position,
+                    tag_id,
+                    max_depth,
+                } => write!(f, "Found tag [0x{tag_id:x?}] at position {position} nested inside {max_depth} or more other tags"),
             }
         }
     }
 
+    ///
+    /// What is known about a tag that was cut off by the end of the input stream.
+    ///
+    /// If the tag's header was read and its data was kept, more data can be added to it with [`Self::resume()`] once the source has grown, rather than reading the tag again from its start.
+    ///
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct PartialTag {
+
+        ///
+        /// The start position of the tag in the source.
+        ///
+        pub tag_start: usize,
+
+        ///
+        /// The id of the tag, if it was read.
+        ///
+        pub id: Option<u64>,
+
+        ///
+        /// The data size declared in the tag's header, if it was read.
+        ///
+        pub size: Option<usize>,
+
+        ///
+        /// The length of the tag's header, if all of it was read.
+        ///
+        pub header_len: Option<usize>,
+
+        ///
+        /// The number of bytes of the tag's data that were read before EOF.
+        ///
+        pub obtained: usize,
+
+        ///
+        /// The data that was read before EOF, if it was kept.  Data that was streamed to the caller (e.g. through an [`ElementReader`][`crate::ElementReader`]) isn't included.
+        ///
+        pub data: Option<Vec<u8>>,
+    }
+
+    impl PartialTag {
+        pub(crate) fn in_header(tag_start: usize, id: Option<u64>) -> Self {
+            PartialTag { tag_start, id, size: None, header_len: None, obtained: 0, data: None }
+        }
+
+        ///
+        /// Returns the range of the source occupied by the tag's header, if all of it was read.
+        ///
+        pub fn header_range(&self) -> Option<Range<usize>> {
+            self.header_len.map(|len| self.tag_start..(self.tag_start + len))
+        }
+
+        ///
+        /// Returns the number of bytes of data still needed to complete the tag, if its size is known.
+        ///
+        pub fn missing(&self) -> Option<usize> {
+            self.size.map(|size| size.saturating_sub(self.obtained))
+        }
+
+        ///
+        /// Returns `true` if [`Self::resume()`] can complete this tag, meaning its header was read and all the data obtained so far was kept.
+        ///
+        pub fn is_resumable(&self) -> bool {
+            self.header_len.is_some() && self.size.is_some() && self.data.is_some()
+        }
+
+        ///
+        /// Appends data read from `source`, which should continue right where the original source ended, up to the end of the tag.  Returns `true` once all of the tag's data has been obtained.
+        ///
+        /// ## Errors
+        ///
+        /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if the tag isn't [resumable](Self::is_resumable), or any error returned by `source`.
+        ///
+        pub fn resume<R: io::Read>(&mut self, source: R) -> io::Result<bool> {
+            let missing = match (self.is_resumable(), self.missing()) {
+                (true, Some(missing)) => missing,
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "tag can't be resumed without its header and data")),
+            };
+            let data = self.data.as_mut().expect("resumable tag should have data");
+            let read = io::Read::read_to_end(&mut source.take(missing as u64), data)?;
+            self.obtained += read;
+            Ok(read == missing)
+        }
+    }
+
     ///
     /// Errors that can occur when reading ebml data.
     ///
@@ -186,30 +509,9 @@ pub mod tag_iterator {
         ///
         /// An error indicating that the iterator reached the end of the input stream unexpectedly while reading a tag.
         /// 
-        /// This error will occur if the iterator is expecting more data (either due to expecting a size after reading a tag id or based on a tag size) but nothing is available in the input stream.
+        /// This error will occur if the iterator is expecting more data (either due to expecting a size after reading a tag id or based on a tag size) but nothing is available in the input stream.  The [`PartialTag`] describes what was read of the tag.
         /// 
-        UnexpectedEOF {
-
-            ///
-            /// The start position of the tag that was being read when EOF was reached.
-            /// 
-            tag_start: usize,
-
-            ///
-            /// The id of the partially read tag, if available.
-            /// 
-            tag_id: Option<u64>,
-
-            ///
-            /// The size of the partially read tag, if available.
-            /// 
-            tag_size: Option<usize>,
-
-            ///
-            /// Any available data that was read for the tag before reaching EOF.
-            /// 
-            partial_data: Option<Vec<u8>>,
-        },
+        UnexpectedEOF(PartialTag),
 
         ///
         /// An error indicating that tag data appears to be corrupted.
@@ -239,23 +541,93 @@ pub mod tag_iterator {
             ///
             source: io::Error,
         },
+
+        ///
+        /// An error indicating that a [`ContentTransform`][`crate::ContentTransform`] failed to decode the data of a tag.
+        ///
+        TransformError {
+
+            ///
+            /// The id of the tag being decoded.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The error returned by the transform.
+            ///
+            source: Box<dyn Error + Send + Sync>,
+        },
+
+        ///
+        /// An error indicating that the source ran out of data while unknown-size "Master" tags were open, and the predicate set by [`TagIterator::set_eof_predicate()`][`crate::TagIterator::set_eof_predicate`] reported that the stream isn't complete.
+        ///
+        /// This isn't a problem with the data: the iterator continues from the same position once more data is available.
+        ///
+        NeedMoreData {
+
+            ///
+            /// The number of bytes read from the source so far.
+            ///
+            position: usize,
+        },
+
+        ///
+        /// An error indicating that the callback set by [`TagIterator::on_large_allocation()`][`crate::TagIterator::on_large_allocation`] refused to grow the read buffer for an element.
+        ///
+        /// The element's data is skipped, so iteration can continue with the following tag.
+        ///
+        AllocationDenied {
+
+            ///
+            /// The start position of the element.
+            ///
+            tag_start: usize,
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The size of the element's data.
+            ///
+            size: usize,
+        },
+
+        ///
+        /// An error indicating that the source didn't return any data within the configured read timeout (see [`TagIterator::with_read_timeout()`][`crate::TagIterator::with_read_timeout`] or `TagIteratorAsync::set_read_timeout()`).
+        ///
+        /// The read is still pending, so the iterator can be called again to keep waiting for the data.  Sources that report their own timeouts with [`io::ErrorKind::TimedOut`] produce this error as well.
+        ///
+        Timeout {
+
+            ///
+            /// The number of bytes read from the source so far.
+            ///
+            position: usize,
+
+            ///
+            /// The timeout that elapsed, if it was configured on the iterator.
+            ///
+            timeout: Option<Duration>,
+        },
     }
     
     impl fmt::Display for TagIteratorError {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             match self {
                 TagIteratorError::CorruptedFileData(err) => write!(f, "Encountered corrupted data.  Message: {err}"),
-                TagIteratorError::UnexpectedEOF { 
-                    tag_start, 
-                    tag_id, 
-                    tag_size, 
-                    partial_data: _ 
-                } => write!(f, "Reached EOF unexpectedly. Partial tag data: {{tag offset:{tag_start}}} {{id:{tag_id:x?}}} {{size:{tag_size:?}}}"),
+                TagIteratorError::UnexpectedEOF(partial) => write!(f, "Reached EOF unexpectedly. Partial tag data: {{tag offset:{}}} {{id:{:x?}}} {{size:{:?}}}", partial.tag_start, partial.id, partial.size),
                 TagIteratorError::CorruptedTagData {
                     tag_id,
                     problem,
                 } => write!(f, "Error reading data for tag id (0x{tag_id:x?}). {problem}"),
                 TagIteratorError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                TagIteratorError::TransformError { tag_id, source: _ } => write!(f, "Error decoding data for tag id (0x{tag_id:x?})."),
+                TagIteratorError::NeedMoreData { position } => write!(f, "Reached the end of the available data at offset {position} before the stream was complete."),
+                TagIteratorError::AllocationDenied { tag_start, tag_id, size } => write!(f, "Allocation of {size} bytes for tag [0x{tag_id:x?}] at position {tag_start} was denied."),
+                TagIteratorError::Timeout { position, timeout: Some(timeout) } => write!(f, "Source didn't return any data within {timeout:?} at offset {position}."),
+                TagIteratorError::Timeout { position, timeout: None } => write!(f, "Reading from source timed out at offset {position}."),
             }
         }
     }
@@ -264,9 +636,13 @@ pub mod tag_iterator {
         fn source(&self) -> Option<&(dyn Error + 'static)> {
             match self {
                 TagIteratorError::CorruptedFileData(_) => None,
-                TagIteratorError::UnexpectedEOF { tag_start: _, tag_id: _, tag_size: _, partial_data: _ } => None,
+                TagIteratorError::UnexpectedEOF(_) => None,
                 TagIteratorError::CorruptedTagData { tag_id: _, problem } => problem.source(),
                 TagIteratorError::ReadError { source } => Some(source),
+                TagIteratorError::TransformError { tag_id: _, source } => Some(source.as_ref()),
+                TagIteratorError::NeedMoreData { position: _ } => None,
+                TagIteratorError::AllocationDenied { tag_start: _, tag_id: _, size: _ } => None,
+                TagIteratorError::Timeout { position: _, timeout: _ } => None,
             }
         }
     }
@@ -275,6 +651,7 @@ pub mod tag_iterator {
 pub mod tag_writer {
     use super::fmt;
     use super::Error;
+    use super::profile::ProfileViolation;
     use std::io;
 
     ///
@@ -333,7 +710,134 @@ pub mod tag_writer {
         WriteError {
             source: io::Error,
         },
-    }
+
+        ///
+        /// An error indicating that a [`ContentTransform`][`crate::ContentTransform`] failed to encode the data of a tag.
+        ///
+        TransformError {
+
+            ///
+            /// The id of the tag being encoded.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The error returned by the transform.
+            ///
+            source: Box<dyn Error + Send + Sync>,
+        },
+
+        ///
+        /// An error indicating that a [`WriteInterceptor`][`crate::WriteInterceptor`] rejected a tag.
+        ///
+        InterceptorError {
+
+            ///
+            /// The id of the tag being intercepted.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The error returned by the interceptor.
+            ///
+            source: Box<dyn Error + Send + Sync>,
+        },
+
+        ///
+        /// An error indicating an unsigned integer tag has a value the specification doesn't allow.
+        ///
+        /// Only reported if enabled through [`TagWriter::validate_restricted_values()`][`crate::TagWriter::validate_restricted_values`].
+        ///
+        RestrictedValue {
+
+            ///
+            /// The id of the tag being written.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The value that was rejected.
+            ///
+            value: u64,
+        },
+
+        ///
+        /// An error indicating a tag breaks the restrictions of the [`Profile`][`crate::Profile`] set on the writer.
+        ///
+        ProfileViolation {
+
+            ///
+            /// The id of the tag being written.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The restriction that was broken.
+            ///
+            violation: ProfileViolation,
+        },
+
+        ///
+        /// An error indicating no bookmark with the given name was reserved using [`TagWriter::reserve_bookmark()`][`crate::TagWriter::reserve_bookmark`].
+        ///
+        UnknownBookmark {
+
+            ///
+            /// The name of the bookmark.
+            ///
+            name: String,
+        },
+
+        ///
+        /// An error indicating a tag doesn't fit in the space reserved for a bookmark.
+        ///
+        /// This also occurs if the tag would leave exactly one byte of the reserved space unused, since that is too small to hold a `Void` element.
+        ///
+        BookmarkTooSmall {
+
+            ///
+            /// The name of the bookmark.
+            ///
+            name: String,
+
+            ///
+            /// The number of bytes reserved for the bookmark.
+            ///
+            reserved: usize,
+
+            ///
+            /// The encoded length of the tag.
+            ///
+            required: usize,
+        },
+
+        ///
+        /// An error indicating the entries of a [`SeekIndex`](crate::SeekIndex) couldn't be determined, because the space reserved for the index (or the "Master" element containing it) hasn't been written to the destination yet, or because the writer's audit log wasn't recording it.
+        ///
+        IndexUnavailable {
+
+            ///
+            /// The name of the bookmark reserved for the index.
+            ///
+            name: String,
+        },
+
+        ///
+        /// An error indicating the bytes passed to [`TagWriter::write_preencoded()`][`crate::TagWriter::write_preencoded`] aren't a single valid element header, or declare a size that doesn't match the payload.
+        ///
+        InvalidPreencodedHeader {
+
+            ///
+            /// The header that was rejected.
+            ///
+            header: Vec<u8>,
+
+            ///
+            /// The length of the payload passed with the header.
+            ///
+            payload_len: usize,
+        },
+    }
 
     impl fmt::Display for TagWriterError {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -346,6 +850,14 @@ pub mod tag_writer {
                     None => write!(f, "Unexpected closing tag 0x'{tag_id:x?}'"),
                 },
                 TagWriterError::WriteError { source: _ } => write!(f, "Error writing to destination."),
+                TagWriterError::TransformError { tag_id, source: _ } => write!(f, "Error encoding data for tag id (0x{tag_id:x?})."),
+                TagWriterError::InterceptorError { tag_id, source: _ } => write!(f, "Interceptor failed on tag id (0x{tag_id:x?})."),
+                TagWriterError::RestrictedValue { tag_id, value } => write!(f, "Value {value} is not allowed for tag id (0x{tag_id:x?})."),
+                TagWriterError::ProfileViolation { tag_id, violation } => write!(f, "Tag id (0x{tag_id:x?}) breaks the profile. {violation}"),
+                TagWriterError::UnknownBookmark { name } => write!(f, "No bookmark named '{name}' has been reserved."),
+                TagWriterError::BookmarkTooSmall { name, reserved, required } => write!(f, "Tag of {required} bytes does not fit in the {reserved} bytes reserved for bookmark '{name}'."),
+                TagWriterError::IndexUnavailable { name } => write!(f, "Offsets for the index at bookmark '{name}' are not available yet."),
+                TagWriterError::InvalidPreencodedHeader { header, payload_len } => write!(f, "Header {header:x?} is not a valid element header for a payload of {payload_len} bytes."),
             }
         }
     }
@@ -358,7 +870,1409 @@ pub mod tag_writer {
                 TagWriterError::TagSizeError(_) => None,
                 TagWriterError::UnexpectedClosingTag { tag_id: _, expected_id: _ } => None,
                 TagWriterError::WriteError { source } => Some(source),
+                TagWriterError::TransformError { tag_id: _, source } => Some(source.as_ref()),
+                TagWriterError::InterceptorError { tag_id: _, source } => Some(source.as_ref()),
+                TagWriterError::RestrictedValue { tag_id: _, value: _ } => None,
+                TagWriterError::ProfileViolation { tag_id: _, violation } => Some(violation),
+                TagWriterError::UnknownBookmark { name: _ } => None,
+                TagWriterError::BookmarkTooSmall { name: _, reserved: _, required: _ } => None,
+                TagWriterError::IndexUnavailable { name: _ } => None,
+                TagWriterError::InvalidPreencodedHeader { header: _, payload_len: _ } => None,
+            }
+        }
+    }
+}
+pub mod profile {
+    use super::fmt;
+    use super::Error;
+
+    ///
+    /// Ways an element can break the restrictions of a [`Profile`][`crate::Profile`].
+    ///
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ProfileViolation {
+
+        ///
+        /// The element is not part of the profile.
+        ///
+        ElementNotAllowed,
+
+        ///
+        /// The element id is longer than the profile allows.
+        ///
+        IdTooLong {
+
+            ///
+            /// The length of the element id, in bytes.
+            ///
+            length: usize,
+        },
+
+        ///
+        /// The element has an unknown size, which the profile doesn't allow for it.
+        ///
+        UnknownSizeNotAllowed,
+    }
+
+    impl fmt::Display for ProfileViolation {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ProfileViolation::ElementNotAllowed => write!(f, "Element is not allowed by the profile"),
+                ProfileViolation::IdTooLong { length } => write!(f, "Element id length ({length} bytes) is longer than the profile allows"),
+                ProfileViolation::UnknownSizeNotAllowed => write!(f, "Element cannot have an unknown size in the profile"),
+            }
+        }
+    }
+
+    impl Error for ProfileViolation {}
+}
+
+pub mod ebml_reader {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when navigating a document with an [`EbmlReader`][`crate::EbmlReader`] (or its async counterpart, `EbmlReaderAsync`).
+    ///
+    #[derive(Debug)]
+    pub enum EbmlReaderError {
+
+        ///
+        /// An error indicating a document path could not be resolved using the current specification.
+        ///
+        InvalidPath(String),
+
+        ///
+        /// An error indicating no element exists in the document at the requested path.
+        ///
+        ElementNotFound(String),
+
+        ///
+        /// An error indicating no element could be found that ends at the given offset while reading elements in reverse.
+        ///
+        UnrecognizedData(usize),
+
+        ///
+        /// An error that wraps a problem reading or parsing the underlying source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+    }
+
+    impl fmt::Display for EbmlReaderError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                EbmlReaderError::InvalidPath(path) => write!(f, "Could not resolve path \"{path}\" using the current specification"),
+                EbmlReaderError::ElementNotFound(path) => write!(f, "No element found at path \"{path}\""),
+                EbmlReaderError::UnrecognizedData(end) => write!(f, "No element found ending at offset {end}"),
+                EbmlReaderError::ReadError { source: _ } => write!(f, "Error reading from source."),
+            }
+        }
+    }
+
+    impl Error for EbmlReaderError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                EbmlReaderError::InvalidPath(_) => None,
+                EbmlReaderError::ElementNotFound(_) => None,
+                EbmlReaderError::UnrecognizedData(_) => None,
+                EbmlReaderError::ReadError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for EbmlReaderError {
+        fn from(source: TagIteratorError) -> Self {
+            EbmlReaderError::ReadError { source }
+        }
+    }
+}
+
+pub mod ebml_editor {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+    use super::tag_writer::TagWriterError;
+
+    ///
+    /// Errors that can occur when modifying a document with an [`EbmlEditor`][`crate::EbmlEditor`].
+    ///
+    #[derive(Debug)]
+    pub enum EbmlEditorError {
+
+        ///
+        /// An error indicating a document path could not be resolved using the current specification.
+        ///
+        InvalidPath(String),
+
+        ///
+        /// An error indicating no element exists in the document at the requested path.
+        ///
+        ElementNotFound(String),
+
+        ///
+        /// An error indicating the replacement tag has a different id than the element at the requested path.
+        ///
+        TagIdMismatch {
+            element_id: u64,
+            tag_id: u64,
+        },
+
+        ///
+        /// An error indicating the replacement tag is a [`Master::Start`][`crate::specs::Master::Start`] or [`Master::End`][`crate::specs::Master::End`] marker.  Master elements can only be replaced with [`Master::Full`][`crate::specs::Master::Full`] tags.
+        ///
+        IncompleteMaster(u64),
+
+        ///
+        /// An error indicating the element to replace has an unknown size.  Only elements of known size can be edited in place.
+        ///
+        UnknownSize(u64),
+
+        ///
+        /// An error indicating the encoded replacement doesn't fit in the space occupied by the existing element.
+        ///
+        InsufficientSpace {
+            required: usize,
+            available: usize,
+        },
+
+        ///
+        /// An error that wraps a problem encoding the replacement tag.
+        ///
+        EncodeError {
+
+            ///
+            /// The [`TagWriterError`] that caused this problem.
+            ///
+            source: TagWriterError,
+        },
+
+        ///
+        /// An error that wraps a problem reading or parsing the underlying source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to the underlying source.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+    }
+
+    impl fmt::Display for EbmlEditorError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                EbmlEditorError::InvalidPath(path) => write!(f, "Could not resolve path \"{path}\" using the current specification"),
+                EbmlEditorError::ElementNotFound(path) => write!(f, "No element found at path \"{path}\""),
+                EbmlEditorError::TagIdMismatch { element_id, tag_id } => write!(f, "Cannot replace element with id {element_id} using tag with id {tag_id}"),
+                EbmlEditorError::IncompleteMaster(id) => write!(f, "Master tag with id {id} must be a Full variant to be written in place"),
+                EbmlEditorError::UnknownSize(id) => write!(f, "Cannot edit element with id {id} because it has an unknown size"),
+                EbmlEditorError::InsufficientSpace { required, available } => write!(f, "Replacement requires {required} bytes, but only {available} are available"),
+                EbmlEditorError::EncodeError { source: _ } => write!(f, "Error encoding replacement tag."),
+                EbmlEditorError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                EbmlEditorError::WriteError { source: _ } => write!(f, "Error writing to source."),
+            }
+        }
+    }
+
+    impl Error for EbmlEditorError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                EbmlEditorError::EncodeError { source } => Some(source),
+                EbmlEditorError::ReadError { source } => Some(source),
+                EbmlEditorError::WriteError { source } => Some(source),
+                _ => None,
             }
         }
     }
-}
\ No newline at end of file
+
+    impl From<TagIteratorError> for EbmlEditorError {
+        fn from(source: TagIteratorError) -> Self {
+            EbmlEditorError::ReadError { source }
+        }
+    }
+
+    impl From<TagWriterError> for EbmlEditorError {
+        fn from(source: TagWriterError) -> Self {
+            EbmlEditorError::EncodeError { source }
+        }
+    }
+}
+
+pub mod extract {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when extracting element data with [`extract()`][`crate::utils::extract`].
+    ///
+    #[derive(Debug)]
+    pub enum ExtractError {
+
+        ///
+        /// An error indicating a document path could not be resolved using the current specification.
+        ///
+        InvalidPath(String),
+
+        ///
+        /// An error indicating a matching element has an unknown size, so its data can't be extracted as a single payload.
+        ///
+        UnknownSize {
+            tag_id: u64,
+            position: usize,
+        },
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to the sink.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+    }
+
+    impl fmt::Display for ExtractError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ExtractError::InvalidPath(path) => write!(f, "Could not resolve path \"{path}\" using the current specification"),
+                ExtractError::UnknownSize { tag_id, position } => write!(f, "Cannot extract data of unknown sized element with id {tag_id} at position {position}"),
+                ExtractError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                ExtractError::WriteError { source: _ } => write!(f, "Error writing to sink."),
+            }
+        }
+    }
+
+    impl Error for ExtractError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                ExtractError::ReadError { source } => Some(source),
+                ExtractError::WriteError { source } => Some(source),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for ExtractError {
+        fn from(source: TagIteratorError) -> Self {
+            ExtractError::ReadError { source }
+        }
+    }
+}
+
+pub mod redact {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when redacting a document with [`redact()`][`crate::utils::redact`] or [`redact_drop()`][`crate::utils::redact_drop`].
+    ///
+    #[derive(Debug)]
+    pub enum RedactError {
+
+        ///
+        /// An error indicating an element to redact has an unknown size.
+        ///
+        UnknownSize {
+            tag_id: u64,
+            position: usize,
+        },
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to the destination.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+    }
+
+    impl fmt::Display for RedactError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RedactError::UnknownSize { tag_id, position } => write!(f, "Cannot redact unknown sized element with id {tag_id} at position {position}"),
+                RedactError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                RedactError::WriteError { source: _ } => write!(f, "Error writing to destination."),
+            }
+        }
+    }
+
+    impl Error for RedactError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                RedactError::UnknownSize { tag_id: _, position: _ } => None,
+                RedactError::ReadError { source } => Some(source),
+                RedactError::WriteError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for RedactError {
+        fn from(source: TagIteratorError) -> Self {
+            RedactError::ReadError { source }
+        }
+    }
+}
+
+pub mod compact {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when compacting `Void` elements with [`compact_voids()`][`crate::utils::compact_voids`] or [`compact_voids_in_place()`][`crate::utils::compact_voids_in_place`].
+    ///
+    #[derive(Debug)]
+    pub enum CompactError {
+
+        ///
+        /// An error indicating a `Void` element has an unknown size, so its end can't be found.
+        ///
+        UnknownSizeVoid {
+
+            ///
+            /// The start position of the element.
+            ///
+            position: usize,
+        },
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to the destination.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+    }
+
+    impl fmt::Display for CompactError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                CompactError::UnknownSizeVoid { position } => write!(f, "Void element at position {position} has an unknown size"),
+                CompactError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                CompactError::WriteError { source: _ } => write!(f, "Error writing to destination."),
+            }
+        }
+    }
+
+    impl Error for CompactError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                CompactError::UnknownSizeVoid { position: _ } => None,
+                CompactError::ReadError { source } => Some(source),
+                CompactError::WriteError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for CompactError {
+        fn from(source: TagIteratorError) -> Self {
+            CompactError::ReadError { source }
+        }
+    }
+}
+
+pub mod splitter {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when splitting a document with a [`Splitter`][`crate::utils::Splitter`].
+    ///
+    #[derive(Debug)]
+    pub enum SplitterError {
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when creating or writing to an output.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+    }
+
+    impl fmt::Display for SplitterError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                SplitterError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                SplitterError::WriteError { source: _ } => write!(f, "Error writing to output."),
+            }
+        }
+    }
+
+    impl Error for SplitterError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                SplitterError::ReadError { source } => Some(source),
+                SplitterError::WriteError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for SplitterError {
+        fn from(source: TagIteratorError) -> Self {
+            SplitterError::ReadError { source }
+        }
+    }
+}
+
+pub mod join {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when joining documents with [`join()`][`crate::utils::join`].
+    ///
+    #[derive(Debug)]
+    pub enum JoinError {
+
+        ///
+        /// An error that wraps a problem reading or parsing one of the inputs.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to the output.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+    }
+
+    impl fmt::Display for JoinError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                JoinError::ReadError { source: _ } => write!(f, "Error reading from input."),
+                JoinError::WriteError { source: _ } => write!(f, "Error writing to output."),
+            }
+        }
+    }
+
+    impl Error for JoinError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                JoinError::ReadError { source } => Some(source),
+                JoinError::WriteError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for JoinError {
+        fn from(source: TagIteratorError) -> Self {
+            JoinError::ReadError { source }
+        }
+    }
+}
+
+pub mod patch {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+    use super::tag_writer::TagWriterError;
+
+    ///
+    /// Errors that can occur when encoding, decoding, or applying a [`Patch`][`crate::utils::Patch`].
+    ///
+    #[derive(Debug)]
+    pub enum PatchError {
+
+        ///
+        /// An error that wraps a problem reading or parsing the source document (or a tag embedded in patch data).
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps a problem writing the output (or encoding a tag in a patch).
+        ///
+        WriteError {
+
+            ///
+            /// The [`TagWriterError`] that caused this problem.
+            ///
+            source: TagWriterError,
+        },
+
+        ///
+        /// An error indicating that patch data could not be decoded.
+        ///
+        InvalidPatchData {
+
+            ///
+            /// The position in the patch data where the problem was found.
+            ///
+            position: usize,
+        },
+    }
+
+    impl fmt::Display for PatchError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                PatchError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                PatchError::WriteError { source: _ } => write!(f, "Error writing to output."),
+                PatchError::InvalidPatchData { position } => write!(f, "Invalid patch data at position {position}."),
+            }
+        }
+    }
+
+    impl Error for PatchError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                PatchError::ReadError { source } => Some(source),
+                PatchError::WriteError { source } => Some(source),
+                PatchError::InvalidPatchData { position: _ } => None,
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for PatchError {
+        fn from(source: TagIteratorError) -> Self {
+            PatchError::ReadError { source }
+        }
+    }
+
+    impl From<TagWriterError> for PatchError {
+        fn from(source: TagWriterError) -> Self {
+            PatchError::WriteError { source }
+        }
+    }
+}
+
+#[cfg(feature = "dom")]
+pub mod ebml_document {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+    use super::tag_writer::TagWriterError;
+
+    ///
+    /// Errors that can occur when reading, querying, or writing an [`EbmlDocument`][`crate::EbmlDocument`].
+    ///
+    #[derive(Debug)]
+    pub enum EbmlDocumentError {
+
+        ///
+        /// An error indicating that a query could not be parsed.  Contains the query.
+        ///
+        InvalidQuery(String),
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps a problem writing the document.
+        ///
+        WriteError {
+
+            ///
+            /// The [`TagWriterError`] that caused this problem.
+            ///
+            source: TagWriterError,
+        },
+    }
+
+    impl fmt::Display for EbmlDocumentError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                EbmlDocumentError::InvalidQuery(query) => write!(f, "Invalid query: {query}"),
+                EbmlDocumentError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                EbmlDocumentError::WriteError { source: _ } => write!(f, "Error writing document."),
+            }
+        }
+    }
+
+    impl Error for EbmlDocumentError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                EbmlDocumentError::InvalidQuery(_) => None,
+                EbmlDocumentError::ReadError { source } => Some(source),
+                EbmlDocumentError::WriteError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for EbmlDocumentError {
+        fn from(source: TagIteratorError) -> Self {
+            EbmlDocumentError::ReadError { source }
+        }
+    }
+
+    impl From<TagWriterError> for EbmlDocumentError {
+        fn from(source: TagWriterError) -> Self {
+            EbmlDocumentError::WriteError { source }
+        }
+    }
+}
+
+pub mod streaming_copier {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when copying a document with a [`StreamingCopier`][`crate::utils::StreamingCopier`].
+    ///
+    #[derive(Debug)]
+    pub enum StreamingCopierError {
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to the destination.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+    }
+
+    impl fmt::Display for StreamingCopierError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                StreamingCopierError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                StreamingCopierError::WriteError { source: _ } => write!(f, "Error writing to destination."),
+            }
+        }
+    }
+
+    impl Error for StreamingCopierError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                StreamingCopierError::ReadError { source } => Some(source),
+                StreamingCopierError::WriteError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for StreamingCopierError {
+        fn from(source: TagIteratorError) -> Self {
+            StreamingCopierError::ReadError { source }
+        }
+    }
+}
+
+pub mod finalize {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when giving a document exact sizes with [`finalize_sizes()`][`crate::utils::finalize_sizes`] or [`finalize_sizes_two_pass()`][`crate::utils::finalize_sizes_two_pass`].
+    ///
+    #[derive(Debug)]
+    pub enum FinalizeError {
+
+        ///
+        /// An error that wraps a problem reading the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to (or seeking in) the destination.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+
+        ///
+        /// An element is too large for its size to be written.
+        ///
+        ElementTooLarge {
+
+            ///
+            /// The id of the element.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The size of the element's data.
+            ///
+            size: u64,
+        },
+
+        ///
+        /// The source held different elements when it was read the second time.
+        ///
+        SourceChanged,
+    }
+
+    impl fmt::Display for FinalizeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                FinalizeError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                FinalizeError::WriteError { source: _ } => write!(f, "Error writing to destination."),
+                FinalizeError::ElementTooLarge { tag_id, size } => write!(f, "Element 0x{tag_id:x?} is too large to write its size ({size} bytes)"),
+                FinalizeError::SourceChanged => write!(f, "Source changed between reads"),
+            }
+        }
+    }
+
+    impl Error for FinalizeError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                FinalizeError::ReadError { source } => Some(source),
+                FinalizeError::WriteError { source } => Some(source),
+                FinalizeError::ElementTooLarge { tag_id: _, size: _ } => None,
+                FinalizeError::SourceChanged => None,
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for FinalizeError {
+        fn from(source: TagIteratorError) -> Self {
+            FinalizeError::ReadError { source }
+        }
+    }
+}
+
+pub mod passthrough {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+    use super::tag_writer::TagWriterError;
+
+    ///
+    /// Errors that can occur when copying a document with [`passthrough()`][`crate::utils::passthrough`].
+    ///
+    #[derive(Debug)]
+    pub enum PassthroughError {
+
+        ///
+        /// An error that wraps a problem reading the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps a problem writing to the destination, including invalid replacement tags.
+        ///
+        WriteError {
+
+            ///
+            /// The [`TagWriterError`] that caused this problem.
+            ///
+            source: TagWriterError,
+        },
+    }
+
+    impl fmt::Display for PassthroughError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                PassthroughError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                PassthroughError::WriteError { source: _ } => write!(f, "Error writing to destination."),
+            }
+        }
+    }
+
+    impl Error for PassthroughError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                PassthroughError::ReadError { source } => Some(source),
+                PassthroughError::WriteError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for PassthroughError {
+        fn from(source: TagIteratorError) -> Self {
+            PassthroughError::ReadError { source }
+        }
+    }
+
+    impl From<TagWriterError> for PassthroughError {
+        fn from(source: TagWriterError) -> Self {
+            PassthroughError::WriteError { source }
+        }
+    }
+}
+
+pub mod migrate {
+    use super::fmt;
+    use super::Error;
+    use crate::specs::TagDataType;
+
+    ///
+    /// Errors that can occur when converting tags between specifications with [`migrate()`][`crate::utils::migrate`].
+    ///
+    #[derive(Debug)]
+    pub enum MigrateError {
+
+        ///
+        /// The tag id is known to the source specification but not to the target specification.
+        ///
+        UnknownTag {
+
+            ///
+            /// The id of the tag.
+            ///
+            id: u64,
+        },
+
+        ///
+        /// The two specifications disagree on the data type of the tag.
+        ///
+        TypeMismatch {
+
+            ///
+            /// The id of the tag.
+            ///
+            id: u64,
+
+            ///
+            /// The data type of the tag in the source specification.
+            ///
+            from: TagDataType,
+
+            ///
+            /// The data type of the tag in the target specification.
+            ///
+            to: TagDataType,
+        },
+
+        ///
+        /// The data of the tag can't be represented as its data type in the target specification, such as raw data of the wrong length or text that is not valid UTF-8.
+        ///
+        InvalidData {
+
+            ///
+            /// The id of the tag.
+            ///
+            id: u64,
+
+            ///
+            /// The data type of the tag in the target specification.
+            ///
+            expected: TagDataType,
+        },
+    }
+
+    impl fmt::Display for MigrateError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                MigrateError::UnknownTag { id } => write!(f, "Tag id 0x{id:x} is not in the target specification."),
+                MigrateError::TypeMismatch { id, from, to } => write!(f, "Tag id 0x{id:x} is {from:?} data in the source specification but {to:?} data in the target specification."),
+                MigrateError::InvalidData { id, expected } => write!(f, "Data of tag id 0x{id:x} is not valid {expected:?} data."),
+            }
+        }
+    }
+
+    impl Error for MigrateError {}
+}
+
+pub mod typed_reader {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+    use crate::specs::TagDataType;
+
+    ///
+    /// Errors that can occur when reading values with a [`TypedReader`][`crate::iterator::TypedReader`].
+    ///
+    #[derive(Debug)]
+    pub enum TypedReadError {
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// The source ended before the expected tag.
+        ///
+        UnexpectedEnd {
+
+            ///
+            /// The id of the expected tag, if a specific tag was expected.
+            ///
+            expected_id: Option<u64>,
+        },
+
+        ///
+        /// The next tag is not the expected tag.
+        ///
+        UnexpectedTag {
+
+            ///
+            /// The id of the tag that was read.
+            ///
+            id: u64,
+
+            ///
+            /// The id of the expected tag.
+            ///
+            expected_id: u64,
+        },
+
+        ///
+        /// The next tag does not have the expected data type.
+        ///
+        TypeMismatch {
+
+            ///
+            /// The id of the tag that was read.
+            ///
+            id: u64,
+
+            ///
+            /// The expected data type.
+            ///
+            expected: TagDataType,
+
+            ///
+            /// The data type of the tag in the specification, or `None` if the tag isn't in the specification.
+            ///
+            found: Option<TagDataType>,
+        },
+
+        ///
+        /// The next tag is the expected "Master" tag, but not the expected [`Master`][`crate::specs::Master`] variant (such as an end where a start was expected).
+        ///
+        UnexpectedMasterVariant {
+
+            ///
+            /// The id of the tag.
+            ///
+            id: u64,
+        },
+    }
+
+    impl fmt::Display for TypedReadError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TypedReadError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                TypedReadError::UnexpectedEnd { expected_id: Some(expected_id) } => write!(f, "Source ended before tag id 0x{expected_id:x}."),
+                TypedReadError::UnexpectedEnd { expected_id: None } => write!(f, "Source ended before the next tag."),
+                TypedReadError::UnexpectedTag { id, expected_id } => write!(f, "Read tag id 0x{id:x} where tag id 0x{expected_id:x} was expected."),
+                TypedReadError::TypeMismatch { id, expected, found } => write!(f, "Tag id 0x{id:x} is {found:?} data, but {expected:?} data was expected."),
+                TypedReadError::UnexpectedMasterVariant { id } => write!(f, "Tag id 0x{id:x} is not the expected start, end, or full master tag."),
+            }
+        }
+    }
+
+    impl Error for TypedReadError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                TypedReadError::ReadError { source } => Some(source),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for TypedReadError {
+        fn from(source: TagIteratorError) -> Self {
+            TypedReadError::ReadError { source }
+        }
+    }
+}
+
+pub mod doctype {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when determining the DocType of a document with [`sniff_doctype()`][`crate::utils::sniff_doctype`].
+    ///
+    #[derive(Debug)]
+    pub enum DocTypeError {
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error indicating the source doesn't start with a valid EBML header.
+        ///
+        InvalidHeader,
+
+        ///
+        /// An error indicating the EBML header doesn't contain a DocType element.
+        ///
+        MissingDocType,
+
+        ///
+        /// An error indicating no handler is registered for the DocType of the document.
+        ///
+        UnknownDocType(String),
+    }
+
+    impl fmt::Display for DocTypeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                DocTypeError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                DocTypeError::InvalidHeader => write!(f, "Source does not start with a valid EBML header"),
+                DocTypeError::MissingDocType => write!(f, "EBML header does not contain a DocType"),
+                DocTypeError::UnknownDocType(doctype) => write!(f, "No handler registered for DocType \"{doctype}\""),
+            }
+        }
+    }
+
+    impl Error for DocTypeError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                DocTypeError::ReadError { source } => Some(source),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for DocTypeError {
+        fn from(source: TagIteratorError) -> Self {
+            DocTypeError::ReadError { source }
+        }
+    }
+}
+
+pub mod attachments {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+    use super::tag_writer::TagWriterError;
+    use std::io;
+
+    ///
+    /// Errors that can occur when listing, extracting or injecting attachments with the helpers in [`utils`][`crate::utils`].
+    ///
+    #[derive(Debug)]
+    pub enum AttachmentError {
+
+        ///
+        /// An error indicating an attachment's name, media type or data has an unknown size, so it can't be read as a single value.
+        ///
+        UnknownSize {
+            tag_id: u64,
+            position: usize,
+        },
+
+        ///
+        /// An error that wraps a problem reading or parsing the source document.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when opening or writing to a sink for extracted data.
+        ///
+        SinkError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: io::Error,
+        },
+
+        ///
+        /// An error that wraps an IO error when reading the contents of an attachment being injected, including the contents ending early.
+        ///
+        DataError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: io::Error,
+        },
+
+        ///
+        /// An error that wraps a problem writing an injected attachment to the destination.
+        ///
+        WriteError {
+
+            ///
+            /// The [`TagWriterError`] that caused this problem.
+            ///
+            source: TagWriterError,
+        },
+    }
+
+    impl fmt::Display for AttachmentError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                AttachmentError::UnknownSize { tag_id, position } => write!(f, "Cannot read attachment element with id {tag_id} at position {position} because it has an unknown size"),
+                AttachmentError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                AttachmentError::SinkError { source: _ } => write!(f, "Error writing to sink."),
+                AttachmentError::DataError { source: _ } => write!(f, "Error reading attachment data."),
+                AttachmentError::WriteError { source: _ } => write!(f, "Error writing to destination."),
+            }
+        }
+    }
+
+    impl Error for AttachmentError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                AttachmentError::ReadError { source } => Some(source),
+                AttachmentError::SinkError { source } => Some(source),
+                AttachmentError::DataError { source } => Some(source),
+                AttachmentError::WriteError { source } => Some(source),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for AttachmentError {
+        fn from(source: TagIteratorError) -> Self {
+            AttachmentError::ReadError { source }
+        }
+    }
+
+    impl From<TagWriterError> for AttachmentError {
+        fn from(source: TagWriterError) -> Self {
+            AttachmentError::WriteError { source }
+        }
+    }
+}
+
+#[cfg(feature = "digest")]
+pub mod element_digest {
+    use super::fmt;
+    use super::Error;
+    use super::tag_iterator::TagIteratorError;
+
+    ///
+    /// Errors that can occur when hashing elements with [`digest_elements()`][`crate::utils::digest_elements`].
+    ///
+    #[derive(Debug)]
+    pub enum DigestError {
+
+        ///
+        /// An error that wraps a problem reading or parsing the source.
+        ///
+        ReadError {
+
+            ///
+            /// The [`TagIteratorError`] that caused this problem.
+            ///
+            source: TagIteratorError,
+        },
+
+        ///
+        /// An error that wraps an IO error when writing to the sink.
+        ///
+        WriteError {
+
+            ///
+            /// The [`std::io::Error`] that caused this problem.
+            ///
+            source: std::io::Error,
+        },
+    }
+
+    impl fmt::Display for DigestError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                DigestError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                DigestError::WriteError { source: _ } => write!(f, "Error writing to sink."),
+            }
+        }
+    }
+
+    impl Error for DigestError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                DigestError::ReadError { source } => Some(source),
+                DigestError::WriteError { source } => Some(source),
+            }
+        }
+    }
+
+    impl From<TagIteratorError> for DigestError {
+        fn from(source: TagIteratorError) -> Self {
+            DigestError::ReadError { source }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub mod tag_serde {
+    use super::fmt;
+    use super::Error;
+    use crate::specs::TagDataType;
+
+    ///
+    /// Errors that can occur when converting a [`SerializedTag`][`crate::utils::SerializedTag`] back into a tag.
+    ///
+    #[derive(Debug)]
+    pub enum SerializedTagError {
+
+        ///
+        /// The tag id is not in the specification, and the tag's value was not [`SerializedValue::Raw`][`crate::utils::SerializedValue::Raw`].
+        ///
+        UnknownTag {
+
+            ///
+            /// The id of the tag.
+            ///
+            id: u64,
+        },
+
+        ///
+        /// The tag's value does not match its data type in the specification.
+        ///
+        InvalidValue {
+
+            ///
+            /// The id of the tag.
+            ///
+            id: u64,
+
+            ///
+            /// The data type of the tag in the specification.
+            ///
+            expected: TagDataType,
+        },
+    }
+
+    impl fmt::Display for SerializedTagError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                SerializedTagError::UnknownTag { id } => write!(f, "Tag id 0x{id:x} is not in the specification."),
+                SerializedTagError::InvalidValue { id, expected } => write!(f, "Value of tag id 0x{id:x} is not valid {expected:?} data."),
+            }
+        }
+    }
+
+    impl Error for SerializedTagError {}
+}
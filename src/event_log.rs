@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Range;
+
+use crate::tag_iterator_util::ElementSize;
+
+///
+/// A decision made by a [`TagIterator`](crate::TagIterator) while parsing, as recorded in its event log (see [`TagIterator::record_event_log()`](crate::TagIterator::record_event_log)).
+///
+/// Offsets are in the same terms as [`TagIterator::last_emitted_tag_offset()`](crate::TagIterator::last_emitted_tag_offset).
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseEvent {
+
+    ///
+    /// An element header was read.
+    ///
+    Header {
+        /// The offset of the header
+        offset: usize,
+        /// The id of the element
+        id: u64,
+        /// The length of the header (id and size)
+        header_len: usize,
+        /// The size declared in the header
+        size: ElementSize,
+    },
+
+    ///
+    /// An element is read with a smaller size than its header declares, because it extends past the end of the source (see [`SizePastEnd::Truncate`](crate::iterator::SizePastEnd::Truncate)).
+    ///
+    SizeTruncated {
+        /// The offset of the element's header
+        offset: usize,
+        /// The id of the element
+        id: u64,
+        /// The size declared in the header
+        declared: usize,
+        /// The size the element is read with
+        used: usize,
+    },
+
+    ///
+    /// A "Master" element was opened.
+    ///
+    Push {
+        /// The offset of the element's header
+        offset: usize,
+        /// The id of the element
+        id: u64,
+        /// The number of "Master" elements that were already open
+        level: usize,
+    },
+
+    ///
+    /// A "Master" element was closed.
+    ///
+    Pop {
+        /// The offset where the element was found to end
+        offset: usize,
+        /// The id of the element
+        id: u64,
+        /// The number of "Master" elements still open around it
+        level: usize,
+        /// Why the element was closed
+        cause: EndCause,
+    },
+
+    ///
+    /// [`TagIterator::try_recover()`](crate::TagIterator::try_recover) scanned over corrupted data.
+    ///
+    RecoveryScan {
+        /// The bytes that were skipped
+        skipped: Range<usize>,
+        /// Whether a valid element header was found after the skipped bytes
+        found_header: bool,
+    },
+
+    ///
+    /// An error was returned by the iterator.
+    ///
+    Error {
+        /// The offset the iterator was reading from
+        offset: usize,
+        /// The error's message
+        message: String,
+    },
+}
+
+///
+/// Why a "Master" element was closed, recorded in a [`ParseEvent::Pop`].
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EndCause {
+
+    ///
+    /// The end of the element's known size was reached.
+    ///
+    SizeReached,
+
+    ///
+    /// The element has an unknown size, and was ended by an element with this id that can't be one of its children.
+    ///
+    EndedBy(u64),
+
+    ///
+    /// The source ended (see [`TagIterator::emit_master_end_when_eof()`](crate::TagIterator::emit_master_end_when_eof)).
+    ///
+    EndOfSource,
+}
+
+impl fmt::Display for ParseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseEvent::Header { offset, id, header_len, size: ElementSize::Known(size) } => write!(f, "@{offset} header 0x{id:x} ({header_len} bytes), size {size}"),
+            ParseEvent::Header { offset, id, header_len, size: ElementSize::Unknown } => write!(f, "@{offset} header 0x{id:x} ({header_len} bytes), unknown size"),
+            ParseEvent::SizeTruncated { offset, id, declared, used } => write!(f, "@{offset} truncated 0x{id:x} from {declared} to {used} bytes"),
+            ParseEvent::Push { offset, id, level } => write!(f, "@{offset} push 0x{id:x} at level {level}"),
+            ParseEvent::Pop { offset, id, level, cause: EndCause::SizeReached } => write!(f, "@{offset} pop 0x{id:x} at level {level}: size reached"),
+            ParseEvent::Pop { offset, id, level, cause: EndCause::EndedBy(next) } => write!(f, "@{offset} pop 0x{id:x} at level {level}: ended by 0x{next:x}"),
+            ParseEvent::Pop { offset, id, level, cause: EndCause::EndOfSource } => write!(f, "@{offset} pop 0x{id:x} at level {level}: end of source"),
+            ParseEvent::RecoveryScan { skipped, found_header: true } => write!(f, "@{} recovery skipped {} bytes to a valid header", skipped.start, skipped.len()),
+            ParseEvent::RecoveryScan { skipped, found_header: false } => write!(f, "@{} recovery skipped {} bytes to the end of the source", skipped.start, skipped.len()),
+            ParseEvent::Error { offset, message } => write!(f, "@{offset} error: {message}"),
+        }
+    }
+}
+
+///
+/// The most recent [`ParseEvent`]s recorded by a [`TagIterator`](crate::TagIterator), obtained using [`TagIterator::event_log()`](crate::TagIterator::event_log).
+///
+/// The log holds a limited number of events, dropping the oldest ones as new ones are recorded.  Its [`Display`](fmt::Display) implementation writes one event per line, which is meant to be dumped when an error is returned and attached to bug reports.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventLog {
+    events: VecDeque<ParseEvent>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl EventLog {
+    pub(crate) fn new(capacity: usize) -> Self {
+        EventLog { events: VecDeque::with_capacity(capacity.min(1024)), capacity, dropped: 0 }
+    }
+
+    pub(crate) fn record(&mut self, event: ParseEvent) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    ///
+    /// Returns the recorded events, oldest first.
+    ///
+    pub fn events(&self) -> impl Iterator<Item = &ParseEvent> {
+        self.events.iter()
+    }
+
+    ///
+    /// Returns the number of events in the log.
+    ///
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    ///
+    /// Returns `true` if no events are in the log.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    ///
+    /// Returns the number of events that were dropped to keep the log within its capacity.
+    ///
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl fmt::Display for EventLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dropped > 0 {
+            writeln!(f, "({} earlier events dropped)", self.dropped)?;
+        }
+        for event in &self.events {
+            writeln!(f, "{event}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::io::{Read, Write};
+
+use crate::header_walker::HeaderWalker;
+use crate::spec_util::parse_path;
+use crate::tag_iterator_util::ElementSize::Known;
+
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::extract::ExtractError;
+
+///
+/// Identifies which elements to operate on.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementSelector<'a> {
+
+    ///
+    /// Elements at a `/` delimited path of tag names or hex ids starting at a root element, e.g. `"Segment/Attachments/AttachedFile/FileData"`.
+    ///
+    Path(&'a str),
+
+    ///
+    /// Elements with the given id, at any depth in the document.
+    ///
+    Id(u64),
+}
+
+impl<'a> From<&'a str> for ElementSelector<'a> {
+    fn from(path: &'a str) -> Self {
+        ElementSelector::Path(path)
+    }
+}
+
+impl From<u64> for ElementSelector<'_> {
+    fn from(id: u64) -> Self {
+        ElementSelector::Id(id)
+    }
+}
+
+///
+/// Streams the data of every element matching `selector` from `source` into `sink`.
+///
+/// Only element headers are parsed; element data is copied in small chunks, so payloads never need to fit in memory.  Data from multiple matching elements is written back to back.  Matching "Master" elements have their raw (encoded) children copied.  Returns the number of elements that were extracted.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::utils::extract;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let source = File::open("my_ebml_file.ebml")?;
+/// let mut sink = File::create("attachment.bin")?;
+/// let count = extract::<EmptySpec, _, _>(source, "0x18538067/0x1941a469/0x61a7/0x465c", &mut sink)?;
+/// println!("Extracted {} attachments", count);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Errors
+///
+/// Returns [`ExtractError::UnknownSize`] if a matching element has an unknown size.  The other possible error states are enumerated in [`ExtractError`].
+///
+pub fn extract<'a, TSpec, R: Read, W: Write>(source: R, selector: impl Into<ElementSelector<'a>>, mut sink: W) -> Result<usize, ExtractError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    match selector.into() {
+        ElementSelector::Id(id) => extract_where::<TSpec, _, _>(source, &mut sink, |_, tag_id| (tag_id == id, true)),
+        ElementSelector::Path(path_str) => {
+            let path = parse_path::<TSpec>(path_str)
+                .filter(|ids| !ids.is_empty())
+                .ok_or_else(|| ExtractError::InvalidPath(path_str.to_string()))?;
+
+            extract_where::<TSpec, _, _>(source, &mut sink, |walker, tag_id| {
+                let depth = walker.depth();
+                let on_path = depth < path.len() && path[depth] == tag_id && walker.parents().eq(path[..depth].iter().copied());
+                (on_path && depth + 1 == path.len(), on_path)
+            })
+        },
+    }
+}
+
+///
+/// `test` returns whether the element should be extracted, and whether it should be descended into otherwise.
+///
+fn extract_where<TSpec, R: Read, W: Write>(source: R, sink: &mut W, test: impl Fn(&HeaderWalker<R, TSpec>, u64) -> (bool, bool)) -> Result<usize, ExtractError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut walker: HeaderWalker<R, TSpec> = HeaderWalker::new(source);
+    let mut count = 0;
+    while let Some(header) = walker.next_header()? {
+        let (is_match, descend) = test(&walker, header.id);
+        if is_match {
+            match header.size {
+                Known(size) => walker.copy_data(size, sink, |source| ExtractError::WriteError { source })?,
+                _ => return Err(ExtractError::UnknownSize { tag_id: header.id, position: walker.position() - header.header_len }),
+            }
+            count += 1;
+        } else if descend && HeaderWalker::<R, TSpec>::is_master(&header) {
+            walker.descend(&header);
+        } else {
+            walker.skip_data(&header)?;
+        }
+    }
+
+    sink.flush().map_err(|source| ExtractError::WriteError { source })?;
+    Ok(count)
+}
@@ -0,0 +1,207 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::raw_frames::{RawFrame, RawFrames};
+use crate::spec_util::is_ended_by;
+use crate::tag_iterator_util::ElementHeader;
+use crate::tag_iterator_util::ElementSize::Known;
+
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::finalize::FinalizeError;
+use super::errors::tag_iterator::TagIteratorError;
+
+// The largest size an 8 byte vint can hold, since all ones means an unknown size
+const MAX_SIZE: u64 = (1 << 56) - 2;
+
+///
+/// Copies a document from `source` to `dest`, giving every element that has an unknown size (as written by live captures) its exact size.
+///
+/// The document is read once.  The header of each unknown-sized element is written with an 8 byte size that is filled in by seeking back once the element ends; everything else is copied byte for byte.  Unknown-sized elements end where a [`TagIterator`](crate::TagIterator) would end them: at the first element that isn't allowed as one of their children, or at the end of the source.  Elements inside an element that already has a known size are copied as they are.
+///
+/// Returns the number of bytes written.  Use [`finalize_sizes_two_pass()`] if `dest` can't seek.
+///
+/// ## Errors
+///
+/// Returns a [`FinalizeError::ReadError`] if the source can't be read, a [`FinalizeError::WriteError`] if writing or seeking fails, and a [`FinalizeError::ElementTooLarge`] if an element doesn't fit in an 8 byte size.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::utils::finalize_sizes;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let capture = File::open("live_capture.webm")?;
+/// let finalized = File::create("finalized.webm")?;
+/// finalize_sizes::<EmptySpec, _, _>(capture, finalized)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+pub fn finalize_sizes<TSpec, R: Read, W: Write + Seek>(source: R, mut dest: W) -> Result<u64, FinalizeError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let start = dest.stream_position().map_err(|source| FinalizeError::WriteError { source })?;
+    let mut position = start;
+
+    // The header offset of each open unknown-sized element
+    let mut open: Vec<u64> = Vec::new();
+    walk::<TSpec, R>(source, |step| {
+        let bytes = match step {
+            Step::Copy(frame) => {
+                write(&mut dest, &frame.header)?;
+                write(&mut dest, &frame.payload)?;
+                frame.header.len() + frame.payload.len()
+            },
+            Step::Start(frame) => {
+                open.push(position);
+                let header = sized_header(frame.id, 0, 8);
+                write(&mut dest, &header)?;
+                header.len()
+            },
+            Step::End(id) => {
+                let header_start = open.pop().expect("ended elements should have been started");
+                let header_len = id_len(id) + 8;
+                let size = position - header_start - header_len as u64;
+                if size > MAX_SIZE {
+                    return Err(FinalizeError::ElementTooLarge { tag_id: id, size });
+                }
+                dest.seek(SeekFrom::Start(header_start)).map_err(|source| FinalizeError::WriteError { source })?;
+                write(&mut dest, &sized_header(id, size, 8))?;
+                dest.seek(SeekFrom::Start(position)).map_err(|source| FinalizeError::WriteError { source })?;
+                0
+            },
+        };
+        position += bytes as u64;
+        Ok(())
+    })?;
+
+    dest.flush().map_err(|source| FinalizeError::WriteError { source })?;
+    Ok(position - start)
+}
+
+///
+/// Copies a document from `source` to `dest` like [`finalize_sizes()`], for destinations that can't seek.
+///
+/// The source is read twice instead: once to work out the size of every unknown-sized element, and again (from the position it was at when this was called) to write the document.  Since sizes are known before their headers are written, each one is written with as few bytes as possible.
+///
+/// Returns the number of bytes written.
+///
+/// ## Errors
+///
+/// Returns the same errors as [`finalize_sizes()`], and a [`FinalizeError::SourceChanged`] if the second read of the source doesn't hold the same unknown-sized elements, or the same number of bytes, as the first.
+///
+pub fn finalize_sizes_two_pass<TSpec, R: Read + Seek, W: Write>(mut source: R, mut dest: W) -> Result<u64, FinalizeError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let start = source.stream_position().map_err(|source| FinalizeError::ReadError { source: TagIteratorError::ReadError { source } })?;
+
+    // Sizes in the order the elements start, filled in as they end
+    let mut sizes: Vec<u64> = Vec::new();
+    let mut open: Vec<(usize, u64)> = Vec::new();
+    let mut total = 0;
+    walk::<TSpec, _>(&mut source, |step| {
+        let bytes = match step {
+            Step::Copy(frame) => (frame.header.len() + frame.payload.len()) as u64,
+            Step::Start(_) => {
+                open.push((sizes.len(), 0));
+                sizes.push(0);
+                return Ok(());
+            },
+            Step::End(id) => {
+                let (index, size) = open.pop().expect("ended elements should have been started");
+                if size > MAX_SIZE {
+                    return Err(FinalizeError::ElementTooLarge { tag_id: id, size });
+                }
+                sizes[index] = size;
+                (id_len(id) + size_length(size)) as u64 + size
+            },
+        };
+        match open.last_mut() {
+            Some((_, size)) => *size += bytes,
+            None => total += bytes,
+        }
+        Ok(())
+    })?;
+
+    source.seek(SeekFrom::Start(start)).map_err(|source| FinalizeError::ReadError { source: TagIteratorError::ReadError { source } })?;
+    let mut sizes = sizes.into_iter();
+    let mut written = 0;
+    walk::<TSpec, _>(source, |step| {
+        match step {
+            Step::Copy(frame) => {
+                write(&mut dest, &frame.header)?;
+                write(&mut dest, &frame.payload)?;
+                written += (frame.header.len() + frame.payload.len()) as u64;
+            },
+            Step::Start(frame) => {
+                let size = sizes.next().ok_or(FinalizeError::SourceChanged)?;
+                let header = sized_header(frame.id, size, size_length(size));
+                write(&mut dest, &header)?;
+                written += header.len() as u64;
+            },
+            Step::End(_) => {},
+        }
+        Ok(())
+    })?;
+    if sizes.next().is_some() || written != total {
+        return Err(FinalizeError::SourceChanged);
+    }
+
+    dest.flush().map_err(|source| FinalizeError::WriteError { source })?;
+    Ok(written)
+}
+
+enum Step<'a> {
+    /// An element with a known size, to be copied as is
+    Copy(&'a RawFrame),
+    /// The header of an element with an unknown size
+    Start(&'a RawFrame),
+    /// The end of an element with an unknown size
+    End(u64),
+}
+
+fn walk<TSpec, R: Read>(source: R, mut step: impl FnMut(Step) -> Result<(), FinalizeError>) -> Result<(), FinalizeError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut open: Vec<u64> = Vec::new();
+    for frame in RawFrames::new(source) {
+        let frame = frame?;
+        while let Some(&id) = open.last() {
+            if !is_ended_by::<TSpec>(id, frame.id) {
+                break;
+            }
+            open.pop();
+            step(Step::End(id))?;
+        }
+        if frame.size.is_unknown() {
+            open.push(frame.id);
+            step(Step::Start(&frame))?;
+        } else {
+            step(Step::Copy(&frame))?;
+        }
+    }
+    while let Some(id) = open.pop() {
+        step(Step::End(id))?;
+    }
+    Ok(())
+}
+
+fn write<W: Write>(dest: &mut W, data: &[u8]) -> Result<(), FinalizeError> {
+    dest.write_all(data).map_err(|source| FinalizeError::WriteError { source })
+}
+
+fn id_len(id: u64) -> usize {
+    id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count()
+}
+
+fn size_length(size: u64) -> usize {
+    (1..8).find(|len| size < (1 << (7 * len)) - 1).unwrap_or(8)
+}
+
+fn sized_header(id: u64, size: u64, size_length: usize) -> Vec<u8> {
+    ElementHeader { id, size: Known(size as usize), header_len: id_len(id) + size_length }.encode()
+}
@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+use std::io::Read;
+
+use super::tag_iterator::TagIterator;
+use super::specs::{EbmlSpecification, EbmlTag, Master, PathPart, TagDataType};
+use super::errors::tag_iterator::TagIteratorError;
+
+///
+/// The value of a non-master element, as emitted by [`FlattenValues`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlatValue {
+
+    ///
+    /// Data of a [`TagDataType::UnsignedInt`] tag.
+    ///
+    UnsignedInt(u64),
+
+    ///
+    /// Data of a [`TagDataType::Integer`] tag.
+    ///
+    Integer(i64),
+
+    ///
+    /// Data of a [`TagDataType::Utf8`] tag.  Text that isn't valid UTF-8 (possible when the specification stores text as [`LazyUtf8`][`crate::specs::LazyUtf8`]) is converted lossily.
+    ///
+    Utf8(String),
+
+    ///
+    /// Data of a [`TagDataType::Binary`] tag, or of a tag that isn't in the specification.
+    ///
+    Binary(Vec<u8>),
+
+    ///
+    /// Data of a [`TagDataType::Float`] tag.
+    ///
+    Float(f64),
+}
+
+impl FlatValue {
+    fn from_tag<TSpec>(tag: &TSpec) -> Self
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        let id = tag.get_id();
+        match TSpec::get_tag_data_type(id) {
+            Some(TagDataType::UnsignedInt) => FlatValue::UnsignedInt(*tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", id))),
+            Some(TagDataType::Integer) => FlatValue::Integer(*tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", id))),
+            Some(TagDataType::Utf8) => FlatValue::Utf8(String::from_utf8_lossy(tag.as_utf8_bytes().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", id))).into_owned()),
+            Some(TagDataType::Float) => FlatValue::Float(*tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", id))),
+            Some(TagDataType::Binary) | Some(TagDataType::Master) | None => FlatValue::Binary(tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", id)).to_vec()),
+        }
+    }
+}
+
+///
+/// An iterator over the non-master elements of a document, each paired with the ids of the "Master" elements containing it.
+///
+/// This is returned by [`TagIterator::flatten_values()`].  "Master" tags are never emitted: their [`Master::Start`] and [`Master::End`] tags only update the path, and the children of any buffered [`Master::Full`] tags are emitted individually.  Errors from the underlying [`TagIterator`] are passed through.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagIterator;
+/// use ebml_iterable::iterator::FlatValue;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let data = [0x42, 0x86, 0x81, 0x01];
+/// let iter: TagIterator<_, EmptySpec> = TagIterator::new(&data[..], &[]);
+///
+/// for value in iter.flatten_values() {
+///     let (path, value) = value.unwrap();
+///     println!("{:x?} = {:?}", path, value);
+///     # assert_eq!((vec![0x4286], FlatValue::Binary(vec![0x01])), (path, value));
+/// }
+/// ```
+///
+pub struct FlattenValues<R: Read, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iterator: TagIterator<R, TSpec>,
+    path: Vec<u64>,
+    pending: VecDeque<(Vec<u64>, FlatValue)>,
+}
+
+impl<R: Read, TSpec> FlattenValues<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    pub(crate) fn new(iterator: TagIterator<R, TSpec>) -> Self {
+        FlattenValues {
+            iterator,
+            path: Vec::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    ///
+    /// Consumes self and returns the underlying [`TagIterator`].
+    ///
+    pub fn into_inner(self) -> TagIterator<R, TSpec> {
+        self.iterator
+    }
+
+    fn set_level(&mut self, tag: &TSpec, level: usize) {
+        if self.path.len() < level {
+            // Parents that were inferred (when reading started mid-document) are only known from the spec
+            self.path = TSpec::get_path_by_tag(tag).iter().filter_map(|part| match part {
+                PathPart::Id(id) => Some(*id),
+                PathPart::Global(_) => None,
+            }).collect();
+        }
+        self.path.truncate(level);
+    }
+
+    fn queue_children(&mut self, path: &mut Vec<u64>, children: &[TSpec]) {
+        for child in children {
+            match child.as_master() {
+                Some(Master::Full(grandchildren)) => {
+                    path.push(child.get_id());
+                    self.queue_children(path, grandchildren);
+                    path.pop();
+                },
+                Some(_) => {},
+                None => {
+                    let mut child_path = path.clone();
+                    child_path.push(child.get_id());
+                    self.pending.push_back((child_path, FlatValue::from_tag(child)));
+                },
+            }
+        }
+    }
+}
+
+impl<R: Read, TSpec> Iterator for FlattenValues<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<(Vec<u64>, FlatValue), TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(value) = self.pending.pop_front() {
+                return Some(Ok(value));
+            }
+
+            let tag = match self.iterator.next()? {
+                Ok(tag) => tag,
+                Err(err) => return Some(Err(err)),
+            };
+            let level = self.iterator.last_emitted_tag_level();
+            self.set_level(&tag, level);
+
+            match tag.as_master() {
+                Some(Master::Start) => self.path.push(tag.get_id()),
+                Some(Master::End) => {},
+                Some(Master::Full(children)) => {
+                    let mut path = self.path.clone();
+                    path.push(tag.get_id());
+                    self.queue_children(&mut path, children);
+                },
+                None => {
+                    let mut path = self.path.clone();
+                    path.push(tag.get_id());
+                    return Some(Ok((path, FlatValue::from_tag(&tag))));
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+const BLOCK_LEN: usize = 64 * 1024;
+const DEFAULT_CACHED_BLOCKS: usize = 8;
+
+///
+/// A cursor over a [`Seek`]able source that can be cloned into independent cursors over the same data.
+///
+/// All cursors share the source and a small cache of the blocks read from it most recently, so cursors reading near each other (such as two iterators demuxing interleaved tracks) don't read the same data from the source twice.  Each cursor has its own position and seeks the source before reading when necessary.
+///
+/// A [`TagIterator`][crate::TagIterator] reading from a [`ForkableSource`] can be split into two iterators with [`TagIterator::fork()`][crate::TagIterator::fork].
+///
+pub struct ForkableSource<R: Read + Seek> {
+    shared: Arc<Mutex<SharedBlocks<R>>>,
+    position: u64,
+}
+
+struct SharedBlocks<R> {
+    source: R,
+    source_position: Option<u64>,
+
+    /// Recently read blocks, keyed by their (block aligned) start position, most recently used last
+    blocks: VecDeque<(u64, Vec<u8>)>,
+    max_blocks: usize,
+}
+
+impl<R: Read + Seek> ForkableSource<R> {
+
+    ///
+    /// Returns a new [`ForkableSource`] starting at the current position of `source`.
+    ///
+    pub fn new(source: R) -> Self {
+        Self::with_cached_blocks(source, DEFAULT_CACHED_BLOCKS)
+    }
+
+    ///
+    /// Returns a new [`ForkableSource`] that keeps up to `max_blocks` blocks of 64KiB cached for its cursors.  The default is 8 blocks.
+    ///
+    pub fn with_cached_blocks(mut source: R, max_blocks: usize) -> Self {
+        let position = source.stream_position().ok();
+        ForkableSource {
+            position: position.unwrap_or(0),
+            shared: Arc::new(Mutex::new(SharedBlocks {
+                source,
+                source_position: position,
+                blocks: VecDeque::new(),
+                max_blocks: max_blocks.max(1),
+            })),
+        }
+    }
+
+    ///
+    /// Returns a new cursor `rewind` bytes before this one.
+    ///
+    pub(crate) fn fork_at(&self, rewind: u64) -> Self {
+        ForkableSource {
+            shared: Arc::clone(&self.shared),
+            position: self.position - rewind,
+        }
+    }
+}
+
+impl<R: Read + Seek> Clone for ForkableSource<R> {
+    fn clone(&self) -> Self {
+        self.fork_at(0)
+    }
+}
+
+impl<R: Read + Seek> SharedBlocks<R> {
+    fn block(&mut self, start: u64) -> std::io::Result<&[u8]> {
+        match self.blocks.iter().position(|(block_start, _)| *block_start == start) {
+            Some(index) => {
+                let block = self.blocks.remove(index).expect("index should be in bounds");
+                self.blocks.push_back(block);
+            },
+            None => {
+                if self.source_position != Some(start) {
+                    self.source.seek(SeekFrom::Start(start))?;
+                }
+                let mut data = if self.blocks.len() >= self.max_blocks {
+                    self.blocks.pop_front().expect("cache should not be empty").1
+                } else {
+                    Vec::with_capacity(BLOCK_LEN)
+                };
+                data.clear();
+                let read = (&mut self.source).take(BLOCK_LEN as u64).read_to_end(&mut data);
+                self.source_position = read.as_ref().ok().map(|len| start + *len as u64);
+                read?;
+                self.blocks.push_back((start, data));
+            },
+        }
+        Ok(&self.blocks.back().expect("block was just added").1)
+    }
+}
+
+impl<R: Read + Seek> Read for ForkableSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut shared = self.shared.lock().map_err(|_| std::io::Error::other("forkable source lock was poisoned"))?;
+        let offset = (self.position % BLOCK_LEN as u64) as usize;
+        let block = shared.block(self.position - offset as u64)?;
+        // A short block is the end of the source
+        let available = block.get(offset..).unwrap_or_default();
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl<R: Read + Seek> Seek for ForkableSource<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = match pos {
+            SeekFrom::Start(position) => position,
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset).ok_or_else(|| std::io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative position"))?,
+            SeekFrom::End(_) => {
+                let mut shared = self.shared.lock().map_err(|_| std::io::Error::other("forkable source lock was poisoned"))?;
+                let position = shared.source.seek(pos);
+                shared.source_position = position.as_ref().ok().copied();
+                position?
+            },
+        };
+        Ok(self.position)
+    }
+}
@@ -0,0 +1,157 @@
+use std::io::Read;
+
+use super::tag_iterator::TagIterator;
+use super::specs::{EbmlSpecification, EbmlTag, Master};
+use super::errors::tag_iterator::TagIteratorError;
+
+///
+/// Tells [`parse_with_handler()`] whether to keep going after a [`Handler`] callback.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandlerAction {
+
+    ///
+    /// Keep parsing.  When returned from [`Handler::on_error()`], the parser attempts to recover from the error (see [`TagIterator::try_recover()`]).
+    ///
+    Continue,
+
+    ///
+    /// Stop parsing.
+    ///
+    Stop,
+}
+
+///
+/// Receives events from [`parse_with_handler()`] as a document is parsed.
+///
+/// This is an event-driven alternative to iterating over a [`TagIterator`], similar to the callback interfaces of libebml or mkvparser.  Every callback has a default implementation that ignores the event, so only the events of interest need to be handled.  Each callback also receives the byte offset of the tag's start in the source.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::{Handler, HandlerAction};
+/// use ebml_iterable::specs::EbmlTag;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// // Counts the tags inside of each "Master" tag
+/// struct ChildCounter {
+///     counts: Vec<usize>,
+/// }
+///
+/// impl Handler<EmptySpec> for ChildCounter {
+///     fn on_master_start(&mut self, _tag: &EmptySpec, _offset: usize) -> HandlerAction {
+///         self.counts.push(0);
+///         HandlerAction::Continue
+///     }
+///
+///     fn on_tag(&mut self, _tag: EmptySpec, _offset: usize) -> HandlerAction {
+///         if let Some(count) = self.counts.last_mut() {
+///             *count += 1;
+///         }
+///         HandlerAction::Continue
+///     }
+///
+///     fn on_master_end(&mut self, tag: &EmptySpec, _offset: usize) -> HandlerAction {
+///         println!("0x{:x} had {} children", tag.get_id(), self.counts.pop().unwrap_or(0));
+///         HandlerAction::Continue
+///     }
+/// }
+/// ```
+///
+pub trait Handler<TSpec> {
+
+    ///
+    /// Called when a "Master" tag starts.  `tag` is the [`Master::Start`] variant.
+    ///
+    fn on_master_start(&mut self, _tag: &TSpec, _offset: usize) -> HandlerAction {
+        HandlerAction::Continue
+    }
+
+    ///
+    /// Called for every tag that isn't a "Master" tag.
+    ///
+    fn on_tag(&mut self, _tag: TSpec, _offset: usize) -> HandlerAction {
+        HandlerAction::Continue
+    }
+
+    ///
+    /// Called when a "Master" tag ends.  `tag` is the [`Master::End`] variant, and `offset` is the start of the "Master" tag.
+    ///
+    fn on_master_end(&mut self, _tag: &TSpec, _offset: usize) -> HandlerAction {
+        HandlerAction::Continue
+    }
+
+    ///
+    /// Called when the source can't be parsed.
+    ///
+    /// Returning [`HandlerAction::Continue`] skips ahead to the next valid tag, while [`HandlerAction::Stop`] (the default) makes [`parse_with_handler()`] return the error.
+    ///
+    fn on_error(&mut self, _error: &TagIteratorError) -> HandlerAction {
+        HandlerAction::Stop
+    }
+}
+
+///
+/// Parses every tag in `reader`, passing them to `handler` as events.
+///
+/// Parsing continues until the end of the source, or until a callback returns [`HandlerAction::Stop`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::{Handler, HandlerAction};
+/// use ebml_iterable::utils::parse_with_handler;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// struct Printer;
+///
+/// impl Handler<EmptySpec> for Printer {
+///     fn on_tag(&mut self, tag: EmptySpec, offset: usize) -> HandlerAction {
+///         println!("{}: {:?}", offset, tag);
+///         HandlerAction::Continue
+///     }
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// parse_with_handler(File::open("my_ebml_file.ebml")?, &mut Printer)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Errors
+///
+/// Returns the error that [`Handler::on_error()`] chose to stop on, or the error from recovering if no valid tag could be found after an error.
+///
+pub fn parse_with_handler<R, TSpec, H>(reader: R, handler: &mut H) -> Result<(), TagIteratorError>
+    where
+    R: Read,
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    H: Handler<TSpec> + ?Sized,
+{
+    let mut iterator: TagIterator<R, TSpec> = TagIterator::new(reader, &[]);
+    while let Some(tag) = iterator.next() {
+        let action = match tag {
+            Ok(tag) => {
+                let offset = iterator.last_emitted_tag_offset();
+                match tag.as_master() {
+                    Some(Master::Start) => handler.on_master_start(&tag, offset),
+                    Some(Master::End) => handler.on_master_end(&tag, offset),
+                    _ => handler.on_tag(tag, offset),
+                }
+            },
+            Err(err) => match handler.on_error(&err) {
+                HandlerAction::Continue => {
+                    iterator.try_recover()?;
+                    HandlerAction::Continue
+                },
+                HandlerAction::Stop => return Err(err),
+            },
+        };
+
+        if action == HandlerAction::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
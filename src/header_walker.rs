@@ -0,0 +1,165 @@
+use std::io::{ErrorKind, Read, Write};
+use std::marker::PhantomData;
+
+use crate::spec_util::is_ended_by;
+use crate::tag_iterator_util::{ElementHeader, read_element_header};
+use crate::tag_iterator_util::ElementSize::Known;
+
+use super::specs::{EbmlSpecification, EbmlTag, TagDataType};
+use super::errors::tag_iterator::{CorruptedFileError, PartialTag, TagIteratorError};
+
+const COPY_CHUNK_SIZE: usize = 8192;
+
+///
+/// Walks the element headers of a document read from a [`std::io::Read`] source without decoding any element data.
+///
+/// After each header returned by [`Self::next_header()`], the caller decides whether to [descend](Self::descend) into the element, [skip](Self::skip_data) its data, or [copy](Self::copy_data) its data elsewhere.
+///
+pub(crate) struct HeaderWalker<R: Read, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    source: R,
+    position: usize,
+
+    /// Ids of the masters we've descended into, with the end position of known-size masters
+    open: Vec<(u64, Option<usize>)>,
+    _spec: PhantomData<TSpec>,
+}
+
+impl<R: Read, TSpec> HeaderWalker<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    pub fn new(source: R) -> Self {
+        HeaderWalker {
+            source,
+            position: 0,
+            open: Vec::new(),
+            _spec: PhantomData,
+        }
+    }
+
+    ///
+    /// Current position in the source.  Right after [`Self::next_header()`] this is the start of the element data.
+    ///
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    ///
+    /// Ids of the master elements containing the element most recently returned by [`Self::next_header()`].
+    ///
+    pub fn parents(&self) -> impl Iterator<Item = u64> + '_ {
+        self.open.iter().map(|p| p.0)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    ///
+    /// Reads the next element header, closing any parents that the element falls outside of.  Returns `Ok(None)` at the end of the source.
+    ///
+    /// Returns [`CorruptedFileError::OversizedChildElement`] if the element would run past the end of its known-size parent.
+    ///
+    pub fn next_header(&mut self) -> Result<Option<ElementHeader>, TagIteratorError> {
+        let header_start = self.position;
+        let header = match read_element_header(&mut self.source, header_start)? {
+            Some(header) => header,
+            None => {
+                self.open.clear();
+                return Ok(None);
+            }
+        };
+        self.position += header.header_len;
+
+        loop {
+            match self.open.last() {
+                Some((_, Some(end))) if header_start >= *end => {},
+                Some((id, None)) if is_ended_by::<TSpec>(*id, header.id) => {},
+                _ => break,
+            }
+            self.open.pop();
+        }
+
+        // Children can't run past the end of a known-size parent
+        if let (Some((_, Some(end))), Known(size)) = (self.open.last(), header.size) {
+            if self.position + size > *end {
+                return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement { position: header_start, tag_id: header.id, size }));
+            }
+        }
+
+        Ok(Some(header))
+    }
+
+    ///
+    /// Returns whether the element is a "Master" that can be descended into.
+    ///
+    pub fn is_master(header: &ElementHeader) -> bool {
+        matches!(TSpec::get_tag_data_type(header.id), Some(TagDataType::Master))
+    }
+
+    ///
+    /// Treats the following headers as children of `header`.
+    ///
+    pub fn descend(&mut self, header: &ElementHeader) {
+        let end = match header.size {
+            Known(size) => Some(self.position + size),
+            _ => None,
+        };
+        self.open.push((header.id, end));
+    }
+
+    ///
+    /// Skips over the data of `header`.  Unknown sized elements are descended into instead, since their end can only be found by reading their children.
+    ///
+    pub fn skip_data(&mut self, header: &ElementHeader) -> Result<(), TagIteratorError> {
+        match header.size {
+            Known(size) => {
+                let copied = std::io::copy(&mut self.source.by_ref().take(size as u64), &mut std::io::sink()).map_err(|source| TagIteratorError::ReadError { source })?;
+                self.position += copied as usize;
+                if (copied as usize) < size {
+                    return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start: self.position - header.header_len - copied as usize, id: Some(header.id), size: Some(size), header_len: Some(header.header_len), obtained: copied as usize, data: None }));
+                }
+            },
+            _ => self.descend(header),
+        }
+        Ok(())
+    }
+
+    ///
+    /// Copies `len` bytes from the source to `sink` in fixed size chunks.  `map_write_err` converts errors from writing to `sink`.
+    ///
+    pub fn copy_data<W: Write + ?Sized, E: From<TagIteratorError>>(&mut self, len: usize, sink: &mut W, map_write_err: impl Fn(std::io::Error) -> E) -> Result<(), E> {
+        let mut buffer = [0u8; COPY_CHUNK_SIZE];
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = &mut buffer[..remaining.min(COPY_CHUNK_SIZE)];
+            let read = match self.source.read(chunk) {
+                Ok(0) => return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start: self.position, id: None, size: Some(remaining), header_len: None, obtained: 0, data: None }).into()),
+                Ok(read) => read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(source) => return Err(TagIteratorError::ReadError { source }.into()),
+            };
+            sink.write_all(&chunk[..read]).map_err(&map_write_err)?;
+            self.position += read;
+            remaining -= read;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Writes the header and data of `header` to `dest`.  Elements of unknown size are descended into instead of having their data copied, so their children can be handled as they're read.
+    ///
+    pub fn copy_element<W: Write + ?Sized, E: From<TagIteratorError>>(&mut self, header: &ElementHeader, dest: &mut W, map_write_err: impl Fn(std::io::Error) -> E) -> Result<(), E> {
+        dest.write_all(&header.encode()).map_err(&map_write_err)?;
+        match header.size {
+            Known(size) => self.copy_data(size, dest, map_write_err),
+            _ => {
+                self.descend(header);
+                Ok(())
+            },
+        }
+    }
+}
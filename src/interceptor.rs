@@ -0,0 +1,206 @@
+use std::error::Error;
+use std::io::Write;
+
+use super::tag_writer::{TagWriter, WriteOptions};
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::tag_writer::TagWriterError;
+
+///
+/// A step in the chain of an [`InterceptingWriter`] that can observe, rewrite, drop, or inject tags before they are encoded.
+///
+/// Each tag is passed by value to [`Self::intercept()`] along with an output list.  Pushing the tag (or a modified version of it) to the output forwards it to the next interceptor in the chain, not pushing anything drops it, and pushing additional tags injects them.
+///
+/// ## Example
+///
+/// ```
+/// use std::error::Error;
+/// use ebml_iterable::WriteInterceptor;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+/// use ebml_iterable::specs::EbmlTag;
+///
+/// // Drops every "Void" element
+/// struct DropVoid;
+///
+/// impl WriteInterceptor<EmptySpec> for DropVoid {
+///     fn intercept(&mut self, tag: EmptySpec, output: &mut Vec<EmptySpec>) -> Result<(), Box<dyn Error + Send + Sync>> {
+///         if tag.get_id() != 0xec {
+///             output.push(tag);
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+pub trait WriteInterceptor<TSpec> {
+
+    ///
+    /// Handles a single tag, pushing whatever should continue down the chain onto `output`.
+    ///
+    /// Returning an error stops the write, which fails with a [`TagWriterError::InterceptorError`].
+    ///
+    fn intercept(&mut self, tag: TSpec, output: &mut Vec<TSpec>) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    ///
+    /// Called once when the writer is flushed, allowing any final tags to be injected.  Does nothing by default.
+    ///
+    fn finish(&mut self, _output: &mut Vec<TSpec>) {}
+}
+
+///
+/// Wraps a [`TagWriter`] with an ordered chain of [`WriteInterceptor`]s that every written tag passes through before being encoded.
+///
+/// Interceptors are called in the order they were added, and each one receives the output of the one before it.  This allows small, reusable policies (injecting elements, rewriting timestamps, rejecting tags that shouldn't be in a document) to be combined on one writer.
+///
+/// Tags are intercepted as they are passed to [`Self::write()`], so the children of a [`Master::Full`][`crate::specs::Master::Full`] tag are seen as part of their parent rather than individually.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::error::Error;
+/// use std::fs::File;
+/// use ebml_iterable::{InterceptingWriter, TagWriter, WriteInterceptor};
+/// use ebml_iterable::specs::EbmlTag;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// struct DropVoid;
+///
+/// impl WriteInterceptor<EmptySpec> for DropVoid {
+///     fn intercept(&mut self, tag: EmptySpec, output: &mut Vec<EmptySpec>) -> Result<(), Box<dyn Error + Send + Sync>> {
+///         if tag.get_id() != 0xec {
+///             output.push(tag);
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let file = File::create("my_ebml_file.ebml")?;
+/// let mut writer = InterceptingWriter::new(TagWriter::new(file));
+/// writer.add_interceptor(DropVoid);
+/// writer.write(&EmptySpec::with_data(0xec, &[0, 0, 0]))?;
+/// writer.into_inner()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct InterceptingWriter<W: Write, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    writer: TagWriter<W>,
+    interceptors: Vec<Box<dyn WriteInterceptor<TSpec>>>,
+    finished: bool,
+}
+
+impl<W: Write, TSpec> InterceptingWriter<W, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`InterceptingWriter`] that writes to `writer` once tags have passed through the chain.
+    ///
+    pub fn new(writer: TagWriter<W>) -> Self {
+        InterceptingWriter {
+            writer,
+            interceptors: Vec::new(),
+            finished: false,
+        }
+    }
+
+    ///
+    /// Adds an interceptor to the end of the chain.
+    ///
+    pub fn add_interceptor(&mut self, interceptor: impl WriteInterceptor<TSpec> + 'static) {
+        self.interceptors.push(Box::new(interceptor));
+    }
+
+    ///
+    /// Passes a tag through the interceptor chain and writes whatever comes out the other end.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if an interceptor rejects a tag or there is a problem writing the resulting tags.  The different possible error states are enumerated in [`TagWriterError`].
+    ///
+    pub fn write(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        self.write_advanced(tag, WriteOptions::default())
+    }
+
+    ///
+    /// Like [`Self::write()`], but writes using advanced options.
+    ///
+    /// `options` are only applied to tags coming out of the chain with the same id as `tag`; injected tags with other ids are written normally.
+    ///
+    pub fn write_advanced(&mut self, tag: &TSpec, options: WriteOptions) -> Result<(), TagWriterError> {
+        if self.interceptors.is_empty() {
+            return self.writer.write_advanced(tag, options);
+        }
+
+        let tag_id = tag.get_id();
+        for output in self.run_chain(0, vec![tag.clone()])? {
+            if output.get_id() == tag_id {
+                self.writer.write_advanced(&output, options)?;
+            } else {
+                self.writer.write(&output)?;
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Gives every interceptor a chance to inject final tags (see [`WriteInterceptor::finish()`]) and then flushes the underlying [`TagWriter`].
+    ///
+    /// Interceptors are only finished the first time this is called; later calls just flush the writer.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if an interceptor rejects an injected tag or there is a problem writing to the destination.
+    ///
+    pub fn flush(&mut self) -> Result<(), TagWriterError> {
+        if !self.finished {
+            self.finished = true;
+            for index in 0..self.interceptors.len() {
+                let mut injected = Vec::new();
+                self.interceptors[index].finish(&mut injected);
+                for output in self.run_chain(index + 1, injected)? {
+                    self.writer.write(&output)?;
+                }
+            }
+        }
+        self.writer.flush()
+    }
+
+    ///
+    /// Consumes self, flushing it (see [`Self::flush()`]), and returns the underlying write stream.
+    ///
+    pub fn into_inner(mut self) -> Result<W, TagWriterError> {
+        self.flush()?;
+        self.writer.into_inner()
+    }
+
+    ///
+    /// Gets a mutable reference to the wrapped [`TagWriter`].  Tags written directly to it skip the interceptor chain.
+    ///
+    pub fn get_mut(&mut self) -> &mut TagWriter<W> {
+        &mut self.writer
+    }
+
+    ///
+    /// Gets a reference to the wrapped [`TagWriter`].
+    ///
+    pub fn get_ref(&self) -> &TagWriter<W> {
+        &self.writer
+    }
+
+    fn run_chain(&mut self, start: usize, mut tags: Vec<TSpec>) -> Result<Vec<TSpec>, TagWriterError> {
+        for interceptor in self.interceptors[start..].iter_mut() {
+            let mut output = Vec::with_capacity(tags.len());
+            for tag in tags {
+                let tag_id = tag.get_id();
+                interceptor.intercept(tag, &mut output).map_err(|source| TagWriterError::InterceptorError { tag_id, source })?;
+            }
+            tags = output;
+        }
+        Ok(tags)
+    }
+}
@@ -0,0 +1,105 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::header_walker::HeaderWalker;
+use crate::spec_util::CRC32_ID;
+use crate::tag_iterator_util::ElementHeader;
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+
+use super::specs::{EbmlSpecification, EbmlTag, PathPart};
+use super::errors::join::JoinError;
+
+///
+/// Merges multiple documents that share the same structure into a single document written to `output`.
+///
+/// Documents are joined on elements with id `join_id` (e.g. `Cluster` elements for WebM recordings).  The first document is copied up to its first `join_id` element, and the masters containing `join_id` elements (e.g. `Segment`) are merged so they hold the content of every input.  In the remaining documents everything before the first `join_id` element (like repeated EBML headers, `Info`, and `Tracks` elements) is dropped.  Elements that come between or after the `join_id` elements in any document (like `Cues`, `Tags` or a trailing `SeekHead`, whose offsets wouldn't match the merged document) and elements that come after the merged masters close are also dropped.
+///
+/// Merged masters are written with 8 byte size vints, which are rewritten with their final sizes once all inputs have been copied.  `CRC-32` elements directly inside merged masters are dropped since they would no longer be valid.  Returns the number of `join_id` elements written.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::utils::join;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let inputs = vec![File::open("part0.ebml")?, File::open("part1.ebml")?];
+/// let output = File::create("joined.ebml")?;
+/// join::<EmptySpec, _, _, _>(inputs, output, 0x1f43b675)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Errors
+///
+/// This method can error if there is a problem reading an input or writing the output.  The different possible error states are enumerated in [`JoinError`].
+///
+pub fn join<TSpec, I, R, W>(inputs: I, mut output: W, join_id: u64) -> Result<usize, JoinError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone,
+    I: IntoIterator<Item = R>,
+    R: Read,
+    W: Write + Seek,
+{
+    let write_err = |source| JoinError::WriteError { source };
+    let chain: Vec<u64> = TSpec::get_path_by_id(join_id).iter().filter_map(|p| match p {
+        PathPart::Id(id) => Some(*id),
+        PathPart::Global(_) => None,
+    }).collect();
+
+    let base = output.stream_position().map_err(write_err)?;
+    let mut written = 0;
+    let mut count = 0;
+
+    // Output position and header of each merged master
+    let mut merged: Vec<(usize, ElementHeader)> = Vec::new();
+
+    for input in inputs {
+        let mut walker: HeaderWalker<R, TSpec> = HeaderWalker::new(input);
+        let mut joining = false;
+
+        while let Some(header) = walker.next_header()? {
+            let depth = walker.depth();
+            let in_chain = depth >= chain.len() && walker.parents().take(chain.len()).eq(chain.iter().copied());
+            let in_merged = depth > 0 && depth <= chain.len() && walker.parents().eq(chain[..depth].iter().copied());
+            // Only `join_id` elements (and their children) are joined, so anything else between or after them is dropped
+            if depth <= chain.len() && header.id != join_id {
+                joining = false;
+            }
+
+            if in_chain && (joining || header.id == join_id) {
+                joining = true;
+                if header.id == join_id && depth == chain.len() {
+                    count += 1;
+                }
+                written += header.total_len().unwrap_or(header.header_len);
+                walker.copy_element(&header, &mut output, write_err)?;
+            } else if depth < chain.len() && header.id == chain[depth] && walker.parents().eq(chain[..depth].iter().copied()) {
+                if merged.len() == depth {
+                    let placeholder = ElementHeader { size: Unknown, header_len: header.id_len() + 8, ..header };
+                    merged.push((written, placeholder));
+                    let encoded = placeholder.encode();
+                    output.write_all(&encoded).map_err(write_err)?;
+                    written += encoded.len();
+                }
+                walker.descend(&header);
+            } else if count == 0 && !(header.id == CRC32_ID && in_merged) {
+                // Leading elements only come from the first document
+                written += header.total_len().unwrap_or(header.header_len);
+                walker.copy_element(&header, &mut output, write_err)?;
+            } else {
+                walker.skip_data(&header)?;
+            }
+        }
+    }
+
+    for (position, header) in merged {
+        let size = written - position - header.header_len;
+        output.seek(SeekFrom::Start(base + position as u64)).map_err(write_err)?;
+        output.write_all(&ElementHeader { size: Known(size), ..header }.encode()).map_err(write_err)?;
+    }
+    output.seek(SeekFrom::Start(base + written as u64)).map_err(write_err)?;
+    output.flush().map_err(write_err)?;
+
+    Ok(count)
+}
@@ -11,10 +11,34 @@
 //!
 //! # Features
 //!
-//! There is currently only one optional feature in this crate, but that may change over time as needs arise.
+//! There are currently only a few optional features in this crate, but that may change over time as needs arise.
 //!
 //! * **derive-spec** -
-//!     When enabled, this provides the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro to simplify implementation of the [`EbmlSpecification`][`specs::EbmlSpecification`] and [`EbmlTag`][`specs::EbmlTag`] traits.  This introduces dependencies on [`syn`](https://crates.io/crates/syn), [`quote`](https://crates.io/crates/quote), and [`proc-macro2`](https://crates.io/crates/proc-macro2), so expect compile times to increase a little.
+//!   When enabled, this provides the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro to simplify implementation of the [`EbmlSpecification`][`specs::EbmlSpecification`] and [`EbmlTag`][`specs::EbmlTag`] traits.  This introduces dependencies on [`syn`](https://crates.io/crates/syn), [`quote`](https://crates.io/crates/quote), and [`proc-macro2`](https://crates.io/crates/proc-macro2), so expect compile times to increase a little.
+//!
+//! * **dom** -
+//!   When enabled, this provides [`EbmlDocument`], which loads a whole document into memory as a tree of [`EbmlNode`]s that can be queried with XPath-like paths, modified, and written back out.
+//!
+//! * **test-utils** -
+//!   When enabled, this provides the [`test_utils`] module for property testing specifications using random documents generated by the [`arbitrary`](https://crates.io/crates/arbitrary) crate, and for checking that written documents conform to their spec.  This also enables **dom**.
+//!
+//! * **bytes** -
+//!   When enabled, the [`TagIterator`] reads into a reference counted [`bytes`](https://crates.io/crates/bytes) buffer and creates binary tags using [`EbmlSpecification::get_binary_tag_bytes()`][`specs::EbmlSpecification::get_binary_tag_bytes`].  Specifications whose binary variants hold `bytes::Bytes` receive slices of the read buffer rather than copies, so payloads can be handed to network code without copying.  It also adds [`TagIterator::from_buf()`] and [`TagWriter::from_buf_mut()`] for reading from a `Buf` and writing into a `BufMut` directly.
+//!
+//! * **metrics** -
+//!   When enabled, the counters tracked in [`ReadMetrics`] and [`WriteMetrics`] are also published through the [`metrics`](https://crates.io/crates/metrics) crate, so they can be exported by whichever recorder the application installs.
+//!
+//! * **digest** -
+//!   When enabled, this provides [`utils::digest_elements()`], [`utils::DigestStream`], [`utils::HashingReader`] and [`utils::HashingWriter`] for hashing elements, byte ranges or whole streams while data is read or written, using any hash implementing the [`digest`](https://crates.io/crates/digest) crate's `Digest` trait.
+//!
+//! * **cli** -
+//!   When enabled, this builds the `ebml-inspect` binary, which prints the element tree, an element id histogram, or a report of corrupted ranges for any EBML file (e.g. `ebml-inspect tree my_file.mkv`).  No specification is needed: elements whose data parses exactly into child elements are shown as "Master" elements.
+//!
+//! * **rayon** -
+//!   When enabled, this adds `par_children()` and `par_descendants()` to [`specs::Master`] so that work on the tags in a buffered [`specs::Master::Full`] tree can run in parallel using [`rayon`](https://crates.io/crates/rayon).
+//!
+//! * **serde** -
+//!   When enabled, this provides [`utils::SerializedTag`] and [`utils::SerdeTag`] so that tags from any specification (including whole "Master" trees) can be serialized to and from formats supported by [`serde`](https://crates.io/crates/serde), such as JSON, CBOR, or MessagePack.
 //!
 //! [EBML]: http://ebml.sourceforge.net/
 //! [webm]: https://www.webmproject.org/
@@ -26,19 +50,123 @@
 mod errors;
 mod tag_iterator;
 mod tag_writer;
+mod ebml_reader;
+#[cfg(feature = "futures")]
+mod ebml_reader_async;
+mod ebml_editor;
+#[cfg(feature = "dom")]
+mod ebml_document;
+mod header_walker;
+mod extract;
+mod redact;
+mod compact;
+mod splitter;
+mod join;
+mod push_decoder;
+mod patch;
+mod transform;
+mod stats;
+mod streaming_copier;
+mod tee_writer;
+mod interceptor;
+mod sizing_writer;
+mod seek_index;
+mod cue_builder;
+mod handler;
+mod profile;
+mod doctype;
+mod flatten;
+mod typed_reader;
+mod raw_frames;
+mod passthrough;
+mod migrate;
+mod watchdog;
+mod forkable_source;
+mod range_source;
+mod normalize;
+mod finalize;
+mod event_log;
+mod document_writer;
+mod attachments;
+#[cfg(feature = "digest")]
+mod element_digest;
+#[cfg(feature = "serde")]
+mod tag_serde;
 pub mod tools;
 pub mod specs;
 mod tag_iterator_util;
 mod spec_util;
 
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
 #[cfg(feature = "futures")]
 pub mod nonblocking;
 
-pub use self::tag_iterator::TagIterator;
-pub use self::tag_writer::{TagWriter, WriteOptions};
+pub use self::tag_iterator::{TagIterator, ElementReader};
+pub use self::tag_writer::{TagWriter, WriteOptions, WrittenElement};
+pub use self::document_writer::DocumentWriter;
+pub use self::interceptor::{InterceptingWriter, WriteInterceptor};
+pub use self::sizing_writer::SizingWriter;
+pub use self::seek_index::SeekIndex;
+pub use self::cue_builder::CueBuilder;
+pub use self::handler::{Handler, HandlerAction};
+pub use self::profile::Profile;
+pub use self::ebml_reader::{EbmlReader, ElementHandle, RevElements};
+pub use self::ebml_editor::EbmlEditor;
+#[cfg(feature = "dom")]
+pub use self::ebml_document::{EbmlDocument, EbmlNode};
+pub use self::push_decoder::PushDecoder;
+pub use self::transform::{ContentTransform, HeaderStripping};
+pub use self::stats::{ReadMetrics, WriteMetrics};
 
 pub mod iterator {
-    pub use super::tag_iterator_util::AllowableErrors;
+    pub use super::tag_iterator_util::{AllowableErrors, CrcMismatch, ElementLayout, ElementSize, SizePastEnd, TagEncoding, TagSpan, TrailingData, ZeroLengthValues};
+    pub use super::flatten::{FlattenValues, FlatValue};
+    pub use super::typed_reader::TypedReader;
+    pub use super::raw_frames::{RawFrames, RawFrame};
+    pub use super::forkable_source::ForkableSource;
+    pub use super::tag_iterator::{BorrowedTag, ReadEvent, PendingElement, WithSpans};
+    pub use super::event_log::{EndCause, EventLog, ParseEvent};
+}
+
+pub mod phase {
+
+    //!
+    //! Marker types for the phases of a [`DocumentWriter`](crate::DocumentWriter).
+    //!
+    pub use super::document_writer::{NeedsHeader, NeedsRoot, Body, Finished};
+}
+
+pub mod utils {
+
+    //!
+    //! Helpers for common operations on whole documents.
+    //!
+    pub use super::extract::{extract, ElementSelector};
+    pub use super::redact::{redact, redact_drop};
+    pub use super::compact::{compact_voids, compact_voids_in_place, VoidCompaction, CompactionSummary};
+    pub use super::spec_util::{can_contain, is_valid_path, parse_doc_path};
+    pub use super::splitter::Splitter;
+    pub use super::join::join;
+    pub use super::push_decoder::decode_slice;
+    #[cfg(feature = "bytes")]
+    pub use super::push_decoder::decode_bytes;
+    pub use super::handler::parse_with_handler;
+    pub use super::doctype::{sniff_doctype, SniffedDocType, DocTypeRegistry};
+    pub use super::streaming_copier::StreamingCopier;
+    pub use super::tee_writer::TeeWriter;
+    pub use super::passthrough::{passthrough, FrameAction};
+    pub use super::migrate::migrate;
+    pub use super::watchdog::WatchdogReader;
+    pub use super::range_source::{RangeSource, RangeReader};
+    pub use super::finalize::{finalize_sizes, finalize_sizes_two_pass};
+    pub use super::attachments::{list_attachments, extract_attachments, inject_attachment, AttachmentIds, AttachmentInfo};
+    pub use super::patch::{create_patch, apply_patch, Patch, PatchOperation, PathStep};
+    #[cfg(feature = "digest")]
+    pub use super::element_digest::{digest_elements, DigestStream, ElementDigest, HashingReader, HashingWriter};
+    #[cfg(feature = "serde")]
+    pub use super::tag_serde::{SerializedTag, SerializedValue, SerdeTag};
 }
 
 pub mod error {
@@ -48,7 +176,30 @@ pub mod error {
     //!
     pub use super::errors::tag_iterator::TagIteratorError;
     pub use super::errors::tag_iterator::CorruptedFileError;
+    pub use super::errors::tag_iterator::PartialTag;
     pub use super::errors::tag_writer::TagWriterError;
+    pub use super::errors::profile::ProfileViolation;
+    pub use super::errors::ebml_reader::EbmlReaderError;
+    pub use super::errors::ebml_editor::EbmlEditorError;
+    #[cfg(feature = "dom")]
+    pub use super::errors::ebml_document::EbmlDocumentError;
+    pub use super::errors::extract::ExtractError;
+    pub use super::errors::redact::RedactError;
+    pub use super::errors::compact::CompactError;
+    pub use super::errors::splitter::SplitterError;
+    pub use super::errors::join::JoinError;
+    pub use super::errors::patch::PatchError;
+    pub use super::errors::streaming_copier::StreamingCopierError;
+    pub use super::errors::passthrough::PassthroughError;
+    pub use super::errors::finalize::FinalizeError;
+    pub use super::errors::migrate::MigrateError;
+    pub use super::errors::typed_reader::TypedReadError;
+    pub use super::errors::doctype::DocTypeError;
+    pub use super::errors::attachments::AttachmentError;
+    #[cfg(feature = "digest")]
+    pub use super::errors::element_digest::DigestError;
+    #[cfg(feature = "serde")]
+    pub use super::errors::tag_serde::SerializedTagError;
 
     ///
     /// Error details that may be included in some thrown errors
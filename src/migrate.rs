@@ -0,0 +1,97 @@
+use crate::error::MigrateError;
+use crate::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use crate::tools;
+
+///
+/// Converts a tag of specification `A` into the tag with the same id in specification `B`.
+///
+/// This is for specifications that share ids, such as a project specific subset of Matroska and the full Matroska specification in another crate, so tags can be passed between them without matching on every variant.  Tags are matched by id and their data is carried over as-is, so the data type of the tag must be the same in both specifications.  The children of [`Master::Full`] tags are converted as well.
+///
+/// Raw tags (tags with ids `A` doesn't know) stay raw if `B` doesn't know them either.  If `B` does, their data is read as the tag's data type in `B`, except for "Master" tags, whose children aren't decoded.
+///
+/// ## Errors
+///
+/// Returns [`MigrateError::UnknownTag`] if `A` knows the id but `B` doesn't, [`MigrateError::TypeMismatch`] if the specifications disagree on the data type, and [`MigrateError::InvalidData`] if the data can't be represented in `B`.
+///
+/// # Panics
+///
+/// This can panic if `A` or `B` is an internally inconsistent specification (i.e. it claims that a specific tag variant is a specific data type but it is not).
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::utils::migrate;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let tag = EmptySpec::with_data(0x4286, &[0x01]);
+/// let migrated: EmptySpec = migrate(&tag).unwrap();
+/// assert_eq!(tag, migrated);
+/// ```
+///
+pub fn migrate<A, B>(tag: &A) -> Result<B, MigrateError>
+    where
+    A: EbmlSpecification<A> + EbmlTag<A> + Clone,
+    B: EbmlSpecification<B> + EbmlTag<B> + Clone
+{
+    let id = tag.get_id();
+    let (from, to) = match (A::get_tag_data_type(id), B::get_tag_data_type(id)) {
+        (None, None) => return Ok(B::get_raw_tag(id, raw_data(tag))),
+        (None, Some(to)) => return from_raw(id, to, raw_data(tag)),
+        (Some(_), None) => return Err(MigrateError::UnknownTag { id }),
+        (Some(from), Some(to)) => (from, to),
+    };
+    if from != to {
+        return Err(MigrateError::TypeMismatch { id, from, to });
+    }
+
+    let migrated = match from {
+        TagDataType::UnsignedInt => B::get_unsigned_int_tag(id, *tag.as_unsigned_int().unwrap_or_else(|| bad_spec(id, from))),
+        TagDataType::Integer => B::get_signed_int_tag(id, *tag.as_signed_int().unwrap_or_else(|| bad_spec(id, from))),
+        TagDataType::Utf8 => match tag.as_utf8() {
+            Some(val) => B::get_utf8_tag(id, val.to_string()),
+            None => from_utf8_bytes(id, tag.as_utf8_bytes().unwrap_or_else(|| bad_spec(id, from)).to_vec())?,
+        },
+        TagDataType::Binary => B::get_binary_tag(id, tag.as_binary().unwrap_or_else(|| bad_spec(id, from))),
+        TagDataType::Float => B::get_float_tag(id, *tag.as_float().unwrap_or_else(|| bad_spec(id, from))),
+        TagDataType::Master => match tag.as_master().unwrap_or_else(|| bad_spec(id, from)) {
+            Master::Start => B::get_master_tag(id, Master::Start),
+            Master::End => B::get_master_tag(id, Master::End),
+            Master::Full(children) => {
+                let children = children.iter().map(migrate).collect::<Result<Vec<B>, _>>()?;
+                B::get_master_tag(id, Master::Full(children))
+            },
+        },
+    };
+
+    Ok(migrated.unwrap_or_else(|| bad_spec(id, to)))
+}
+
+fn bad_spec<T>(id: u64, data_type: TagDataType) -> T {
+    panic!("Bad specification implementation: Tag id {} type was {:?}, but could not get tag!", id, data_type)
+}
+
+fn raw_data<A: EbmlSpecification<A> + EbmlTag<A> + Clone>(tag: &A) -> &[u8] {
+    tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was raw tag, but could not get binary data!", tag.get_id()))
+}
+
+fn from_utf8_bytes<B: EbmlSpecification<B> + EbmlTag<B> + Clone>(id: u64, data: Vec<u8>) -> Result<Option<B>, MigrateError> {
+    match B::get_utf8_tag_bytes(id, data) {
+        Some(Ok(tag)) => Ok(Some(tag)),
+        Some(Err(_)) => Err(MigrateError::InvalidData { id, expected: TagDataType::Utf8 }),
+        None => Ok(None),
+    }
+}
+
+fn from_raw<B: EbmlSpecification<B> + EbmlTag<B> + Clone>(id: u64, to: TagDataType, data: &[u8]) -> Result<B, MigrateError> {
+    let invalid = || MigrateError::InvalidData { id, expected: to };
+    let migrated = match to {
+        TagDataType::UnsignedInt => B::get_unsigned_int_tag(id, tools::arr_to_u64(data).map_err(|_| invalid())?),
+        TagDataType::Integer => B::get_signed_int_tag(id, tools::arr_to_i64(data).map_err(|_| invalid())?),
+        TagDataType::Utf8 => from_utf8_bytes(id, data.to_vec())?,
+        TagDataType::Binary => B::get_binary_tag(id, data),
+        TagDataType::Float => B::get_float_tag(id, tools::arr_to_f64(data).map_err(|_| invalid())?),
+        TagDataType::Master => return Err(invalid()),
+    };
+
+    Ok(migrated.unwrap_or_else(|| bad_spec(id, to)))
+}
@@ -1,21 +1,87 @@
-use std::io::Cursor;
+use std::future::Future;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 use ebml_iterable_specification::{EbmlSpecification, EbmlTag};
-use futures::{AsyncRead, AsyncReadExt, Stream};
-use crate::error::TagIteratorError;
-use crate::TagIterator;
+use futures::future::BoxFuture;
+use futures::{AsyncRead, AsyncWrite, FutureExt, Sink, Stream};
+use crate::error::{TagIteratorError, TagWriterError};
+use crate::push_decoder::PayloadStart;
+use crate::iterator::ElementSize;
+use crate::{PushDecoder, TagWriter};
+
+pub use crate::ebml_reader_async::{EbmlReaderAsync, ElementHandleAsync};
+
+const READ_CHUNK_LEN: usize = 1024 * 64;
+const WRITE_HIGH_WATER_LEN: usize = 1024 * 64;
+
+type Timer = Box<dyn FnMut(Duration) -> BoxFuture<'static, ()> + Send>;
+
+///
+/// Returns a [`Timer`] backed by a single background thread, which is started the first time the timer is armed and stops once the timer is dropped.
+///
+/// Arming the timer replaces any deadline that hasn't expired yet, so the thread only ever waits for the latest one.
+///
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn thread_timer() -> Timer {
+    use std::sync::mpsc::{self, RecvTimeoutError};
+    use std::time::Instant;
+    use futures::channel::oneshot;
+    type Deadline = (Instant, oneshot::Sender<()>);
+
+    let mut requests: Option<mpsc::Sender<Deadline>> = None;
+    Box::new(move |timeout| {
+        let (expire, expired) = oneshot::channel();
+        let requests = requests.get_or_insert_with(|| {
+            let (requests, received) = mpsc::channel::<Deadline>();
+            std::thread::spawn(move || {
+                let mut pending: Option<Deadline> = None;
+                loop {
+                    let next = match &pending {
+                        Some((deadline, _)) => received.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                        None => received.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                    };
+                    match next {
+                        Ok(request) => pending = Some(request),
+                        Err(RecvTimeoutError::Timeout) => {
+                            if let Some((_, expire)) = pending.take() {
+                                let _ = expire.send(());
+                            }
+                        },
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            });
+            requests
+        });
+        let _ = requests.send((Instant::now() + timeout, expire));
+        // A replaced deadline is never polled again, but one that is dropped without expiring must not count as a timeout
+        async move {
+            if expired.await.is_err() {
+                futures::future::pending::<()>().await;
+            }
+        }.boxed()
+    })
+}
 
 ///
 /// This can be transformed into a [`Stream`] using [`into_stream`][TagIteratorAsync::into_stream], or consumed directly by calling [`.next().await`] in a loop.
 ///
 /// The struct can be created with the [`new()`][TagIteratorAsync::new] function on any source that implements the [`futures::AsyncRead`] trait.
 ///
+/// Data is only read from the source when the tags read so far have all been emitted, and the space used by bytes that have already been decoded is reused.
+///
 pub struct TagIteratorAsync<R: AsyncRead + Unpin, TSpec>
     where
         TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
 {
     source: R,
-    buffer: Box<[u8]>,
-    iterator: TagIterator<Cursor<Vec<u8>>, TSpec>
+    decoder: PushDecoder<TSpec>,
+    bytes_read: usize,
+    read_timeout: Option<Duration>,
+    timer: Option<Timer>,
+    deadline: Option<BoxFuture<'static, ()>>,
 }
 
 impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
@@ -24,24 +90,130 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
 {
 
     pub fn new(source: R, tags_to_buffer: &[TSpec]) -> Self {
-        let buffer = vec![0u8; 1024 * 64];
         Self {
             source,
-            buffer: buffer.into_boxed_slice(), 
-            iterator: TagIterator::new(Cursor::new(Vec::new()), tags_to_buffer)
+            decoder: PushDecoder::new(tags_to_buffer),
+            bytes_read: 0,
+            read_timeout: None,
+            timer: None,
+            deadline: None,
         }
     }
 
+    ///
+    /// Configures how long a read from the source may wait for data before [`TagIteratorError::Timeout`] is returned.  The default is `None`, which waits indefinitely.
+    ///
+    /// This keeps a stalled source (such as a pipe whose writer has hung) from blocking the consumer forever.  The timer doesn't depend on an async runtime: a single background thread is started the first time a read has to wait for data, and is reused for every read after that.  Nothing is lost when a read times out, so calling [`Self::next()`] again continues waiting for the data.  Reads through an [`AsyncElementReader`] time out with an error of kind [`std::io::ErrorKind::TimedOut`].
+    ///
+    /// This isn't available on `wasm32-unknown-unknown`, where threads can't be started.  Use [`Self::set_read_timeout_with()`] to provide a timer from the async runtime instead.
+    ///
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) {
+        self.read_timeout = timeout;
+        self.timer = timeout.map(|_| thread_timer());
+        self.deadline = None;
+    }
+
+    ///
+    /// Configures how long a read from the source may wait for data before [`TagIteratorError::Timeout`] is returned, like [`Self::set_read_timeout()`], using `timer` to wait for the timeout.
+    ///
+    /// `timer` is called with the timeout each time a read has to wait for data, and the read times out when the returned future completes.  This lets the timeout use the async runtime's own timer (such as `tokio::time::sleep`) rather than a background thread.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use ebml_iterable::nonblocking::TagIteratorAsync;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut iter: TagIteratorAsync<_, EmptySpec> = TagIteratorAsync::new(&[0x42, 0x86, 0x81, 0x01][..], &[]);
+    /// // A timer that never expires, in place of something like `tokio::time::sleep`
+    /// iter.set_read_timeout_with(Some(Duration::from_secs(30)), |_| futures::future::pending());
+    /// ```
+    ///
+    pub fn set_read_timeout_with<T, F>(&mut self, timeout: Option<Duration>, mut timer: T)
+        where
+        T: FnMut(Duration) -> F + Send + 'static,
+        F: Future<Output = ()> + Send + 'static
+    {
+        self.read_timeout = timeout;
+        self.timer = timeout.map(|_| Box::new(move |timeout| timer(timeout).boxed()) as Timer);
+        self.deadline = None;
+    }
+
     pub async fn next(&mut self) -> Option<Result<TSpec, TagIteratorError>> {
-        match self.source.read(&mut self.buffer).await {
-            Ok(len) => {
-                self.iterator.get_mut().get_mut().append(&mut self.buffer[..len].to_vec());
-                self.iterator.next()
-            },
-            Err(e) => {
-                Some(Err(TagIteratorError::ReadError { source: e }))
+        loop {
+            if let Some(tag) = self.decoder.next_tag() {
+                return Some(tag);
             }
-        } 
+            if self.decoder.is_finished() {
+                return None;
+            }
+
+            if let Err(err) = self.fill().await {
+                return Some(Err(err));
+            }
+        }
+    }
+
+    ///
+    /// Returns an [`AsyncRead`] view over the payload of the next element, without waiting for (or buffering) the whole element.
+    ///
+    /// This is the async counterpart of [`TagIterator::next_element_reader()`][crate::TagIterator::next_element_reader()], and is useful for streaming large embedded data into another decoder.  The returned [`AsyncElementReader`] is limited to the element's payload.  Any part of it that isn't read is skipped by the next call to [`Self::next()`] or [`Self::next_element_reader()`].
+    ///
+    /// Returns `Ok(None)` if the next item is not an element with a payload (such as a "Master" tag), in which case [`Self::next()`] should be used to advance past it.
+    ///
+    pub async fn next_element_reader(&mut self) -> Result<Option<AsyncElementReader<'_, R, TSpec>>, TagIteratorError> {
+        loop {
+            match self.decoder.begin_payload()? {
+                PayloadStart::Started { tag_id, size } => return Ok(Some(AsyncElementReader { iterator: self, tag_id, size })),
+                PayloadStart::NotPayload => return Ok(None),
+                PayloadStart::NeedData => {},
+            }
+
+            self.fill().await?;
+        }
+    }
+
+    async fn fill(&mut self) -> Result<(), TagIteratorError> {
+        match futures::future::poll_fn(|cx| self.poll_fill(cx)).await {
+            Err(e) if e.kind() == ErrorKind::Interrupted => Ok(()),
+            Err(e) if e.kind() == ErrorKind::TimedOut => Err(TagIteratorError::Timeout { position: self.bytes_read, timeout: self.read_timeout }),
+            Err(e) => Err(TagIteratorError::ReadError { source: e }),
+            Ok(()) => Ok(()),
+        }
+    }
+
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        // Read straight into the decoder's buffer so the data isn't copied through an intermediate chunk
+        match Pin::new(&mut self.source).poll_read(cx, self.decoder.unfilled(READ_CHUNK_LEN)) {
+            Poll::Pending => {
+                if self.poll_deadline(cx).is_ready() {
+                    self.deadline = None;
+                    return Poll::Ready(Err(std::io::Error::new(ErrorKind::TimedOut, format!("no data was read within {:?}", self.read_timeout.unwrap_or_default()))));
+                }
+                Poll::Pending
+            },
+            Poll::Ready(result) => {
+                self.deadline = None;
+                match result? {
+                    0 => self.decoder.finish(),
+                    len => {
+                        self.bytes_read += len;
+                        self.decoder.commit(len);
+                    },
+                }
+                Poll::Ready(Ok(()))
+            },
+        }
+    }
+
+    fn poll_deadline(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let (timeout, timer) = match (self.read_timeout, self.timer.as_mut()) {
+            (Some(timeout), Some(timer)) => (timeout, timer),
+            _ => return Poll::Pending,
+        };
+        self.deadline.get_or_insert_with(|| timer(timeout)).poll_unpin(cx)
     }
 
     pub fn into_stream(self) -> impl Stream<Item=Result<TSpec, TagIteratorError>> {
@@ -52,6 +224,229 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
     }
 
     pub fn last_emitted_tag_offset(&self) -> usize {
-        self.iterator.last_emitted_tag_offset()
+        self.decoder.last_emitted_tag_offset()
+    }
+
+    pub fn last_emitted_tag_level(&self) -> usize {
+        self.decoder.last_emitted_tag_level()
+    }
+
+    pub fn last_emitted_tag_size(&self) -> ElementSize {
+        self.decoder.last_emitted_tag_size()
+    }
+}
+
+///
+/// An [`AsyncRead`] view over the payload of a single element, created by [`TagIteratorAsync::next_element_reader()`].
+///
+/// Reads are limited to the element's payload.  If the source ends before the payload is complete, reads fail with [`std::io::ErrorKind::UnexpectedEof`].
+///
+pub struct AsyncElementReader<'a, R: AsyncRead + Unpin, TSpec>
+    where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iterator: &'a mut TagIteratorAsync<R, TSpec>,
+    tag_id: u64,
+    size: usize,
+}
+
+impl<R: AsyncRead + Unpin, TSpec> AsyncElementReader<'_, R, TSpec>
+    where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns the id of the element being read.
+    ///
+    pub fn tag_id(&self) -> u64 {
+        self.tag_id
+    }
+
+    ///
+    /// Returns the total size of the element's payload.
+    ///
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    ///
+    /// Returns the number of payload bytes that haven't been read yet.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.iterator.decoder.payload_remaining()
+    }
+}
+
+impl<R: AsyncRead + Unpin, TSpec> AsyncRead for AsyncElementReader<'_, R, TSpec>
+    where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let iterator = &mut *this.iterator;
+        loop {
+            let remaining = iterator.decoder.payload_remaining();
+            if remaining == 0 || buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let bytes_read = iterator.decoder.read_payload(buf);
+            if bytes_read > 0 {
+                return Poll::Ready(Ok(bytes_read));
+            }
+            if iterator.decoder.is_finished() {
+                return Poll::Ready(Err(std::io::Error::new(ErrorKind::UnexpectedEof, format!("source ended with {} bytes of element 0x{:x} unread", remaining, this.tag_id))));
+            }
+
+            match iterator.poll_fill(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(())) => {},
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            }
+        }
     }
-}
\ No newline at end of file
+}
+
+///
+/// The async counterpart of [`TagWriter`], writing to a destination that implements [`futures::AsyncWrite`].
+///
+/// Tags are sent through the [`Sink`] implementation, so the writer can be used with [`SinkExt::send()`][futures::SinkExt::send()], [`SinkExt::send_all()`][futures::SinkExt::send_all()] or [`StreamExt::forward()`][futures::StreamExt::forward()].  Tags are encoded by an internal [`TagWriter`] into a buffer which is written out to the destination as it accepts data.  Sending waits for the destination while more than 64KiB of encoded data is waiting to be written.
+///
+/// Flushing the sink writes everything that can be written, but (unlike [`TagWriter::flush()`]) leaves open "Master" tags open.  Closing the sink ends any open tags before closing the destination.
+///
+/// ## Example
+///
+/// ```
+/// use futures::SinkExt;
+/// use ebml_iterable::nonblocking::TagWriterAsync;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # futures::executor::block_on(async {
+/// let mut writer = TagWriterAsync::new(Vec::new());
+/// writer.send(EmptySpec::with_data(0x4286, &[0x01])).await.unwrap();
+/// writer.close().await.unwrap();
+/// assert_eq!(vec![0x42, 0x86, 0x81, 0x01], writer.into_inner());
+/// # });
+/// ```
+///
+pub struct TagWriterAsync<W: AsyncWrite + Unpin> {
+    dest: W,
+    writer: TagWriter<Vec<u8>>,
+    written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> TagWriterAsync<W> {
+
+    ///
+    /// Returns a new [`TagWriterAsync`] instance.
+    ///
+    pub fn new(dest: W) -> Self {
+        Self {
+            dest,
+            writer: TagWriter::new(Vec::new()),
+            written: 0,
+        }
+    }
+
+    ///
+    /// Consumes self and returns the underlying write stream.
+    ///
+    /// Encoded data that hasn't been written yet is lost, so the sink should be flushed or closed first.
+    ///
+    pub fn into_inner(self) -> W {
+        self.dest
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying write stream.
+    ///
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.dest
+    }
+
+    ///
+    /// Gets a reference to the underlying write stream.
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.dest
+    }
+
+    ///
+    /// Writes all encoded data to the destination and flushes it, leaving open "Master" tags open.
+    ///
+    /// This is the same as flushing the [`Sink`], but doesn't need the tag type to be specified.
+    ///
+    pub async fn flush(&mut self) -> Result<(), TagWriterError> {
+        futures::future::poll_fn(|cx| self.poll_flush_dest(cx)).await
+    }
+
+    ///
+    /// Ends any open "Master" tags, writes all encoded data to the destination and closes it.
+    ///
+    /// This is the same as closing the [`Sink`], but doesn't need the tag type to be specified.
+    ///
+    pub async fn close(&mut self) -> Result<(), TagWriterError> {
+        futures::future::poll_fn(|cx| self.poll_close_dest(cx)).await
+    }
+
+    fn buffered_len(&self) -> usize {
+        self.writer.get_ref().len() - self.written
+    }
+
+    fn poll_flush_dest(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), TagWriterError>> {
+        futures::ready!(self.poll_write_buffered(cx))?;
+        Pin::new(&mut self.dest).poll_flush(cx).map_err(|source| TagWriterError::WriteError { source })
+    }
+
+    fn poll_close_dest(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), TagWriterError>> {
+        // Ending the open tags only appends to the encoded buffer, so this is safe to repeat if the destination isn't ready yet
+        self.writer.flush()?;
+        futures::ready!(self.poll_write_buffered(cx))?;
+        Pin::new(&mut self.dest).poll_close(cx).map_err(|source| TagWriterError::WriteError { source })
+    }
+
+    fn poll_write_buffered(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), TagWriterError>> {
+        loop {
+            let buffer = self.writer.get_ref();
+            if self.written == buffer.len() {
+                self.writer.get_mut().clear();
+                self.written = 0;
+                return Poll::Ready(Ok(()));
+            }
+
+            match Pin::new(&mut self.dest).poll_write(cx, &buffer[self.written..]) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(TagWriterError::WriteError { source: ErrorKind::WriteZero.into() })),
+                Poll::Ready(Ok(len)) => self.written += len,
+                Poll::Ready(Err(e)) if e.kind() == ErrorKind::Interrupted => {},
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(TagWriterError::WriteError { source: e })),
+            }
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin, TSpec> Sink<TSpec> for TagWriterAsync<W>
+    where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Error = TagWriterError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        if this.buffered_len() < WRITE_HIGH_WATER_LEN {
+            return Poll::Ready(Ok(()));
+        }
+        this.poll_write_buffered(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: TSpec) -> Result<(), Self::Error> {
+        self.get_mut().writer.write(&item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_flush_dest(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_close_dest(cx)
+    }
+}
@@ -0,0 +1,104 @@
+use crate::spec_util::{default_tag, validate_tag_path, CRC32_ID};
+use crate::tag_iterator_util::ElementSize::Known;
+
+use super::specs::{EbmlSpecification, EbmlTag, ElementMeta, Master, PathPart};
+
+///
+/// Rearranges the children of a [`Master::Full`] `tag` (being written below `parents`) to satisfy the spec described by `metadata`.
+///
+/// Throughout the tree:
+///  - Children that aren't allowed in their parent are moved up to the closest ancestor (within `tag`) that allows them, following the child they were found in.
+///  - Mandatory children (`min_occurs` of at least 1) that are missing are added with their default value, if the spec declares one.
+///  - A `CRC-32` child is moved first, followed by children that may only occur once in the order the spec declares them.  Children that may repeat keep their order after those, so sequences like the blocks of a cluster aren't shuffled.
+///
+/// Children that aren't allowed anywhere in `tag` are left in place for the writer to report.
+///
+pub(crate) fn normalize<TSpec>(tag: &TSpec, parents: &[u64], metadata: &[ElementMeta]) -> TSpec
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut path = parents.to_vec();
+    let min_depth = path.len() + 1;
+    // Nothing can be hoisted out of the root, since targets are limited to its descendants
+    normalize_tag(tag.clone(), &mut path, min_depth, metadata).0
+}
+
+///
+/// Normalizes `tag` below `path`, returning it along with any descendants that must move to a shallower parent (paired with the length of that parent's path).
+///
+fn normalize_tag<TSpec>(tag: TSpec, path: &mut Vec<u64>, min_depth: usize, metadata: &[ElementMeta]) -> (TSpec, Vec<(usize, TSpec)>)
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let id = tag.get_id();
+    let children = match tag.as_master() {
+        Some(Master::Full(children)) => children.clone(),
+        _ => return (tag, Vec::new()),
+    };
+
+    path.push(id);
+    let depth = path.len();
+    let mut normalized: Vec<TSpec> = Vec::with_capacity(children.len());
+    let mut hoisted: Vec<(usize, TSpec)> = Vec::new();
+    for child in children {
+        let (child, descendants) = normalize_tag(child, path, min_depth, metadata);
+        match hoist_target::<TSpec>(child.get_id(), path, min_depth) {
+            Some(target) => hoisted.push((target, child)),
+            None => normalized.push(child),
+        }
+        for (target, descendant) in descendants {
+            if target == depth {
+                normalized.push(descendant);
+            } else {
+                hoisted.push((target, descendant));
+            }
+        }
+    }
+
+    for meta in metadata.iter().filter(|meta| meta.min_occurs.unwrap_or(0) > 0 && !is_global::<TSpec>(meta.id) && is_allowed::<TSpec>(meta.id, path)) {
+        if let Some(default) = meta.default.and_then(|default| default_tag::<TSpec>(meta.id, meta.data_type, default)) {
+            let present = normalized.iter().filter(|child| child.get_id() == meta.id).count() as u64;
+            let missing = meta.min_occurs.unwrap_or(0).saturating_sub(present);
+            normalized.extend(std::iter::repeat_n(default, missing as usize));
+        }
+    }
+    path.pop();
+
+    normalized.sort_by_key(|child| order_key(child.get_id(), metadata));
+    let tag = TSpec::get_master_tag(id, Master::Full(normalized)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", id));
+    (tag, hoisted)
+}
+
+///
+/// Returns the length of the path of the closest ancestor that should hold `id` instead of the last element of `path`, if it isn't allowed there.
+///
+fn hoist_target<TSpec>(id: u64, path: &[u64], min_depth: usize) -> Option<usize>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    if TSpec::get_tag_data_type(id).is_none() || is_allowed::<TSpec>(id, path) {
+        return None;
+    }
+    (min_depth..path.len()).rev().find(|len| is_allowed::<TSpec>(id, &path[..*len]))
+}
+
+fn is_allowed<TSpec>(id: u64, path: &[u64]) -> bool
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    validate_tag_path::<TSpec>(id, path.iter().map(|id| (*id, Known(0), 0)))
+}
+
+fn is_global<TSpec>(id: u64) -> bool
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    TSpec::get_path_by_id(id).iter().any(|part| matches!(part, PathPart::Global(_)))
+}
+
+fn order_key(id: u64, metadata: &[ElementMeta]) -> usize {
+    if id == CRC32_ID {
+        return 0;
+    }
+    metadata.iter().position(|meta| meta.id == id && meta.max_occurs == Some(1)).map_or(usize::MAX, |index| index + 1)
+}
@@ -0,0 +1,100 @@
+use std::io::{Read, Write};
+
+use super::raw_frames::{RawFrame, RawFrames};
+use super::tag_writer::TagWriter;
+use super::spec_util::is_ended_by;
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::passthrough::PassthroughError;
+
+///
+/// What [`passthrough()`] does with a frame.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum FrameAction<TSpec> {
+
+    ///
+    /// Copy the frame byte for byte.
+    ///
+    Copy,
+
+    ///
+    /// Leave the frame out of the output.
+    ///
+    Drop,
+
+    ///
+    /// Write the given tags in place of the frame.
+    ///
+    Replace(Vec<TSpec>),
+}
+
+///
+/// Copies every element read from `source` to `dest`, calling `filter` for each [`RawFrame`] to decide what to do with it.
+///
+/// Frames that are copied are written exactly as they were read (including the lengths of their id and size vints), so untouched elements are bit-identical in the output.  As with [`RawFrames`], `filter` sees an element with a known size as a single frame, children included; the children of an element with an unknown size are passed to `filter` individually.  Dropping or replacing an element with an unknown size also leaves out all of its children.  Replacement tags are validated as children of the unknown sized elements they are written in.
+///
+/// `dest` is flushed once the source has been copied.
+///
+/// ## Errors
+///
+/// Returns a [`PassthroughError::ReadError`] if the source can't be read, and a [`PassthroughError::WriteError`] if writing fails or a replacement tag isn't valid at its position.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagWriter;
+/// use ebml_iterable::utils::{passthrough, FrameAction};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let data = [0x42, 0x86, 0x40, 0x01, 0x01, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'];
+/// let mut writer = TagWriter::new(Vec::new());
+/// passthrough(&data[..], &mut writer, |frame| match frame.id {
+///     0x4282 => FrameAction::Replace(vec![EmptySpec::with_data(0x4282, b"mkv")]),
+///     _ => FrameAction::Copy,
+/// }).unwrap();
+/// assert_eq!(vec![0x42, 0x86, 0x40, 0x01, 0x01, 0x42, 0x82, 0x83, b'm', b'k', b'v'], writer.into_inner().unwrap());
+/// ```
+///
+pub fn passthrough<TSpec, R: Read, W: Write>(source: R, dest: &mut TagWriter<W>, mut filter: impl FnMut(&RawFrame) -> FrameAction<TSpec>) -> Result<(), PassthroughError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    // The unknown sized frames that are still open, and whether each one was copied
+    let mut open: Vec<(u64, bool)> = Vec::new();
+
+    for frame in RawFrames::new(source) {
+        let frame = frame?;
+        while let Some(&(id, copied)) = open.last() {
+            if !is_ended_by::<TSpec>(id, frame.id) {
+                break;
+            }
+            open.pop();
+            if copied {
+                dest.end_encoded(id)?;
+            }
+        }
+        if open.iter().any(|(_, copied)| !copied) {
+            if frame.size.is_unknown() {
+                open.push((frame.id, false));
+            }
+            continue;
+        }
+
+        let action = filter(&frame);
+        if frame.size.is_unknown() {
+            open.push((frame.id, matches!(action, FrameAction::Copy)));
+        }
+        match action {
+            FrameAction::Copy => dest.write_encoded(frame.id, frame.size, &frame.header, &frame.payload)?,
+            FrameAction::Drop => {},
+            FrameAction::Replace(tags) => {
+                for tag in tags.iter() {
+                    dest.write(tag)?;
+                }
+            },
+        }
+    }
+
+    dest.flush()?;
+    Ok(())
+}
@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::tools::{self, Vint};
+use crate::{TagIterator, TagWriter};
+
+use super::specs::{EbmlSpecification, EbmlTag, Master};
+use super::errors::patch::PatchError;
+
+///
+/// A single step in the path to an element: the element id and which occurrence of that id (starting at 0) it is among its siblings.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PathStep {
+    pub id: u64,
+    pub occurrence: usize,
+}
+
+///
+/// A change to a single element.  All paths and indexes refer to positions in the *original* document, so operations don't affect each other.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchOperation<TSpec> {
+
+    ///
+    /// Replaces the element at `path` (and all of its children) with `tag`.
+    ///
+    Replace { path: Vec<PathStep>, tag: TSpec },
+
+    ///
+    /// Inserts `tag` as a child of the element at `parent`, before the child at `index` in the original document.  An `index` past the last child appends `tag`.  An empty `parent` refers to the top level of the document.
+    ///
+    Insert { parent: Vec<PathStep>, index: usize, tag: TSpec },
+
+    ///
+    /// Removes the element at `path` (and all of its children).
+    ///
+    Delete { path: Vec<PathStep> },
+}
+
+///
+/// A list of element level changes that transform one document into another.
+///
+/// Patches are created using [`create_patch()`] and applied using [`apply_patch()`].  They can be converted to and from a compact binary representation using [`Self::to_bytes()`] and [`Self::from_bytes()`], so that small changes (like metadata updates) can be sent without sending the entire document.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patch<TSpec> {
+    pub operations: Vec<PatchOperation<TSpec>>,
+}
+
+const REPLACE_OPERATION: u8 = 0;
+const INSERT_OPERATION: u8 = 1;
+const DELETE_OPERATION: u8 = 2;
+
+impl<TSpec> Patch<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns whether the patch contains no changes.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    ///
+    /// Encodes the patch in a compact binary format.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`PatchError::WriteError`] if a tag in the patch can't be encoded.
+    ///
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PatchError> {
+        let mut bytes = Vec::new();
+        for operation in self.operations.iter() {
+            match operation {
+                PatchOperation::Replace { path, tag } => {
+                    bytes.push(REPLACE_OPERATION);
+                    write_path(&mut bytes, path);
+                    write_tag(&mut bytes, &path[..path.len().saturating_sub(1)], tag)?;
+                },
+                PatchOperation::Insert { parent, index, tag } => {
+                    bytes.push(INSERT_OPERATION);
+                    write_path(&mut bytes, parent);
+                    write_number(&mut bytes, *index);
+                    write_tag(&mut bytes, parent, tag)?;
+                },
+                PatchOperation::Delete { path } => {
+                    bytes.push(DELETE_OPERATION);
+                    write_path(&mut bytes, path);
+                },
+            }
+        }
+        Ok(bytes)
+    }
+
+    ///
+    /// Decodes a patch from bytes created by [`Self::to_bytes()`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`PatchError::InvalidPatchData`] if `bytes` isn't a valid patch.
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PatchError> {
+        let mut reader = PatchReader { bytes, position: 0 };
+        let mut operations = Vec::new();
+        while reader.position < bytes.len() {
+            let kind = reader.read_byte()?;
+            let path = reader.read_path()?;
+            operations.push(match kind {
+                REPLACE_OPERATION => PatchOperation::Replace { tag: reader.read_tag()?, path },
+                INSERT_OPERATION => {
+                    let index = reader.read_number()?;
+                    PatchOperation::Insert { parent: path, index, tag: reader.read_tag()? }
+                },
+                DELETE_OPERATION => PatchOperation::Delete { path },
+                _ => return Err(PatchError::InvalidPatchData { position: reader.position - 1 }),
+            });
+        }
+        Ok(Patch { operations })
+    }
+}
+
+///
+/// Creates a [`Patch`] that transforms document `a` into document `b`.
+///
+/// Both documents are lists of top level tags, where "Master" tags are [`Master::Full`] variants (like those returned by a [`TagIterator`] that buffers all top level tags).  Children are matched up by id and occurrence, and only the differences are included in the patch.  If matching children appear in a different order in `b`, the parent is replaced entirely.
+///
+/// ## Example
+///
+/// ```no_run
+/// use ebml_iterable::utils::{create_patch, apply_patch};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let original = vec![EmptySpec::with_data(0x4286, &[0x01])];
+/// let updated = vec![EmptySpec::with_data(0x4286, &[0x02])];
+/// let patch = create_patch(&original, &updated);
+///
+/// let source = std::fs::File::open("original.ebml")?;
+/// let out = std::fs::File::create("updated.ebml")?;
+/// apply_patch(source, &patch, out)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+pub fn create_patch<TSpec>(a: &[TSpec], b: &[TSpec]) -> Patch<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + PartialEq
+{
+    let mut operations = Vec::new();
+    diff_children(&[], a, b, &mut operations);
+    Patch { operations }
+}
+
+fn child_steps<TSpec: EbmlTag<TSpec> + Clone>(children: &[TSpec]) -> Vec<PathStep> {
+    let mut occurrences: HashMap<u64, usize> = HashMap::new();
+    children.iter().map(|child| {
+        let occurrence = occurrences.entry(child.get_id()).or_insert(0);
+        *occurrence += 1;
+        PathStep { id: child.get_id(), occurrence: *occurrence - 1 }
+    }).collect()
+}
+
+fn diff_children<TSpec>(parent: &[PathStep], a: &[TSpec], b: &[TSpec], operations: &mut Vec<PatchOperation<TSpec>>)
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + PartialEq
+{
+    let a_steps = child_steps(a);
+    let b_steps = child_steps(b);
+    let with_parent = |step: &PathStep| parent.iter().chain(std::iter::once(step)).copied().collect::<Vec<_>>();
+
+    let common_in_a = a_steps.iter().filter(|s| b_steps.contains(s));
+    let common_in_b = b_steps.iter().filter(|s| a_steps.contains(s));
+    if !common_in_a.eq(common_in_b) {
+        // Children were reordered - it's simplest to start over
+        operations.extend(a_steps.iter().map(|step| PatchOperation::Delete { path: with_parent(step) }));
+        operations.extend(b.iter().map(|tag| PatchOperation::Insert { parent: parent.to_vec(), index: a.len(), tag: tag.clone() }));
+        return;
+    }
+
+    operations.extend(a_steps.iter().filter(|s| !b_steps.contains(s)).map(|step| PatchOperation::Delete { path: with_parent(step) }));
+
+    for (b_index, step) in b_steps.iter().enumerate() {
+        match a_steps.iter().position(|s| s == step) {
+            Some(a_index) => {
+                let (old, new) = (&a[a_index], &b[b_index]);
+                if old == new {
+                    continue;
+                }
+                match (old.as_master(), new.as_master()) {
+                    (Some(Master::Full(old_children)), Some(Master::Full(new_children))) => diff_children(&with_parent(step), old_children, new_children, operations),
+                    _ => operations.push(PatchOperation::Replace { path: with_parent(step), tag: new.clone() }),
+                }
+            },
+            None => {
+                // Insert before the next child that exists in both documents
+                let index = b_steps[(b_index + 1)..].iter()
+                    .find_map(|s| a_steps.iter().position(|a_step| a_step == s))
+                    .unwrap_or(a.len());
+                operations.push(PatchOperation::Insert { parent: parent.to_vec(), index, tag: b[b_index].clone() });
+            },
+        }
+    }
+}
+
+///
+/// Reads a document from `doc`, applies `patch`, and writes the result to `out`.
+///
+/// The document is processed as a stream of tags, so it doesn't need to fit in memory.  Operations targeting elements that don't exist in `doc` are ignored.
+///
+/// ## Errors
+///
+/// This method can error if there is a problem reading `doc` or writing to `out`.  The different possible error states are enumerated in [`PatchError`].
+///
+pub fn apply_patch<TSpec, R: Read, W: Write>(doc: R, patch: &Patch<TSpec>, out: W) -> Result<(), PatchError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut changes: HashMap<&[PathStep], Option<&TSpec>> = HashMap::new();
+    let mut inserts: HashMap<&[PathStep], Vec<(usize, &TSpec)>> = HashMap::new();
+    for operation in patch.operations.iter() {
+        match operation {
+            PatchOperation::Replace { path, tag } => { changes.insert(path, Some(tag)); },
+            PatchOperation::Delete { path } => { changes.insert(path, None); },
+            PatchOperation::Insert { parent, index, tag } => inserts.entry(parent).or_default().push((*index, tag)),
+        }
+    }
+
+    let mut writer = TagWriter::new(out);
+    let write_inserts = |writer: &mut TagWriter<W>, frame: &Frame, until: Option<usize>| -> Result<(), PatchError> {
+        for (_, tag) in inserts.get(frame.path.as_slice()).into_iter().flatten().filter(|(index, _)| *index == frame.next_index || (until.is_none() && *index > frame.next_index)) {
+            writer.write(*tag)?;
+        }
+        Ok(())
+    };
+
+    let mut frames = vec![Frame::new(Vec::new())];
+    let mut skip_depth = 0;
+    for tag in TagIterator::<R, TSpec>::new(doc, &[]) {
+        let tag = tag?;
+        let master = tag.as_master();
+
+        if skip_depth > 0 {
+            match master {
+                Some(Master::Start) => skip_depth += 1,
+                Some(Master::End) => skip_depth -= 1,
+                _ => {},
+            }
+            continue;
+        }
+
+        if let Some(Master::End) = master {
+            let frame = frames.pop().expect("frame should exist for open master");
+            write_inserts(&mut writer, &frame, None)?;
+            writer.write(&tag)?;
+            continue;
+        }
+
+        let frame = frames.last_mut().expect("top level frame should always exist");
+        write_inserts(&mut writer, frame, Some(frame.next_index))?;
+        let path = frame.child_path(tag.get_id());
+
+        match changes.get(path.as_slice()) {
+            Some(change) => {
+                if let Some(replacement) = change {
+                    writer.write(*replacement)?;
+                }
+                if let Some(Master::Start) = master {
+                    skip_depth = 1;
+                }
+            },
+            None => {
+                writer.write(&tag)?;
+                if let Some(Master::Start) = master {
+                    frames.push(Frame::new(path));
+                }
+            },
+        }
+    }
+
+    while let Some(frame) = frames.pop() {
+        write_inserts(&mut writer, &frame, None)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+///
+/// Tracks children of a master while applying a patch.
+///
+struct Frame {
+    path: Vec<PathStep>,
+    occurrences: HashMap<u64, usize>,
+    next_index: usize,
+}
+
+impl Frame {
+    fn new(path: Vec<PathStep>) -> Self {
+        Frame { path, occurrences: HashMap::new(), next_index: 0 }
+    }
+
+    ///
+    /// Returns the path to the next child (with id `id`) and advances the child counters.
+    ///
+    fn child_path(&mut self, id: u64) -> Vec<PathStep> {
+        let occurrence = self.occurrences.entry(id).or_insert(0);
+        let step = PathStep { id, occurrence: *occurrence };
+        *occurrence += 1;
+        self.next_index += 1;
+        self.path.iter().copied().chain(std::iter::once(step)).collect()
+    }
+}
+
+fn write_number(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend((value as u64).as_vint().expect("patch values should be representable as vints"));
+}
+
+fn write_path(bytes: &mut Vec<u8>, path: &[PathStep]) {
+    write_number(bytes, path.len());
+    for step in path {
+        bytes.extend_from_slice(&step.id.to_be_bytes()[(step.id.leading_zeros() / 8) as usize..]);
+        write_number(bytes, step.occurrence);
+    }
+}
+
+fn write_tag<TSpec>(bytes: &mut Vec<u8>, parent: &[PathStep], tag: &TSpec) -> Result<(), PatchError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut writer = TagWriter::new(Vec::new());
+    writer.assume_open_parents(&parent.iter().map(|s| s.id).collect::<Vec<_>>());
+    writer.write(tag)?;
+    let encoded = writer.into_inner()?;
+    write_number(bytes, encoded.len());
+    bytes.extend(encoded);
+    Ok(())
+}
+
+struct PatchReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl PatchReader<'_> {
+    fn invalid(&self) -> PatchError {
+        PatchError::InvalidPatchData { position: self.position }
+    }
+
+    fn read_byte(&mut self) -> Result<u8, PatchError> {
+        let byte = *self.bytes.get(self.position).ok_or_else(|| self.invalid())?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn read_number(&mut self) -> Result<usize, PatchError> {
+        let (value, len) = tools::read_vint(&self.bytes[self.position..]).ok().flatten().ok_or_else(|| self.invalid())?;
+        self.position += len;
+        Ok(value as usize)
+    }
+
+    fn read_path(&mut self) -> Result<Vec<PathStep>, PatchError> {
+        let len = self.read_number()?;
+        let mut path = Vec::new();
+        for _ in 0..len {
+            let first = *self.bytes.get(self.position).filter(|b| **b != 0).ok_or_else(|| self.invalid())?;
+            let id_len = first.leading_zeros() as usize + 1;
+            let id_bytes = self.bytes.get(self.position..(self.position + id_len)).ok_or_else(|| self.invalid())?;
+            let id = tools::arr_to_u64(id_bytes).map_err(|_| self.invalid())?;
+            self.position += id_len;
+            path.push(PathStep { id, occurrence: self.read_number()? });
+        }
+        Ok(path)
+    }
+
+    fn read_tag<TSpec>(&mut self) -> Result<TSpec, PatchError>
+        where
+        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        let len = self.read_number()?;
+        let start = self.position;
+        let data = self.bytes.get(start..(start + len)).ok_or_else(|| self.invalid())?;
+        self.position += len;
+
+        let first = *data.first().filter(|b| **b != 0).ok_or(PatchError::InvalidPatchData { position: start })?;
+        let id = tools::arr_to_u64(&data[..(first.leading_zeros() as usize + 1).min(data.len())]).map_err(|_| PatchError::InvalidPatchData { position: start })?;
+        let to_buffer: Vec<TSpec> = TSpec::get_master_tag(id, Master::Start).into_iter().collect();
+        let mut iter: TagIterator<&[u8], TSpec> = TagIterator::new(data, &to_buffer);
+        match iter.next() {
+            Some(tag) => Ok(tag?),
+            None => Err(PatchError::InvalidPatchData { position: start }),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::errors::profile::ProfileViolation;
+
+const SEGMENT_ID: u64 = 0x18538067;
+const CLUSTER_ID: u64 = 0x1F43B675;
+
+///
+/// Restrictions that a profile (such as WebM) places on top of a specification.
+///
+/// Specifications can declare their profiles as constants, e.g. `Profile::webm(&WEBM_IDS)`.  A profile can be enforced while writing with [`TagWriter::set_profile()`](crate::TagWriter::set_profile) to guarantee compliant output, or while reading with [`TagIterator::set_profile()`](crate::TagIterator::set_profile) to validate a file.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Profile {
+
+    ///
+    /// The ids of the elements allowed by the profile.  `None` allows every element.
+    ///
+    pub allowed_ids: Option<&'static [u64]>,
+
+    ///
+    /// The maximum length of an element id, in bytes.  `None` allows any length.
+    ///
+    pub max_id_length: Option<usize>,
+
+    ///
+    /// The ids of the elements that are allowed to have an unknown size.  `None` allows any "Master" element to have an unknown size.
+    ///
+    pub unknown_size_ids: Option<&'static [u64]>,
+}
+
+impl Profile {
+
+    ///
+    /// Returns a profile without any restrictions.
+    ///
+    pub const fn unrestricted() -> Self {
+        Profile {
+            allowed_ids: None,
+            max_id_length: None,
+            unknown_size_ids: None,
+        }
+    }
+
+    ///
+    /// Returns the WebM profile, given the ids of the elements WebM supports.
+    ///
+    /// Element ids are limited to 4 bytes, and only Segment and Cluster elements can have an unknown size.
+    ///
+    pub const fn webm(allowed_ids: &'static [u64]) -> Self {
+        Profile {
+            allowed_ids: Some(allowed_ids),
+            max_id_length: Some(4),
+            unknown_size_ids: Some(&[SEGMENT_ID, CLUSTER_ID]),
+        }
+    }
+
+    ///
+    /// Checks whether an element with the given id (and unknown size, if `unknown_size` is set) is allowed by the profile.
+    ///
+    pub fn check(&self, tag_id: u64, unknown_size: bool) -> Result<(), ProfileViolation> {
+        if let Some(max) = self.max_id_length {
+            let length = tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count();
+            if length > max {
+                return Err(ProfileViolation::IdTooLong { length });
+            }
+        }
+        if matches!(self.allowed_ids, Some(ids) if !ids.contains(&tag_id)) {
+            return Err(ProfileViolation::ElementNotAllowed);
+        }
+        if unknown_size && matches!(self.unknown_size_ids, Some(ids) if !ids.contains(&tag_id)) {
+            return Err(ProfileViolation::UnknownSizeNotAllowed);
+        }
+        Ok(())
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::unrestricted()
+    }
+}
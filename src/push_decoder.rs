@@ -0,0 +1,459 @@
+use std::io::Read;
+
+use crate::tag_iterator_util::{read_element_header, AllowableErrors, ZeroLengthValues};
+use crate::tag_iterator_util::ElementSize::{self, Known};
+use crate::{Profile, TagIterator};
+
+use super::specs::{EbmlSpecification, EbmlTag, TagDataType};
+use super::errors::tag_iterator::{PartialTag, TagIteratorError};
+
+///
+/// Decodes tags from data that is pushed in as it becomes available, rather than pulled from a [`std::io::Read`] source.
+///
+/// This is useful in environments where blocking reads aren't possible (such as `wasm32-unknown-unknown` in a browser), where data arrives in chunks from callbacks.  Data is added using [`Self::push()`] and tags are pulled out with [`Self::next_tag()`], which returns `None` once more data is needed.  The decoder only hands complete elements to its internal [`TagIterator`], so chunks can be split at any byte.  Once all data has been pushed, call [`Self::finish()`] so that any open "Master" tags are ended.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::PushDecoder;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut decoder: PushDecoder<EmptySpec> = PushDecoder::new(&[]);
+/// for chunk in [&[0x42, 0x86][..], &[0x81, 0x01][..]] {
+///   decoder.push(chunk);
+///   while let Some(tag) = decoder.next_tag() {
+///     println!("{:?}", tag);
+///   }
+/// }
+/// decoder.finish();
+/// while let Some(tag) = decoder.next_tag() {
+///   println!("{:?}", tag);
+/// }
+/// ```
+///
+pub struct PushDecoder<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iterator: TagIterator<PushSource, TSpec>,
+    tag_ids_to_buffer: Vec<u64>,
+    finished: bool,
+    payload: Option<StreamedPayload>,
+    unreleased_payload: usize,
+}
+
+///
+/// The element whose payload is being streamed out of the decoder with [`PushDecoder::read_payload()`].
+///
+struct StreamedPayload {
+    tag_id: u64,
+    tag_start: usize,
+    size: usize,
+    remaining: usize,
+}
+
+#[cfg(feature = "futures")]
+///
+/// The outcome of [`PushDecoder::begin_payload()`].
+///
+pub(crate) enum PayloadStart {
+    Started { tag_id: u64, size: usize },
+    NotPayload,
+    NeedData,
+}
+
+impl<TSpec> PushDecoder<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`PushDecoder<TSpec>`] instance.
+    ///
+    /// `tags_to_buffer` works the same as in [`TagIterator::new()`].  Buffered tags are only emitted once all of their data has been pushed, so "Master" tags with an unknown size that are buffered aren't emitted until [`Self::finish()`] is called.
+    ///
+    pub fn new(tags_to_buffer: &[TSpec]) -> Self {
+        let mut iterator = TagIterator::new(PushSource::default(), tags_to_buffer);
+        iterator.emit_master_end_when_eof(false);
+        PushDecoder {
+            iterator,
+            tag_ids_to_buffer: tags_to_buffer.iter().map(|tag| tag.get_id()).collect(),
+            finished: false,
+            payload: None,
+            unreleased_payload: 0,
+        }
+    }
+
+    ///
+    /// Configures how strictly the decoder abides `<TSpec>`.  See [`TagIterator::allow_errors()`].
+    ///
+    pub fn allow_errors(&mut self, errors: &[AllowableErrors]) {
+        self.iterator.allow_errors(errors);
+    }
+
+    ///
+    /// Configures the maximum size of a tag the decoder will accept.  See [`TagIterator::set_max_allowable_tag_size()`].
+    ///
+    pub fn set_max_allowable_tag_size(&mut self, size: Option<usize>) {
+        self.iterator.set_max_allowable_tag_size(size);
+    }
+
+    ///
+    /// Configures whether the decoder checks unsigned integer tags against the values allowed by `<TSpec>`.  See [`TagIterator::validate_restricted_values()`].
+    ///
+    pub fn validate_restricted_values(&mut self, validate: bool) {
+        self.iterator.validate_restricted_values(validate);
+    }
+
+    ///
+    /// Configures how the decoder handles number and string tags that have no data.  See [`TagIterator::set_zero_length_values()`].
+    ///
+    pub fn set_zero_length_values(&mut self, behavior: ZeroLengthValues) {
+        self.iterator.set_zero_length_values(behavior);
+    }
+
+    ///
+    /// Configures whether the decoder skips `CRC-32` elements instead of emitting them.  See [`TagIterator::skip_crc32_elements()`].
+    ///
+    pub fn skip_crc32_elements(&mut self, skip: bool) {
+        self.iterator.skip_crc32_elements(skip);
+    }
+
+    ///
+    /// Sets a [`Profile`] that every decoded tag must conform to.  See [`TagIterator::set_profile()`].
+    ///
+    pub fn set_profile(&mut self, profile: Option<Profile>) {
+        self.iterator.set_profile(profile);
+    }
+
+    ///
+    /// Adds data to the decoder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after [`Self::finish()`].
+    ///
+    pub fn push(&mut self, data: &[u8]) {
+        self.unfilled(data.len())[..data.len()].copy_from_slice(data);
+        self.commit(data.len());
+    }
+
+    #[cfg(feature = "bytes")]
+    ///
+    /// Adds the data in a [`bytes::Buf`] to the decoder, such as a [`bytes::Bytes`] received from a network stream or a JavaScript `Uint8Array`.
+    ///
+    /// The data is copied straight into the decoder's buffer, so chained buffers don't need to be made contiguous first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after [`Self::finish()`].
+    ///
+    pub fn push_buf<B: bytes::Buf>(&mut self, mut data: B) {
+        let len = data.remaining();
+        data.copy_to_slice(&mut self.unfilled(len)[..len]);
+        self.commit(len);
+    }
+
+    ///
+    /// Returns space (at least `min_len` bytes long) that data can be written into directly, avoiding the copy in [`Self::push()`].  Once written, the data is added with [`Self::commit()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after [`Self::finish()`].
+    ///
+    pub(crate) fn unfilled(&mut self, min_len: usize) -> &mut [u8] {
+        assert!(!self.finished, "`push` called after `finish`");
+        self.iterator.get_mut().unfilled(min_len)
+    }
+
+    ///
+    /// Adds the first `len` bytes written into [`Self::unfilled()`] to the decoder.
+    ///
+    pub(crate) fn commit(&mut self, len: usize) {
+        self.iterator.get_mut().filled += len;
+        self.release_complete_elements();
+    }
+
+    ///
+    /// Signals that no more data will be pushed.
+    ///
+    /// Any remaining data is handed to the decoder (incomplete elements will produce errors) and all open "Master" tags will be ended.
+    ///
+    pub fn finish(&mut self) {
+        self.finished = true;
+        self.iterator.emit_master_end_when_eof(true);
+        self.unreleased_payload = 0;
+        let source = self.iterator.get_mut();
+        source.released = source.filled;
+    }
+
+    ///
+    /// Returns whether [`Self::finish()`] has been called.
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    ///
+    /// Returns the number of bytes that have been pushed but are waiting for the rest of their element.
+    ///
+    pub fn pending_len(&self) -> usize {
+        let source = self.iterator.get_ref();
+        source.filled - source.released
+    }
+
+    ///
+    /// Returns the next decoded tag, or `None` if more data is needed (or if all data has been decoded after calling [`Self::finish()`]).
+    ///
+    pub fn next_tag(&mut self) -> Option<Result<TSpec, TagIteratorError>> {
+        match self.skip_payload()? {
+            Ok(()) => self.iterator.next(),
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    ///
+    /// Starts streaming the payload of the next element out through [`Self::read_payload()`], without waiting for the whole element to be pushed.
+    ///
+    /// Any unread part of a previously streamed payload is skipped first.
+    ///
+    pub(crate) fn begin_payload(&mut self) -> Result<PayloadStart, TagIteratorError> {
+        match self.skip_payload() {
+            None => return Ok(PayloadStart::NeedData),
+            Some(result) => result?,
+        }
+
+        if let Some(start) = self.iterator.begin_element_payload()? {
+            return Ok(self.start_payload(start));
+        }
+        if self.finished || !self.iterator.is_drained() {
+            return Ok(PayloadStart::NotPayload);
+        }
+
+        // Everything released so far has been emitted, so the next element is the incomplete one being staged.  Release just its header, and its payload as it arrives.
+        let source = self.iterator.get_mut();
+        let header = match read_element_header(&mut &source.data[source.released..source.filled], 0) {
+            Ok(Some(header)) => header,
+            Ok(None) | Err(TagIteratorError::UnexpectedEOF { .. }) => return Ok(PayloadStart::NeedData),
+            Err(_) => return Ok(PayloadStart::NotPayload),
+        };
+        let is_master = matches!(TSpec::get_tag_data_type(header.id), Some(TagDataType::Master));
+        let size = match header.size {
+            Known(size) if !is_master && !self.iterator.would_end_open_master(header.id) => size,
+            _ => return Ok(PayloadStart::NotPayload),
+        };
+
+        let source = self.iterator.get_mut();
+        source.released += header.header_len;
+        self.unreleased_payload = size;
+        self.release_complete_elements();
+
+        match self.iterator.begin_element_payload()? {
+            Some(start) => Ok(self.start_payload(start)),
+            None => Ok(PayloadStart::NotPayload),
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    fn start_payload(&mut self, (tag_id, tag_start, size): (u64, usize, usize)) -> PayloadStart {
+        self.payload = Some(StreamedPayload { tag_id, tag_start, size, remaining: size });
+        PayloadStart::Started { tag_id, size }
+    }
+
+    #[cfg(feature = "futures")]
+    ///
+    /// Returns the number of payload bytes that haven't been read since [`Self::begin_payload()`].
+    ///
+    pub(crate) fn payload_remaining(&self) -> usize {
+        self.payload.as_ref().map(|payload| payload.remaining).unwrap_or(0)
+    }
+
+    ///
+    /// Reads from the payload started by [`Self::begin_payload()`].  Returns 0 once the payload is complete, or when more data needs to be pushed.
+    ///
+    pub(crate) fn read_payload(&mut self, buf: &mut [u8]) -> usize {
+        let payload = match self.payload.as_mut() {
+            Some(payload) => payload,
+            None => return 0,
+        };
+
+        let len = buf.len().min(payload.remaining);
+        let bytes_read = self.iterator.read_payload(&mut buf[..len]).expect("reading pushed data should never fail");
+        payload.remaining -= bytes_read;
+        if payload.remaining == 0 {
+            self.payload = None;
+        }
+        bytes_read
+    }
+
+    ///
+    /// Skips whatever is left of a streamed payload.  Returns `None` if more data needs to be pushed.
+    ///
+    fn skip_payload(&mut self) -> Option<Result<(), TagIteratorError>> {
+        let mut scratch = [0u8; 4096];
+        while self.payload.is_some() {
+            if self.read_payload(&mut scratch) == 0 {
+                if !self.finished {
+                    return None;
+                }
+                let payload = self.payload.take().unwrap();
+                return Some(Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start: payload.tag_start, id: Some(payload.tag_id), size: Some(payload.size), header_len: None, obtained: payload.size - payload.remaining, data: None })));
+            }
+        }
+        Some(Ok(()))
+    }
+
+    ///
+    /// Returns the byte offset of the start of the last emitted tag.  See [`TagIterator::last_emitted_tag_offset()`].
+    ///
+    pub fn last_emitted_tag_offset(&self) -> usize {
+        self.iterator.last_emitted_tag_offset()
+    }
+
+    ///
+    /// Returns the nesting level of the last emitted tag.  See [`TagIterator::last_emitted_tag_level()`].
+    ///
+    pub fn last_emitted_tag_level(&self) -> usize {
+        self.iterator.last_emitted_tag_level()
+    }
+
+    ///
+    /// Returns the data size declared by the last emitted tag.  See [`TagIterator::last_emitted_tag_size()`].
+    ///
+    pub fn last_emitted_tag_size(&self) -> ElementSize {
+        self.iterator.last_emitted_tag_size()
+    }
+
+    ///
+    /// Releases every complete element following the already released data to the iterator.
+    ///
+    /// Only the header of a "Master" element needs to be present (unless it's being buffered), since its children are released separately.
+    ///
+    fn release_complete_elements(&mut self) {
+        let source = self.iterator.get_mut();
+        let staged = &source.data[source.released..source.filled];
+        let mut released = self.unreleased_payload.min(staged.len());
+        self.unreleased_payload -= released;
+        // A streamed payload is released as it arrives, and the elements after it once it is complete
+        if self.unreleased_payload == 0 {
+            loop {
+                let mut remaining = &staged[released..];
+                let header = match read_element_header(&mut remaining, 0) {
+                    Ok(Some(header)) => header,
+                    Ok(None) | Err(TagIteratorError::UnexpectedEOF { .. }) => break,
+                    Err(_) => {
+                        // Let the iterator report the corrupted data
+                        released = staged.len();
+                        break;
+                    },
+                };
+
+                let is_master = matches!(TSpec::get_tag_data_type(header.id), Some(TagDataType::Master));
+                let len = match header.size {
+                    Known(size) if !is_master || self.tag_ids_to_buffer.contains(&header.id) => header.header_len + size,
+                    _ if is_master && self.tag_ids_to_buffer.contains(&header.id) => break,
+                    _ => header.header_len,
+                };
+                if released + len > staged.len() {
+                    break;
+                }
+                released += len;
+            }
+        }
+
+        source.released += released;
+    }
+}
+
+///
+/// A [`std::io::Read`] source that returns whatever data has been released to it, and reports EOF when empty.
+///
+/// `data[read_position..released]` is waiting to be read, `data[released..filled]` is staged until its element is complete, and anything after `filled` is spare space for the next push.
+///
+#[derive(Default)]
+struct PushSource {
+    data: Vec<u8>,
+    read_position: usize,
+    released: usize,
+    filled: usize,
+}
+
+impl PushSource {
+    fn unfilled(&mut self, min_len: usize) -> &mut [u8] {
+        // Only shift the unread data down once at least as much has been read, so each byte is moved a bounded number of times
+        if self.read_position > 0 && self.read_position >= self.filled - self.read_position {
+            self.data.copy_within(self.read_position..self.filled, 0);
+            self.released -= self.read_position;
+            self.filled -= self.read_position;
+            self.read_position = 0;
+        }
+        if self.data.len() - self.filled < min_len {
+            self.data.resize(self.filled + min_len, 0);
+        }
+        &mut self.data[self.filled..]
+    }
+}
+
+impl Read for PushSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.released - self.read_position);
+        buf[..len].copy_from_slice(&self.data[self.read_position..(self.read_position + len)]);
+        self.read_position += len;
+        Ok(len)
+    }
+}
+
+///
+/// Decodes every tag in `data`.
+///
+/// This is a convenience for callers that already hold an entire document in memory (such as a browser `ArrayBuffer` copied into wasm memory).  `tags_to_buffer` works the same as in [`TagIterator::new()`].
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::utils::decode_slice;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let tags = decode_slice::<EmptySpec>(&[0x42, 0x86, 0x81, 0x01], &[]).unwrap();
+/// assert_eq!(vec![EmptySpec::with_data(0x4286, &[0x01])], tags);
+/// ```
+///
+/// ## Errors
+///
+/// Returns the first error encountered while decoding.  The different possible error states are enumerated in [`TagIteratorError`].
+///
+pub fn decode_slice<TSpec>(data: &[u8], tags_to_buffer: &[TSpec]) -> Result<Vec<TSpec>, TagIteratorError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    TagIterator::new(data, tags_to_buffer).collect()
+}
+
+#[cfg(feature = "bytes")]
+///
+/// Decodes every tag in a [`bytes::Bytes`] buffer.
+///
+/// This is the same as [`decode_slice()`] for callers that hold the document as [`bytes::Bytes`].  The buffer is read in place rather than copied up front.
+///
+/// ## Example
+///
+/// ```
+/// use bytes::Bytes;
+/// use ebml_iterable::utils::decode_bytes;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let tags = decode_bytes::<EmptySpec>(Bytes::from_static(&[0x42, 0x86, 0x81, 0x01]), &[]).unwrap();
+/// assert_eq!(vec![EmptySpec::with_data(0x4286, &[0x01])], tags);
+/// ```
+///
+/// ## Errors
+///
+/// Returns the first error encountered while decoding.  The different possible error states are enumerated in [`TagIteratorError`].
+///
+pub fn decode_bytes<TSpec>(data: bytes::Bytes, tags_to_buffer: &[TSpec]) -> Result<Vec<TSpec>, TagIteratorError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    TagIterator::from_buf(data, tags_to_buffer).collect()
+}
@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+const DEFAULT_CHUNK_LEN: usize = 256 * 1024;
+const DEFAULT_READ_AHEAD: usize = 1;
+const DEFAULT_CACHED_CHUNKS: usize = 16;
+
+///
+/// A source that can fetch arbitrary byte ranges, such as a file behind HTTP range requests or object storage GETs.
+///
+/// Wrap it in a [`RangeReader`] to use it anywhere a [`Read`] + [`Seek`] source is accepted, like an [`EbmlReader`][crate::EbmlReader].  Any `FnMut(Range<u64>) -> std::io::Result<Vec<u8>>` closure is a [`RangeSource`] whose length isn't known.
+///
+pub trait RangeSource {
+
+    ///
+    /// Fetches the bytes in `range`.
+    ///
+    /// Fewer bytes than requested may only be returned if the source ends before `range.end`.
+    ///
+    fn fetch(&mut self, range: Range<u64>) -> std::io::Result<Vec<u8>>;
+
+    ///
+    /// Returns the total length of the source, if it is known.  Needed for seeking relative to the end of the source.
+    ///
+    fn total_len(&mut self) -> std::io::Result<Option<u64>> {
+        Ok(None)
+    }
+}
+
+impl<F: FnMut(Range<u64>) -> std::io::Result<Vec<u8>>> RangeSource for F {
+    fn fetch(&mut self, range: Range<u64>) -> std::io::Result<Vec<u8>> {
+        self(range)
+    }
+}
+
+///
+/// Reads from a [`RangeSource`], fetching it in chunks and caching the chunks fetched most recently.
+///
+/// Each fetch covers the chunk being read plus a configurable number of chunks after it, so sequential reads need few requests while seeking over skipped elements doesn't fetch their data.  Chunks are cached so that re-reading nearby data (such as headers read while locating an element and then again while iterating it) doesn't fetch it again.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::ops::Range;
+/// use ebml_iterable::EbmlReader;
+/// use ebml_iterable::utils::RangeReader;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+/// # fn http_get_range(url: &str, range: Range<u64>) -> std::io::Result<Vec<u8>> { unimplemented!() }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let source = RangeReader::new(|range: Range<u64>| http_get_range("https://example.com/file.ebml", range));
+/// let mut reader: EbmlReader<_, EmptySpec> = EbmlReader::new(source);
+/// let tags = reader.open("0x18538067/0x1654ae6b")?.buffer()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct RangeReader<S: RangeSource> {
+    source: S,
+    position: u64,
+    chunk_len: usize,
+    read_ahead: usize,
+    max_chunks: usize,
+
+    /// The end of the source, once a fetch has reached it
+    end: Option<u64>,
+
+    /// Recently fetched chunks, keyed by their (chunk aligned) start position, most recently used last
+    chunks: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl<S: RangeSource> RangeReader<S> {
+
+    ///
+    /// Returns a new [`RangeReader`] starting at position 0 of `source`.
+    ///
+    /// By default, data is fetched in chunks of 256KiB, each fetch reads one chunk ahead, and up to 16 chunks are cached.
+    ///
+    pub fn new(source: S) -> Self {
+        RangeReader {
+            source,
+            position: 0,
+            chunk_len: DEFAULT_CHUNK_LEN,
+            read_ahead: DEFAULT_READ_AHEAD,
+            max_chunks: DEFAULT_CACHED_CHUNKS,
+            end: None,
+            chunks: VecDeque::new(),
+        }
+    }
+
+    ///
+    /// Configures the size of the chunks data is fetched and cached in.  Clears the cache.
+    ///
+    pub fn set_chunk_len(&mut self, len: usize) {
+        self.chunk_len = len.max(1);
+        self.chunks.clear();
+    }
+
+    ///
+    /// Configures how many chunks after the one being read are fetched along with it.
+    ///
+    pub fn set_read_ahead(&mut self, chunks: usize) {
+        self.read_ahead = chunks;
+    }
+
+    ///
+    /// Configures the maximum number of chunks kept in the cache.
+    ///
+    pub fn set_cached_chunks(&mut self, chunks: usize) {
+        self.max_chunks = chunks.max(1);
+        while self.chunks.len() > self.max_chunks {
+            self.chunks.pop_front();
+        }
+    }
+
+    ///
+    /// Consumes self and returns the underlying source.
+    ///
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    ///
+    /// Gets a mutable reference to the underlying source.
+    ///
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    ///
+    /// Gets a reference to the underlying source.
+    ///
+    pub fn get_ref(&self) -> &S {
+        &self.source
+    }
+
+    fn chunk(&mut self, start: u64) -> std::io::Result<&[u8]> {
+        match self.chunks.iter().position(|(chunk_start, _)| *chunk_start == start) {
+            Some(index) => {
+                let chunk = self.chunks.remove(index).expect("index should be in bounds");
+                self.chunks.push_back(chunk);
+            },
+            None => {
+                let chunk_len = self.chunk_len as u64;
+                let fetch_len = chunk_len * (1 + self.read_ahead as u64);
+                let data = self.source.fetch(start..(start + fetch_len))?;
+                if data.len() as u64 > fetch_len {
+                    return Err(std::io::Error::new(ErrorKind::InvalidData, format!("range source returned {} bytes when {} were requested", data.len(), fetch_len)));
+                }
+                if (data.len() as u64) < fetch_len {
+                    self.end = Some(start + data.len() as u64);
+                }
+
+                // A short (or empty) chunk is the end of the source
+                let mut fetched = data.chunks(self.chunk_len).enumerate().map(|(i, chunk)| (start + i as u64 * chunk_len, chunk.to_vec()));
+                let requested = fetched.next().unwrap_or((start, Vec::new()));
+                for chunk in fetched {
+                    self.insert(chunk);
+                }
+                // The chunk being read goes last so it's the most recently used
+                self.insert(requested);
+            },
+        }
+        Ok(&self.chunks.back().expect("chunk was just added").1)
+    }
+
+    fn insert(&mut self, chunk: (u64, Vec<u8>)) {
+        self.chunks.retain(|(start, _)| *start != chunk.0);
+        if self.chunks.len() >= self.max_chunks {
+            self.chunks.pop_front();
+        }
+        self.chunks.push_back(chunk);
+    }
+}
+
+impl<S: RangeSource> Read for RangeReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || matches!(self.end, Some(end) if self.position >= end) {
+            return Ok(0);
+        }
+        let offset = (self.position % self.chunk_len as u64) as usize;
+        let chunk = self.chunk(self.position - offset as u64)?;
+        let available = chunk.get(offset..).unwrap_or_default();
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl<S: RangeSource> Seek for RangeReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => {
+                let len = self.source.total_len()?.ok_or_else(|| std::io::Error::new(ErrorKind::Unsupported, "range source length is unknown"))?;
+                len.checked_add_signed(offset)
+            },
+        }.ok_or_else(|| std::io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative position"))?;
+        Ok(self.position)
+    }
+}
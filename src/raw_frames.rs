@@ -0,0 +1,133 @@
+use std::io::Read;
+
+use crate::tag_iterator_util::{read_element_header, ElementSize};
+use crate::tag_iterator_util::ElementSize::Known;
+
+use super::errors::tag_iterator::{CorruptedFileError, PartialTag, TagIteratorError};
+
+///
+/// An element read by [`RawFrames`], exactly as it is encoded in the source.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawFrame {
+
+    ///
+    /// The id of the element.
+    ///
+    pub id: u64,
+
+    ///
+    /// The data size declared in the element's header.
+    ///
+    pub size: ElementSize,
+
+    ///
+    /// The encoded header (id and size) of the element.
+    ///
+    pub header: Vec<u8>,
+
+    ///
+    /// The data of the element, or nothing if it has an unknown size.
+    ///
+    pub payload: Vec<u8>,
+}
+
+///
+/// An iterator over the elements of a source that yields each one as an undecoded [`RawFrame`].
+///
+/// No specification is involved, so elements are never decoded or validated: a proxy can route or filter them by id and write `header` followed by `payload` to re-emit them verbatim.  Since it isn't known which elements are "Master" elements, an element with a known size is always yielded as a single frame (children included).  An element with an unknown size is yielded with an empty payload, and its children follow as frames of their own.
+///
+/// Iteration ends after the first error.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::iterator::RawFrames;
+///
+/// let data = [0x42, 0x86, 0x81, 0x01, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'];
+/// let ids: Vec<u64> = RawFrames::new(&data[..]).map(|frame| frame.unwrap().id).collect();
+/// assert_eq!(vec![0x4286, 0x4282], ids);
+/// ```
+///
+pub struct RawFrames<R: Read> {
+    source: R,
+    position: usize,
+    max_frame_size: Option<usize>,
+    finished: bool,
+}
+
+impl<R: Read> RawFrames<R> {
+
+    ///
+    /// Returns a new [`RawFrames`] iterator over the elements read from `source`.
+    ///
+    pub fn new(source: R) -> Self {
+        RawFrames {
+            source,
+            position: 0,
+            max_frame_size: Some(4 * usize::pow(1000, 3)), // 4GB
+            finished: false,
+        }
+    }
+
+    ///
+    /// Configures the maximum payload size of a frame before the iterator considers it invalid.  See [`TagIterator::set_max_allowable_tag_size()`](crate::TagIterator::set_max_allowable_tag_size).
+    ///
+    pub fn set_max_frame_size(&mut self, size: Option<usize>) {
+        self.max_frame_size = size;
+    }
+
+    ///
+    /// Returns the offset in the source right after the last yielded frame.
+    ///
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    ///
+    /// Consumes self and returns the underlying read stream.
+    ///
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    fn read_frame(&mut self) -> Result<Option<RawFrame>, TagIteratorError> {
+        let tag_start = self.position;
+        let header = match read_element_header(&mut self.source, tag_start)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        let mut payload = Vec::new();
+        if let Known(size) = header.size {
+            if matches!(self.max_frame_size, Some(max_size) if size > max_size) {
+                return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagSize { position: tag_start, tag_id: header.id, size }));
+            }
+            (&mut self.source).take(size as u64).read_to_end(&mut payload).map_err(|source| TagIteratorError::ReadError { source })?;
+            if payload.len() < size {
+                return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start, id: Some(header.id), size: Some(size), header_len: Some(header.header_len), obtained: payload.len(), data: Some(payload) }));
+            }
+        }
+        self.position += header.header_len + payload.len();
+
+        Ok(Some(RawFrame {
+            id: header.id,
+            size: header.size,
+            header: header.encode(),
+            payload,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for RawFrames<R> {
+    type Item = Result<RawFrame, TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.read_frame().transpose();
+        self.finished = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::header_walker::HeaderWalker;
+use crate::tag_iterator_util::ElementHeader;
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::redact::RedactError;
+
+///
+/// Copies a document from `source` to `dest`, replacing every element with an id in `ids` with a `Void` element of the same length.
+///
+/// Everything else is copied byte for byte (only element headers are parsed), so all offsets of untouched data are preserved.  This makes it useful for scrubbing metadata from files without invalidating any indexes (like cues) into them.  Returns the number of elements that were redacted.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::utils::redact;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let source = File::open("my_ebml_file.ebml")?;
+/// let dest = File::create("redacted.ebml")?;
+/// // Remove the "Title" element
+/// redact::<EmptySpec, _, _>(source, dest, &[0x7ba9])?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Errors
+///
+/// Returns [`RedactError::UnknownSize`] if an element to redact has an unknown size.  The other possible error states are enumerated in [`RedactError`].
+///
+pub fn redact<TSpec, R: Read, W: Write>(source: R, mut dest: W, ids: &[u64]) -> Result<usize, RedactError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    copy_redacted::<TSpec, _, _>(source, &mut dest, ids, None)
+}
+
+///
+/// Copies a document from `source` to `dest`, leaving out every element with an id in `ids`.
+///
+/// The sizes of known-size master elements containing removed elements are updated by seeking back in `dest` once the master has been written (the size vint keeps its original length).  Everything else is copied byte for byte.  Unlike [`redact()`], this changes the offsets of all data following a removed element.  Returns the number of elements that were removed.
+///
+/// ## Errors
+///
+/// Returns [`RedactError::UnknownSize`] if an element to remove has an unknown size.  The other possible error states are enumerated in [`RedactError`].
+///
+pub fn redact_drop<TSpec, R: Read, W: Write + Seek>(source: R, mut dest: W, ids: &[u64]) -> Result<usize, RedactError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let base = dest.stream_position().map_err(|source| RedactError::WriteError { source })?;
+    let mut patch_header = |dest: &mut W, position: usize, header: &[u8], resume: usize| -> std::io::Result<()> {
+        dest.seek(SeekFrom::Start(base + position as u64))?;
+        dest.write_all(header)?;
+        dest.seek(SeekFrom::Start(base + resume as u64))?;
+        Ok(())
+    };
+    copy_redacted::<TSpec, _, _>(source, &mut dest, ids, Some(&mut patch_header))
+}
+
+type PatchHeader<'a, W> = &'a mut dyn FnMut(&mut W, usize, &[u8], usize) -> std::io::Result<()>;
+
+///
+/// Redacts by writing Voids if `patch_header` is `None`, otherwise drops elements and uses `patch_header` to rewrite the headers of masters whose size changed.
+///
+fn copy_redacted<TSpec, R: Read, W: Write>(source: R, dest: &mut W, ids: &[u64], mut patch_header: Option<PatchHeader<'_, W>>) -> Result<usize, RedactError>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let write_err = |source| RedactError::WriteError { source };
+    let mut walker: HeaderWalker<R, TSpec> = HeaderWalker::new(source);
+    let mut written = 0;
+    let mut count = 0;
+
+    // Output position and original header of each master we're inside of (only tracked when dropping)
+    let mut open: Vec<(usize, ElementHeader)> = Vec::new();
+
+    loop {
+        let next = walker.next_header()?;
+
+        if let Some(patch_header) = patch_header.as_mut() {
+            while open.len() > walker.depth() {
+                let (position, header) = open.pop().expect("open should not be empty");
+                let size = written - position - header.header_len;
+                if matches!(header.size, Known(original) if original != size) {
+                    patch_header(dest, position, &ElementHeader { size: Known(size), ..header }.encode(), written).map_err(write_err)?;
+                }
+            }
+        }
+
+        let header = match next {
+            Some(header) => header,
+            None => break,
+        };
+
+        if ids.contains(&header.id) {
+            let total_len = header.total_len().ok_or(RedactError::UnknownSize { tag_id: header.id, position: walker.position() - header.header_len })?;
+            // The element is skipped first so that nothing is written for data the source doesn't actually have
+            walker.skip_data(&header)?;
+            if patch_header.is_none() {
+                let void = ElementHeader::void(total_len).encode();
+                dest.write_all(&void).map_err(write_err)?;
+                std::io::copy(&mut std::io::repeat(0).take((total_len - void.len()) as u64), dest).map_err(write_err)?;
+                written += total_len;
+            }
+            count += 1;
+            continue;
+        }
+
+        let encoded = header.encode();
+        dest.write_all(&encoded).map_err(write_err)?;
+        match header.size {
+            Known(size) if !HeaderWalker::<R, TSpec>::is_master(&header) => {
+                written += encoded.len();
+                walker.copy_data(size, dest, write_err)?;
+                written += size;
+            },
+            Known(_) | Unknown => {
+                if patch_header.is_some() {
+                    open.push((written, header));
+                }
+                written += encoded.len();
+                walker.descend(&header);
+            },
+        }
+    }
+
+    dest.flush().map_err(write_err)?;
+    Ok(count)
+}
@@ -0,0 +1,104 @@
+use std::io::{Seek, Write};
+
+use super::tag_writer::TagWriter;
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::tag_writer::TagWriterError;
+
+///
+/// Builds an index of where certain elements were written, in the style of Matroska's `SeekHead`.
+///
+/// The index is written into space reserved (using [`TagWriter::reserve_bookmark()`]) before the indexed elements, once they have all been written.  This type doesn't know the ids of the index elements themselves: when finishing, the caller turns the collected `(id, offset)` entries into a tag of their own specification.  Offsets are relative to the start of the data of the "Master" element that contains the index (e.g. the `Segment` in Matroska), or to the first byte written by the writer if the index is at the top level.
+///
+/// Offsets are collected from the writer's audit log, so it must be recording (see [`TagWriter::record_audit_log()`]) before the element containing the index is started, and must not be taken or disabled before the index is finished.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::{SeekIndex, TagWriter};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+/// writer.record_audit_log(true);
+///
+/// let mut index = SeekIndex::new("index", &[0x1654ae6b]);
+/// index.reserve(&mut writer, 16).unwrap();
+/// writer.write(&EmptySpec::with_data(0x1654ae6b, &[0x01])).unwrap();
+///
+/// assert_eq!(vec![(0x1654ae6b, 16)], index.entries(&writer).unwrap());
+///
+/// index.finish(&mut writer, |entries| {
+///     let offsets: Vec<u8> = entries.iter().flat_map(|(_, offset)| offset.to_be_bytes()).collect();
+///     EmptySpec::with_data(0x4dbb, &offsets)
+/// }).unwrap();
+/// ```
+///
+pub struct SeekIndex {
+    name: String,
+    indexed_ids: Vec<u64>,
+    parent: Option<u64>,
+}
+
+impl SeekIndex {
+
+    ///
+    /// Returns a new [`SeekIndex`] recording the offsets of elements with any of the `indexed_ids`.  `name` is the name of the bookmark reserved for the index.
+    ///
+    pub fn new(name: &str, indexed_ids: &[u64]) -> Self {
+        SeekIndex {
+            name: name.to_string(),
+            indexed_ids: indexed_ids.to_vec(),
+            parent: None,
+        }
+    }
+
+    ///
+    /// Reserves `len` bytes for the index at the current position of `writer`.  See [`TagWriter::reserve_bookmark()`].
+    ///
+    pub fn reserve<W: Write>(&mut self, writer: &mut TagWriter<W>, len: usize) -> Result<(), TagWriterError> {
+        self.parent = writer.current_parent();
+        writer.reserve_bookmark(&self.name, len)
+    }
+
+    ///
+    /// Returns the `(id, offset)` of every indexed element written so far, in the order they were written.
+    ///
+    /// Only elements inside the "Master" element containing the index are included.  Elements still buffered in a known-size "Master" element that hasn't been closed aren't included yet.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`TagWriterError::IndexUnavailable`] if the index's position isn't known yet (see [`TagWriter::bookmark_offset()`]), or if its containing element isn't in the audit log.
+    ///
+    pub fn entries<W: Write>(&self, writer: &TagWriter<W>) -> Result<Vec<(u64, u64)>, TagWriterError> {
+        let unavailable = || TagWriterError::IndexUnavailable { name: self.name.clone() };
+        let index_offset = writer.bookmark_offset(&self.name).ok_or_else(unavailable)?;
+        let log = writer.audit_log();
+
+        let (start, end) = match self.parent {
+            Some(parent) => {
+                let element = log.iter().rev().find(|e| e.id == parent && e.offset < index_offset).ok_or_else(unavailable)?;
+                (element.offset + element.header_len as u64, element.size.map(|size| element.offset + size))
+            },
+            None => (0, None),
+        };
+
+        Ok(log.iter()
+            .filter(|e| e.offset >= start && !matches!(end, Some(end) if e.offset >= end) && self.indexed_ids.contains(&e.id))
+            .map(|e| (e.id, e.offset - start))
+            .collect())
+    }
+
+    ///
+    /// Writes the index into its reserved space.  `build` receives the [`entries`](Self::entries) and returns the index tag to write.
+    ///
+    /// This can be called more than once, e.g. to keep the index up to date while streaming; each call replaces the previously written index.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same errors as [`Self::entries()`] and [`TagWriter::write_at_bookmark()`].
+    ///
+    pub fn finish<W: Write + Seek, TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self, writer: &mut TagWriter<W>, build: impl FnOnce(&[(u64, u64)]) -> TSpec) -> Result<(), TagWriterError> {
+        let entries = self.entries(writer)?;
+        writer.write_at_bookmark(&self.name, &build(&entries))
+    }
+}
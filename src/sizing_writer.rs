@@ -0,0 +1,87 @@
+use std::io::{self, Sink};
+
+use super::tag_writer::{TagWriter, WriteOptions};
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::tag_writer::TagWriterError;
+
+///
+/// A [`TagWriter`] that only counts the bytes it would write.
+///
+/// This computes the exact encoded size of a sequence of tags (headers and vints included) without keeping the output, e.g. to size an allocation or a `Content-Length` header before writing the data for real.  Tags are validated and encoded exactly like they are by a [`TagWriter`], so the same errors are returned.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::SizingWriter;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut sizer = SizingWriter::new();
+/// sizer.write(&EmptySpec::with_data(0x4286, &[0x01])).unwrap();
+/// sizer.write(&EmptySpec::with_data(0x4282, b"webm")).unwrap();
+/// assert_eq!(11, sizer.finish().unwrap());
+/// ```
+///
+pub struct SizingWriter {
+    writer: TagWriter<Sink>,
+}
+
+impl SizingWriter {
+
+    ///
+    /// Returns a new [`SizingWriter`] that hasn't counted anything yet.
+    ///
+    pub fn new() -> Self {
+        SizingWriter { writer: TagWriter::new(io::sink()) }
+    }
+
+    ///
+    /// Counts the bytes for a tag.  See [`TagWriter::write()`].
+    ///
+    pub fn write<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        self.writer.write(tag)
+    }
+
+    ///
+    /// Counts the bytes for a tag written using advanced options.  See [`TagWriter::write_advanced()`].
+    ///
+    pub fn write_advanced<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec, options: WriteOptions) -> Result<(), TagWriterError> {
+        self.writer.write_advanced(tag, options)
+    }
+
+    ///
+    /// Counts the bytes for raw tag data.  See [`TagWriter::write_raw()`].
+    ///
+    pub fn write_raw(&mut self, tag_id: u64, data: &[u8]) -> Result<(), TagWriterError> {
+        self.writer.write_raw(tag_id, data)
+    }
+
+    ///
+    /// Returns the number of bytes counted so far.
+    ///
+    /// Tags inside of a known-size "Master" tag are only counted once that tag has been closed.  Use [`Self::finish()`] to close any open tags and get the final size.
+    ///
+    pub fn size(&self) -> u64 {
+        self.writer.bytes_flushed()
+    }
+
+    ///
+    /// Closes any open tags (see [`TagWriter::flush()`]) and returns the total number of bytes counted.
+    ///
+    pub fn finish(mut self) -> Result<u64, TagWriterError> {
+        self.writer.flush()?;
+        Ok(self.size())
+    }
+
+    ///
+    /// Gets a mutable reference to the wrapped [`TagWriter`], e.g. to configure it the same way as the writer whose output is being sized.
+    ///
+    pub fn get_mut(&mut self) -> &mut TagWriter<Sink> {
+        &mut self.writer
+    }
+}
+
+impl Default for SizingWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
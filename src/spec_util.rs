@@ -1,6 +1,18 @@
-use ebml_iterable_specification::{EbmlSpecification, EbmlTag, PathPart};
+use std::collections::HashSet;
 
-use crate::tag_iterator_util::EBMLSize;
+use ebml_iterable_specification::{EbmlSpecification, EbmlTag, PathPart, TagDataType};
+
+use crate::tag_iterator_util::{ElementSize, TagStack};
+
+///
+/// Id of the global `Void` element defined in the EBML RFC.
+///
+pub const VOID_ID: u64 = 0xec;
+
+///
+/// Id of the global `CRC-32` element defined in the EBML RFC.
+///
+pub const CRC32_ID: u64 = 0xbf;
 
 ///
 /// Returns whether or not the a `test_id` is a parent of `current_id`.
@@ -38,48 +50,194 @@ pub fn is_ended_by<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(current_id: u64
     )
 }
 
-#[inline(always)]
-pub fn validate_tag_path<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(tag_id: u64, doc_path: impl Iterator<Item = (u64, EBMLSize, usize)>) -> bool {
+pub fn validate_tag_path<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(tag_id: u64, doc_path: impl Iterator<Item = (u64, ElementSize, usize)>) -> bool {
     let path = <T>::get_path_by_id(tag_id);
-    let mut path_marker = 0;
-    let mut global_counter = 0;
+    let mut parents: TagStack<u64> = TagStack::new();
     for item in doc_path {
-        let current_node_id = item.0;
-
-        if !item.1.is_known() && is_ended_by::<T>(current_node_id, tag_id) {
+        // A tag that ends an unknown-sized parent is valid as long as it fits where that parent was
+        if !item.1.is_known() && is_ended_by::<T>(item.0, tag_id) && path_prefix_matches(path, &parents) {
             return true;
         }
+        parents.push(item.0);
+    }
+    path_matches(path, &parents)
+}
 
-        if path_marker >= path.len() {
-            return false;
-        }
+///
+/// Returns whether an element with id `tag_id` may be a child of the last of `parents` (or a top-level element, if `parents` is empty).
+///
+/// This is the check readers and writers make to report hierarchy errors, exposed so tools can validate a placement without reading or writing it.  Global elements are checked against the range of levels their path allows: an element whose path is `(1-1)/Crc32` may only be a child of a top-level element, while `(1-)/Crc32` may be nested at any depth below the top level.
+///
+pub fn is_valid_path<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(tag_id: u64, parents: &[u64]) -> bool {
+    <T>::get_tag_data_type(tag_id).is_some() && path_matches(<T>::get_path_by_id(tag_id), parents)
+}
 
-        match path[path_marker] {
-            PathPart::Id(id) => {
-                if id != current_node_id {
-                    return false;
-                }
-                path_marker += 1;
-            },
+///
+/// Returns whether an element with id `parent_id` may directly contain an element with id `child_id`, wherever the parent is in a document.
+///
+/// Both paths are taken into account, so a child whose path is `(1-1)/Crc32` can be contained by top-level "Master" elements only, and a child declared below a global parent (like `(1-)/Container/Label`) can be contained by that parent wherever it appears.  Returns `false` if either id isn't in the spec or the parent isn't a "Master" element.
+///
+pub fn can_contain<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(parent_id: u64, child_id: u64) -> bool {
+    if <T>::get_tag_data_type(parent_id) != Some(TagDataType::Master) || <T>::get_tag_data_type(child_id).is_none() {
+        return false;
+    }
+    // The child's parents must be a path the parent itself can be found at, followed by the parent
+    let mut parent_path = path_tokens(<T>::get_path_by_id(parent_id));
+    parent_path.push(PathToken::Id(parent_id));
+    let child_path = path_tokens(<T>::get_path_by_id(child_id));
+    tokens_overlap(&parent_path, &child_path, &mut HashSet::new(), 0, 0)
+}
+
+///
+/// A single step of a path pattern, with global placeholders expanded into the number of ancestors they stand for.
+///
+#[derive(Copy, Clone, PartialEq)]
+enum PathToken {
+    Id(u64),
+    /// Exactly one ancestor
+    Any,
+    /// At most one ancestor
+    Optional,
+    /// Any number of ancestors
+    Repeated,
+}
+
+fn path_tokens(path: &[PathPart]) -> Vec<PathToken> {
+    let mut tokens = Vec::new();
+    for part in path {
+        match part {
+            PathPart::Id(id) => tokens.push(PathToken::Id(*id)),
             PathPart::Global((min, max)) => {
-                global_counter += 1;
-                if max.is_some() && global_counter > max.unwrap_or_default() {
-                    return false;
-                }
-                if path.len() > (path_marker + 1) && matches!(path[path_marker + 1], PathPart::Id(id) if id == current_node_id) {
-                    if min.is_some() && global_counter < min.unwrap_or_default() {
-                        return false;
-                    }
-                    path_marker += 2;
-                    global_counter = 0;
+                let min = min.unwrap_or(0) as usize;
+                tokens.extend(std::iter::repeat_n(PathToken::Any, min));
+                match max {
+                    Some(max) => tokens.extend(std::iter::repeat_n(PathToken::Optional, (*max as usize).saturating_sub(min))),
+                    None => tokens.push(PathToken::Repeated),
                 }
             },
         }
     }
+    tokens
+}
+
+///
+/// Returns whether some list of ancestors matches both patterns `a[i..]` and `b[j..]`.  `failed` remembers positions already known not to match.
+///
+fn tokens_overlap(a: &[PathToken], b: &[PathToken], failed: &mut HashSet<(usize, usize)>, i: usize, j: usize) -> bool {
+    if i == a.len() && j == b.len() {
+        return true;
+    }
+    if failed.contains(&(i, j)) {
+        return false;
+    }
+
+    let skippable = |token: Option<&PathToken>| matches!(token, Some(PathToken::Optional) | Some(PathToken::Repeated));
+    let overlaps = (skippable(a.get(i)) && tokens_overlap(a, b, failed, i + 1, j))
+        || (skippable(b.get(j)) && tokens_overlap(a, b, failed, i, j + 1))
+        || match (a.get(i), b.get(j)) {
+            // Consuming an ancestor in both repeated tokens leads back to the same position
+            (Some(PathToken::Repeated), Some(PathToken::Repeated)) => false,
+            (Some(PathToken::Id(x)), Some(PathToken::Id(y))) if x != y => false,
+            (Some(x), Some(y)) => {
+                let next_i = if *x == PathToken::Repeated { i } else { i + 1 };
+                let next_j = if *y == PathToken::Repeated { j } else { j + 1 };
+                tokens_overlap(a, b, failed, next_i, next_j)
+            },
+            _ => false,
+        };
+    if !overlaps {
+        failed.insert((i, j));
+    }
+    overlaps
+}
+
+///
+/// Returns whether `parents` is exactly the document path described by `path`.  Global placeholders stand for any number of ancestors within their range.
+///
+fn path_matches(path: &[PathPart], parents: &[u64]) -> bool {
+    match path.split_first() {
+        None => parents.is_empty(),
+        Some((PathPart::Id(id), rest)) => parents.first() == Some(id) && path_matches(rest, &parents[1..]),
+        Some((PathPart::Global((min, max)), rest)) => {
+            let max = max.map_or(parents.len(), |max| (max as usize).min(parents.len()));
+            (min.unwrap_or(0) as usize..=max).any(|levels| path_matches(rest, &parents[levels..]))
+        },
+    }
+}
+
+///
+/// Returns whether `parents` is the start of a document path described by `path`.
+///
+fn path_prefix_matches(path: &[PathPart], parents: &[u64]) -> bool {
+    if parents.is_empty() {
+        return true;
+    }
+    match path.split_first() {
+        None => false,
+        Some((PathPart::Id(id), rest)) => parents.first() == Some(id) && path_prefix_matches(rest, &parents[1..]),
+        Some((PathPart::Global((min, max)), rest)) => {
+            // The placeholder can stand for all of the remaining parents, or for some of them followed by the rest of the path
+            let max = max.map_or(usize::MAX, |max| max as usize);
+            parents.len() <= max || (min.unwrap_or(0) as usize..=max).any(|levels| path_prefix_matches(rest, &parents[levels..]))
+        },
+    }
+}
+
+///
+/// Converts a document path string (e.g. `"Segment/Tracks"`) into a list of tag ids.
+///
+/// Each path segment is resolved using [`EbmlSpecification::get_id_by_name`], falling back to a hexadecimal id (e.g. `"0x1654ae6b"`).  Returns `None` if any segment cannot be resolved.
+///
+pub fn parse_path<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(path: &str) -> Option<Vec<u64>> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            <T>::get_id_by_name(segment).or_else(|| {
+                segment.strip_prefix("0x")
+                    .or_else(|| segment.strip_prefix("0X"))
+                    .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+            })
+        })
+        .collect()
+}
+
+///
+/// Converts a document path string (e.g. `"Segment/Info/Title"`, as held by the `PATH_*` constants generated by `#[ebml_specification]`) into the [`PathPart`]s of every element along it, the last element included.
+///
+/// Segments are resolved the same way as in [`parse_path()`].  Global wildcards can be given in the notation used by `#[doc_path]`, e.g. `"Segment/(1-)/Crc32"`.  Returns `None` if any segment cannot be resolved.
+///
+pub fn parse_doc_path<T: EbmlSpecification<T> + EbmlTag<T> + Clone>(path: &str) -> Option<Vec<PathPart>> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| match segment.strip_prefix('(').and_then(|range| range.strip_suffix(')')) {
+            Some(range) => {
+                let (min, max) = range.split_once('-')?;
+                let bound = |bound: &str| if bound.is_empty() { Some(None) } else { bound.parse::<u64>().ok().map(Some) };
+                Some(PathPart::Global((bound(min)?, bound(max)?)))
+            },
+            None => parse_path::<T>(segment).and_then(|ids| ids.first().copied()).map(PathPart::Id),
+        })
+        .collect()
+}
 
-    // Validate that we compared ALL parents in the path
-    path.len() == path_marker || 
-    // or that the last parent was a global whose minimum was met
-        ((path.len() - 1) == path_marker && matches!(path[path_marker], PathPart::Global((min, _)) if global_counter >= min.unwrap_or(0)))
-    
-}
\ No newline at end of file
+///
+/// Builds a tag with id `id` holding `default`, a default value as written in the spec (see [`EbmlSpecification::get_default_value()`]), if it can be parsed for `data_type`.
+///
+pub(crate) fn default_tag<TSpec>(id: u64, data_type: TagDataType, default: &str) -> Option<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    match data_type {
+        TagDataType::UnsignedInt => {
+            let value = match default.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+                None => default.parse().ok()?,
+            };
+            TSpec::get_unsigned_int_tag(id, value)
+        },
+        TagDataType::Integer => TSpec::get_signed_int_tag(id, default.parse().ok()?),
+        TagDataType::Float => TSpec::get_float_tag(id, default.parse().ok()?),
+        TagDataType::Utf8 => TSpec::get_utf8_tag(id, default.to_string()),
+        TagDataType::Binary | TagDataType::Master => None,
+    }
+}
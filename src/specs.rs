@@ -14,3 +14,6 @@ pub use ebml_iterable_specification::EbmlTag as EbmlTag;
 pub use ebml_iterable_specification::TagDataType as TagDataType;
 pub use ebml_iterable_specification::Master as Master;
 pub use ebml_iterable_specification::PathPart as PathPart;
+pub use ebml_iterable_specification::ElementMeta as ElementMeta;
+pub use ebml_iterable_specification::ebml_header as ebml_header;
+pub use ebml_iterable_specification::LazyUtf8 as LazyUtf8;
@@ -0,0 +1,154 @@
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+
+use crate::header_walker::HeaderWalker;
+use crate::spec_util::CRC32_ID;
+use crate::tag_iterator_util::ElementHeader;
+use crate::tag_iterator_util::ElementSize::Unknown;
+
+use super::specs::{EbmlSpecification, EbmlTag, PathPart};
+use super::errors::splitter::SplitterError;
+
+///
+/// Splits a document into multiple independently parseable documents at the boundaries of a chosen element.
+///
+/// Everything in the input before the first split element (e.g. the EBML header, `Info`, and `Tracks` in a WebM file) is replicated at the start of every output.  Master elements that contain the split element (e.g. `Segment`) are written with an unknown size in each output, so outputs can be produced in a single pass.  `CRC-32` elements directly inside those masters are dropped since they would no longer be valid.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use ebml_iterable::utils::Splitter;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let source = File::open("my_ebml_file.ebml")?;
+/// // Split on "Cluster" elements, 10 per file
+/// let mut splitter: Splitter<EmptySpec> = Splitter::new(0x1f43b675);
+/// splitter.set_max_elements(Some(10));
+/// let count = splitter.split(source, |index| File::create(format!("part{}.ebml", index)))?;
+/// println!("Wrote {} files", count);
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct Splitter<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    split_id: u64,
+    max_elements: Option<usize>,
+    max_bytes: Option<usize>,
+    _spec: PhantomData<TSpec>,
+}
+
+impl<TSpec> Splitter<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`Splitter`] that cuts documents before elements with id `split_id`.
+    ///
+    /// By default no limits are set, so everything is written to a single output.  Use [`Self::set_max_elements()`] and/or [`Self::set_max_bytes()`] to configure when a new output is started.
+    ///
+    pub fn new(split_id: u64) -> Self {
+        Splitter {
+            split_id,
+            max_elements: None,
+            max_bytes: None,
+            _spec: PhantomData,
+        }
+    }
+
+    ///
+    /// Configures the maximum number of split elements written to each output.
+    ///
+    pub fn set_max_elements(&mut self, count: Option<usize>) {
+        self.max_elements = count;
+    }
+
+    ///
+    /// Configures the size (in bytes) at which an output is considered full.
+    ///
+    /// A new output is started before the next split element once an output reaches this size.  Outputs always contain at least one split element, so an output can exceed this size if a single split element is larger than it.
+    ///
+    pub fn set_max_bytes(&mut self, bytes: Option<usize>) {
+        self.max_bytes = bytes;
+    }
+
+    ///
+    /// Reads a document from `source` and writes it out in pieces.
+    ///
+    /// `next_output` is called with the index of each output (starting at 0) whenever a new destination is needed.  Returns the number of outputs that were written.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if there is a problem reading the source or writing an output.  The different possible error states are enumerated in [`SplitterError`].
+    ///
+    pub fn split<R: Read, W: Write>(&self, source: R, mut next_output: impl FnMut(usize) -> std::io::Result<W>) -> Result<usize, SplitterError> {
+        let write_err = |source| SplitterError::WriteError { source };
+        let split_path = TSpec::get_path_by_id(self.split_id);
+        let is_ancestor = |id: u64| split_path.iter().any(|p| matches!(p, PathPart::Id(p) if *p == id));
+
+        let mut walker: HeaderWalker<R, TSpec> = HeaderWalker::new(source);
+        let mut preamble: Vec<u8> = Vec::new();
+        let mut output: Option<Output<W>> = None;
+        let mut output_count = 0;
+
+        while let Some(header) = walker.next_header()? {
+            if header.id == self.split_id {
+                let is_full = output.as_ref().is_some_and(|o| {
+                    o.elements > 0 &&
+                    (matches!(self.max_elements, Some(max) if o.elements >= max) || matches!(self.max_bytes, Some(max) if o.bytes >= max))
+                });
+                if is_full || output.is_none() {
+                    if let Some(mut previous) = output.take() {
+                        previous.dest.flush().map_err(write_err)?;
+                    }
+                    let mut dest = next_output(output_count).map_err(write_err)?;
+                    dest.write_all(&preamble).map_err(write_err)?;
+                    output = Some(Output { dest, bytes: preamble.len(), elements: 0 });
+                    output_count += 1;
+                }
+                output.as_mut().expect("output should be open").elements += 1;
+            }
+
+            match output.as_mut() {
+                None => {
+                    if is_ancestor(header.id) {
+                        preamble.extend(ElementHeader { size: Unknown, ..header }.encode());
+                        walker.descend(&header);
+                    } else if header.id == CRC32_ID && walker.parents().last().is_some_and(is_ancestor) {
+                        walker.skip_data(&header)?;
+                    } else {
+                        walker.copy_element(&header, &mut preamble, write_err)?;
+                    }
+                },
+                Some(output) => {
+                    output.bytes += header.total_len().unwrap_or(header.header_len);
+                    walker.copy_element(&header, &mut output.dest, write_err)?;
+                },
+            }
+        }
+
+        // The split element never showed up, so everything ends up in a single output
+        if output.is_none() {
+            let mut dest = next_output(output_count).map_err(write_err)?;
+            dest.write_all(&preamble).map_err(write_err)?;
+            output = Some(Output { dest, bytes: preamble.len(), elements: 0 });
+            output_count += 1;
+        }
+
+        if let Some(mut output) = output {
+            output.dest.flush().map_err(write_err)?;
+        }
+        Ok(output_count)
+    }
+}
+
+struct Output<W: Write> {
+    dest: W,
+    bytes: usize,
+    elements: usize,
+}
+
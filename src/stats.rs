@@ -0,0 +1,315 @@
+use std::time::{Duration, Instant};
+
+///
+/// Progress and throughput counters for a [`TagIterator`][`crate::TagIterator`], obtained using [`TagIterator::metrics()`][`crate::TagIterator::metrics`].
+///
+/// When the `"metrics"` feature is enabled, these counters are also published through the [`metrics`](https://crates.io/crates/metrics) crate as they change (as `ebml_iterable.bytes_read`, `ebml_iterable.tags_emitted`, `ebml_iterable.errors_emitted`, `ebml_iterable.recoveries`, `ebml_iterable.zero_length_values`, `ebml_iterable.trailing_bytes`, `ebml_iterable.truncated_elements`, `ebml_iterable.bytes_seeked_over`, and `ebml_iterable.crc_mismatches` counters, and `ebml_iterable.read_buffer_high_water_mark` and `ebml_iterable.emission_queue_high_water_mark` gauges).
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadMetrics {
+
+    ///
+    /// The number of bytes read from the source.
+    ///
+    pub bytes_read: u64,
+
+    ///
+    /// The number of tags successfully emitted.
+    ///
+    pub tags_emitted: u64,
+
+    ///
+    /// The number of errors emitted.
+    ///
+    pub errors_emitted: u64,
+
+    ///
+    /// The number of successful calls to [`TagIterator::try_recover()`][`crate::TagIterator::try_recover`].
+    ///
+    pub recoveries: u64,
+
+    ///
+    /// The number of unsigned integer, signed integer, float, and utf8 tags read without any data.  Only counted if enabled through [`TagIterator::set_zero_length_values()`][`crate::TagIterator::set_zero_length_values`] with [`ZeroLengthValues::Warn`][`crate::iterator::ZeroLengthValues::Warn`] or [`ZeroLengthValues::Error`][`crate::iterator::ZeroLengthValues::Error`].
+    ///
+    pub zero_length_values: u64,
+
+    ///
+    /// The number of bytes found after the last top-level element of the document.  Only counted if enabled through [`TagIterator::set_trailing_data()`][`crate::TagIterator::set_trailing_data`] with [`TrailingData::Warn`][`crate::iterator::TrailingData::Warn`].
+    ///
+    pub trailing_bytes: u64,
+
+    ///
+    /// The number of elements cut short because their size extended past the end of the source.  Only counted if enabled through [`TagIterator::set_size_past_end()`][`crate::TagIterator::set_size_past_end`] with [`SizePastEnd::Truncate`][`crate::iterator::SizePastEnd::Truncate`].
+    ///
+    pub truncated_elements: u64,
+
+    ///
+    /// The number of payload bytes stepped over by seeking the source instead of reading it.  Only counted if enabled through [`TagIterator::seek_over_binary_payloads()`][`crate::TagIterator::seek_over_binary_payloads`].
+    ///
+    pub bytes_seeked_over: u64,
+
+    ///
+    /// The number of "Master" elements whose data didn't match their `CRC-32` child.  Only counted if enabled through [`TagIterator::set_crc_mismatch()`][`crate::TagIterator::set_crc_mismatch`].
+    ///
+    pub crc_mismatches: u64,
+
+    ///
+    /// The largest size (in bytes) the internal read buffer has grown to.
+    ///
+    pub buffer_high_water_mark: usize,
+
+    ///
+    /// The largest number of items that have been waiting in the internal emission queue.  This grows when "Master" tags are buffered into [`Master::Full`][`crate::specs::Master::Full`] variants.
+    ///
+    pub queue_high_water_mark: usize,
+
+    ///
+    /// Time since the first read from the source.  Always zero on `wasm32-unknown-unknown`, which has no clock.
+    ///
+    pub elapsed: Duration,
+}
+
+impl ReadMetrics {
+
+    ///
+    /// Returns the average number of tags emitted per second.
+    ///
+    pub fn tags_per_second(&self) -> f64 {
+        per_second(self.tags_emitted, self.elapsed)
+    }
+
+    ///
+    /// Returns the average number of bytes read per second.
+    ///
+    pub fn bytes_per_second(&self) -> f64 {
+        per_second(self.bytes_read, self.elapsed)
+    }
+}
+
+///
+/// Progress and throughput counters for a [`TagWriter`][`crate::TagWriter`], obtained using [`TagWriter::metrics()`][`crate::TagWriter::metrics`].
+///
+/// When the `"metrics"` feature is enabled, these counters are also published through the [`metrics`](https://crates.io/crates/metrics) crate as they change (as `ebml_iterable.bytes_written` and `ebml_iterable.tags_written` counters, and an `ebml_iterable.write_buffer_high_water_mark` gauge).
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WriteMetrics {
+
+    ///
+    /// The number of bytes written to the destination.
+    ///
+    pub bytes_written: u64,
+
+    ///
+    /// The number of tags written, including the children of [`Master::Full`][`crate::specs::Master::Full`] tags.
+    ///
+    pub tags_written: u64,
+
+    ///
+    /// The largest number of bytes held in the writer's working buffer before being written to the destination.  This grows while known-size "Master" tags are open.
+    ///
+    pub buffer_high_water_mark: usize,
+
+    ///
+    /// Time since the first tag was written.  Always zero on `wasm32-unknown-unknown`, which has no clock.
+    ///
+    pub elapsed: Duration,
+}
+
+impl WriteMetrics {
+
+    ///
+    /// Returns the average number of tags written per second.
+    ///
+    pub fn tags_per_second(&self) -> f64 {
+        per_second(self.tags_written, self.elapsed)
+    }
+
+    ///
+    /// Returns the average number of bytes written per second.
+    ///
+    pub fn bytes_per_second(&self) -> f64 {
+        per_second(self.bytes_written, self.elapsed)
+    }
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        0.0
+    } else {
+        count as f64 / elapsed.as_secs_f64()
+    }
+}
+
+pub(crate) trait Timed: Clone + Default {
+    fn set_elapsed(&mut self, elapsed: Duration);
+}
+
+impl Timed for ReadMetrics {
+    fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+}
+
+impl Timed for WriteMetrics {
+    fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+}
+
+type MetricsCallback<T> = Box<dyn FnMut(&T) + Send>;
+
+///
+/// Returns the current time, or `None` on `wasm32-unknown-unknown` where [`Instant::now()`] panics.
+///
+#[inline]
+pub(crate) fn now() -> Option<Instant> {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    { Some(Instant::now()) }
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    { None }
+}
+
+///
+/// Keeps the counters for a reader or writer and calls the user's callback (if any) at most once per interval.
+///
+#[derive(Default)]
+pub(crate) struct MetricsTracker<T: Timed> {
+    current: T,
+    started: Option<Instant>,
+    reporter: Option<(Duration, Option<Instant>, MetricsCallback<T>)>,
+}
+
+impl<T: Timed> MetricsTracker<T> {
+    pub fn snapshot(&self) -> T {
+        let mut snapshot = self.current.clone();
+        snapshot.set_elapsed(self.started.map(|s| s.elapsed()).unwrap_or_default());
+        snapshot
+    }
+
+    pub fn set_callback(&mut self, interval: Duration, callback: MetricsCallback<T>) {
+        self.reporter = Some((interval, now(), callback));
+    }
+
+    #[inline(always)]
+    pub fn start(&mut self) {
+        if self.started.is_none() {
+            self.started = now();
+        }
+    }
+
+    #[inline]
+    pub fn tick(&mut self) {
+        if let Some((interval, last, _)) = &self.reporter {
+            // Without a clock the interval can't be measured, so every tick is reported
+            if last.is_none_or(|last| last.elapsed() >= *interval) {
+                let snapshot = self.snapshot();
+                let (_, last, callback) = self.reporter.as_mut().unwrap();
+                *last = now();
+                callback(&snapshot);
+            }
+        }
+    }
+}
+
+impl MetricsTracker<ReadMetrics> {
+    #[inline]
+    pub fn add_bytes_read(&mut self, count: usize) {
+        self.start();
+        self.current.bytes_read += count as u64;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.bytes_read").increment(count as u64);
+    }
+
+    #[inline]
+    pub fn add_emitted(&mut self, is_ok: bool) {
+        if is_ok {
+            self.current.tags_emitted += 1;
+            #[cfg(feature = "metrics")]
+            ::metrics::counter!("ebml_iterable.tags_emitted").increment(1);
+        } else {
+            self.current.errors_emitted += 1;
+            #[cfg(feature = "metrics")]
+            ::metrics::counter!("ebml_iterable.errors_emitted").increment(1);
+        }
+    }
+
+    pub fn add_recovery(&mut self) {
+        self.current.recoveries += 1;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.recoveries").increment(1);
+    }
+
+    pub fn add_zero_length_value(&mut self) {
+        self.current.zero_length_values += 1;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.zero_length_values").increment(1);
+    }
+
+    pub fn add_trailing_bytes(&mut self, len: u64) {
+        self.current.trailing_bytes += len;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.trailing_bytes").increment(len);
+    }
+
+    pub fn add_truncated_element(&mut self) {
+        self.current.truncated_elements += 1;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.truncated_elements").increment(1);
+    }
+
+    pub fn add_crc_mismatch(&mut self) {
+        self.current.crc_mismatches += 1;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.crc_mismatches").increment(1);
+    }
+
+    pub fn add_bytes_seeked_over(&mut self, count: usize) {
+        self.current.bytes_seeked_over += count as u64;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.bytes_seeked_over").increment(count as u64);
+    }
+
+    #[inline]
+    pub fn observe_buffer(&mut self, len: usize) {
+        if len > self.current.buffer_high_water_mark {
+            self.current.buffer_high_water_mark = len;
+            #[cfg(feature = "metrics")]
+            ::metrics::gauge!("ebml_iterable.read_buffer_high_water_mark").set(len as f64);
+        }
+    }
+
+    #[inline]
+    pub fn observe_queue(&mut self, len: usize) {
+        if len > self.current.queue_high_water_mark {
+            self.current.queue_high_water_mark = len;
+            #[cfg(feature = "metrics")]
+            ::metrics::gauge!("ebml_iterable.emission_queue_high_water_mark").set(len as f64);
+        }
+    }
+}
+
+impl MetricsTracker<WriteMetrics> {
+    #[inline]
+    pub fn add_bytes_written(&mut self, count: usize) {
+        self.current.bytes_written += count as u64;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.bytes_written").increment(count as u64);
+    }
+
+    #[inline]
+    pub fn add_tag_written(&mut self) {
+        self.start();
+        self.current.tags_written += 1;
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("ebml_iterable.tags_written").increment(1);
+    }
+
+    #[inline]
+    pub fn observe_buffer(&mut self, len: usize) {
+        if len > self.current.buffer_high_water_mark {
+            self.current.buffer_high_water_mark = len;
+            #[cfg(feature = "metrics")]
+            ::metrics::gauge!("ebml_iterable.write_buffer_high_water_mark").set(len as f64);
+        }
+    }
+}
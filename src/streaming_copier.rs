@@ -0,0 +1,182 @@
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use crate::header_walker::HeaderWalker;
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+
+use super::specs::{EbmlSpecification, EbmlTag};
+use super::errors::streaming_copier::StreamingCopierError;
+
+type TimestampCallback = Box<dyn FnMut(u64, &[u8]) -> Option<Duration>>;
+
+///
+/// Copies a document from a reader to a writer, optionally pacing the output instead of writing it as fast as possible.
+///
+/// This is meant for serving EBML to clients that expect to receive it live (like a WebM stream over HTTP), where sending the whole file in one burst isn't acceptable.  Two pacing policies are available and can be combined:
+///
+///  - A maximum rate in bytes per second ([`Self::set_bytes_per_second()`]).
+///  - Real-time pacing ([`Self::set_timestamp_callback()`]), where a callback reports the presentation timestamp of elements and each element is held back until that much time has passed since the copy started.
+///
+/// Data is copied byte for byte, and the destination is flushed before every pause so that clients receive everything written so far.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::net::TcpStream;
+/// use ebml_iterable::utils::StreamingCopier;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let source = File::open("my_ebml_file.ebml")?;
+/// let dest = TcpStream::connect("127.0.0.1:8080")?;
+/// let mut copier: StreamingCopier<EmptySpec> = StreamingCopier::new();
+/// copier.set_bytes_per_second(Some(256 * 1024));
+/// copier.copy(source, dest)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct StreamingCopier<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    bytes_per_second: Option<u64>,
+    timestamp: Option<TimestampCallback>,
+    _spec: PhantomData<TSpec>,
+}
+
+impl<TSpec> StreamingCopier<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`StreamingCopier`] with no pacing configured.
+    ///
+    pub fn new() -> Self {
+        StreamingCopier {
+            bytes_per_second: None,
+            timestamp: None,
+            _spec: PhantomData,
+        }
+    }
+
+    ///
+    /// Configures the maximum average rate (in bytes per second) at which data is written.
+    ///
+    pub fn set_bytes_per_second(&mut self, rate: Option<u64>) {
+        self.bytes_per_second = rate.filter(|rate| *rate > 0);
+    }
+
+    ///
+    /// Configures real-time pacing using a callback that returns the timestamp of an element.
+    ///
+    /// The callback is called with the id and data of every non-"Master" element before it is written.  If it returns a timestamp, the element isn't written until that much time has passed since the copy started.  Elements can be tracked across calls to calculate timestamps (e.g. adding a `SimpleBlock`'s relative timestamp to the most recent `Cluster` timestamp).
+    ///
+    /// Since the callback needs each element's data, non-"Master" elements are buffered in memory one at a time while this is set.
+    ///
+    pub fn set_timestamp_callback(&mut self, callback: impl FnMut(u64, &[u8]) -> Option<Duration> + 'static) {
+        self.timestamp = Some(Box::new(callback));
+    }
+
+    ///
+    /// Copies a document from `source` to `dest`, applying the configured pacing.  Returns the number of bytes written.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if there is a problem reading the source or writing to the destination.  The different possible error states are enumerated in [`StreamingCopierError`].
+    ///
+    pub fn copy<R: Read, W: Write>(&mut self, source: R, dest: W) -> Result<usize, StreamingCopierError> {
+        let write_err = |source| StreamingCopierError::WriteError { source };
+        let start = Instant::now();
+        let mut walker: HeaderWalker<R, TSpec> = HeaderWalker::new(source);
+        let mut dest = PacedWriter { inner: dest, bytes_per_second: self.bytes_per_second, start, written: 0 };
+
+        while let Some(header) = walker.next_header()? {
+            let callback = match self.timestamp.as_mut() {
+                Some(callback) => callback,
+                None => {
+                    walker.copy_element(&header, &mut dest, write_err)?;
+                    continue;
+                },
+            };
+
+            if HeaderWalker::<R, TSpec>::is_master(&header) {
+                dest.write_all(&header.encode()).map_err(write_err)?;
+                walker.descend(&header);
+                continue;
+            }
+
+            let mut data = Vec::new();
+            match header.size {
+                Known(size) => walker.copy_data(size, &mut data, write_err)?,
+                Unknown => walker.descend(&header),
+            }
+            if let Some(timestamp) = callback(header.id, &data) {
+                dest.wait_until(start + timestamp).map_err(write_err)?;
+            }
+            dest.write_all(&header.encode()).map_err(write_err)?;
+            dest.write_all(&data).map_err(write_err)?;
+        }
+
+        dest.flush().map_err(write_err)?;
+        Ok(dest.written)
+    }
+}
+
+impl<TSpec> Default for StreamingCopier<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///
+/// Writes through to `inner`, sleeping as needed to stay under `bytes_per_second`.
+///
+struct PacedWriter<W: Write> {
+    inner: W,
+    bytes_per_second: Option<u64>,
+    start: Instant,
+    written: usize,
+}
+
+impl<W: Write> PacedWriter<W> {
+    fn wait_until(&mut self, deadline: Instant) -> std::io::Result<()> {
+        let now = Instant::now();
+        if deadline > now {
+            self.inner.flush()?;
+            std::thread::sleep(deadline - now);
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for PacedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let rate = match self.bytes_per_second {
+            Some(rate) => rate,
+            None => {
+                let len = self.inner.write(buf)?;
+                self.written += len;
+                return Ok(len);
+            },
+        };
+
+        // Write at most a tenth of a second worth of data at a time so output stays smooth
+        let len = buf.len().min((rate / 10).max(1) as usize);
+        let deadline = self.start + Duration::from_secs_f64((self.written + len) as f64 / rate as f64);
+        self.wait_until(deadline)?;
+        let len = self.inner.write(&buf[..len])?;
+        self.written += len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -1,19 +1,105 @@
-use std::io::Read;
+use std::io::{Read, Seek};
 use std::collections::{HashSet, VecDeque};
+use std::convert::TryInto;
+use std::ops::Range;
+use std::time::Duration;
 
-use crate::spec_util::validate_tag_path;
-use crate::tag_iterator_util::EBMLSize::{Known, Unknown};
-use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, EBMLSize, ProcessingTag, AllowableErrors};
+use crate::spec_util::{default_tag, validate_tag_path, CRC32_ID};
+use crate::transform::{ContentTransform, ContentTransforms};
+use crate::stats::{MetricsTracker, ReadMetrics};
+use crate::profile::Profile;
+use crate::event_log::{EndCause, EventLog, ParseEvent};
+use crate::flatten::FlattenValues;
+use crate::typed_reader::TypedReader;
+use crate::watchdog::WatchdogReader;
+use crate::forkable_source::ForkableSource;
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, ElementLayout, ElementSize, ProcessingTag, TagEncoding, TagSpan, TagStack, AllowableErrors, CrcMismatch, SizePastEnd, TrailingData, ZeroLengthValues};
 
 use super::tools;
 use super::specs::{EbmlSpecification, EbmlTag, Master, TagDataType, PathPart};
-use super::errors::tag_iterator::{CorruptedFileError, TagIteratorError};
+use super::errors::tag_iterator::{CorruptedFileError, PartialTag, TagIteratorError};
 use super::errors::tool::ToolError;
 
 const INVALID_TAG_ID_ERROR         : u8 = 0x01;
 const INVALID_HIERARCHY_ERROR      : u8 = 0x02;
 const OVERSIZED_CHILD_ERROR        : u8 = 0x04;
 
+// EBMLMaxIDLength from the EBML header, and its default when a document doesn't declare it
+const EBML_MAX_ID_LENGTH_ID: u64 = 0x42f2;
+const DEFAULT_MAX_ID_LENGTH: usize = 4;
+
+const MAX_POOLED_PAYLOADS: usize = 64;
+
+type EofPredicate = Box<dyn FnMut(usize) -> bool + Send>;
+type AllocationHook = (usize, Box<dyn FnMut(usize) -> bool + Send>);
+type SeekBy<R> = fn(&mut R, i64) -> std::io::Result<u64>;
+const DEFAULT_QUEUE_LEN: usize = 16;
+
+///
+/// A tag waiting to be emitted, along with the details reported through [`TagIterator::last_emitted_tag_offset()`] and friends.
+///
+#[derive(Clone)]
+struct QueuedTag<TSpec> {
+    tag: TSpec,
+    start: usize,
+    level: usize,
+    size: ElementSize,
+    size_length: usize,
+
+    /// Where a buffered [`Master::Full`] tag and its children were read from, if layouts are being recorded
+    layout: Option<ElementLayout>,
+}
+
+///
+/// The tag ids passed to [`TagIterator::set_tag_id_filter()`], along with the "Master" elements that can lead to them.
+///
+#[derive(Clone)]
+struct TagIdFilter {
+    ids: HashSet<u64>,
+    ancestors: HashSet<u64>,
+
+    /// Global elements can appear in any "Master" element, so none of them can be skipped
+    has_global: bool,
+}
+
+impl TagIdFilter {
+    fn new<TSpec>(ids: &[u64]) -> Self
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        let mut ancestors = HashSet::new();
+        let mut has_global = false;
+        for id in ids {
+            for part in TSpec::get_path_by_id(*id) {
+                match part {
+                    PathPart::Id(ancestor) => { ancestors.insert(*ancestor); },
+                    PathPart::Global(_) => has_global = true,
+                }
+            }
+        }
+        TagIdFilter { ids: ids.iter().copied().collect(), ancestors, has_global }
+    }
+}
+
+///
+/// Limits the elements emitted by a [`TagIterator`], as set by [`TagIterator::set_tag_id_filter()`] or [`TagIterator::set_path_filter()`].
+///
+#[derive(Clone)]
+enum TagFilter {
+    Ids(TagIdFilter),
+    Paths(Vec<Vec<u64>>),
+}
+
+///
+/// The header of a tag whose data was cut off by EOF, kept so the tag can be finished by [`TagIterator::resume_partial()`].
+///
+struct PartialHeader {
+    tag_start: usize,
+    tag_id: u64,
+    spec_tag_type: Option<TagDataType>,
+    size: usize,
+}
+
 ///
 /// Provides an iterator over EBML files (read from a source implementing the [`std::io::Read`] trait). Can be configured to read specific "Master" tags as complete objects rather than just emitting when they start and end.
 ///
@@ -21,6 +107,8 @@ const OVERSIZED_CHILD_ERROR        : u8 = 0x04;
 ///
 /// Note: The [`Self::with_capacity()`] method can be used to construct a `TagIterator` with a specified default buffer size.  This is only useful as a microoptimization to memory management if you know the maximum tag size of the file you're reading.
 ///
+/// If the source starts in the middle of a document (e.g. a file that was seeked to a cluster), the parents implied by the first tag's document path are tracked so that hierarchy validation and [`Self::last_emitted_tag_level()`] work as usual.  No [`Master::End`] tags are emitted for these parents, since their [`Master::Start`] tags were never read.
+///
 /// ## Example
 ///
 /// ```no_run
@@ -56,17 +144,52 @@ pub struct TagIterator<R: Read, TSpec>
     tag_ids_to_buffer: HashSet<u64>,
     allowed_errors: u8,
     max_allowed_tag_size: Option<usize>,
+    max_nesting_depth: Option<usize>,
+    validate_restricted_values: bool,
+    zero_length_values: ZeroLengthValues,
+    trailing_data: TrailingData,
+    reached_trailing_data: bool,
+    size_past_end: SizePastEnd,
+    crc_mismatch: CrcMismatch,
+    source_len: Option<usize>,
+    read_timeout: Option<Duration>,
+    skip_crc32_elements: bool,
+    binary_seek: Option<(usize, SeekBy<R>)>,
+    profile: Option<Profile>,
+    max_id_length: usize,
+    transforms: ContentTransforms,
+    metrics: MetricsTracker<ReadMetrics>,
+    corrupt_ranges: Vec<Range<usize>>,
+    event_log: Option<EventLog>,
+    tag_filter: Option<TagFilter>,
 
+    #[cfg(not(feature = "bytes"))]
     buffer: Box<[u8]>,
+    #[cfg(feature = "bytes")]
+    buffer: bytes::BytesMut,
+    #[cfg(feature = "bytes")]
+    buffer_capacity: usize,
     buffer_offset: Option<usize>,
     buffered_byte_length: usize,
     internal_buffer_position: usize,
-    tag_stack: Vec<ProcessingTag<TSpec>>,
-    emission_queue: VecDeque<Result<(TSpec, usize), TagIteratorError>>,
+    tag_stack: TagStack<ProcessingTag<TSpec>>,
+    emission_queue: VecDeque<Result<QueuedTag<TSpec>, TagIteratorError>>,
+    payload_pool: Vec<Vec<u8>>,
     last_emitted_tag_offset: usize,
+    last_emitted_tag_level: usize,
+    last_emitted_tag_size: ElementSize,
+    last_emitted_tag_size_length: usize,
+    last_emitted_tag_header_len: usize,
+    last_emitted_tag_layout: Option<ElementLayout>,
+    record_buffered_layout: bool,
     has_determined_doc_path: bool,
 
     emit_master_end_when_eof: bool,
+    header_event_threshold: Option<usize>,
+    eof_predicate: Option<EofPredicate>,
+    allocation_hook: Option<AllocationHook>,
+    partial_header: Option<PartialHeader>,
+    resume_header: Option<PartialHeader>,
 }
 
 impl<R: Read, TSpec> TagIterator<R, TSpec>
@@ -89,22 +212,59 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
     /// This initializes the [`TagIterator`] with a specific byte capacity.  The iterator will still reallocate if necessary. (Reallocation occurs if the iterator comes across a tag that should be output as a [`Master::Full`] and its size in bytes is greater than the iterator's current buffer capacity.)
     ///
     pub fn with_capacity(source: R, tags_to_buffer: &[TSpec], capacity: usize) -> Self {
-        let buffer = vec![0;capacity];
+        #[cfg(not(feature = "bytes"))]
+        let buffer = vec![0;capacity].into_boxed_slice();
+        #[cfg(feature = "bytes")]
+        let buffer = bytes::BytesMut::zeroed(capacity);
+        let mut metrics: MetricsTracker<ReadMetrics> = MetricsTracker::default();
+        metrics.observe_buffer(capacity);
 
         TagIterator {
             source,
             tag_ids_to_buffer: tags_to_buffer.iter().map(|tag| tag.get_id()).collect(),
             allowed_errors: 0,
             max_allowed_tag_size: Some(4 * usize::pow(1000, 3)), // 4GB
-            buffer: buffer.into_boxed_slice(),
+            max_nesting_depth: None,
+            validate_restricted_values: false,
+            zero_length_values: ZeroLengthValues::EmitDefault,
+            trailing_data: TrailingData::Parse,
+            reached_trailing_data: false,
+            size_past_end: SizePastEnd::Read,
+            crc_mismatch: CrcMismatch::Ignore,
+            source_len: None,
+            read_timeout: None,
+            skip_crc32_elements: false,
+            binary_seek: None,
+            profile: None,
+            max_id_length: DEFAULT_MAX_ID_LENGTH,
+            transforms: ContentTransforms::default(),
+            metrics,
+            corrupt_ranges: Vec::new(),
+            event_log: None,
+            tag_filter: None,
+            buffer,
+            #[cfg(feature = "bytes")]
+            buffer_capacity: capacity,
             buffered_byte_length: 0,
             buffer_offset: None,
             internal_buffer_position: 0,
-            tag_stack: Vec::new(),
-            emission_queue: VecDeque::new(),
+            tag_stack: TagStack::new(),
+            emission_queue: VecDeque::with_capacity(DEFAULT_QUEUE_LEN),
+            payload_pool: Vec::new(),
             last_emitted_tag_offset: 0,
+            last_emitted_tag_level: 0,
+            last_emitted_tag_size: Unknown,
+            last_emitted_tag_size_length: 0,
+            last_emitted_tag_header_len: 0,
+            last_emitted_tag_layout: None,
+            record_buffered_layout: false,
             has_determined_doc_path: false,
             emit_master_end_when_eof: true,
+            header_event_threshold: None,
+            eof_predicate: None,
+            allocation_hook: None,
+            partial_header: None,
+            resume_header: None,
         }
     }
 
@@ -138,16 +298,193 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         self.max_allowed_tag_size = size;
     }
 
+    ///
+    /// Configures the maximum number of "Master" elements that can be open inside one another before the iterator considers the file invalid.
+    ///
+    /// There is no limit by default.  When one is set, the iterator returns a [`CorruptedFileError::NestingTooDeep`] error for any "Master" element that would be opened inside `depth` others, so that a crafted file with thousands of nested elements can't make it use memory without bound.  Real specifications nest far fewer than 128 levels deep, which makes that a reasonable limit for untrusted input.  The offending element is stepped over along with its children if it has a known size; the children of an element with an unknown size are read as they come (and rejected in turn if they are "Master" elements too deep to open).  Passing `None` removes the limit.
+    ///
+    pub fn set_max_nesting_depth(&mut self, depth: Option<usize>) {
+        self.max_nesting_depth = depth;
+    }
+
+    ///
+    /// Registers a callback that must approve growing the read buffer to more than `threshold` bytes.
+    ///
+    /// The buffer only grows when an element's data doesn't fit in it, so the callback receives the size of that data and can apply the application's own memory policy (rather than the single limit set by [`Self::set_max_allowable_tag_size()`]).  If the callback returns `false`, the element's data is skipped without being buffered and the iterator returns a [`TagIteratorError::AllocationDenied`] error in its place; iteration can continue past the error.  Registering a new callback replaces the previous one.
+    ///
+    pub fn on_large_allocation(&mut self, threshold: usize, callback: impl FnMut(usize) -> bool + Send + 'static) {
+        self.allocation_hook = Some((threshold, Box::new(callback)));
+    }
+
+    ///
+    /// Configures whether the iterator checks unsigned integer tags against the values allowed by `<TSpec>` (see [`EbmlSpecification::get_restricted_values()`]).
+    ///
+    /// Disabled by default.  When enabled, a tag holding any other value is returned as a [`CorruptedFileError::RestrictedValue`] error instead of the tag.  Iteration can continue past the error.
+    ///
+    pub fn validate_restricted_values(&mut self, validate: bool) {
+        self.validate_restricted_values = validate;
+    }
+
+    ///
+    /// Configures how the iterator handles unsigned integer, signed integer, float, and utf8 tags that have no data.
+    ///
+    /// By default, these tags are emitted with the default value declared by the spec (see [`EbmlSpecification::get_default_value()`]), or with a value of `0`, `0.0`, or an empty string if the spec doesn't declare one.  See [`ZeroLengthValues`] for the other options.
+    ///
+    pub fn set_zero_length_values(&mut self, behavior: ZeroLengthValues) {
+        self.zero_length_values = behavior;
+    }
+
+    ///
+    /// Configures how the iterator handles data after the last top-level element of the document.
+    ///
+    /// By default, the iterator tries to read it as more tags, which can surface confusing corruption errors for files that end in padding or junk.  See [`TrailingData`] for the alternatives.
+    ///
+    pub fn set_trailing_data(&mut self, behavior: TrailingData) {
+        self.trailing_data = behavior;
+    }
+
+    ///
+    /// Configures how the iterator handles elements whose size extends past the end of the source.
+    ///
+    /// By default, the iterator tries to read them anyway, which means buffering the rest of the source before finding out the element is cut short.  See [`SizePastEnd`] for the alternatives.  Elements are only checked once the length of the source is known, which can be set using [`Self::set_source_len()`] (or [`Self::detect_source_len()`] for sources that implement [`Seek`]).
+    ///
+    pub fn set_size_past_end(&mut self, behavior: SizePastEnd) {
+        self.size_past_end = behavior;
+    }
+
+    ///
+    /// Configures whether the iterator checks "Master" elements against their `CRC-32` child, and what happens to elements that don't match.
+    ///
+    /// By default, checksums aren't checked.  Depending on how far corruption should be allowed to spread, mismatched elements can be reported as errors, emitted with a warning counted in [`ReadMetrics::crc_mismatches`], or skipped along with all of their children.  See [`CrcMismatch`] for which elements can be checked.
+    ///
+    pub fn set_crc_mismatch(&mut self, behavior: CrcMismatch) {
+        self.crc_mismatch = behavior;
+    }
+
+    ///
+    /// Sets the total length in bytes of the source, counted from where the iterator started reading, or clears it if `len` is `None`.
+    ///
+    /// This is only used to check element sizes as configured by [`Self::set_size_past_end()`].
+    ///
+    pub fn set_source_len(&mut self, len: Option<usize>) {
+        self.source_len = len;
+    }
+
+    ///
+    /// Limits the iterator to the elements with the given `ids`, or removes the limit if `ids` is `None` (the default).
+    ///
+    /// Only the listed elements, their descendants, and the "Master" elements that (according to `<TSpec>`) can lead to them are emitted.  Every other element with a known size is stepped over along with all of its children, without being decoded or copied into a tag, which makes scanning for a few metadata elements of a large document much cheaper.  "Master" elements with an unknown size can't be stepped over, so their children are read (and filtered) without the element itself being emitted.  If any of the `ids` is a global element, every "Master" element is read since any of them could contain it.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::specs::Master;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x42, 0x86, 0x81, 0x01, 0x42, 0x87, 0x81, 0x02];
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// iterator.set_tag_id_filter(Some(&[0x4287]));
+    /// let tags: Vec<EmptySpec> = iterator.map(|t| t.unwrap()).collect();
+    /// assert_eq!(vec![EmptySpec::with_data(0x4287, &[0x02])], tags);
+    /// ```
+    ///
+    pub fn set_tag_id_filter(&mut self, ids: Option<&[u64]>) {
+        self.tag_filter = ids.map(|ids| TagFilter::Ids(TagIdFilter::new::<TSpec>(ids)));
+    }
+
+    ///
+    /// Limits the iterator to the elements at the given document `paths`, or removes the limit if `paths` is `None` (the default).
+    ///
+    /// Each path is a list of tag ids starting at a root element, e.g. `[Segment, Cluster, SimpleBlock]`.  The element at the end of each path is emitted along with its descendants and the elements along the path leading to it, so filtering on `[Segment, Tracks]` emits the `Segment` start and end tags around everything in `Tracks`.  Paths are matched against the tags that are actually open (including any inferred parents), so an element with the right id somewhere else in the document isn't emitted.  Other elements are stepped over the same way as with [`Self::set_tag_id_filter()`], which this filter replaces.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x42, 0x86, 0x81, 0x01, 0x42, 0x87, 0x81, 0x02];
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// iterator.set_path_filter(Some(&[&[0x4287]]));
+    /// let tags: Vec<EmptySpec> = iterator.map(|t| t.unwrap()).collect();
+    /// assert_eq!(vec![EmptySpec::with_data(0x4287, &[0x02])], tags);
+    /// ```
+    ///
+    pub fn set_path_filter(&mut self, paths: Option<&[&[u64]]>) {
+        self.tag_filter = paths.map(|paths| TagFilter::Paths(paths.iter().map(|path| path.to_vec()).collect()));
+    }
+
+    ///
+    /// Configures whether the iterator skips `CRC-32` elements instead of emitting them.
+    ///
+    /// Disabled by default.  The iterator never validates checksums, so applications that trust their input can enable this to step over the payload of every `CRC-32` element without copying it into a tag.  Elements that would otherwise cause an error (e.g. if they are truncated) are still read normally so the error is reported.
+    ///
+    pub fn skip_crc32_elements(&mut self, skip: bool) {
+        self.skip_crc32_elements = skip;
+    }
+
+    ///
+    /// Sets a [`Profile`] that every read tag must conform to, or removes it if `profile` is `None`.
+    ///
+    /// Tags that break the profile are returned as [`CorruptedFileError::ProfileViolation`] errors instead of the tag.  Iteration can continue past the error.
+    ///
+    pub fn set_profile(&mut self, profile: Option<Profile>) {
+        self.profile = profile;
+    }
+
+    ///
+    /// Registers a [`ContentTransform`] to decode the data of binary tags with any of the given `ids`.
+    ///
+    /// Emitted tags contain the decoded data.  If a transform fails, the iterator returns a [`TagIteratorError::TransformError`].  Registering a transform for an id that already has one replaces it.
+    ///
+    pub fn add_content_transform(&mut self, ids: &[u64], transform: impl ContentTransform + 'static) {
+        self.transforms.add(ids, Box::new(transform));
+    }
+
+    ///
+    /// Returns the iterator's progress and throughput counters so far.  See [`ReadMetrics`] for details.
+    ///
+    pub fn metrics(&self) -> ReadMetrics {
+        self.metrics.snapshot()
+    }
+
+    ///
+    /// Registers a callback that periodically receives the iterator's [`ReadMetrics`].
+    ///
+    /// The callback is called from [`Iterator::next()`] at most once per `interval`, which makes it suitable for exporting parsing health to a monitoring system without polling.  On `wasm32-unknown-unknown`, which has no clock, the interval can't be measured and the callback is called every time instead.  Registering a new callback replaces the previous one.
+    ///
+    pub fn set_metrics_callback(&mut self, interval: Duration, callback: impl FnMut(&ReadMetrics) + Send + 'static) {
+        self.metrics.set_callback(interval, Box::new(callback));
+    }
+
+    ///
+    /// Returns the data buffer of a tag that is no longer needed so that the iterator can reuse its allocation.
+    ///
+    /// The iterator keeps a small pool of returned buffers and fills them with the data of later binary and utf8 tags, which avoids an allocation per tag when reading many small tags.  Buffers can be taken from binary tag variants, or from utf8 tag variants using [`String::into_bytes()`].  Binary tags only reuse pooled buffers if the specification implements [`EbmlSpecification::get_binary_tag_owned()`] (specifications generated by the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro do), and not when the `"bytes"` feature is enabled.
+    ///
+    pub fn recycle_payload(&mut self, payload: Vec<u8>) {
+        if self.payload_pool.len() < MAX_POOLED_PAYLOADS {
+            self.payload_pool.push(payload);
+        }
+    }
+
     ///
     /// Instructs the iterator to attempt to recover after reaching corrupted file data.
     /// 
     /// This method can be used to skip over corrupted sections of a read stream without recreating a new iterator.  The iterator will seek forward from its current internal position until it reaches either a valid EBML tag id or EOF.  After recovery, [`Iterator::next()`] *should* return an [`Ok`] result.
     /// 
+    /// The skipped bytes are recorded in [`Self::corrupt_ranges()`], including when no valid tag is found before EOF.
+    ///
     pub fn try_recover(&mut self) -> Result<(), TagIteratorError> {
+        self.partial_header = None;
         let original_position = self.current_offset();        
         loop {
             if !self.ensure_data_read(1)? {
-                return Err(TagIteratorError::UnexpectedEOF { tag_start: self.current_offset(), tag_id: None, tag_size: None, partial_data: None });
+                let skipped = original_position..self.current_offset();
+                self.log_event(|| ParseEvent::RecoveryScan { skipped: skipped.clone(), found_header: false });
+                self.record_corrupt_range(skipped);
+                return Err(TagIteratorError::UnexpectedEOF(PartialTag::in_header(self.current_offset(), None)));
             }
 
             self.internal_buffer_position += 1;
@@ -159,14 +496,68 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         // As part of recovery, update internal tag stack sizes so that we don't get "oversized children" errors after skipping corrupted data
         let diff = self.current_offset() - original_position;
         for tag in self.tag_stack.iter_mut() {
-            if let EBMLSize::Known(size) = &tag.size {
-                tag.size = EBMLSize::Known(size + diff);
+            if let ElementSize::Known(size) = &tag.size {
+                tag.size = ElementSize::Known(size.saturating_add(diff));
             }
         }
 
+        let skipped = original_position..self.current_offset();
+        self.log_event(|| ParseEvent::RecoveryScan { skipped: skipped.clone(), found_header: true });
+        self.record_corrupt_range(skipped);
+        self.metrics.add_recovery();
         Ok(())
     }
 
+    fn record_corrupt_range(&mut self, range: Range<usize>) {
+        match self.corrupt_ranges.last_mut() {
+            _ if range.is_empty() => {},
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => self.corrupt_ranges.push(range),
+        }
+    }
+
+    ///
+    /// Returns the byte ranges of the source that were skipped by [`Self::try_recover()`], in the order they were skipped.
+    ///
+    /// Offsets are in the same terms as [`Self::last_emitted_tag_offset()`].  Adjacent ranges (from recovering several times in a row) are merged.  This lets repair tools report exactly which parts of a damaged file could not be read.
+    ///
+    pub fn corrupt_ranges(&self) -> &[Range<usize>] {
+        &self.corrupt_ranges
+    }
+
+    ///
+    /// Controls whether the iterator records the decisions it makes while parsing (see [`ParseEvent`]), keeping up to `capacity` of the most recent ones, or stops recording if `capacity` is `None` (the default).
+    ///
+    /// The log can be retrieved using [`Self::event_log()`].  Dumping it when an error is returned shows how the iterator got there (which headers it read, which elements it thought were open, and why it closed them), which is usually the missing piece in reports about corrupted files.  Enabling or disabling the log discards anything recorded so far.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x1a, 0x45, 0xdf, 0xa3, 0x84, 0x42, 0x86, 0x81, 0x01];
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// iterator.record_event_log(Some(100));
+    /// for tag in iterator.by_ref() {
+    ///     if tag.is_err() {
+    ///         eprintln!("{}", iterator.event_log().unwrap());
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    ///
+    pub fn record_event_log(&mut self, capacity: Option<usize>) {
+        self.event_log = capacity.map(EventLog::new);
+    }
+
+    ///
+    /// Returns the events recorded since the log was enabled with [`Self::record_event_log()`], or `None` if it isn't enabled.
+    ///
+    pub fn event_log(&self) -> Option<&EventLog> {
+        self.event_log.as_ref()
+    }
+
     ///
     /// Consumes self and returns the underlying read stream.
     /// 
@@ -203,6 +594,123 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         self.last_emitted_tag_offset
     }
 
+    ///
+    /// Returns the nesting level of the last emitted tag.
+    ///
+    /// Top level tags (like the EBML header or a Matroska Segment) are at level 0, their children at level 1, and so on.  A [`Master::End`] variant is at the same level as its [`Master::Start`].  If the iterator started in the middle of a document, levels count the parents implied by the first tag's document path.
+    ///
+    pub fn last_emitted_tag_level(&self) -> usize {
+        self.last_emitted_tag_level
+    }
+
+    ///
+    /// Returns the data size declared by the last emitted tag.
+    ///
+    /// This is the size of the tag's data, not including its header.  For "Master" tags it is reported along with the [`Master::Start`] (as well as the [`Master::End`] and [`Master::Full`] variants), so consumers can plan to skip elements or track progress without waiting for the tag to end.
+    ///
+    pub fn last_emitted_tag_size(&self) -> ElementSize {
+        self.last_emitted_tag_size
+    }
+
+    ///
+    /// Returns how the header of the last emitted tag was encoded in the source.
+    ///
+    /// Writing each tag with [`WriteOptions::with_encoding()`](crate::WriteOptions::with_encoding) and the encoding reported here reproduces the source byte for byte (as long as no tags are buffered into [`Master::Full`] variants, since only the outer tag's encoding is reported for those, and no content transforms or Crc32 skipping are configured).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// // The size vint of this element is 2 bytes long, though 1 would do
+    /// let data: &[u8] = &[0x42, 0x86, 0x40, 0x02, 0x00, 0x01];
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// while let Some(tag) = iterator.next() {
+    ///     writer.write_advanced(&tag.unwrap(), WriteOptions::with_encoding(iterator.last_emitted_tag_encoding())).unwrap();
+    /// }
+    /// assert_eq!(data, &writer.into_inner().unwrap()[..]);
+    /// ```
+    ///
+    pub fn last_emitted_tag_encoding(&self) -> TagEncoding {
+        TagEncoding { size: self.last_emitted_tag_size, size_length: self.last_emitted_tag_size_length }
+    }
+
+    ///
+    /// Returns where the last emitted tag was read from: its offset, header length, and data size.
+    ///
+    /// This combines [`Self::last_emitted_tag_offset()`] and [`Self::last_emitted_tag_size()`] with the length of the tag's header, which together locate the tag's original bytes.  See [`TagSpan`].
+    ///
+    pub fn last_emitted_tag_span(&self) -> TagSpan {
+        TagSpan { offset: self.last_emitted_tag_offset, header_len: self.last_emitted_tag_header_len, size: self.last_emitted_tag_size }
+    }
+
+    ///
+    /// Returns an iterator that yields each tag along with its [`TagSpan`], for applications that need to slice the original bytes of tags out of the source (like remuxers).
+    ///
+    /// The returned iterator borrows this one, so iteration can continue with [`Iterator::next()`] once it is dropped.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x42, 0x86, 0x81, 0x01, 0x42, 0x87, 0x82, 0x01, 0x02];
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// for tag in iterator.iter_with_spans() {
+    ///     let (tag, span) = tag.unwrap();
+    ///     let original = &data[span.range().unwrap()];
+    ///     println!("{:?} was read from {:x?}", tag, original);
+    ///     # assert_eq!(span.data_offset() - span.offset, 3);
+    /// }
+    /// ```
+    ///
+    pub fn iter_with_spans(&mut self) -> WithSpans<'_, R, TSpec> {
+        WithSpans { iterator: self }
+    }
+
+    ///
+    /// Controls whether the iterator records where the children of buffered [`Master::Full`] tags were read from.
+    ///
+    /// Buffered children are returned inside their parent, so [`Self::last_emitted_tag_offset()`] and friends can only describe the outer tag.  When enabled, [`Self::last_emitted_tag_layout()`] also describes every child, which lets index builders map buffered children back to positions in the file.  Disabled by default.
+    ///
+    pub fn record_buffered_layout(&mut self, record: bool) {
+        self.record_buffered_layout = record;
+    }
+
+    ///
+    /// Returns the layout of the last emitted tag if it was a [`Master::Full`] variant and [`Self::record_buffered_layout()`] is enabled.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::specs::Master;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Buffer Matroska Cues, but keep track of where each CuePoint is in the file
+    /// let file = File::open("my_ebml_file.ebml")?;
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(file, &[EmptySpec::with_children(0x1c53bb6b, vec![])]);
+    /// iterator.record_buffered_layout(true);
+    /// while let Some(tag) = iterator.next() {
+    ///     if let Some(layout) = iterator.last_emitted_tag_layout() {
+    ///         for cue_point in layout.children.iter() {
+    ///             println!("0x{:x} at {}", cue_point.id, cue_point.offset);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn last_emitted_tag_layout(&self) -> Option<&ElementLayout> {
+        self.last_emitted_tag_layout.as_ref()
+    }
+
     ///
     /// Control whether the iterator should emit closing tags when it reaches EOF.
     /// 
@@ -214,59 +722,470 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         self.emit_master_end_when_eof = emit;
     }
 
-    #[inline(always)]
-    fn current_offset(&self) -> usize {
-        self.buffer_offset.unwrap_or(0) + self.internal_buffer_position
+    ///
+    /// Sets a predicate that decides whether the stream is complete when the source runs out of data while unknown-size "Master" tags are open.
+    ///
+    /// Unknown-size tags can't end until the stream does, but a source that is still growing (such as a live recording, or a download in progress) reaches EOF many times before that.  The predicate receives the number of bytes read so far and should only return `true` once the stream is known to be complete (e.g. when an HTTP `Content-Length` has been reached).  Until then, the iterator returns [`TagIteratorError::NeedMoreData`] instead of closing the open tags, and calling [`Iterator::next()`] again continues from the same position once more data is available.  When the predicate returns `true`, open tags are closed as configured by [`Self::emit_master_end_when_eof()`].  Running out of data partway through an element is still reported as [`TagIteratorError::UnexpectedEOF`] (see [`Self::resume_partial()`]).
+    ///
+    /// Setting a new predicate replaces the previous one.
+    ///
+    pub fn set_eof_predicate(&mut self, predicate: impl FnMut(usize) -> bool + Send + 'static) {
+        self.eof_predicate = Some(Box::new(predicate));
     }
 
-    fn private_read(&mut self, internal_buffer_start: usize) -> Result<bool, TagIteratorError> {
-        let bytes_read = self.source.read(&mut self.buffer[internal_buffer_start..]).map_err(|source| TagIteratorError::ReadError { source })?;
-        if bytes_read == 0 {
-            Ok(false)
-        } else {
-            self.buffered_byte_length += bytes_read;
-            Ok(true)
+    ///
+    /// Finishes reading a tag whose data was cut off by EOF, once more of the data is available.
+    ///
+    /// `partial` must come from the [`TagIteratorError::UnexpectedEOF`] error the iterator just returned.  The rest of the tag's data is taken from `partial` (if more was added with [`PartialTag::resume()`]) and then from `additional_source`, which must continue right where the iterator's source ended.  The tag isn't read again from its start, and iteration continues from the iterator's own source right after the tag, so the bytes taken from `additional_source` must not be read through the iterator's source as well.
+    ///
+    /// Returns the completed tag, unless it ends unknown-size "Master" tags; their [`Master::End`] tags are returned first and the completed tag follows from [`Iterator::next()`].  If there still isn't enough data, another [`TagIteratorError::UnexpectedEOF`] is returned, and this can be called again with its [`PartialTag`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagIteratorError::ReadError`] of kind [`std::io::ErrorKind::InvalidInput`] if `partial` isn't the tag the iterator stopped in, as well as any error from reading `additional_source` or the tag itself.
+    ///
+    pub fn resume_partial(&mut self, partial: PartialTag, mut additional_source: impl Read) -> Result<TSpec, TagIteratorError> {
+        let header = match self.partial_header.take() {
+            Some(header) if header.tag_start == partial.tag_start && partial.id == Some(header.tag_id) => header,
+            other => {
+                self.partial_header = other;
+                return Err(TagIteratorError::ReadError { source: std::io::Error::new(std::io::ErrorKind::InvalidInput, "partial tag doesn't match the tag the iterator stopped in") });
+            },
+        };
+
+        let buffered = self.buffered_byte_length - self.internal_buffer_position;
+        let mut data = partial.data.as_deref().and_then(|data| data.get(buffered..)).unwrap_or(&[]).to_vec();
+        let missing = header.size.saturating_sub(buffered + data.len());
+        (&mut additional_source).take(missing as u64).read_to_end(&mut data).map_err(|source| TagIteratorError::ReadError { source })?;
+        if data.is_empty() {
+            self.partial_header = Some(header);
+            return Err(TagIteratorError::UnexpectedEOF(partial));
         }
-    }
 
-    fn ensure_capacity(&mut self, required_capacity: usize) {
-        if required_capacity > self.buffer.len() {
-            let mut new_buffer = Vec::from(&self.buffer[..]);
-            new_buffer.resize(required_capacity, 0);
-            self.buffer = new_buffer.into_boxed_slice();
+        if self.buffered_byte_length + data.len() > self.buffer.len() {
+            self.compact_buffer();
         }
+        self.buffer[self.buffered_byte_length..(self.buffered_byte_length + data.len())].copy_from_slice(&data);
+        self.buffered_byte_length += data.len();
+        self.metrics.add_bytes_read(data.len());
+
+        self.resume_header = Some(header);
+        self.next().expect("resumed tag should be read from the buffer")
     }
 
-    fn ensure_data_read(&mut self, length: usize) -> Result<bool, TagIteratorError> {
-        if self.internal_buffer_position + length <= self.buffered_byte_length {
-            return Ok(true)
-        }
+    ///
+    /// Returns a reader over the payload of the next element, without decoding it into a tag.
+    ///
+    /// This lets existing decoders (image loaders, codec parsers, etc.) consume large payloads straight from the source instead of from an intermediate [`Vec`].  The returned [`ElementReader`] is limited to the element's payload, and any part of it that isn't read is skipped when the reader is dropped, after which the iterator continues with the following tag.  Data is passed through as it is stored, so content transforms are not applied.
+    ///
+    /// Returns `Ok(None)` if the next item is not an element with a payload - a "Master" tag, an element with an unknown size, a tag that is already queued (such as a [`Master::End`]), or the end of the source.  In that case, use [`Iterator::next()`] to advance past it.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x42, 0x86, 0x83, 0x01, 0x02, 0x03];
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// let mut reader = iterator.next_element_reader().unwrap().unwrap();
+    /// let mut payload = Vec::new();
+    /// reader.read_to_end(&mut payload).unwrap();
+    /// assert_eq!(vec![0x01, 0x02, 0x03], payload);
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the next element's header can't be read.  Like [`Iterator::next()`], the same error is returned until [`Self::try_recover()`] is called.
+    ///
+    pub fn next_element_reader(&mut self) -> Result<Option<ElementReader<'_, R, TSpec>>, TagIteratorError> {
+        Ok(self.begin_element_payload()?.map(move |(tag_id, tag_start, size)| ElementReader {
+            iterator: self,
+            tag_id,
+            tag_start,
+            size,
+            remaining: size,
+        }))
+    }
 
-        if self.buffer_offset.is_none() {
-            if !self.private_read(0)? {
-                return Ok(false);
-            }
-            self.buffer_offset = Some(0);
-            self.internal_buffer_position = 0;
-        } else {
-            while self.internal_buffer_position + length > self.buffered_byte_length {
-                self.buffer.copy_within(self.internal_buffer_position..self.buffered_byte_length, 0);
-                self.buffered_byte_length -= self.internal_buffer_position;
-                self.buffer_offset = Some(self.current_offset());
-                self.internal_buffer_position = 0;
-                if !self.private_read(self.buffered_byte_length)? {
-                    return Ok(false);
-                }
+    ///
+    /// Configures which elements [`Self::next_event()`] returns as a [`ReadEvent::Header`] instead of reading their payload, or disables header events if `threshold` is `None` (the default).
+    ///
+    /// Binary elements (and elements with ids that aren't in `<TSpec>`) with a known size of at least `threshold` bytes are returned as headers.
+    ///
+    pub fn set_header_event_threshold(&mut self, threshold: Option<usize>) {
+        self.header_event_threshold = threshold;
+    }
+
+    ///
+    /// Returns the next tag, or just the header of the next element if its payload is large enough to be returned as a [`ReadEvent::Header`] (see [`Self::set_header_event_threshold()`]).
+    ///
+    /// Header events are returned as soon as the element's header has been read, before any of its payload is.  The caller then decides whether to stream the payload, skip it, or buffer it into a tag through the returned [`PendingElement`].  Everything else is returned as a [`ReadEvent::Tag`], exactly as [`Iterator::next()`] would.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::iterator::ReadEvent;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x42, 0x86, 0x83, 0x01, 0x02, 0x03, 0x42, 0x87, 0x81, 0x01];
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// iterator.set_header_event_threshold(Some(2));
+    /// match iterator.next_event().unwrap().unwrap() {
+    ///     ReadEvent::Header(pending) => {
+    ///         assert_eq!((0x4286, 3, 3), (pending.tag_id(), pending.size(), pending.data_offset()));
+    ///         pending.skip().unwrap();
+    ///     },
+    ///     ReadEvent::Tag(_) => unreachable!(),
+    /// }
+    /// assert!(matches!(iterator.next_event(), Some(Ok(ReadEvent::Tag(_)))));
+    /// ```
+    ///
+    pub fn next_event(&mut self) -> Option<Result<ReadEvent<'_, R, TSpec>, TagIteratorError>> {
+        if let Some(threshold) = self.header_event_threshold {
+            let is_large_binary = |spec_tag_type: Option<TagDataType>, size: usize| matches!(spec_tag_type, Some(TagDataType::Binary) | None) && size >= threshold;
+            // Errors reading the header are left for `next()` to report
+            if let Ok(Some((tag_id, tag_start, size))) = self.begin_element_payload_if(is_large_binary) {
+                let data_offset = self.current_offset();
+                return Some(Ok(ReadEvent::Header(PendingElement {
+                    data_offset,
+                    reader: ElementReader { iterator: self, tag_id, tag_start, size, remaining: size },
+                })));
             }
         }
-        Ok(true)
+        self.next().map(|tag| tag.map(ReadEvent::Tag))
     }
 
-    #[inline(always)]
-    fn peek_tag_id(&mut self) -> Result<(u64, usize), TagIteratorError> {
-        self.ensure_data_read(8)?;
-        if self.buffer[self.internal_buffer_position] == 0 {
-            return Ok((0, 1));
+    ///
+    /// Returns the next tag like [`Iterator::next()`], except that the data of "Utf8" and "Binary" elements is borrowed from the iterator's buffer instead of being copied into the tag.
+    ///
+    /// The borrowed data is only valid until the iterator is used again, so this can't be expressed through [`Iterator`], but it avoids an allocation per element when most of a large document is scanned and thrown away.  Elements that need more than the buffered data (content transforms, skipped `CRC-32` elements, seeking over binary payloads, or errors of any kind) are read the regular way and returned as a [`BorrowedTag::Owned`], as are all other tags.  Use [`BorrowedTag::into_owned()`] to keep a tag around.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagIterator;
+    /// use ebml_iterable::iterator::BorrowedTag;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x42, 0x86, 0x83, 0x01, 0x02, 0x03];
+    /// let mut iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// while let Some(tag) = iterator.next_borrowed() {
+    ///     match tag.unwrap() {
+    ///         BorrowedTag::Binary { value, .. } => assert_eq!(&[0x01, 0x02, 0x03], value),
+    ///         _ => {},
+    ///     }
+    /// }
+    /// ```
+    ///
+    pub fn next_borrowed(&mut self) -> Option<Result<BorrowedTag<'_, TSpec>, TagIteratorError>> {
+        match self.read_borrowable_element() {
+            Some((id, data_type, data)) => {
+                let value = &self.buffer[data];
+                Some(Ok(match data_type {
+                    TagDataType::Utf8 => BorrowedTag::Utf8 { id, value: std::str::from_utf8(value).expect("utf8 data should have been validated") },
+                    _ => BorrowedTag::Binary { id, value },
+                }))
+            },
+            None => self.next().map(|tag| tag.map(BorrowedTag::Owned)),
+        }
+    }
+
+    ///
+    /// Consumes the next element if it is a "Utf8" or "Binary" element that can be returned straight from the buffer, returning its id, type, and the position of its data in the buffer.
+    ///
+    fn read_borrowable_element(&mut self) -> Option<(u64, TagDataType, Range<usize>)> {
+        if !self.emission_queue.is_empty() || self.resume_header.is_some() || self.skip_crc32_elements || self.binary_seek.is_some() || self.tag_filter.is_some() {
+            return None;
+        }
+        self.queue_ended_masters();
+        if !self.emission_queue.is_empty() || !matches!(self.ensure_data_read(1), Ok(true)) {
+            return None;
+        }
+
+        let (tag_id, spec_tag_type, size, header_len) = self.peek_valid_tag_header().ok()?;
+        let data_type = match spec_tag_type {
+            Some(TagDataType::Binary) if !self.transforms.handles(tag_id) => TagDataType::Binary,
+            Some(TagDataType::Utf8) => TagDataType::Utf8,
+            _ => return None,
+        };
+        // Empty values and elements cut short by the end of the source are left for the regular read to report
+        let size = match size {
+            Known(size) if size > 0 && !matches!(self.available_after_header(header_len), Some(available) if size > available) => size,
+            _ => return None,
+        };
+        let element_len = header_len + size;
+        if self.would_end_open_master(tag_id) || self.needs_allocation_approval(element_len) {
+            return None;
+        }
+        self.ensure_capacity(element_len);
+        if !matches!(self.ensure_data_read(element_len), Ok(true)) {
+            return None;
+        }
+
+        let tag_start = self.current_offset();
+        let data = (self.internal_buffer_position + header_len)..(self.internal_buffer_position + element_len);
+        if data_type == TagDataType::Utf8 && std::str::from_utf8(&self.buffer[data.clone()]).is_err() {
+            return None;
+        }
+
+        self.partial_header = None;
+        self.internal_buffer_position += element_len;
+        self.log_header(tag_start, tag_id, header_len, Known(size), Known(size));
+        self.last_emitted_tag_offset = tag_start;
+        self.last_emitted_tag_level = self.tag_stack.len();
+        self.last_emitted_tag_size = Known(size);
+        self.last_emitted_tag_size_length = header_len - tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count();
+        self.last_emitted_tag_header_len = header_len;
+        self.last_emitted_tag_layout = None;
+        self.metrics.add_emitted(true);
+        self.metrics.tick();
+        Some((tag_id, data_type, data))
+    }
+
+    ///
+    /// Consumes self and returns an iterator over the values of every non-master element, each paired with the path of "Master" element ids containing it.
+    ///
+    /// This hides [`Master::Start`] and [`Master::End`] tags entirely, which is convenient when only the metadata fields of a document are of interest.  See [`FlattenValues`] for details.
+    ///
+    pub fn flatten_values(self) -> FlattenValues<R, TSpec> {
+        FlattenValues::new(self)
+    }
+
+    ///
+    /// Consumes self and returns a reader that checks the id and data type of each tag as it extracts its value.
+    ///
+    /// This suits formats that store their elements in a strict order.  See [`TypedReader`] for details.
+    ///
+    pub fn typed(self) -> TypedReader<R, TSpec> {
+        TypedReader::new(self)
+    }
+
+    ///
+    /// Creates the tag for a binary (or unknown) element from its payload.
+    ///
+    fn payload_tag(&mut self, tag_id: u64, data: Vec<u8>) -> Result<TSpec, TagIteratorError> {
+        if TSpec::get_tag_data_type(tag_id).is_none() {
+            return Ok(TSpec::get_raw_tag(tag_id, &data));
+        }
+        let data = match self.transforms.decode(tag_id, &data) {
+            Some(decoded) => decoded.map_err(|source| TagIteratorError::TransformError { tag_id, source })?,
+            None => data,
+        };
+        Ok(TSpec::get_binary_tag_owned(tag_id, data).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", tag_id)))
+    }
+
+    ///
+    /// Consumes the header of the next element if it has a payload that can be read with [`Self::read_payload()`], returning its id, start offset and payload size.
+    ///
+    pub(crate) fn begin_element_payload(&mut self) -> Result<Option<(u64, usize, usize)>, TagIteratorError> {
+        self.begin_element_payload_if(|_, _| true)
+    }
+
+    ///
+    /// Works like [`Self::begin_element_payload()`], but only for elements that `accept` approves based on their data type and payload size.
+    ///
+    fn begin_element_payload_if(&mut self, accept: impl FnOnce(Option<TagDataType>, usize) -> bool) -> Result<Option<(u64, usize, usize)>, TagIteratorError> {
+        if !self.emission_queue.is_empty() {
+            return Ok(None);
+        }
+
+        self.queue_ended_masters();
+        if !self.emission_queue.is_empty() {
+            return Ok(None);
+        }
+
+        if self.internal_buffer_position == self.buffered_byte_length && !self.ensure_data_read(1)? {
+            return Ok(None);
+        }
+
+        let tag_start = self.current_offset();
+        let (tag_id, spec_tag_type, declared_size, header_len) = self.peek_valid_tag_header()?;
+        let size = self.truncate_past_end(declared_size, header_len);
+        let data_size = match size {
+            Known(data_size) if !matches!(spec_tag_type, Some(TagDataType::Master)) => data_size,
+            _ => return Ok(None),
+        };
+        if self.would_end_open_master(tag_id) || !self.is_relevant(tag_id, spec_tag_type) || !accept(spec_tag_type, data_size) {
+            return Ok(None);
+        }
+
+        self.internal_buffer_position += header_len;
+        self.log_header(tag_start, tag_id, header_len, declared_size, size);
+        self.last_emitted_tag_offset = tag_start;
+        self.last_emitted_tag_level = self.tag_stack.len();
+        self.last_emitted_tag_size = size;
+        self.last_emitted_tag_size_length = header_len - tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count();
+        self.last_emitted_tag_header_len = header_len;
+        self.last_emitted_tag_layout = None;
+        self.metrics.add_emitted(true);
+        Ok(Some((tag_id, tag_start, data_size)))
+    }
+
+    ///
+    /// Returns whether a tag with this id would end the innermost open "Master" tag (if it has an unknown size).
+    ///
+    pub(crate) fn would_end_open_master(&self, tag_id: u64) -> bool {
+        matches!(self.tag_stack.last(), Some(open_tag) if open_tag.size == Unknown && open_tag.is_ended_by(tag_id))
+    }
+
+    #[cfg(feature = "futures")]
+    ///
+    /// Returns whether every tag read from the source so far has been emitted.
+    ///
+    pub(crate) fn is_drained(&self) -> bool {
+        self.emission_queue.is_empty() && self.internal_buffer_position == self.buffered_byte_length
+    }
+
+    pub(crate) fn read_payload(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let buffered = self.buffered_byte_length - self.internal_buffer_position;
+        if buffered > 0 {
+            let len = buf.len().min(buffered);
+            buf[..len].copy_from_slice(&self.buffer[self.internal_buffer_position..(self.internal_buffer_position + len)]);
+            self.internal_buffer_position += len;
+            return Ok(len);
+        }
+
+        // Nothing is buffered, so read into the caller's buffer instead of staging the data in ours
+        let bytes_read = self.source.read(buf)?;
+        self.buffer_offset = Some(self.current_offset() + bytes_read);
+        self.internal_buffer_position = 0;
+        self.buffered_byte_length = 0;
+        self.metrics.add_bytes_read(bytes_read);
+        Ok(bytes_read)
+    }
+
+    #[inline(always)]
+    fn current_offset(&self) -> usize {
+        self.buffer_offset.unwrap_or(0) + self.internal_buffer_position
+    }
+
+    fn private_read(&mut self, internal_buffer_start: usize) -> Result<bool, TagIteratorError> {
+        let bytes_read = self.source.read(&mut self.buffer[internal_buffer_start..]).map_err(|source| self.read_error(source))?;
+        if bytes_read == 0 {
+            Ok(false)
+        } else {
+            self.buffered_byte_length += bytes_read;
+            self.metrics.add_bytes_read(bytes_read);
+            Ok(true)
+        }
+    }
+
+    #[cfg(not(feature = "bytes"))]
+    fn ensure_capacity(&mut self, required_capacity: usize) {
+        if required_capacity > self.buffer.len() {
+            let mut new_buffer = Vec::from(&self.buffer[..]);
+            new_buffer.resize(required_capacity, 0);
+            self.buffer = new_buffer.into_boxed_slice();
+            self.metrics.observe_buffer(required_capacity);
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    fn ensure_capacity(&mut self, required_capacity: usize) {
+        // The buffer itself is grown the next time data is read, since payloads split off of it may still be in use
+        if required_capacity > self.buffer_capacity {
+            self.buffer_capacity = required_capacity;
+            self.metrics.observe_buffer(required_capacity);
+        }
+    }
+
+    #[cfg(not(feature = "bytes"))]
+    fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    #[cfg(feature = "bytes")]
+    fn capacity(&self) -> usize {
+        self.buffer_capacity
+    }
+
+    fn needs_allocation_approval(&self, required_capacity: usize) -> bool {
+        matches!(self.allocation_hook, Some((threshold, _)) if required_capacity > threshold && required_capacity > self.capacity())
+    }
+
+    fn approve_allocation(&mut self, required_capacity: usize) -> bool {
+        if !self.needs_allocation_approval(required_capacity) {
+            return true;
+        }
+        match self.allocation_hook.as_mut() {
+            Some((_, callback)) => callback(required_capacity),
+            None => true,
+        }
+    }
+
+    ///
+    /// Steps over `size` bytes of data without buffering them.
+    ///
+    fn skip_data(&mut self, size: usize) -> Result<bool, TagIteratorError> {
+        let mut scratch = [0u8; 4096];
+        let mut remaining = size;
+        while remaining > 0 {
+            let len = remaining.min(scratch.len());
+            match self.read_payload(&mut scratch[..len]).map_err(|source| self.read_error(source))? {
+                0 => return Ok(false),
+                read => remaining -= read,
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_error(&self, source: std::io::Error) -> TagIteratorError {
+        if source.kind() == std::io::ErrorKind::TimedOut {
+            TagIteratorError::Timeout { position: self.buffer_offset.unwrap_or(0) + self.buffered_byte_length, timeout: self.read_timeout }
+        } else {
+            TagIteratorError::ReadError { source }
+        }
+    }
+
+    fn ensure_data_read(&mut self, length: usize) -> Result<bool, TagIteratorError> {
+        if self.internal_buffer_position + length <= self.buffered_byte_length {
+            return Ok(true)
+        }
+
+        if self.buffer_offset.is_none() {
+            if !self.private_read(0)? {
+                return Ok(false);
+            }
+            self.buffer_offset = Some(0);
+            self.internal_buffer_position = 0;
+        } else {
+            while self.internal_buffer_position + length > self.buffered_byte_length {
+                // Keep appending after the unread bytes while there is room, and only slide them to the front once the requested data can't fit behind them (or there's no space left to read into)
+                if self.internal_buffer_position + length > self.buffer.len() || self.buffered_byte_length == self.buffer.len() {
+                    self.compact_buffer();
+                }
+                if !self.private_read(self.buffered_byte_length)? {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn compact_buffer(&mut self) {
+        self.buffer.copy_within(self.internal_buffer_position..self.buffered_byte_length, 0);
+        self.buffered_byte_length -= self.internal_buffer_position;
+        self.buffer_offset = Some(self.current_offset());
+        self.internal_buffer_position = 0;
+        #[cfg(feature = "bytes")]
+        if self.buffer.len() < self.buffer_capacity {
+            self.buffer.truncate(self.buffered_byte_length);
+            self.buffer.resize(self.buffer_capacity, 0);
+        }
+    }
+
+    #[inline(always)]
+    fn peek_tag_id(&mut self) -> Result<(u64, usize), TagIteratorError> {
+        self.ensure_data_read(8)?;
+        if let Some(word) = tools::load_vint_word(&self.buffer[self.internal_buffer_position..]) {
+            return Ok(tools::split_vint_word(word).unwrap_or((0, 1)));
+        }
+
+        if self.buffer[self.internal_buffer_position] == 0 {
+            return Ok((0, 1));
         }
         let length = 8 - self.buffer[self.internal_buffer_position].ilog2() as usize;
         let mut val = self.buffer[self.internal_buffer_position] as u64;
@@ -278,26 +1197,31 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
     }
 
     #[inline]
-    fn peek_valid_tag_header(&mut self) -> Result<(u64, Option<TagDataType>, EBMLSize, usize), TagIteratorError> {
+    fn peek_valid_tag_header(&mut self) -> Result<(u64, Option<TagDataType>, ElementSize, usize), TagIteratorError> {
         self.ensure_data_read(16)?;
         let (tag_id, id_len) = self.peek_tag_id()?;
         let spec_tag_type = <TSpec>::get_tag_data_type(tag_id);
         
-        let (size, size_len) = tools::read_vint(&self.buffer[(self.internal_buffer_position + id_len)..])
+        let size_start = (self.internal_buffer_position + id_len).min(self.buffered_byte_length);
+        let (size, size_len) = tools::read_vint(&self.buffer[size_start..self.buffered_byte_length])
         .or(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData{tag_id, position: self.current_offset() })))?
-        .ok_or(TagIteratorError::UnexpectedEOF { tag_start: self.current_offset(), tag_id: Some(tag_id), tag_size: None, partial_data: None })?;
+        .ok_or(TagIteratorError::UnexpectedEOF(PartialTag::in_header(self.current_offset(), Some(tag_id))))?;
     
-        if self.buffered_byte_length <= id_len + size_len {
-            return Err(TagIteratorError::UnexpectedEOF { tag_start: self.current_offset(), tag_id: Some(tag_id), tag_size: None, partial_data: None });
+        if self.buffered_byte_length < self.internal_buffer_position + id_len + size_len {
+            return Err(TagIteratorError::UnexpectedEOF(PartialTag::in_header(self.current_offset(), Some(tag_id))));
         }
 
         if matches!(spec_tag_type, Some(TagDataType::UnsignedInt) | Some(TagDataType::Integer) | Some(TagDataType::Float)) && size > 8 {
             return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData{tag_id, position: self.current_offset() }));
         }
 
-        let size = EBMLSize::new(size, size_len);
-
         let header_len = id_len + size_len;
+        let size = ElementSize::checked(size, size_len, tag_id, self.current_offset(), header_len)?;
+
+        // Ids the spec knows about are always accepted, even if they are longer than the document declares
+        if spec_tag_type.is_none() && id_len > self.max_id_length {
+            return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedTagId{tag_id, position: self.current_offset(), length: id_len }));
+        }
 
         if (self.allowed_errors & INVALID_TAG_ID_ERROR == 0) && spec_tag_type.is_none() {
             return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagId{tag_id, position: self.current_offset() }));
@@ -314,10 +1238,11 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
                         match id {
                             PathPart::Id(id) => {
                                 ProcessingTag { 
-                                    tag: <TSpec>::get_master_tag(*id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was in path, but could not get master tag!", id)),
-                                    size: EBMLSize::Unknown,
+                                    tag: <TSpec>::get_master_tag(*id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was in path, but could not get master tag!", id)),
+                                    size: ElementSize::Unknown,
                                     tag_start: 0,
                                     data_start: 0,
+                                    is_inferred: true,
                                 }
                             },
                             PathPart::Global(_) => unreachable!()
@@ -331,13 +1256,25 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
             }
         }
 
-        if (self.allowed_errors & OVERSIZED_CHILD_ERROR == 0) && size.is_known() && self.is_invalid_tag_size(header_len + size.value()) {
-            return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement{ position: self.current_offset(), tag_id, size: size.value()}));
-        }
+        if let Known(data_size) = size {
+            let available = self.available_after_header(header_len);
+            if let (SizePastEnd::Error, Some(available)) = (self.size_past_end, available) {
+                if data_size > available {
+                    return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::SizePastEnd { position: self.current_offset(), tag_id, size: data_size, available }));
+                }
+            }
+
+            // Truncated elements are checked against their parents by the size they'll be read with
+            let bounded_size = match (self.size_past_end, available) {
+                (SizePastEnd::Truncate, Some(available)) => data_size.min(available),
+                _ => data_size,
+            };
+            if (self.allowed_errors & OVERSIZED_CHILD_ERROR == 0) && self.is_invalid_tag_size(header_len + bounded_size) {
+                return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement{ position: self.current_offset(), tag_id, size: data_size}));
+            }
 
-        if let Some(max_size) = self.max_allowed_tag_size {
-            if size.is_known() && size.value() > max_size {
-                return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagSize { position: self.current_offset(), tag_id, size: size.value() }));
+            if matches!(self.max_allowed_tag_size, Some(max_size) if data_size > max_size) {
+                return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagSize { position: self.current_offset(), tag_id, size: data_size }));
             }
         }
 
@@ -345,47 +1282,160 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
     }
 
     #[inline(always)]
-    fn read_valid_tag_header(&mut self) -> Result<(u64, Option<TagDataType>, EBMLSize), TagIteratorError> {
-        let (tag_id, spec_tag_type, size, header_len) = self.peek_valid_tag_header()?;
-            
+    fn read_valid_tag_header(&mut self) -> Result<(u64, Option<TagDataType>, ElementSize), TagIteratorError> {
+        let tag_start = self.current_offset();
+        let (tag_id, spec_tag_type, declared_size, header_len) = self.peek_valid_tag_header()?;
+        let size = self.truncate_past_end(declared_size, header_len);
+
         self.internal_buffer_position += header_len;
+        self.log_header(tag_start, tag_id, header_len, declared_size, size);
         Ok((tag_id, spec_tag_type, size))
     }
 
-    fn read_tag_data(&mut self, size: usize) -> Result<Option<&[u8]>, TagIteratorError> {
+    fn log_header(&mut self, offset: usize, id: u64, header_len: usize, declared_size: ElementSize, size: ElementSize) {
+        if let Some(log) = self.event_log.as_mut() {
+            log.record(ParseEvent::Header { offset, id, header_len, size: declared_size });
+            if let (Known(declared), Known(used)) = (declared_size, size) {
+                if declared != used {
+                    log.record(ParseEvent::SizeTruncated { offset, id, declared, used });
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn log_event(&mut self, event: impl FnOnce() -> ParseEvent) {
+        if let Some(log) = self.event_log.as_mut() {
+            log.record(event());
+        }
+    }
+
+    ///
+    /// Returns the number of bytes left in the source after a header of `header_len` bytes at the current position, if the length of the source is known.
+    ///
+    fn available_after_header(&self, header_len: usize) -> Option<usize> {
+        self.source_len.map(|len| len.saturating_sub(self.current_offset().saturating_add(header_len)))
+    }
+
+    fn truncate_past_end(&mut self, size: ElementSize, header_len: usize) -> ElementSize {
+        match (self.size_past_end, size, self.available_after_header(header_len)) {
+            (SizePastEnd::Truncate, Known(data_size), Some(available)) if data_size > available => {
+                self.metrics.add_truncated_element();
+                Known(available)
+            },
+            _ => size,
+        }
+    }
+
+    fn read_tag_data(&mut self, size: usize) -> Result<bool, TagIteratorError> {
         self.ensure_capacity(size);
         if !self.ensure_data_read(size)? {
-            return Ok(None);
+            return Ok(false);
         }
 
         self.internal_buffer_position += size;
-        Ok(Some(&self.buffer[(self.internal_buffer_position-size)..self.internal_buffer_position]))
+        Ok(true)
+    }
+
+    ///
+    /// Splits the data of the tag that was just read off of the front of the buffer without copying it.
+    ///
+    #[cfg(feature = "bytes")]
+    fn split_payload(&mut self, size: usize) -> bytes::Bytes {
+        let end = self.internal_buffer_position;
+        self.buffer_offset = Some(self.current_offset());
+        self.buffered_byte_length -= end;
+        self.internal_buffer_position = 0;
+        self.buffer.split_to(end).freeze().slice((end - size)..)
+    }
+
+    #[inline]
+    fn take_payload(pool: &mut Vec<Vec<u8>>, data: &[u8]) -> Vec<u8> {
+        match pool.pop() {
+            Some(mut payload) => {
+                payload.clear();
+                payload.extend_from_slice(data);
+                payload
+            },
+            None => data.to_vec(),
+        }
     }
 
     fn read_tag(&mut self) -> Result<ProcessingTag<TSpec>, TagIteratorError> {
-        let tag_start = self.current_offset();
+        if let Some(header) = self.resume_header.take() {
+            return self.read_tag_data_of(header.tag_start, header.tag_id, header.spec_tag_type, Known(header.size));
+        }
+        self.partial_header = None;
 
+        let tag_start = self.current_offset();
         let (tag_id, spec_tag_type, size) = self.read_valid_tag_header()?;
+        self.read_tag_data_of(tag_start, tag_id, spec_tag_type, size)
+    }
 
+    fn read_tag_data_of(&mut self, tag_start: usize, tag_id: u64, spec_tag_type: Option<TagDataType>, size: ElementSize) -> Result<ProcessingTag<TSpec>, TagIteratorError> {
         let data_start = self.current_offset();
+        let is_transformed = matches!(spec_tag_type, Some(TagDataType::Binary)) && self.transforms.handles(tag_id);
         let raw_data = if matches!(spec_tag_type, Some(TagDataType::Master)) {
             &[]
         } else if let Known(size) = size {
-            if let Some(data) = self.read_tag_data(size)? {
-                data
+            if !self.approve_allocation(size) {
+                if !self.skip_data(size)? {
+                    return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start, id: Some(tag_id), size: Some(size), header_len: Some(data_start - tag_start), obtained: 0, data: None }));
+                }
+                return Err(TagIteratorError::AllocationDenied { tag_start, tag_id, size });
+            }
+            if self.read_tag_data(size)? {
+                &self.buffer[(self.internal_buffer_position - size)..self.internal_buffer_position]
             } else {
-                return Err(TagIteratorError::UnexpectedEOF { tag_start, tag_id: Some(tag_id), tag_size: Some(size), partial_data: Some(self.buffer[self.internal_buffer_position..].to_vec()) });
+                let data = self.buffer[self.internal_buffer_position..self.buffered_byte_length].to_vec();
+                self.partial_header = Some(PartialHeader { tag_start, tag_id, spec_tag_type, size });
+                return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start, id: Some(tag_id), size: Some(size), header_len: Some(data_start - tag_start), obtained: data.len(), data: Some(data) }));
             }
         } else {
             return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData{ tag_id, position: tag_start }));
         };
 
+        let is_zero_length = raw_data.is_empty() && matches!(spec_tag_type, Some(TagDataType::UnsignedInt) | Some(TagDataType::Integer) | Some(TagDataType::Float) | Some(TagDataType::Utf8));
+        if is_zero_length {
+            match self.zero_length_values {
+                ZeroLengthValues::EmitDefault => {},
+                ZeroLengthValues::Warn => self.metrics.add_zero_length_value(),
+                ZeroLengthValues::Error => {
+                    self.metrics.add_zero_length_value();
+                    return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::ZeroLengthValue { position: tag_start, tag_id }));
+                },
+            }
+        }
+
+        if let Some(profile) = &self.profile {
+            profile.check(tag_id, size == Unknown).map_err(|violation| TagIteratorError::CorruptedFileData(CorruptedFileError::ProfileViolation { position: tag_start, tag_id, violation }))?;
+        }
+
+        // Empty elements hold their declared default, and only fall back to the zero value of their type (decoded below) without one
+        let default = match spec_tag_type {
+            Some(data_type) if is_zero_length => TSpec::get_default_value(tag_id).and_then(|default| default_tag::<TSpec>(tag_id, data_type, default)),
+            _ => None,
+        };
+        if let Some(tag) = default {
+            return Ok(ProcessingTag { tag, size, tag_start, data_start, is_inferred: false });
+        }
+
+        // Specs don't need to define the header element for its limit to apply
+        if tag_id == EBML_MAX_ID_LENGTH_ID && !raw_data.is_empty() {
+            if let Ok(val) = tools::arr_to_u64(raw_data) {
+                self.max_id_length = val.min(8) as usize;
+            }
+        }
+
         let tag = match spec_tag_type {
             Some(TagDataType::Master) => {
                 TSpec::get_master_tag(tag_id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", tag_id))
             },
             Some(TagDataType::UnsignedInt) => {
                 let val = tools::arr_to_u64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                if self.validate_restricted_values && TSpec::get_restricted_values(tag_id).is_some_and(|allowed| !allowed.contains(&val)) {
+                    return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::RestrictedValue { position: tag_start, tag_id, value: val }));
+                }
                 TSpec::get_unsigned_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was unsigned int, but could not get tag!", tag_id))
             },
             Some(TagDataType::Integer) => {
@@ -393,11 +1443,26 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
                 TSpec::get_signed_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was integer, but could not get tag!", tag_id))
             },
             Some(TagDataType::Utf8) => {
-                let val = String::from_utf8(raw_data.to_vec()).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: ToolError::FromUtf8Error(raw_data.to_vec(), e) })?;
-                TSpec::get_utf8_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", tag_id))
+                TSpec::get_utf8_tag_bytes(tag_id, Self::take_payload(&mut self.payload_pool, raw_data))
+                    .unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", tag_id))
+                    .map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: ToolError::FromUtf8Error(raw_data.to_vec(), e) })?
+            },
+            Some(TagDataType::Binary) if is_transformed => {
+                let stored = raw_data.to_vec();
+                let decoded = self.transforms.decode(tag_id, &stored)
+                    .expect("transform should exist for tag")
+                    .map_err(|source| TagIteratorError::TransformError { tag_id, source })?;
+                TSpec::get_binary_tag_owned(tag_id, decoded).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", tag_id))
             },
             Some(TagDataType::Binary) => {
-                TSpec::get_binary_tag(tag_id, raw_data).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", tag_id))
+                #[cfg(not(feature = "bytes"))]
+                let tag = TSpec::get_binary_tag_owned(tag_id, Self::take_payload(&mut self.payload_pool, raw_data));
+                #[cfg(feature = "bytes")]
+                let tag = {
+                    let data_len = raw_data.len();
+                    TSpec::get_binary_tag_bytes(tag_id, self.split_payload(data_len))
+                };
+                tag.unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", tag_id))
             },
             Some(TagDataType::Float) => {
                 let val = tools::arr_to_f64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
@@ -408,7 +1473,7 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
             }
         };
 
-        Ok(ProcessingTag { tag, size, tag_start, data_start })
+        Ok(ProcessingTag { tag, size, tag_start, data_start, is_inferred: false })
     }
 
     fn read_tag_checked(&mut self) -> Option<Result<ProcessingTag<TSpec>, TagIteratorError>> {
@@ -433,109 +1498,399 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
         Some(self.read_tag())
     }
 
-    fn read_next(&mut self) {
+    fn queue_ended_masters(&mut self) {
         //If we have reached the known end of any open master tags, queue that tag and all children to emit ends
-        let ended_tag_index = self.tag_stack.iter().position(|tag| matches!(tag.size, Known(size) if self.current_offset() >= tag.data_start + size));
+        let ended_tag_index = self.tag_stack.iter().position(|tag| matches!(tag.end(), Some(end) if self.current_offset() >= end));
         if let Some(index) = ended_tag_index {
-            self.emission_queue.extend(self.tag_stack.drain(index..).map(|t| Ok((t.tag, t.tag_start))).rev());
-        }
-
-        if let Some(next_read) = self.read_tag_checked() {
-            if let Ok(next_tag) = &next_read {
-                while matches!(self.tag_stack.last(), Some(open_tag) if open_tag.size == Unknown) {
-                    let open_tag = self.tag_stack.last().unwrap();
-                    let previous_tag_ended = open_tag.is_ended_by(next_tag.tag.get_id());
-        
-                    if previous_tag_ended {
-                        let t = self.tag_stack.pop().unwrap();
-                        self.emission_queue.push_back(Ok((t.tag, t.tag_start)));
-                    } else {
-                        break;
-                    }
-                }
-
-                if let Some(Master::Start) = next_tag.tag.as_master() {
-                    let tag_id = next_tag.tag.get_id();
-
-                    self.tag_stack.push(ProcessingTag {
-                        tag: TSpec::get_master_tag(tag_id, Master::End).unwrap(),
-                        size: next_tag.size,
-                        tag_start: next_tag.tag_start,
-                        data_start: next_tag.data_start,
-                    });
-
-                    if self.tag_ids_to_buffer.contains(&tag_id) {
-                        self.buffer_master(tag_id);
-                        return;
-                    }
+            if let Some(log) = self.event_log.as_mut() {
+                let offset = self.buffer_offset.unwrap_or(0) + self.internal_buffer_position;
+                for (level, tag) in self.tag_stack.iter().enumerate().skip(index).rev().filter(|(_, t)| !t.is_inferred) {
+                    log.record(ParseEvent::Pop { offset, id: tag.tag.get_id(), level, cause: EndCause::SizeReached });
                 }
             }
-
-            self.emission_queue.push_back(next_read.map(|r| (r.tag, r.tag_start)));
-        } else if self.emit_master_end_when_eof {
-            while let Some(tag) = self.tag_stack.pop() {
-                self.emission_queue.push_back(Ok((tag.tag, tag.tag_start)));
-            }
+            self.emission_queue.extend(self.tag_stack.drain(index..).enumerate().filter(|(_, t)| !t.is_inferred).map(|(i, t)| Ok(QueuedTag { size_length: t.size_length(), tag: t.tag, start: t.tag_start, level: index + i, size: t.size, layout: None })).rev());
         }
     }
 
-    fn buffer_master(&mut self, tag_id: u64) {
-        let tag_start = self.current_offset();
-        let pre_queue_len = self.emission_queue.len();
-
-        let mut position = pre_queue_len;
-        'endTagSearch: loop {
-            if position >= self.emission_queue.len() {
-                self.read_next();
-    
-                if position >= self.emission_queue.len() {
-                    self.emission_queue.push_back(Err(TagIteratorError::UnexpectedEOF{ tag_start, tag_id: Some(tag_id), tag_size: None, partial_data: None }));
-                    return;
-                }
-            }
-
-            while position < self.emission_queue.len() {
-                if let Some(r) = self.emission_queue.get(position) {
-                    match r {
-                        Err(_) => break 'endTagSearch,
-                        Ok(t) => {
-                            if t.0.get_id() == tag_id && matches!(t.0.as_master(), Some(Master::End)) {
-                                break 'endTagSearch;
-                            }
-                        }
-                    }
-                }
-                position += 1;
-            }
+    ///
+    /// Steps over the next element if it is a valid `CRC-32` element that has been fully read into the buffer (or can be).
+    ///
+    fn skip_crc32_element(&mut self) -> bool {
+        if !matches!(self.ensure_data_read(1), Ok(true)) {
+            return false;
         }
-
-        let mut children = self.emission_queue.split_off(pre_queue_len);
-        let split_to = position - pre_queue_len;
-        if children.get(split_to).unwrap().is_ok() {
-            let remaining = children.split_off(split_to).into_iter().skip(1);
-            let full_tag = Self::roll_up_children(tag_id, children.into_iter().map(|c| c.unwrap().0).collect());
-            self.emission_queue.push_back(Ok((full_tag, tag_start)));
-            self.emission_queue.extend(remaining);
-        } else {
-            self.emission_queue.extend(children.drain(split_to..).take(1));
+        let element_len = match self.peek_valid_tag_header() {
+            Ok((CRC32_ID, _, Known(size), header_len)) => header_len + size,
+            _ => return false,
+        };
+        if self.needs_allocation_approval(element_len) {
+            // Left for the regular read, which asks for approval
+            return false;
         }
+        self.ensure_capacity(element_len);
+        if !matches!(self.ensure_data_read(element_len), Ok(true)) {
+            return false;
+        }
+        self.internal_buffer_position += element_len;
+        true
     }
 
-    fn roll_up_children(tag_id: u64, children: Vec<TSpec>) -> TSpec {
-        let mut rolled_children = Vec::new();
-
-        let mut iter = children.into_iter();
-        while let Some(child) = iter.next() {
-            if let Some(Master::Start) = child.as_master() {
-                let child_id = child.get_id();
-                let subchildren = iter.by_ref().take_while(|c| !matches!(c.as_master(), Some(Master::End)) || c.get_id() != child_id).collect();
-                rolled_children.push(Self::roll_up_children(child_id, subchildren));
-            } else {
-                rolled_children.push(child);
+    ///
+    /// Closes the open "Master" tags with unknown sizes that can't contain an element with `tag_id`, found at `offset`.
+    ///
+    fn end_masters_ended_by(&mut self, tag_id: u64, offset: usize) {
+        while matches!(self.tag_stack.last(), Some(open_tag) if open_tag.size == Unknown && open_tag.is_ended_by(tag_id)) {
+            let t = self.tag_stack.pop().unwrap();
+            if !t.is_inferred {
+                let (id, level) = (t.tag.get_id(), self.tag_stack.len());
+                self.log_event(|| ParseEvent::Pop { offset, id, level, cause: EndCause::EndedBy(tag_id) });
+                self.emission_queue.push_back(Ok(QueuedTag { size_length: t.size_length(), tag: t.tag, start: t.tag_start, level: self.tag_stack.len(), size: t.size, layout: None }));
+            }
+        }
+    }
+
+    ///
+    /// Returns whether an element passes the filter set by [`Self::set_tag_id_filter()`] or [`Self::set_path_filter()`], given the tags that are currently open.
+    ///
+    fn is_relevant(&self, tag_id: u64, spec_tag_type: Option<TagDataType>) -> bool {
+        match &self.tag_filter {
+            None => true,
+            Some(TagFilter::Ids(filter)) => filter.ids.contains(&tag_id)
+                || (matches!(spec_tag_type, Some(TagDataType::Master)) && (filter.has_global || filter.ancestors.contains(&tag_id)))
+                || self.tag_stack.iter().any(|t| filter.ids.contains(&t.tag.get_id())),
+            Some(TagFilter::Paths(paths)) => {
+                // The element is along a path if one is a prefix of the other
+                let element_path = self.tag_stack.iter().map(|t| t.tag.get_id()).chain(std::iter::once(tag_id));
+                paths.iter().any(|path| path.iter().zip(element_path.clone()).all(|(expected, id)| *expected == id))
+            },
+        }
+    }
+
+    ///
+    /// Steps over the next element and all of its children if it has a known size and doesn't pass the filter set by [`Self::set_tag_id_filter()`] or [`Self::set_path_filter()`].
+    ///
+    fn skip_irrelevant_element(&mut self) -> Result<bool, TagIteratorError> {
+        if self.tag_filter.is_none() || !matches!(self.ensure_data_read(1), Ok(true)) {
+            return Ok(false);
+        }
+        let tag_start = self.current_offset();
+        let (tag_id, spec_tag_type) = match self.peek_valid_tag_header() {
+            Ok((tag_id, spec_tag_type, Known(_), _)) => (tag_id, spec_tag_type),
+            _ => return Ok(false),
+        };
+        // Skipped elements still end the open tags they can't belong to
+        self.end_masters_ended_by(tag_id, tag_start);
+        if self.is_relevant(tag_id, spec_tag_type) {
+            return Ok(false);
+        }
+
+        let (_, _, size) = self.read_valid_tag_header()?;
+        let header_len = self.current_offset() - tag_start;
+        let data_size = match size {
+            Known(data_size) => data_size,
+            Unknown => unreachable!("skipped elements have known sizes"),
+        };
+        if !self.skip_data(data_size)? {
+            return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start, id: Some(tag_id), size: Some(data_size), header_len: Some(header_len), obtained: 0, data: None }));
+        }
+        Ok(true)
+    }
+
+    ///
+    /// Steps over the next element if it is a valid "Binary" element large enough to be skipped by seeking the source (see [`Self::seek_over_binary_payloads()`]).
+    ///
+    fn seek_over_binary_element(&mut self) -> Result<bool, TagIteratorError> {
+        let (min_size, seek_by) = match self.binary_seek {
+            Some(binary_seek) => binary_seek,
+            None => return Ok(false),
+        };
+        if !matches!(self.ensure_data_read(1), Ok(true)) {
+            return Ok(false);
+        }
+        let tag_start = self.current_offset();
+        let (tag_id, size, header_len) = match self.peek_valid_tag_header() {
+            Ok((tag_id, Some(TagDataType::Binary), size @ Known(data_size), header_len)) if data_size >= min_size => (tag_id, size, header_len),
+            _ => return Ok(false),
+        };
+        let data_size = match self.truncate_past_end(size, header_len) {
+            Known(data_size) => data_size,
+            Unknown => unreachable!("truncated sizes are always known"),
+        };
+
+        self.internal_buffer_position += header_len;
+        let buffered = self.buffered_byte_length - self.internal_buffer_position;
+        if data_size <= buffered {
+            self.internal_buffer_position += data_size;
+            return Ok(true);
+        }
+
+        // Everything past what is buffered is seeked over, except the last byte which is read to make sure the source doesn't end early
+        let data_end = self.current_offset() + data_size;
+        let unbuffered = data_size - buffered;
+        self.buffer_offset = Some(data_end);
+        self.internal_buffer_position = 0;
+        self.buffered_byte_length = 0;
+        if unbuffered > 1 {
+            seek_by(&mut self.source, (unbuffered - 1) as i64).map_err(|source| self.read_error(source))?;
+            self.metrics.add_bytes_seeked_over(unbuffered - 1);
+        }
+        let mut last = [0u8; 1];
+        loop {
+            match self.source.read(&mut last) {
+                Ok(0) => return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start, id: Some(tag_id), size: Some(data_size), header_len: Some(header_len), obtained: 0, data: None })),
+                Ok(_) => break,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(source) => return Err(self.read_error(source)),
+            }
+        }
+        self.metrics.add_bytes_read(1);
+        Ok(true)
+    }
+
+    fn read_next(&mut self) {
+        while self.read_next_tag() {
+            // A "Master" element failed its CRC check and was skipped without queueing anything
+        }
+    }
+
+    ///
+    /// Reads the next tag into the emission queue.  Returns `true` if a "Master" element was skipped by [`CrcMismatch::Skip`] instead, so another tag needs to be read.
+    ///
+    fn read_next_tag(&mut self) -> bool {
+        if self.reached_trailing_data {
+            return false;
+        }
+        self.queue_ended_masters();
+        loop {
+            let skipped = (self.skip_crc32_elements && self.skip_crc32_element()) || match self.seek_over_binary_element().and_then(|seeked| if seeked { Ok(true) } else { self.skip_irrelevant_element() }) {
+                Ok(skipped) => skipped,
+                Err(err) => {
+                    self.emission_queue.push_back(Err(err));
+                    return false;
+                },
+            };
+            if !skipped {
+                break;
+            }
+            // The skipped element may have been the last child of a master
+            self.queue_ended_masters();
+        }
+
+        let tag_start = self.current_offset();
+        if let Some(next_read) = self.read_tag_checked() {
+            if matches!(&next_read, Err(err) if self.is_trailing_data(tag_start, err)) {
+                self.skip_trailing_data(tag_start);
+                return false;
+            }
+
+            let mut level = self.tag_stack.len();
+            if let Ok(next_tag) = &next_read {
+                self.end_masters_ended_by(next_tag.tag.get_id(), tag_start);
+
+                level = self.tag_stack.len();
+                if let Some(Master::Start) = next_tag.tag.as_master() {
+                    let tag_id = next_tag.tag.get_id();
+
+                    if let Some(max_depth) = self.max_nesting_depth.filter(|max_depth| level >= *max_depth) {
+                        if let Known(size) = next_tag.size {
+                            if let Err(err) = self.skip_data(size) {
+                                self.emission_queue.push_back(Err(err));
+                                return false;
+                            }
+                        }
+                        self.emission_queue.push_back(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::NestingTooDeep { position: next_tag.tag_start, tag_id, max_depth })));
+                        return false;
+                    }
+
+                    if let (Known(size), Some((expected, computed))) = (next_tag.size, self.check_crc(next_tag.size)) {
+                        self.metrics.add_crc_mismatch();
+                        match self.crc_mismatch {
+                            CrcMismatch::Error => {
+                                self.internal_buffer_position += size;
+                                self.emission_queue.push_back(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::CrcMismatch { position: next_tag.tag_start, tag_id, expected, computed })));
+                                return false;
+                            },
+                            CrcMismatch::Skip => {
+                                self.internal_buffer_position += size;
+                                return true;
+                            },
+                            CrcMismatch::Warn | CrcMismatch::Ignore => {},
+                        }
+                    }
+
+                    // Irrelevant elements that can't be stepped over are left open without being emitted, like the inferred parents of the first tag
+                    let is_hidden = !self.is_relevant(tag_id, Some(TagDataType::Master));
+                    self.tag_stack.push(ProcessingTag {
+                        tag: TSpec::get_master_tag(tag_id, Master::End).unwrap(),
+                        size: next_tag.size,
+                        tag_start: next_tag.tag_start,
+                        data_start: next_tag.data_start,
+                        is_inferred: is_hidden,
+                    });
+                    let offset = next_tag.tag_start;
+                    self.log_event(|| ParseEvent::Push { offset, id: tag_id, level });
+                    if is_hidden {
+                        return true;
+                    }
+
+                    if self.tag_ids_to_buffer.contains(&tag_id) {
+                        self.buffer_master(tag_id);
+                        return false;
+                    }
+                }
+            }
+
+            self.emission_queue.push_back(next_read.map(|r| QueuedTag { size_length: r.size_length(), tag: r.tag, start: r.tag_start, level, size: r.size, layout: None }));
+        } else if !self.stream_complete() {
+            self.emission_queue.push_back(Err(TagIteratorError::NeedMoreData { position: self.current_offset() }));
+        } else if self.emit_master_end_when_eof {
+            while let Some(tag) = self.tag_stack.pop() {
+                if !tag.is_inferred {
+                    let (offset, id, level) = (self.current_offset(), tag.tag.get_id(), self.tag_stack.len());
+                    self.log_event(|| ParseEvent::Pop { offset, id, level, cause: EndCause::EndOfSource });
+                    self.emission_queue.push_back(Ok(QueuedTag { size_length: tag.size_length(), tag: tag.tag, start: tag.tag_start, level: self.tag_stack.len(), size: tag.size, layout: None }));
+                }
+            }
+        }
+        false
+    }
+
+    ///
+    /// Checks the data of the "Master" element whose header was just read against its `CRC-32` child, if [`Self::set_crc_mismatch()`] enabled checking and the element can be checked.  Returns the expected and computed checksums if they differ.
+    ///
+    fn check_crc(&mut self, size: ElementSize) -> Option<(u32, u32)> {
+        let size = match size {
+            Known(size) if self.crc_mismatch != CrcMismatch::Ignore => size,
+            _ => return None,
+        };
+        if !self.approve_allocation(size) {
+            return None;
+        }
+        self.ensure_capacity(size);
+        if !matches!(self.ensure_data_read(size), Ok(true)) {
+            return None;
+        }
+
+        // The CRC-32 element has to be the first child, and covers the rest of the element's data
+        let data = &self.buffer[self.internal_buffer_position..(self.internal_buffer_position + size)];
+        if data.first() != Some(&(CRC32_ID as u8)) {
+            return None;
+        }
+        let crc_start = match tools::read_vint(&data[1..]) {
+            Ok(Some((4, size_len))) => 1 + size_len,
+            _ => return None,
+        };
+        let expected = u32::from_le_bytes(data.get(crc_start..(crc_start + 4))?.try_into().expect("slice should be 4 bytes long"));
+        let computed = tools::crc32(&data[(crc_start + 4)..]);
+        if expected == computed {
+            None
+        } else {
+            Some((expected, computed))
+        }
+    }
+
+    fn is_trailing_data(&self, tag_start: usize, err: &TagIteratorError) -> bool {
+        // Running out of data is a truncated element rather than junk, and stays resumable
+        self.trailing_data != TrailingData::Parse && tag_start > 0 && self.tag_stack.is_empty()
+            && !matches!(err, TagIteratorError::ReadError { .. } | TagIteratorError::Timeout { .. } | TagIteratorError::UnexpectedEOF(_) | TagIteratorError::NeedMoreData { .. })
+    }
+
+    fn skip_trailing_data(&mut self, tag_start: usize) {
+        self.reached_trailing_data = true;
+        match self.trailing_data {
+            TrailingData::Warn => {
+                let buffered = (self.buffer_offset.unwrap_or(0) + self.buffered_byte_length).saturating_sub(tag_start) as u64;
+                match std::io::copy(&mut self.source, &mut std::io::sink()) {
+                    Ok(rest) => {
+                        self.metrics.add_bytes_read(rest as usize);
+                        self.metrics.add_trailing_bytes(buffered + rest);
+                    },
+                    Err(source) => {
+                        let err = self.read_error(source);
+                        self.emission_queue.push_back(Err(err));
+                    },
+                }
+            },
+            TrailingData::Error => self.emission_queue.push_back(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::TrailingData { position: tag_start }))),
+            TrailingData::Parse | TrailingData::Ignore => {},
+        }
+        self.internal_buffer_position = self.buffered_byte_length;
+    }
+
+    fn stream_complete(&mut self) -> bool {
+        let position = self.current_offset();
+        match self.eof_predicate.as_mut() {
+            Some(predicate) if self.tag_stack.iter().any(|tag| tag.size == Unknown) => predicate(position),
+            _ => true,
+        }
+    }
+
+    fn buffer_master(&mut self, tag_id: u64) {
+        let tag_start = self.current_offset();
+        let level = self.tag_stack.len() - 1;
+        let size = self.tag_stack[level].size;
+        let size_length = self.tag_stack[level].size_length();
+        let master_start = self.tag_stack[level].tag_start;
+        let pre_queue_len = self.emission_queue.len();
+        let record_layout = self.record_buffered_layout;
+        let root_layout = ElementLayout { id: tag_id, offset: master_start, header_len: self.tag_stack[level].data_start - master_start, size, children: Vec::new() };
+
+        // Children are folded into their parents as soon as they are read, so the tree is built without queueing every Start/End
+        let mut open_masters: TagStack<(u64, Vec<TSpec>)> = smallvec::smallvec![(tag_id, Vec::new())];
+        let mut open_layouts: Vec<ElementLayout> = if record_layout { vec![root_layout] } else { Vec::new() };
+        loop {
+            self.read_next();
+            if self.emission_queue.len() == pre_queue_len {
+                self.emission_queue.push_back(Err(TagIteratorError::UnexpectedEOF(PartialTag::in_header(tag_start, Some(tag_id)))));
+                return;
+            }
+
+            while self.emission_queue.len() > pre_queue_len {
+                let queued = match self.emission_queue.remove(pre_queue_len).unwrap() {
+                    Ok(queued) => queued,
+                    Err(err) => {
+                        self.emission_queue.truncate(pre_queue_len);
+                        self.emission_queue.push_back(Err(err));
+                        return;
+                    }
+                };
+                let QueuedTag { tag, start, size: tag_size, size_length: tag_size_length, layout: tag_layout, .. } = queued;
+
+                match tag.as_master() {
+                    Some(Master::Start) => {
+                        if record_layout {
+                            open_layouts.push(ElementLayout { id: tag.get_id(), offset: start, header_len: header_len(tag.get_id(), tag_size_length), size: tag_size, children: Vec::new() });
+                        }
+                        open_masters.push((tag.get_id(), Vec::new()));
+                    },
+                    Some(Master::End) => {
+                        let (id, children) = open_masters.pop().unwrap();
+                        let layout = open_layouts.pop();
+                        let full_tag = TSpec::get_master_tag(id, Master::Full(children)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", id));
+                        match open_masters.last_mut() {
+                            Some((_, siblings)) => {
+                                siblings.push(full_tag);
+                                if let (Some(layout), Some(parent)) = (layout, open_layouts.last_mut()) {
+                                    parent.children.push(layout);
+                                }
+                            },
+                            None => {
+                                self.emission_queue.insert(pre_queue_len, Ok(QueuedTag { tag: full_tag, start: master_start, level, size, size_length, layout }));
+                                return;
+                            }
+                        }
+                    },
+                    _ => {
+                        if let Some(parent) = open_layouts.last_mut() {
+                            // Masters buffered on their own arrive with their layout already built
+                            let id = tag.get_id();
+                            parent.children.push(tag_layout.unwrap_or_else(|| ElementLayout { id, offset: start, header_len: header_len(id, tag_size_length), size: tag_size, children: Vec::new() }));
+                        }
+                        open_masters.last_mut().unwrap().1.push(tag);
+                    },
+                }
             }
         }
-
-        TSpec::get_master_tag(tag_id, Master::Full(rolled_children)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was master, but could not get tag!", tag_id))
     }
 
     #[inline(always)]
@@ -545,9 +1900,149 @@ impl<R: Read, TSpec> TagIterator<R, TSpec>
 
     #[inline(always)]
     fn is_invalid_tag_size(&self, size: usize) -> bool {
-        self.tag_stack.iter().filter(|p| p.size.is_known()).any(|t| {
-            (t.data_start + t.size.value()) < (self.current_offset() + size)
-        })
+        let tag_end = match self.current_offset().checked_add(size) {
+            Some(end) => end,
+            None => return true,
+        };
+        self.tag_stack.iter().filter_map(|t| t.end()).any(|end| end < tag_end)
+    }
+}
+
+fn header_len(tag_id: u64, size_length: usize) -> usize {
+    tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count() + size_length
+}
+
+impl<R: Read + Seek, TSpec> TagIterator<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Sets the length of the source for [`Self::set_size_past_end()`] by seeking to its end, and then back to where the iterator was reading.  Returns the length, counted from where the iterator started reading.
+    ///
+    /// ## Errors
+    ///
+    /// Returns any error from seeking the source.
+    ///
+    pub fn detect_source_len(&mut self) -> std::io::Result<usize> {
+        let position = self.source.stream_position()?;
+        let end = self.source.seek(std::io::SeekFrom::End(0))?;
+        self.source.seek(std::io::SeekFrom::Start(position))?;
+        let len = self.buffer_offset.unwrap_or(0) + self.buffered_byte_length + end.saturating_sub(position) as usize;
+        self.source_len = Some(len);
+        Ok(len)
+    }
+
+    ///
+    /// Configures the iterator to step over "Binary" elements whose payload is at least `min_size` bytes by seeking the source past it, instead of reading it.  Pass `None` to read every element again.
+    ///
+    /// Disabled by default.  This is meant for applications that only need the metadata of a document with large binary payloads, like the blocks of a multi-gigabyte Matroska file: skipped elements aren't emitted (or included in buffered "Master" tags), and their data is never copied into the iterator's buffer.  Payload bytes that were already buffered are simply discarded, and the last byte of each payload is read so that a source ending early is still reported as [`TagIteratorError::UnexpectedEOF`].  The number of bytes seeked over is counted in [`ReadMetrics::bytes_seeked_over`].
+    ///
+    /// Elements that would otherwise cause an error (e.g. an element that isn't allowed in its parent) are read normally so the error is reported.
+    ///
+    pub fn seek_over_binary_payloads(&mut self, min_size: Option<usize>) {
+        self.binary_seek = min_size.map(|min_size| (min_size, (|source: &mut R, offset: i64| source.seek(std::io::SeekFrom::Current(offset))) as SeekBy<R>));
+    }
+}
+
+impl<TSpec> TagIterator<WatchdogReader, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`TagIterator<TSpec>`] instance that stops waiting for data from `source` after `timeout`.
+    ///
+    /// This is meant for sources that can block indefinitely, such as pipes or sockets whose writer has stalled.  The source is read on a background thread through a [`WatchdogReader`], and a read that doesn't return within `timeout` produces [`TagIteratorError::Timeout`] instead of blocking the consumer.  The read stays pending, so calling [`Iterator::next()`] again continues waiting for it without losing data.
+    ///
+    /// `tags_to_buffer` works the same as in [`Self::new()`].
+    ///
+    pub fn with_read_timeout<S: Read + Send + 'static>(source: S, tags_to_buffer: &[TSpec], timeout: Duration) -> Self {
+        let mut iterator = Self::new(WatchdogReader::new(source, timeout), tags_to_buffer);
+        iterator.read_timeout = Some(timeout);
+        iterator
+    }
+}
+
+impl<R: Read + Seek, TSpec> TagIterator<ForkableSource<R>, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a second iterator that continues from the same position as this one, with its own cursor over the shared source.
+    ///
+    /// The two iterators are independent from then on, so a demuxer can, for example, follow one track with each iterator and interleave their reads without reopening the file.  Data is shared through the [`ForkableSource`] block cache rather than copied, and only the fork's read buffer is allocated.  The fork starts with the same open tags and configuration, except for content transforms, the EOF predicate, the allocation hook, and the metrics callback, which need to be set on it again if they are needed.  Tags that were already queued are emitted by both iterators, but queued errors are only returned by this one.
+    ///
+    pub fn fork(&self) -> Self {
+        let unconsumed = self.buffered_byte_length - self.internal_buffer_position;
+        let mut fork = TagIterator::with_capacity(self.source.fork_at(unconsumed as u64), &[], self.capacity());
+        fork.tag_ids_to_buffer = self.tag_ids_to_buffer.clone();
+        fork.allowed_errors = self.allowed_errors;
+        fork.max_allowed_tag_size = self.max_allowed_tag_size;
+        fork.max_nesting_depth = self.max_nesting_depth;
+        fork.validate_restricted_values = self.validate_restricted_values;
+        fork.zero_length_values = self.zero_length_values;
+        fork.trailing_data = self.trailing_data;
+        fork.reached_trailing_data = self.reached_trailing_data;
+        fork.size_past_end = self.size_past_end;
+        fork.crc_mismatch = self.crc_mismatch;
+        fork.source_len = self.source_len;
+        fork.read_timeout = self.read_timeout;
+        fork.skip_crc32_elements = self.skip_crc32_elements;
+        fork.binary_seek = self.binary_seek;
+        fork.profile = self.profile;
+        fork.max_id_length = self.max_id_length;
+        fork.corrupt_ranges = self.corrupt_ranges.clone();
+        fork.event_log = self.event_log.clone();
+        fork.tag_filter = self.tag_filter.clone();
+        fork.buffer_offset = self.buffer_offset.map(|_| self.current_offset());
+        fork.tag_stack = self.tag_stack.clone();
+        fork.emission_queue = self.emission_queue.iter().filter_map(|queued| queued.as_ref().ok().cloned().map(Ok)).collect();
+        fork.last_emitted_tag_offset = self.last_emitted_tag_offset;
+        fork.last_emitted_tag_level = self.last_emitted_tag_level;
+        fork.last_emitted_tag_size = self.last_emitted_tag_size;
+        fork.last_emitted_tag_size_length = self.last_emitted_tag_size_length;
+        fork.last_emitted_tag_header_len = self.last_emitted_tag_header_len;
+        fork.last_emitted_tag_layout = self.last_emitted_tag_layout.clone();
+        fork.record_buffered_layout = self.record_buffered_layout;
+        fork.has_determined_doc_path = self.has_determined_doc_path;
+        fork.emit_master_end_when_eof = self.emit_master_end_when_eof;
+        fork.header_event_threshold = self.header_event_threshold;
+        fork
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<B: bytes::Buf, TSpec> TagIterator<bytes::buf::Reader<B>, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns a new [`TagIterator<TSpec>`] instance that reads from a [`bytes::Buf`], such as a network buffer.
+    ///
+    /// `tags_to_buffer` works the same as in [`Self::new()`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let buf = Bytes::from_static(&[0x42, 0x86, 0x81, 0x01]);
+    /// let tags: Vec<EmptySpec> = TagIterator::from_buf(buf, &[]).map(|t| t.unwrap()).collect();
+    /// assert_eq!(vec![EmptySpec::with_data(0x4286, &[0x01])], tags);
+    /// ```
+    ///
+    pub fn from_buf(buf: B, tags_to_buffer: &[TSpec]) -> Self {
+        Self::new(bytes::Buf::reader(buf), tags_to_buffer)
+    }
+
+    ///
+    /// Consumes self and returns the underlying buffer, advanced past the data that has been read.
+    ///
+    /// As with [`Self::into_inner()`], data that was read into the iterator's internal buffer but not yet emitted is dropped.
+    ///
+    pub fn into_buf(self) -> B {
+        self.into_inner().into_inner()
     }
 }
 
@@ -560,10 +2055,314 @@ impl<R: Read, TSpec> Iterator for TagIterator<R, TSpec>
         if self.emission_queue.is_empty() {
             self.read_next();
         }
+        self.metrics.observe_queue(self.emission_queue.len());
         let next_item = self.emission_queue.pop_front();
-        if let Some(Ok(ref tuple)) = next_item {
-            self.last_emitted_tag_offset = tuple.1;
+        match next_item {
+            Some(Ok(ref queued)) => {
+                self.last_emitted_tag_offset = queued.start;
+                self.last_emitted_tag_level = queued.level;
+                self.last_emitted_tag_size = queued.size;
+                self.last_emitted_tag_size_length = queued.size_length;
+                self.last_emitted_tag_header_len = header_len(queued.tag.get_id(), queued.size_length);
+                self.last_emitted_tag_layout = queued.layout.clone();
+                self.metrics.add_emitted(true);
+            },
+            Some(Err(ref err)) => {
+                let offset = self.current_offset();
+                self.log_event(|| ParseEvent::Error { offset, message: err.to_string() });
+                self.metrics.add_emitted(false);
+            },
+            None => {},
         }
-        next_item.map(|r| r.map(|t| t.0))
+        self.metrics.tick();
+        next_item.map(|r| r.map(|t| t.tag))
+    }
+}
+
+///
+/// An iterator over tags paired with the [`TagSpan`] they were read from, created by [`TagIterator::iter_with_spans()`].
+///
+pub struct WithSpans<'a, R: Read, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iterator: &'a mut TagIterator<R, TSpec>,
+}
+
+impl<R: Read, TSpec> Iterator for WithSpans<'_, R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<(TSpec, TagSpan), TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tag = self.iterator.next()?;
+        Some(tag.map(|tag| (tag, self.iterator.last_emitted_tag_span())))
+    }
+}
+
+///
+/// Reads the payload of a single element, created by [`TagIterator::next_element_reader()`].
+///
+/// Reads are limited to the element's payload.  If the source ends before the payload is complete, reads fail with [`std::io::ErrorKind::UnexpectedEof`].  Dropping the reader skips whatever wasn't read; if that fails, the error is returned by the iterator's next call to [`Iterator::next()`].
+///
+pub struct ElementReader<'a, R: Read, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iterator: &'a mut TagIterator<R, TSpec>,
+    tag_id: u64,
+    tag_start: usize,
+    size: usize,
+    remaining: usize,
+}
+
+impl<R: Read, TSpec> ElementReader<'_, R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns the id of the element being read.
+    ///
+    pub fn tag_id(&self) -> u64 {
+        self.tag_id
+    }
+
+    ///
+    /// Returns the total size of the element's payload.
+    ///
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    ///
+    /// Returns the number of payload bytes that haven't been read yet.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<R: Read, TSpec> Read for ElementReader<'_, R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.remaining);
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let bytes_read = self.iterator.read_payload(&mut buf[..len])?;
+        if bytes_read == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, format!("source ended with {} bytes of element 0x{:x} unread", self.remaining, self.tag_id)));
+        }
+        self.remaining -= bytes_read;
+        Ok(bytes_read)
+    }
+}
+
+impl<R: Read, TSpec> Drop for ElementReader<'_, R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn drop(&mut self) {
+        if let Err(err) = self.skip_rest() {
+            self.iterator.emission_queue.push_back(Err(err));
+        }
+    }
+}
+
+impl<R: Read, TSpec> ElementReader<'_, R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Skips the unread part of the payload.  Nothing is left to skip afterwards, even if an error occurs.
+    ///
+    fn skip_rest(&mut self) -> Result<(), TagIteratorError> {
+        let mut scratch = [0u8; 4096];
+        while self.remaining > 0 {
+            let len = scratch.len().min(self.remaining);
+            match self.iterator.read_payload(&mut scratch[..len]) {
+                Ok(0) => {
+                    let obtained = self.size - self.remaining;
+                    self.remaining = 0;
+                    return Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start: self.tag_start, id: Some(self.tag_id), size: Some(self.size), header_len: None, obtained, data: None }));
+                },
+                Ok(bytes_read) => self.remaining -= bytes_read,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {},
+                Err(source) => {
+                    self.remaining = 0;
+                    return Err(TagIteratorError::ReadError { source });
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// An item returned by [`TagIterator::next_event()`].
+///
+pub enum ReadEvent<'a, R: Read, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// A tag, read the same way as by [`Iterator::next()`].
+    ///
+    Tag(TSpec),
+
+    ///
+    /// The header of an element whose payload hasn't been read yet.
+    ///
+    Header(PendingElement<'a, R, TSpec>),
+}
+
+///
+/// An element whose header has been read but whose payload hasn't, returned in a [`ReadEvent::Header`].
+///
+/// The payload can be streamed by reading from the element directly (or from [`Self::into_reader()`]), skipped with [`Self::skip()`], or read into a tag with [`Self::buffer()`].  Nothing is allocated for the payload unless it is buffered, so huge binary elements like attachments can be copied straight to their destination.  Dropping the element skips whatever wasn't read, like dropping an [`ElementReader`].
+///
+pub struct PendingElement<'a, R: Read, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    reader: ElementReader<'a, R, TSpec>,
+    data_offset: usize,
+}
+
+impl<'a, R: Read, TSpec> PendingElement<'a, R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns the id of the element.
+    ///
+    pub fn tag_id(&self) -> u64 {
+        self.reader.tag_id
+    }
+
+    ///
+    /// Returns the size of the element's payload.
+    ///
+    pub fn size(&self) -> usize {
+        self.reader.size
+    }
+
+    ///
+    /// Returns the offset of the element's header, counted from where the iterator started reading.
+    ///
+    pub fn tag_start(&self) -> usize {
+        self.reader.tag_start
+    }
+
+    ///
+    /// Returns the offset where the element's payload starts.
+    ///
+    pub fn data_offset(&self) -> usize {
+        self.data_offset
+    }
+
+    ///
+    /// Returns the number of payload bytes that haven't been read yet.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.reader.remaining
+    }
+
+    ///
+    /// Returns a reader over the element's payload.  Data is passed through as it is stored, so content transforms are not applied.
+    ///
+    pub fn into_reader(self) -> ElementReader<'a, R, TSpec> {
+        self.reader
+    }
+
+    ///
+    /// Skips the element's payload.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the payload can't be read from the source.
+    ///
+    pub fn skip(mut self) -> Result<(), TagIteratorError> {
+        self.reader.skip_rest()
+    }
+
+    ///
+    /// Reads the element's payload and returns it as a tag, applying any content transform registered for it.  Only the part of the payload that hasn't already been read from the element is included.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagIteratorError::UnexpectedEOF`] error holding the data that was read if the source ends before the payload does.  The other possible errors are reading from the source and failing to decode the payload.
+    ///
+    pub fn buffer(mut self) -> Result<TSpec, TagIteratorError> {
+        let mut data = Vec::new();
+        if let Err(source) = self.reader.read_to_end(&mut data) {
+            let reader = &mut self.reader;
+            reader.remaining = 0;
+            return Err(match source.kind() {
+                std::io::ErrorKind::UnexpectedEof => TagIteratorError::UnexpectedEOF(PartialTag { tag_start: reader.tag_start, id: Some(reader.tag_id), size: Some(reader.size), header_len: Some(self.data_offset - reader.tag_start), obtained: data.len(), data: Some(data) }),
+                _ => TagIteratorError::ReadError { source },
+            });
+        }
+        self.reader.iterator.payload_tag(self.reader.tag_id, data)
+    }
+}
+
+///
+/// A tag returned by [`TagIterator::next_borrowed()`], whose data may be borrowed from the iterator's buffer.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorrowedTag<'a, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// A tag read the same way as by [`Iterator::next()`].
+    ///
+    Owned(TSpec),
+
+    ///
+    /// A "Utf8" element, with its value borrowed from the iterator's buffer.
+    ///
+    Utf8 {
+        /// The id of the element
+        id: u64,
+        /// The element's value
+        value: &'a str,
+    },
+
+    ///
+    /// A "Binary" element, with its data borrowed from the iterator's buffer.
+    ///
+    Binary {
+        /// The id of the element
+        id: u64,
+        /// The element's data
+        value: &'a [u8],
+    },
+}
+
+impl<TSpec> BorrowedTag<'_, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ///
+    /// Returns the id of the tag.
+    ///
+    pub fn id(&self) -> u64 {
+        match self {
+            BorrowedTag::Owned(tag) => tag.get_id(),
+            BorrowedTag::Utf8 { id, .. } | BorrowedTag::Binary { id, .. } => *id,
+        }
+    }
+
+    ///
+    /// Copies any borrowed data into a regular tag.
+    ///
+    pub fn into_owned(self) -> TSpec {
+        match self {
+            BorrowedTag::Owned(tag) => tag,
+            BorrowedTag::Utf8 { id, value } => TSpec::get_utf8_tag(id, value.to_string()).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was utf8, but could not get tag!", id)),
+            BorrowedTag::Binary { id, value } => TSpec::get_binary_tag(id, value).unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} type was binary, but could not get tag!", id)),
+        }
+    }
+}
+
+impl<R: Read, TSpec> Read for PendingElement<'_, R, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
     }
 }
@@ -1,70 +1,144 @@
 use ebml_iterable_specification::{EbmlSpecification, EbmlTag};
+use smallvec::SmallVec;
 use std::convert::TryInto;
-use crate::{tag_iterator_util::EBMLSize::{Known, Unknown}, spec_util::is_ended_by};
+use std::io::{ErrorKind, Read};
+use std::ops::Range;
+use crate::{tag_iterator_util::ElementSize::{Known, Unknown}, spec_util::{is_ended_by, VOID_ID}};
+use crate::errors::tag_iterator::{CorruptedFileError, PartialTag, TagIteratorError};
+use crate::tools;
 
+///
+/// The data size declared in an element's header.
+///
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-pub enum EBMLSize {
+pub enum ElementSize {
+
+    ///
+    /// The element's data is this many bytes long.
+    ///
     Known(usize),
+
+    ///
+    /// The element was written with an unknown size (only allowed for "Master" elements), so it ends wherever the next element that can't be one of its children starts.  Sizes too large for a `usize` are also treated as unknown.
+    ///
     Unknown
 }
 
-impl EBMLSize {
-    pub fn new(size: u64, vint_length: usize) -> Self {
-        match vint_length {
-            1 => if size == ((1 << (7))     - 1) { return Unknown; },
-            2 => if size == ((1 << (7 * 2)) - 1) { return Unknown; },
-            3 => if size == ((1 << (7 * 3)) - 1) { return Unknown; },
-            4 => if size == ((1 << (7 * 4)) - 1) { return Unknown; },
-            5 => if size == ((1 << (7 * 5)) - 1) { return Unknown; },
-            6 => if size == ((1 << (7 * 6)) - 1) { return Unknown; },
-            7 => if size == ((1 << (7 * 7)) - 1) { return Unknown; },
-            8 => if size == ((1 << (7 * 8)) - 1) { return Unknown; },
-            _ => {},
+impl ElementSize {
+    ///
+    /// Returns `None` if the size is known but doesn't fit in a `usize`.
+    ///
+    pub(crate) fn new(size: u64, vint_length: usize) -> Option<Self> {
+        if (1..=8).contains(&vint_length) && size == ((1 << (7 * vint_length)) - 1) {
+            return Some(Unknown);
         }
 
-        match size.try_into() {
-            Ok(value) => Known(value),
-            Err(_) => Unknown
+        size.try_into().ok().map(Known)
+    }
+
+    ///
+    /// Returns the size of an element read from a document, making sure that the end of its data can be represented as an offset.
+    ///
+    pub(crate) fn checked(size: u64, vint_length: usize, tag_id: u64, tag_start: usize, header_len: usize) -> Result<Self, TagIteratorError> {
+        let overflow = || TagIteratorError::CorruptedFileData(CorruptedFileError::SizeOverflow { position: tag_start, tag_id, size });
+        let element_size = Self::new(size, vint_length).ok_or_else(overflow)?;
+        if let Known(data_size) = element_size {
+            tag_start.checked_add(header_len).and_then(|data_start| data_start.checked_add(data_size)).ok_or_else(overflow)?;
         }
+        Ok(element_size)
     }
 
+    ///
+    /// Returns `true` if the size is [`ElementSize::Known`].
+    ///
     #[inline(always)]
     pub fn is_known(&self) -> bool {
-        matches!(&self, &EBMLSize::Known(_))
+        matches!(&self, &ElementSize::Known(_))
     }
 
     ///
-    /// # Panics
-    /// 
-    /// Panics if the current variant is not EBMLSize::Known
-    /// 
+    /// Returns `true` if the size is [`ElementSize::Unknown`].
+    ///
+    #[inline(always)]
+    pub fn is_unknown(&self) -> bool {
+        !self.is_known()
+    }
+
+    ///
+    /// Returns the size if it is known.
+    ///
     #[inline(always)]
-    pub fn value(&self) -> usize {
+    pub fn known(&self) -> Option<usize> {
         match &self {
-            EBMLSize::Known(val) => *val,
-            _ => panic!("Called EBMLSize::value() on an unknown size!"),
+            ElementSize::Known(val) => Some(*val),
+            ElementSize::Unknown => None,
         }
     }
 }
 
+impl From<ElementSize> for Option<usize> {
+    fn from(size: ElementSize) -> Self {
+        size.known()
+    }
+}
+
+///
+/// How an element's header was encoded in its source, as reported by [`TagIterator::last_emitted_tag_encoding()`](crate::TagIterator::last_emitted_tag_encoding).
+///
+/// Passing this to [`WriteOptions::with_encoding()`](crate::WriteOptions::with_encoding) makes the writer encode the element the same way, so documents can be re-serialized without changing their bytes.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TagEncoding {
+
+    ///
+    /// The data size declared in the element's header.  For integer and float elements, this is also the width of the value.
+    ///
+    pub size: ElementSize,
+
+    ///
+    /// The byte length of the element's size vint.
+    ///
+    pub size_length: usize,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct ProcessingTag<TSpec>
     where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
 {
     pub tag: TSpec,
-    pub size: EBMLSize,
+    pub size: ElementSize,
     pub tag_start: usize,
     pub data_start: usize,
+
+    /// Set for parents that were inferred from the document path rather than read from the source.  No `End` is emitted for these.
+    pub is_inferred: bool,
 }
 
 impl<TSpec> ProcessingTag<TSpec> where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone {
     pub fn is_ended_by(&self, id: u64) -> bool {
         is_ended_by::<TSpec>(self.tag.get_id(), id)
     }
+
+    ///
+    /// Returns the offset where the data of this tag ends, if its size is known.
+    ///
+    pub fn end(&self) -> Option<usize> {
+        self.size.known().and_then(|size| self.data_start.checked_add(size))
+    }
+
+    pub fn size_length(&self) -> usize {
+        let id_len = self.tag.get_id().to_be_bytes().iter().skip_while(|&v| *v == 0u8).count();
+        (self.data_start - self.tag_start).saturating_sub(id_len)
+    }
 }
 
 pub const DEFAULT_BUFFER_LEN: usize = 1024 * 64;
 
+///
+/// Stack of currently open "Master" tags.  Documents are rarely nested more than a few levels deep, so these are kept inline to avoid allocating.
+///
+pub type TagStack<T> = SmallVec<[T; 8]>;
+
 ///
 /// Used to relax rules on how strictly a [`TagIterator`](crate::TagIterator) should validate the read stream.
 /// 
@@ -83,4 +157,295 @@ pub enum AllowableErrors {
     /// Causes the [`TagIterator`](crate::TagIterator) to emit tags even if they exceed the length of a parent element.
     /// 
     OversizedTags,
-}
\ No newline at end of file
+}
+
+///
+/// Configures how a [`TagIterator`](crate::TagIterator) handles unsigned integer, signed integer, float, and utf8 elements that have no data.
+///
+/// EBML allows these elements to be empty, in which case they hold the default value declared by the spec ([RFC 8794 section 6.3](https://www.rfc-editor.org/rfc/rfc8794.html#section-6.3)), or the zero value for their type if there isn't one.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ZeroLengthValues {
+    ///
+    /// Emits the tag with its default value from [`EbmlSpecification::get_default_value()`](crate::specs::EbmlSpecification::get_default_value), or with a value of `0`, `0.0`, or an empty string if the spec doesn't declare a default.  This is the default.
+    ///
+    EmitDefault,
+
+    ///
+    /// Emits the tag like [`ZeroLengthValues::EmitDefault`], counting it in [`ReadMetrics::zero_length_values`](crate::ReadMetrics::zero_length_values) so applications can warn about it.
+    ///
+    Warn,
+
+    ///
+    /// Returns a [`CorruptedFileError::ZeroLengthValue`](crate::error::CorruptedFileError::ZeroLengthValue) error instead of the tag.  Iteration can continue past the error.
+    ///
+    Error,
+}
+
+///
+/// Configures how a [`TagIterator`](crate::TagIterator) handles data after the last top-level element of a document.
+///
+/// Some files end with padding or junk that doesn't hold valid elements.  Unless the iterator reads it as more elements, data is treated as trailing once a top-level element can't be read after at least one has been read, i.e. its header is invalid or it isn't allowed at the top level.  Elements cut off by the end of the source are still reported as [`TagIteratorError::UnexpectedEOF`](crate::error::TagIteratorError::UnexpectedEOF), as are errors reading from the source itself.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TrailingData {
+    ///
+    /// Reads the data as more elements, reporting errors for anything that can't be read.  This is the default.
+    ///
+    Parse,
+
+    ///
+    /// Ends iteration without reading the rest of the source.
+    ///
+    Ignore,
+
+    ///
+    /// Ends iteration after reading the rest of the source, counting the trailing bytes in [`ReadMetrics::trailing_bytes`](crate::ReadMetrics::trailing_bytes) so applications can warn about them.
+    ///
+    Warn,
+
+    ///
+    /// Returns a [`CorruptedFileError::TrailingData`](crate::error::CorruptedFileError::TrailingData) error, and then ends iteration.
+    ///
+    Error,
+}
+
+///
+/// Configures how a [`TagIterator`](crate::TagIterator) handles elements whose declared size extends past the end of the source.
+///
+/// This can only be detected once the length of the source is known, which is set with [`TagIterator::set_source_len()`](crate::TagIterator::set_source_len) or [`TagIterator::detect_source_len()`](crate::TagIterator::detect_source_len).  Otherwise the iterator only finds out when it runs out of data.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SizePastEnd {
+    ///
+    /// Reads the element as usual, which buffers the rest of the source before reporting a [`TagIteratorError::UnexpectedEOF`](crate::error::TagIteratorError::UnexpectedEOF) error.  This is the default.
+    ///
+    Read,
+
+    ///
+    /// Returns a [`CorruptedFileError::SizePastEnd`](crate::error::CorruptedFileError::SizePastEnd) error as soon as the element's header is read.  As with other corrupted headers, [`TagIterator::try_recover()`](crate::TagIterator::try_recover) can be used to continue past the error.
+    ///
+    Error,
+
+    ///
+    /// Treats the element as ending at the end of the source, counting it in [`ReadMetrics::truncated_elements`](crate::ReadMetrics::truncated_elements) so applications can warn about it.
+    ///
+    Truncate,
+}
+
+///
+/// Configures whether a [`TagIterator`](crate::TagIterator) checks `CRC-32` elements, and how it handles "Master" elements whose data doesn't match their checksum.
+///
+/// Only "Master" elements with a known size whose first child is a `CRC-32` element can be checked.  The whole element is read into the iterator's buffer before its [`Master::Start`](crate::specs::Master::Start) tag is emitted, so elements that can't be buffered (see [`TagIterator::on_large_allocation()`](crate::TagIterator::on_large_allocation)) or that are cut off by the end of the source are read without being checked.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CrcMismatch {
+    ///
+    /// Doesn't check `CRC-32` elements.  This is the default.
+    ///
+    Ignore,
+
+    ///
+    /// Returns a [`CorruptedFileError::CrcMismatch`](crate::error::CorruptedFileError::CrcMismatch) error in place of the element, and continues with its next sibling.
+    ///
+    Error,
+
+    ///
+    /// Emits the element and its children anyway, counting it in [`ReadMetrics::crc_mismatches`](crate::ReadMetrics::crc_mismatches) so applications can warn about it.
+    ///
+    Warn,
+
+    ///
+    /// Skips the element and its children, counting it in [`ReadMetrics::crc_mismatches`](crate::ReadMetrics::crc_mismatches), and continues with its next sibling.
+    ///
+    Skip,
+}
+
+///
+/// The position and size of an element buffered into a [`Master::Full`](crate::specs::Master::Full) tag, along with the layouts of its children.
+///
+/// Returned by [`TagIterator::last_emitted_tag_layout()`](crate::TagIterator::last_emitted_tag_layout) when [`TagIterator::record_buffered_layout()`](crate::TagIterator::record_buffered_layout) is enabled.  `children` lines up with the children of the [`Master::Full`](crate::specs::Master::Full) variant, so `children[i]` describes where the `i`th child tag was read from.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ElementLayout {
+    /// The id of the element
+    pub id: u64,
+
+    /// The offset of the element's header in the source
+    pub offset: usize,
+
+    /// The length of the element's header (id and size)
+    pub header_len: usize,
+
+    /// The size of the element's data, as declared in its header
+    pub size: ElementSize,
+
+    /// The layouts of the element's children, if it is a "Master" element
+    pub children: Vec<ElementLayout>,
+}
+
+impl ElementLayout {
+    ///
+    /// Returns the offset of the element's data in the source.
+    ///
+    pub fn data_offset(&self) -> usize {
+        self.offset + self.header_len
+    }
+
+    ///
+    /// Returns the offset just past the end of the element, if its size is known.
+    ///
+    pub fn end_offset(&self) -> Option<usize> {
+        self.size.known().map(|size| self.data_offset() + size)
+    }
+}
+
+///
+/// Where a tag emitted by a [`TagIterator`](crate::TagIterator) was read from in its source.
+///
+/// Returned alongside each tag by [`TagIterator::iter_with_spans()`](crate::TagIterator::iter_with_spans), or for the last emitted tag by [`TagIterator::last_emitted_tag_span()`](crate::TagIterator::last_emitted_tag_span).  Spans of [`Master::End`](crate::specs::Master::End) tags describe the whole "Master" element, the same as their [`Master::Start`](crate::specs::Master::Start).
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TagSpan {
+    /// The offset of the tag's header in the source
+    pub offset: usize,
+
+    /// The length of the tag's header (id and size)
+    pub header_len: usize,
+
+    /// The size of the tag's data, as declared in its header
+    pub size: ElementSize,
+}
+
+impl TagSpan {
+    ///
+    /// Returns the offset of the tag's data in the source.
+    ///
+    pub fn data_offset(&self) -> usize {
+        self.offset + self.header_len
+    }
+
+    ///
+    /// Returns the offset just past the end of the tag, if its size is known.
+    ///
+    pub fn end_offset(&self) -> Option<usize> {
+        self.size.known().map(|size| self.data_offset() + size)
+    }
+
+    ///
+    /// Returns the range of the source holding the whole tag (header and data), if its size is known.  This can be used to slice the original bytes of the tag out of the source.
+    ///
+    pub fn range(&self) -> Option<Range<usize>> {
+        self.end_offset().map(|end| self.offset..end)
+    }
+}
+
+///
+/// Header information (id and size) for an element read directly from a source.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ElementHeader {
+    pub id: u64,
+    pub size: ElementSize,
+    pub header_len: usize,
+}
+
+impl ElementHeader {
+    ///
+    /// Returns the total length of the element (header + data), if the element size is known.
+    ///
+    pub fn total_len(&self) -> Option<usize> {
+        match self.size {
+            Known(size) => Some(self.header_len + size),
+            Unknown => None,
+        }
+    }
+
+    ///
+    /// Returns the number of bytes used to encode the element id.
+    ///
+    pub fn id_len(&self) -> usize {
+        8 - (self.id.leading_zeros() / 8) as usize
+    }
+
+    ///
+    /// Returns the header of a `Void` element that is exactly `total_len` bytes long (header included).  `total_len` must be at least 2.
+    ///
+    pub fn void(total_len: usize) -> Self {
+        assert!(total_len >= 2, "Void elements are at least 2 bytes long");
+        for size_len in 1..=8 {
+            let data_len = total_len - 1 - size_len;
+            // All ones is reserved for unknown sizes
+            if (data_len as u64) < (1 << (7 * size_len)) - 1 {
+                return ElementHeader { id: VOID_ID, size: Known(data_len), header_len: 1 + size_len };
+            }
+        }
+        panic!("Void element length {} is too large to encode", total_len);
+    }
+
+    ///
+    /// Encodes the header, using the same size vint length it was read with.
+    ///
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.id.to_be_bytes()[(8 - self.id_len())..].to_vec();
+        let size_len = self.header_len - bytes.len();
+        let value = match self.size {
+            Known(size) => size as u64,
+            Unknown => (1 << (7 * size_len)) - 1,
+        };
+        let marked = value | (1 << (7 * size_len));
+        bytes.extend_from_slice(&marked.to_be_bytes()[(8 - size_len)..]);
+        bytes
+    }
+}
+
+///
+/// Reads an element header from the current position of `source`.
+///
+/// Returns `Ok(None)` if the source is already at EOF.  `position` is only used for error reporting.
+///
+pub fn read_element_header<R: Read>(source: &mut R, position: usize) -> Result<Option<ElementHeader>, TagIteratorError> {
+    let mut buffer = [0u8; 8];
+    let id_len = match read_vint_bytes(source, &mut buffer, position, None)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+    let id = tools::arr_to_u64(&buffer[..id_len]).expect("vint length should never exceed 8");
+
+    let size_len = match read_vint_bytes(source, &mut buffer, position, Some(id))? {
+        Some(len) => len,
+        None => return Err(TagIteratorError::UnexpectedEOF(PartialTag::in_header(position, Some(id)))),
+    };
+    let size = tools::read_vint(&buffer[..size_len])
+        .map_err(|_| TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id: id, position }))?
+        .expect("buffer should contain a complete vint")
+        .0;
+
+    Ok(Some(ElementHeader {
+        id,
+        size: ElementSize::checked(size, size_len, id, position, id_len + size_len)?,
+        header_len: id_len + size_len,
+    }))
+}
+
+fn read_vint_bytes<R: Read>(source: &mut R, buffer: &mut [u8; 8], position: usize, tag_id: Option<u64>) -> Result<Option<usize>, TagIteratorError> {
+    loop {
+        match source.read(&mut buffer[..1]) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(source) => return Err(TagIteratorError::ReadError { source }),
+        }
+    }
+
+    if buffer[0] == 0 {
+        return Err(TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id: tag_id.unwrap_or(0), position }));
+    }
+
+    let length = 8 - buffer[0].ilog2() as usize;
+    source.read_exact(&mut buffer[1..length]).map_err(|source| match source.kind() {
+        ErrorKind::UnexpectedEof => TagIteratorError::UnexpectedEOF(PartialTag::in_header(position, tag_id)),
+        _ => TagIteratorError::ReadError { source },
+    })?;
+    Ok(Some(length))
+}
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::SerializedTagError;
+use crate::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+
+///
+/// A self-describing representation of a tag (and, for "Master" tags, all of its children) that can be serialized through [`serde`](https://crates.io/crates/serde).
+///
+/// This works with any `TSpec`, without the specification needing to implement `Serialize` or `Deserialize` itself.  The layout only depends on the tag id and data type, so serialized documents stay readable as long as the specification's ids do.  For convenience, the tag's name (if the specification has one) is also included.
+///
+/// [`SerdeTag`] can be used to serialize or deserialize `TSpec` tags directly.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::utils::SerializedTag;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let tag = EmptySpec::with_data(0x4286, &[0x01]);
+/// let serialized = SerializedTag::from_tag(&tag);
+/// assert_eq!(0x4286, serialized.id);
+/// assert_eq!(tag, serialized.into_tag::<EmptySpec>().unwrap());
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SerializedTag {
+
+    ///
+    /// The id of the tag.
+    ///
+    pub id: u64,
+
+    ///
+    /// The name of the tag in the specification.  This is informational only and is ignored when converting back into a tag.
+    ///
+    /// This is always serialized (as a null for tags that aren't in the specification) so that formats encoding structs positionally still work, but may be left out of input for self-describing formats.
+    ///
+    #[serde(default)]
+    pub name: Option<String>,
+
+    ///
+    /// The data contained in the tag.
+    ///
+    pub value: SerializedValue,
+}
+
+///
+/// The data contained in a [`SerializedTag`].
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerializedValue {
+
+    ///
+    /// Data of a [`TagDataType::UnsignedInt`] tag.
+    ///
+    UnsignedInt(u64),
+
+    ///
+    /// Data of a [`TagDataType::Integer`] tag.
+    ///
+    Integer(i64),
+
+    ///
+    /// Data of a [`TagDataType::Utf8`] tag.
+    ///
+    Utf8(String),
+
+    ///
+    /// Data of a [`TagDataType::Utf8`] tag that is not valid UTF-8 (possible when the specification stores text as [`LazyUtf8`][`crate::specs::LazyUtf8`]).
+    ///
+    InvalidUtf8(#[serde(with = "byte_buf")] Vec<u8>),
+
+    ///
+    /// Data of a [`TagDataType::Binary`] tag.
+    ///
+    Binary(#[serde(with = "byte_buf")] Vec<u8>),
+
+    ///
+    /// Data of a [`TagDataType::Float`] tag.
+    ///
+    Float(f64),
+
+    ///
+    /// A [`Master::Start`] tag.
+    ///
+    Start,
+
+    ///
+    /// A [`Master::End`] tag.
+    ///
+    End,
+
+    ///
+    /// The children of a [`Master::Full`] tag.
+    ///
+    Children(Vec<SerializedTag>),
+
+    ///
+    /// Data of a tag that is not in the specification.
+    ///
+    Raw(#[serde(with = "byte_buf")] Vec<u8>),
+}
+
+impl SerializedTag {
+
+    ///
+    /// Creates the serializable representation of `tag`.
+    ///
+    /// # Panics
+    ///
+    /// This can panic if `<TSpec>` is an internally inconsistent specification (i.e. it claims that a specific tag variant is a specific data type but it is not).
+    ///
+    pub fn from_tag<TSpec>(tag: &TSpec) -> Self
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        let id = tag.get_id();
+        let value = match TSpec::get_tag_data_type(id) {
+            Some(TagDataType::UnsignedInt) => SerializedValue::UnsignedInt(*tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", id))),
+            Some(TagDataType::Integer) => SerializedValue::Integer(*tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", id))),
+            Some(TagDataType::Utf8) => match tag.as_utf8() {
+                Some(val) => SerializedValue::Utf8(val.to_string()),
+                None => SerializedValue::InvalidUtf8(tag.as_utf8_bytes().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", id)).to_vec()),
+            },
+            Some(TagDataType::Binary) => SerializedValue::Binary(tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", id)).to_vec()),
+            Some(TagDataType::Float) => SerializedValue::Float(*tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", id))),
+            Some(TagDataType::Master) => match tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", id)) {
+                Master::Start => SerializedValue::Start,
+                Master::End => SerializedValue::End,
+                Master::Full(children) => SerializedValue::Children(children.iter().map(Self::from_tag).collect()),
+            },
+            None => SerializedValue::Raw(tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was raw tag, but could not get binary data!", id)).to_vec()),
+        };
+
+        SerializedTag { id, name: TSpec::get_name_by_id(id).map(String::from), value }
+    }
+
+    ///
+    /// Converts this representation back into a tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the id isn't in `<TSpec>` (unless the value is [`SerializedValue::Raw`]), or if the value doesn't match the tag's data type.
+    ///
+    pub fn into_tag<TSpec>(self) -> Result<TSpec, SerializedTagError>
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        let id = self.id;
+        let data_type = TSpec::get_tag_data_type(id);
+        let tag = match (data_type, self.value) {
+            (None, SerializedValue::Raw(data)) => Some(TSpec::get_raw_tag(id, &data)),
+            (None, _) => return Err(SerializedTagError::UnknownTag { id }),
+            (Some(TagDataType::UnsignedInt), SerializedValue::UnsignedInt(val)) => TSpec::get_unsigned_int_tag(id, val),
+            (Some(TagDataType::Integer), SerializedValue::Integer(val)) => TSpec::get_signed_int_tag(id, val),
+            (Some(TagDataType::Utf8), SerializedValue::Utf8(val)) => TSpec::get_utf8_tag(id, val),
+            (Some(TagDataType::Utf8), SerializedValue::InvalidUtf8(data)) => match TSpec::get_utf8_tag_bytes(id, data) {
+                Some(Ok(tag)) => Some(tag),
+                Some(Err(_)) => return Err(SerializedTagError::InvalidValue { id, expected: TagDataType::Utf8 }),
+                None => None,
+            },
+            (Some(TagDataType::Binary), SerializedValue::Binary(data)) => TSpec::get_binary_tag_owned(id, data),
+            (Some(TagDataType::Float), SerializedValue::Float(val)) => TSpec::get_float_tag(id, val),
+            (Some(TagDataType::Master), SerializedValue::Start) => TSpec::get_master_tag(id, Master::Start),
+            (Some(TagDataType::Master), SerializedValue::End) => TSpec::get_master_tag(id, Master::End),
+            (Some(TagDataType::Master), SerializedValue::Children(children)) => {
+                let children = children.into_iter().map(Self::into_tag).collect::<Result<Vec<TSpec>, _>>()?;
+                TSpec::get_master_tag(id, Master::Full(children))
+            },
+            (Some(expected), _) => return Err(SerializedTagError::InvalidValue { id, expected }),
+        };
+
+        Ok(tag.unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was {:?}, but could not get tag!", id, data_type)))
+    }
+}
+
+///
+/// Wraps a `TSpec` tag so it can be serialized and deserialized directly, using the [`SerializedTag`] representation.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::utils::SerdeTag;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// fn store<S: serde::Serializer>(tags: Vec<EmptySpec>, serializer: S) -> Result<S::Ok, S::Error> {
+///     serde::Serialize::serialize(&tags.into_iter().map(SerdeTag).collect::<Vec<_>>(), serializer)
+/// }
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SerdeTag<TSpec>(pub TSpec);
+
+impl<TSpec> Serialize for SerdeTag<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedTag::from_tag(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de, TSpec> Deserialize<'de> for SerdeTag<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SerializedTag::deserialize(deserializer)?
+            .into_tag()
+            .map(SerdeTag)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+///
+/// Serializes binary data as a byte string (for formats that have one) rather than as a sequence of numbers.
+///
+mod byte_buf {
+    use std::fmt;
+
+    use serde::de::{SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(data)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        deserializer.deserialize_byte_buf(ByteBufVisitor)
+    }
+
+    struct ByteBufVisitor;
+
+    impl<'de> Visitor<'de> for ByteBufVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a byte array")
+        }
+
+        fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(byte) = seq.next_element()? {
+                data.push(byte);
+            }
+            Ok(data)
+        }
+    }
+}
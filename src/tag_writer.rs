@@ -1,23 +1,33 @@
-use std::io::Write;
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Seek, SeekFrom, Write};
 use std::convert::{TryInto, TryFrom};
+use std::time::{Duration, Instant};
 
 use crate::errors::tool::ToolError;
-use crate::spec_util::validate_tag_path;
+use crate::spec_util::{validate_tag_path, VOID_ID};
+use crate::transform::{ContentTransform, ContentTransforms};
+use crate::stats::{self, MetricsTracker, WriteMetrics};
+use crate::profile::Profile;
+use crate::cue_builder::CueBuilder;
+use crate::normalize::normalize;
 
-use super::tag_iterator_util::EBMLSize::{self, Known, Unknown};
+use super::tag_iterator_util::ElementSize::{self, Known, Unknown};
+use super::tag_iterator_util::{read_element_header, ElementHeader, TagEncoding, TagStack};
 
-use super::tools::{Vint, is_vint};
-use super::specs::{EbmlSpecification, EbmlTag, TagDataType, Master};
+use super::tools::{self, Vint, is_vint};
+use super::specs::{EbmlSpecification, EbmlTag, ElementMeta, TagDataType, Master};
 
 use super::errors::tag_writer::TagWriterError;
 
 ///
 /// Options that can be passed to the writer to customize written output
 /// 
+#[derive(Clone, Copy, Debug, Default)]
 pub struct WriteOptions
 {
     size_byte_length: Option<usize>,
     unknown_sized_element: bool,
+    encoding: Option<TagEncoding>,
 }
 
 impl WriteOptions {
@@ -34,7 +44,8 @@ impl WriteOptions {
         assert!(len > 0 && len < 9, "Size byte count for written vints must be within 1-8 (inclusive)");
         Self {
             size_byte_length: Some(len),
-            unknown_sized_element: false
+            unknown_sized_element: false,
+            encoding: None,
         }
     }
 
@@ -46,9 +57,179 @@ impl WriteOptions {
     pub fn is_unknown_sized_element() -> Self {
         Self {
             size_byte_length: None,
-            unknown_sized_element: true
+            unknown_sized_element: true,
+            encoding: None,
         }
     }
+
+    ///
+    /// Specifies that the element should be encoded the way it was in its source.
+    ///
+    /// The [`WriteOptions`] generated by this function replay an encoding reported by [`TagIterator::last_emitted_tag_encoding()`](crate::TagIterator::last_emitted_tag_encoding): the length of the size vint, whether a "Master" tag has an unknown size, and the width of integer and float values.  Tags written this way are byte-identical to their source, so tools can rewrite a document without changing the parts they didn't touch.  If a tag was modified and its value or size no longer fits the recorded encoding, it is encoded as [`TagWriter::write()`] would encode it.
+    ///
+    /// Only the outer tag of a [`Master::Full`] variant is written with the encoding; its children are written as usual.
+    ///
+    pub fn with_encoding(encoding: TagEncoding) -> Self {
+        Self {
+            size_byte_length: None,
+            unknown_sized_element: false,
+            encoding: Some(encoding),
+        }
+    }
+}
+
+fn encode_master_size(size: u64, size_length: usize) -> Result<Vec<u8>, TagWriterError> {
+    match size_length {
+        1 => size.as_vint_with_length::<1>().map(|v| v.to_vec()),
+        2 => size.as_vint_with_length::<2>().map(|v| v.to_vec()),
+        3 => size.as_vint_with_length::<3>().map(|v| v.to_vec()),
+        4 => size.as_vint_with_length::<4>().map(|v| v.to_vec()),
+        5 => size.as_vint_with_length::<5>().map(|v| v.to_vec()),
+        6 => size.as_vint_with_length::<6>().map(|v| v.to_vec()),
+        7 => size.as_vint_with_length::<7>().map(|v| v.to_vec()),
+        8 => size.as_vint_with_length::<8>().map(|v| v.to_vec()),
+        _ => tools::size_as_vint(size),
+    }.map_err(|e| TagWriterError::TagSizeError(e.to_string()))
+}
+
+fn fits_size_length(size: u64, size_length: usize) -> bool {
+    // The largest value of each length is reserved for unknown sizes
+    (1..=8).contains(&size_length) && size < (1 << (7 * size_length)) - 1
+}
+
+fn replay_size(size: u64, size_length: usize) -> Result<Vec<u8>, TagWriterError> {
+    if fits_size_length(size, size_length) {
+        encode_master_size(size, size_length)
+    } else {
+        encode_master_size(size, 0)
+    }
+}
+
+fn replay_unsigned_int(val: u64, width: usize) -> Option<Vec<u8>> {
+    let bytes = val.to_be_bytes();
+    (width <= 8 && bytes[..(8 - width)].iter().all(|b| *b == 0)).then(|| bytes[(8 - width)..].to_vec())
+}
+
+fn replay_signed_int(val: i64, width: usize) -> Option<Vec<u8>> {
+    let bytes = val.to_be_bytes();
+    let fill = if val < 0 { 0xff } else { 0 };
+    let fits = match width {
+        0 => val == 0,
+        1..=8 => bytes[..(8 - width)].iter().all(|b| *b == fill) && (bytes[8 - width] ^ fill) & 0x80 == 0,
+        _ => false,
+    };
+    fits.then(|| bytes[(8 - width)..].to_vec())
+}
+
+fn replay_float(val: f64, width: usize) -> Option<Vec<u8>> {
+    match width {
+        0 if val.to_bits() == 0 => Some(Vec::new()),
+        4 if f64::from(val as f32).to_bits() == val.to_bits() => Some((val as f32).to_be_bytes().to_vec()),
+        8 => Some(val.to_be_bytes().to_vec()),
+        _ => None,
+    }
+}
+
+///
+/// The header of a known-size "Master" tag, which can't be written until the tag is closed and its size is known.
+///
+/// Rather than shifting the tag's content to make room for the header in the working buffer, headers are kept aside (in document order) and stitched into the output when it is flushed.
+///
+struct PendingHeader {
+    position: usize,
+    preceding_header_len: usize,
+    bytes: Vec<u8>,
+
+    /// Set when the size length was replayed from a [`TagEncoding`], in which case a minimal size is used if the content outgrew it
+    replayed: bool,
+}
+
+///
+/// An element written by a [`TagWriter`], as recorded in its audit log (see [`TagWriter::record_audit_log()`]).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WrittenElement {
+
+    ///
+    /// The id of the element.
+    ///
+    pub id: u64,
+
+    ///
+    /// The offset of the element's header, relative to the first byte written by the writer.
+    ///
+    pub offset: u64,
+
+    ///
+    /// The total length of the element (header included), or `None` for "Master" elements written with an unknown size.
+    ///
+    pub size: Option<u64>,
+
+    ///
+    /// The length of the element's header (its id and size), so that its data starts at `offset + header_len`.
+    ///
+    pub header_len: usize,
+
+    ///
+    /// When the element was passed to the writer.  For known-size "Master" elements written using [`Master::Start`], this is when the element was started.
+    ///
+    /// This is `None` on `wasm32-unknown-unknown`, which has no clock.
+    ///
+    pub written_at: Option<Instant>,
+}
+
+///
+/// An audit log entry whose element is still in the working buffer.
+///
+struct PendingAuditEntry {
+    element: WrittenElement,
+    position: usize,
+    preceding_headers: usize,
+    awaiting_size: bool,
+}
+
+///
+/// Space reserved by [`TagWriter::reserve_bookmark()`], along with the ids of the tags that were open around it.
+///
+struct Bookmark {
+    location: BookmarkLocation,
+    len: usize,
+    parents: Vec<u64>,
+}
+
+enum BookmarkLocation {
+    Buffered(usize),
+    Written(u64),
+}
+
+///
+/// The callback registered by [`TagWriter::build_cues()`], along with the ids of the elements it is called for.
+///
+struct AnchorCallback {
+    ids: Vec<u64>,
+    callback: Box<dyn FnMut(&WrittenElement) + Send>,
+}
+
+fn void_element(len: usize) -> Vec<u8> {
+    let mut element = ElementHeader::void(len).encode();
+    element.resize(len, 0);
+    element
+}
+
+///
+/// Writes as much of `data` as `dest` accepts, returning how many bytes were written along with the error that stopped the write (if any).
+///
+fn write_partial<W: Write>(dest: &mut W, data: &[u8]) -> (usize, Option<io::Error>) {
+    let mut written = 0;
+    while written < data.len() {
+        match dest.write(&data[written..]) {
+            Ok(0) => return (written, Some(ErrorKind::WriteZero.into())),
+            Ok(len) => written += len,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {},
+            Err(err) => return (written, Some(err)),
+        }
+    }
+    (written, None)
 }
 
 ///
@@ -59,8 +240,24 @@ impl WriteOptions {
 pub struct TagWriter<W: Write>
 {
     dest: W,
-    open_tags: Vec<(u64, EBMLSize, usize)>,
+    open_tags: TagStack<(u64, ElementSize, usize)>,
     working_buffer: Vec<u8>,
+    pending_headers: Vec<PendingHeader>,
+    pending_header_len: usize,
+    direct_depth: usize,
+    transforms: ContentTransforms,
+    metrics: MetricsTracker<WriteMetrics>,
+    validate_restricted_values: bool,
+    profile: Option<Profile>,
+    normalization: Option<&'static [ElementMeta]>,
+    bookmarks: HashMap<String, Bookmark>,
+    bytes_flushed: u64,
+    audit_log: Option<Vec<WrittenElement>>,
+    pending_audit: Vec<PendingAuditEntry>,
+    anchors: Option<AnchorCallback>,
+
+    /// Output that the destination didn't accept because of an error, written before anything else
+    unwritten: Vec<u8>,
 }
 
 impl<W: Write> TagWriter<W>
@@ -73,8 +270,22 @@ impl<W: Write> TagWriter<W>
     pub fn new(dest: W) -> Self {
         TagWriter {
             dest,
-            open_tags: Vec::new(),
+            open_tags: TagStack::new(),
             working_buffer: Vec::new(),
+            pending_headers: Vec::new(),
+            pending_header_len: 0,
+            direct_depth: 0,
+            transforms: ContentTransforms::default(),
+            metrics: MetricsTracker::default(),
+            validate_restricted_values: false,
+            profile: None,
+            normalization: None,
+            bookmarks: HashMap::new(),
+            bytes_flushed: 0,
+            audit_log: None,
+            pending_audit: Vec::new(),
+            anchors: None,
+            unwritten: Vec::new(),
         }
     }
 
@@ -102,13 +313,243 @@ impl<W: Write> TagWriter<W>
         &self.dest
     }
 
+    ///
+    /// Returns the ids of the "Master" tags that are currently open, outermost first.
+    ///
+    /// After a write fails, this shows where the document was cut off, e.g. to decide whether to [retry](Self::retry_write) or to [close the open tags](Self::flush) on a [replacement destination](Self::replace_destination).
+    ///
+    pub fn open_tag_ids(&self) -> Vec<u64> {
+        self.open_tags.iter().map(|t| t.0).collect()
+    }
+
+    ///
+    /// Returns the number of bytes that are ready for the destination but haven't been accepted by it because a write failed.
+    ///
+    /// Returns 0 unless a previous call returned a [`TagWriterError::WriteError`].
+    ///
+    pub fn unwritten_len(&self) -> usize {
+        self.unwritten.len()
+    }
+
+    ///
+    /// Retries writing the output that the destination didn't accept because of an error, e.g. once a network connection has recovered.
+    ///
+    /// When a write to the destination fails, the writer keeps track of exactly which bytes were not accepted and its open tags stay as they were, so writing can continue once this succeeds without restarting the document.  Anything written to the writer in the meantime is queued behind the unwritten bytes.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagWriterError::WriteError`] if the destination fails again.  The bytes it didn't accept are kept for another attempt.
+    ///
+    pub fn retry_write(&mut self) -> Result<(), TagWriterError> {
+        self.write_output(&[])?;
+        self.dest.flush().map_err(|source| TagWriterError::WriteError { source })
+    }
+
+    ///
+    /// Replaces the destination with `dest`, returning the previous one.
+    ///
+    /// Output that the previous destination didn't accept (see [`Self::unwritten_len()`]) is written to the new destination, followed by everything written afterwards, so the new destination continues exactly where the previous one stopped.  Calling [`Self::flush()`] after replacing a failed destination closes every open tag, so a document cut off by a broken connection can be completed elsewhere without rewriting what was already sent.
+    ///
+    pub fn replace_destination(&mut self, dest: W) -> W {
+        std::mem::replace(&mut self.dest, dest)
+    }
+
+    ///
+    /// Writes `data` to the destination after any output left over from a failed write, keeping whatever isn't accepted.
+    ///
+    fn write_output(&mut self, data: &[u8]) -> Result<(), TagWriterError> {
+        let (written, mut err) = write_partial(&mut self.dest, &self.unwritten);
+        self.unwritten.drain(..written);
+        let mut data = data;
+        if err.is_none() {
+            let (written, data_err) = write_partial(&mut self.dest, data);
+            data = &data[written..];
+            err = data_err;
+        }
+        match err {
+            Some(source) => {
+                self.unwritten.extend_from_slice(data);
+                Err(TagWriterError::WriteError { source })
+            },
+            None => Ok(()),
+        }
+    }
+
+    ///
+    /// Registers a [`ContentTransform`] to encode the data of binary tags with any of the given `ids` before they are written.
+    ///
+    /// If a transform fails, the write returns a [`TagWriterError::TransformError`].  Registering a transform for an id that already has one replaces it.  Data written using [`Self::write_raw()`] is not transformed.
+    ///
+    pub fn add_content_transform(&mut self, ids: &[u64], transform: impl ContentTransform + 'static) {
+        self.transforms.add(ids, Box::new(transform));
+    }
+
+    ///
+    /// Configures whether the writer checks unsigned integer tags against the values allowed by the specification (see [`EbmlSpecification::get_restricted_values()`]).
+    ///
+    /// Disabled by default.  When enabled, writing a tag holding any other value fails with a [`TagWriterError::RestrictedValue`] and nothing is written.  Data written using [`Self::write_raw()`] is not checked.
+    ///
+    pub fn validate_restricted_values(&mut self, validate: bool) {
+        self.validate_restricted_values = validate;
+    }
+
+    ///
+    /// Sets a [`Profile`] that every written tag must conform to, or removes it if `profile` is `None`.
+    ///
+    /// Tags that break the profile (including data written using [`Self::write_raw()`]) fail with a [`TagWriterError::ProfileViolation`] and nothing is written for them.
+    ///
+    pub fn set_profile(&mut self, profile: Option<Profile>) {
+        self.profile = profile;
+    }
+
+    ///
+    /// Enables normalizing [`Master::Full`] tags before they are written, using the spec described by `metadata` (the `SPEC_METADATA` table generated by `#[ebml_specification(metadata)]`), or disables it if `metadata` is `None`.
+    ///
+    /// Disabled by default.  This turns trees built on a best-effort basis by application code into conformant output:
+    ///  - Children that aren't allowed in their parent are moved up to the closest ancestor in the tree that allows them.
+    ///  - Missing mandatory children are added with their default value, if the spec declares one.
+    ///  - A `CRC-32` child is moved first, followed by children that may only occur once in the order the spec declares them.  Children that may repeat keep their relative order.
+    ///
+    /// Tags written as [`Master::Start`] and [`Master::End`] pairs aren't normalized, since their children aren't known up front.
+    ///
+    pub fn set_normalization(&mut self, metadata: Option<&'static [ElementMeta]>) {
+        self.normalization = metadata;
+    }
+
+    ///
+    /// Configures whether the writer records the id, offset, and size of every element it writes.
+    ///
+    /// Disabled by default.  The recorded elements, in the order they were written, can be retrieved using [`Self::audit_log()`] or [`Self::take_audit_log()`].  This is useful for debugging complex muxing pipelines, or for collecting the positions needed to build an index of the output.  Disabling the log discards anything recorded so far.
+    ///
+    pub fn record_audit_log(&mut self, record: bool) {
+        if record {
+            self.audit_log.get_or_insert_with(Vec::new);
+        } else {
+            self.audit_log = None;
+            if self.anchors.is_none() {
+                self.pending_audit.clear();
+            }
+        }
+    }
+
+    ///
+    /// Returns the elements recorded in the audit log (see [`Self::record_audit_log()`]).
+    ///
+    /// Elements only appear once they have been written to the destination, so the contents of known-size "Master" tags are only included after the tags are closed.
+    ///
+    pub fn audit_log(&self) -> &[WrittenElement] {
+        self.audit_log.as_deref().unwrap_or_default()
+    }
+
+    ///
+    /// Returns the elements recorded in the audit log so far and clears it.  Recording continues if it is enabled.
+    ///
+    pub fn take_audit_log(&mut self) -> Vec<WrittenElement> {
+        self.audit_log.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    ///
+    /// Calls `callback` with every "anchor" element (any element with one of the `anchor_ids`) once it has been written, and collects the index entries it returns.
+    ///
+    /// This is the groundwork for building an index like Matroska's `Cues`: the callback sees the offset of every `Cluster` and block as they are written, and the caller serializes the collected entries using their own specification once the anchored elements are done.  Elements are passed to the callback in the order they were written, so a "Master" element comes before its children.  Like the audit log (see [`Self::record_audit_log()`]), elements inside known-size "Master" tags are only passed to the callback once those tags are closed.
+    ///
+    /// Registering a new callback replaces the previous one.  The returned [`CueBuilder`] holds the collected entries.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// let cues = writer.build_cues(&[0x1f43b675], |element| Some(element.offset));
+    /// writer.write(&EmptySpec::with_data(0x4286, &[0x01])).unwrap();
+    /// writer.write(&EmptySpec::with_data(0x1f43b675, &[])).unwrap();
+    /// assert_eq!(vec![4], cues.take_entries());
+    /// ```
+    ///
+    pub fn build_cues<E, F>(&mut self, anchor_ids: &[u64], callback: F) -> CueBuilder<E>
+        where
+        E: Send + 'static,
+        F: FnMut(&WrittenElement) -> Option<E> + Send + 'static
+    {
+        let cues = CueBuilder::new();
+        self.anchors = Some(AnchorCallback { ids: anchor_ids.to_vec(), callback: Box::new(cues.recorder(callback)) });
+        cues
+    }
+
+    fn audit(&mut self, id: u64, position: usize, preceding_headers: usize, header_len: usize, size: Option<u64>, awaiting_size: bool) {
+        if self.audit_log.is_some() || self.anchors.is_some() {
+            self.pending_audit.push(PendingAuditEntry {
+                element: WrittenElement { id, offset: 0, size, header_len, written_at: stats::now() },
+                position,
+                preceding_headers,
+                awaiting_size,
+            });
+        }
+    }
+
+    ///
+    /// Returns the header length of the element with id `id` encoded at `position` in the working buffer.
+    ///
+    fn buffered_header_len(&self, id: u64, position: usize) -> usize {
+        let id_len = id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count();
+        id_len + self.working_buffer[position + id_len].leading_zeros() as usize + 1
+    }
+
+    fn check_profile(&self, tag_id: u64, unknown_size: bool) -> Result<(), TagWriterError> {
+        match &self.profile {
+            Some(profile) => profile.check(tag_id, unknown_size).map_err(|violation| TagWriterError::ProfileViolation { tag_id, violation }),
+            None => Ok(()),
+        }
+    }
+
+    fn check_restricted_value<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self, tag: &TSpec, tag_id: u64, tag_type: Option<TagDataType>) -> Result<(), TagWriterError> {
+        if self.validate_restricted_values && matches!(tag_type, Some(TagDataType::UnsignedInt)) {
+            if let (Some(allowed), Some(value)) = (TSpec::get_restricted_values(tag_id), tag.as_unsigned_int()) {
+                if !allowed.contains(value) {
+                    return Err(TagWriterError::RestrictedValue { tag_id, value: *value });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns the writer's progress and throughput counters so far.  See [`WriteMetrics`] for details.
+    ///
+    pub fn metrics(&self) -> WriteMetrics {
+        self.metrics.snapshot()
+    }
+
+    ///
+    /// Registers a callback that periodically receives the writer's [`WriteMetrics`].
+    ///
+    /// The callback is called while tags are being written, at most once per `interval`.  On `wasm32-unknown-unknown`, which has no clock, the interval can't be measured and the callback is called every time instead.  Registering a new callback replaces the previous one.
+    ///
+    pub fn set_metrics_callback(&mut self, interval: Duration, callback: impl FnMut(&WriteMetrics) + Send + 'static) {
+        self.metrics.set_callback(interval, Box::new(callback));
+    }
+
+    ///
+    /// Treats `parents` as already open (with unknown sizes) so that written tags are validated as children of that path.  Nothing is written for the parents themselves.
+    ///
+    pub(crate) fn assume_open_parents(&mut self, parents: &[u64]) {
+        self.open_tags.extend(parents.iter().map(|id| (*id, Unknown, 0)));
+    }
+
     fn start_tag(&mut self, id: u64, size_length: usize) {
-        self.open_tags.push((id, Known(self.working_buffer.len()), size_length));
+        self.audit(id, self.working_buffer.len(), self.pending_headers.len(), 0, None, true);
+        self.open_tags.push((id, Known(self.pending_headers.len()), size_length));
+        self.pending_headers.push(PendingHeader { position: self.working_buffer.len(), preceding_header_len: self.pending_header_len, bytes: Vec::new(), replayed: false });
     }
 
-    fn start_unknown_size_tag(&mut self, id: u64) {
+    fn start_unknown_size_tag(&mut self, id: u64, size_length: usize) {
+        let id_len = id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count();
+        self.audit(id, self.working_buffer.len(), self.pending_headers.len(), id_len + size_length, None, false);
         self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
-        self.working_buffer.extend_from_slice(&(u64::MAX >> 7).to_be_bytes());
+        // Every value bit of the size vint is set
+        self.working_buffer.extend_from_slice(&(u64::MAX >> (63 - 7 * size_length)).to_be_bytes()[(8 - size_length)..]);
         self.open_tags.push((id, Unknown, 0));
     }
 
@@ -116,22 +557,25 @@ impl<W: Write> TagWriter<W>
         match self.open_tags.pop() {
             Some(open_tag) => {
                 if open_tag.0 == id {
-                    if let Known(start) = open_tag.1 {
-                        let size: u64 = self.working_buffer.len()
-                            .checked_sub(start).expect("overflow subtracting tag size from working buffer length")
+                    if let Known(header_index) = open_tag.1 {
+                        let header = &self.pending_headers[header_index];
+                        let size: u64 = (self.working_buffer.len() + self.pending_header_len)
+                            .checked_sub(header.position + header.preceding_header_len).expect("overflow subtracting tag size from working buffer length")
                             .try_into().expect("couldn't convert usize to u64");
-    
-                        match open_tag.2 {
-                            1 => { let size_vint = size.as_vint_with_length::<1>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                            2 => { let size_vint = size.as_vint_with_length::<2>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                            3 => { let size_vint = size.as_vint_with_length::<3>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                            4 => { let size_vint = size.as_vint_with_length::<4>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                            5 => { let size_vint = size.as_vint_with_length::<5>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                            6 => { let size_vint = size.as_vint_with_length::<6>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                            7 => { let size_vint = size.as_vint_with_length::<7>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                            8 => { let size_vint = size.as_vint_with_length::<8>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                            _ => { let size_vint = size.as_vint().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?; self.working_buffer.splice(start..start, open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8).chain(size_vint.iter()).copied()); }
-                        };
+
+                        let size_vint = if header.replayed { replay_size(size, open_tag.2)? } else { encode_master_size(size, open_tag.2)? };
+
+                        let header = &mut self.pending_headers[header_index];
+                        header.bytes.extend(open_tag.0.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
+                        header.bytes.extend_from_slice(&size_vint);
+                        self.pending_header_len += header.bytes.len();
+
+                        let total_size = size + header.bytes.len() as u64;
+                        if let Some(entry) = self.pending_audit.iter_mut().rev().find(|e| e.awaiting_size && e.element.id == id) {
+                            entry.element.size = Some(total_size);
+                            entry.element.header_len = header.bytes.len();
+                            entry.awaiting_size = false;
+                        }
                     }
                     Ok(())
                 } else {
@@ -142,11 +586,78 @@ impl<W: Write> TagWriter<W>
         }
     }
 
+    fn flush_completed(&mut self) -> Result<(), TagWriterError> {
+        if self.direct_depth > 0 {
+            self.resolve_offsets();
+            self.bytes_flushed += self.working_buffer.len() as u64;
+            self.metrics.observe_buffer(self.working_buffer.len());
+            self.metrics.add_bytes_written(self.working_buffer.len());
+            let output = std::mem::take(&mut self.working_buffer);
+            let result = self.write_output(&output);
+            self.working_buffer = output;
+            self.working_buffer.clear();
+            result
+        } else if !self.open_tags.iter().any(|t| matches!(t.1, Known(_))) {
+            self.private_flush()
+        } else {
+            Ok(())
+        }
+    }
+
     fn private_flush(&mut self) -> Result<(), TagWriterError> {
-        self.dest.write_all(self.working_buffer.drain(..).as_slice()).map_err(|source| TagWriterError::WriteError { source })?;
+        let total_len = self.working_buffer.len() + self.pending_header_len;
+        self.resolve_offsets();
+        self.bytes_flushed += total_len as u64;
+        self.metrics.observe_buffer(total_len);
+        self.metrics.add_bytes_written(total_len);
+        let result = if self.pending_headers.is_empty() {
+            let output = std::mem::take(&mut self.working_buffer);
+            let result = self.write_output(&output);
+            self.working_buffer = output;
+            result
+        } else {
+            // Stitch the headers of the closed "Master" tags in front of their content
+            let mut output = Vec::with_capacity(total_len);
+            let mut written = 0;
+            for header in self.pending_headers.drain(..) {
+                output.extend_from_slice(&self.working_buffer[written..header.position]);
+                output.extend_from_slice(&header.bytes);
+                written = header.position;
+            }
+            output.extend_from_slice(&self.working_buffer[written..]);
+            self.pending_header_len = 0;
+            self.write_output(&output)
+        };
+        self.working_buffer.clear();
+        result?;
         self.dest.flush().map_err(|source| TagWriterError::WriteError { source })
     }
 
+    ///
+    /// Records where bookmarks and audit log entries (or anchors) in the working buffer end up in the output, accounting for the "Master" headers stitched in front of them.
+    ///
+    fn resolve_offsets(&mut self) {
+        for entry in self.pending_audit.drain(..) {
+            let header_len: usize = self.pending_headers[..entry.preceding_headers].iter().map(|h| h.bytes.len()).sum();
+            let element = WrittenElement { offset: self.bytes_flushed + (entry.position + header_len) as u64, ..entry.element };
+            if let Some(anchors) = self.anchors.as_mut() {
+                if anchors.ids.contains(&element.id) {
+                    (anchors.callback)(&element);
+                }
+            }
+            if let Some(log) = self.audit_log.as_mut() {
+                log.push(element);
+            }
+        }
+
+        for bookmark in self.bookmarks.values_mut() {
+            if let BookmarkLocation::Buffered(position) = bookmark.location {
+                let header_len: usize = self.pending_headers.iter().take_while(|h| h.position <= position).map(|h| h.bytes.len()).sum();
+                bookmark.location = BookmarkLocation::Written(self.bytes_flushed + (position + header_len) as u64);
+            }
+        }
+    }
+
     fn write_unsigned_int_tag<const SIZE_LENGTH: usize>(&mut self, id: u64, data: &u64) -> Result<(), TagWriterError> {
         self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
         let data = *data;
@@ -238,13 +749,12 @@ impl<W: Write> TagWriter<W>
             }).map_err(|err: ToolError| TagWriterError::TagSizeError(err.to_string()))
     }
 
-    fn write_utf8_tag<const SIZE_LENGTH: usize>(&mut self, id: u64, data: &str) -> Result<(), TagWriterError> {
+    fn write_utf8_tag<const SIZE_LENGTH: usize>(&mut self, id: u64, slice: &[u8]) -> Result<(), TagWriterError> {
         self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
 
-        let slice: &[u8] = data.as_bytes();
         let size: u64 = slice.len().try_into().expect("couldn't convert usize to u64");
         if SIZE_LENGTH == 0 { 
-            let size_vint = size.as_vint().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
+            let size_vint = tools::size_as_vint(size).map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
             self.working_buffer.extend_from_slice(&size_vint);
         } else { 
             let size_vint = size.as_vint_with_length::<SIZE_LENGTH>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
@@ -260,7 +770,7 @@ impl<W: Write> TagWriter<W>
 
         let size: u64 = data.len().try_into().expect("couldn't convert usize to u64");
         if SIZE_LENGTH == 0 {
-            let size_vint = size.as_vint().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
+            let size_vint = tools::size_as_vint(size).map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
             self.working_buffer.extend_from_slice(&size_vint);
         } else {
             let size_vint = size.as_vint_with_length::<SIZE_LENGTH>().map_err(|e| TagWriterError::TagSizeError(e.to_string()))?;
@@ -316,7 +826,7 @@ impl<W: Write> TagWriter<W>
     /// ```
     ///
     pub fn write<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
-        self.write_advanced(tag, WriteOptions { size_byte_length: None, unknown_sized_element: false })
+        self.write_advanced(tag, WriteOptions::default())
     }
 
     ///
@@ -333,41 +843,121 @@ impl<W: Write> TagWriter<W>
     /// This method can panic if `<TSpec>` is an internally inconsistent specification (i.e. it claims that a specific tag variant is a specific data type but it is not).  This won't happen if the specification being used was created using the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro.
     /// 
     pub fn write_advanced<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec, options: WriteOptions) -> Result<(), TagWriterError> {
+        let normalized;
+        let tag = match (self.normalization, tag.as_master()) {
+            (Some(metadata), Some(Master::Full(_))) => {
+                let parents: Vec<u64> = self.open_tags.iter().map(|t| t.0).collect();
+                normalized = normalize(tag, &parents, metadata);
+                &normalized
+            },
+            _ => tag,
+        };
         let tag_id = tag.get_id();
         let tag_type = TSpec::get_tag_data_type(tag_id);
+        self.metrics.add_tag_written();
+        self.metrics.tick();
 
-        if options.unknown_sized_element {
+        let is_end = matches!(tag_type, Some(TagDataType::Master)) && matches!(tag.as_master(), Some(Master::End));
+        let replays_unknown_size = matches!(options.encoding, Some(encoding) if encoding.size == Unknown) && matches!(tag_type, Some(TagDataType::Master)) && matches!(tag.as_master(), Some(Master::Start));
+        let unknown_sized_element = options.unknown_sized_element || replays_unknown_size;
+        if !is_end {
+            self.check_profile(tag_id, unknown_sized_element)?;
+        }
+
+        if unknown_sized_element {
             match tag_type {
                 Some(TagDataType::Master) => {},
                 _ => {
                     return Err(TagWriterError::TagSizeError(format!("Cannot write an unknown size for tag of type {tag_type:?}")))
                 }
             };
-            self.start_unknown_size_tag(tag_id);
+            let size_length = options.encoding.map_or(8, |encoding| encoding.size_length.clamp(1, 8));
+            self.start_unknown_size_tag(tag_id, size_length);
         } else {
             let should_validate = tag_type.is_some() && (!matches!(tag_type, Some(TagDataType::Master)) || !matches!(tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)), Master::End));
             if should_validate && !validate_tag_path::<TSpec>(tag_id, self.open_tags.iter().copied()) {
                 return Err(TagWriterError::UnexpectedTag { tag_id, current_path: self.open_tags.iter().map(|t| t.0).collect() });
             }
+            self.check_restricted_value(tag, tag_id, tag_type)?;
 
-            match options.size_byte_length {
-                Some(1) => self.write_explicit_sized::<TSpec, 1>(tag, tag_id, tag_type)?,
-                Some(2) => self.write_explicit_sized::<TSpec, 2>(tag, tag_id, tag_type)?,
-                Some(3) => self.write_explicit_sized::<TSpec, 3>(tag, tag_id, tag_type)?,
-                Some(4) => self.write_explicit_sized::<TSpec, 4>(tag, tag_id, tag_type)?,
-                Some(5) => self.write_explicit_sized::<TSpec, 5>(tag, tag_id, tag_type)?,
-                Some(6) => self.write_explicit_sized::<TSpec, 6>(tag, tag_id, tag_type)?,
-                Some(7) => self.write_explicit_sized::<TSpec, 7>(tag, tag_id, tag_type)?,
-                Some(8) => self.write_explicit_sized::<TSpec, 8>(tag, tag_id, tag_type)?,
-                _ => self.write_explicit_sized::<TSpec, 0>(tag, tag_id, tag_type)?,
+            if let Some(encoding) = options.encoding {
+                return self.write_replayed(tag, tag_id, tag_type, encoding);
             }
+
+            self.write_with_size_length(tag, tag_id, tag_type, options.size_byte_length)?;
         }
 
         Ok(())
     }
 
+    fn write_with_size_length<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec, tag_id: u64, tag_type: Option<TagDataType>, size_byte_length: Option<usize>) -> Result<(), TagWriterError> {
+        match size_byte_length {
+            Some(1) => self.write_explicit_sized::<TSpec, 1>(tag, tag_id, tag_type),
+            Some(2) => self.write_explicit_sized::<TSpec, 2>(tag, tag_id, tag_type),
+            Some(3) => self.write_explicit_sized::<TSpec, 3>(tag, tag_id, tag_type),
+            Some(4) => self.write_explicit_sized::<TSpec, 4>(tag, tag_id, tag_type),
+            Some(5) => self.write_explicit_sized::<TSpec, 5>(tag, tag_id, tag_type),
+            Some(6) => self.write_explicit_sized::<TSpec, 6>(tag, tag_id, tag_type),
+            Some(7) => self.write_explicit_sized::<TSpec, 7>(tag, tag_id, tag_type),
+            Some(8) => self.write_explicit_sized::<TSpec, 8>(tag, tag_id, tag_type),
+            _ => self.write_explicit_sized::<TSpec, 0>(tag, tag_id, tag_type),
+        }
+    }
+
+    ///
+    /// Writes a tag with the size length and value width recorded in `encoding`, falling back to the usual encoding for anything that doesn't fit them.
+    ///
+    fn write_replayed<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec, tag_id: u64, tag_type: Option<TagDataType>, encoding: TagEncoding) -> Result<(), TagWriterError> {
+        let width = encoding.size.known();
+        let data = match tag_type {
+            Some(TagDataType::UnsignedInt) => {
+                let val = tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id));
+                width.and_then(|width| replay_unsigned_int(*val, width))
+            },
+            Some(TagDataType::Integer) => {
+                let val = tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id));
+                width.and_then(|width| replay_signed_int(*val, width))
+            },
+            Some(TagDataType::Float) => {
+                let val = tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id));
+                width.and_then(|width| replay_float(*val, width))
+            },
+            Some(TagDataType::Master) if matches!(tag.as_master(), Some(Master::Start)) => {
+                self.start_tag(tag_id, encoding.size_length.clamp(1, 8));
+                self.pending_headers.last_mut().expect("started tag should have a pending header").replayed = true;
+                return self.flush_completed();
+            },
+            _ => None,
+        };
+
+        let data = match data {
+            Some(data) => data,
+            None => {
+                let data_len = match tag_type {
+                    Some(TagDataType::Utf8) => tag.as_utf8_bytes().map(|val| val.len()),
+                    Some(TagDataType::Binary) if !self.transforms.handles(tag_id) => tag.as_binary().map(|val| val.len()),
+                    None => tag.as_binary().map(|val| val.len()),
+                    _ => None,
+                };
+                let size_byte_length = data_len.filter(|len| fits_size_length(*len as u64, encoding.size_length)).map(|_| encoding.size_length);
+                return self.write_with_size_length(tag, tag_id, tag_type, size_byte_length);
+            }
+        };
+
+        let position = self.working_buffer.len();
+        let preceding_headers = self.pending_headers.len();
+        self.working_buffer.extend(tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
+        self.working_buffer.extend_from_slice(&replay_size(data.len() as u64, encoding.size_length)?);
+        self.working_buffer.extend_from_slice(&data);
+        let header_len = self.buffered_header_len(tag_id, position);
+        self.audit(tag_id, position, preceding_headers, header_len, Some((self.working_buffer.len() - position) as u64), false);
+        self.flush_completed()
+    }
+
     fn write_explicit_sized<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone, const SIZE_LENGTH: usize>(&mut self, tag: &TSpec, tag_id: u64, tag_type: Option<TagDataType>) -> Result<(), TagWriterError> {
         assert!(SIZE_LENGTH < 9, "Vint length must be less than 9 bytes");
+        let position = self.working_buffer.len();
+        let preceding_headers = self.pending_headers.len();
         match tag_type {
             Some(TagDataType::UnsignedInt) => {
                 let val = tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id));
@@ -378,12 +968,18 @@ impl<W: Write> TagWriter<W>
                 self.write_signed_int_tag::<SIZE_LENGTH>(tag_id, val)?
             },
             Some(TagDataType::Utf8) => {
-                let val = tag.as_utf8().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id));
+                let val = tag.as_utf8_bytes().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id));
                 self.write_utf8_tag::<SIZE_LENGTH>(tag_id, val)?
             },
             Some(TagDataType::Binary) => {
                 let val = tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", tag_id));
-                self.write_binary_tag::<SIZE_LENGTH>(tag_id, val)?
+                match self.transforms.encode(tag_id, val) {
+                    Some(encoded) => {
+                        let encoded = encoded.map_err(|source| TagWriterError::TransformError { tag_id, source })?;
+                        self.write_binary_tag::<SIZE_LENGTH>(tag_id, &encoded)?
+                    },
+                    None => self.write_binary_tag::<SIZE_LENGTH>(tag_id, val)?,
+                }
             },
             Some(TagDataType::Float) => {
                 let val = tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id));
@@ -396,6 +992,16 @@ impl<W: Write> TagWriter<W>
                     Master::Start => self.start_tag(tag_id, SIZE_LENGTH),
                     Master::End => self.end_tag(tag_id)?,
                     Master::Full(children) => {
+                        // The whole tree is checked before anything is written, so a failed write leaves no partial output
+                        let mut path = self.open_tags.clone();
+                        path.push((tag_id, Known(0), SIZE_LENGTH));
+                        let size = self.direct_children_len::<TSpec>(&mut path, children)?;
+
+                        let can_write_directly = self.direct_depth > 0 || !self.open_tags.iter().any(|t| matches!(t.1, Known(_)));
+                        if let (true, Some(size)) = (can_write_directly, size) {
+                            return self.write_direct(tag_id, SIZE_LENGTH, size, children);
+                        }
+
                         self.start_tag(tag_id, SIZE_LENGTH);
                         for child in children {
                             self.write(child)?;
@@ -414,11 +1020,102 @@ impl<W: Write> TagWriter<W>
             }
         }
 
-        if !self.open_tags.iter().any(|t| matches!(t.1, Known(_))) {
-            self.private_flush()
-        } else {
-            Ok(())
+        if !matches!(tag_type, Some(TagDataType::Master)) {
+            let header_len = self.buffered_header_len(tag_id, position);
+            self.audit(tag_id, position, preceding_headers, header_len, Some((self.working_buffer.len() - position) as u64), false);
+        }
+        self.flush_completed()
+    }
+
+    ///
+    /// Writes a [`Master::Full`] tag whose size has already been computed by writing its header first and then streaming each child straight to the destination.
+    ///
+    fn write_direct<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag_id: u64, size_length: usize, size: u64, children: &[TSpec]) -> Result<(), TagWriterError> {
+        let size_vint = encode_master_size(size, size_length)?;
+        let position = self.working_buffer.len();
+        self.working_buffer.extend(tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
+        self.working_buffer.extend_from_slice(&size_vint);
+        let header_len = self.working_buffer.len() - position;
+        self.audit(tag_id, position, self.pending_headers.len(), header_len, Some(header_len as u64 + size), false);
+
+        // Direct tags never get a pending header, so they are popped here rather than closed through `end_tag()`
+        self.open_tags.push((tag_id, Known(0), size_length));
+        self.direct_depth += 1;
+        // The header already promised every child, so a failed destination write doesn't stop the rest of the tree from being encoded.  Their output is queued behind the unwritten bytes for a retry or replacement destination.
+        let mut result = self.flush_completed();
+        for child in children {
+            if let Err(err) = self.write(child) {
+                let write_failed = matches!(err, TagWriterError::WriteError { .. });
+                if result.is_ok() || !write_failed {
+                    result = Err(err);
+                }
+                if !write_failed {
+                    break;
+                }
+            }
         }
+        self.open_tags.pop();
+        self.direct_depth -= 1;
+        result?;
+
+        self.flush_completed()
+    }
+
+    ///
+    /// Checks every tag in `children` the same way [`Self::write()`] would, and returns their total encoded length if every tag in the tree can be sized up front.
+    ///
+    /// Trees containing [`Master::Start`] or [`Master::End`] tags or tags with a content transform can't be sized, and are written through the working buffer instead.  Checking stops at the first [`Master::Start`] or [`Master::End`] tag, since the tags after it belong to a different parent.
+    ///
+    fn direct_children_len<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self, path: &mut TagStack<(u64, ElementSize, usize)>, children: &[TSpec]) -> Result<Option<u64>, TagWriterError> {
+        let mut total = Some(0u64);
+        for child in children {
+            if matches!(child.as_master(), Some(Master::Start) | Some(Master::End)) {
+                return Ok(None);
+            }
+            let len = self.direct_tag_len(path, child)?;
+            total = total.zip(len).and_then(|(total, len)| total.checked_add(len));
+        }
+        Ok(total)
+    }
+
+    fn direct_tag_len<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&self, path: &mut TagStack<(u64, ElementSize, usize)>, tag: &TSpec) -> Result<Option<u64>, TagWriterError> {
+        let tag_id = tag.get_id();
+        let tag_type = TSpec::get_tag_data_type(tag_id);
+        if tag_type.is_some() && !validate_tag_path::<TSpec>(tag_id, path.iter().copied()) {
+            return Err(TagWriterError::UnexpectedTag { tag_id, current_path: path.iter().map(|t| t.0).collect() });
+        }
+        self.check_profile(tag_id, false)?;
+        self.check_restricted_value(tag, tag_id, tag_type)?;
+
+        let data_len = match tag_type {
+            Some(TagDataType::UnsignedInt) => tag.as_unsigned_int().map(|val| {
+                if u8::try_from(*val).is_ok() { 1 } else if u16::try_from(*val).is_ok() { 2 } else if u32::try_from(*val).is_ok() { 4 } else { 8 }
+            }),
+            Some(TagDataType::Integer) => tag.as_signed_int().map(|val| {
+                if i8::try_from(*val).is_ok() { 1 } else if i16::try_from(*val).is_ok() { 2 } else if i32::try_from(*val).is_ok() { 4 } else { 8 }
+            }),
+            Some(TagDataType::Utf8) => tag.as_utf8_bytes().map(|val| val.len() as u64),
+            Some(TagDataType::Binary) if self.transforms.handles(tag_id) => None,
+            Some(TagDataType::Binary) => tag.as_binary().map(|val| val.len() as u64),
+            Some(TagDataType::Float) => Some(8),
+            Some(TagDataType::Master) => match tag.as_master() {
+                Some(Master::Full(children)) => {
+                    path.push((tag_id, Known(0), 0));
+                    let len = self.direct_children_len(path, children);
+                    path.pop();
+                    len?
+                },
+                _ => None,
+            },
+            None if is_vint(tag_id) => tag.as_binary().map(|val| val.len() as u64),
+            None => return Err(TagWriterError::TagIdError(tag_id)),
+        };
+
+        let id_len = tag_id.to_be_bytes().iter().skip_while(|&v| *v == 0u8).count() as u64;
+        Ok(data_len.and_then(|data_len| {
+            let size_len = tools::size_as_vint(data_len).ok()?.len() as u64;
+            data_len.checked_add(id_len + size_len)
+        }))
     }
 
     ///
@@ -442,7 +1139,8 @@ impl<W: Write> TagWriter<W>
                 return Err(TagWriterError::TagSizeError(format!("Cannot write an unknown size for tag of type {tag_type:?}")))
             }
         };
-        self.start_unknown_size_tag(tag_id);
+        self.check_profile(tag_id, true)?;
+        self.start_unknown_size_tag(tag_id, 8);
         Ok(())
     }
 
@@ -470,13 +1168,167 @@ impl<W: Write> TagWriter<W>
     /// ```
     ///
     pub fn write_raw(&mut self, tag_id: u64, data: &[u8]) -> Result<(), TagWriterError> {
+        self.check_profile(tag_id, false)?;
+        let position = self.working_buffer.len();
         self.write_binary_tag::<0>(tag_id, data)?;
-        
-        if !self.open_tags.iter().any(|t| matches!(t.1, Known(_))) {
-            self.private_flush()
-        } else {
-            Ok(())
-        }        
+        let header_len = self.buffered_header_len(tag_id, position);
+        self.audit(tag_id, position, self.pending_headers.len(), header_len, Some((self.working_buffer.len() - position) as u64), false);
+        self.flush_completed()
+    }
+
+    ///
+    /// Writes an element that is already encoded, copying `header` and `payload` to the destination as they are.
+    ///
+    /// This lets passthrough tools (like remuxers) copy elements they don't change without decoding and re-encoding them, while the writer keeps track of the open "Master" tags around them as usual.  The element is validated like a tag passed to [`Self::write()`]: its id must be allowed below the open tags according to `<TSpec>`, and it must satisfy the writer's [`Profile`].  The payload is written as stored, so content transforms are not applied.
+    ///
+    /// `header` must hold exactly one element header, declaring a size equal to the length of `payload`.  A "Master" element header with an unknown size (and an empty `payload`) opens the element, which is then closed by writing its [`Master::End`] tag.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagWriterError::InvalidPreencodedHeader`] if `header` can't be read or doesn't match `payload`.  The other possible errors are the same as for [`Self::write()`].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use ebml_iterable::TagWriter;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut writer = TagWriter::new(Vec::new());
+    /// writer.write_preencoded::<EmptySpec>(&[0x42, 0x86, 0x83], &[0x01, 0x02, 0x03]).unwrap();
+    /// assert_eq!(vec![0x42, 0x86, 0x83, 0x01, 0x02, 0x03], writer.into_inner().unwrap());
+    /// ```
+    ///
+    pub fn write_preencoded<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, header: &[u8], payload: &[u8]) -> Result<(), TagWriterError> {
+        let parsed = self.check_preencoded::<TSpec>(header, payload.len())?;
+        self.working_buffer.extend_from_slice(payload);
+        if parsed.size == Unknown {
+            self.open_tags.push((parsed.id, Unknown, 0));
+        }
+        self.flush_completed()
+    }
+
+    ///
+    /// Starts an already encoded element whose `payload_len` bytes of data are passed to [`Self::write_preencoded_data()`] afterwards, so large payloads can be streamed rather than held in memory.  Nothing else may be written until all of the data has been passed.
+    ///
+    pub(crate) fn begin_preencoded<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, header: &[u8], payload_len: usize) -> Result<(), TagWriterError> {
+        match self.check_preencoded::<TSpec>(header, payload_len)?.size {
+            Known(_) => self.flush_completed(),
+            Unknown => Err(TagWriterError::InvalidPreencodedHeader { header: header.to_vec(), payload_len }),
+        }
+    }
+
+    ///
+    /// Writes part of the data of the element started by [`Self::begin_preencoded()`].
+    ///
+    pub(crate) fn write_preencoded_data(&mut self, data: &[u8]) -> Result<(), TagWriterError> {
+        self.working_buffer.extend_from_slice(data);
+        self.flush_completed()
+    }
+
+    ///
+    /// Validates an encoded element header for a payload of `payload_len` bytes, then buffers and audits the header.
+    ///
+    fn check_preencoded<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, header: &[u8], payload_len: usize) -> Result<ElementHeader, TagWriterError> {
+        let invalid = || TagWriterError::InvalidPreencodedHeader { header: header.to_vec(), payload_len };
+        let parsed = match read_element_header(&mut &header[..], 0) {
+            Ok(Some(parsed)) if parsed.header_len == header.len() => parsed,
+            _ => return Err(invalid()),
+        };
+        let tag_id = parsed.id;
+        let tag_type = TSpec::get_tag_data_type(tag_id);
+        match parsed.size {
+            Known(size) if size == payload_len => {},
+            Unknown if payload_len == 0 && matches!(tag_type, Some(TagDataType::Master)) => {},
+            _ => return Err(invalid()),
+        }
+        self.metrics.add_tag_written();
+        self.metrics.tick();
+
+        self.check_profile(tag_id, parsed.size == Unknown)?;
+        if tag_type.is_some() && !validate_tag_path::<TSpec>(tag_id, self.open_tags.iter().copied()) {
+            return Err(TagWriterError::UnexpectedTag { tag_id, current_path: self.open_tags.iter().map(|t| t.0).collect() });
+        }
+
+        let position = self.working_buffer.len();
+        self.working_buffer.extend_from_slice(header);
+        let size = parsed.total_len().map(|len| len as u64);
+        self.audit(tag_id, position, self.pending_headers.len(), header.len(), size, false);
+        Ok(parsed)
+    }
+
+    ///
+    /// Reserves `len` bytes at the current position for a tag that will be written later using [`Self::write_at_bookmark()`].
+    ///
+    /// The space is filled with a `Void` element until then, so the output is valid whether or not the bookmark is ever used.  The tag written at the bookmark is validated as a child of the tags that are open right now.  Reserving a bookmark with a name that is already in use replaces the old bookmark.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`TagWriterError::TagSizeError`] if `len` is less than 2, which is the size of the smallest `Void` element.
+    ///
+    pub fn reserve_bookmark(&mut self, name: &str, len: usize) -> Result<(), TagWriterError> {
+        if len < 2 {
+            return Err(TagWriterError::TagSizeError(format!("Cannot reserve {len} bytes for a bookmark; at least 2 are required")));
+        }
+        self.check_profile(VOID_ID, false)?;
+
+        let position = self.working_buffer.len();
+        self.working_buffer.extend_from_slice(&void_element(len));
+        let header_len = self.buffered_header_len(VOID_ID, position);
+        self.audit(VOID_ID, position, self.pending_headers.len(), header_len, Some(len as u64), false);
+        self.bookmarks.insert(name.to_string(), Bookmark {
+            location: BookmarkLocation::Buffered(position),
+            len,
+            parents: self.open_tags.iter().map(|t| t.0).collect(),
+        });
+        self.flush_completed()
+    }
+
+    ///
+    /// Writes an element that is already encoded, without validating it.  An element with an unknown size is left open (its children are expected to follow) until [`Self::end_encoded()`] is called.
+    ///
+    pub(crate) fn write_encoded(&mut self, id: u64, size: ElementSize, header: &[u8], payload: &[u8]) -> Result<(), TagWriterError> {
+        let position = self.working_buffer.len();
+        self.audit(id, position, self.pending_headers.len(), header.len(), size.known().map(|size| (header.len() + size) as u64), false);
+        self.working_buffer.extend_from_slice(header);
+        self.working_buffer.extend_from_slice(payload);
+        if size.is_unknown() {
+            self.open_tags.push((id, Unknown, 0));
+        }
+        self.flush_completed()
+    }
+
+    ///
+    /// Closes an unknown sized element started by [`Self::write_encoded()`].
+    ///
+    pub(crate) fn end_encoded(&mut self, id: u64) -> Result<(), TagWriterError> {
+        self.end_tag(id)?;
+        self.flush_completed()
+    }
+
+    ///
+    /// Returns the number of bytes that have been written to the destination.
+    ///
+    pub(crate) fn bytes_flushed(&self) -> u64 {
+        self.bytes_flushed
+    }
+
+    ///
+    /// Returns the id of the innermost open "Master" tag, if any.
+    ///
+    pub(crate) fn current_parent(&self) -> Option<u64> {
+        self.open_tags.last().map(|t| t.0)
+    }
+
+    ///
+    /// Returns the offset (relative to the first byte written by this writer) of the space reserved for the bookmark named `name`.
+    ///
+    /// Returns `None` if there is no such bookmark, or if it is inside of a "Master" tag that hasn't been written to the destination yet.
+    ///
+    pub fn bookmark_offset(&self, name: &str) -> Option<u64> {
+        match self.bookmarks.get(name)?.location {
+            BookmarkLocation::Written(offset) => Some(offset),
+            BookmarkLocation::Buffered(_) => None,
+        }
     }
 
     ///
@@ -498,6 +1350,94 @@ impl<W: Write> TagWriter<W>
     //TODO: panic on drop if there is an open tag that hasn't been written.  Or maybe flush stream of any open tags?
 }
 
+impl<W: Write + Seek> TagWriter<W>
+{
+    ///
+    /// Writes `tag` into the space reserved by [`Self::reserve_bookmark()`], e.g. to fill in a duration or an index once the rest of a document has been written.
+    ///
+    /// If the bookmark has already been written to the destination, the writer seeks back to it and then returns to the end of the output.  Any reserved space the tag doesn't use is filled with a `Void` element, so the sizes of the tags around the bookmark never change.  A bookmark can be written to more than once; each write replaces the previous one.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`TagWriterError::UnknownBookmark`] if no bookmark named `name` was reserved, and [`TagWriterError::BookmarkTooSmall`] if the encoded tag doesn't fit.  The tag itself is validated just like in [`Self::write()`].
+    ///
+    pub fn write_at_bookmark<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, name: &str, tag: &TSpec) -> Result<(), TagWriterError> {
+        let bookmark = self.bookmarks.get(name).ok_or_else(|| TagWriterError::UnknownBookmark { name: name.to_string() })?;
+
+        let mut encoder = TagWriter::new(Vec::new());
+        encoder.transforms = std::mem::take(&mut self.transforms);
+        encoder.validate_restricted_values = self.validate_restricted_values;
+        encoder.profile = self.profile;
+        encoder.normalization = self.normalization;
+        encoder.assume_open_parents(&bookmark.parents);
+        let result = encoder.write(tag);
+        self.transforms = std::mem::take(&mut encoder.transforms);
+        result?;
+        let mut encoded = encoder.into_inner()?;
+
+        let remaining = bookmark.len.checked_sub(encoded.len());
+        match remaining {
+            Some(0) => {},
+            Some(remaining) if remaining >= 2 => encoded.extend_from_slice(&void_element(remaining)),
+            _ => return Err(TagWriterError::BookmarkTooSmall { name: name.to_string(), reserved: bookmark.len, required: encoded.len() }),
+        }
+
+        match bookmark.location {
+            BookmarkLocation::Buffered(position) => {
+                self.working_buffer[position..(position + encoded.len())].copy_from_slice(&encoded);
+                Ok(())
+            },
+            BookmarkLocation::Written(offset) => {
+                self.write_output(&[])?;
+                let end = self.dest.stream_position().map_err(|source| TagWriterError::WriteError { source })?;
+                let start = end - self.bytes_flushed;
+                self.dest.seek(SeekFrom::Start(start + offset))
+                    .and_then(|_| self.dest.write_all(&encoded))
+                    .and_then(|_| self.dest.seek(SeekFrom::Start(end)))
+                    .map(|_| ())
+                    .map_err(|source| TagWriterError::WriteError { source })
+            },
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<B: bytes::BufMut> TagWriter<bytes::buf::Writer<B>>
+{
+    ///
+    /// Returns a new [`TagWriter`] instance that writes into a [`bytes::BufMut`], such as a [`bytes::BytesMut`] that will be sent over the network.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use ebml_iterable::TagWriter;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let mut writer = TagWriter::from_buf_mut(BytesMut::new());
+    /// writer.write(&EmptySpec::with_data(0x4286, &[0x01])).unwrap();
+    /// let buf = writer.into_buf_mut().unwrap().freeze();
+    /// assert_eq!(&[0x42, 0x86, 0x81, 0x01][..], &buf[..]);
+    /// ```
+    ///
+    pub fn from_buf_mut(buf: B) -> Self {
+        Self::new(bytes::BufMut::writer(buf))
+    }
+
+    ///
+    /// Consumes self and returns the underlying buffer.
+    ///
+    /// Any incomplete tags are written out before returning the buffer.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if the buffer runs out of space.
+    ///
+    pub fn into_buf_mut(self) -> Result<B, TagWriterError> {
+        self.into_inner().map(|writer| writer.into_inner())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
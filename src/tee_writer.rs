@@ -0,0 +1,125 @@
+use std::io::{self, Write};
+
+struct TeeDestination<W: Write> {
+    dest: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TeeDestination<W> {
+    fn new(dest: W) -> Self {
+        TeeDestination { dest, error: None }
+    }
+
+    fn run(&mut self, op: impl FnOnce(&mut W) -> io::Result<()>) {
+        if self.error.is_none() {
+            if let Err(err) = op(&mut self.dest) {
+                self.error = Some(err);
+            }
+        }
+    }
+}
+
+///
+/// A [`std::io::Write`] implementation that copies everything written to it into two destinations.
+///
+/// Using this as the destination of a [`TagWriter`](crate::TagWriter) encodes each tag once while writing it to both places, e.g. archiving a live stream to a file while also sending it over the network.  More destinations can be added by nesting [`TeeWriter`]s.
+///
+/// Each destination fails independently: once a write to one of them fails, its error is kept (see [`Self::first_error()`] and [`Self::second_error()`]), nothing more is written to it, and writing continues to the other.  Writes only fail once both destinations have failed.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::net::TcpStream;
+/// use ebml_iterable::TagWriter;
+/// use ebml_iterable::utils::TeeWriter;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let file = File::create("archive.ebml")?;
+/// let socket = TcpStream::connect("127.0.0.1:8080")?;
+/// let mut writer = TagWriter::new(TeeWriter::new(file, socket));
+/// writer.write(&EmptySpec::with_data(0x4286, &[0x01]))?;
+///
+/// let tee = writer.into_inner()?;
+/// if let Some(err) = tee.second_error() {
+///     println!("Streaming stopped early: {}", err);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct TeeWriter<A: Write, B: Write> {
+    first: TeeDestination<A>,
+    second: TeeDestination<B>,
+}
+
+impl<A: Write, B: Write> TeeWriter<A, B> {
+
+    ///
+    /// Returns a new [`TeeWriter`] that writes to both `first` and `second`.
+    ///
+    pub fn new(first: A, second: B) -> Self {
+        TeeWriter {
+            first: TeeDestination::new(first),
+            second: TeeDestination::new(second),
+        }
+    }
+
+    ///
+    /// Returns the error that stopped writes to the first destination, if any.
+    ///
+    pub fn first_error(&self) -> Option<&io::Error> {
+        self.first.error.as_ref()
+    }
+
+    ///
+    /// Returns the error that stopped writes to the second destination, if any.
+    ///
+    pub fn second_error(&self) -> Option<&io::Error> {
+        self.second.error.as_ref()
+    }
+
+    ///
+    /// Gets references to both destinations.
+    ///
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first.dest, &self.second.dest)
+    }
+
+    ///
+    /// Gets mutable references to both destinations.
+    ///
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.first.dest, &mut self.second.dest)
+    }
+
+    ///
+    /// Consumes self and returns both destinations.
+    ///
+    pub fn into_inner(self) -> (A, B) {
+        (self.first.dest, self.second.dest)
+    }
+
+    fn result(&self) -> io::Result<()> {
+        if self.first.error.is_some() && self.second.error.is_some() {
+            Err(io::Error::other("every destination of the tee has failed"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.first.run(|dest| dest.write_all(buf));
+        self.second.run(|dest| dest.write_all(buf));
+        self.result().map(|_| buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.first.run(|dest| dest.flush());
+        self.second.run(|dest| dest.flush());
+        self.result()
+    }
+}
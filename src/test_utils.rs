@@ -0,0 +1,586 @@
+//!
+//! Property testing helpers for specifications, built on the [`arbitrary`] crate.
+//!
+//! [`check_conformance()`] and [`assert_conformant()`] re-read a written document with strict validation enabled, so tests can check that what they wrote is readable and conforms to the spec in one call.
+//!
+//! Specifications don't expose a list of their tags, so a [`DocumentGenerator`] is created with the ids to use, or from the `SPEC_METADATA` table generated by `#[ebml_specification(metadata)]` (in which case element occurrence limits are respected too).  It generates documents that are valid for the spec (every tag is placed somewhere its path allows, and unsigned integers with restricted values use one of them), which can be fed to [`assert_round_trip()`] to check that a spec writes and reads its own tags consistently.  [`DocumentGenerator::generate_corrupted()`] produces invalid data for checking that decoding fails gracefully (see [`assert_decodes_gracefully()`]), while [`corrupt()`] damages an existing document one element at a time and labels each variant, for systematically testing how an application recovers from specific kinds of damage.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use arbitrary::Unstructured;
+//! use ebml_iterable::test_utils::{assert_round_trip, DocumentGenerator};
+//! # use ebml_iterable_specification::empty_spec::EmptySpec;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let generator: DocumentGenerator<EmptySpec> = DocumentGenerator::new(&[0x1a45dfa3, 0x4286, 0x18538067]);
+//! let seed = [0x5a; 1024];
+//! let tags = generator.generate(&mut Unstructured::new(&seed))?;
+//! assert_round_trip(&tags);
+//! # Ok(())
+//! # }
+//! ```
+//!
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+use std::ops::{Range, RangeInclusive};
+
+use arbitrary::{Result, Unstructured};
+
+use crate::spec_util::{validate_tag_path, CRC32_ID};
+use crate::tag_iterator_util::ElementSize::{Known, Unknown};
+use crate::tag_iterator_util::{read_element_header, ElementHeader};
+use crate::tag_iterator_util::{SizePastEnd, TrailingData};
+use crate::error::{CorruptedFileError, TagIteratorError};
+use crate::{EbmlDocument, TagIterator, TagWriter};
+
+use super::specs::{EbmlSpecification, EbmlTag, ElementMeta, Master, PathPart, TagDataType};
+
+///
+/// Generates random documents for `TSpec` from [`Unstructured`] data.
+///
+pub struct DocumentGenerator<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    ids: Vec<u64>,
+    max_depth: usize,
+    children: RangeInclusive<usize>,
+    data_len: RangeInclusive<usize>,
+    data_len_by_id: HashMap<u64, RangeInclusive<usize>>,
+    weights: HashMap<u64, u32>,
+
+    /// Minimum and maximum number of times an element may appear in its parent
+    occurrences: HashMap<u64, (u64, Option<u64>)>,
+    _spec: PhantomData<TSpec>,
+}
+
+impl<TSpec> DocumentGenerator<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`DocumentGenerator<TSpec>`] that builds documents from tags with the given `ids`.
+    ///
+    /// Ids that `TSpec` doesn't define a data type for are ignored.
+    ///
+    pub fn new(ids: &[u64]) -> Self {
+        DocumentGenerator {
+            ids: ids.iter().copied().filter(|id| TSpec::get_tag_data_type(*id).is_some()).collect(),
+            max_depth: 8,
+            children: 0..=8,
+            data_len: 0..=64,
+            data_len_by_id: HashMap::new(),
+            weights: HashMap::new(),
+            occurrences: HashMap::new(),
+            _spec: PhantomData,
+        }
+    }
+
+    ///
+    /// Returns a new [`DocumentGenerator<TSpec>`] that builds documents from every element in a metadata table, such as the `SPEC_METADATA` constant generated by `#[ebml_specification(metadata)]`.
+    ///
+    /// Generated documents respect the `min_occurs` and `max_occurs` of each element (other than global elements, which can appear in too many places to require them).  Mandatory elements are still left out past the maximum depth.  `CRC-32` elements are left out, since their data would have to match the rest of their parent.
+    ///
+    pub fn from_metadata(metadata: &[ElementMeta]) -> Self {
+        let ids: Vec<u64> = metadata.iter().map(|meta| meta.id).filter(|id| *id != CRC32_ID).collect();
+        let mut generator = Self::new(&ids);
+        generator.occurrences = metadata.iter()
+            .filter(|meta| meta.min_occurs.is_some() || meta.max_occurs.is_some())
+            .map(|meta| (meta.id, (meta.min_occurs.unwrap_or(0), meta.max_occurs)))
+            .collect();
+        generator
+    }
+
+    ///
+    /// Configures how deeply "Master" tags can be nested.  Defaults to 8.
+    ///
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = depth;
+    }
+
+    ///
+    /// Configures the maximum number of children generated in each "Master" tag (and at the top level).  Defaults to 8.
+    ///
+    pub fn set_max_children(&mut self, count: usize) {
+        self.children = 0..=count;
+    }
+
+    ///
+    /// Configures the range the number of children generated in each "Master" tag (and at the top level) is picked from.  Mandatory elements are added on top of these, and fewer children are generated if occurrence limits don't allow more.
+    ///
+    pub fn set_children_range(&mut self, range: RangeInclusive<usize>) {
+        self.children = range;
+    }
+
+    ///
+    /// Configures the maximum length of generated utf-8 and binary data.  Defaults to 64.
+    ///
+    pub fn set_max_data_len(&mut self, len: usize) {
+        self.data_len = 0..=len;
+    }
+
+    ///
+    /// Configures the range the length of generated utf-8 and binary data is picked from.
+    ///
+    pub fn set_data_len_range(&mut self, range: RangeInclusive<usize>) {
+        self.data_len = range;
+    }
+
+    ///
+    /// Configures the range the data length of the element with `id` is picked from, overriding [`Self::set_data_len_range()`].  This is useful for elements that are much larger than the rest in real documents, like media blocks.
+    ///
+    pub fn set_data_len_range_for(&mut self, id: u64, range: RangeInclusive<usize>) {
+        self.data_len_by_id.insert(id, range);
+    }
+
+    ///
+    /// Configures how likely the element with `id` is to be picked relative to the other elements allowed in the same place.  Every element has a weight of 1 by default, and a weight of 0 only generates the element if it is mandatory.
+    ///
+    /// Giving "Master" elements higher weights produces deeper documents, and giving leaf elements higher weights produces flatter ones.
+    ///
+    pub fn set_weight(&mut self, id: u64, weight: u32) {
+        self.weights.insert(id, weight);
+    }
+
+    ///
+    /// Generates a list of top level tags, with "Master" tags as [`Master::Full`] variants.
+    ///
+    pub fn generate(&self, u: &mut Unstructured) -> Result<Vec<TSpec>> {
+        self.generate_children(u, &mut Vec::new())
+    }
+
+    ///
+    /// Generates an encoded document.
+    ///
+    pub fn generate_bytes(&self, u: &mut Unstructured) -> Result<Vec<u8>> {
+        let tags = self.generate(u)?;
+        Ok(encode(&tags))
+    }
+
+    ///
+    /// Generates an encoded document and then damages it by flipping bits, inserting or removing bytes, or truncating it.
+    ///
+    /// The result may still happen to be valid, but usually won't be.
+    ///
+    pub fn generate_corrupted(&self, u: &mut Unstructured) -> Result<Vec<u8>> {
+        let mut data = self.generate_bytes(u)?;
+        let corruptions = u.int_in_range(1..=4)?;
+        for _ in 0..corruptions {
+            if data.is_empty() {
+                let len = u.int_in_range(1..=16)?;
+                data.extend(u.bytes(len)?);
+                continue;
+            }
+            let position = u.choose_index(data.len())?;
+            match u.int_in_range(0..=3)? {
+                0 => data[position] ^= 1 << u.int_in_range(0..=7)?,
+                1 => {
+                    let len = u.int_in_range(1..=16)?;
+                    let garbage = u.bytes(len)?.to_vec();
+                    data.splice(position..position, garbage);
+                },
+                2 => {
+                    let end = (position + u.int_in_range(1..=16)?).min(data.len());
+                    data.drain(position..end);
+                },
+                _ => data.truncate(position),
+            }
+        }
+        Ok(data)
+    }
+
+    fn generate_children(&self, u: &mut Unstructured, parents: &mut Vec<u64>) -> Result<Vec<TSpec>> {
+        let candidates: Vec<u64> = self.ids.iter().copied()
+            .filter(|id| parents.len() < self.max_depth || !matches!(TSpec::get_tag_data_type(*id), Some(TagDataType::Master)))
+            .filter(|id| validate_tag_path::<TSpec>(*id, parents.iter().map(|p| (*p, Known(0), 0))))
+            .collect();
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut counts: HashMap<u64, u64> = HashMap::new();
+        let mut ids: Vec<u64> = Vec::new();
+        for id in candidates.iter().copied().filter(|id| !is_global::<TSpec>(*id)) {
+            let min = self.occurrences.get(&id).map(|(min, _)| *min).unwrap_or(0);
+            ids.extend(std::iter::repeat_n(id, min as usize));
+            counts.insert(id, min);
+        }
+
+        let count = u.int_in_range(self.children.clone())?;
+        for _ in 0..count {
+            let allowed: Vec<(u64, u32)> = candidates.iter().copied()
+                .filter(|id| !matches!(self.occurrences.get(id), Some((_, Some(max))) if counts.get(id).copied().unwrap_or(0) >= *max))
+                .map(|id| (id, self.weights.get(&id).copied().unwrap_or(1)))
+                .filter(|(_, weight)| *weight > 0)
+                .collect();
+            let total: u64 = allowed.iter().map(|(_, weight)| *weight as u64).sum();
+            if total == 0 {
+                break;
+            }
+            let mut pick = u.int_in_range(0..=(total - 1))?;
+            let id = allowed.iter().find(|(_, weight)| {
+                let found = pick < *weight as u64;
+                pick = pick.saturating_sub(*weight as u64);
+                found
+            }).expect("pick should be within the total weight").0;
+            *counts.entry(id).or_default() += 1;
+            ids.push(id);
+        }
+
+        // Mandatory elements were added first, so shuffle them in with the rest
+        for i in (1..ids.len()).rev() {
+            let j = u.int_in_range(0..=i)?;
+            ids.swap(i, j);
+        }
+
+        ids.into_iter().map(|id| self.generate_tag(u, id, parents)).collect()
+    }
+
+    fn data_len(&self, u: &mut Unstructured, id: u64) -> Result<usize> {
+        u.int_in_range(self.data_len_by_id.get(&id).unwrap_or(&self.data_len).clone())
+    }
+
+    fn generate_tag(&self, u: &mut Unstructured, id: u64, parents: &mut Vec<u64>) -> Result<TSpec> {
+        let data_type = TSpec::get_tag_data_type(id).expect("ids should be filtered to those with a data type");
+        let tag = match data_type {
+            TagDataType::Master => {
+                parents.push(id);
+                let children = self.generate_children(u, parents);
+                parents.pop();
+                TSpec::get_master_tag(id, Master::Full(children?))
+            },
+            TagDataType::UnsignedInt => {
+                let value = match TSpec::get_restricted_values(id) {
+                    Some(allowed) if !allowed.is_empty() => *u.choose(allowed)?,
+                    _ => u.arbitrary()?,
+                };
+                TSpec::get_unsigned_int_tag(id, value)
+            },
+            TagDataType::Integer => TSpec::get_signed_int_tag(id, u.arbitrary()?),
+            TagDataType::Float => {
+                let value: f64 = u.arbitrary()?;
+                // NaN never compares equal, which would make round trip checks fail
+                TSpec::get_float_tag(id, if value.is_nan() { 0.0 } else { value })
+            },
+            TagDataType::Utf8 => {
+                let len = self.data_len(u, id)?;
+                let value: String = (0..len).map(|_| u.arbitrary::<char>()).collect::<Result<_>>()?;
+                TSpec::get_utf8_tag(id, value)
+            },
+            TagDataType::Binary => {
+                let len = self.data_len(u, id)?;
+                TSpec::get_binary_tag(id, u.bytes(len)?)
+            },
+        };
+        Ok(tag.unwrap_or_else(|| panic!("Bad specification implementation: Tag id 0x{:x?} has type {:?}, but could not get tag!", id, data_type)))
+    }
+}
+
+fn is_global<TSpec>(id: u64) -> bool
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    TSpec::get_path_by_id(id).iter().any(|part| matches!(part, PathPart::Global(_)))
+}
+
+fn encode<TSpec>(tags: &[TSpec]) -> Vec<u8>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut writer = TagWriter::new(Vec::new());
+    for tag in tags {
+        writer.write(tag).unwrap_or_else(|err| panic!("Failed to write {:?}: {:?}", tag.get_id(), err));
+    }
+    writer.into_inner().unwrap_or_else(|err| panic!("Failed to write document: {:?}", err))
+}
+
+///
+/// Asserts that writing `tags` and reading them back produces the same tags.
+///
+/// "Master" tags in `tags` must be [`Master::Full`] variants.
+///
+/// # Panics
+///
+/// Panics if the tags can't be written or read, or if the tags that are read differ from `tags`.
+///
+pub fn assert_round_trip<TSpec>(tags: &[TSpec])
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + PartialEq + Debug
+{
+    let data = encode(tags);
+    let decoded = EbmlDocument::<TSpec>::read(&data[..])
+        .unwrap_or_else(|err| panic!("Failed to read written document: {:?}", err))
+        .to_tags();
+    assert_eq!(tags, &decoded[..], "tags read back differ from tags written");
+}
+
+///
+/// Asserts that decoding `data` terminates without panicking, and that if it decodes successfully the resulting tags survive a round trip through [`assert_round_trip()`].
+///
+/// This is intended for use with [`DocumentGenerator::generate_corrupted()`] or fuzzer input.
+///
+pub fn assert_decodes_gracefully<TSpec>(data: &[u8])
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone + PartialEq + Debug
+{
+    // No element can be larger than the data itself, which keeps corrupted sizes from causing huge allocations
+    let mut iter: TagIterator<&[u8], TSpec> = TagIterator::new(data, &[]);
+    iter.set_max_allowable_tag_size(Some(data.len()));
+    if let Ok(document) = EbmlDocument::from_tag_iterator(iter) {
+        assert_round_trip(&document.to_tags());
+    }
+}
+
+///
+/// The result of re-reading a document with [`check_conformance()`].
+///
+#[derive(Debug, Default)]
+pub struct ConformanceReport {
+
+    ///
+    /// The number of tags read successfully (counting [`Master::Start`] and [`Master::End`] separately).
+    ///
+    pub tags_read: usize,
+
+    ///
+    /// Every error found, in document order.
+    ///
+    pub issues: Vec<TagIteratorError>,
+
+    ///
+    /// The ranges of data that had to be skipped to continue reading after an issue.
+    ///
+    pub corrupt_ranges: Vec<Range<usize>>,
+}
+
+impl ConformanceReport {
+
+    ///
+    /// Returns whether the document was read without any issues.
+    ///
+    pub fn is_conformant(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Display for ConformanceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "read {} tags with {} issues", self.tags_read, self.issues.len())?;
+        for issue in self.issues.iter() {
+            write!(f, "\n  - {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Reads `data` back with strict validation and reports every problem found.
+///
+/// Besides the checks the iterator always makes (ids, hierarchy, and sizes), values are checked against the restricted values of `TSpec`, elements can't extend past the end of `data`, and nothing may follow the last top-level element.  After an issue the reader skips ahead to the next valid tag, so one problem doesn't hide the rest; issues that mean the document ends early stop the check.
+///
+pub fn check_conformance<TSpec>(data: &[u8]) -> ConformanceReport
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut iter: TagIterator<&[u8], TSpec> = TagIterator::new(data, &[]);
+    iter.validate_restricted_values(true);
+    iter.set_trailing_data(TrailingData::Error);
+    iter.set_size_past_end(SizePastEnd::Error);
+    iter.set_source_len(Some(data.len()));
+    iter.set_max_allowable_tag_size(Some(data.len()));
+
+    let mut report = ConformanceReport::default();
+    while let Some(tag) = iter.next() {
+        let err = match tag {
+            Ok(_) => {
+                report.tags_read += 1;
+                continue;
+            },
+            Err(err) => err,
+        };
+
+        // Errors about a value are returned after the tag is read, but the reader is stuck on a bad header until it recovers
+        let needs_recovery = matches!(&err, TagIteratorError::CorruptedFileData(corruption) if !matches!(corruption, CorruptedFileError::RestrictedValue { .. } | CorruptedFileError::ProfileViolation { .. } | CorruptedFileError::ZeroLengthValue { .. } | CorruptedFileError::TrailingData { .. }));
+        let is_fatal = matches!(&err, TagIteratorError::UnexpectedEOF(_) | TagIteratorError::ReadError { .. });
+        report.issues.push(err);
+        if is_fatal || (needs_recovery && iter.try_recover().is_err()) {
+            break;
+        }
+    }
+    report.corrupt_ranges = iter.corrupt_ranges().to_vec();
+    report
+}
+
+///
+/// Asserts that `data` passes [`check_conformance()`] without any issues.
+///
+/// # Panics
+///
+/// Panics with the [`ConformanceReport`] if any issues are found.
+///
+pub fn assert_conformant<TSpec>(data: &[u8])
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let report = check_conformance::<TSpec>(data);
+    assert!(report.is_conformant(), "document doesn't conform to the spec: {}", report);
+}
+
+///
+/// The kind of damage [`corrupt()`] applies to each element.
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CorruptionStrategy {
+
+    ///
+    /// Flips the lowest bit of the element's first data byte (or of its last header byte if it has no data).
+    ///
+    BitFlip,
+
+    ///
+    /// Cuts the document off halfway through the element's data (or inside its header if it has no data).
+    ///
+    Truncate,
+
+    ///
+    /// Rewrites the element's size to the largest known size that fits in the same number of bytes.  Elements with an unknown size are left alone.
+    ///
+    InflateSize,
+
+    ///
+    /// Replaces the element's id with an id of the same length that the specification doesn't define.
+    ///
+    MangleId,
+}
+
+impl Display for CorruptionStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorruptionStrategy::BitFlip => write!(f, "bit flip"),
+            CorruptionStrategy::Truncate => write!(f, "truncation"),
+            CorruptionStrategy::InflateSize => write!(f, "inflated size"),
+            CorruptionStrategy::MangleId => write!(f, "mangled id"),
+        }
+    }
+}
+
+///
+/// A copy of a document with a single element damaged, produced by [`corrupt()`].
+///
+/// The [`Display`] implementation describes the damage, for labeling test cases.
+///
+#[derive(Clone, Debug)]
+pub struct CorruptedVariant {
+
+    ///
+    /// The kind of damage applied.
+    ///
+    pub strategy: CorruptionStrategy,
+
+    ///
+    /// The id of the damaged element (before any id mangling).
+    ///
+    pub tag_id: u64,
+
+    ///
+    /// The position of the damaged element's header in the document.
+    ///
+    pub position: usize,
+
+    ///
+    /// The damaged document.
+    ///
+    pub data: Vec<u8>,
+}
+
+impl Display for CorruptedVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} in element 0x{:x} at {}", self.strategy, self.tag_id, self.position)
+    }
+}
+
+///
+/// Produces one damaged copy of `document` for each element in it (including children of master elements), applying `strategy` to that element only.
+///
+/// Damage is deterministic, so the same document always produces the same variants.  Elements are located by walking the headers of `document`, which stops at the first header that can't be read; strategies that don't apply to an element (such as [`CorruptionStrategy::InflateSize`] on an unknown-size element) produce no variant for it.
+///
+/// ## Example
+///
+/// ```no_run
+/// use ebml_iterable::test_utils::{corrupt, CorruptionStrategy};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+/// # let document: Vec<u8> = Vec::new();
+///
+/// for variant in corrupt::<EmptySpec>(&document, CorruptionStrategy::MangleId) {
+///     // Check that the application copes with `variant.data`, using `variant` as the test case label
+///     println!("{}", variant);
+/// }
+/// ```
+///
+pub fn corrupt<TSpec>(document: &[u8], strategy: CorruptionStrategy) -> Vec<CorruptedVariant>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    element_headers::<TSpec>(document).into_iter().filter_map(|(position, header)| {
+        let data_start = position + header.header_len;
+        let mut data = document.to_vec();
+        match strategy {
+            CorruptionStrategy::BitFlip => {
+                let target = if matches!(header.size, Known(0)) || data_start >= data.len() { data_start - 1 } else { data_start };
+                data[target] ^= 1;
+            },
+            CorruptionStrategy::Truncate => {
+                let cut = match header.size {
+                    Known(size) if size > 0 => data_start + size / 2,
+                    _ => data_start - 1,
+                };
+                data.truncate(cut);
+            },
+            CorruptionStrategy::InflateSize => {
+                if header.size == Unknown {
+                    return None;
+                }
+                let size_len = header.header_len - header.id_len();
+                let inflated = ElementHeader { id: header.id, size: Known((1 << (7 * size_len)) - 2), header_len: header.header_len };
+                data.splice(position..data_start, inflated.encode());
+            },
+            CorruptionStrategy::MangleId => {
+                let id_len = header.id_len();
+                // Counting down from the largest id of the same length, skipping the reserved all-ones value
+                let marker = 1u64 << (7 * id_len);
+                let id = (1..marker - 1).rev().take(1024)
+                    .map(|value| marker | value)
+                    .find(|id| TSpec::get_tag_data_type(*id).is_none())?;
+                data.splice(position..(position + id_len), id.to_be_bytes()[(8 - id_len)..].iter().copied());
+            },
+        }
+        Some(CorruptedVariant { strategy, tag_id: header.id, position, data })
+    }).collect()
+}
+
+fn element_headers<TSpec>(document: &[u8]) -> Vec<(usize, ElementHeader)>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    let mut headers = Vec::new();
+    let mut position = 0;
+    while position < document.len() {
+        let header = match read_element_header(&mut &document[position..], position) {
+            Ok(Some(header)) => header,
+            _ => break,
+        };
+        headers.push((position, header));
+
+        // Children of masters follow their header directly, so stepping into masters visits every element
+        position = match (TSpec::get_tag_data_type(header.id), header.size) {
+            (Some(TagDataType::Master), _) => position + header.header_len,
+            (_, Known(size)) => (position + header.header_len).saturating_add(size),
+            (_, Unknown) => break,
+        };
+    }
+    headers
+}
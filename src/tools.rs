@@ -61,6 +61,26 @@ impl Vint for u32 { }
 impl Vint for u16 { }
 impl Vint for u8 { }
 
+///
+/// Encodes an element data size as a vint.
+///
+/// Unlike [`Vint::as_vint()`], this never returns a vint with every value bit set, since those values are reserved for elements with an unknown size.  Sizes that would encode that way use a vint one byte longer instead.
+///
+pub(crate) fn size_as_vint(size: u64) -> Result<Vec<u8>, ToolError> {
+    let vint = size.as_vint()?;
+    let length = vint.len();
+    if size != (1 << (7 * length)) - 1 {
+        return Ok(vint);
+    }
+    if length == 8 {
+        return Err(ToolError::WriteVintOverflow(size));
+    }
+
+    let mut bytes = size.to_be_bytes()[(7 - length)..].to_vec();
+    bytes[0] |= 1 << (7 - length);
+    Ok(bytes)
+}
+
 #[inline]
 fn check_size_u64(val: u64, max_length: usize) -> Result<(), ToolError> {
     if val >= 1 << (max_length * 7) {
@@ -89,6 +109,11 @@ fn as_vint_no_check_u64<const LENGTH: usize>(val: u64) -> [u8; LENGTH] {
 /// This method can return a `ToolError` if the input array cannot be read as a vint.
 /// 
 pub fn read_vint(buffer: &[u8]) -> Result<Option<(u64, usize)>, ToolError> {
+    if let Some(word) = load_vint_word(buffer) {
+        let (raw, length) = split_vint_word(word).ok_or(ToolError::ReadVintOverflow)?;
+        return Ok(Some((raw ^ (1 << (7 * length)), length)));
+    }
+
     if buffer.is_empty() {
         return Ok(None);
     }
@@ -115,12 +140,34 @@ pub fn read_vint(buffer: &[u8]) -> Result<Option<(u64, usize)>, ToolError> {
     Ok(Some((value, length)))
 }
 
+///
+/// Loads the first 8 bytes of `buffer` as a big-endian word, if there are that many.
+///
+#[inline(always)]
+pub(crate) fn load_vint_word(buffer: &[u8]) -> Option<u64> {
+    buffer.get(..8).map(|bytes| u64::from_be_bytes(bytes.try_into().expect("slice should be 8 bytes long")))
+}
+
+///
+/// Decodes the vint at the start of a big-endian word using the leading zero count rather than walking it byte by byte.
+///
+/// Returns the vint bytes (including the length marker bit) and the vint length, or `None` if the first byte is 0.
+///
+#[inline(always)]
+pub(crate) fn split_vint_word(word: u64) -> Option<(u64, usize)> {
+    if word >> 56 == 0 {
+        return None;
+    }
+    let length = word.leading_zeros() as usize + 1;
+    Some((word >> (64 - 8 * length), length))
+}
+
 pub fn is_vint(val: u64) -> bool {
     if val == 0 {
         return false;
     }
 
-    (val.ilog2() % 7) == 0
+    val.ilog2().is_multiple_of(7)
 }
 
 ///
@@ -254,7 +301,7 @@ pub fn read_signed_vint(buffer: &[u8]) -> Result<Option<(i64, usize)>, ToolError
 ///
 /// Reads a `u64` value from any length array slice.
 /// 
-/// Rather than forcing the input to be a `[u8; 8]` like standard library methods, this can interpret a `u64` from a slice of any length < 8.  Bytes are assumed to be least significant when reading the value - i.e. an array of `[4, 0]` would return a value of `1024`.  
+/// Rather than forcing the input to be a `[u8; 8]` like standard library methods, this can interpret a `u64` from a slice of any length < 8.  Bytes are assumed to be least significant when reading the value - i.e. an array of `[4, 0]` would return a value of `1024`.  An empty slice is read as `0`.
 ///
 /// # Errors
 ///
@@ -287,7 +334,7 @@ pub fn arr_to_u64(arr: &[u8]) -> Result<u64, ToolError> {
 ///
 /// Reads an `i64` value from any length array slice.
 /// 
-/// Rather than forcing the input to be a `[u8; 8]` like standard library methods, this can interpret an `i64` from a slice of any length < 8.  Bytes are assumed to be least significant when reading the value - i.e. an array of `[4, 0]` would return a value of `1024`.  
+/// Rather than forcing the input to be a `[u8; 8]` like standard library methods, this can interpret an `i64` from a slice of any length < 8.  Bytes are assumed to be least significant when reading the value - i.e. an array of `[4, 0]` would return a value of `1024`.  An empty slice is read as `0`.
 ///
 /// # Errors
 ///
@@ -309,7 +356,7 @@ pub fn arr_to_i64(arr: &[u8]) -> Result<i64, ToolError> {
         return Err(ToolError::ReadI64Overflow(Vec::from(arr)));
     }
 
-    if arr[0] > 127 {
+    if arr.first().is_some_and(|b| *b > 127) {
         if arr.len() == 8 {
             Ok(i64::from_be_bytes(arr.try_into().expect("[u8;8] should be convertible to i64")))
         } else {
@@ -321,16 +368,18 @@ pub fn arr_to_i64(arr: &[u8]) -> Result<i64, ToolError> {
 }
 
 ///
-/// Reads an `f64` value from an array slice of length 4 or 8.
+/// Reads an `f64` value from an array slice of length 0, 4 or 8.
 /// 
-/// This method wraps `f32` and `f64` conversions from big endian byte arrays and casts the result as an `f64`.  
+/// This method wraps `f32` and `f64` conversions from big endian byte arrays and casts the result as an `f64`.  An empty slice is read as `0.0`, matching how EBML treats zero-length float elements.
 ///
 /// # Errors
 ///
-/// This method will throw an error if the input slice length is not 4 or 8.
+/// This method will throw an error if the input slice length is not 0, 4 or 8.
 /// 
 pub fn arr_to_f64(arr: &[u8]) -> Result<f64, ToolError> {
-    if arr.len() == 4 {
+    if arr.is_empty() {
+        Ok(0.0)
+    } else if arr.len() == 4 {
         Ok(f32::from_be_bytes(arr.try_into().expect("arr should be [u8;4]")) as f64)
     } else if arr.len() == 8 {
         Ok(f64::from_be_bytes(arr.try_into().expect("arr should be [u8;8]")))
@@ -339,6 +388,87 @@ pub fn arr_to_f64(arr: &[u8]) -> Result<f64, ToolError> {
     }
 }
 
+const CRC32_TABLE: [u32; 256] = build_crc32_table();
+
+const fn build_crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 { 0xEDB88320 ^ (value >> 1) } else { value >> 1 };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+///
+/// Incrementally computes the CRC-32 checksum used by EBML `CRC-32` elements.
+///
+/// This is the common IEEE 802.3 variant of CRC-32.  Note that EBML stores the checksum in little endian byte order, unlike every other number in the format.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::tools::Crc32Hasher;
+///
+/// let mut hasher = Crc32Hasher::new();
+/// hasher.update(b"1234");
+/// hasher.update(b"56789");
+/// assert_eq!(0xCBF43926, hasher.finish());
+/// ```
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32Hasher {
+    value: u32,
+}
+
+impl Crc32Hasher {
+    ///
+    /// Returns a new hasher with no data.
+    ///
+    pub fn new() -> Self {
+        Crc32Hasher { value: 0xFFFFFFFF }
+    }
+
+    ///
+    /// Adds `data` to the checksum.
+    ///
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.value = CRC32_TABLE[((self.value ^ *byte as u32) & 0xff) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    ///
+    /// Returns the checksum of all data added so far.
+    ///
+    pub fn finish(&self) -> u32 {
+        self.value ^ 0xFFFFFFFF
+    }
+}
+
+impl Default for Crc32Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///
+/// Computes the CRC-32 checksum of `data`, as stored in EBML `CRC-32` elements.
+///
+/// See [`Crc32Hasher`] to compute a checksum over data in chunks.
+///
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut hasher = Crc32Hasher::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,6 +503,14 @@ mod tests {
         assert_eq!(vec![255u8], result);
     }
 
+    #[test]
+    fn size_vint_avoids_unknown_size() {
+        assert_eq!(vec![0x40, 0x7f], size_as_vint(127).unwrap());
+        assert_eq!(vec![0x20, 0x3f, 0xff], size_as_vint((1 << 14) - 1).unwrap());
+        assert_eq!(vec![0xfe], size_as_vint(126).unwrap());
+        assert!(size_as_vint((1 << 56) - 1).is_err());
+    }
+
     #[test]
     fn read_vint_two_hundred() {
         let buffer = [64, 200];
@@ -412,6 +550,17 @@ mod tests {
         assert_eq!(vec![1, 0, 0, 0, 0, 0, 0, 1], result);
     }
 
+    #[test]
+    fn read_vint_with_trailing_data() {
+        for val in (0..500_000).chain([(1 << 56) - 2]) {
+            let mut bytes = val.as_vint().unwrap();
+            let length = bytes.len();
+            bytes.extend_from_slice(&[0xff; 8]);
+            assert_eq!(Some((val, length)), read_vint(&bytes).unwrap());
+        }
+        assert!(read_vint(&[0, 0x81, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+
     #[test]
     fn read_vint_overflow() {
         let buffer = [1, 0, 0, 0];
@@ -517,4 +666,18 @@ mod tests {
         assert!(!is_vint(0xfa4c));
         assert!(!is_vint(0x1a5d));
     }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(0xCBF43926, crc32(b"123456789"));
+        assert_eq!(0, crc32(&[]));
+    }
+
+    #[test]
+    fn empty_arrays_read_as_zero() {
+        assert_eq!(0, arr_to_u64(&[]).unwrap());
+        assert_eq!(0, arr_to_i64(&[]).unwrap());
+        assert_eq!(0.0, arr_to_f64(&[]).unwrap());
+        assert!(arr_to_f64(&[0, 0]).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+///
+/// A reversible transformation of binary tag data, such as compression or encryption.
+///
+/// Transforms are registered for specific tag ids on a [`TagIterator`][`crate::TagIterator`] (using [`TagIterator::add_content_transform()`][`crate::TagIterator::add_content_transform`]) and/or a [`TagWriter`][`crate::TagWriter`] (using [`TagWriter::add_content_transform()`][`crate::TagWriter::add_content_transform`]).  The iterator calls [`Self::decode()`] on the data of matching binary tags before emitting them, and the writer calls [`Self::encode()`] on the data of matching binary tags before writing them.  Registering different transforms on each side converts between encodings in a single pass.
+///
+/// ## Example
+///
+/// ```
+/// use std::error::Error;
+/// use ebml_iterable::ContentTransform;
+///
+/// // Not a real encryption scheme!
+/// struct Xor(u8);
+///
+/// impl ContentTransform for Xor {
+///     fn decode(&mut self, _tag_id: u64, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+///         Ok(data.iter().map(|b| b ^ self.0).collect())
+///     }
+///
+///     fn encode(&mut self, tag_id: u64, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+///         self.decode(tag_id, data)
+///     }
+/// }
+/// ```
+///
+pub trait ContentTransform: Send {
+
+    ///
+    /// Converts data as stored in a document into the data that should be emitted from the reader.
+    ///
+    fn decode(&mut self, tag_id: u64, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+
+    ///
+    /// Converts data into the form that should be stored in a document.  This should be the inverse of [`Self::decode()`].
+    ///
+    fn encode(&mut self, tag_id: u64, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+}
+
+///
+/// Matroska's "header stripping" compression, where a fixed prefix is removed from the stored data of every frame.
+///
+/// Decoding prepends the header to the data, and encoding removes it.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderStripping {
+    header: Vec<u8>,
+}
+
+impl HeaderStripping {
+
+    ///
+    /// Returns a new [`HeaderStripping`] transform for the stripped `header` bytes (the `ContentCompSettings` value in Matroska).
+    ///
+    pub fn new(header: &[u8]) -> Self {
+        HeaderStripping { header: header.to_vec() }
+    }
+}
+
+impl ContentTransform for HeaderStripping {
+    fn decode(&mut self, _tag_id: u64, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut decoded = Vec::with_capacity(self.header.len() + data.len());
+        decoded.extend_from_slice(&self.header);
+        decoded.extend_from_slice(data);
+        Ok(decoded)
+    }
+
+    fn encode(&mut self, tag_id: u64, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        match data.strip_prefix(&self.header[..]) {
+            Some(stripped) => Ok(stripped.to_vec()),
+            None => Err(format!("Data for tag 0x{tag_id:x} does not start with the stripped header").into()),
+        }
+    }
+}
+
+///
+/// The transforms registered on a reader or writer, keyed by tag id.
+///
+#[derive(Default)]
+pub(crate) struct ContentTransforms {
+    transforms: Vec<Box<dyn ContentTransform>>,
+    by_id: HashMap<u64, usize>,
+}
+
+impl ContentTransforms {
+    pub fn add(&mut self, ids: &[u64], transform: Box<dyn ContentTransform>) {
+        let index = self.transforms.len();
+        self.transforms.push(transform);
+        self.by_id.extend(ids.iter().map(|id| (*id, index)));
+    }
+
+    pub fn handles(&self, tag_id: u64) -> bool {
+        self.by_id.contains_key(&tag_id)
+    }
+
+    pub fn decode(&mut self, tag_id: u64, data: &[u8]) -> Option<Result<Vec<u8>, Box<dyn Error + Send + Sync>>> {
+        let index = *self.by_id.get(&tag_id)?;
+        Some(self.transforms[index].decode(tag_id, data))
+    }
+
+    pub fn encode(&mut self, tag_id: u64, data: &[u8]) -> Option<Result<Vec<u8>, Box<dyn Error + Send + Sync>>> {
+        let index = *self.by_id.get(&tag_id)?;
+        Some(self.transforms[index].encode(tag_id, data))
+    }
+}
@@ -0,0 +1,213 @@
+use std::io::Read;
+
+use super::tag_iterator::TagIterator;
+use super::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use super::errors::typed_reader::TypedReadError;
+
+///
+/// A wrapper around a [`TagIterator`] that reads tags in a fixed order, checking the id and data type of each one and returning its value.
+///
+/// This is returned by [`TagIterator::typed()`].  Formats built on EBML often store their elements in a strict order, and reading them this way avoids matching on specification variants for every field.  Each method consumes the next tag and fails if it isn't what was expected; [`Self::peek_id()`] can be used to check for optional elements first.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagIterator;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let data = [0x42, 0x86, 0x81, 0x01, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'];
+/// let iter: TagIterator<_, EmptySpec> = TagIterator::new(&data[..], &[]);
+/// let mut reader = iter.typed();
+///
+/// assert_eq!(vec![0x01], reader.expect_binary(0x4286).unwrap());
+/// assert_eq!(Some(0x4282), reader.peek_id().unwrap());
+/// assert_eq!(b"webm".to_vec(), reader.next_binary().unwrap());
+/// assert_eq!(None, reader.peek_id().unwrap());
+/// ```
+///
+pub struct TypedReader<R: Read, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    iterator: TagIterator<R, TSpec>,
+    peeked: Option<TSpec>,
+}
+
+impl<R: Read, TSpec> TypedReader<R, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    pub(crate) fn new(iterator: TagIterator<R, TSpec>) -> Self {
+        TypedReader {
+            iterator,
+            peeked: None,
+        }
+    }
+
+    ///
+    /// Consumes self and returns the underlying [`TagIterator`].
+    ///
+    /// A tag that was peeked with [`Self::peek_id()`] but not read is lost.
+    ///
+    pub fn into_inner(self) -> TagIterator<R, TSpec> {
+        self.iterator
+    }
+
+    ///
+    /// Returns the id of the next tag without consuming it, or `None` if the source has ended.
+    ///
+    pub fn peek_id(&mut self) -> Result<Option<u64>, TypedReadError> {
+        if self.peeked.is_none() {
+            self.peeked = self.iterator.next().transpose()?;
+        }
+        Ok(self.peeked.as_ref().map(|tag| tag.get_id()))
+    }
+
+    ///
+    /// Returns the next tag, whatever it is.
+    ///
+    pub fn next_tag(&mut self) -> Result<TSpec, TypedReadError> {
+        self.next_with_id(None)
+    }
+
+    ///
+    /// Reads the [`Master::Start`] of the "Master" tag `id`.
+    ///
+    pub fn expect_start(&mut self, id: u64) -> Result<(), TypedReadError> {
+        match self.next_master(id)? {
+            Master::Start => Ok(()),
+            _ => Err(TypedReadError::UnexpectedMasterVariant { id }),
+        }
+    }
+
+    ///
+    /// Reads the [`Master::End`] of the "Master" tag `id`.
+    ///
+    pub fn expect_end(&mut self, id: u64) -> Result<(), TypedReadError> {
+        match self.next_master(id)? {
+            Master::End => Ok(()),
+            _ => Err(TypedReadError::UnexpectedMasterVariant { id }),
+        }
+    }
+
+    ///
+    /// Reads the "Master" tag `id` as a [`Master::Full`] (which requires the iterator to buffer it), returning its children.
+    ///
+    pub fn expect_full(&mut self, id: u64) -> Result<Vec<TSpec>, TypedReadError> {
+        match self.next_master(id)? {
+            Master::Full(children) => Ok(children),
+            _ => Err(TypedReadError::UnexpectedMasterVariant { id }),
+        }
+    }
+
+    ///
+    /// Reads the unsigned integer tag `id`, returning its value.
+    ///
+    pub fn expect_uint(&mut self, id: u64) -> Result<u64, TypedReadError> {
+        let tag = self.next_value(Some(id), TagDataType::UnsignedInt)?;
+        Ok(*tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", id)))
+    }
+
+    ///
+    /// Reads the next tag, which must be an unsigned integer, returning its value.
+    ///
+    pub fn next_uint(&mut self) -> Result<u64, TypedReadError> {
+        let tag = self.next_value(None, TagDataType::UnsignedInt)?;
+        Ok(*tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag.get_id())))
+    }
+
+    ///
+    /// Reads the integer tag `id`, returning its value.
+    ///
+    pub fn expect_int(&mut self, id: u64) -> Result<i64, TypedReadError> {
+        let tag = self.next_value(Some(id), TagDataType::Integer)?;
+        Ok(*tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", id)))
+    }
+
+    ///
+    /// Reads the next tag, which must be an integer, returning its value.
+    ///
+    pub fn next_int(&mut self) -> Result<i64, TypedReadError> {
+        let tag = self.next_value(None, TagDataType::Integer)?;
+        Ok(*tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag.get_id())))
+    }
+
+    ///
+    /// Reads the float tag `id`, returning its value.
+    ///
+    pub fn expect_float(&mut self, id: u64) -> Result<f64, TypedReadError> {
+        let tag = self.next_value(Some(id), TagDataType::Float)?;
+        Ok(*tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", id)))
+    }
+
+    ///
+    /// Reads the next tag, which must be a float, returning its value.
+    ///
+    pub fn next_float(&mut self) -> Result<f64, TypedReadError> {
+        let tag = self.next_value(None, TagDataType::Float)?;
+        Ok(*tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag.get_id())))
+    }
+
+    ///
+    /// Reads the utf8 tag `id`, returning its value.  Text that isn't valid UTF-8 (possible when the specification stores text as [`LazyUtf8`][`crate::specs::LazyUtf8`]) is converted lossily.
+    ///
+    pub fn expect_utf8(&mut self, id: u64) -> Result<String, TypedReadError> {
+        let tag = self.next_value(Some(id), TagDataType::Utf8)?;
+        Ok(Self::utf8_value(&tag))
+    }
+
+    ///
+    /// Reads the next tag, which must be a utf8 tag, returning its value.  Text that isn't valid UTF-8 is converted lossily.
+    ///
+    pub fn next_utf8(&mut self) -> Result<String, TypedReadError> {
+        let tag = self.next_value(None, TagDataType::Utf8)?;
+        Ok(Self::utf8_value(&tag))
+    }
+
+    ///
+    /// Reads the binary tag `id`, returning its data.  Tags that aren't in the specification are read as binary tags.
+    ///
+    pub fn expect_binary(&mut self, id: u64) -> Result<Vec<u8>, TypedReadError> {
+        let tag = self.next_value(Some(id), TagDataType::Binary)?;
+        Ok(tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", id)).to_vec())
+    }
+
+    ///
+    /// Reads the next tag, which must be a binary tag (or a tag that isn't in the specification), returning its data.
+    ///
+    pub fn next_binary(&mut self) -> Result<Vec<u8>, TypedReadError> {
+        let tag = self.next_value(None, TagDataType::Binary)?;
+        Ok(tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", tag.get_id())).to_vec())
+    }
+
+    fn next_with_id(&mut self, expected_id: Option<u64>) -> Result<TSpec, TypedReadError> {
+        let tag = match self.peeked.take() {
+            Some(tag) => tag,
+            None => self.iterator.next().transpose()?.ok_or(TypedReadError::UnexpectedEnd { expected_id })?,
+        };
+        match expected_id {
+            Some(expected_id) if tag.get_id() != expected_id => Err(TypedReadError::UnexpectedTag { id: tag.get_id(), expected_id }),
+            _ => Ok(tag),
+        }
+    }
+
+    fn next_value(&mut self, expected_id: Option<u64>, expected: TagDataType) -> Result<TSpec, TypedReadError> {
+        let tag = self.next_with_id(expected_id)?;
+        let id = tag.get_id();
+        let found = TSpec::get_tag_data_type(id);
+        if found == Some(expected) || (found.is_none() && expected == TagDataType::Binary) {
+            Ok(tag)
+        } else {
+            Err(TypedReadError::TypeMismatch { id, expected, found })
+        }
+    }
+
+    fn next_master(&mut self, id: u64) -> Result<Master<TSpec>, TypedReadError> {
+        let tag = self.next_value(Some(id), TagDataType::Master)?;
+        Ok(tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", id)).clone())
+    }
+
+    fn utf8_value(tag: &TSpec) -> String {
+        String::from_utf8_lossy(tag.as_utf8_bytes().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag.get_id()))).into_owned()
+    }
+}
@@ -0,0 +1,100 @@
+use std::io::{self, ErrorKind, Read};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+///
+/// A [`Read`] adapter that gives up on reads that take longer than a timeout, for sources that can block indefinitely (such as pipes).
+///
+/// Blocking reads can't be cancelled, so the source is moved to a background thread which performs the reads.  When a read doesn't finish within the timeout, an error of kind [`ErrorKind::TimedOut`] is returned while the read stays pending, and the next call to [`Read::read()`] continues waiting for it.  No data is lost, so reading can simply be retried.
+///
+/// The background thread exits once the reader is dropped and any pending read on the source returns.
+///
+/// This is usually created through [`TagIterator::with_read_timeout()`][crate::TagIterator::with_read_timeout], which reports timeouts as [`TagIteratorError::Timeout`][crate::error::TagIteratorError::Timeout].
+///
+pub struct WatchdogReader {
+    requests: Sender<Vec<u8>>,
+    responses: Receiver<(Vec<u8>, io::Result<usize>)>,
+    timeout: Duration,
+    pending: bool,
+    spare: Option<Vec<u8>>,
+    leftover: Vec<u8>,
+}
+
+impl WatchdogReader {
+
+    ///
+    /// Returns a new [`WatchdogReader`] that moves `source` to a background thread and waits at most `timeout` for each read.
+    ///
+    pub fn new<R: Read + Send + 'static>(mut source: R, timeout: Duration) -> Self {
+        let (requests, pending_requests) = mpsc::channel::<Vec<u8>>();
+        let (completed_requests, responses) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(mut buf) = pending_requests.recv() {
+                let result = loop {
+                    match source.read(&mut buf) {
+                        Err(err) if err.kind() == ErrorKind::Interrupted => {},
+                        result => break result,
+                    }
+                };
+                if completed_requests.send((buf, result)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            requests,
+            responses,
+            timeout,
+            pending: false,
+            spare: None,
+            leftover: Vec::new(),
+        }
+    }
+
+    ///
+    /// Returns the timeout for each read.
+    ///
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl Read for WatchdogReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if !self.leftover.is_empty() {
+            let len = self.leftover.len().min(buf.len());
+            buf[..len].copy_from_slice(&self.leftover[..len]);
+            self.leftover.drain(..len);
+            return Ok(len);
+        }
+
+        if !self.pending {
+            let mut request = self.spare.take().unwrap_or_default();
+            request.resize(buf.len(), 0);
+            self.requests.send(request).map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "watchdog read thread has stopped"))?;
+            self.pending = true;
+        }
+
+        let (mut data, result) = match self.responses.recv_timeout(self.timeout) {
+            Ok(response) => response,
+            Err(RecvTimeoutError::Timeout) => return Err(io::Error::new(ErrorKind::TimedOut, format!("no data was read within {:?}", self.timeout))),
+            Err(RecvTimeoutError::Disconnected) => return Err(io::Error::new(ErrorKind::BrokenPipe, "watchdog read thread has stopped")),
+        };
+        self.pending = false;
+
+        // The pending read may have been requested by an earlier call with a larger buffer, so anything that doesn't fit is kept for the next read
+        let read = result?;
+        let len = read.min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        self.leftover.extend_from_slice(&data[len..read]);
+        data.clear();
+        self.spare = Some(data);
+        Ok(len)
+    }
+}
@@ -0,0 +1,48 @@
+mod test_spec;
+
+pub mod allocation_hook_tests {
+    use std::sync::{Arc, Mutex};
+
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![
+            TestSpec::Block(vec![1; 24]),
+            TestSpec::Block(vec![2; 100]),
+            TestSpec::Count(3),
+            TestSpec::Block(vec![4; 50]),
+        ]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn callback_approves_large_allocations() {
+        let data = data();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&data[..], &[], 16);
+        let seen = Arc::clone(&requests);
+        iter.on_large_allocation(32, move |size| {
+            seen.lock().unwrap().push(size);
+            size < 64
+        });
+
+        assert_eq!(TestSpec::Segment(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Cluster(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Block(vec![1; 24]), iter.next().unwrap().unwrap());
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::AllocationDenied { tag_start: 38, tag_id: 0xa1, size: 100 }))));
+        assert_eq!(TestSpec::Count(3), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Block(vec![4; 50]), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Cluster(Master::End), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Segment(Master::End), iter.next().unwrap().unwrap());
+        assert!(iter.next().is_none());
+
+        assert_eq!(vec![100, 50], *requests.lock().unwrap());
+    }
+}
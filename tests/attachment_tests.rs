@@ -0,0 +1,121 @@
+mod test_spec;
+
+pub mod attachment_tests {
+    use std::cell::RefCell;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    use ebml_iterable::error::{AttachmentError, TagWriterError};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{extract_attachments, inject_attachment, list_attachments, AttachmentIds, AttachmentInfo};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    const IDS: AttachmentIds = AttachmentIds { attachment: 0x1f43b675, name: 0xa1, mime_type: 0xa3, data: 0xec };
+
+    struct SharedSink(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn contents(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        inject_attachment::<TestSpec, _, _>(&mut writer, &IDS, "first.bin", "application/octet-stream", &contents(20000)[..], 20000).unwrap();
+        inject_attachment::<TestSpec, _, _>(&mut writer, &IDS, "second.txt", "text/plain", &b"hello"[..], 5).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn injected_attachments_are_valid_elements() {
+        let data = get_data();
+        let tags: Vec<TestSpec> = TagIterator::new(&data[..], &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Block(b"first.bin".to_vec()), TestSpec::SimpleBlock(b"application/octet-stream".to_vec()), TestSpec::Void(contents(20000))])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Block(b"second.txt".to_vec()), TestSpec::SimpleBlock(b"text/plain".to_vec()), TestSpec::Void(b"hello".to_vec())])),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn attachments_are_listed() {
+        let data = get_data();
+        let attachments = list_attachments::<TestSpec, _>(&data[..], &IDS).unwrap();
+        assert_eq!(2, attachments.len());
+        assert_eq!(AttachmentInfo {
+            name: Some("second.txt".to_string()),
+            mime_type: Some("text/plain".to_string()),
+            offset: attachments[1].offset,
+            data_offset: attachments[1].data_offset,
+            data_len: Some(5),
+        }, attachments[1]);
+        assert_eq!(Some("first.bin".to_string()), attachments[0].name);
+        assert_eq!(Some(20000), attachments[0].data_len);
+
+        let first = &attachments[0];
+        assert_eq!(&[0x1f, 0x43, 0xb6, 0x75], &data[first.offset..first.offset + 4]);
+        assert_eq!(&contents(20000)[..], &data[first.data_offset.unwrap()..first.data_offset.unwrap() + 20000]);
+    }
+
+    #[test]
+    pub fn attachments_are_extracted_to_sinks() {
+        let data = get_data();
+        let mut sinks: Vec<(String, Rc<RefCell<Vec<u8>>>)> = Vec::new();
+        let count = extract_attachments::<TestSpec, _, _>(&data[..], &IDS, |info| {
+            let sink = Rc::new(RefCell::new(Vec::new()));
+            sinks.push((info.name.clone().unwrap(), sink.clone()));
+            Ok(if info.mime_type.as_deref() == Some("text/plain") { None } else { Some(SharedSink(sink)) })
+        });
+        assert_eq!(1, count.unwrap());
+        let sinks: Vec<(String, Vec<u8>)> = sinks.into_iter().map(|(name, sink)| (name, sink.take())).collect();
+        assert_eq!(vec![("first.bin".to_string(), contents(20000)), ("second.txt".to_string(), Vec::new())], sinks);
+    }
+
+    #[test]
+    pub fn names_after_the_data_are_only_listed() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Void(vec![1, 2, 3]), TestSpec::Block(b"late.bin\0\0".to_vec())])),
+        ]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let attachments = list_attachments::<TestSpec, _>(&data[..], &IDS).unwrap();
+        assert_eq!(Some("late.bin".to_string()), attachments[0].name);
+
+        let mut names = Vec::new();
+        let count = extract_attachments::<TestSpec, _, Vec<u8>>(&data[..], &IDS, |info| {
+            names.push(info.name.clone());
+            Ok(None)
+        }).unwrap();
+        assert_eq!(0, count);
+        assert_eq!(vec![None], names);
+    }
+
+    #[test]
+    pub fn injection_errors_are_returned() {
+        let mut writer = TagWriter::new(Vec::new());
+        let result = inject_attachment::<TestSpec, _, _>(&mut writer, &IDS, "a", "b", &b"data"[..], 4);
+        assert!(matches!(result, Err(AttachmentError::WriteError { source: TagWriterError::UnexpectedTag { tag_id: 0x1f43b675, .. } })), "{:?}", result);
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        let result = inject_attachment::<TestSpec, _, _>(&mut writer, &IDS, "a", "b", &b"data"[..], 5);
+        assert!(matches!(result, Err(AttachmentError::DataError { .. })), "{:?}", result);
+    }
+}
@@ -0,0 +1,65 @@
+mod test_spec;
+
+pub mod audit_log_tests {
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn summary(writer: &TagWriter<Vec<u8>>) -> Vec<(u64, u64, Option<u64>)> {
+        writer.audit_log().iter().map(|e| (e.id, e.offset, e.size)).collect()
+    }
+
+    #[test]
+    pub fn records_written_elements() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Count(1)).unwrap_err();
+        writer.record_audit_log(true);
+
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Count(2)).unwrap();
+        assert_eq!(vec![(0x18538067, 0, None), (0x83, 12, Some(3))], summary(&writer));
+
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap_err();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0; 4])]))).unwrap();
+        writer.reserve_bookmark("cues", 4).unwrap();
+        writer.write_raw(0xbf, &[0; 4]).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let log = writer.take_audit_log();
+        assert!(writer.audit_log().is_empty());
+        let data = writer.into_inner().unwrap();
+
+        // Every recorded element matches what a reader finds at that offset
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let mut read = Vec::new();
+        while let Some(tag) = iter.next() {
+            let tag = tag.unwrap();
+            if !matches!(tag.as_master(), Some(Master::End)) {
+                read.push((tag.get_id(), iter.last_emitted_tag_offset() as u64));
+            }
+        }
+        assert_eq!(read, log.iter().map(|e| (e.id, e.offset)).collect::<Vec<_>>());
+        assert_eq!(vec![None, Some(3), Some(9), Some(4), Some(11), Some(6), Some(4), Some(6)], log.iter().map(|e| e.size).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn known_size_masters_appear_once_closed() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.record_audit_log(true);
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Count(2)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        assert!(writer.audit_log().is_empty());
+
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        assert_eq!(vec![(0x18538067, 0, Some(14)), (0x1F43B675, 5, Some(9)), (0x4100, 10, Some(4))], summary(&writer));
+
+        writer.record_audit_log(false);
+        writer.write(&TestSpec::Root(Master::Full(vec![]))).unwrap();
+        assert!(writer.audit_log().is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+mod test_spec;
+
+pub mod binary_seek_tests {
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+    use std::io::Cursor;
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 1_000_000]), TestSpec::Block(vec![0x02; 10])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0x03; 1_000_000])])),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn without_large_blocks(data: &[u8]) -> Vec<TestSpec> {
+        TagIterator::new(data, &[]).map(|t| t.unwrap()).filter(|t| !matches!(t, TestSpec::Block(data) if data.len() >= 1000)).collect()
+    }
+
+    #[test]
+    pub fn large_payloads_are_seeked_over() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        iter.seek_over_binary_payloads(Some(1000));
+        let tags: Vec<TestSpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+
+        assert_eq!(without_large_blocks(&data), tags);
+        assert!(tags.contains(&TestSpec::Block(vec![0x02; 10])));
+        let metrics = iter.metrics();
+        assert!(metrics.bytes_read < data.len() as u64 / 10, "{:?}", metrics);
+        assert_eq!(data.len() as u64, metrics.bytes_read + metrics.bytes_seeked_over);
+    }
+
+    #[test]
+    pub fn seeked_over_payloads_are_left_out_of_buffered_tags() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[TestSpec::Cluster(Master::Start)]);
+        iter.seek_over_binary_payloads(Some(1000));
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x02; 10])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)])),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn truncated_payloads_are_reported() {
+        let mut data = get_data();
+        data.truncate(data.len() - 10);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        iter.seek_over_binary_payloads(Some(1000));
+        let err = iter.find_map(|t| t.err()).unwrap();
+        match err {
+            TagIteratorError::UnexpectedEOF(partial) => assert_eq!(Some(1_000_000), partial.size),
+            err => panic!("{:?}", err),
+        }
+    }
+
+    #[test]
+    pub fn payloads_are_read_by_default() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        assert_eq!(12, iter.by_ref().count());
+        assert_eq!(data.len() as u64, iter.metrics().bytes_read);
+        assert_eq!(0, iter.metrics().bytes_seeked_over);
+    }
+}
@@ -0,0 +1,89 @@
+mod test_spec;
+
+pub mod bookmark_tests {
+    use std::io::{Cursor, Seek, SeekFrom};
+
+    use ebml_iterable::error::TagWriterError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn read(data: &[u8]) -> Vec<TestSpec> {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(data, &[]);
+        iter.collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    pub fn fills_written_bookmark() {
+        let mut dest = Cursor::new(vec![0xaa, 0xbb]);
+        dest.seek(SeekFrom::End(0)).unwrap();
+
+        let mut writer = TagWriter::new(dest);
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.reserve_bookmark("track_type", 8).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        assert_eq!(Some(12), writer.bookmark_offset("track_type"));
+
+        writer.write_at_bookmark("track_type", &TestSpec::TrackType(5)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)]))).unwrap();
+        let data = writer.into_inner().unwrap().into_inner();
+
+        assert_eq!(&[0xaa, 0xbb], &data[..2]);
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(5),
+            TestSpec::Void(vec![0; 3]),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(2),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], read(&data[2..]));
+    }
+
+    #[test]
+    pub fn fills_buffered_bookmark() {
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.reserve_bookmark("count", 3).unwrap();
+        writer.write(&TestSpec::Block(vec![0x01])).unwrap();
+        assert_eq!(None, writer.bookmark_offset("count"));
+
+        assert!(matches!(writer.write_at_bookmark("count", &TestSpec::Count(7)), Err(TagWriterError::BookmarkTooSmall { reserved: 3, required: 4, .. })));
+        writer.write_at_bookmark("count", &TestSpec::CueRefCluster(7)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        assert_eq!(Some(10), writer.bookmark_offset("count"));
+        let data = writer.into_inner().unwrap().into_inner();
+
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::CueRefCluster(7),
+            TestSpec::Block(vec![0x01]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], read(&data));
+    }
+
+    #[test]
+    pub fn rejects_invalid_bookmark_writes() {
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.reserve_bookmark("small", 4).unwrap();
+        assert!(writer.reserve_bookmark("tiny", 1).is_err());
+
+        assert!(matches!(writer.write_at_bookmark("missing", &TestSpec::TrackType(1)), Err(TagWriterError::UnknownBookmark { name }) if name == "missing"));
+        assert!(matches!(writer.write_at_bookmark("small", &TestSpec::TrackType(0x010000)), Err(TagWriterError::BookmarkTooSmall { reserved: 4, required: 6, .. })));
+        assert!(matches!(writer.write_at_bookmark("small", &TestSpec::TrackType(1)), Err(TagWriterError::BookmarkTooSmall { reserved: 4, required: 3, .. })));
+        assert!(matches!(writer.write_at_bookmark("small", &TestSpec::Count(1)), Err(TagWriterError::UnexpectedTag { tag_id: 0x4100, .. })));
+        writer.write_at_bookmark("small", &TestSpec::TrackType(0x0100)).unwrap();
+
+        let data = writer.into_inner().unwrap().into_inner();
+        assert_eq!(vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(0x0100), TestSpec::Segment(Master::End)], read(&data));
+    }
+}
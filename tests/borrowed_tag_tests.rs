@@ -0,0 +1,81 @@
+mod test_spec;
+
+pub mod borrowed_tag_tests {
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::iterator::BorrowedTag;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{HeaderStripping, TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Full(vec![
+            TestSpec::Int(1),
+            TestSpec::String("borrowed".to_string()),
+            TestSpec::String(String::new()),
+        ]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 100]), TestSpec::SimpleBlock(vec![0x02; 10])])),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn data_is_borrowed() {
+        let data = get_data();
+        let expected: Vec<TestSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+
+        for capacity in [16, 64, 4096] {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&data[..], &[], capacity);
+            let mut tags = Vec::new();
+            let mut borrowed = 0;
+            let mut offsets = Vec::new();
+            while let Some(tag) = iter.next_borrowed() {
+                let tag = tag.unwrap();
+                if !matches!(tag, BorrowedTag::Owned(_)) {
+                    borrowed += 1;
+                }
+                tags.push(tag.into_owned());
+                offsets.push(iter.last_emitted_tag_offset());
+            }
+            assert_eq!(3, borrowed);
+            assert_eq!(expected, tags);
+
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+            let expected_offsets: Vec<usize> = std::iter::from_fn(|| iter.next().map(|_| iter.last_emitted_tag_offset())).collect();
+            assert_eq!(expected_offsets, offsets);
+        }
+    }
+
+    #[test]
+    pub fn transformed_elements_are_owned() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.add_content_transform(&[0xa1], HeaderStripping::new(&[0x00]));
+        while let Some(tag) = iter.next_borrowed() {
+            match tag.unwrap() {
+                BorrowedTag::Owned(TestSpec::Block(block)) => assert_eq!([vec![0x00], vec![0x01; 100]].concat(), block),
+                BorrowedTag::Binary { id, .. } => assert_eq!(0xa3, id),
+                _ => {},
+            }
+        }
+    }
+
+    #[test]
+    pub fn errors_are_returned() {
+        let mut data = get_data();
+        data.truncate(data.len() - 5);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let mut ids = Vec::new();
+        let err = loop {
+            match iter.next_borrowed() {
+                Some(Ok(tag)) => ids.push(tag.id()),
+                Some(Err(err)) => break err,
+                None => panic!("{:?}", ids),
+            }
+        };
+        assert!(matches!(err, TagIteratorError::UnexpectedEOF(_)), "{:?}", err);
+        assert_eq!(Some(&0xa1), ids.last());
+    }
+}
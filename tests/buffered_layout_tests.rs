@@ -0,0 +1,94 @@
+mod test_spec;
+
+pub mod buffered_layout_tests {
+    use ebml_iterable::iterator::{ElementLayout, ElementSize};
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 200])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::CueRefCluster(5)])),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn unbuffered_offsets(data: &[u8]) -> Vec<(u64, usize, ElementSize)> {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(data, &[]);
+        let mut offsets = Vec::new();
+        while let Some(tag) = iter.next() {
+            let tag = tag.unwrap();
+            if !matches!(tag.as_master(), Some(Master::End)) {
+                offsets.push((tag.get_id(), iter.last_emitted_tag_offset(), iter.last_emitted_tag_size()));
+            }
+        }
+        offsets
+    }
+
+    fn flatten(layout: &ElementLayout, out: &mut Vec<(u64, usize, ElementSize)>) {
+        out.push((layout.id, layout.offset, layout.size));
+        for child in layout.children.iter() {
+            flatten(child, out);
+        }
+    }
+
+    #[test]
+    pub fn buffered_children_keep_their_offsets() {
+        let data = get_data();
+        let expected = unbuffered_offsets(&data);
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[TestSpec::Cluster(Master::Start)]);
+        iter.record_buffered_layout(true);
+        let mut found = Vec::new();
+        while let Some(tag) = iter.next() {
+            let tag = tag.unwrap();
+            match tag.as_master() {
+                Some(Master::Full(children)) => {
+                    let layout = iter.last_emitted_tag_layout().unwrap();
+                    assert_eq!(tag.get_id(), layout.id);
+                    assert_eq!(iter.last_emitted_tag_size(), layout.size);
+                    assert_eq!(children.iter().map(|c| c.get_id()).collect::<Vec<_>>(), layout.children.iter().map(|c| c.id).collect::<Vec<_>>());
+                    flatten(layout, &mut found);
+                },
+                Some(Master::End) => assert!(iter.last_emitted_tag_layout().is_none()),
+                _ => {
+                    assert!(iter.last_emitted_tag_layout().is_none());
+                    found.push((tag.get_id(), iter.last_emitted_tag_offset(), iter.last_emitted_tag_size()));
+                },
+            }
+        }
+        assert_eq!(expected, found);
+    }
+
+    #[test]
+    pub fn nested_buffers_keep_their_offsets() {
+        let data = get_data();
+        let expected = unbuffered_offsets(&data);
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[TestSpec::Segment(Master::Start), TestSpec::Cluster(Master::Start)]);
+        iter.record_buffered_layout(true);
+        iter.next().unwrap().unwrap();
+        let layout = iter.last_emitted_tag_layout().unwrap();
+        let mut found = Vec::new();
+        flatten(layout, &mut found);
+        assert_eq!(expected, found);
+
+        let block = &layout.children[1].children[1];
+        assert_eq!(0xa1, block.id);
+        assert_eq!(&[0x01; 200][..], &data[block.data_offset()..block.end_offset().unwrap()]);
+        assert_eq!(Some(data.len()), layout.end_offset());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    pub fn layouts_are_not_recorded_by_default() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[TestSpec::Segment(Master::Start)]);
+        assert!(matches!(iter.next().unwrap().unwrap().as_master(), Some(Master::Full(_))));
+        assert!(iter.last_emitted_tag_layout().is_none());
+    }
+}
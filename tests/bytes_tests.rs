@@ -0,0 +1,128 @@
+#[cfg(all(feature = "bytes", feature = "derive-spec"))]
+pub mod bytes_tests {
+    use bytes::{Buf, Bytes, BytesMut};
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::utils::decode_bytes;
+    use ebml_iterable::{PushDecoder, TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum BytesSpec {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        Root,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root)]
+        Count,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Root)]
+        Frame(Bytes),
+
+        #[id(0xa2)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Root)]
+        Owned,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&BytesSpec::Root(Master::Full(vec![
+            BytesSpec::Frame(Bytes::from_static(&[1; 100])),
+            BytesSpec::Count(7),
+            BytesSpec::Frame(Bytes::from_static(&[2; 100])),
+            BytesSpec::Owned(vec![3; 10]),
+            BytesSpec::Frame(Bytes::from(vec![4; 20_000])),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn read_bytes_payloads() {
+        let data = get_data();
+        let tags: Vec<BytesSpec> = TagIterator::new(&data[..], &[BytesSpec::Root(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![BytesSpec::Root(Master::Full(vec![
+            BytesSpec::Frame(Bytes::from_static(&[1; 100])),
+            BytesSpec::Count(7),
+            BytesSpec::Frame(Bytes::from_static(&[2; 100])),
+            BytesSpec::Owned(vec![3; 10]),
+            BytesSpec::Frame(Bytes::from(vec![4; 20_000])),
+        ]))], tags);
+    }
+
+    #[test]
+    pub fn payloads_share_the_read_buffer() {
+        let data = get_data();
+        let mut iter: TagIterator<_, BytesSpec> = TagIterator::new(&data[..], &[]);
+        let frames: Vec<Bytes> = iter.by_ref().filter_map(|t| match t.unwrap() {
+            BytesSpec::Frame(frame) => Some(frame),
+            _ => None,
+        }).collect();
+        assert_eq!(3, frames.len());
+
+        // The first two frames were read in the same chunk, so they point into the same allocation
+        let first_end = frames[0].as_ptr() as usize + frames[0].len();
+        let gap = frames[1].as_ptr() as usize - first_end;
+        assert_eq!(4 + 2, gap);
+    }
+
+    #[test]
+    pub fn read_from_buf() {
+        let data = get_data();
+        let expected: Vec<BytesSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+
+        // A chained buffer whose data is split across two chunks
+        let (first, second) = data.split_at(150);
+        let buf = Bytes::copy_from_slice(first).chain(Bytes::copy_from_slice(second));
+        let mut iter: TagIterator<_, BytesSpec> = TagIterator::from_buf(buf, &[]);
+        let tags: Vec<BytesSpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(expected, tags);
+        assert!(!iter.into_buf().has_remaining());
+    }
+
+    #[test]
+    pub fn push_bufs() {
+        let data = get_data();
+        let expected: Vec<BytesSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+
+        let mut decoder: PushDecoder<BytesSpec> = PushDecoder::new(&[]);
+        let mut tags = Vec::new();
+        for chunk in data.chunks(150) {
+            let (first, second) = chunk.split_at(chunk.len() / 2);
+            decoder.push_buf(Bytes::copy_from_slice(first).chain(Bytes::copy_from_slice(second)));
+            while let Some(tag) = decoder.next_tag() {
+                tags.push(tag.unwrap());
+            }
+        }
+        decoder.finish();
+        tags.extend(std::iter::from_fn(|| decoder.next_tag()).map(|t| t.unwrap()));
+        assert_eq!(expected, tags);
+    }
+
+    #[test]
+    pub fn decode_whole_bytes() {
+        let data = get_data();
+        let expected: Vec<BytesSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+        assert_eq!(expected, decode_bytes::<BytesSpec>(Bytes::from(data), &[]).unwrap());
+    }
+
+    #[test]
+    pub fn write_to_buf_mut() {
+        let data = get_data();
+        let mut writer = TagWriter::from_buf_mut(BytesMut::new());
+        for tag in TagIterator::<_, BytesSpec>::new(&data[..], &[]) {
+            writer.write(&tag.unwrap()).unwrap();
+        }
+        assert_eq!(&data[..], &writer.into_buf_mut().unwrap()[..]);
+    }
+
+    #[test]
+    pub fn write_to_full_buf_mut() {
+        let mut dest = [0u8; 8];
+        let mut writer = TagWriter::from_buf_mut(&mut dest[..]);
+        assert!(writer.write(&BytesSpec::Root(Master::Full(vec![BytesSpec::Owned(vec![3; 10])]))).is_err());
+    }
+}
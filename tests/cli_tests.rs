@@ -0,0 +1,111 @@
+#[cfg(feature = "cli")]
+mod test_spec;
+
+#[cfg(feature = "cli")]
+pub mod cli_tests {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn inspect(command: &str, data: &[u8]) -> (i32, String) {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ebml-inspect"))
+            .args([command, "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(data).unwrap();
+        let output = child.wait_with_output().unwrap();
+        (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap())
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0xff; 40])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(3)])),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn prints_tree() {
+        let (code, output) = inspect("tree", &get_data());
+        assert_eq!(0, code);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(vec![
+            "0x1a45dfa3 Ebml @0 size=0: ",
+            "0x18538067  @5 size=63",
+            "  0x83  @10 size=1: 1 (0x01)",
+            "  0x1f43b675  @13 size=46",
+            "    0x4100  @18 size=1: 2 (0x02)",
+            "    0xa1  @22 size=40: ffffffffffffffffffffffffffffffff...",
+            "  0x1f43b675  @64 size=4",
+            "    0x4100  @69 size=1: 3 (0x03)",
+        ], lines);
+    }
+
+    #[test]
+    pub fn prints_histogram() {
+        let (code, output) = inspect("histogram", &get_data());
+        assert_eq!(0, code);
+        let rows: Vec<Vec<&str>> = output.lines().skip(1).map(|line| line.split_whitespace().collect()).collect();
+        assert_eq!(vec!["0x4100", "2", "8"], rows[0]);
+        assert_eq!(vec!["0x1f43b675", "2", "60"], rows[1]);
+        assert_eq!(vec!["0x83", "1", "3"], rows[2]);
+    }
+
+    #[test]
+    pub fn reports_corruption() {
+        let (code, _) = inspect("check", &get_data());
+        assert_eq!(0, code);
+
+        let mut data = vec![0x00, 0x00, 0x00];
+        data.extend_from_slice(&get_data());
+        let (code, output) = inspect("check", &data);
+        assert_eq!(1, code);
+        assert!(output.lines().any(|line| line == "corrupted bytes 0..3"), "{}", output);
+    }
+
+    fn get_unknown_size_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(3)]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn prints_unknown_size_children() {
+        let (code, output) = inspect("tree", &get_unknown_size_data());
+        assert_eq!(0, code);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(vec![
+            "0x1a45dfa3 Ebml @0 size=0: ",
+            "0x18538067  @5 size=unknown",
+            "  0x1f43b675  @17 size=4",
+            "    0x4100  @22 size=1: 2 (0x02)",
+            "  0x1f43b675  @26 size=4",
+            "    0x4100  @31 size=1: 3 (0x03)",
+        ], lines);
+    }
+
+    #[test]
+    pub fn reports_corruption_inside_unknown_size_elements() {
+        let (code, output) = inspect("check", &get_unknown_size_data());
+        assert_eq!(0, code, "{}", output);
+
+        let mut data = get_unknown_size_data();
+        data.splice(26..26, [0x00, 0x00]);
+        let (code, output) = inspect("check", &data);
+        assert_eq!(1, code);
+        assert!(output.lines().any(|line| line == "corrupted bytes 26..28"), "{}", output);
+    }
+}
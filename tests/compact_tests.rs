@@ -0,0 +1,78 @@
+mod test_spec;
+
+pub mod compact_tests {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{compact_voids, compact_voids_in_place, CompactionSummary, VoidCompaction};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+    use std::io::Cursor;
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(0x01),
+            TestSpec::Void(vec![0x01; 3]),
+            TestSpec::Void(vec![0x02; 5]),
+            TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Count(2),
+                TestSpec::Void(vec![0x03; 2]),
+            ])),
+            TestSpec::Void(vec![0x04; 4]),
+        ]))).unwrap();
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Void(vec![0x05; 300])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x06; 10])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn read_all(data: &[u8]) -> Vec<TestSpec> {
+        TagIterator::new(data, &[]).map(|t| t.unwrap()).collect()
+    }
+
+    #[test]
+    pub fn merge_adjacent_voids() {
+        let data = get_data();
+        let mut dest = Cursor::new(Vec::new());
+        let summary = compact_voids::<TestSpec, _, _>(&data[..], &mut dest, VoidCompaction::Merge).unwrap();
+        assert_eq!(CompactionSummary { voids_removed: 1, bytes_removed: 0, len: data.len() }, summary);
+
+        let mut expected: Vec<TestSpec> = read_all(&data).into_iter().map(|t| match t {
+            TestSpec::Void(data) => TestSpec::Void(vec![0; data.len()]),
+            t => t,
+        }).collect();
+        expected.splice(2..4, [TestSpec::Void(vec![0; 10])]);
+        assert_eq!(expected, read_all(dest.get_ref()));
+    }
+
+    #[test]
+    pub fn remove_voids() {
+        let data = get_data();
+        let mut dest = Cursor::new(Vec::new());
+        let summary = compact_voids::<TestSpec, _, _>(&data[..], &mut dest, VoidCompaction::Remove).unwrap();
+        assert_eq!(CompactionSummary { voids_removed: 5, bytes_removed: 5 + 7 + 4 + 6 + 303, len: data.len() - 325 }, summary);
+
+        let expected: Vec<TestSpec> = read_all(&data).into_iter().filter(|t| !matches!(t, TestSpec::Void(_))).collect();
+        assert_eq!(expected, read_all(dest.get_ref()));
+        assert_eq!(summary.len, dest.get_ref().len());
+    }
+
+    #[test]
+    pub fn compact_in_place() {
+        let data = get_data();
+        let mut copy = Cursor::new(Vec::new());
+        compact_voids::<TestSpec, _, _>(&data[..], &mut copy, VoidCompaction::Remove).unwrap();
+
+        let mut file = Cursor::new(data.clone());
+        let summary = compact_voids_in_place::<TestSpec, _>(&mut file, VoidCompaction::Remove).unwrap();
+        let mut compacted = file.into_inner();
+        compacted.truncate(summary.len);
+        assert_eq!(copy.into_inner(), compacted);
+
+        let mut file = Cursor::new(data.clone());
+        let summary = compact_voids_in_place::<TestSpec, _>(&mut file, VoidCompaction::Merge).unwrap();
+        assert_eq!(data.len(), summary.len);
+        assert_eq!(1, read_all(file.get_ref()).iter().filter(|t| matches!(t, TestSpec::Void(v) if v.len() == 10)).count());
+    }
+}
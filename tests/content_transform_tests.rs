@@ -0,0 +1,91 @@
+mod test_spec;
+
+pub mod content_transform_tests {
+    use std::error::Error;
+
+    use ebml_iterable::error::{TagIteratorError, TagWriterError};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{ContentTransform, HeaderStripping, TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    struct Xor(u8);
+
+    impl ContentTransform for Xor {
+        fn decode(&mut self, _tag_id: u64, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+            Ok(data.iter().map(|b| b ^ self.0).collect())
+        }
+
+        fn encode(&mut self, tag_id: u64, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+            self.decode(tag_id, data)
+        }
+    }
+
+    struct Failing;
+
+    impl ContentTransform for Failing {
+        fn decode(&mut self, _tag_id: u64, _data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+            Err("nope".into())
+        }
+
+        fn encode(&mut self, _tag_id: u64, _data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+            Err("nope".into())
+        }
+    }
+
+    fn cluster(block: &[u8]) -> TestSpec {
+        TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(block.to_vec())]))]))
+    }
+
+    fn read(data: &[u8], stripped_header: Option<&[u8]>) -> Vec<TestSpec> {
+        let mut iter = TagIterator::new(data, &[TestSpec::Segment(Master::Start)]);
+        if let Some(header) = stripped_header {
+            iter.add_content_transform(&[0xa1], HeaderStripping::new(header));
+        }
+        iter.map(|t| t.unwrap()).collect()
+    }
+
+    #[test]
+    pub fn header_stripping_round_trip() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.add_content_transform(&[0xa1], HeaderStripping::new(&[0x0f, 0x0e]));
+        writer.write(&cluster(&[0x0f, 0x0e, 0x01, 0x02])).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        assert_eq!(vec![cluster(&[0x01, 0x02])], read(&data, None));
+        assert_eq!(vec![cluster(&[0x0f, 0x0e, 0x01, 0x02])], read(&data, Some(&[0x0f, 0x0e])));
+    }
+
+    #[test]
+    pub fn transcode_in_one_pass() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.add_content_transform(&[0xa1], Xor(0xff));
+        writer.write(&cluster(&[0x01, 0x02])).unwrap();
+        let encrypted = writer.into_inner().unwrap();
+        assert_eq!(vec![cluster(&[0xfe, 0xfd])], read(&encrypted, None));
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&encrypted[..], &[]);
+        iter.add_content_transform(&[0xa1], Xor(0xff));
+        let mut writer = TagWriter::new(Vec::new());
+        writer.add_content_transform(&[0xa1], Xor(0x0f));
+        for tag in iter {
+            writer.write(&tag.unwrap()).unwrap();
+        }
+        let reencrypted = writer.into_inner().unwrap();
+        assert_eq!(vec![cluster(&[0x0e, 0x0d])], read(&reencrypted, None));
+    }
+
+    #[test]
+    pub fn transform_errors() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.add_content_transform(&[0xa1], HeaderStripping::new(&[0x0f]));
+        assert!(matches!(writer.write(&cluster(&[0x01])), Err(TagWriterError::TransformError { tag_id: 0xa1, .. })));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&cluster(&[0x01])).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.add_content_transform(&[0xa1], Failing);
+        assert!(iter.take(10).any(|t| matches!(t, Err(TagIteratorError::TransformError { tag_id: 0xa1, .. }))));
+    }
+}
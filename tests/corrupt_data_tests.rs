@@ -201,4 +201,74 @@ pub mod corrupt_data_tests {
             }
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn records_corrupt_ranges() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        let mut data = writer.into_inner().unwrap();
+        data.splice(15..15, [0x0a; 3]);
+        data.extend_from_slice(&[0x0a; 2]);
+        let len = data.len();
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.corrupt_ranges().is_empty());
+        reader.try_recover().unwrap();
+        assert_eq!(vec![15..18], reader.corrupt_ranges());
+
+        let tags: Vec<_> = reader.by_ref().take(3).collect::<Result<_, _>>().unwrap();
+        assert_eq!(TestSpec::Cluster(Master::End), tags[2]);
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.try_recover().is_err());
+        assert_eq!(&[15..18, (len - 2)..len], reader.corrupt_ranges());
+    }
+
+    #[test]
+    pub fn error_on_ids_longer_than_header_allows() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::RawTag(0x0810000000, vec![0x01])).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        reader.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(reader.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedTagId{ tag_id: 0x0810000000, position: 5, length: 5 }))));
+    }
+
+    #[test]
+    pub fn allow_ids_up_to_declared_max_id_length() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Start)).unwrap();
+        writer.write_raw(0x42f2, &[0x05]).unwrap();
+        writer.write(&TestSpec::Ebml(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::RawTag(0x0810000000, vec![0x01])).unwrap();
+        writer.write(&TestSpec::RawTag(0x041000000000, vec![0x01])).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        reader.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        let tags: Vec<_> = reader.by_ref().take(5).collect::<Result<_, _>>().unwrap();
+        assert_eq!(TestSpec::RawTag(0x0810000000, vec![0x01]), tags[4]);
+        assert!(matches!(reader.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedTagId{ length: 6, .. }))));
+    }
+
+    #[test]
+    pub fn hostile_sizes_do_not_overflow() {
+        // Segment containing a Block whose size is the largest known size a vint can hold
+        let data = vec![0x18, 0x53, 0x80, 0x67, 0x8f, 0x1f, 0x43, 0xb6, 0x75, 0x8a, 0xa1, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x00];
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        assert!(matches!(reader.next().unwrap(), Ok(TestSpec::Segment(Master::Start))));
+        assert!(matches!(reader.next().unwrap(), Ok(TestSpec::Cluster(Master::Start))));
+        assert!(matches!(reader.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement{ tag_id: 0xa1, position: 10, size: 0xfffffffffffffe }))));
+        assert!(reader.take(10).all(|t| !matches!(t, Ok(TestSpec::Block(_)))));
+    }
+}
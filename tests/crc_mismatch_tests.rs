@@ -0,0 +1,122 @@
+mod test_spec;
+
+pub mod crc_mismatch_tests {
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::iterator::CrcMismatch;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::tools::crc32;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn encode(tags: &[TestSpec]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    fn checked_cluster(children: Vec<TestSpec>) -> TestSpec {
+        let data = encode(&[TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(children.clone()))]))]);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let (_, span) = iter.iter_with_spans().map(|t| t.unwrap()).find(|(t, _)| matches!(t, TestSpec::Cluster(_))).unwrap();
+        let crc = crc32(&data[span.data_offset()..span.end_offset().unwrap()]);
+        TestSpec::Cluster(Master::Full([vec![TestSpec::Crc32(crc.to_le_bytes().to_vec())], children].concat()))
+    }
+
+    // The second cluster's block is changed after its checksum is computed
+    fn get_data() -> Vec<u8> {
+        let mut data = encode(&[TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(0x01),
+            checked_cluster(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 10])]),
+            checked_cluster(vec![TestSpec::Count(2), TestSpec::Block(vec![0x02; 10])]),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(3)])),
+        ]))]);
+        let corrupted = data.iter().rposition(|b| *b == 0x02).unwrap();
+        data[corrupted] = 0x03;
+        data
+    }
+
+    fn read(behavior: CrcMismatch) -> (Vec<Result<TestSpec, TagIteratorError>>, u64) {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.set_crc_mismatch(behavior);
+        let tags = iter.by_ref().collect();
+        (tags, iter.metrics().crc_mismatches)
+    }
+
+    fn counts(tags: &[Result<TestSpec, TagIteratorError>]) -> Vec<u64> {
+        tags.iter().filter_map(|t| match t {
+            Ok(TestSpec::Count(count)) => Some(*count),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    pub fn checksums_are_ignored_by_default() {
+        let (tags, mismatches) = read(CrcMismatch::Ignore);
+        assert!(tags.iter().all(|t| t.is_ok()), "{:?}", tags);
+        assert_eq!(vec![1, 2, 3], counts(&tags));
+        assert_eq!(0, mismatches);
+    }
+
+    #[test]
+    pub fn mismatches_can_be_errors() {
+        let (tags, mismatches) = read(CrcMismatch::Error);
+        assert_eq!(vec![1, 3], counts(&tags));
+        assert_eq!(1, mismatches);
+
+        let errors: Vec<&TagIteratorError> = tags.iter().filter_map(|t| t.as_ref().err()).collect();
+        assert_eq!(1, errors.len());
+        match errors[0] {
+            TagIteratorError::CorruptedFileData(CorruptedFileError::CrcMismatch { tag_id, expected, computed, .. }) => {
+                assert_eq!(0x1f43b675, *tag_id);
+                assert_ne!(expected, computed);
+            },
+            err => panic!("{:?}", err),
+        }
+
+        // Reading continues with the next sibling
+        let position = tags.iter().position(|t| t.is_err()).unwrap();
+        assert_eq!(TestSpec::Cluster(Master::Start), *tags[position + 1].as_ref().unwrap());
+        assert_eq!(TestSpec::Count(3), *tags[position + 2].as_ref().unwrap());
+    }
+
+    #[test]
+    pub fn mismatches_can_be_emitted_with_warnings() {
+        let (tags, mismatches) = read(CrcMismatch::Warn);
+        assert!(tags.iter().all(|t| t.is_ok()), "{:?}", tags);
+        assert_eq!(vec![1, 2, 3], counts(&tags));
+        assert_eq!(1, mismatches);
+    }
+
+    #[test]
+    pub fn mismatches_can_be_skipped() {
+        let (tags, mismatches) = read(CrcMismatch::Skip);
+        let tags: Vec<TestSpec> = tags.into_iter().map(|t| t.unwrap()).collect();
+        assert_eq!(1, mismatches);
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Start),
+            tags[3].clone(),
+            TestSpec::Count(1),
+            TestSpec::Block(vec![0x01; 10]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(3),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn buffered_masters_are_checked() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[TestSpec::Cluster(Master::Start)]);
+        iter.set_crc_mismatch(CrcMismatch::Skip);
+        let clusters = iter.filter(|t| matches!(t, Ok(TestSpec::Cluster(_)))).count();
+        assert_eq!(2, clusters);
+    }
+}
@@ -0,0 +1,70 @@
+mod test_spec;
+
+pub mod cue_builder_tests {
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    const CLUSTER_ID: u64 = 0x1F43B675;
+    const BLOCK_ID: u64 = 0xa1;
+
+    #[test]
+    pub fn collects_entries_for_anchors() {
+        let mut writer = TagWriter::new(Vec::new());
+        let mut cluster_offset = 0;
+        let cues = writer.build_cues(&[CLUSTER_ID, BLOCK_ID], move |element| {
+            if element.id == CLUSTER_ID {
+                cluster_offset = element.offset;
+                None
+            } else {
+                Some((cluster_offset, element.offset - cluster_offset))
+            }
+        });
+
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Count(1)).unwrap();
+        writer.write(&TestSpec::Block(vec![0; 4])).unwrap();
+        writer.write(&TestSpec::Block(vec![0; 2])).unwrap();
+        assert!(cues.is_empty());
+
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        assert_eq!(2, cues.len());
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0; 3])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        assert!(writer.audit_log().is_empty());
+        let data = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let mut expected = Vec::new();
+        let mut cluster_offset = 0;
+        while let Some(tag) = iter.next() {
+            let tag = tag.unwrap();
+            let offset = iter.last_emitted_tag_offset() as u64;
+            match tag.get_id() {
+                CLUSTER_ID => cluster_offset = offset,
+                BLOCK_ID => expected.push((cluster_offset, offset - cluster_offset)),
+                _ => {},
+            }
+        }
+        assert_eq!(expected, cues.take_entries());
+        assert!(cues.is_empty());
+    }
+
+    #[test]
+    pub fn keeps_audit_log_separate() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.record_audit_log(true);
+        let cues = writer.build_cues(&[CLUSTER_ID], |element| element.size);
+
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        writer.record_audit_log(false);
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0; 4])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+
+        assert!(writer.audit_log().is_empty());
+        assert_eq!(vec![9, 11], cues.take_entries());
+    }
+}
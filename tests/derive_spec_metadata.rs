@@ -0,0 +1,76 @@
+#[cfg(feature = "derive-spec")]
+pub mod derive_spec_metadata {
+    use ebml_iterable::specs::{ebml_specification, ElementMeta, TagDataType};
+
+    #[ebml_specification(metadata)]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Described {
+        #[id(0x01)]
+        #[data_type(TagDataType::Master)]
+        #[min_occurs(1)]
+        #[max_occurs(1)]
+        Root,
+
+        #[id(0x02)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root)]
+        Parent,
+
+        #[id(0x100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root/Parent)]
+        #[default_value(1)]
+        #[min_version(2)]
+        Count,
+
+        #[id(0x101)]
+        #[data_type(TagDataType::Integer)]
+        #[doc_path(Root/Parent)]
+        #[default_value(-4)]
+        #[max_version(3)]
+        Offset,
+
+        #[id(0x201)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Root/Parent)]
+        #[default_value("eng")]
+        Language,
+    }
+
+    #[test]
+    pub fn metadata_table_describes_spec() {
+        let table = Described::SPEC_METADATA;
+        assert_eq!(7, table.len());
+
+        assert_eq!(ElementMeta {
+            id: 0x01,
+            name: "Root",
+            data_type: TagDataType::Master,
+            path: "\\Root",
+            default: None,
+            min_occurs: Some(1),
+            max_occurs: Some(1),
+            min_version: None,
+            max_version: None,
+        }, table[0]);
+
+        let count = table.iter().find(|m| m.name == "Count").unwrap();
+        assert_eq!(0x100, count.id);
+        assert_eq!(TagDataType::UnsignedInt, count.data_type);
+        assert_eq!("\\Root\\Parent\\Count", count.path);
+        assert_eq!(Some("1"), count.default);
+        assert_eq!((Some(2), None), (count.min_version, count.max_version));
+
+        let offset = table.iter().find(|m| m.name == "Offset").unwrap();
+        assert_eq!(Some("-4"), offset.default);
+        assert_eq!((None, Some(3)), (offset.min_version, offset.max_version));
+
+        let language = table.iter().find(|m| m.name == "Language").unwrap();
+        assert_eq!(Some("eng"), language.default);
+
+        let crc = table.iter().find(|m| m.id == 0xbf).unwrap();
+        assert_eq!("\\(1-\\)Crc32", crc.path);
+        let void = table.iter().find(|m| m.id == 0xec).unwrap();
+        assert_eq!("\\(-\\)Void", void.path);
+    }
+}
@@ -0,0 +1,33 @@
+#[cfg(feature = "derive-spec")]
+pub mod derive_spec_non_exhaustive {
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, EbmlTag, TagDataType};
+    use ebml_iterable::iterator::AllowableErrors;
+    use ebml_iterable::TagIterator;
+
+    #[ebml_specification(metadata, non_exhaustive(Extension))]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Versioned {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        Root,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root)]
+        Count,
+    }
+
+    #[test]
+    pub fn unknown_elements_use_extension_variant() {
+        assert_eq!(Versioned::Extension(0x4200, vec![1, 2]), Versioned::get_raw_tag(0x4200, &[1, 2]));
+        assert_eq!(0x4200, Versioned::Extension(0x4200, vec![]).get_id());
+        assert_eq!(Some(&[3u8][..]), Versioned::Extension(0x4200, vec![3]).as_binary());
+        assert!(Versioned::SPEC_METADATA.iter().all(|meta| meta.name != "Extension"));
+
+        let data = [0x42, 0x00, 0x81, 0x05, 0x41, 0x00, 0x81, 0x02];
+        let mut iter: TagIterator<_, Versioned> = TagIterator::new(&data[..], &[]);
+        iter.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        let tags: Vec<Versioned> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![Versioned::Extension(0x4200, vec![5]), Versioned::Count(2)], tags);
+    }
+}
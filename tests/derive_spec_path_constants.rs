@@ -0,0 +1,37 @@
+#[cfg(feature = "derive-spec")]
+pub mod derive_spec_path_constants {
+    use ebml_iterable::specs::{easy_ebml, PathPart, TagDataType};
+    use ebml_iterable::utils::parse_doc_path;
+
+    easy_ebml! {
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum Pathed {
+            Segment                    : Master = 0x18538067,
+            Segment/Info               : Master = 0x1549a966,
+            Segment/Info/Title         : Utf8 = 0x7ba9,
+            Segment/Info/EBMLMaxIDLength : UnsignedInt = 0x42f2,
+            (1-)/Tags                  : Master = 0x1254c367,
+        }
+    }
+
+    #[test]
+    pub fn path_constants_are_generated() {
+        assert_eq!("Segment", Pathed::PATH_SEGMENT);
+        assert_eq!("Segment/Info", Pathed::PATH_SEGMENT_INFO);
+        assert_eq!("Segment/Info/Title", Pathed::PATH_SEGMENT_INFO_TITLE);
+        assert_eq!("Segment/Info/EBMLMaxIDLength", Pathed::PATH_SEGMENT_INFO_EBML_MAX_ID_LENGTH);
+    }
+
+    #[test]
+    pub fn path_constants_parse_to_path_parts() {
+        assert_eq!(
+            Some(vec![PathPart::Id(0x18538067), PathPart::Id(0x1549a966), PathPart::Id(0x7ba9)]),
+            parse_doc_path::<Pathed>(Pathed::PATH_SEGMENT_INFO_TITLE)
+        );
+        assert_eq!(
+            Some(vec![PathPart::Id(0x18538067), PathPart::Global((Some(1), None)), PathPart::Id(0x1254c367)]),
+            parse_doc_path::<Pathed>("Segment/(1-)/Tags")
+        );
+        assert_eq!(None, parse_doc_path::<Pathed>("Segment/Inof/Title"));
+    }
+}
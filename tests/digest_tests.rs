@@ -0,0 +1,91 @@
+#[cfg(feature = "digest")]
+mod test_spec;
+
+#[cfg(feature = "digest")]
+pub mod digest_tests {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{digest_elements, DigestStream, HashingReader, HashingWriter};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+    use sha2::{Digest, Sha256};
+
+    use super::test_spec::TestSpec;
+
+    fn write_clusters(writer: &mut TagWriter<impl std::io::Write>) {
+        for i in 0..3u64 {
+            writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i), TestSpec::Block(vec![i as u8; 100])]))).unwrap();
+        }
+    }
+
+    #[test]
+    pub fn digest_nested_elements() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        write_clusters(&mut writer);
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut copy = Vec::new();
+        let digests = digest_elements::<TestSpec, Sha256, _, _>(&data[..], &mut copy, &[0x1F43B675, 0xa1]).unwrap();
+        assert_eq!(data, copy);
+
+        let ids: Vec<u64> = digests.iter().map(|d| d.id).collect();
+        assert_eq!(vec![0x1F43B675, 0xa1, 0x1F43B675, 0xa1, 0x1F43B675, 0xa1], ids);
+        for digest in digests.iter() {
+            let bytes = &data[digest.position..(digest.position + digest.len)];
+            assert_eq!(Sha256::digest(bytes), digest.hash);
+        }
+        assert_eq!(102, digests[1].len);
+        assert_eq!(digests[0].position + digests[0].len, digests[2].position);
+    }
+
+    #[test]
+    pub fn digest_unknown_sized_element() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        write_clusters(&mut writer);
+        let data = writer.into_inner().unwrap();
+
+        let digests = digest_elements::<TestSpec, Sha256, _, _>(&data[..], std::io::sink(), &[0x18538067]).unwrap();
+        assert_eq!(1, digests.len());
+        assert_eq!(5, digests[0].position);
+        assert_eq!(data.len() - 5, digests[0].len);
+        assert_eq!(Sha256::digest(&data[5..]), digests[0].hash);
+    }
+
+    #[test]
+    pub fn digest_ranges_while_writing() {
+        let mut writer = TagWriter::new(DigestStream::<_, Sha256>::new(Vec::new(), &[0..5, 10..20, 300..10_000]));
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        write_clusters(&mut writer);
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let stream = writer.into_inner().unwrap();
+        assert_eq!(stream.get_ref().len() as u64, stream.position());
+
+        let (data, hashes) = stream.finalize();
+        assert_eq!(Sha256::digest(&data[0..5]), hashes[0]);
+        assert_eq!(Sha256::digest(&data[10..20]), hashes[1]);
+        assert_eq!(Sha256::digest(&data[300..]), hashes[2]);
+    }
+
+    #[test]
+    pub fn hash_whole_streams() {
+        let mut writer = TagWriter::new(HashingWriter::<_, Sha256>::new(Vec::new()));
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        write_clusters(&mut writer);
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let hashing_writer = writer.into_inner().unwrap();
+        assert_eq!(hashing_writer.get_ref().len() as u64, hashing_writer.len());
+        let (data, written_hash) = hashing_writer.finalize();
+        assert_eq!(Sha256::digest(&data), written_hash);
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(HashingReader::<_, Sha256>::new(&data[..]), &[], 16);
+        let tags: Vec<TestSpec> = iter.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(14, tags.len());
+        let hashing_reader = iter.into_inner();
+        assert_eq!(data.len() as u64, hashing_reader.len());
+        assert_eq!(written_hash, hashing_reader.finalize().1);
+    }
+}
@@ -0,0 +1,81 @@
+mod test_spec;
+
+pub mod doctype_tests {
+    use std::io::{Cursor, Read};
+
+    use ebml_iterable::error::DocTypeError;
+    use ebml_iterable::iterator::AllowableErrors;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{sniff_doctype, DocTypeRegistry};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn header(doctype: Option<&str>) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Start)).unwrap();
+        writer.write_raw(0x4286, &[0x01]).unwrap();
+        if let Some(doctype) = doctype {
+            writer.write_raw(0x4282, doctype.as_bytes()).unwrap();
+        }
+        writer.write(&TestSpec::Ebml(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn body() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn sniff_reads_only_the_header() {
+        let header = header(Some("webm\0\0"));
+        let data = [header.clone(), body()].concat();
+
+        let sniffed = sniff_doctype(Cursor::new(data)).unwrap();
+        assert_eq!("webm", sniffed.doctype());
+        assert_eq!(&header[..], sniffed.header());
+
+        let (_, _, mut source) = sniffed.into_parts();
+        let mut rest = Vec::new();
+        source.read_to_end(&mut rest).unwrap();
+        assert_eq!(body(), rest);
+    }
+
+    #[test]
+    pub fn sniffed_reader_replays_header() {
+        let data = [header(Some("webm")), body()].concat();
+        let sniffed = sniff_doctype(Cursor::new(data)).unwrap();
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(sniffed.into_reader(), &[TestSpec::Ebml(Master::Start)]);
+        reader.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        let tags: Vec<TestSpec> = reader.collect::<Result<_, _>>().unwrap();
+        assert!(matches!(&tags[0], TestSpec::Ebml(Master::Full(children)) if children.len() == 2));
+        assert_eq!(vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(1), TestSpec::Segment(Master::End)], tags[1..]);
+    }
+
+    #[test]
+    pub fn registry_picks_handler_by_doctype() {
+        let mut registry: DocTypeRegistry<Cursor<Vec<u8>>, (&'static str, usize)> = DocTypeRegistry::new();
+        registry.register("webm", |sniffed| ("webm", sniffed.header().len()));
+        registry.register("matroska", |sniffed| ("matroska", sniffed.header().len()));
+
+        let webm = header(Some("webm"));
+        assert_eq!(("webm", webm.len()), registry.open(Cursor::new([webm.clone(), body()].concat())).unwrap());
+        assert_eq!("matroska", registry.open(Cursor::new(header(Some("matroska")))).unwrap().0);
+        assert!(matches!(registry.open(Cursor::new(header(Some("custom")))), Err(DocTypeError::UnknownDocType(doctype)) if doctype == "custom"));
+    }
+
+    #[test]
+    pub fn sniff_rejects_invalid_headers() {
+        assert!(matches!(sniff_doctype(Cursor::new(body())), Err(DocTypeError::InvalidHeader)));
+        assert!(matches!(sniff_doctype(Cursor::new(header(None))), Err(DocTypeError::MissingDocType)));
+        assert!(matches!(sniff_doctype(Cursor::new(Vec::new())), Err(DocTypeError::InvalidHeader)));
+
+        let truncated = header(Some("webm"));
+        assert!(matches!(sniff_doctype(Cursor::new(truncated[..truncated.len() - 2].to_vec())), Err(DocTypeError::ReadError { .. })));
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(all(feature = "test-utils", feature = "derive-spec"))]
+pub mod document_generator_tests {
+    use arbitrary::Unstructured;
+    use ebml_iterable::specs::{ebml_specification, EbmlTag, Master, TagDataType};
+    use ebml_iterable::test_utils::{assert_conformant, assert_round_trip, DocumentGenerator};
+    use ebml_iterable::TagWriter;
+
+    #[ebml_specification(metadata)]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Generated {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        #[min_occurs(1)]
+        #[max_occurs(1)]
+        Root,
+
+        #[id(0x4280)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root)]
+        #[min_occurs(1)]
+        #[max_occurs(1)]
+        Header,
+
+        #[id(0x4281)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root/Header)]
+        #[min_occurs(1)]
+        #[max_occurs(1)]
+        #[restricted_values(1, 2)]
+        Version,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root)]
+        Cluster,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root/Cluster)]
+        #[max_occurs(1)]
+        Count,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Root/Cluster)]
+        Block,
+    }
+
+    fn seed(index: u32) -> Vec<u8> {
+        let mut state = index.wrapping_mul(0x9e3779b9) | 1;
+        (0..65536).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        }).collect()
+    }
+
+    fn children(tag: &Generated) -> &[Generated] {
+        match tag.as_master() {
+            Some(Master::Full(children)) => children,
+            _ => panic!("expected a full master, found {:?}", tag),
+        }
+    }
+
+    fn generator() -> DocumentGenerator<Generated> {
+        let mut generator: DocumentGenerator<Generated> = DocumentGenerator::from_metadata(Generated::SPEC_METADATA);
+        generator.set_weight(0xec, 0);
+        generator
+    }
+
+    fn encode(tags: &[Generated]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn documents_respect_occurrences() {
+        let mut generator = generator();
+        generator.set_data_len_range_for(0xa1, 100..=200);
+        for i in 0..100 {
+            let tags = generator.generate(&mut Unstructured::new(&seed(i))).unwrap();
+            assert_eq!(1, tags.len());
+            let root = children(&tags[0]);
+            let headers: Vec<&Generated> = root.iter().filter(|t| matches!(t, Generated::Header(_))).collect();
+            assert_eq!(1, headers.len());
+            assert!(matches!(children(headers[0]), [Generated::Version(1 | 2)]));
+            for cluster in root.iter().filter(|t| matches!(t, Generated::Cluster(_))) {
+                let cluster = children(cluster);
+                assert!(cluster.iter().filter(|t| matches!(t, Generated::Count(_))).count() <= 1);
+                assert!(cluster.iter().all(|t| !matches!(t, Generated::Block(data) if data.len() < 100 || data.len() > 200)));
+            }
+
+            assert_round_trip(&tags);
+            assert_conformant::<Generated>(&encode(&tags));
+        }
+    }
+
+    #[test]
+    pub fn weights_shape_documents() {
+        let mut generator = generator();
+        generator.set_weight(0x1F43B675, 0);
+        generator.set_children_range(4..=4);
+        let tags = generator.generate(&mut Unstructured::new(&seed(1))).unwrap();
+        assert!(matches!(children(&tags[0]), [Generated::Header(_)]));
+
+        generator.set_weight(0x1F43B675, 1);
+        let tags = generator.generate(&mut Unstructured::new(&seed(1))).unwrap();
+        assert_eq!(5, children(&tags[0]).len());
+    }
+}
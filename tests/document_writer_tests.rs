@@ -0,0 +1,71 @@
+mod test_spec;
+
+pub mod document_writer_tests {
+    use ebml_iterable::error::TagWriterError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{DocumentWriter, TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn header() -> TestSpec {
+        TestSpec::Ebml(Master::Full(vec![]))
+    }
+
+    #[test]
+    pub fn documents_are_written_in_order() {
+        let writer: DocumentWriter<_, TestSpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+        let mut body = writer.write_header(&header()).unwrap().open_root(&TestSpec::Segment(Master::Start)).unwrap();
+        body.write(&TestSpec::TrackType(1)).unwrap();
+        body.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        let output = body.finish().unwrap().into_inner().unwrap();
+
+        let tags: Vec<TestSpec> = TagIterator::new(&output[..], &[TestSpec::Ebml(Master::Start), TestSpec::Segment(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            header(),
+            TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(1), TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))])),
+        ], tags);
+    }
+
+    #[test]
+    pub fn roots_can_have_unknown_sizes() {
+        let writer: DocumentWriter<_, TestSpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+        let mut body = writer.write_header(&header()).unwrap().open_root_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        body.write(&TestSpec::TrackType(1)).unwrap();
+        let output = body.finish().unwrap().into_inner().unwrap();
+        assert_eq!(5 + 12 + 3, output.len());
+    }
+
+    #[test]
+    pub fn tags_are_checked_against_the_phase() {
+        let writer: DocumentWriter<_, TestSpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+        let result = writer.write_header(&TestSpec::Ebml(Master::Start));
+        assert!(matches!(result, Err(TagWriterError::UnexpectedTag { tag_id: 0x1a45dfa3, .. })));
+
+        let writer: DocumentWriter<_, TestSpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+        let result = writer.write_header(&TestSpec::Segment(Master::Full(vec![])));
+        assert!(matches!(result, Err(TagWriterError::UnexpectedTag { tag_id: 0x18538067, .. })));
+
+        for root in [TestSpec::Ebml(Master::Start), TestSpec::Cluster(Master::Start), TestSpec::Segment(Master::Full(vec![]))] {
+            let writer: DocumentWriter<_, TestSpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+            let result = writer.write_header(&header()).unwrap().open_root(&root);
+            assert!(matches!(result, Err(TagWriterError::UnexpectedTag { .. })));
+        }
+
+        let writer: DocumentWriter<_, TestSpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+        let mut body = writer.write_header(&header()).unwrap().open_root(&TestSpec::Segment(Master::Start)).unwrap();
+        for tag in [header(), TestSpec::Segment(Master::End), TestSpec::Root(Master::Full(vec![]))] {
+            let result = body.write(&tag);
+            assert!(matches!(result, Err(TagWriterError::UnexpectedTag { current_path, .. }) if current_path == vec![0x18538067]));
+        }
+        body.write(&TestSpec::Count(1)).unwrap_err();
+    }
+
+    #[test]
+    pub fn open_children_prevent_finishing() {
+        let writer: DocumentWriter<_, TestSpec, _> = DocumentWriter::new(TagWriter::new(Vec::new()));
+        let mut body = writer.write_header(&header()).unwrap().open_root(&TestSpec::Segment(Master::Start)).unwrap();
+        body.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        let result = body.finish();
+        assert!(matches!(result, Err(TagWriterError::UnexpectedClosingTag { tag_id: 0x18538067, expected_id: Some(0x1f43b675) })));
+    }
+}
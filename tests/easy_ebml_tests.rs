@@ -0,0 +1,48 @@
+#[cfg(feature = "derive-spec")]
+pub mod easy_ebml_tests {
+    use ebml_iterable::specs::{easy_ebml, ebml_specification, EbmlSpecification, PathPart, TagDataType};
+
+    // Extends a base spec (TestSpec) whose Segment isn't part of this invocation
+    easy_ebml! {
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum Extension {
+            Segment(0x18538067)/Chapters        : Master = 0x1043a770,
+            Segment/Chapters/EditionEntry       : Master = 0x45b9,
+            Segment/Chapters/EditionEntry/Uid   : UnsignedInt = 0x45bc,
+            (1-)/Crc32                          : Binary = 0xbf,
+            (1-)/Tags                           : Master = 0x1254c367,
+            (1-)/Tags/Tag                       : Master = 0x7373,
+            (1-)/Tags/Tag/TagName               : Utf8 = 0x45a3,
+        }
+    }
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum AttributeExtension {
+        #[id(0x1043a770)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Segment(0x18538067))]
+        Chapters,
+
+        #[id(0x45bc)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment(0x18538067)/Chapters)]
+        Uid,
+    }
+
+    #[test]
+    pub fn resolves_external_elements() {
+        assert_eq!(&[PathPart::Id(0x18538067)], Extension::get_path_by_id(0x1043a770));
+        assert_eq!(&[PathPart::Id(0x18538067), PathPart::Id(0x1043a770), PathPart::Id(0x45b9)], Extension::get_path_by_id(0x45bc));
+        assert_eq!(None, Extension::get_tag_data_type(0x18538067));
+
+        assert_eq!(&[PathPart::Id(0x18538067), PathPart::Id(0x1043a770)], AttributeExtension::get_path_by_id(0x45bc));
+    }
+
+    #[test]
+    pub fn resolves_global_parents() {
+        assert_eq!(&[PathPart::Global((Some(1), None))], Extension::get_path_by_id(0xbf));
+        assert_eq!(&[PathPart::Global((Some(1), None)), PathPart::Id(0x1254c367), PathPart::Id(0x7373)], Extension::get_path_by_id(0x45a3));
+        assert_eq!(Some(TagDataType::Binary), Extension::get_tag_data_type(0xec));
+    }
+}
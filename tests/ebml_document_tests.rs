@@ -0,0 +1,69 @@
+#[cfg(feature = "dom")]
+mod test_spec;
+
+#[cfg(feature = "dom")]
+pub mod ebml_document_tests {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{EbmlDocument, EbmlNode, TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn cluster(count: u64, block: u8) -> TestSpec {
+        TestSpec::Cluster(Master::Full(vec![TestSpec::Count(count), TestSpec::Block(vec![block; 4])]))
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(1),
+            cluster(1, 1),
+            cluster(2, 2),
+            cluster(2, 3),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn select_with_predicates() {
+        let document: EbmlDocument<TestSpec> = EbmlDocument::read(&get_data()[..]).unwrap();
+
+        assert_eq!(3, document.select("Segment/Cluster").unwrap().len());
+        assert_eq!(4, document.select("Segment/*").unwrap().len());
+
+        let found = document.select("Segment/Cluster[Count=2]").unwrap();
+        assert_eq!(vec![cluster(2, 2), cluster(2, 3)], found.iter().map(|n| n.to_tag()).collect::<Vec<_>>());
+
+        let found = document.select("Segment/Cluster[Count=2][2]/Block").unwrap();
+        assert_eq!(vec![&TestSpec::Block(vec![3; 4])], found.iter().map(|n| n.tag()).collect::<Vec<_>>());
+
+        let found = document.select_first("/0x18538067/Cluster[Block=0x01010101]").unwrap().unwrap();
+        assert_eq!(cluster(1, 1), found.to_tag());
+
+        assert!(document.select("Segment/Nope").is_err());
+        assert!(document.select("Segment/Cluster[Count=2").is_err());
+    }
+
+    #[test]
+    pub fn mutate_and_write() {
+        let mut document: EbmlDocument<TestSpec> = EbmlDocument::read(&get_data()[..]).unwrap();
+
+        assert_eq!(2, document.remove("Segment/Cluster[Count=2]").unwrap());
+        for node in document.select_mut("Segment/TrackType").unwrap() {
+            node.set_tag(TestSpec::TrackType(2));
+        }
+        let segment = document.select_mut("Segment").unwrap().pop().unwrap();
+        segment.children_mut().push(EbmlNode::new(cluster(5, 5)));
+
+        let mut out = Vec::new();
+        document.write(&mut out).unwrap();
+
+        let tags: Vec<TestSpec> = TagIterator::new(&out[..], &[TestSpec::Segment(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Ebml(Master::Start),
+            TestSpec::Ebml(Master::End),
+            TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(2), cluster(1, 1), cluster(5, 5)])),
+        ], tags);
+        assert_eq!(document, EbmlDocument::from_tags(document.to_tags()));
+    }
+}
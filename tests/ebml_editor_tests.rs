@@ -0,0 +1,108 @@
+mod test_spec;
+
+pub mod ebml_editor_tests {
+    use ebml_iterable::error::EbmlEditorError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::tools::crc32;
+    use ebml_iterable::{EbmlEditor, TagIterator, TagWriter};
+    use std::io::Cursor;
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Cursor<Vec<u8>> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Crc32(vec![0; 4]),
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Crc32(vec![0; 4]),
+                TestSpec::Block(vec![0x01; 8]),
+                TestSpec::Count(3),
+            ])),
+        ]))).unwrap();
+        drop(writer);
+
+        dest.set_position(0);
+        dest
+    }
+
+    fn read_all(source: &mut Cursor<Vec<u8>>) -> Vec<TestSpec> {
+        source.set_position(0);
+        let tags = TagIterator::new(&mut *source, &[]).map(|t| t.unwrap()).collect();
+        source.set_position(0);
+        tags
+    }
+
+    #[test]
+    pub fn replace_pads_with_void_and_updates_crcs() {
+        let mut editor: EbmlEditor<_, TestSpec> = EbmlEditor::new(get_data());
+        editor.replace("Segment/Cluster/Block", &TestSpec::Block(vec![0x02; 4])).unwrap();
+        let mut source = editor.into_inner();
+
+        // Segment data starts at 5 and Cluster data at 19, each with a 6 byte CRC-32 element first
+        let segment_crc = crc32(&source.get_ref()[11..]).to_le_bytes().to_vec();
+        let cluster_crc = crc32(&source.get_ref()[25..]).to_le_bytes().to_vec();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Crc32(segment_crc),
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Crc32(cluster_crc),
+            TestSpec::Block(vec![0x02; 4]),
+            TestSpec::Void(vec![0x00; 2]),
+            TestSpec::Count(3),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], read_all(&mut source));
+    }
+
+    #[test]
+    pub fn replace_absorbs_single_leftover_byte() {
+        let mut editor: EbmlEditor<_, TestSpec> = EbmlEditor::new(get_data());
+        editor.replace("Segment/Cluster/Block", &TestSpec::Block(vec![0x02; 7])).unwrap();
+        let mut source = editor.into_inner();
+
+        let original_len = get_data().into_inner().len();
+        assert_eq!(original_len, source.get_ref().len());
+        assert!(read_all(&mut source).contains(&TestSpec::Block(vec![0x02; 7])));
+    }
+
+    #[test]
+    pub fn replace_grows_into_following_void() {
+        let mut editor: EbmlEditor<_, TestSpec> = EbmlEditor::new(get_data());
+        editor.replace("Segment/Cluster/Block", &TestSpec::Block(vec![0x02; 4])).unwrap();
+        assert!(matches!(editor.replace("Segment/Cluster/Block", &TestSpec::Block(vec![0x03; 9])), Err(EbmlEditorError::InsufficientSpace { required: 11, available: 10 })));
+        editor.replace("Segment/Cluster/Block", &TestSpec::Block(vec![0x03; 8])).unwrap();
+        assert!(matches!(editor.replace("Segment/TrackType", &TestSpec::Count(1)), Err(EbmlEditorError::TagIdMismatch { element_id: 0x83, tag_id: 0x4100 })));
+
+        let mut source = editor.into_inner();
+        let mut expected = get_data();
+        let expected_tags = read_all(&mut expected);
+        let tags = read_all(&mut source);
+        assert_eq!(expected_tags.len(), tags.len());
+        assert!(tags.contains(&TestSpec::Block(vec![0x03; 8])));
+    }
+
+    #[test]
+    pub fn replace_ignores_void_past_parent_end() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Block(vec![0x01; 4]),
+                TestSpec::Void(vec![]),
+            ])),
+            TestSpec::TrackType(0x01),
+        ]))).unwrap();
+        let mut data = writer.into_inner().unwrap();
+
+        // Make the Void claim 3 bytes, which would run past the end of the Cluster into the TrackType
+        let void_position = data.iter().position(|b| *b == 0xec).unwrap();
+        data[void_position + 1] = 0x83;
+
+        let mut editor: EbmlEditor<_, TestSpec> = EbmlEditor::new(Cursor::new(data.clone()));
+        assert!(matches!(editor.replace("Segment/Cluster/Block", &TestSpec::Block(vec![0x02; 6])), Err(EbmlEditorError::InsufficientSpace { required: 8, available: 6 })));
+        assert_eq!(data, editor.into_inner().into_inner());
+    }
+}
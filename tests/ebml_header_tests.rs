@@ -0,0 +1,50 @@
+pub mod ebml_header_tests {
+    use std::io::Cursor;
+
+    use ebml_iterable::iterator::AllowableErrors;
+    use ebml_iterable::specs::ebml_header::{self, EbmlHeader};
+    use ebml_iterable::specs::{EbmlSpecification, Master, PathPart, TagDataType};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    fn header() -> EbmlHeader {
+        EbmlHeader::Ebml(Master::Full(vec![
+            EbmlHeader::EbmlVersion(1),
+            EbmlHeader::EbmlReadVersion(1),
+            EbmlHeader::EbmlMaxIdLength(4),
+            EbmlHeader::EbmlMaxSizeLength(8),
+            EbmlHeader::DocType(String::from("webm")),
+            EbmlHeader::DocTypeVersion(4),
+            EbmlHeader::DocTypeReadVersion(2),
+            EbmlHeader::DocTypeExtension(Master::Full(vec![
+                EbmlHeader::DocTypeExtensionName(String::from("ext")),
+                EbmlHeader::DocTypeExtensionVersion(1),
+            ])),
+            EbmlHeader::Void(vec![0; 3]),
+        ]))
+    }
+
+    #[test]
+    pub fn header_round_trips() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&header()).unwrap();
+        writer.write_raw(0x18538067, &[]).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut reader: TagIterator<_, EbmlHeader> = TagIterator::new(Cursor::new(data), &[EbmlHeader::Ebml(Master::Start)]);
+        reader.allow_errors(&[AllowableErrors::InvalidTagIds]);
+        let tags: Vec<EbmlHeader> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(vec![header(), EbmlHeader::RawTag(0x18538067, vec![])], tags);
+    }
+
+    #[test]
+    pub fn spec_describes_header_elements() {
+        assert_eq!(Some(TagDataType::Utf8), EbmlHeader::get_tag_data_type(ebml_header::DOC_TYPE_ID));
+        assert_eq!(None, EbmlHeader::get_tag_data_type(0x18538067));
+        assert_eq!(&[PathPart::Id(ebml_header::EBML_ID)], EbmlHeader::get_path_by_id(ebml_header::DOC_TYPE_ID));
+        assert_eq!(Some(ebml_header::EBML_MAX_ID_LENGTH_ID), EbmlHeader::get_id_by_name("EbmlMaxIdLength"));
+
+        let max_id_length = EbmlHeader::SPEC_METADATA.iter().find(|m| m.id == ebml_header::EBML_MAX_ID_LENGTH_ID).unwrap();
+        assert_eq!(Some("4"), max_id_length.default);
+        assert_eq!("\\Ebml\\EbmlMaxIdLength", max_id_length.path);
+    }
+}
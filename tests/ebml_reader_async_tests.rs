@@ -0,0 +1,93 @@
+#[cfg(feature = "futures")]
+mod test_spec;
+
+#[cfg(feature = "futures")]
+pub mod ebml_reader_async_tests {
+    use ebml_iterable::error::EbmlReaderError;
+    use ebml_iterable::nonblocking::EbmlReaderAsync;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagWriter, WriteOptions};
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use futures::AsyncReadExt;
+
+    use super::test_spec::TestSpec;
+
+    fn get_data(unknown_sized_cluster: bool) -> Cursor<Vec<u8>> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        if unknown_sized_cluster {
+            writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        } else {
+            writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        }
+        writer.write(&TestSpec::Block((0..64).collect())).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        Cursor::new(writer.into_inner().unwrap())
+    }
+
+    #[test]
+    pub fn open_and_iterate() {
+        for unknown_sized_cluster in [false, true] {
+            let mut reader: EbmlReaderAsync<_, TestSpec> = EbmlReaderAsync::new(get_data(unknown_sized_cluster));
+            let tags = block_on(async {
+                let mut iter = reader.open("Segment/Cluster").await.unwrap().iter().await.unwrap();
+                let mut tags = Vec::new();
+                while let Some(tag) = iter.next().await {
+                    tags.push(tag.unwrap());
+                }
+                tags
+            });
+            assert_eq!(vec![
+                TestSpec::Cluster(Master::Start),
+                TestSpec::Block((0..64).collect()),
+                TestSpec::Cluster(Master::End),
+            ], tags);
+
+            let track_type = block_on(async { reader.open("Segment/TrackType").await.unwrap().buffer().await.unwrap() });
+            assert_eq!(TestSpec::TrackType(0x01), track_type);
+        }
+    }
+
+    #[test]
+    pub fn buffer_and_skip_unknown_size() {
+        let mut reader: EbmlReaderAsync<_, TestSpec> = EbmlReaderAsync::new(get_data(true));
+        block_on(async {
+            let handle = reader.open("Segment/Cluster").await.unwrap();
+            assert_eq!(None, handle.data_size());
+            assert_eq!(TestSpec::Cluster(Master::Full(vec![TestSpec::Block((0..64).collect())])), handle.buffer().await.unwrap());
+
+            let segment_end = reader.open("Segment").await.unwrap().skip().await.unwrap();
+            assert_eq!(reader.get_ref().get_ref().len(), segment_end);
+            assert_eq!(segment_end as u64, reader.get_ref().position());
+        });
+    }
+
+    #[test]
+    pub fn stream_payload_range() {
+        let mut reader: EbmlReaderAsync<_, TestSpec> = EbmlReaderAsync::new(get_data(false));
+        block_on(async {
+            let mut payload = Vec::new();
+            reader.open("Segment/Cluster/Block").await.unwrap().payload_range(10..20).await.unwrap().read_to_end(&mut payload).await.unwrap();
+            assert_eq!((10..20).collect::<Vec<u8>>(), payload);
+
+            let mut payload = Vec::new();
+            reader.open("Segment/Cluster/Block").await.unwrap().payload_range(60..100).await.unwrap().read_to_end(&mut payload).await.unwrap();
+            assert_eq!((60..64).collect::<Vec<u8>>(), payload);
+        });
+    }
+
+    #[test]
+    pub fn missing_elements() {
+        let mut reader: EbmlReaderAsync<_, TestSpec> = EbmlReaderAsync::new(get_data(false));
+        block_on(async {
+            assert!(matches!(reader.open("Segment/Cluster/CueRefCluster").await, Err(EbmlReaderError::ElementNotFound(_))));
+            assert!(matches!(reader.open("Segment/Unknown").await, Err(EbmlReaderError::InvalidPath(_))));
+            assert!(matches!(reader.open_by_ids(&[]).await, Err(EbmlReaderError::InvalidPath(_))));
+        });
+    }
+}
@@ -0,0 +1,116 @@
+mod test_spec;
+
+pub mod ebml_reader_tests {
+    use ebml_iterable::error::EbmlReaderError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{EbmlReader, TagWriter, WriteOptions};
+    use std::io::Cursor;
+
+    use super::test_spec::TestSpec;
+
+    fn get_data(unknown_sized_cluster: bool) -> Cursor<Vec<u8>> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        if unknown_sized_cluster {
+            writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        } else {
+            writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        }
+        writer.write(&TestSpec::Block(vec![0x01; 64])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        drop(writer);
+
+        dest.set_position(0);
+        dest
+    }
+
+    #[test]
+    pub fn open_by_name() {
+        let mut reader: EbmlReader<_, TestSpec> = EbmlReader::new(get_data(false));
+        let tags: Vec<TestSpec> = reader.open("Segment/Cluster").unwrap().iter().unwrap().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Block(vec![0x01; 64]),
+            TestSpec::Cluster(Master::End),
+        ], tags);
+
+        let track_type = reader.open("Segment/TrackType").unwrap().buffer().unwrap();
+        assert_eq!(TestSpec::TrackType(0x01), track_type);
+    }
+
+    #[test]
+    pub fn buffer_and_skip_unknown_size() {
+        let mut reader: EbmlReader<_, TestSpec> = EbmlReader::new(get_data(true));
+        let handle = reader.open("Segment/Cluster").unwrap();
+        assert_eq!(None, handle.data_size());
+        let cluster = handle.buffer().unwrap();
+        assert_eq!(TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x01; 64])])), cluster);
+
+        let handle = reader.open("Segment/Cluster/Block").unwrap();
+        let end = handle.skip().unwrap();
+        let mut source = reader.into_inner();
+        assert_eq!(end as u64, source.position());
+        source.set_position(0);
+
+        let mut reader: EbmlReader<_, TestSpec> = EbmlReader::new(source);
+        assert!(matches!(reader.open("Segment/Cluster/Count"), Err(EbmlReaderError::ElementNotFound(_))));
+        assert!(matches!(reader.open("Segment/Unknown"), Err(EbmlReaderError::InvalidPath(_))));
+    }
+
+    #[test]
+    pub fn rev_top_level() {
+        let mut reader: EbmlReader<_, TestSpec> = EbmlReader::new(get_data(false));
+        let found: Vec<(u64, usize)> = reader.rev_top_level().unwrap().map(|e| e.map(|e| (e.id, e.offset)).unwrap()).collect();
+        let segment = reader.open("Segment").unwrap().offset();
+        assert_eq!(vec![(0x18538067, segment), (0x1a45dfa3, 0)], found);
+    }
+
+    #[test]
+    pub fn rev_children_finds_the_last_cluster() {
+        for unknown_sized_cluster in [false, true] {
+            let mut reader: EbmlReader<_, TestSpec> = EbmlReader::new(get_data(unknown_sized_cluster));
+            let ids: Vec<u64> = reader.rev_children("Segment").unwrap().map(|e| e.unwrap().id).collect();
+            assert_eq!(vec![0x1f43b675, 0x1f43b675, 0x83], ids);
+
+            let last = reader.rev_children("Segment").unwrap().next().unwrap().unwrap();
+            let cluster = reader.open_at(last.offset).unwrap().buffer().unwrap();
+            assert_eq!(TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)])), cluster);
+        }
+    }
+
+    #[test]
+    pub fn rev_children_of_unknown_size() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Count(1)).unwrap();
+        // Data that looks like a TrackType at the end of the segment
+        writer.write(&TestSpec::Block(vec![0x83, 0x81, 0x01])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        drop(writer);
+
+        let mut reader: EbmlReader<_, TestSpec> = EbmlReader::new(dest);
+        let found: Vec<(u64, Option<usize>)> = reader.rev_children("Segment").unwrap().map(|e| e.map(|e| (e.id, e.size.known())).unwrap()).collect();
+        assert_eq!(vec![(0x1f43b675, None), (0x83, Some(1))], found);
+        assert_eq!(1, reader.rev_top_level().unwrap().count());
+    }
+
+    #[test]
+    pub fn rev_stops_at_unrecognized_data() {
+        let mut data = get_data(false).into_inner();
+        data.extend([0x00, 0x00, 0x00]);
+        let mut reader: EbmlReader<_, TestSpec> = EbmlReader::new(Cursor::new(data));
+        let mut iter = reader.rev_top_level().unwrap();
+        assert!(matches!(iter.next(), Some(Err(EbmlReaderError::UnrecognizedData(_)))));
+        assert!(iter.next().is_none());
+    }
+}
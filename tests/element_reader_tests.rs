@@ -0,0 +1,125 @@
+mod test_spec;
+
+pub mod element_reader_tests {
+    use std::io::{Cursor, Read};
+
+    use ebml_iterable::error::{PartialTag, TagIteratorError};
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Full(
+            (0..10u8).map(|i| TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Count(i as u64),
+                TestSpec::Block((0..(i as usize * 1000)).map(|b| b as u8).collect()),
+            ]))).collect()
+        ))).expect("Test shouldn't error");
+        dest.into_inner()
+    }
+
+    ///
+    /// Reads every tag, taking blocks through an [`ElementReader`] (reading `read_len` bytes of each block when given, or all of it otherwise).
+    ///
+    fn read_with_element_reader<R: Read>(iter: &mut TagIterator<R, TestSpec>, read_len: Option<usize>) -> Vec<(TestSpec, usize)> {
+        let mut tags = Vec::new();
+        loop {
+            let payload_tag = match iter.next_element_reader().expect("Test shouldn't error") {
+                Some(mut reader) => {
+                    let mut payload = Vec::new();
+                    match read_len {
+                        Some(len) => { (&mut reader).take(len as u64).read_to_end(&mut payload).unwrap(); },
+                        None => { reader.read_to_end(&mut payload).unwrap(); },
+                    }
+                    Some(if reader.tag_id() == 0xa1 { TestSpec::Block(payload) } else { TestSpec::Count(payload.iter().fold(0, |acc, b| (acc << 8) + *b as u64)) })
+                },
+                None => None,
+            };
+
+            if let Some(tag) = payload_tag {
+                tags.push((tag, iter.last_emitted_tag_offset()));
+            } else if let Some(tag) = iter.next() {
+                tags.push((tag.expect("Test shouldn't error"), iter.last_emitted_tag_offset()));
+            } else {
+                return tags;
+            }
+        }
+    }
+
+    #[test]
+    pub fn payloads_match_iterated_tags() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let expected: Vec<(TestSpec, usize)> = std::iter::from_fn(|| iter.next().map(|t| (t.unwrap(), iter.last_emitted_tag_offset()))).collect();
+
+        for capacity in [16, 64, 4096, 100_000] {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&data[..], &[], capacity);
+            assert_eq!(expected, read_with_element_reader(&mut iter, None));
+        }
+    }
+
+    #[test]
+    pub fn unread_payload_is_skipped() {
+        let data = get_data();
+        let expected: Vec<TestSpec> = TagIterator::<_, TestSpec>::new(&data[..], &[]).map(|t| t.unwrap()).filter(|t| t.as_master().is_some()).collect();
+
+        for capacity in [16, 4096] {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&data[..], &[], capacity);
+            let tags = read_with_element_reader(&mut iter, Some(10));
+            for (tag, _) in tags.iter() {
+                if let TestSpec::Block(payload) = tag {
+                    assert!(payload.len() <= 10);
+                }
+            }
+            let masters: Vec<TestSpec> = tags.into_iter().map(|(t, _)| t).filter(|t| t.as_master().is_some()).collect();
+            assert_eq!(expected, masters);
+        }
+    }
+
+    #[test]
+    pub fn masters_are_not_readable() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        assert!(iter.next_element_reader().unwrap().is_none());
+        assert_eq!(TestSpec::Segment(Master::Start), iter.next().unwrap().unwrap());
+    }
+
+    #[test]
+    pub fn truncated_payload() {
+        let data = get_data();
+        let data = &data[..data.len() - 100];
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(data, &[], 16);
+        let mut last_error = None;
+        loop {
+            let read_payload = match iter.next_element_reader().unwrap() {
+                Some(mut reader) => {
+                    if let Err(err) = reader.read_to_end(&mut Vec::new()) {
+                        last_error = Some(err);
+                    }
+                    true
+                },
+                None => false,
+            };
+            if !read_payload && iter.next().is_none() {
+                break;
+            }
+        }
+        assert_eq!(std::io::ErrorKind::UnexpectedEof, last_error.unwrap().kind());
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(data, &[], 16);
+        loop {
+            let is_payload = iter.next_element_reader().unwrap().is_some();
+            if !is_payload {
+                match iter.next() {
+                    Some(Err(TagIteratorError::UnexpectedEOF(PartialTag { id: Some(0xa1), .. }))) => break,
+                    Some(Ok(_)) => {},
+                    other => panic!("Expected truncated block error, got {:?}", other),
+                }
+            }
+        }
+    }
+}
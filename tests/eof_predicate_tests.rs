@@ -0,0 +1,79 @@
+mod test_spec;
+
+pub mod eof_predicate_tests {
+    use std::cell::RefCell;
+    use std::io::Read;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    // A source that only returns the data appended to it so far
+    struct GrowingSource {
+        data: Rc<RefCell<Vec<u8>>>,
+        position: usize,
+    }
+
+    impl Read for GrowingSource {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let data = self.data.borrow();
+            let len = buf.len().min(data.len() - self.position);
+            buf[..len].copy_from_slice(&data[self.position..(self.position + len)]);
+            self.position += len;
+            Ok(len)
+        }
+    }
+
+    fn encode(tags: &[TestSpec]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&tags[0], WriteOptions::is_unknown_sized_element()).unwrap();
+        for tag in &tags[1..] {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn waits_for_more_data_until_complete() {
+        let mut full = encode(&[TestSpec::Segment(Master::Start), TestSpec::TrackType(1), TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))]);
+        let rest = full.split_off(15);
+        let data = Rc::new(RefCell::new(full));
+        let complete = Arc::new(AtomicBool::new(false));
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(GrowingSource { data: Rc::clone(&data), position: 0 }, &[]);
+        let is_complete = Arc::clone(&complete);
+        iter.set_eof_predicate(move |_| is_complete.load(Ordering::SeqCst));
+
+        assert_eq!(TestSpec::Segment(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::TrackType(1), iter.next().unwrap().unwrap());
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::NeedMoreData { position: 15 }))));
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::NeedMoreData { position: 15 }))));
+
+        data.borrow_mut().extend(rest);
+        assert_eq!(TestSpec::Cluster(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Count(1), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Cluster(Master::End), iter.next().unwrap().unwrap());
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::NeedMoreData { position: 24 }))));
+
+        complete.store(true, Ordering::SeqCst);
+        assert_eq!(TestSpec::Segment(Master::End), iter.next().unwrap().unwrap());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    pub fn predicate_only_applies_to_unknown_size_masters() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(1)]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.set_eof_predicate(|_| false);
+        let tags: Vec<TestSpec> = iter.collect::<Result<_, _>>().unwrap();
+        assert_eq!(vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(1), TestSpec::Segment(Master::End)], tags);
+    }
+}
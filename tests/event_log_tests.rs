@@ -0,0 +1,83 @@
+mod test_spec;
+
+pub mod event_log_tests {
+    use ebml_iterable::iterator::{EndCause, ElementSize, ParseEvent};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn parser_decisions_are_recorded() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.record_event_log(Some(100));
+        assert_eq!(7, iter.by_ref().filter(|t| t.is_ok()).count());
+
+        let events: Vec<ParseEvent> = iter.event_log().unwrap().events().cloned().collect();
+        assert_eq!(vec![
+            ParseEvent::Header { offset: 0, id: 0x18538067, header_len: 12, size: ElementSize::Unknown },
+            ParseEvent::Push { offset: 0, id: 0x18538067, level: 0 },
+            ParseEvent::Header { offset: 12, id: 0x1f43b675, header_len: 5, size: ElementSize::Known(4) },
+            ParseEvent::Push { offset: 12, id: 0x1f43b675, level: 1 },
+            ParseEvent::Header { offset: 17, id: 0x4100, header_len: 3, size: ElementSize::Known(1) },
+            ParseEvent::Pop { offset: 21, id: 0x1f43b675, level: 1, cause: EndCause::SizeReached },
+            ParseEvent::Header { offset: 21, id: 0x1a45dfa3, header_len: 5, size: ElementSize::Known(0) },
+            ParseEvent::Pop { offset: 21, id: 0x18538067, level: 0, cause: EndCause::EndedBy(0x1a45dfa3) },
+            ParseEvent::Push { offset: 21, id: 0x1a45dfa3, level: 0 },
+            ParseEvent::Pop { offset: 26, id: 0x1a45dfa3, level: 0, cause: EndCause::SizeReached },
+        ], events);
+    }
+
+    #[test]
+    pub fn only_recent_events_are_kept() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.record_event_log(Some(3));
+        iter.by_ref().for_each(|t| { t.unwrap(); });
+
+        let log = iter.event_log().unwrap();
+        assert_eq!(3, log.len());
+        assert_eq!(7, log.dropped());
+        let dump = log.to_string();
+        assert!(dump.starts_with("(7 earlier events dropped)\n"), "{}", dump);
+        assert_eq!(4, dump.lines().count());
+    }
+
+    #[test]
+    pub fn errors_and_recovery_are_recorded() {
+        let mut data = get_data();
+        data.splice(12..12, [0x00, 0x00]);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.record_event_log(Some(100));
+        let mut errors = 0;
+        while let Some(tag) = iter.next() {
+            if tag.is_err() {
+                errors += 1;
+                iter.try_recover().unwrap();
+            }
+        }
+        assert_eq!(1, errors);
+
+        let events: Vec<&ParseEvent> = iter.event_log().unwrap().events().collect();
+        let error = events.iter().position(|e| matches!(e, ParseEvent::Error { offset: 12, .. })).unwrap();
+        assert_eq!(&ParseEvent::RecoveryScan { skipped: 12..14, found_header: true }, events[error + 1]);
+    }
+
+    #[test]
+    pub fn nothing_is_recorded_by_default() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.by_ref().for_each(|t| { t.unwrap(); });
+        assert!(iter.event_log().is_none());
+    }
+}
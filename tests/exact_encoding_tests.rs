@@ -0,0 +1,87 @@
+mod test_spec;
+
+pub mod exact_encoding_tests {
+    use ebml_iterable::iterator::{ElementSize, TagEncoding};
+    use ebml_iterable::specs::{EbmlSpecification, EbmlTag, Master};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn round_trip<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(data: &[u8]) -> Vec<u8> {
+        let mut iter: TagIterator<_, TSpec> = TagIterator::new(data, &[]);
+        let mut writer = TagWriter::new(Vec::new());
+        while let Some(tag) = iter.next() {
+            writer.write_advanced(&tag.unwrap(), WriteOptions::with_encoding(iter.last_emitted_tag_encoding())).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn reports_encoding() {
+        let data = [0x18, 0x53, 0x80, 0x67, 0xff, 0x83, 0x40, 0x03, 0x00, 0x00, 0x05];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+
+        assert_eq!(TestSpec::Segment(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(TagEncoding { size: ElementSize::Unknown, size_length: 1 }, iter.last_emitted_tag_encoding());
+        assert_eq!(TestSpec::TrackType(5), iter.next().unwrap().unwrap());
+        assert_eq!(TagEncoding { size: ElementSize::Known(3), size_length: 2 }, iter.last_emitted_tag_encoding());
+    }
+
+    #[test]
+    pub fn round_trip_is_byte_identical() {
+        let data = vec![
+            0x81, 0x86, 0x41, 0x02, 0x83, b'a', 0x00, 0x00,
+            0x18, 0x53, 0x80, 0x67, 0xff,
+                0x83, 0x40, 0x03, 0x00, 0x00, 0x05,
+                0x1f, 0x43, 0xb6, 0x75, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x14,
+                    0x41, 0x00, 0x80,
+                    0xa1, 0x10, 0x00, 0x00, 0x02, 0xaa, 0xbb,
+                    0x97, 0x88, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+                0x1f, 0x43, 0xb6, 0x75, 0x7f, 0xff,
+                    0x41, 0x00, 0x81, 0x07,
+        ];
+
+        assert_eq!(data, round_trip::<TestSpec>(&data));
+    }
+
+    #[test]
+    pub fn modified_tags_fall_back_to_default_encoding() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::with_encoding(TagEncoding { size: ElementSize::Known(4), size_length: 1 })).unwrap();
+        writer.write_advanced(&TestSpec::Count(1000), WriteOptions::with_encoding(TagEncoding { size: ElementSize::Known(1), size_length: 1 })).unwrap();
+        writer.write_advanced(&TestSpec::Block(vec![0; 200]), WriteOptions::with_encoding(TagEncoding { size: ElementSize::Known(2), size_length: 1 })).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+
+        let mut expected = TagWriter::new(Vec::new());
+        expected.write(&TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1000), TestSpec::Block(vec![0; 200])]))]))).unwrap();
+        assert_eq!(expected.into_inner().unwrap(), writer.into_inner().unwrap());
+    }
+
+    #[cfg(feature = "derive-spec")]
+    mod values {
+        use ebml_iterable::specs::{easy_ebml, TagDataType};
+
+        easy_ebml! {
+            #[derive(Clone, Debug, PartialEq)]
+            pub enum Values {
+                Root            : Master = 0x81,
+                Root/Signed     : Integer = 0x82,
+                Root/Decimal    : Float = 0x83,
+            }
+        }
+
+        #[test]
+        pub fn round_trip_keeps_value_widths() {
+            let data = vec![
+                0x81, 0x8d,
+                    0x82, 0x83, 0xff, 0xff, 0xfe,
+                    0x83, 0x84, 0x3f, 0xc0, 0x00, 0x00,
+                    0x83, 0x80,
+            ];
+
+            assert_eq!(data, super::round_trip::<Values>(&data));
+        }
+    }
+}
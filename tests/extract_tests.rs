@@ -0,0 +1,64 @@
+mod test_spec;
+
+pub mod extract_tests {
+    use ebml_iterable::error::ExtractError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::extract;
+    use ebml_iterable::{TagWriter, WriteOptions};
+    use std::io::Cursor;
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Cursor<Vec<u8>> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![
+            TestSpec::Block(vec![0x01; 10]),
+            TestSpec::Count(1),
+        ]))).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Block(vec![0x02; 20000])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        drop(writer);
+
+        dest.set_position(0);
+        dest
+    }
+
+    fn expected_blocks() -> Vec<u8> {
+        let mut expected = vec![0x01; 10];
+        expected.extend_from_slice(&[0x02; 20000]);
+        expected
+    }
+
+    #[test]
+    pub fn extract_by_path() {
+        let mut sink = Vec::new();
+        let count = extract::<TestSpec, _, _>(get_data(), "Segment/Cluster/Block", &mut sink).unwrap();
+        assert_eq!(2, count);
+        assert_eq!(expected_blocks(), sink);
+
+        let mut sink = Vec::new();
+        let count = extract::<TestSpec, _, _>(get_data(), "Segment/TrackType", &mut sink).unwrap();
+        assert_eq!(1, count);
+        assert_eq!(vec![0x01], sink);
+    }
+
+    #[test]
+    pub fn extract_by_id() {
+        let mut sink = Vec::new();
+        let count = extract::<TestSpec, _, _>(get_data(), 0xa1, &mut sink).unwrap();
+        assert_eq!(2, count);
+        assert_eq!(expected_blocks(), sink);
+    }
+
+    #[test]
+    pub fn extract_errors() {
+        assert!(matches!(extract::<TestSpec, _, _>(get_data(), "Segment/Nothing", Vec::new()), Err(ExtractError::InvalidPath(_))));
+        assert!(matches!(extract::<TestSpec, _, _>(get_data(), 0x1F43B675, Vec::new()), Err(ExtractError::UnknownSize { tag_id: 0x1F43B675, position: _ })));
+    }
+}
@@ -0,0 +1,130 @@
+mod test_spec;
+
+pub mod finalize_tests {
+    use ebml_iterable::error::FinalizeError;
+    use ebml_iterable::iterator::ElementSize;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{finalize_sizes, finalize_sizes_two_pass};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Count(1)).unwrap();
+        writer.write(&TestSpec::Block(vec![0x01; 200])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0x02; 5])]))).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Count(3)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn read_sized(data: &[u8]) -> Vec<(TestSpec, ElementSize)> {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(data, &[]);
+        let mut tags = Vec::new();
+        while let Some(tag) = iter.next() {
+            let tag = tag.unwrap();
+            let size = iter.last_emitted_tag_size();
+            tags.push((tag, size));
+        }
+        tags
+    }
+
+    fn assert_finalized(data: &[u8], finalized: &[u8]) {
+        let expected: Vec<TestSpec> = read_sized(data).into_iter().map(|(tag, _)| tag).collect();
+        let found = read_sized(finalized);
+        assert!(found.iter().all(|(_, size)| matches!(size, ElementSize::Known(_))), "{:?}", found);
+        assert_eq!(expected, found.into_iter().map(|(tag, _)| tag).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn one_pass_gives_exact_sizes() {
+        let data = get_data();
+        let mut dest = Cursor::new(Vec::new());
+        let written = finalize_sizes::<TestSpec, _, _>(&data[..], &mut dest).unwrap();
+        assert_eq!(dest.get_ref().len() as u64, written);
+        assert_finalized(&data, dest.get_ref());
+    }
+
+    #[test]
+    pub fn two_pass_gives_exact_sizes() {
+        let data = get_data();
+        let mut dest = Vec::new();
+        let written = finalize_sizes_two_pass::<TestSpec, _, _>(Cursor::new(&data), &mut dest).unwrap();
+        assert_eq!(dest.len() as u64, written);
+        assert_finalized(&data, &dest);
+
+        // Sizes are written with as few bytes as possible
+        assert!(dest.len() < data.len() + 3 * 7);
+        let mut one_pass = Cursor::new(Vec::new());
+        finalize_sizes::<TestSpec, _, _>(&data[..], &mut one_pass).unwrap();
+        assert!(dest.len() < one_pass.get_ref().len());
+    }
+
+    #[test]
+    pub fn documents_with_known_sizes_are_unchanged() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 10])])),
+        ]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut dest = Cursor::new(Vec::new());
+        finalize_sizes::<TestSpec, _, _>(&data[..], &mut dest).unwrap();
+        assert_eq!(data, dest.into_inner());
+
+        let mut dest = Vec::new();
+        finalize_sizes_two_pass::<TestSpec, _, _>(Cursor::new(&data), &mut dest).unwrap();
+        assert_eq!(data, dest);
+    }
+
+    #[test]
+    pub fn two_pass_starts_from_the_source_position() {
+        let data = get_data();
+        let mut source = Cursor::new([vec![0xec, 0x81, 0x00], data.clone()].concat());
+        source.seek(SeekFrom::Start(3)).unwrap();
+        let mut dest = Vec::new();
+        finalize_sizes_two_pass::<TestSpec, _, _>(&mut source, &mut dest).unwrap();
+        assert_finalized(&data, &dest);
+    }
+
+    struct Changing {
+        data: Vec<u8>,
+        position: usize,
+    }
+
+    impl Read for Changing {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.data.len() - self.position);
+            buf[..len].copy_from_slice(&self.data[self.position..(self.position + len)]);
+            self.position += len;
+            Ok(len)
+        }
+    }
+
+    impl Seek for Changing {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            if let SeekFrom::Start(position) = pos {
+                // The capture grows before it's read again
+                self.position = position as usize;
+                self.data.extend([0xa1, 0x82, 0x03, 0x03]);
+            }
+            Ok(self.position as u64)
+        }
+    }
+
+    #[test]
+    pub fn two_pass_detects_changed_sources() {
+        let mut source = Changing { data: get_data(), position: 0 };
+        let result = finalize_sizes_two_pass::<TestSpec, _, _>(&mut source, Vec::new());
+        assert!(matches!(result, Err(FinalizeError::SourceChanged)), "{:?}", result);
+    }
+}
@@ -0,0 +1,58 @@
+mod test_spec;
+
+pub mod flatten_values_tests {
+    use ebml_iterable::iterator::FlatValue;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    const SEGMENT: u64 = 0x18538067;
+    const CLUSTER: u64 = 0x1F43B675;
+
+    fn document() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0x03])]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(4)]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::String(String::from("root"))]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn expected() -> Vec<(Vec<u64>, FlatValue)> {
+        vec![
+            (vec![SEGMENT, 0x83], FlatValue::UnsignedInt(1)),
+            (vec![SEGMENT, CLUSTER, 0x4100], FlatValue::UnsignedInt(2)),
+            (vec![SEGMENT, CLUSTER, 0xa1], FlatValue::Binary(vec![0x03])),
+            (vec![SEGMENT, CLUSTER, 0x4100], FlatValue::UnsignedInt(4)),
+            (vec![0x81, 0x4102], FlatValue::Utf8(String::from("root"))),
+        ]
+    }
+
+    #[test]
+    pub fn flattens_streamed_masters() {
+        let data = document();
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let values: Vec<(Vec<u64>, FlatValue)> = iter.flatten_values().collect::<Result<_, _>>().unwrap();
+        assert_eq!(expected(), values);
+    }
+
+    #[test]
+    pub fn flattens_buffered_masters() {
+        let data = document();
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[TestSpec::Cluster(Master::Start), TestSpec::Root(Master::Start)]);
+        let values: Vec<(Vec<u64>, FlatValue)> = iter.flatten_values().collect::<Result<_, _>>().unwrap();
+        assert_eq!(expected(), values);
+    }
+
+    #[test]
+    pub fn infers_parents_mid_document() {
+        let data = document();
+        let count_offset = data.windows(3).position(|w| w == [0x41, 0x00, 0x81]).unwrap();
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(&data[count_offset..], &[]);
+        let values: Vec<(Vec<u64>, FlatValue)> = iter.flatten_values().collect::<Result<_, _>>().unwrap();
+        assert_eq!(expected()[1..], values[..]);
+    }
+}
@@ -0,0 +1,77 @@
+mod test_spec;
+
+pub mod fork_tests {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    use ebml_iterable::iterator::ForkableSource;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(
+            (0..40u8).map(|i| TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i as u64), TestSpec::Block(vec![i; 5_000])]))).collect()
+        ))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn forked_iterators_read_independently() {
+        let data = get_data();
+        let expected: Vec<TestSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+
+        let mut first: TagIterator<_, TestSpec> = TagIterator::new(ForkableSource::new(Cursor::new(data)), &[]);
+        assert_eq!(expected[..3], first.by_ref().take(3).map(|t| t.unwrap()).collect::<Vec<_>>()[..]);
+
+        let mut second = first.fork();
+        assert_eq!(first.last_emitted_tag_offset(), second.last_emitted_tag_offset());
+
+        // Interleave the two iterators, each reading at its own pace
+        let mut from_first = Vec::new();
+        let mut from_second = Vec::new();
+        loop {
+            let a = first.next();
+            let b = second.next();
+            let c = second.next();
+            if a.is_none() && b.is_none() && c.is_none() {
+                break;
+            }
+            from_first.extend(a.map(|t| t.unwrap()));
+            from_second.extend(b.into_iter().chain(c).map(|t| t.unwrap()));
+        }
+        assert_eq!(expected[3..], from_first[..]);
+        assert_eq!(expected[3..], from_second[..]);
+    }
+
+    #[test]
+    pub fn fork_keeps_buffered_tags() {
+        let data = get_data();
+        let mut first: TagIterator<_, TestSpec> = TagIterator::new(ForkableSource::new(Cursor::new(data)), &[TestSpec::Cluster(Master::Start)]);
+        first.next();
+        first.next();
+        let mut second = first.fork();
+        assert_eq!(first.next().unwrap().unwrap(), second.next().unwrap().unwrap());
+        assert!(matches!(second.next(), Some(Ok(TestSpec::Cluster(Master::Full(_))))));
+    }
+
+    #[test]
+    pub fn cloned_sources_have_independent_positions() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+        let mut first = ForkableSource::with_cached_blocks(Cursor::new(data.clone()), 1);
+        let mut second = first.clone();
+
+        second.seek(SeekFrom::Start(150_000)).unwrap();
+        let mut a = [0u8; 10];
+        let mut b = [0u8; 10];
+        first.read_exact(&mut a).unwrap();
+        second.read_exact(&mut b).unwrap();
+        assert_eq!(data[..10], a);
+        assert_eq!(data[150_000..150_010], b);
+
+        let mut rest = Vec::new();
+        first.read_to_end(&mut rest).unwrap();
+        assert_eq!(data[10..], rest[..]);
+    }
+}
@@ -0,0 +1,201 @@
+#[cfg(feature = "derive-spec")]
+pub mod global_elements_tests {
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError, TagWriterError};
+    use ebml_iterable::specs::{easy_ebml, ebml_specification, EbmlSpecification, Master, PathPart, TagDataType};
+    use ebml_iterable::utils::{can_contain, is_valid_path};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[global_elements((1-)/Checksum: Binary = 0x4dbb, (-)/Padding: Binary = 0x6dbb, (1-)/Container: Master = 0x6dbc)]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Protocol {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        Root,
+
+        #[id(0x82)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root)]
+        Parent,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root/Parent)]
+        Count,
+
+        #[id(0x4201)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path((1-)/Container)]
+        Label,
+    }
+
+    #[ebml_specification]
+    #[global_elements((1-1)/Crc32: Binary = 0xbf)]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum LevelOne {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        Root,
+
+        #[id(0x82)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root)]
+        Parent,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root/Parent)]
+        Count,
+    }
+
+    easy_ebml! {
+        #[global_elements((0-1)/Crc32: Binary = 0xbf)]
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum EasyProtocol {
+            Root            : Master = 0x81,
+            Root/Count      : UnsignedInt = 0x4100,
+        }
+    }
+
+    #[test]
+    pub fn declares_global_paths() {
+        assert_eq!(Some(TagDataType::Binary), Protocol::get_tag_data_type(0x4dbb));
+        assert_eq!(&[PathPart::Global((Some(1), None))], Protocol::get_path_by_id(0x4dbb));
+        assert_eq!(&[PathPart::Global((None, None))], Protocol::get_path_by_id(0x6dbb));
+        assert_eq!(&[PathPart::Global((Some(1), None)), PathPart::Id(0x6dbc)], Protocol::get_path_by_id(0x4201));
+        assert_eq!(Some("Padding"), Protocol::get_name_by_id(0x6dbb));
+
+        // The default global elements are still added
+        assert_eq!(&[PathPart::Global((Some(1), None))], Protocol::get_path_by_id(0xbf));
+        assert_eq!(&[PathPart::Global((Some(0), Some(1)))], EasyProtocol::get_path_by_id(0xbf));
+        assert_eq!(Some(TagDataType::Binary), EasyProtocol::get_tag_data_type(0xec));
+    }
+
+    #[test]
+    pub fn globals_are_valid_in_any_parent() {
+        let root = Protocol::Root(Master::Full(vec![
+            Protocol::Checksum(vec![1, 2]),
+            Protocol::Parent(Master::Full(vec![
+                Protocol::Padding(vec![0; 3]),
+                Protocol::Count(5),
+                Protocol::Container(Master::Full(vec![Protocol::Label(String::from("label"))])),
+            ])),
+        ]));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&Protocol::Padding(vec![0; 2])).unwrap();
+        writer.write(&root).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, Protocol> = TagIterator::new(&data[..], &[Protocol::Root(Master::Start)]);
+        assert_eq!(Protocol::Padding(vec![0; 2]), iter.next().unwrap().unwrap());
+        assert_eq!(root, iter.next().unwrap().unwrap());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    pub fn global_levels_are_checked() {
+        assert!(!is_valid_path::<LevelOne>(0xbf, &[]));
+        assert!(is_valid_path::<LevelOne>(0xbf, &[0x81]));
+        assert!(!is_valid_path::<LevelOne>(0xbf, &[0x81, 0x82]));
+
+        assert!(is_valid_path::<EasyProtocol>(0xbf, &[]));
+        assert!(is_valid_path::<EasyProtocol>(0xbf, &[0x81]));
+        assert!(!is_valid_path::<EasyProtocol>(0xbf, &[0x81, 0x81]));
+
+        assert!(!is_valid_path::<Protocol>(0x4dbb, &[]));
+        assert!(is_valid_path::<Protocol>(0x4dbb, &[0x81, 0x82]));
+        assert!(is_valid_path::<Protocol>(0x6dbb, &[]));
+        assert!(!is_valid_path::<Protocol>(0x4100, &[0x81]));
+        assert!(!is_valid_path::<Protocol>(0x9999, &[]));
+    }
+
+    #[test]
+    pub fn placeholders_do_not_count_the_named_parent() {
+        // `(1-)/Container/Label` needs one ancestor besides the Container itself
+        assert!(!is_valid_path::<Protocol>(0x4201, &[0x6dbc]));
+        assert!(is_valid_path::<Protocol>(0x4201, &[0x81, 0x6dbc]));
+        assert!(is_valid_path::<Protocol>(0x4201, &[0x81, 0x82, 0x6dbc]));
+        // The placeholder can also stand for another element with the same id as the parent
+        assert!(is_valid_path::<Protocol>(0x4201, &[0x81, 0x6dbc, 0x6dbc]));
+        assert!(!is_valid_path::<Protocol>(0x4201, &[0x81, 0x6dbc, 0x82]));
+    }
+
+    #[test]
+    pub fn containment_follows_paths() {
+        assert!(can_contain::<Protocol>(0x81, 0x82));
+        assert!(can_contain::<Protocol>(0x82, 0x4100));
+        assert!(!can_contain::<Protocol>(0x81, 0x4100));
+        assert!(!can_contain::<Protocol>(0x82, 0x81));
+
+        // Not masters, or not in the spec
+        assert!(!can_contain::<Protocol>(0x4100, 0x4dbb));
+        assert!(!can_contain::<Protocol>(0x81, 0x9999));
+        assert!(!can_contain::<Protocol>(0x9999, 0x81));
+    }
+
+    #[test]
+    pub fn containment_follows_global_levels() {
+        // `(1-)` globals can be in any master, `(-)` globals too
+        assert!(can_contain::<Protocol>(0x81, 0x4dbb));
+        assert!(can_contain::<Protocol>(0x82, 0x4dbb));
+        assert!(can_contain::<Protocol>(0x6dbc, 0x6dbb));
+
+        // Label is declared below the global Container, wherever that is
+        assert!(can_contain::<Protocol>(0x6dbc, 0x4201));
+        assert!(can_contain::<Protocol>(0x6dbc, 0x6dbc));
+        assert!(!can_contain::<Protocol>(0x82, 0x4201));
+
+        // `(1-1)/Crc32` is only allowed in top-level elements
+        assert!(can_contain::<LevelOne>(0x81, 0xbf));
+        assert!(!can_contain::<LevelOne>(0x82, 0xbf));
+        // `(0-1)/Crc32` can also be in top-level elements
+        assert!(can_contain::<EasyProtocol>(0x81, 0xbf));
+    }
+
+    #[test]
+    pub fn writer_enforces_global_levels() {
+        let mut writer = TagWriter::new(Vec::new());
+        assert!(matches!(writer.write(&LevelOne::Crc32(vec![0; 4])), Err(TagWriterError::UnexpectedTag { tag_id: 0xbf, .. })));
+        writer.write(&LevelOne::Root(Master::Start)).unwrap();
+        writer.write(&LevelOne::Crc32(vec![0; 4])).unwrap();
+        writer.write(&LevelOne::Parent(Master::Start)).unwrap();
+        assert!(matches!(writer.write(&LevelOne::Crc32(vec![0; 4])), Err(TagWriterError::UnexpectedTag { tag_id: 0xbf, .. })));
+    }
+
+    #[test]
+    pub fn reader_enforces_global_levels() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&LevelOne::Root(Master::Start)).unwrap();
+        writer.write(&LevelOne::Crc32(vec![0; 4])).unwrap();
+        writer.write(&LevelOne::Parent(Master::Start)).unwrap();
+        writer.write_raw(0xbf, &[0; 4]).unwrap();
+        writer.write(&LevelOne::Count(1)).unwrap();
+        writer.write(&LevelOne::Parent(Master::End)).unwrap();
+        writer.write(&LevelOne::Root(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, LevelOne> = TagIterator::new(&data[..], &[]);
+        assert_eq!(LevelOne::Root(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(LevelOne::Crc32(vec![0; 4]), iter.next().unwrap().unwrap());
+        assert_eq!(LevelOne::Parent(Master::Start), iter.next().unwrap().unwrap());
+        assert!(matches!(iter.next().unwrap(), Err(TagIteratorError::CorruptedFileData(CorruptedFileError::HierarchyError { found_tag_id: 0xbf, current_parent_id: Some(0x82) }))));
+    }
+
+    #[test]
+    pub fn nested_containers_are_read() {
+        let root = Protocol::Root(Master::Full(vec![
+            Protocol::Container(Master::Full(vec![
+                Protocol::Container(Master::Full(vec![Protocol::Label(String::from("inner"))])),
+                Protocol::Label(String::from("outer")),
+            ])),
+        ]));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&root).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let tags: Vec<Protocol> = TagIterator::new(&data[..], &[Protocol::Root(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![root], tags);
+    }
+}
@@ -0,0 +1,105 @@
+mod test_spec;
+
+pub mod handler_tests {
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::utils::parse_with_handler;
+    use ebml_iterable::{Handler, HandlerAction, TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<(&'static str, TestSpec, usize)>,
+        errors: usize,
+        stop_on_id: Option<u64>,
+        recover: bool,
+    }
+
+    impl Recorder {
+        fn record(&mut self, event: &'static str, tag: TestSpec, offset: usize) -> HandlerAction {
+            let stop = self.stop_on_id == Some(tag.get_id());
+            self.events.push((event, tag, offset));
+            if stop { HandlerAction::Stop } else { HandlerAction::Continue }
+        }
+    }
+
+    impl Handler<TestSpec> for Recorder {
+        fn on_master_start(&mut self, tag: &TestSpec, offset: usize) -> HandlerAction {
+            self.record("start", tag.clone(), offset)
+        }
+
+        fn on_tag(&mut self, tag: TestSpec, offset: usize) -> HandlerAction {
+            self.record("tag", tag, offset)
+        }
+
+        fn on_master_end(&mut self, tag: &TestSpec, offset: usize) -> HandlerAction {
+            self.record("end", tag.clone(), offset)
+        }
+
+        fn on_error(&mut self, _error: &TagIteratorError) -> HandlerAction {
+            self.errors += 1;
+            if self.recover { HandlerAction::Continue } else { HandlerAction::Stop }
+        }
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![3; 10])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(4)])),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn events_match_iterator() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let expected: Vec<(&'static str, TestSpec, usize)> = std::iter::from_fn(|| iter.next().map(|t| {
+            let tag = t.unwrap();
+            let event = match tag.as_master() {
+                Some(Master::Start) => "start",
+                Some(Master::End) => "end",
+                _ => "tag",
+            };
+            (event, tag, iter.last_emitted_tag_offset())
+        })).collect();
+
+        let mut recorder = Recorder::default();
+        parse_with_handler(&data[..], &mut recorder).unwrap();
+        assert_eq!(expected, recorder.events);
+        assert_eq!(0, recorder.errors);
+    }
+
+    #[test]
+    pub fn stops_when_asked() {
+        let data = get_data();
+        let mut recorder = Recorder { stop_on_id: Some(0x4100), ..Default::default() };
+        parse_with_handler(&data[..], &mut recorder).unwrap();
+        assert_eq!(Some(&("tag", TestSpec::Count(2), 13)), recorder.events.last());
+        assert_eq!(4, recorder.events.len());
+    }
+
+    #[test]
+    pub fn recovers_from_errors() {
+        let mut data = TagWriter::new(Vec::new());
+        data.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        let mut data = data.into_inner().unwrap();
+        data.extend_from_slice(&[0x00, 0x00, 0x00]);
+        data.extend_from_slice(&get_data());
+
+        let mut recorder = Recorder::default();
+        let result = parse_with_handler(&data[..], &mut recorder);
+        assert!(matches!(result, Err(TagIteratorError::CorruptedFileData(_))));
+        assert_eq!(1, recorder.errors);
+        assert_eq!(2, recorder.events.len());
+
+        let mut recorder = Recorder { recover: true, ..Default::default() };
+        parse_with_handler(&data[..], &mut recorder).unwrap();
+        assert_eq!(1, recorder.errors);
+        assert_eq!(TestSpec::Segment(Master::Start), recorder.events[2].1);
+        assert_eq!(TestSpec::Segment(Master::End), recorder.events.last().unwrap().1);
+    }
+}
@@ -0,0 +1,112 @@
+mod test_spec;
+
+pub mod header_event_tests {
+    use std::io::Read;
+
+    use ebml_iterable::iterator::ReadEvent;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(
+            (0..10u8).map(|i| TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Count(i as u64),
+                TestSpec::Block((0..(i as usize * 1000)).map(|b| b as u8).collect()),
+            ]))).collect()
+        ))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn large_payloads_are_returned_as_headers() {
+        let data = get_data();
+        let expected: Vec<TestSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+
+        for capacity in [16, 4096, 100_000] {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&data[..], &[], capacity);
+            iter.set_header_event_threshold(Some(2500));
+            let mut tags = Vec::new();
+            let mut headers = 0;
+            while let Some(event) = iter.next_event() {
+                match event.unwrap() {
+                    ReadEvent::Tag(tag) => {
+                        assert!(!matches!(&tag, TestSpec::Block(payload) if payload.len() >= 2500));
+                        tags.push(tag);
+                    },
+                    ReadEvent::Header(pending) => {
+                        assert_eq!(0xa1, pending.tag_id());
+                        assert!(pending.size() >= 2500);
+                        assert_eq!(data[pending.data_offset()..(pending.data_offset() + pending.size())], (0..pending.size()).map(|b| b as u8).collect::<Vec<u8>>()[..]);
+                        headers += 1;
+                        tags.push(match headers % 3 {
+                            0 => pending.buffer().unwrap(),
+                            1 => {
+                                let mut payload = Vec::new();
+                                pending.into_reader().read_to_end(&mut payload).unwrap();
+                                TestSpec::Block(payload)
+                            },
+                            _ => {
+                                let size = pending.size();
+                                pending.skip().unwrap();
+                                TestSpec::Block((0..size).map(|b| b as u8).collect())
+                            },
+                        });
+                    },
+                }
+            }
+            assert_eq!(7, headers);
+            assert_eq!(expected, tags);
+        }
+    }
+
+    #[test]
+    pub fn pending_elements_can_be_read_directly() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.set_header_event_threshold(Some(9000));
+        let mut count = None;
+        while let Some(event) = iter.next_event() {
+            match event.unwrap() {
+                ReadEvent::Tag(TestSpec::Count(c)) => count = Some(c),
+                ReadEvent::Tag(_) => {},
+                ReadEvent::Header(mut pending) => {
+                    let mut start = [0u8; 4];
+                    pending.read_exact(&mut start).unwrap();
+                    assert_eq!([0, 1, 2, 3], start);
+                    assert_eq!(8996, pending.remaining());
+                },
+            }
+        }
+        // The rest of the payload was skipped when the element was dropped
+        assert_eq!(Some(9), count);
+    }
+
+    #[test]
+    pub fn no_headers_without_threshold() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        while let Some(event) = iter.next_event() {
+            assert!(matches!(event, Ok(ReadEvent::Tag(_))));
+        }
+    }
+
+    #[test]
+    pub fn truncated_payload_keeps_data() {
+        let mut data = get_data();
+        data.truncate(data.len() - 10);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.set_header_event_threshold(Some(9000));
+        loop {
+            if let ReadEvent::Header(pending) = iter.next_event().unwrap().unwrap() {
+                match pending.buffer() {
+                    Err(ebml_iterable::error::TagIteratorError::UnexpectedEOF(partial)) => assert_eq!(Some(8990), partial.data.map(|d| d.len())),
+                    other => panic!("unexpected result {:?}", other),
+                }
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,17 @@
+mod test_spec;
+
+pub mod inferred_parent_tests {
+    use ebml_iterable::TagIterator;
+
+    use super::test_spec::TestSpec;
+
+    #[test]
+    pub fn no_end_tags_for_inferred_parents() {
+        // Count (in Segment/Cluster) followed by TrackType (in Segment), as if the source was seeked to the middle of a cluster
+        let src = [0x41, 0x00, 0x81, 0x01, 0x83, 0x81, 0x03];
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(&src[..], &[]);
+        let tags: Vec<TestSpec> = iter.collect::<Result<_, _>>().unwrap();
+        assert_eq!(vec![TestSpec::Count(1), TestSpec::TrackType(3)], tags);
+    }
+}
@@ -0,0 +1,132 @@
+mod test_spec;
+
+pub mod interceptor_tests {
+    use std::error::Error;
+
+    use ebml_iterable::error::TagWriterError;
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{InterceptingWriter, TagIterator, TagWriter, WriteInterceptor};
+
+    use super::test_spec::TestSpec;
+
+    struct OffsetCounts(u64);
+
+    impl WriteInterceptor<TestSpec> for OffsetCounts {
+        fn intercept(&mut self, tag: TestSpec, output: &mut Vec<TestSpec>) -> Result<(), Box<dyn Error + Send + Sync>> {
+            match tag {
+                TestSpec::Count(count) => output.push(TestSpec::Count(count + self.0)),
+                other => output.push(other),
+            }
+            Ok(())
+        }
+    }
+
+    struct DropTrackType;
+
+    impl WriteInterceptor<TestSpec> for DropTrackType {
+        fn intercept(&mut self, tag: TestSpec, output: &mut Vec<TestSpec>) -> Result<(), Box<dyn Error + Send + Sync>> {
+            if !matches!(tag, TestSpec::TrackType(_)) {
+                output.push(tag);
+            }
+            Ok(())
+        }
+    }
+
+    struct CountClusters {
+        clusters: u64,
+    }
+
+    impl WriteInterceptor<TestSpec> for CountClusters {
+        fn intercept(&mut self, tag: TestSpec, output: &mut Vec<TestSpec>) -> Result<(), Box<dyn Error + Send + Sync>> {
+            let is_cluster_start = matches!(tag, TestSpec::Cluster(Master::Start));
+            output.push(tag);
+            if is_cluster_start {
+                self.clusters += 1;
+                output.push(TestSpec::Count(self.clusters));
+            }
+            Ok(())
+        }
+
+        fn finish(&mut self, output: &mut Vec<TestSpec>) {
+            output.push(TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0)])));
+            output.push(TestSpec::Segment(Master::End));
+        }
+    }
+
+    struct RejectBlocks;
+
+    impl WriteInterceptor<TestSpec> for RejectBlocks {
+        fn intercept(&mut self, tag: TestSpec, output: &mut Vec<TestSpec>) -> Result<(), Box<dyn Error + Send + Sync>> {
+            if let TestSpec::Block(_) = tag {
+                return Err("blocks are not allowed".into());
+            }
+            output.push(tag);
+            Ok(())
+        }
+    }
+
+    fn read_all(data: &[u8]) -> Vec<TestSpec> {
+        TagIterator::new(data, &[]).map(|t| t.unwrap()).collect()
+    }
+
+    #[test]
+    pub fn interceptors_run_in_order() {
+        let mut writer = InterceptingWriter::new(TagWriter::new(Vec::new()));
+        writer.add_interceptor(CountClusters { clusters: 0 });
+        writer.add_interceptor(OffsetCounts(100));
+        writer.add_interceptor(DropTrackType);
+
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(101),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(0),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], read_all(&data));
+    }
+
+    #[test]
+    pub fn no_interceptors_writes_unchanged() {
+        let tags = [
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(3)])),
+            TestSpec::Segment(Master::End),
+        ];
+
+        let mut plain = TagWriter::new(Vec::new());
+        let mut intercepted: InterceptingWriter<_, TestSpec> = InterceptingWriter::new(TagWriter::new(Vec::new()));
+        for tag in tags.iter() {
+            plain.write(tag).unwrap();
+            intercepted.write(tag).unwrap();
+        }
+        assert_eq!(plain.into_inner().unwrap(), intercepted.into_inner().unwrap());
+    }
+
+    #[test]
+    pub fn interceptor_rejects_tag() {
+        let mut writer = InterceptingWriter::new(TagWriter::new(Vec::new()));
+        writer.add_interceptor(RejectBlocks);
+
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        let block = TestSpec::Block(vec![1, 2, 3]);
+        match writer.write(&block) {
+            Err(TagWriterError::InterceptorError { tag_id, source }) => {
+                assert_eq!(block.get_id(), tag_id);
+                assert_eq!("blocks are not allowed", source.to_string());
+            },
+            other => panic!("{:?}", other),
+        }
+        writer.write(&TestSpec::Count(1)).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+mod test_spec;
+
+pub mod join_tests {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::join;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+    use std::io::Cursor;
+
+    use super::test_spec::TestSpec;
+
+    fn cluster(value: u8) -> TestSpec {
+        TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![value; 16])]))
+    }
+
+    fn get_data(clusters: &[u8], unknown_sized_segment: bool) -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        if unknown_sized_segment {
+            writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        } else {
+            writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        }
+        writer.write(&TestSpec::Crc32(vec![0; 4])).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        for c in clusters {
+            writer.write(&cluster(*c)).unwrap();
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        drop(writer);
+
+        dest.into_inner()
+    }
+
+    #[test]
+    pub fn join_documents() {
+        let inputs = [get_data(&[1, 2], false), get_data(&[3], true), get_data(&[4, 5], false)];
+        let mut output = Cursor::new(Vec::new());
+        let count = join::<TestSpec, _, _, _>(inputs.iter().map(|i| &i[..]), &mut output, 0x1F43B675).unwrap();
+        assert_eq!(5, count);
+
+        let tags: Vec<TestSpec> = TagIterator::new(output.get_ref().as_slice(), &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Ebml(Master::Start),
+            TestSpec::Ebml(Master::End),
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            cluster(1),
+            cluster(2),
+            cluster(3),
+            cluster(4),
+            cluster(5),
+            TestSpec::Segment(Master::End),
+        ], tags);
+
+        // Merged segment is written with an 8 byte size
+        assert_eq!(0x01, output.get_ref()[9]);
+        assert_eq!(output.get_ref().len() - 17, output.get_ref()[10..17].iter().fold(0usize, |acc, b| (acc << 8) + *b as usize));
+    }
+
+    #[test]
+    pub fn join_drops_trailing_elements() {
+        // The TrackType after the clusters stands in for index elements like Cues and Tags
+        let get_data = |clusters: &[u8]| {
+            let mut writer = TagWriter::new(Vec::new());
+            writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+            writer.write(&TestSpec::TrackType(0x01)).unwrap();
+            for c in clusters {
+                writer.write(&cluster(*c)).unwrap();
+            }
+            writer.write(&TestSpec::TrackType(0x09)).unwrap();
+            writer.write(&TestSpec::Segment(Master::End)).unwrap();
+            writer.into_inner().unwrap()
+        };
+
+        let inputs = [get_data(&[1, 2]), get_data(&[3])];
+        let mut output = Cursor::new(Vec::new());
+        assert_eq!(3, join::<TestSpec, _, _, _>(inputs.iter().map(|i| &i[..]), &mut output, 0x1F43B675).unwrap());
+
+        let tags: Vec<TestSpec> = TagIterator::new(output.get_ref().as_slice(), &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            cluster(1),
+            cluster(2),
+            cluster(3),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(feature = "derive-spec")]
+pub mod lazy_utf8_tests {
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::specs::{ebml_specification, EbmlTag, LazyUtf8, Master, TagDataType};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum LazySpec {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        Root,
+
+        #[id(0x4101)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Root)]
+        Title(LazyUtf8),
+
+        #[id(0x4102)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Root)]
+        Name,
+    }
+
+    fn write(tags: &[LazySpec]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&LazySpec::Root(Master::Full(tags.to_vec()))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn lazy_tags_round_trip() {
+        let tags = vec![
+            LazySpec::Title(LazyUtf8::from("hello")),
+            LazySpec::Name(String::from("world")),
+        ];
+        let data = write(&tags);
+        let read: Vec<LazySpec> = TagIterator::new(&data[..], &[LazySpec::Root(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![LazySpec::Root(Master::Full(tags))], read);
+        if let LazySpec::Root(Master::Full(children)) = &read[0] {
+            assert_eq!(Some("hello"), children[0].as_utf8());
+            assert_eq!(Some("world"), children[1].as_utf8());
+        }
+    }
+
+    #[test]
+    pub fn invalid_lazy_tags_are_only_reported_when_accessed() {
+        let data = write(&[LazySpec::Title(LazyUtf8::new(vec![b'a', 0xff, b'b']))]);
+        let read: Vec<LazySpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+
+        let title = &read[1];
+        assert_eq!(None, title.as_utf8());
+        assert_eq!(Some(&[b'a', 0xff, b'b'][..]), title.as_utf8_bytes());
+        if let LazySpec::Title(text) = title {
+            assert!(text.to_str().is_err());
+            assert_eq!("a\u{fffd}b", text.to_string_lossy());
+        } else {
+            panic!("{:?}", title);
+        }
+
+        // Invalid data is written back out unchanged
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in read.iter() {
+            writer.write(tag).unwrap();
+        }
+        assert_eq!(data, writer.into_inner().unwrap());
+    }
+
+    #[test]
+    pub fn invalid_eager_tags_are_errors() {
+        let mut data = write(&[LazySpec::Name(String::from("ab"))]);
+        let len = data.len();
+        data[len - 1] = 0xff;
+        let mut iter: TagIterator<_, LazySpec> = TagIterator::new(&data[..], &[]);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(matches!(iter.next().unwrap(), Err(TagIteratorError::CorruptedTagData { tag_id: 0x4102, .. })));
+    }
+}
@@ -0,0 +1,80 @@
+mod test_spec;
+
+pub mod metrics_tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 1000])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![0; 3000])])),
+        ]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn read_metrics() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&data[..], &[TestSpec::Cluster(Master::Start)], 16);
+        let tags: Vec<TestSpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+
+        let metrics = iter.metrics();
+        assert_eq!(data.len() as u64, metrics.bytes_read);
+        assert_eq!(tags.len() as u64, metrics.tags_emitted);
+        assert_eq!(0, metrics.errors_emitted);
+        assert_eq!(0, metrics.recoveries);
+        assert!(metrics.buffer_high_water_mark >= 3000);
+        assert!(metrics.queue_high_water_mark >= 1);
+    }
+
+    #[test]
+    pub fn read_metrics_count_errors_and_recoveries() {
+        let mut data = vec![0x18, 0x53, 0x80, 0x67, 0x88, 0xff, 0xff];
+        data.extend_from_slice(&[0x83, 0x81, 0x01]);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err());
+        iter.try_recover().unwrap();
+        assert_eq!(TestSpec::TrackType(1), iter.next().unwrap().unwrap());
+
+        let metrics = iter.metrics();
+        assert_eq!(2, metrics.tags_emitted);
+        assert_eq!(1, metrics.errors_emitted);
+        assert_eq!(1, metrics.recoveries);
+    }
+
+    #[test]
+    pub fn metrics_callback() {
+        let data = get_data();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reports_clone = reports.clone();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.set_metrics_callback(Duration::ZERO, move |metrics| reports_clone.lock().unwrap().push(metrics.tags_emitted));
+        let count = iter.by_ref().count();
+
+        let reports = reports.lock().unwrap();
+        assert!(!reports.is_empty());
+        assert_eq!(Some(&(count as u64)), reports.last());
+        assert!(reports.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    pub fn write_metrics() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 500])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let metrics = writer.metrics();
+        let data = writer.into_inner().unwrap();
+
+        assert_eq!(data.len() as u64, metrics.bytes_written);
+        assert_eq!(5, metrics.tags_written);
+        assert_eq!(data.len(), metrics.buffer_high_water_mark);
+    }
+}
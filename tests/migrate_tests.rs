@@ -0,0 +1,95 @@
+mod test_spec;
+
+pub mod migrate_tests {
+    use ebml_iterable::error::MigrateError;
+    use ebml_iterable::specs::{Master, TagDataType};
+    use ebml_iterable::utils::migrate;
+    use ebml_iterable_specification::empty_spec::EmptySpec;
+
+    use super::test_spec::TestSpec;
+
+    #[test]
+    pub fn migrates_to_spec_with_same_types() {
+        let block = TestSpec::Block(vec![1, 2, 3]);
+        let migrated: EmptySpec = migrate(&block).unwrap();
+        assert_eq!(EmptySpec::with_data(0xa1, &[1, 2, 3]), migrated);
+        assert_eq!(block, migrate::<EmptySpec, TestSpec>(&migrated).unwrap());
+    }
+
+    #[test]
+    pub fn rejects_type_mismatch() {
+        let result = migrate::<TestSpec, EmptySpec>(&TestSpec::Count(1));
+        assert!(matches!(result, Err(MigrateError::TypeMismatch { id: 0x4100, from: TagDataType::UnsignedInt, to: TagDataType::Binary })));
+
+        let result = migrate::<TestSpec, EmptySpec>(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![1])])));
+        assert!(matches!(result, Err(MigrateError::TypeMismatch { id: 0x1F43B675, .. })));
+    }
+
+    #[test]
+    pub fn keeps_raw_tags() {
+        let raw = TestSpec::RawTag(0x4200, vec![1, 0]);
+        assert_eq!(raw, migrate::<TestSpec, TestSpec>(&raw).unwrap());
+    }
+}
+
+#[cfg(feature = "derive-spec")]
+pub mod migrate_derived_spec_tests {
+    use ebml_iterable::error::MigrateError;
+    use ebml_iterable::specs::{ebml_specification, Master, TagDataType};
+    use ebml_iterable::utils::migrate;
+
+    use super::test_spec::TestSpec;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Subset {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Segment)]
+        Cluster,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Segment/Cluster)]
+        Block,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment/Cluster)]
+        Count,
+
+        #[id(0x4200)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Segment/Cluster)]
+        Extra,
+    }
+
+    #[test]
+    pub fn migrates_children() {
+        let cluster = TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![1])]));
+        let migrated: Subset = migrate(&cluster).unwrap();
+        assert_eq!(Subset::Cluster(Master::Full(vec![Subset::Count(2), Subset::Block(vec![1])])), migrated);
+        assert_eq!(cluster, migrate::<Subset, TestSpec>(&migrated).unwrap());
+
+        assert_eq!(Subset::Segment(Master::Start), migrate::<TestSpec, Subset>(&TestSpec::Segment(Master::Start)).unwrap());
+    }
+
+    #[test]
+    pub fn rejects_unknown_tags() {
+        let cluster = TestSpec::Cluster(Master::Full(vec![TestSpec::CueRefCluster(2)]));
+        assert!(matches!(migrate::<TestSpec, Subset>(&cluster), Err(MigrateError::UnknownTag { id: 0x97 })));
+    }
+
+    #[test]
+    pub fn decodes_raw_tags() {
+        assert_eq!(Subset::Extra(256), migrate::<TestSpec, Subset>(&TestSpec::RawTag(0x4200, vec![1, 0])).unwrap());
+        assert_eq!(TestSpec::RawTag(0x4300, vec![1, 0]), migrate::<Subset, TestSpec>(&Subset::RawTag(0x4300, vec![1, 0])).unwrap());
+
+        let result = migrate::<TestSpec, Subset>(&TestSpec::RawTag(0x4200, vec![0; 9]));
+        assert!(matches!(result, Err(MigrateError::InvalidData { id: 0x4200, expected: TagDataType::UnsignedInt })));
+    }
+}
@@ -0,0 +1,63 @@
+mod test_spec;
+
+pub mod nesting_depth_tests {
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::iterator::AllowableErrors;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    // `depth` "Root" elements inside one another, each with an 8 byte size vint
+    fn nested_roots(depth: usize) -> Vec<u8> {
+        (0..depth).fold(Vec::new(), |inner, _| {
+            let size = (inner.len() as u64 | 0x01 << 56).to_be_bytes();
+            [&[0x81][..], &size[..], &inner[..]].concat()
+        })
+    }
+
+    fn read(data: &[u8], max_depth: Option<Option<usize>>) -> Vec<Result<TestSpec, TagIteratorError>> {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(data, &[]);
+        iter.allow_errors(&[AllowableErrors::HierarchyProblems]);
+        if let Some(max_depth) = max_depth {
+            iter.set_max_nesting_depth(max_depth);
+        }
+        iter.take(1000).collect()
+    }
+
+    #[test]
+    pub fn deep_nesting_is_allowed_by_default() {
+        let data = nested_roots(200);
+        let tags = read(&data, None);
+        assert_eq!(400, tags.len());
+        assert!(tags.iter().all(|t| t.is_ok()));
+    }
+
+    #[test]
+    pub fn deep_nesting_is_rejected_when_limited() {
+        let data = nested_roots(200);
+        let tags = read(&data, Some(Some(128)));
+        let errors: Vec<&TagIteratorError> = tags.iter().filter_map(|t| t.as_ref().err()).collect();
+        assert_eq!(1, errors.len());
+        assert!(matches!(errors[0], TagIteratorError::CorruptedFileData(CorruptedFileError::NestingTooDeep { position, tag_id: 0x81, max_depth: 128 }) if *position == 128 * 9), "{:?}", errors[0]);
+
+        // Reading continues after the skipped element
+        assert_eq!(128 * 2 + 1, tags.len());
+        assert!(tags[129..].iter().all(|t| matches!(t, Ok(TestSpec::Root(Master::End)))));
+    }
+
+    #[test]
+    pub fn nesting_depth_can_be_configured() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)])),
+            TestSpec::TrackType(2),
+        ]))).unwrap();
+        let data = writer.into_inner().unwrap();
+        let tags = read(&data, Some(Some(1)));
+        assert!(matches!(tags[2], Err(TagIteratorError::CorruptedFileData(CorruptedFileError::NestingTooDeep { tag_id: 0x1f43b675, max_depth: 1, .. }))), "{:?}", tags);
+        let tags: Vec<TestSpec> = tags.into_iter().filter_map(|t| t.ok()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(1), TestSpec::TrackType(2), TestSpec::Segment(Master::End)], tags);
+    }
+}
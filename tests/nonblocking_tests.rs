@@ -0,0 +1,220 @@
+#[cfg(feature = "futures")]
+mod test_spec;
+
+#[cfg(feature = "futures")]
+pub mod nonblocking_tests {
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use ebml_iterable::error::{TagIteratorError, TagWriterError};
+    use ebml_iterable::nonblocking::{TagIteratorAsync, TagWriterAsync};
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{TagIterator, TagWriter};
+    use futures::executor::block_on;
+    use futures::{AsyncRead, AsyncReadExt, AsyncWrite, SinkExt, StreamExt};
+
+    use super::test_spec::TestSpec;
+
+    struct ChunkedAsyncReader {
+        data: Vec<u8>,
+        position: usize,
+        chunk: usize,
+    }
+
+    impl AsyncRead for ChunkedAsyncReader {
+        fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+            let len = buf.len().min(self.chunk).min(self.data.len() - self.position);
+            buf[..len].copy_from_slice(&self.data[self.position..(self.position + len)]);
+            self.position += len;
+            Poll::Ready(Ok(len))
+        }
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(
+            (0..20u8).map(|i| TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i as u64), TestSpec::Block(vec![i; 10_000])]))).collect()
+        ))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn matches_sync_iterator() {
+        let data = get_data();
+        for tags_to_buffer in [vec![], vec![TestSpec::Cluster(Master::Start)], vec![TestSpec::Segment(Master::Start)]] {
+            let expected: Vec<TestSpec> = TagIterator::new(&data[..], &tags_to_buffer).map(|t| t.unwrap()).collect();
+            for chunk in [7, 1000, 100_000] {
+                let iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(ChunkedAsyncReader { data: data.clone(), position: 0, chunk }, &tags_to_buffer);
+                let tags: Vec<TestSpec> = block_on(iter.into_stream().map(|t| t.unwrap()).collect());
+                assert_eq!(expected, tags);
+            }
+        }
+    }
+
+    #[test]
+    pub fn reports_truncated_data() {
+        let data = get_data();
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(&data[..(data.len() - 10)], &[TestSpec::Cluster(Master::Start)]);
+        let error = block_on(async {
+            while let Some(tag) = iter.next().await {
+                if let Err(err) = tag {
+                    return Some(err);
+                }
+            }
+            None
+        });
+        assert!(matches!(error, Some(TagIteratorError::UnexpectedEOF { .. })));
+    }
+
+    ///
+    /// Reads every tag, taking blocks through an element reader (reading `read_len` bytes of each block when given, or all of it otherwise).
+    ///
+    async fn read_with_element_reader<R: AsyncRead + Unpin>(iter: &mut TagIteratorAsync<R, TestSpec>, read_len: Option<usize>) -> Vec<TestSpec> {
+        let mut tags = Vec::new();
+        loop {
+            let block = match iter.next_element_reader().await.unwrap() {
+                Some(reader) => {
+                    let mut payload = Vec::new();
+                    match read_len {
+                        Some(len) => { reader.take(len as u64).read_to_end(&mut payload).await.unwrap(); },
+                        None => {
+                            let mut reader = reader;
+                            reader.read_to_end(&mut payload).await.unwrap();
+                            assert_eq!(0, reader.remaining());
+                        },
+                    }
+                    Some(payload)
+                },
+                None => None,
+            };
+
+            match block {
+                Some(payload) if payload.len() > 1 || read_len.is_some() => tags.push(TestSpec::Block(payload)),
+                Some(payload) => tags.push(TestSpec::Count(payload.first().copied().unwrap_or(0) as u64)),
+                None => match iter.next().await {
+                    Some(tag) => {
+                        let tag = tag.unwrap();
+                        assert!(tag.as_master().is_some(), "{:?} should have been read through an element reader", tag);
+                        tags.push(tag);
+                    },
+                    None => return tags,
+                },
+            }
+        }
+    }
+
+    #[test]
+    pub fn element_reader_streams_payloads() {
+        let data = get_data();
+        let expected: Vec<TestSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+        for chunk in [7, 1000, 100_000] {
+            let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(ChunkedAsyncReader { data: data.clone(), position: 0, chunk }, &[]);
+            assert_eq!(expected, block_on(read_with_element_reader(&mut iter, None)));
+        }
+    }
+
+    #[test]
+    pub fn element_reader_skips_unread_payload() {
+        let data = get_data();
+        let expected: Vec<TestSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).map(|t| match t {
+            TestSpec::Block(payload) => TestSpec::Block(payload[..10].to_vec()),
+            TestSpec::Count(count) => TestSpec::Block(vec![count as u8]),
+            other => other,
+        }).collect();
+        for chunk in [7, 1000, 100_000] {
+            let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(ChunkedAsyncReader { data: data.clone(), position: 0, chunk }, &[]);
+            assert_eq!(expected, block_on(read_with_element_reader(&mut iter, Some(10))));
+        }
+    }
+
+    #[test]
+    pub fn element_reader_reports_truncated_payload() {
+        let data = get_data();
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(ChunkedAsyncReader { data: data[..(data.len() - 100)].to_vec(), position: 0, chunk: 1000 }, &[]);
+        let error = block_on(async {
+            loop {
+                let is_payload = match iter.next_element_reader().await.unwrap() {
+                    Some(mut reader) => {
+                        if let Err(err) = reader.read_to_end(&mut Vec::new()).await {
+                            return Some(err);
+                        }
+                        true
+                    },
+                    None => false,
+                };
+                if !is_payload {
+                    iter.next().await?.unwrap();
+                }
+            }
+        });
+        assert_eq!(io::ErrorKind::UnexpectedEof, error.unwrap().kind());
+    }
+
+    ///
+    /// Accepts at most `chunk` bytes per write, and returns [`Poll::Pending`] on every other call.
+    ///
+    struct ChunkedAsyncWriter {
+        data: Vec<u8>,
+        chunk: usize,
+        ready: bool,
+    }
+
+    impl AsyncWrite for ChunkedAsyncWriter {
+        fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let len = buf.len().min(self.chunk);
+            self.data.extend_from_slice(&buf[..len]);
+            Poll::Ready(Ok(len))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    pub fn writer_matches_sync_writer() {
+        let data = get_data();
+        for tags_to_buffer in [vec![], vec![TestSpec::Cluster(Master::Start)]] {
+            for chunk in [7, 100_000] {
+                let iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(&data[..], &tags_to_buffer);
+                let mut writer = TagWriterAsync::new(ChunkedAsyncWriter { data: Vec::new(), chunk, ready: false });
+                block_on(async {
+                    iter.into_stream().map(|t| Ok(t.unwrap())).forward(&mut writer).await.unwrap();
+                });
+                assert_eq!(data, writer.into_inner().data);
+            }
+        }
+    }
+
+    #[test]
+    pub fn writer_closes_open_tags() {
+        let mut writer = TagWriterAsync::new(Vec::new());
+        block_on(async {
+            writer.send(TestSpec::Segment(Master::Start)).await.unwrap();
+            writer.send(TestSpec::TrackType(1)).await.unwrap();
+            assert!(writer.get_ref().is_empty());
+            writer.close().await.unwrap();
+        });
+
+        let mut sync_writer = TagWriter::new(Vec::new());
+        sync_writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(1)]))).unwrap();
+        assert_eq!(sync_writer.into_inner().unwrap(), writer.into_inner());
+    }
+
+    #[test]
+    pub fn writer_reports_invalid_tags() {
+        let mut writer = TagWriterAsync::new(Vec::new());
+        let result = block_on(writer.send(TestSpec::Cluster(Master::End)));
+        assert!(matches!(result, Err(TagWriterError::UnexpectedClosingTag { .. })));
+    }
+}
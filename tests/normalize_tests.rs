@@ -0,0 +1,165 @@
+#[cfg(feature = "derive-spec")]
+pub mod normalize_tests {
+    use ebml_iterable::specs::{ebml_specification, Master, TagDataType};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification(metadata)]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Normalized {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        #[min_occurs(1)]
+        #[max_occurs(1)]
+        Root,
+
+        #[id(0x4280)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root)]
+        #[max_occurs(1)]
+        Header,
+
+        #[id(0x4281)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root/Header)]
+        #[min_occurs(1)]
+        #[max_occurs(1)]
+        #[default_value(1)]
+        Version,
+
+        #[id(0x4282)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Root/Header)]
+        #[min_occurs(1)]
+        #[max_occurs(1)]
+        #[default_value("eng")]
+        Language,
+
+        #[id(0x4283)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Root/Header)]
+        #[max_occurs(1)]
+        Name,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root)]
+        Cluster,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root/Cluster)]
+        #[min_occurs(1)]
+        #[max_occurs(1)]
+        #[default_value(0)]
+        Timestamp,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Root/Cluster)]
+        Block,
+
+        #[id(0xa0)]
+        #[data_type(TagDataType::Master)]
+        #[doc_path(Root/Cluster)]
+        BlockGroup,
+
+        #[id(0xa2)]
+        #[data_type(TagDataType::Binary)]
+        #[doc_path(Root/Cluster/BlockGroup)]
+        Data,
+    }
+
+    fn write_normalized(tags: &[Normalized]) -> Normalized {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_normalization(Some(Normalized::SPEC_METADATA));
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut tags: Vec<Normalized> = TagIterator::new(&data[..], &[Normalized::Root(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(1, tags.len());
+        tags.remove(0)
+    }
+
+    #[test]
+    pub fn missing_mandatory_children_get_defaults() {
+        let root = write_normalized(&[Normalized::Root(Master::Full(vec![
+            Normalized::Header(Master::Full(vec![Normalized::Name("test".to_string())])),
+            Normalized::Cluster(Master::Full(vec![Normalized::Block(vec![0x01])])),
+        ]))]);
+
+        assert_eq!(Normalized::Root(Master::Full(vec![
+            Normalized::Header(Master::Full(vec![Normalized::Version(1), Normalized::Language("eng".to_string()), Normalized::Name("test".to_string())])),
+            Normalized::Cluster(Master::Full(vec![Normalized::Timestamp(0), Normalized::Block(vec![0x01])])),
+        ])), root);
+    }
+
+    #[test]
+    pub fn children_are_ordered_without_shuffling_repeated_elements() {
+        let root = write_normalized(&[Normalized::Root(Master::Full(vec![
+            Normalized::Cluster(Master::Full(vec![
+                Normalized::Block(vec![0x01]),
+                Normalized::BlockGroup(Master::Full(vec![Normalized::Data(vec![0x02])])),
+                Normalized::Block(vec![0x03]),
+                Normalized::Timestamp(5),
+                Normalized::Crc32(vec![0x00; 4]),
+            ])),
+        ]))]);
+
+        assert_eq!(Normalized::Root(Master::Full(vec![
+            Normalized::Cluster(Master::Full(vec![
+                Normalized::Crc32(vec![0x00; 4]),
+                Normalized::Timestamp(5),
+                Normalized::Block(vec![0x01]),
+                Normalized::BlockGroup(Master::Full(vec![Normalized::Data(vec![0x02])])),
+                Normalized::Block(vec![0x03]),
+            ])),
+        ])), root);
+    }
+
+    #[test]
+    pub fn misplaced_children_move_up_to_an_allowed_parent() {
+        let root = write_normalized(&[Normalized::Root(Master::Full(vec![
+            Normalized::Cluster(Master::Full(vec![
+                Normalized::Timestamp(1),
+                Normalized::BlockGroup(Master::Full(vec![Normalized::Data(vec![0x02]), Normalized::Block(vec![0x03])])),
+                Normalized::Header(Master::Full(vec![Normalized::Version(2), Normalized::Language("fra".to_string())])),
+                Normalized::Block(vec![0x04]),
+            ])),
+        ]))]);
+
+        assert_eq!(Normalized::Root(Master::Full(vec![
+            Normalized::Header(Master::Full(vec![Normalized::Version(2), Normalized::Language("fra".to_string())])),
+            Normalized::Cluster(Master::Full(vec![
+                Normalized::Timestamp(1),
+                Normalized::BlockGroup(Master::Full(vec![Normalized::Data(vec![0x02])])),
+                Normalized::Block(vec![0x03]),
+                Normalized::Block(vec![0x04]),
+            ])),
+        ])), root);
+    }
+
+    #[test]
+    pub fn trees_are_normalized_below_open_tags() {
+        let root = write_normalized(&[
+            Normalized::Root(Master::Start),
+            Normalized::Cluster(Master::Full(vec![Normalized::Block(vec![0x01])])),
+            Normalized::Root(Master::End),
+        ]);
+
+        assert_eq!(Normalized::Root(Master::Full(vec![
+            Normalized::Cluster(Master::Full(vec![Normalized::Timestamp(0), Normalized::Block(vec![0x01])])),
+        ])), root);
+    }
+
+    #[test]
+    pub fn trees_are_written_as_given_by_default() {
+        let cluster = Normalized::Cluster(Master::Full(vec![Normalized::Block(vec![0x01]), Normalized::Timestamp(5)]));
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&Normalized::Root(Master::Full(vec![cluster.clone()]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let tags: Vec<Normalized> = TagIterator::new(&data[..], &[Normalized::Root(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![Normalized::Root(Master::Full(vec![cluster]))], tags);
+    }
+}
@@ -0,0 +1,90 @@
+mod test_spec;
+
+pub mod passthrough_tests {
+    use ebml_iterable::error::{PassthroughError, TagWriterError};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{passthrough, FrameAction};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    const CLUSTER_ID: u64 = 0x1F43B675;
+    const BLOCK_ID: u64 = 0xa1;
+
+    // A document using non-minimal size vints, which a decode and re-encode would not preserve
+    fn source() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write_advanced(&TestSpec::TrackType(1), WriteOptions::set_size_byte_count(4)).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write_advanced(&TestSpec::Block(vec![1; 3]), WriteOptions::set_size_byte_count(2)).unwrap();
+        writer.write(&TestSpec::Count(2)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![2; 2])])), WriteOptions::set_size_byte_count(8)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn read_tags(data: &[u8]) -> Vec<TestSpec> {
+        TagIterator::<_, TestSpec>::new(data, &[]).map(|tag| tag.unwrap()).collect()
+    }
+
+    #[test]
+    pub fn copies_bit_identical() {
+        let data = source();
+        let mut writer = TagWriter::new(Vec::new());
+        let mut ids = Vec::new();
+        passthrough::<TestSpec, _, _>(&data[..], &mut writer, |frame| {
+            ids.push(frame.id);
+            FrameAction::Copy
+        }).unwrap();
+
+        assert_eq!(data, writer.into_inner().unwrap());
+        assert_eq!(vec![0x18538067, 0x83, CLUSTER_ID, BLOCK_ID, 0x4100, CLUSTER_ID], ids);
+    }
+
+    #[test]
+    pub fn drops_and_replaces_frames() {
+        let data = source();
+        let mut writer = TagWriter::new(Vec::new());
+        passthrough(&data[..], &mut writer, |frame| match frame.id {
+            BLOCK_ID => FrameAction::Replace(vec![TestSpec::Block(vec![9])]),
+            0x4100 => FrameAction::Drop,
+            _ => FrameAction::Copy,
+        }).unwrap();
+        let output = writer.into_inner().unwrap();
+
+        let mut expected = read_tags(&data);
+        expected.retain(|tag| !matches!(tag, TestSpec::Count(_)));
+        expected[3] = TestSpec::Block(vec![9]);
+        assert_eq!(expected, read_tags(&output));
+        assert_eq!(data[..22], output[..22]);
+        assert_eq!(data[data.len() - 16..], output[output.len() - 16..]);
+    }
+
+    #[test]
+    pub fn drops_children_of_unknown_sized_frames() {
+        let data = source();
+        let mut writer = TagWriter::new(Vec::new());
+        let mut ids = Vec::new();
+        passthrough::<TestSpec, _, _>(&data[..], &mut writer, |frame| {
+            ids.push(frame.id);
+            if frame.size.is_unknown() && frame.id == CLUSTER_ID { FrameAction::Drop } else { FrameAction::Copy }
+        }).unwrap();
+
+        assert_eq!(vec![0x18538067, 0x83, CLUSTER_ID, CLUSTER_ID], ids);
+        let tags = read_tags(&writer.into_inner().unwrap());
+        assert_eq!(vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(1), TestSpec::Cluster(Master::Start), TestSpec::Block(vec![2; 2]), TestSpec::Cluster(Master::End), TestSpec::Segment(Master::End)], tags);
+    }
+
+    #[test]
+    pub fn validates_replacements() {
+        let data = source();
+        let mut writer = TagWriter::new(Vec::new());
+        let result = passthrough(&data[..], &mut writer, |frame| match frame.id {
+            0x83 => FrameAction::Replace(vec![TestSpec::Count(2)]),
+            _ => FrameAction::Copy,
+        });
+        assert!(matches!(result, Err(PassthroughError::WriteError { source: TagWriterError::UnexpectedTag { tag_id: 0x4100, .. } })));
+    }
+}
@@ -0,0 +1,73 @@
+mod test_spec;
+
+pub mod patch_tests {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{apply_patch, create_patch, Patch, PatchOperation, PathStep};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn cluster(count: u64, blocks: &[u8]) -> TestSpec {
+        let mut children = vec![TestSpec::Count(count)];
+        children.extend(blocks.iter().map(|b| TestSpec::Block(vec![*b; 4])));
+        TestSpec::Cluster(Master::Full(children))
+    }
+
+    fn encode(tags: &[TestSpec]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    fn decode(data: &[u8]) -> Vec<TestSpec> {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(data, &[TestSpec::Ebml(Master::Start), TestSpec::Segment(Master::Start)]);
+        iter.map(|t| t.unwrap()).collect()
+    }
+
+    fn document(segment: Vec<TestSpec>) -> Vec<TestSpec> {
+        vec![TestSpec::Ebml(Master::Full(vec![])), TestSpec::Segment(Master::Full(segment))]
+    }
+
+    fn apply(original: &[TestSpec], patch: &Patch<TestSpec>) -> Vec<TestSpec> {
+        let mut out = Vec::new();
+        apply_patch(&encode(original)[..], patch, &mut out).unwrap();
+        decode(&out)
+    }
+
+    #[test]
+    pub fn patch_round_trip() {
+        let a = document(vec![TestSpec::TrackType(1), cluster(1, &[1, 2]), cluster(2, &[3]), cluster(3, &[4])]);
+        let b = document(vec![TestSpec::TrackType(2), cluster(1, &[1, 9, 2]), cluster(3, &[4]), cluster(4, &[5])]);
+
+        let patch = create_patch(&a, &b);
+        assert!(!patch.is_empty());
+        assert_eq!(b, apply(&a, &patch));
+        assert!(create_patch(&a, &a).is_empty());
+    }
+
+    #[test]
+    pub fn patch_handles_reordered_children() {
+        let a = document(vec![cluster(1, &[1]), TestSpec::TrackType(1)]);
+        let b = document(vec![TestSpec::TrackType(1), cluster(1, &[1])]);
+
+        assert_eq!(b, apply(&a, &create_patch(&a, &b)));
+    }
+
+    #[test]
+    pub fn patch_bytes_round_trip() {
+        let segment = PathStep { id: 0x18538067, occurrence: 0 };
+        let patch = Patch {
+            operations: vec![
+                PatchOperation::Replace { path: vec![segment, PathStep { id: 0x83, occurrence: 0 }], tag: TestSpec::TrackType(7) },
+                PatchOperation::Insert { parent: vec![segment], index: 2, tag: cluster(5, &[1, 2]) },
+                PatchOperation::Delete { path: vec![segment, PathStep { id: 0x1F43B675, occurrence: 1 }] },
+            ],
+        };
+
+        let bytes = patch.to_bytes().unwrap();
+        assert_eq!(patch, Patch::from_bytes(&bytes).unwrap());
+        assert!(Patch::<TestSpec>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+mod test_spec;
+
+pub mod path_filter_tests {
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    const SEGMENT: u64 = 0x18538067;
+    const CLUSTER: u64 = 0x1f43b675;
+
+    fn get_data(unknown_size_segment: bool) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Void(vec![0; 2]), TestSpec::Int(1)]))).unwrap();
+        if unknown_size_segment {
+            writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        } else {
+            writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        }
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Void(vec![0; 3])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 10])]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::SimpleBlock(vec![0x02; 10]), TestSpec::Void(vec![0; 4])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn read(data: &[u8], paths: &[&[u64]], buffer: &[TestSpec]) -> Vec<TestSpec> {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(data, buffer);
+        iter.set_path_filter(Some(paths));
+        iter.map(|t| t.unwrap()).collect()
+    }
+
+    #[test]
+    pub fn elements_along_the_path_are_emitted() {
+        for unknown_size_segment in [false, true] {
+            let data = get_data(unknown_size_segment);
+            assert_eq!(vec![
+                TestSpec::Segment(Master::Start),
+                TestSpec::Cluster(Master::Start),
+                TestSpec::Cluster(Master::End),
+                TestSpec::Cluster(Master::Start),
+                TestSpec::SimpleBlock(vec![0x02; 10]),
+                TestSpec::Cluster(Master::End),
+                TestSpec::Segment(Master::End),
+            ], read(&data, &[&[SEGMENT, CLUSTER, 0xa3]], &[]));
+        }
+    }
+
+    #[test]
+    pub fn descendants_of_the_path_are_emitted() {
+        let data = get_data(false);
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 10])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::SimpleBlock(vec![0x02; 10]), TestSpec::Void(vec![0; 4])])),
+            TestSpec::Segment(Master::End),
+        ], read(&data, &[&[SEGMENT, CLUSTER]], &[TestSpec::Cluster(Master::Start)]));
+    }
+
+    #[test]
+    pub fn elements_elsewhere_are_not_emitted() {
+        let data = get_data(true);
+        let values: Vec<TestSpec> = read(&data, &[&[SEGMENT, CLUSTER, 0xec], &[0x81, 0x4101]], &[])
+            .into_iter()
+            .filter(|t| t.as_master().is_none())
+            .collect();
+        assert_eq!(vec![TestSpec::Int(1), TestSpec::Void(vec![0; 4])], values);
+
+        // Root elements that aren't on any path are hidden or skipped entirely
+        assert_eq!(vec![TestSpec::Root(Master::Full(vec![TestSpec::Int(1)]))], read(&data, &[&[0x81, 0x4101]], &[TestSpec::Root(Master::Start)]));
+    }
+
+    #[test]
+    pub fn path_filters_replace_id_filters() {
+        let data = get_data(false);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.set_tag_id_filter(Some(&[0x83]));
+        iter.set_path_filter(Some(&[&[0x81]]));
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Root(Master::Start), TestSpec::Void(vec![0; 2]), TestSpec::Int(1), TestSpec::Root(Master::End)], tags);
+    }
+}
@@ -0,0 +1,67 @@
+mod test_spec;
+
+pub mod preencoded_write_tests {
+    use ebml_iterable::error::TagWriterError;
+    use ebml_iterable::iterator::RawFrames;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write_advanced(&TestSpec::TrackType(1), WriteOptions::set_size_byte_count(4)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 3])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn frames_are_passed_through_verbatim() {
+        let data = data();
+        let mut writer = TagWriter::new(Vec::new());
+        for frame in RawFrames::new(&data[..]) {
+            let frame = frame.unwrap();
+            writer.write_preencoded::<TestSpec>(&frame.header, &frame.payload).unwrap();
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        assert_eq!(data, writer.into_inner().unwrap());
+    }
+
+    #[test]
+    pub fn preencoded_elements_count_toward_open_sizes() {
+        let data = data();
+        let cluster = RawFrames::new(&data[..]).map(|f| f.unwrap()).find(|f| f.id == 0x1f43b675).unwrap();
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.record_audit_log(true);
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write_preencoded::<TestSpec>(&cluster.header, &cluster.payload).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let audit: Vec<(u64, u64, Option<u64>)> = writer.audit_log().iter().map(|e| (e.id, e.offset, e.size)).collect();
+        let output = writer.into_inner().unwrap();
+
+        let tags: Vec<TestSpec> = TagIterator::new(&output[..], &[TestSpec::Segment(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 3])])),
+        ]))], tags);
+        assert_eq!(vec![(0x18538067, 0, Some(22)), (0x83, 5, Some(3)), (0x1f43b675, 8, Some(14))], audit);
+    }
+
+    #[test]
+    pub fn preencoded_elements_are_validated() {
+        let mut writer = TagWriter::new(Vec::new());
+        let result = writer.write_preencoded::<TestSpec>(&[0x83, 0x81], &[0x01]);
+        assert!(matches!(result, Err(TagWriterError::UnexpectedTag { tag_id: 0x83, .. })), "{:?}", result);
+
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        for (header, payload) in [(&[0x83, 0x82][..], &[0x01][..]), (&[0x83][..], &[][..]), (&[0x83, 0x81, 0x01][..], &[][..]), (&[0x83, 0xff][..], &[][..])] {
+            let result = writer.write_preencoded::<TestSpec>(header, payload);
+            assert!(matches!(&result, Err(TagWriterError::InvalidPreencodedHeader { header: rejected, .. }) if rejected == header), "{:?}", result);
+        }
+        writer.write_preencoded::<TestSpec>(&[0x83, 0x81], &[0x01]).unwrap();
+    }
+}
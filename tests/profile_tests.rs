@@ -0,0 +1,78 @@
+mod test_spec;
+
+pub mod profile_tests {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::{CorruptedFileError, ProfileViolation, TagIteratorError, TagWriterError};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{Profile, TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    const WEBM_LIKE: Profile = Profile::webm(&[0x18538067, 0x1F43B675, 0x4100, 0xa1, 0x83, 0x81, 0x4103]);
+
+    #[test]
+    pub fn check_reports_violations() {
+        assert_eq!(Ok(()), WEBM_LIKE.check(0x1F43B675, true));
+        assert_eq!(Ok(()), WEBM_LIKE.check(0x4100, false));
+        assert_eq!(Err(ProfileViolation::ElementNotAllowed), WEBM_LIKE.check(0x4101, false));
+        assert_eq!(Err(ProfileViolation::UnknownSizeNotAllowed), WEBM_LIKE.check(0x81, true));
+        assert_eq!(Err(ProfileViolation::IdTooLong { length: 5 }), WEBM_LIKE.check(0x0810000000, false));
+        assert_eq!(Ok(()), Profile::unrestricted().check(0x0810000000, true));
+    }
+
+    #[test]
+    pub fn writer_enforces_profile() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_profile(Some(WEBM_LIKE));
+
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Count(1)).unwrap();
+        assert!(matches!(writer.write(&TestSpec::CueRefCluster(1)), Err(TagWriterError::ProfileViolation { tag_id: 0x97, violation: ProfileViolation::ElementNotAllowed })));
+        assert!(matches!(writer.write_raw(0x4101, &[0x01]), Err(TagWriterError::ProfileViolation { tag_id: 0x4101, violation: ProfileViolation::ElementNotAllowed })));
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+
+        writer.write(&TestSpec::Root(Master::Start)).unwrap();
+        assert!(matches!(writer.write(&TestSpec::Parent(Master::Full(vec![TestSpec::Child(3)]))), Err(TagWriterError::ProfileViolation { tag_id: 0x210301, violation: ProfileViolation::ElementNotAllowed })));
+        assert!(matches!(writer.write_advanced(&TestSpec::Parent(Master::Start), WriteOptions::is_unknown_sized_element()), Err(TagWriterError::ProfileViolation { tag_id: 0x4103, violation: ProfileViolation::UnknownSizeNotAllowed })));
+    }
+
+    #[test]
+    pub fn writer_enforces_profile_in_full_tags() {
+        let valid = TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))]));
+
+        let mut writer = TagWriter::new(Vec::new());
+        writer.set_profile(Some(WEBM_LIKE));
+        assert!(matches!(writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::CueRefCluster(1)]))]))), Err(TagWriterError::ProfileViolation { tag_id: 0x97, violation: ProfileViolation::ElementNotAllowed })));
+        writer.write(&valid).unwrap();
+
+        let mut expected = TagWriter::new(Vec::new());
+        expected.write(&valid).unwrap();
+        assert_eq!(expected.into_inner().unwrap(), writer.into_inner().unwrap());
+    }
+
+    #[test]
+    pub fn reader_enforces_profile() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Start)).unwrap();
+        writer.write(&TestSpec::Int(4)).unwrap();
+        writer.write(&TestSpec::Parent(Master::Start)).unwrap();
+        writer.write(&TestSpec::Child(3)).unwrap();
+        writer.write(&TestSpec::Parent(Master::End)).unwrap();
+        writer.write(&TestSpec::Root(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        reader.set_profile(Some(Profile { max_id_length: Some(2), ..WEBM_LIKE }));
+
+        assert_eq!(TestSpec::Root(Master::Start), reader.next().unwrap().unwrap());
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::ProfileViolation { position: 2, tag_id: 0x4101, violation: ProfileViolation::ElementNotAllowed })))));
+        assert_eq!(TestSpec::Parent(Master::Start), reader.next().unwrap().unwrap());
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::ProfileViolation { tag_id: 0x210301, violation: ProfileViolation::IdTooLong { length: 3 }, .. })))));
+        assert_eq!(TestSpec::Parent(Master::End), reader.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Root(Master::End), reader.next().unwrap().unwrap());
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,78 @@
+mod test_spec;
+
+pub mod push_decoder_tests {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::decode_slice;
+    use ebml_iterable::{PushDecoder, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        for i in 0..3u8 {
+            writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i as u64), TestSpec::Block(vec![i; 300])]))).unwrap();
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn decode_in_chunks(data: &[u8], chunk_size: usize, tags_to_buffer: &[TestSpec]) -> Vec<TestSpec> {
+        let mut decoder: PushDecoder<TestSpec> = PushDecoder::new(tags_to_buffer);
+        let mut tags = Vec::new();
+        for chunk in data.chunks(chunk_size) {
+            decoder.push(chunk);
+            while let Some(tag) = decoder.next_tag() {
+                tags.push(tag.unwrap());
+            }
+        }
+        decoder.finish();
+        while let Some(tag) = decoder.next_tag() {
+            tags.push(tag.unwrap());
+        }
+        tags
+    }
+
+    #[test]
+    pub fn matches_slice_decoding() {
+        let data = get_data();
+        for tags_to_buffer in [&[][..], &[TestSpec::Cluster(Master::Start)][..]] {
+            let expected = decode_slice(&data, tags_to_buffer).unwrap();
+            for chunk_size in [1, 7, 64, data.len()] {
+                assert_eq!(expected, decode_in_chunks(&data, chunk_size, tags_to_buffer), "chunk size {}", chunk_size);
+            }
+        }
+    }
+
+    #[test]
+    pub fn waits_for_complete_elements() {
+        let data = get_data();
+        let mut decoder: PushDecoder<TestSpec> = PushDecoder::new(&[TestSpec::Cluster(Master::Start)]);
+
+        // Ebml (5 bytes), the Segment header (4 byte id + 8 byte unknown size), and part of the TrackType
+        decoder.push(&data[..18]);
+        let mut tags = Vec::new();
+        while let Some(tag) = decoder.next_tag() {
+            tags.push(tag.unwrap());
+        }
+        assert_eq!(vec![TestSpec::Ebml(Master::Start), TestSpec::Ebml(Master::End), TestSpec::Segment(Master::Start)], tags);
+        assert!(decoder.pending_len() > 0);
+
+        decoder.push(&data[18..]);
+        assert_eq!(Some(TestSpec::TrackType(1)), decoder.next_tag().map(|t| t.unwrap()));
+    }
+
+    #[test]
+    pub fn reports_truncated_data_on_finish() {
+        let data = get_data();
+        let mut decoder: PushDecoder<TestSpec> = PushDecoder::new(&[]);
+        decoder.push(&data[..data.len() - 10]);
+        while let Some(tag) = decoder.next_tag() {
+            tag.unwrap();
+        }
+        decoder.finish();
+        assert!(std::iter::from_fn(|| decoder.next_tag()).take(10).any(|t| t.is_err()));
+    }
+}
@@ -0,0 +1,95 @@
+mod test_spec;
+
+pub mod range_source_tests {
+    use std::cell::RefCell;
+    use std::io::{Read, Seek, SeekFrom};
+    use std::ops::Range;
+    use std::rc::Rc;
+
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{RangeReader, RangeSource};
+    use ebml_iterable::{EbmlReader, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    type Fetches = Rc<RefCell<Vec<Range<u64>>>>;
+
+    struct CountingSource {
+        data: Vec<u8>,
+        fetches: Fetches,
+    }
+
+    impl RangeSource for CountingSource {
+        fn fetch(&mut self, range: Range<u64>) -> std::io::Result<Vec<u8>> {
+            self.fetches.borrow_mut().push(range.clone());
+            let start = (range.start as usize).min(self.data.len());
+            let end = (range.end as usize).min(self.data.len());
+            Ok(self.data[start..end].to_vec())
+        }
+
+        fn total_len(&mut self) -> std::io::Result<Option<u64>> {
+            Ok(Some(self.data.len() as u64))
+        }
+    }
+
+    fn counting_reader(data: Vec<u8>) -> (RangeReader<CountingSource>, Fetches) {
+        let fetches = Rc::new(RefCell::new(Vec::new()));
+        (RangeReader::new(CountingSource { data, fetches: Rc::clone(&fetches) }), fetches)
+    }
+
+    #[test]
+    pub fn reads_and_seeks_through_chunks() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        let (mut reader, fetches) = counting_reader(data.clone());
+        reader.set_chunk_len(1000);
+        reader.set_read_ahead(2);
+        reader.set_cached_chunks(4);
+
+        let mut buf = [0u8; 2500];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(data[..2500], buf);
+        assert_eq!(vec![0..3000], *fetches.borrow());
+
+        // Cached chunks aren't fetched again
+        reader.seek(SeekFrom::Start(500)).unwrap();
+        reader.read_exact(&mut buf[..100]).unwrap();
+        assert_eq!(data[500..600], buf[..100]);
+        assert_eq!(1, fetches.borrow().len());
+
+        reader.seek(SeekFrom::End(-10)).unwrap();
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(data[9990..], rest[..]);
+        assert_eq!(vec![0..3000, 9000..12000], *fetches.borrow());
+    }
+
+    #[test]
+    pub fn closures_are_sources() {
+        let data: Vec<u8> = (0..100u8).collect();
+        let source = |range: Range<u64>| Ok(data[(range.start as usize).min(100)..(range.end as usize).min(100)].to_vec());
+        let mut reader = RangeReader::new(source);
+        reader.set_chunk_len(16);
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(data, all);
+        assert!(reader.seek(SeekFrom::End(0)).is_err());
+    }
+
+    #[test]
+    pub fn random_access_only_fetches_needed_chunks() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x01; 100_000])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x02; 100_000])])),
+            TestSpec::TrackType(0x05),
+        ]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let (mut source, fetches) = counting_reader(data);
+        source.set_chunk_len(4096);
+        source.set_read_ahead(0);
+        let mut reader: EbmlReader<_, TestSpec> = EbmlReader::new(source);
+        assert_eq!(TestSpec::TrackType(0x05), reader.open("Segment/TrackType").unwrap().buffer().unwrap());
+        assert!(fetches.borrow().len() <= 4, "{:?}", fetches.borrow());
+    }
+}
@@ -0,0 +1,47 @@
+mod test_spec;
+
+pub mod raw_frames_tests {
+    use ebml_iterable::error::{PartialTag, TagIteratorError};
+    use ebml_iterable::iterator::{ElementSize, RawFrames};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write_advanced(&TestSpec::TrackType(1), WriteOptions::set_size_byte_count(4)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 3])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn yields_verbatim_frames() {
+        let data = data();
+        let frames: Vec<_> = RawFrames::new(&data[..]).collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(vec![0x18538067, 0x83, 0x1F43B675], frames.iter().map(|f| f.id).collect::<Vec<_>>());
+        assert_eq!(vec![ElementSize::Unknown, ElementSize::Known(1), ElementSize::Known(9)], frames.iter().map(|f| f.size).collect::<Vec<_>>());
+        assert_eq!(vec![0x83, 0x10, 0x00, 0x00, 0x01], frames[1].header);
+        assert!(frames[0].payload.is_empty());
+
+        let reencoded: Vec<u8> = frames.iter().flat_map(|f| f.header.iter().chain(f.payload.iter()).copied()).collect();
+        assert_eq!(data, reencoded);
+    }
+
+    #[test]
+    pub fn stops_after_errors() {
+        let data = data();
+        let mut frames = RawFrames::new(&data[..(data.len() - 2)]);
+        assert_eq!(2, frames.by_ref().take(2).filter(|f| f.is_ok()).count());
+        assert!(matches!(frames.next(), Some(Err(TagIteratorError::UnexpectedEOF(PartialTag { tag_start: 18, id: Some(0x1F43B675), size: Some(9), .. })))));
+        assert!(frames.next().is_none());
+
+        let mut frames = RawFrames::new(&data[..]);
+        frames.set_max_frame_size(Some(4));
+        assert_eq!(2, frames.by_ref().filter(|f| f.is_ok()).count());
+        assert_eq!(18, frames.position());
+    }
+}
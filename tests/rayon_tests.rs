@@ -0,0 +1,51 @@
+#[cfg(feature = "rayon")]
+mod test_spec;
+
+#[cfg(feature = "rayon")]
+pub mod rayon_tests {
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{TagIterator, TagWriter};
+    use rayon::prelude::*;
+
+    use super::test_spec::TestSpec;
+
+    fn get_segment() -> TestSpec {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(
+            (0..20u8).map(|i| TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i as u64), TestSpec::Block(vec![i; 100])]))).collect()
+        ))).unwrap();
+        let data = writer.into_inner().unwrap();
+        TagIterator::new(&data[..], &[TestSpec::Segment(Master::Start)]).next().unwrap().unwrap()
+    }
+
+    #[test]
+    pub fn par_children_visits_every_child() {
+        let segment = get_segment();
+        let master = segment.as_master().unwrap();
+        let counts: Vec<u64> = master.par_children().map(|cluster| {
+            match cluster.as_master() {
+                Some(Master::Full(children)) => *children[0].as_unsigned_int().unwrap(),
+                _ => panic!("Expected full cluster"),
+            }
+        }).collect();
+        assert_eq!((0..20).collect::<Vec<u64>>(), counts);
+    }
+
+    #[test]
+    pub fn par_descendants_visits_whole_tree() {
+        let segment = get_segment();
+        let master = segment.as_master().unwrap();
+        let ids: Vec<u64> = master.par_descendants().map(|tag| tag.get_id()).collect();
+        let expected: Vec<u64> = (0..20).flat_map(|_| [0x1F43B675, 0x4100, 0xa1]).collect();
+        assert_eq!(expected, ids);
+
+        let block_bytes: usize = master.par_descendants().filter_map(|tag| tag.as_binary()).map(|data| data.len()).sum();
+        assert_eq!(2000, block_bytes);
+    }
+
+    #[test]
+    pub fn start_and_end_have_no_children() {
+        assert_eq!(0, Master::<TestSpec>::Start.par_children().count());
+        assert_eq!(0, Master::<TestSpec>::End.par_descendants().count());
+    }
+}
@@ -0,0 +1,83 @@
+mod test_spec;
+
+pub mod redact_tests {
+    use ebml_iterable::error::{CorruptedFileError, RedactError, TagIteratorError};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::{redact, redact_drop};
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+    use std::io::Cursor;
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Block(vec![0x01; 8]),
+                TestSpec::Count(2),
+            ])),
+        ]))).unwrap();
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x02; 300])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        drop(writer);
+
+        dest.into_inner()
+    }
+
+    fn read_all(data: &[u8]) -> Vec<TestSpec> {
+        TagIterator::new(data, &[]).map(|t| t.unwrap()).collect()
+    }
+
+    #[test]
+    pub fn redact_with_void() {
+        let data = get_data();
+        let mut dest = Vec::new();
+        assert_eq!(2, redact::<TestSpec, _, _>(&data[..], &mut dest, &[0xa1]).unwrap());
+        assert_eq!(data.len(), dest.len());
+
+        let expected: Vec<TestSpec> = read_all(&data).into_iter().map(|t| match t {
+            TestSpec::Block(data) => TestSpec::Void(vec![0; data.len()]),
+            t => t,
+        }).collect();
+        assert_eq!(expected, read_all(&dest));
+    }
+
+    #[test]
+    pub fn redact_by_dropping() {
+        let data = get_data();
+        let mut dest = Cursor::new(Vec::new());
+        assert_eq!(2, redact_drop::<TestSpec, _, _>(&data[..], &mut dest, &[0xa1]).unwrap());
+
+        let expected: Vec<TestSpec> = read_all(&data).into_iter().filter(|t| !matches!(t, TestSpec::Block(_))).collect();
+        assert_eq!(expected, read_all(dest.get_ref()));
+        assert_eq!(data.len() - 10 - 303, dest.get_ref().len());
+    }
+
+    #[test]
+    pub fn redact_unknown_size() {
+        let data = get_data();
+        assert!(matches!(redact::<TestSpec, _, _>(&data[..], Vec::new(), &[0x18538067]), Err(RedactError::UnknownSize { tag_id: 0x18538067, position: _ })));
+    }
+
+    #[test]
+    pub fn redact_truncated_element() {
+        // A Block claiming about 256MB of data in a 7 byte source
+        let data = [0xa1, 0x1f, 0xff, 0xff, 0xfe, 0x01, 0x02];
+        let mut dest = Vec::new();
+        assert!(matches!(redact::<TestSpec, _, _>(&data[..], &mut dest, &[0xa1]), Err(RedactError::ReadError { source: TagIteratorError::UnexpectedEOF(_) })));
+        assert!(dest.is_empty());
+    }
+
+    #[test]
+    pub fn redact_child_past_parent_end() {
+        // A Segment of 3 bytes holding a Block that claims 5
+        let data = [0x18, 0x53, 0x80, 0x67, 0x83, 0xa1, 0x85, 0x01, 0x02, 0x03, 0x04, 0x05];
+        let mut dest = Vec::new();
+        assert!(matches!(redact::<TestSpec, _, _>(&data[..], &mut dest, &[0xa1]), Err(RedactError::ReadError { source: TagIteratorError::CorruptedFileData(CorruptedFileError::OversizedChildElement { position: 5, tag_id: 0xa1, size: 5 }) })));
+        assert_eq!(&data[..5], &dest[..]);
+    }
+}
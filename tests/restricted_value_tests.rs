@@ -0,0 +1,88 @@
+#[cfg(feature = "derive-spec")]
+pub mod restricted_value_tests {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError, TagWriterError};
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, Master, TagDataType};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Restricted {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        Root,
+
+        #[id(0x83)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root)]
+        #[restricted_values(1, 2, 17)]
+        TrackType,
+
+        #[id(0x84)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root)]
+        Count,
+    }
+
+    fn write_unchecked(tags: &[Restricted]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            writer.write(tag).expect("write should succeed");
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn spec_reports_restricted_values() {
+        assert_eq!(Some(&[1u64, 2, 17][..]), Restricted::get_restricted_values(0x83));
+        assert_eq!(None, Restricted::get_restricted_values(0x84));
+    }
+
+    #[test]
+    pub fn reader_ignores_restrictions_by_default() {
+        let data = write_unchecked(&[Restricted::Root(Master::Start), Restricted::TrackType(9), Restricted::Root(Master::End)]);
+        let reader: TagIterator<_, Restricted> = TagIterator::new(Cursor::new(data), &[]);
+        let tags: Vec<Restricted> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(Restricted::TrackType(9), tags[1]);
+    }
+
+    #[test]
+    pub fn reader_rejects_restricted_values() {
+        let data = write_unchecked(&[Restricted::Root(Master::Start), Restricted::TrackType(9), Restricted::TrackType(17), Restricted::Count(9), Restricted::Root(Master::End)]);
+        let mut reader: TagIterator<_, Restricted> = TagIterator::new(Cursor::new(data), &[]);
+        reader.validate_restricted_values(true);
+
+        assert_eq!(Restricted::Root(Master::Start), reader.next().unwrap().unwrap());
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::RestrictedValue { position: 2, tag_id: 0x83, value: 9 })))));
+        assert_eq!(Restricted::TrackType(17), reader.next().unwrap().unwrap());
+        assert_eq!(Restricted::Count(9), reader.next().unwrap().unwrap());
+        assert_eq!(Restricted::Root(Master::End), reader.next().unwrap().unwrap());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    pub fn writer_rejects_restricted_values() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.validate_restricted_values(true);
+        writer.write(&Restricted::Root(Master::Start)).unwrap();
+        assert!(matches!(writer.write(&Restricted::TrackType(3)), Err(TagWriterError::RestrictedValue { tag_id: 0x83, value: 3 })));
+        writer.write(&Restricted::TrackType(2)).unwrap();
+        writer.write(&Restricted::Count(3)).unwrap();
+        writer.write(&Restricted::Root(Master::End)).unwrap();
+
+        let data = writer.into_inner().unwrap();
+        assert_eq!(write_unchecked(&[Restricted::Root(Master::Start), Restricted::TrackType(2), Restricted::Count(3), Restricted::Root(Master::End)]), data);
+    }
+
+    #[test]
+    pub fn writer_rejects_restricted_values_in_full_tags() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.validate_restricted_values(true);
+        assert!(matches!(writer.write(&Restricted::Root(Master::Full(vec![Restricted::Count(3), Restricted::TrackType(3)]))), Err(TagWriterError::RestrictedValue { tag_id: 0x83, value: 3 })));
+        writer.write(&Restricted::Root(Master::Full(vec![Restricted::Count(3), Restricted::TrackType(17)]))).unwrap();
+
+        let data = writer.into_inner().unwrap();
+        assert_eq!(write_unchecked(&[Restricted::Root(Master::Full(vec![Restricted::Count(3), Restricted::TrackType(17)]))]), data);
+    }
+}
@@ -0,0 +1,82 @@
+mod test_spec;
+
+pub mod resume_partial_tests {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::io::Read;
+    use std::rc::Rc;
+
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    // A source that hands out the bytes queued so far, shared between every clone
+    #[derive(Clone)]
+    struct SharedQueue(Rc<RefCell<VecDeque<u8>>>);
+
+    impl Read for SharedQueue {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().read(buf)
+        }
+    }
+
+    fn encode() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![7; 10])]))).unwrap();
+        writer.write(&TestSpec::TrackType(2)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn expect_partial(iter: &mut TagIterator<SharedQueue, TestSpec>) -> ebml_iterable::error::PartialTag {
+        loop {
+            match iter.next() {
+                Some(Ok(_)) => {},
+                Some(Err(TagIteratorError::UnexpectedEOF(partial))) => return partial,
+                other => panic!("{:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    pub fn resumes_cut_off_tag() {
+        let data = encode();
+        let block_end = data.len() - 3;
+        let source = SharedQueue(Rc::new(RefCell::new(data[..(block_end - 6)].iter().copied().collect())));
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(source.clone(), &[]);
+        iter.set_eof_predicate(|_| false);
+
+        let partial = expect_partial(&mut iter);
+        assert_eq!(Some(0xa1), partial.id);
+        assert_eq!(Some(vec![7; 4]), partial.data);
+
+        source.0.borrow_mut().extend(&data[(block_end - 6)..(block_end - 2)]);
+        let partial = match iter.resume_partial(partial, source.clone()) {
+            Err(TagIteratorError::UnexpectedEOF(partial)) => partial,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(Some(2), partial.missing());
+
+        source.0.borrow_mut().extend(&data[(block_end - 2)..]);
+        assert_eq!(TestSpec::Block(vec![7; 10]), iter.resume_partial(partial, source.clone()).unwrap());
+        assert_eq!(block_end, iter.last_emitted_tag_offset() + 12);
+
+        let rest: Vec<TestSpec> = iter.by_ref().take(2).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Cluster(Master::End), TestSpec::TrackType(2)], rest);
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::NeedMoreData { .. }))));
+    }
+
+    #[test]
+    pub fn rejects_other_tags() {
+        let data = encode();
+        let source = SharedQueue(Rc::new(RefCell::new(data[..(data.len() - 6)].iter().copied().collect())));
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(source.clone(), &[]);
+        let mut partial = expect_partial(&mut iter);
+        partial.tag_start += 1;
+
+        assert!(matches!(iter.resume_partial(partial, &[1u8][..]), Err(TagIteratorError::ReadError { source }) if source.kind() == std::io::ErrorKind::InvalidInput));
+    }
+}
@@ -0,0 +1,90 @@
+mod test_spec;
+
+pub mod seek_index_tests {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::TagWriterError;
+    use ebml_iterable::specs::{EbmlTag, Master};
+    use ebml_iterable::{SeekIndex, TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    const CLUSTER_ID: u64 = 0x1F43B675;
+
+    // TestSpec has no index elements, so a Cluster of (Count = id, CueRefCluster = offset) pairs stands in for one
+    fn build_index(entries: &[(u64, u64)]) -> TestSpec {
+        TestSpec::Cluster(Master::Full(entries.iter().flat_map(|(id, offset)| vec![TestSpec::Count(*id), TestSpec::CueRefCluster(*offset)]).collect()))
+    }
+
+    fn write_clusters(writer: &mut TagWriter<Cursor<Vec<u8>>>) {
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0; 4])]))).unwrap();
+    }
+
+    // Returns the offset of each Cluster in the data, along with the Cluster tags themselves
+    fn read_clusters(data: &[u8]) -> Vec<(u64, TestSpec)> {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(data, &[]);
+        let mut offsets = Vec::new();
+        while let Some(tag) = iter.next() {
+            if matches!(tag.unwrap(), TestSpec::Cluster(Master::Start)) {
+                offsets.push(iter.last_emitted_tag_offset() as u64);
+            }
+        }
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(data, &[TestSpec::Cluster(Master::Start)]);
+        let clusters = iter.map(|tag| tag.unwrap()).filter(|tag| tag.get_id() == CLUSTER_ID);
+        offsets.into_iter().zip(clusters).collect()
+    }
+
+    #[test]
+    pub fn indexes_elements_of_unknown_size_parent() {
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        writer.record_audit_log(true);
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+
+        let mut index = SeekIndex::new("index", &[CLUSTER_ID]);
+        index.reserve(&mut writer, 32).unwrap();
+        write_clusters(&mut writer);
+        index.finish(&mut writer, build_index).unwrap();
+        let data = writer.into_inner().unwrap().into_inner();
+
+        let clusters = read_clusters(&data);
+        let segment_data_start = 12;
+        assert_eq!(segment_data_start, clusters[0].0);
+        assert_eq!(build_index(&[(CLUSTER_ID, clusters[1].0 - segment_data_start), (CLUSTER_ID, clusters[2].0 - segment_data_start)]), clusters[0].1);
+    }
+
+    #[test]
+    pub fn indexes_elements_of_known_size_parent_once_closed() {
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        writer.record_audit_log(true);
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+
+        let mut index = SeekIndex::new("index", &[CLUSTER_ID]);
+        index.reserve(&mut writer, 32).unwrap();
+        write_clusters(&mut writer);
+        assert!(matches!(index.finish(&mut writer, build_index), Err(TagWriterError::IndexUnavailable { name }) if name == "index"));
+
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        assert_eq!(2, index.entries(&writer).unwrap().len());
+        index.finish(&mut writer, build_index).unwrap();
+        let data = writer.into_inner().unwrap().into_inner();
+
+        let clusters = read_clusters(&data);
+        let segment_data_start = clusters[0].0;
+        assert_eq!(build_index(&[(CLUSTER_ID, clusters[1].0 - segment_data_start), (CLUSTER_ID, clusters[2].0 - segment_data_start)]), clusters[0].1);
+    }
+
+    #[test]
+    pub fn requires_audit_log() {
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.record_audit_log(true);
+
+        let mut index = SeekIndex::new("index", &[CLUSTER_ID]);
+        index.reserve(&mut writer, 32).unwrap();
+        write_clusters(&mut writer);
+        assert!(matches!(index.entries(&writer), Err(TagWriterError::IndexUnavailable { .. })));
+    }
+}
@@ -0,0 +1,74 @@
+#[cfg(feature = "serde")]
+mod test_spec;
+
+#[cfg(feature = "serde")]
+pub mod serde_tests {
+    use ebml_iterable::error::SerializedTagError;
+    use ebml_iterable::specs::{Master, TagDataType};
+    use ebml_iterable::utils::{SerdeTag, SerializedTag, SerializedValue};
+
+    use super::test_spec::TestSpec;
+
+    fn get_tree() -> TestSpec {
+        TestSpec::Root(Master::Full(vec![
+            TestSpec::Int(7),
+            TestSpec::String(String::from("hello")),
+            TestSpec::Parent(Master::Full(vec![
+                TestSpec::Child(3),
+                TestSpec::Child(4),
+            ])),
+            TestSpec::Block(vec![0x00, 0x7f, 0xff]),
+            TestSpec::RawTag(0x4242, vec![0x01, 0x02]),
+        ]))
+    }
+
+    #[test]
+    pub fn json_round_trip() {
+        let tags = vec![SerdeTag(get_tree()), SerdeTag(TestSpec::Segment(Master::Start)), SerdeTag(TestSpec::Segment(Master::End))];
+        let json = serde_json::to_string(&tags).unwrap();
+        let decoded: Vec<SerdeTag<TestSpec>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(tags, decoded);
+    }
+
+    #[test]
+    pub fn msgpack_round_trip() {
+        let tag = SerdeTag(get_tree());
+        let encoded = rmp_serde::to_vec(&tag).unwrap();
+        let decoded: SerdeTag<TestSpec> = rmp_serde::from_slice(&encoded).unwrap();
+        assert_eq!(tag, decoded);
+    }
+
+    #[test]
+    pub fn representation_is_self_describing() {
+        let serialized = SerializedTag::from_tag(&TestSpec::Parent(Master::Full(vec![TestSpec::Child(3)])));
+        let json = serde_json::to_value(&serialized).unwrap();
+        assert_eq!(serde_json::json!({
+            "id": 0x4103,
+            "name": "Parent",
+            "value": { "children": [
+                { "id": 0x210301, "name": "Child", "value": { "unsigned_int": 3 } },
+            ]},
+        }), json);
+    }
+
+    #[test]
+    pub fn name_is_optional() {
+        let tag: SerdeTag<TestSpec> = serde_json::from_str(r#"{"id":16641,"value":{"unsigned_int":9}}"#).unwrap();
+        assert_eq!(TestSpec::Int(9), tag.0);
+    }
+
+    #[test]
+    pub fn rejects_mismatched_value() {
+        let serialized = SerializedTag { id: 0x4101, name: None, value: SerializedValue::Utf8(String::from("9")) };
+        assert!(matches!(serialized.into_tag::<TestSpec>(), Err(SerializedTagError::InvalidValue { id: 0x4101, expected: TagDataType::UnsignedInt })));
+
+        let result: Result<SerdeTag<TestSpec>, _> = serde_json::from_str(r#"{"id":16641,"value":{"float":9.0}}"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    pub fn rejects_unknown_tag() {
+        let serialized = SerializedTag { id: 0x4242, name: None, value: SerializedValue::UnsignedInt(1) };
+        assert!(matches!(serialized.into_tag::<TestSpec>(), Err(SerializedTagError::UnknownTag { id: 0x4242 })));
+    }
+}
@@ -0,0 +1,63 @@
+mod test_spec;
+
+pub mod size_past_end_tests {
+    use std::io::Cursor;
+
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::iterator::SizePastEnd;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn get_truncated_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x07; 100])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let mut data = writer.into_inner().unwrap();
+        data.truncate(data.len() - 40);
+        data
+    }
+
+    #[test]
+    pub fn error_at_header() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_truncated_data()), &[]);
+        iter.set_size_past_end(SizePastEnd::Error);
+        let len = iter.detect_source_len().unwrap();
+        assert_eq!(get_truncated_data().len(), len);
+
+        assert_eq!(TestSpec::Segment(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::TrackType(0x01), iter.next().unwrap().unwrap());
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::SizePastEnd { position: 15, tag_id: 0x1F43B675, size: 106, available: 66 })))));
+    }
+
+    #[test]
+    pub fn truncate_elements() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_truncated_data()), &[]);
+        iter.set_size_past_end(SizePastEnd::Truncate);
+        iter.detect_source_len().unwrap();
+
+        let tags: Vec<TestSpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Block(vec![0x07; 60]),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], tags);
+        assert_eq!(2, iter.metrics().truncated_elements);
+    }
+
+    #[test]
+    pub fn read_by_default() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_truncated_data()), &[]);
+        iter.detect_source_len().unwrap();
+        let results: Vec<_> = iter.by_ref().take(10).collect();
+        assert!(matches!(results[4], Err(TagIteratorError::UnexpectedEOF(_))));
+        assert_eq!(0, iter.metrics().truncated_elements);
+    }
+}
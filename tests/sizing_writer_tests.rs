@@ -0,0 +1,52 @@
+mod test_spec;
+
+pub mod sizing_writer_tests {
+    use ebml_iterable::error::TagWriterError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{SizingWriter, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn tags() -> Vec<(TestSpec, WriteOptions)> {
+        vec![
+            (TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()),
+            (TestSpec::TrackType(0x1234), WriteOptions::default()),
+            (TestSpec::Cluster(Master::Start), WriteOptions::set_size_byte_count(4)),
+            (TestSpec::Count(1), WriteOptions::default()),
+            (TestSpec::Block(vec![0; 300]), WriteOptions::default()),
+            (TestSpec::Cluster(Master::End), WriteOptions::default()),
+            (TestSpec::Cluster(Master::Full(vec![TestSpec::CueRefCluster(u64::MAX)])), WriteOptions::default()),
+            (TestSpec::Segment(Master::End), WriteOptions::default()),
+            (TestSpec::Root(Master::Start), WriteOptions::default()),
+            (TestSpec::String(String::from("sized")), WriteOptions::default()),
+        ]
+    }
+
+    #[test]
+    pub fn matches_written_length() {
+        let mut writer = TagWriter::new(Vec::new());
+        let mut sizer = SizingWriter::new();
+        for (tag, options) in tags() {
+            writer.write_advanced(&tag, options).unwrap();
+            sizer.write_advanced(&tag, options).unwrap();
+        }
+        writer.write_raw(0xec, &[0; 5]).unwrap();
+        sizer.write_raw(0xec, &[0; 5]).unwrap();
+
+        let written = writer.into_inner().unwrap();
+        assert_eq!(written.len() as u64, sizer.finish().unwrap());
+    }
+
+    #[test]
+    pub fn counts_closed_tags() {
+        let mut sizer = SizingWriter::new();
+        sizer.write(&TestSpec::Root(Master::Start)).unwrap();
+        sizer.write(&TestSpec::Int(1)).unwrap();
+        assert_eq!(0, sizer.size());
+        sizer.write(&TestSpec::Root(Master::End)).unwrap();
+        assert_eq!(6, sizer.size());
+
+        assert!(matches!(sizer.write(&TestSpec::Count(1)), Err(TagWriterError::UnexpectedTag { tag_id: 0x4100, .. })));
+        assert_eq!(6, sizer.finish().unwrap());
+    }
+}
@@ -1,7 +1,7 @@
 mod test_spec;
 
 pub mod spec_write_read {
-    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::error::{TagIteratorError, TagWriterError};
     use ebml_iterable::specs::{Master, EbmlTag};
     use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
     use std::io::Cursor;
@@ -214,10 +214,10 @@ pub mod spec_write_read {
         let err = iter.next().expect("Shouldn't have reached end of data");
         
         match err.expect_err("Should be an error") {
-            TagIteratorError::UnexpectedEOF { tag_start, tag_id, tag_size, partial_data: _ } => {
-                assert_eq!(tag_start, 20);
-                assert_eq!(tag_id, Some(TestSpec::Block(vec![]).get_id()));
-                assert_eq!(tag_size, Some(9));
+            TagIteratorError::UnexpectedEOF(partial) => {
+                assert_eq!(partial.tag_start, 20);
+                assert_eq!(partial.id, Some(TestSpec::Block(vec![]).get_id()));
+                assert_eq!(partial.size, Some(9));
             },
             other => {
                 println!("{other:?}");
@@ -226,6 +226,29 @@ pub mod spec_write_read {
         }
     }
 
+    #[test]
+    pub fn eof_partial_tag_can_resume() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0, 1, 2, 3, 4, 5, 6, 7, 8])]))]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..data.len() - 5], &[]);
+        let mut partial = match iter.find(|tag| tag.is_err()) {
+            Some(Err(TagIteratorError::UnexpectedEOF(partial))) => partial,
+            other => panic!("{:?}", other),
+        };
+        let tag_start = data.len() - 11;
+        assert_eq!(Some(tag_start..(tag_start + 2)), partial.header_range());
+        assert_eq!(Some(vec![0, 1, 2, 3]), partial.data);
+        assert_eq!(Some(5), partial.missing());
+        assert!(partial.is_resumable());
+
+        assert!(!partial.resume(&data[(data.len() - 5)..(data.len() - 2)]).unwrap());
+        assert!(partial.resume(&data[(data.len() - 2)..]).unwrap());
+        assert_eq!(Some(vec![0, 1, 2, 3, 4, 5, 6, 7, 8]), partial.data);
+        assert_eq!(Some(0), partial.missing());
+    }
+
     #[test]
     pub fn eof_on_tag_size() {
         let tags: Vec<TestSpec> = vec![
@@ -256,11 +279,11 @@ pub mod spec_write_read {
         let err = iter.next().expect("Shouldn't have reached end of data");
         
         match err.expect_err("Should be an error") {
-            TagIteratorError::UnexpectedEOF { tag_start, tag_id, tag_size, partial_data: _ } => {
-                println!("got error - {tag_start}, {tag_id:?}, {tag_size:?}");
-                assert_eq!(tag_start, 30);
-                assert_eq!(tag_id, Some(TestSpec::Block(vec![]).get_id()));
-                assert_eq!(tag_size, None);
+            TagIteratorError::UnexpectedEOF(partial) => {
+                println!("got error - {partial:?}");
+                assert_eq!(partial.tag_start, 30);
+                assert_eq!(partial.id, Some(TestSpec::Block(vec![]).get_id()));
+                assert_eq!(partial.size, None);
             },
             other => {
                 println!("{other:?}");
@@ -331,4 +354,260 @@ pub mod spec_write_read {
             assert_eq!(tags[i], read_tags[i]);
         }       
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn read_empty_master_at_end() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![]))]))).expect("Test shouldn't error");
+
+        let mut src = Cursor::new(dest.get_ref().to_vec());
+        let read_tags: Vec<TestSpec> = TagIterator::new(&mut src, &[]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], read_tags);
+    }
+
+    #[test]
+    pub fn write_read_reserved_size_values() {
+        // Sizes like 127 would encode as all ones, which is reserved for unknown sizes
+        for len in [126, 127, 16383] {
+            let tag = TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x55; len])]))]));
+            let mut dest = Cursor::new(Vec::new());
+            let mut writer = TagWriter::new(&mut dest);
+            writer.write(&tag).expect("Test shouldn't error");
+
+            let mut src = Cursor::new(dest.get_ref().to_vec());
+            let read_tags: Vec<TestSpec> = TagIterator::new(&mut src, &[TestSpec::Segment(Master::Start)]).map(|t| t.unwrap()).collect();
+            assert_eq!(vec![tag], read_tags);
+        }
+    }
+
+    #[test]
+    pub fn read_nested_buffered_tags() {
+        let clusters = vec![
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![1, 2])])),
+            TestSpec::Cluster(Master::Full(vec![])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)])),
+        ];
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Full(clusters.clone()))).expect("Test shouldn't error");
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).expect("Test shouldn't error");
+        writer.write(&TestSpec::TrackType(1)).expect("Test shouldn't error");
+        writer.write(&clusters[0]).expect("Test shouldn't error");
+
+        let src = dest.get_ref().to_vec();
+        let read_tags: Vec<TestSpec> = TagIterator::new(&src[..], &[TestSpec::Segment(Master::Start), TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Full(clusters.clone())),
+            TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(1), clusters[0].clone()])),
+        ], read_tags);
+
+        let read_tags: Vec<TestSpec> = TagIterator::new(&src[..], &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(9, read_tags.len());
+        assert_eq!(clusters[..], read_tags[1..4]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "bytes"))]
+    pub fn recycled_payloads_are_reused() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full((0..5u8).map(|i| TestSpec::Block(vec![i; 32])).collect())),
+        ]))).expect("Test shouldn't error");
+
+        let src = dest.get_ref().to_vec();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&src[..], &[]);
+        let mut blocks = 0;
+        let mut last_ptr = None;
+        while let Some(tag) = iter.next() {
+            if let TestSpec::Block(data) = tag.unwrap() {
+                assert_eq!(vec![blocks; 32], data);
+                if let Some(ptr) = last_ptr {
+                    assert_eq!(ptr, data.as_ptr());
+                }
+                last_ptr = Some(data.as_ptr());
+                iter.recycle_payload(data);
+                blocks += 1;
+            }
+        }
+        assert_eq!(5, blocks);
+    }
+
+    #[test]
+    pub fn write_headers_of_adjacent_masters_in_order() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Test shouldn't error");
+        writer.write(&TestSpec::Cluster(Master::Start)).expect("Test shouldn't error");
+        writer.write(&TestSpec::Cluster(Master::End)).expect("Test shouldn't error");
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::set_size_byte_count(2)).expect("Test shouldn't error");
+        writer.write(&TestSpec::Count(3)).expect("Test shouldn't error");
+        writer.write(&TestSpec::Cluster(Master::End)).expect("Test shouldn't error");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Test shouldn't error");
+
+        assert_eq!(vec![
+            0x18, 0x53, 0x80, 0x67, 0x8f,
+            0x1f, 0x43, 0xb6, 0x75, 0x80,
+            0x1f, 0x43, 0xb6, 0x75, 0x40, 0x04,
+            0x41, 0x00, 0x81, 0x03,
+        ], dest.get_ref().to_vec());
+    }
+
+    #[test]
+    pub fn full_tags_stream_to_destination() {
+        let clusters: Vec<TestSpec> = (0..4u64).map(|i| TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i * 1000), TestSpec::Block(vec![i as u8; 500]), TestSpec::CueRefCluster(i)]))).collect();
+
+        let mut buffered = TagWriter::new(Vec::new());
+        buffered.write(&TestSpec::Segment(Master::Start)).expect("Test shouldn't error");
+        for cluster in clusters.iter() {
+            buffered.write(cluster).expect("Test shouldn't error");
+        }
+        buffered.write(&TestSpec::Segment(Master::End)).expect("Test shouldn't error");
+        let buffered = buffered.into_inner().expect("Test shouldn't error");
+
+        let mut direct = TagWriter::new(Vec::new());
+        direct.write(&TestSpec::Segment(Master::Full(clusters.clone()))).expect("Test shouldn't error");
+        let metrics = direct.metrics();
+        let direct = direct.into_inner().expect("Test shouldn't error");
+
+        assert_eq!(buffered, direct);
+        assert!(metrics.buffer_high_water_mark < 600);
+
+        let read_tags: Vec<TestSpec> = TagIterator::new(&direct[..], &[TestSpec::Segment(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Full(clusters))], read_tags);
+    }
+
+    #[test]
+    pub fn failed_full_tag_writes_nothing() {
+        let valid = TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(1)]));
+
+        // Int isn't allowed in a Cluster, which is only found after the Segment header and first Cluster could have been written
+        let mut writer = TagWriter::new(Vec::new());
+        let invalid = TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Int(2)]))]));
+        assert!(matches!(writer.write(&invalid), Err(TagWriterError::UnexpectedTag { tag_id: 0x4101, .. })));
+        writer.write(&valid).expect("Test shouldn't error");
+
+        let mut expected = TagWriter::new(Vec::new());
+        expected.write(&valid).expect("Test shouldn't error");
+        assert_eq!(expected.into_inner().unwrap(), writer.into_inner().unwrap());
+    }
+
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl std::io::Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.chunk).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    pub fn read_in_small_chunks() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Full(
+            (0..50u8).map(|i| TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i as u64), TestSpec::Block(vec![i; i as usize * 3])]))).collect()
+        ))).expect("Test shouldn't error");
+
+        let src = dest.get_ref().to_vec();
+        let expected: Vec<(TestSpec, usize)> = {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&src[..], &[]);
+            std::iter::from_fn(|| iter.next().map(|t| (t.unwrap(), iter.last_emitted_tag_offset()))).collect()
+        };
+        for chunk in [1, 3, 7, 64] {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(ChunkedReader { data: &src, chunk }, &[], 32);
+            let read_tags: Vec<(TestSpec, usize)> = std::iter::from_fn(|| iter.next().map(|t| (t.unwrap(), iter.last_emitted_tag_offset()))).collect();
+            assert_eq!(expected, read_tags);
+        }
+    }
+
+    #[test]
+    pub fn emitted_tag_levels() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Count(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Block(vec![0x01])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let src = writer.into_inner().unwrap();
+
+        let read_levels = |data: &[u8], buffered: &[TestSpec]| -> Vec<(u64, usize, usize)> {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::new(data, buffered);
+            std::iter::from_fn(|| iter.next().map(|t| (t.unwrap().get_id(), iter.last_emitted_tag_level(), iter.last_emitted_tag_offset()))).collect()
+        };
+
+        let levels = read_levels(&src, &[]);
+        assert_eq!(vec![0, 1, 1, 2, 1, 1, 2, 1, 0], levels.iter().map(|l| l.1).collect::<Vec<_>>());
+
+        let buffered = read_levels(&src, &[TestSpec::Cluster(Master::Start)]);
+        assert_eq!(vec![0, 1, 1, 1, 0], buffered.iter().map(|l| l.1).collect::<Vec<_>>());
+
+        // Starting in the middle of a cluster still counts the implied parents
+        let count_offset = levels.iter().find(|l| l.0 == 0x4100).unwrap().2;
+        let partial = read_levels(&src[count_offset..], &[]);
+        assert_eq!((0x4100, 2), (partial[0].0, partial[0].1));
+        assert_eq!((0x1F43B675, 1), (partial[1].0, partial[1].1));
+    }
+
+    #[test]
+    pub fn skips_crc32_elements() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::Crc32(vec![0x01, 0x02, 0x03, 0x04])).unwrap();
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Crc32(vec![0x05, 0x06, 0x07, 0x08])).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let src = writer.into_inner().unwrap();
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&src[..], &[], 16);
+        iter.skip_crc32_elements(true);
+        let tags: Vec<TestSpec> = iter.collect::<Result<_, _>>().unwrap();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(1),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], tags);
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&src[..], &[TestSpec::Cluster(Master::Start)]);
+        iter.skip_crc32_elements(true);
+        let tags: Vec<TestSpec> = iter.collect::<Result<_, _>>().unwrap();
+        assert_eq!(TestSpec::Cluster(Master::Full(vec![])), tags[2]);
+    }
+
+    #[test]
+    pub fn emitted_tag_sizes() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::TrackType(0x0100)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0; 5])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let src = writer.into_inner().unwrap();
+
+        let read_sizes = |buffered: &[TestSpec]| -> Vec<Option<usize>> {
+            let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&src[..], buffered);
+            std::iter::from_fn(|| iter.next().map(|t| { t.unwrap(); iter.last_emitted_tag_size().known() })).collect()
+        };
+
+        assert_eq!(vec![None, Some(2), Some(11), Some(1), Some(5), Some(11), None], read_sizes(&[]));
+        assert_eq!(vec![None, Some(2), Some(11), None], read_sizes(&[TestSpec::Cluster(Master::Start)]));
+    }
+}
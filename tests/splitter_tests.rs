@@ -0,0 +1,108 @@
+mod test_spec;
+
+pub mod splitter_tests {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::Splitter;
+    use ebml_iterable::{TagIterator, TagWriter};
+    use std::cell::RefCell;
+    use std::io::{Cursor, Write};
+    use std::rc::Rc;
+
+    use super::test_spec::TestSpec;
+
+    fn cluster(value: u8) -> TestSpec {
+        TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![value; 16])]))
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Crc32(vec![0; 4]),
+            TestSpec::TrackType(0x01),
+            cluster(1),
+            cluster(2),
+            cluster(3),
+        ]))).unwrap();
+        drop(writer);
+
+        dest.into_inner()
+    }
+
+    fn read_all(data: &[u8]) -> Vec<TestSpec> {
+        TagIterator::new(data, &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect()
+    }
+
+    fn expected(clusters: &[u8]) -> Vec<TestSpec> {
+        let mut expected = vec![
+            TestSpec::Ebml(Master::Start),
+            TestSpec::Ebml(Master::End),
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+        ];
+        expected.extend(clusters.iter().map(|c| cluster(*c)));
+        expected.push(TestSpec::Segment(Master::End));
+        expected
+    }
+
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn split(splitter: &Splitter<TestSpec>) -> Vec<Vec<u8>> {
+        let outputs: Vec<Rc<RefCell<Vec<u8>>>> = Vec::new();
+        let outputs = RefCell::new(outputs);
+        let count = splitter.split(&get_data()[..], |index| {
+            assert_eq!(outputs.borrow().len(), index);
+            let buffer = Rc::new(RefCell::new(Vec::new()));
+            outputs.borrow_mut().push(buffer.clone());
+            Ok(SharedBuffer(buffer))
+        }).unwrap();
+
+        let outputs: Vec<Vec<u8>> = outputs.into_inner().into_iter().map(|o| o.borrow().clone()).collect();
+        assert_eq!(count, outputs.len());
+        outputs
+    }
+
+    #[test]
+    pub fn split_by_count() {
+        let mut splitter: Splitter<TestSpec> = Splitter::new(0x1F43B675);
+        splitter.set_max_elements(Some(2));
+        let outputs = split(&splitter);
+
+        assert_eq!(2, outputs.len());
+        assert_eq!(expected(&[1, 2]), read_all(&outputs[0]));
+        assert_eq!(expected(&[3]), read_all(&outputs[1]));
+    }
+
+    #[test]
+    pub fn split_by_bytes() {
+        let mut splitter: Splitter<TestSpec> = Splitter::new(0x1F43B675);
+        splitter.set_max_bytes(Some(1));
+        let outputs = split(&splitter);
+
+        assert_eq!(3, outputs.len());
+        assert_eq!(expected(&[1]), read_all(&outputs[0]));
+        assert_eq!(expected(&[2]), read_all(&outputs[1]));
+        assert_eq!(expected(&[3]), read_all(&outputs[2]));
+    }
+
+    #[test]
+    pub fn no_limits_writes_single_output() {
+        let splitter: Splitter<TestSpec> = Splitter::new(0x1F43B675);
+        let outputs = split(&splitter);
+
+        assert_eq!(1, outputs.len());
+        assert_eq!(expected(&[1, 2, 3]), read_all(&outputs[0]));
+    }
+}
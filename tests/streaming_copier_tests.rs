@@ -0,0 +1,67 @@
+mod test_spec;
+
+pub mod streaming_copier_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::StreamingCopier;
+    use ebml_iterable::TagWriter;
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Ebml(Master::Full(vec![]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::Full((0..4u64).map(|i| {
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i), TestSpec::Block(vec![i as u8; 500])]))
+        }).collect()))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn copy_without_pacing() {
+        let data = get_data();
+        let mut out = Vec::new();
+        let mut copier: StreamingCopier<TestSpec> = StreamingCopier::new();
+        assert_eq!(data.len(), copier.copy(&data[..], &mut out).unwrap());
+        assert_eq!(data, out);
+    }
+
+    #[test]
+    pub fn copy_at_rate() {
+        let data = get_data();
+        let mut out = Vec::new();
+        let mut copier: StreamingCopier<TestSpec> = StreamingCopier::new();
+        copier.set_bytes_per_second(Some(data.len() as u64 * 5));
+
+        let start = Instant::now();
+        copier.copy(&data[..], &mut out).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(190), "copy finished in {:?}", start.elapsed());
+        assert_eq!(data, out);
+    }
+
+    #[test]
+    pub fn copy_in_real_time() {
+        let data = get_data();
+        let mut out = Vec::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut copier: StreamingCopier<TestSpec> = StreamingCopier::new();
+        let callback_seen = seen.clone();
+        copier.set_timestamp_callback(move |id, data| {
+            callback_seen.borrow_mut().push(id);
+            // Each cluster "starts" 50ms after the previous one
+            match id {
+                0x4100 => Some(Duration::from_millis(50 * data[data.len() - 1] as u64)),
+                _ => None,
+            }
+        });
+
+        let start = Instant::now();
+        copier.copy(&data[..], &mut out).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(150), "copy finished in {:?}", start.elapsed());
+        assert_eq!(data, out);
+        assert_eq!(vec![0x4100, 0xa1, 0x4100, 0xa1, 0x4100, 0xa1, 0x4100, 0xa1], *seen.borrow());
+    }
+}
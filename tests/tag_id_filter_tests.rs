@@ -0,0 +1,90 @@
+mod test_spec;
+
+pub mod tag_id_filter_tests {
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data(unknown_size_segment: bool) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(1), TestSpec::String("root".to_string())]))).unwrap();
+        if unknown_size_segment {
+            writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        } else {
+            writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        }
+        writer.write(&TestSpec::TrackType(1)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 100])]))).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::SimpleBlock(vec![0x02; 10])]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn read(data: &[u8], ids: Option<&[u64]>, buffer: &[TestSpec]) -> Vec<TestSpec> {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(data, buffer);
+        iter.set_tag_id_filter(ids);
+        iter.map(|t| t.unwrap()).collect()
+    }
+
+    #[test]
+    pub fn only_listed_elements_and_their_ancestors_are_emitted() {
+        let data = get_data(false);
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(1),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(2),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], read(&data, Some(&[0x4100]), &[]));
+
+        assert_eq!(vec![TestSpec::Root(Master::Start), TestSpec::String("root".to_string()), TestSpec::Root(Master::End)], read(&data, Some(&[0x4102]), &[]));
+    }
+
+    #[test]
+    pub fn descendants_of_listed_elements_are_emitted() {
+        let data = get_data(false);
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Full(vec![
+                TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 100])])),
+                TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::SimpleBlock(vec![0x02; 10])])),
+            ])),
+        ], read(&data, Some(&[0x1f43b675]), &[TestSpec::Segment(Master::Start)]));
+    }
+
+    #[test]
+    pub fn unknown_size_elements_are_read_without_being_emitted() {
+        let data = get_data(true);
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x01; 100])])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::SimpleBlock(vec![0x02; 10])])),
+            TestSpec::Segment(Master::End),
+        ], read(&data, Some(&[0xa1, 0xa3]), &[TestSpec::Cluster(Master::Start)]));
+
+        // The segment can't be stepped over, but none of its children are emitted
+        assert_eq!(vec![TestSpec::Root(Master::Full(vec![TestSpec::Int(1)]))], read(&data, Some(&[0x4101]), &[TestSpec::Root(Master::Start)]));
+    }
+
+    #[test]
+    pub fn skipped_elements_are_not_decoded() {
+        let data = get_data(false);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        iter.set_tag_id_filter(Some(&[0x83]));
+        let tags: Vec<TestSpec> = iter.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Start), TestSpec::TrackType(1), TestSpec::Segment(Master::End)], tags);
+        assert_eq!(3, iter.metrics().tags_emitted);
+    }
+
+    #[test]
+    pub fn everything_is_emitted_without_a_filter() {
+        for unknown_size_segment in [false, true] {
+            let data = get_data(unknown_size_segment);
+            let expected: Vec<TestSpec> = TagIterator::new(&data[..], &[]).map(|t| t.unwrap()).collect();
+            assert_eq!(expected, read(&data, None, &[]));
+        }
+    }
+}
@@ -0,0 +1,75 @@
+mod test_spec;
+
+pub mod tag_span_tests {
+    use ebml_iterable::iterator::{ElementSize, TagSpan};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    fn get_data() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01; 200])]))).unwrap();
+        writer.write_advanced(&TestSpec::Cluster(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&TestSpec::Count(2)).unwrap();
+        writer.write(&TestSpec::Cluster(Master::End)).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn spans_slice_the_original_tags() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let spans: Vec<(TestSpec, TagSpan)> = iter.iter_with_spans().map(|t| t.unwrap()).collect();
+        assert_eq!(10, spans.len());
+
+        for (tag, span) in spans.iter() {
+            let range = match span.range() {
+                Some(range) => range,
+                None => {
+                    assert!(matches!(tag, TestSpec::Cluster(_)));
+                    continue;
+                },
+            };
+            if !matches!(tag, TestSpec::Segment(_) | TestSpec::Cluster(_)) {
+                let mut reread: TagIterator<_, TestSpec> = TagIterator::new(&data[range], &[]);
+                reread.allow_errors(&[ebml_iterable::iterator::AllowableErrors::HierarchyProblems]);
+                assert_eq!(tag, &reread.next().unwrap().unwrap());
+            }
+        }
+
+        let block = &spans[4];
+        assert_eq!(TestSpec::Block(vec![0x01; 200]), block.0);
+        assert_eq!(TagSpan { offset: block.1.offset, header_len: 3, size: ElementSize::Known(200) }, block.1);
+        assert_eq!(&[0x01; 200][..], &data[block.1.data_offset()..block.1.end_offset().unwrap()]);
+    }
+
+    #[test]
+    pub fn master_ends_span_the_whole_master() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let spans: Vec<(TestSpec, TagSpan)> = iter.iter_with_spans().map(|t| t.unwrap()).collect();
+        assert_eq!(TestSpec::Segment(Master::Start), spans[0].0);
+        assert_eq!(TestSpec::Segment(Master::End), spans[9].0);
+        assert_eq!(spans[0].1, spans[9].1);
+        assert_eq!(Some(data.len()), spans[9].1.end_offset());
+
+        // The unknown-sized cluster has no end
+        assert_eq!(TestSpec::Cluster(Master::End), spans[8].0);
+        assert_eq!(ElementSize::Unknown, spans[8].1.size);
+        assert_eq!(12, spans[8].1.header_len);
+    }
+
+    #[test]
+    pub fn spans_match_buffered_tags() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[TestSpec::Segment(Master::Start)]);
+        iter.next().unwrap().unwrap();
+        let span = iter.last_emitted_tag_span();
+        assert_eq!(TagSpan { offset: 0, header_len: 6, size: ElementSize::Known(data.len() - 6) }, span);
+        assert!(iter.iter_with_spans().next().is_none());
+    }
+}
@@ -0,0 +1,72 @@
+mod test_spec;
+
+pub mod tee_writer_tests {
+    use std::io::{self, ErrorKind, Write};
+
+    use ebml_iterable::error::TagWriterError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::TeeWriter;
+    use ebml_iterable::TagWriter;
+
+    use super::test_spec::TestSpec;
+
+    // Accepts `limit` bytes and then fails
+    struct LimitedWriter {
+        data: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for LimitedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.data.len() + buf.len() > self.limit {
+                return Err(io::Error::new(ErrorKind::BrokenPipe, "limit reached"));
+            }
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn write_document<W: Write>(writer: &mut TagWriter<W>) -> Result<(), TagWriterError> {
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(1)])))?;
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(2)])))?;
+        writer.flush()
+    }
+
+    #[test]
+    pub fn writes_to_both_destinations() {
+        let mut writer = TagWriter::new(TeeWriter::new(Vec::new(), Vec::new()));
+        write_document(&mut writer).unwrap();
+        let tee = writer.into_inner().unwrap();
+        assert!(tee.first_error().is_none() && tee.second_error().is_none());
+
+        let mut expected = TagWriter::new(Vec::new());
+        write_document(&mut expected).unwrap();
+        let (first, second) = tee.into_inner();
+        assert_eq!(expected.into_inner().unwrap(), first);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    pub fn keeps_writing_after_one_destination_fails() {
+        let mut writer = TagWriter::new(TeeWriter::new(Vec::new(), LimitedWriter { data: Vec::new(), limit: 8 }));
+        write_document(&mut writer).unwrap();
+        let tee = writer.into_inner().unwrap();
+        assert!(tee.first_error().is_none());
+        assert_eq!(ErrorKind::BrokenPipe, tee.second_error().unwrap().kind());
+
+        let (first, second) = tee.into_inner();
+        assert_eq!(14, first.len());
+        assert_eq!(first[..8], second.data[..]);
+    }
+
+    #[test]
+    pub fn fails_once_every_destination_fails() {
+        let tee = TeeWriter::new(LimitedWriter { data: Vec::new(), limit: 0 }, LimitedWriter { data: Vec::new(), limit: 0 });
+        let mut writer = TagWriter::new(tee);
+        assert!(matches!(write_document(&mut writer), Err(TagWriterError::WriteError { .. })));
+    }
+}
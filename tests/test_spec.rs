@@ -1,3 +1,5 @@
+#![allow(clippy::match_single_binding)]
+
 // use ebml_iterable_specification_derive::easy_ebml;
 // easy_ebml!(
 //     pub enum TestSpec {
@@ -95,6 +97,46 @@ impl ebml_iterable::specs::EbmlSpecification<TestSpec> for TestSpec {
             _ => &[],
         }
     }
+    fn get_name_by_id(id: u64) -> Option<&'static str> {
+        match id {
+            129u64 => Some("Root"),
+            16641u64 => Some("Int"),
+            16642u64 => Some("String"),
+            16643u64 => Some("Parent"),
+            2163457u64 => Some("Child"),
+            440786851u64 => Some("Ebml"),
+            408125543u64 => Some("Segment"),
+            131u64 => Some("TrackType"),
+            524531317u64 => Some("Cluster"),
+            151u64 => Some("CueRefCluster"),
+            16640u64 => Some("Count"),
+            161u64 => Some("Block"),
+            163u64 => Some("SimpleBlock"),
+            191u64 => Some("Crc32"),
+            236u64 => Some("Void"),
+            _ => None,
+        }
+    }
+    fn get_id_by_name(name: &str) -> Option<u64> {
+        match name {
+            "Root" => Some(129u64),
+            "Int" => Some(16641u64),
+            "String" => Some(16642u64),
+            "Parent" => Some(16643u64),
+            "Child" => Some(2163457u64),
+            "Ebml" => Some(440786851u64),
+            "Segment" => Some(408125543u64),
+            "TrackType" => Some(131u64),
+            "Cluster" => Some(524531317u64),
+            "CueRefCluster" => Some(151u64),
+            "Count" => Some(16640u64),
+            "Block" => Some(161u64),
+            "SimpleBlock" => Some(163u64),
+            "Crc32" => Some(191u64),
+            "Void" => Some(236u64),
+            _ => None,
+        }
+    }
     fn get_unsigned_int_tag(id: u64, data: u64) -> Option<TestSpec> {
         match id {
             16641u64 => Some(TestSpec::Int(data)),
@@ -125,6 +167,15 @@ impl ebml_iterable::specs::EbmlSpecification<TestSpec> for TestSpec {
             _ => None,
         }
     }
+    fn get_binary_tag_owned(id: u64, data: Vec<u8>) -> Option<TestSpec> {
+        match id {
+            161u64 => Some(TestSpec::Block(data)),
+            163u64 => Some(TestSpec::SimpleBlock(data)),
+            191u64 => Some(TestSpec::Crc32(data)),
+            236u64 => Some(TestSpec::Void(data)),
+            _ => None,
+        }
+    }
     fn get_float_tag(id: u64, _data: f64) -> Option<TestSpec> {
         match id {
             _ => None,
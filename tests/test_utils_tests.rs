@@ -0,0 +1,150 @@
+#[cfg(feature = "test-utils")]
+mod test_spec;
+
+#[cfg(feature = "test-utils")]
+pub mod test_utils_tests {
+    use arbitrary::Unstructured;
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::test_utils::{assert_conformant, assert_decodes_gracefully, assert_round_trip, check_conformance, corrupt, CorruptionStrategy, DocumentGenerator};
+    use ebml_iterable::TagWriter;
+
+    use super::test_spec::TestSpec;
+
+    const IDS: [u64; 13] = [0x81, 0x4101, 0x4102, 0x4103, 0x210301, 0x1a45dfa3, 0x18538067, 0x83, 0x1F43B675, 0x97, 0x4100, 0xa1, 0xec];
+
+    fn seed(index: u32) -> Vec<u8> {
+        // Simple xorshift so each iteration gets different (but repeatable) input
+        let mut state = index.wrapping_mul(0x9e3779b9) | 1;
+        (0..4096).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        }).collect()
+    }
+
+    #[test]
+    pub fn generated_documents_round_trip() {
+        let generator: DocumentGenerator<TestSpec> = DocumentGenerator::new(&IDS);
+        for i in 0..200 {
+            let data = seed(i);
+            let tags = generator.generate(&mut Unstructured::new(&data)).unwrap();
+            assert_round_trip(&tags);
+        }
+    }
+
+    #[test]
+    pub fn corrupted_documents_decode_gracefully() {
+        let generator: DocumentGenerator<TestSpec> = DocumentGenerator::new(&IDS);
+        for i in 0..200 {
+            let data = seed(i);
+            let corrupted = generator.generate_corrupted(&mut Unstructured::new(&data)).unwrap();
+            assert_decodes_gracefully::<TestSpec>(&corrupted);
+        }
+    }
+
+    #[test]
+    pub fn generated_documents_conform() {
+        let generator: DocumentGenerator<TestSpec> = DocumentGenerator::new(&IDS);
+        for i in 0..50 {
+            let data = generator.generate_bytes(&mut Unstructured::new(&seed(i))).unwrap();
+            assert_conformant::<TestSpec>(&data);
+        }
+    }
+
+    #[test]
+    pub fn report_continues_past_issues() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Start)).unwrap();
+        writer.write(&TestSpec::TrackType(0x01)).unwrap();
+        writer.write_raw(0xf2, &[0x01]).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)]))).unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let report = check_conformance::<TestSpec>(&data);
+        assert!(!report.is_conformant());
+        assert_eq!(6, report.tags_read);
+        assert_eq!(1, report.issues.len(), "{}", report);
+        assert!(matches!(report.issues[0], TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagId { tag_id: 0xf2, position: 8 })));
+        assert_eq!(vec![8..11], report.corrupt_ranges);
+    }
+
+    #[test]
+    pub fn trailing_data_is_an_issue() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(0x01)]))).unwrap();
+        let mut data = writer.into_inner().unwrap();
+        let document_len = data.len();
+        data.extend([0xff; 4]);
+
+        let report = check_conformance::<TestSpec>(&data);
+        assert_eq!(3, report.tags_read);
+        assert_eq!(1, report.issues.len(), "{}", report);
+        assert!(matches!(report.issues[0], TagIteratorError::CorruptedFileData(CorruptedFileError::TrailingData { position }) if position == document_len));
+    }
+
+    #[test]
+    pub fn truncated_documents_stop_the_check() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x01; 20])]))]))).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let report = check_conformance::<TestSpec>(&data[..data.len() - 5]);
+        assert_eq!(1, report.issues.len(), "{}", report);
+        assert!(matches!(report.issues[0], TagIteratorError::CorruptedFileData(CorruptedFileError::SizePastEnd { .. })));
+    }
+
+    fn nested_document() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Segment(Master::Full(vec![TestSpec::TrackType(0x01), TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)]))]))).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn corrupt_labels_each_element() {
+        let data = nested_document();
+        let variants = corrupt::<TestSpec>(&data, CorruptionStrategy::MangleId);
+        let elements: Vec<(u64, usize)> = variants.iter().map(|variant| (variant.tag_id, variant.position)).collect();
+        assert_eq!(vec![(0x18538067, 0), (0x83, 5), (0x1F43B675, 8), (0x4100, 13)], elements);
+        assert_eq!("mangled id in element 0x83 at 5", variants[1].to_string());
+        for variant in variants.iter() {
+            assert_eq!(data.len(), variant.data.len());
+            assert_ne!(data, variant.data);
+            assert!(!check_conformance::<TestSpec>(&variant.data).is_conformant(), "{}", variant);
+        }
+    }
+
+    #[test]
+    pub fn corrupt_damages_only_the_chosen_element() {
+        let data = nested_document();
+
+        let truncated = corrupt::<TestSpec>(&data, CorruptionStrategy::Truncate);
+        assert_eq!(vec![11, 7, 15, 16], truncated.iter().map(|variant| variant.data.len()).collect::<Vec<_>>());
+
+        let flipped = corrupt::<TestSpec>(&data, CorruptionStrategy::BitFlip);
+        assert_eq!(0x00, flipped[1].data[7]);
+        assert_eq!(1, flipped[1].data.iter().zip(data.iter()).filter(|(a, b)| a != b).count());
+
+        let inflated = corrupt::<TestSpec>(&data, CorruptionStrategy::InflateSize);
+        assert_eq!(data[..13], inflated[3].data[..13]);
+        assert_eq!([0x41, 0x00, 0xfe], inflated[3].data[13..16]);
+        let report = check_conformance::<TestSpec>(&inflated[3].data);
+        assert!(matches!(report.issues[0], TagIteratorError::CorruptedFileData(CorruptedFileError::InvalidTagData { tag_id: 0x4100, position: 13 })), "{}", report);
+    }
+
+    #[test]
+    pub fn corrupted_variants_decode_gracefully() {
+        let generator: DocumentGenerator<TestSpec> = DocumentGenerator::new(&IDS);
+        for i in 0..20 {
+            let data = generator.generate_bytes(&mut Unstructured::new(&seed(i))).unwrap();
+            for strategy in [CorruptionStrategy::BitFlip, CorruptionStrategy::Truncate, CorruptionStrategy::InflateSize, CorruptionStrategy::MangleId] {
+                for variant in corrupt::<TestSpec>(&data, strategy) {
+                    assert_decodes_gracefully::<TestSpec>(&variant.data);
+                    check_conformance::<TestSpec>(&variant.data);
+                }
+            }
+        }
+    }
+}
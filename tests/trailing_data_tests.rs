@@ -0,0 +1,75 @@
+mod test_spec;
+
+pub mod trailing_data_tests {
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::iterator::TrailingData;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn document(trailing: &[u8]) -> (Vec<u8>, usize) {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(1)]))).unwrap();
+        writer.write(&TestSpec::Root(Master::Full(vec![TestSpec::Int(2)]))).unwrap();
+        let mut data = writer.into_inner().unwrap();
+        let len = data.len();
+        data.extend_from_slice(trailing);
+        (data, len)
+    }
+
+    fn read_all(data: &[u8], behavior: TrailingData) -> (Vec<Result<TestSpec, TagIteratorError>>, u64) {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(data, &[TestSpec::Root(Master::Start)]);
+        iter.set_trailing_data(behavior);
+        // Corrupted data keeps producing errors, so cap the number of reads
+        let tags = iter.by_ref().take(10).collect();
+        (tags, iter.metrics().trailing_bytes)
+    }
+
+    #[test]
+    pub fn parses_trailing_data_by_default() {
+        let (data, _) = document(&[0x00, 0x00, 0xff, 0x12]);
+        let (tags, _) = read_all(&data, TrailingData::Parse);
+        assert!(tags[2].is_err());
+    }
+
+    #[test]
+    pub fn ignores_trailing_data() {
+        let (data, _) = document(&[0x00, 0x00, 0xff, 0x12]);
+        let (tags, trailing_bytes) = read_all(&data, TrailingData::Ignore);
+        assert_eq!(2, tags.len());
+        assert!(tags.iter().all(|tag| tag.is_ok()));
+        assert_eq!(0, trailing_bytes);
+
+    }
+
+    #[test]
+    pub fn truncated_element_is_not_trailing_data() {
+        let (data, _) = document(&[0x81, 0x88, 0x01]);
+        let (tags, _) = read_all(&data, TrailingData::Ignore);
+        assert!(matches!(tags[2], Err(TagIteratorError::UnexpectedEOF(_))));
+    }
+
+    #[test]
+    pub fn counts_trailing_bytes() {
+        let (data, _) = document(&[0x00; 100]);
+        let (tags, trailing_bytes) = read_all(&data, TrailingData::Warn);
+        assert_eq!(2, tags.len());
+        assert_eq!(100, trailing_bytes);
+    }
+
+    #[test]
+    pub fn reports_trailing_data() {
+        let (data, len) = document(&[0x00, 0x00, 0xff, 0x12]);
+        let (tags, _) = read_all(&data, TrailingData::Error);
+        assert_eq!(3, tags.len());
+        assert!(matches!(tags[2], Err(TagIteratorError::CorruptedFileData(CorruptedFileError::TrailingData { position })) if position == len));
+    }
+
+    #[test]
+    pub fn leading_garbage_is_still_an_error() {
+        let data = [0x00, 0x00, 0xff, 0x12];
+        let (tags, _) = read_all(&data, TrailingData::Ignore);
+        assert!(matches!(tags.first(), Some(Err(TagIteratorError::CorruptedFileData(_)))));
+    }
+}
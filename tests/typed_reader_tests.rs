@@ -0,0 +1,75 @@
+mod test_spec;
+
+pub mod typed_reader_tests {
+    use ebml_iterable::error::TypedReadError;
+    use ebml_iterable::specs::{Master, TagDataType};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    const ROOT_ID: u64 = 0x81;
+    const STRING_ID: u64 = 0x4102;
+    const INT_ID: u64 = 0x4101;
+    const PARENT_ID: u64 = 0x4103;
+    const CHILD_ID: u64 = 0x210301;
+
+    fn encode(tags: &[TestSpec]) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    fn document() -> Vec<u8> {
+        encode(&[TestSpec::Root(Master::Full(vec![
+            TestSpec::String(String::from("name")),
+            TestSpec::Int(7),
+            TestSpec::Parent(Master::Full(vec![TestSpec::Child(3)])),
+        ]))])
+    }
+
+    #[test]
+    pub fn reads_values_in_order() {
+        let data = document();
+        let mut reader = TagIterator::<_, TestSpec>::new(&data[..], &[]).typed();
+
+        reader.expect_start(ROOT_ID).unwrap();
+        assert_eq!("name", reader.expect_utf8(STRING_ID).unwrap());
+        assert_eq!(Some(INT_ID), reader.peek_id().unwrap());
+        assert_eq!(7, reader.next_uint().unwrap());
+        reader.expect_start(PARENT_ID).unwrap();
+        assert_eq!(3, reader.expect_uint(CHILD_ID).unwrap());
+        reader.expect_end(PARENT_ID).unwrap();
+        reader.expect_end(ROOT_ID).unwrap();
+        assert_eq!(None, reader.peek_id().unwrap());
+        assert!(matches!(reader.next_tag(), Err(TypedReadError::UnexpectedEnd { expected_id: None })));
+    }
+
+    #[test]
+    pub fn reads_buffered_masters() {
+        let data = document();
+        let mut reader = TagIterator::new(&data[..], &[TestSpec::Parent(Master::Start)]).typed();
+
+        reader.expect_start(ROOT_ID).unwrap();
+        reader.next_tag().unwrap();
+        reader.next_tag().unwrap();
+        assert_eq!(vec![TestSpec::Child(3)], reader.expect_full(PARENT_ID).unwrap());
+    }
+
+    #[test]
+    pub fn reports_unexpected_tags() {
+        let data = document();
+        let mut reader = TagIterator::<_, TestSpec>::new(&data[..], &[]).typed();
+
+        assert!(matches!(reader.expect_end(ROOT_ID), Err(TypedReadError::UnexpectedMasterVariant { id: ROOT_ID })));
+        assert!(matches!(reader.expect_uint(INT_ID), Err(TypedReadError::UnexpectedTag { id: STRING_ID, expected_id: INT_ID })));
+        assert!(matches!(reader.next_binary(), Err(TypedReadError::TypeMismatch { id: INT_ID, expected: TagDataType::Binary, found: Some(TagDataType::UnsignedInt) })));
+
+        let data = encode(&[TestSpec::Root(Master::Full(vec![]))]);
+        let mut reader = TagIterator::<_, TestSpec>::new(&data[..], &[]).typed();
+        reader.expect_start(ROOT_ID).unwrap();
+        reader.expect_end(ROOT_ID).unwrap();
+        assert!(matches!(reader.expect_start(ROOT_ID), Err(TypedReadError::UnexpectedEnd { expected_id: Some(ROOT_ID) })));
+    }
+}
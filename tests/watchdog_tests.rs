@@ -0,0 +1,127 @@
+mod test_spec;
+
+pub mod watchdog_tests {
+    use std::io::{self, Read};
+    use std::sync::mpsc::{self, Receiver};
+    use std::time::Duration;
+
+    use ebml_iterable::error::TagIteratorError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::utils::WatchdogReader;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    struct ChannelReader {
+        chunks: Receiver<Vec<u8>>,
+    }
+
+    impl Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    buf[..chunk.len()].copy_from_slice(&chunk);
+                    Ok(chunk.len())
+                },
+                Err(_) => Ok(0),
+            }
+        }
+    }
+
+    fn get_tag_data(tag: &TestSpec) -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(tag).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn stalled_source_times_out() {
+        let (send, chunks) = mpsc::channel();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_read_timeout(ChannelReader { chunks }, &[TestSpec::Root(Master::Start)], TIMEOUT);
+
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::Timeout { position: 0, timeout: Some(TIMEOUT) }))));
+
+        let tag = TestSpec::Root(Master::Full(vec![TestSpec::Int(5)]));
+        let data = get_tag_data(&tag);
+        send.send(data[..2].to_vec()).unwrap();
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::Timeout { position: 2, .. }))));
+
+        send.send(data[2..].to_vec()).unwrap();
+        drop(send);
+        assert_eq!(tag, iter.next().unwrap().unwrap());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    pub fn watchdog_reader_keeps_data_that_did_not_fit() {
+        let (send, chunks) = mpsc::channel();
+        let mut reader = WatchdogReader::new(ChannelReader { chunks }, TIMEOUT);
+
+        let mut buf = [0u8; 8];
+        assert_eq!(io::ErrorKind::TimedOut, reader.read(&mut buf).unwrap_err().kind());
+
+        send.send(vec![1, 2, 3, 4, 5, 6]).unwrap();
+        drop(send);
+        let mut small = [0u8; 4];
+        assert_eq!(4, reader.read(&mut small).unwrap());
+        assert_eq!([1, 2, 3, 4], small);
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(vec![5, 6], rest);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    pub fn async_stalled_source_times_out() {
+        use ebml_iterable::nonblocking::TagIteratorAsync;
+        use futures::executor::block_on;
+        use futures::TryStreamExt;
+
+        let (send, chunks) = futures::channel::mpsc::unbounded::<io::Result<Vec<u8>>>();
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(chunks.into_async_read(), &[TestSpec::Root(Master::Start)]);
+        iter.set_read_timeout(Some(TIMEOUT));
+
+        assert!(matches!(block_on(iter.next()), Some(Err(TagIteratorError::Timeout { position: 0, timeout: Some(TIMEOUT) }))));
+
+        let tag = TestSpec::Root(Master::Full(vec![TestSpec::Int(5)]));
+        send.unbounded_send(Ok(get_tag_data(&tag))).unwrap();
+        send.close_channel();
+        assert_eq!(tag, block_on(iter.next()).unwrap().unwrap());
+        assert!(block_on(iter.next()).is_none());
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    pub fn async_timeouts_use_the_provided_timer() {
+        use std::sync::{Arc, Mutex};
+        use ebml_iterable::nonblocking::TagIteratorAsync;
+        use futures::executor::block_on;
+        use futures::TryStreamExt;
+
+        let (send, chunks) = futures::channel::mpsc::unbounded::<io::Result<Vec<u8>>>();
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(chunks.into_async_read(), &[TestSpec::Root(Master::Start)]);
+        let armed = Arc::new(Mutex::new(Vec::new()));
+        let timer_armed = armed.clone();
+        // Expires as soon as it is polled, so every read that has to wait times out
+        iter.set_read_timeout_with(Some(TIMEOUT), move |timeout| {
+            timer_armed.lock().unwrap().push(timeout);
+            futures::future::ready(())
+        });
+
+        assert!(matches!(block_on(iter.next()), Some(Err(TagIteratorError::Timeout { position: 0, timeout: Some(TIMEOUT) }))));
+
+        let tag = TestSpec::Root(Master::Full(vec![TestSpec::Int(5)]));
+        let data = get_tag_data(&tag);
+        send.unbounded_send(Ok(data[..2].to_vec())).unwrap();
+        assert!(matches!(block_on(iter.next()), Some(Err(TagIteratorError::Timeout { position: 2, .. }))));
+
+        send.unbounded_send(Ok(data[2..].to_vec())).unwrap();
+        send.close_channel();
+        assert_eq!(tag, block_on(iter.next()).unwrap().unwrap());
+        assert!(block_on(iter.next()).is_none());
+        assert_eq!(vec![TIMEOUT, TIMEOUT], *armed.lock().unwrap());
+    }
+}
@@ -0,0 +1,180 @@
+mod test_spec;
+
+pub mod write_recovery_tests {
+    use std::io::{ErrorKind, Write};
+
+    use ebml_iterable::error::TagWriterError;
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter, WriteOptions};
+
+    use super::test_spec::TestSpec;
+
+    // Accepts `budget` more bytes before failing, like a connection that drops
+    struct FlakyWriter {
+        data: Vec<u8>,
+        budget: usize,
+    }
+
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.budget == 0 {
+                return Err(ErrorKind::BrokenPipe.into());
+            }
+            let len = buf.len().min(self.budget);
+            self.budget -= len;
+            self.data.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn tags() -> Vec<TestSpec> {
+        (1..=5).map(|i| TestSpec::Cluster(Master::Full(vec![TestSpec::Count(i), TestSpec::Block(vec![i as u8; 100])]))).collect()
+    }
+
+    fn expected() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        for tag in tags() {
+            writer.write(&tag).unwrap();
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn nested_tags() -> Vec<TestSpec> {
+        vec![
+            TestSpec::Root(Master::Full(vec![TestSpec::Int(1), TestSpec::Parent(Master::Full(vec![TestSpec::Child(2)])), TestSpec::String(String::from("three"))])),
+            TestSpec::Segment(Master::Full(vec![
+                TestSpec::TrackType(1),
+                TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![7; 10]), TestSpec::SimpleBlock(vec![8; 10])])),
+                TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Block(vec![9; 10])])),
+            ])),
+        ]
+    }
+
+    fn nested_expected() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        for tag in nested_tags() {
+            writer.write(&tag).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn nested_full_tags_can_be_retried_after_failing_at_any_offset() {
+        let expected = nested_expected();
+        for budget in 0..expected.len() {
+            let mut writer = TagWriter::new(FlakyWriter { data: Vec::new(), budget });
+            let mut failures = 0;
+            for tag in nested_tags() {
+                if let Err(err) = writer.write(&tag) {
+                    assert!(matches!(err, TagWriterError::WriteError { .. }), "{:?}", err);
+                    assert!(writer.open_tag_ids().is_empty());
+                    failures += 1;
+
+                    writer.get_mut().budget = usize::MAX;
+                    writer.retry_write().unwrap();
+                }
+            }
+            assert_eq!(1, failures, "failure at byte {}", budget);
+            assert_eq!(expected, writer.into_inner().unwrap().data, "failure at byte {}", budget);
+        }
+    }
+
+    #[test]
+    pub fn nested_full_tags_can_be_redirected_after_failing_at_any_offset() {
+        let expected = nested_expected();
+        for budget in 0..expected.len() {
+            let mut writer = TagWriter::new(FlakyWriter { data: Vec::new(), budget });
+            let mut sent = Vec::new();
+            for tag in nested_tags() {
+                if let Err(err) = writer.write(&tag) {
+                    assert!(matches!(err, TagWriterError::WriteError { .. }), "{:?}", err);
+                    sent = writer.replace_destination(FlakyWriter { data: Vec::new(), budget: usize::MAX }).data;
+                }
+            }
+            assert_eq!(budget, sent.len());
+            let document = [sent, writer.into_inner().unwrap().data].concat();
+            assert_eq!(expected, document, "failure at byte {}", budget);
+        }
+    }
+
+    #[test]
+    pub fn failed_writes_can_be_retried() {
+        let mut writer = TagWriter::new(FlakyWriter { data: Vec::new(), budget: 150 });
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        let mut failures = 0;
+        for tag in tags() {
+            if let Err(err) = writer.write(&tag) {
+                assert!(matches!(err, TagWriterError::WriteError { .. }), "{:?}", err);
+                assert_eq!(vec![0x18538067], writer.open_tag_ids());
+                assert!(writer.unwritten_len() > 0);
+                failures += 1;
+
+                writer.retry_write().unwrap_err();
+                writer.get_mut().budget = 150;
+                writer.retry_write().unwrap();
+            }
+        }
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+        assert!(failures > 1);
+        assert_eq!(0, writer.unwritten_len());
+        assert_eq!(expected(), writer.into_inner().unwrap().data);
+    }
+
+    #[test]
+    pub fn full_tags_are_completed_after_a_failed_child() {
+        let cluster = TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![7; 10]), TestSpec::Block(vec![8; 10])]));
+
+        // Room for the segment header, the cluster header, and half of the first child
+        let mut writer = TagWriter::new(FlakyWriter { data: Vec::new(), budget: 19 });
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        assert!(matches!(writer.write(&cluster), Err(TagWriterError::WriteError { .. })));
+        assert_eq!(vec![0x18538067], writer.open_tag_ids());
+        assert_eq!(26, writer.unwritten_len());
+
+        writer.get_mut().budget = usize::MAX;
+        writer.retry_write().unwrap();
+        writer.write(&TestSpec::Segment(Master::End)).unwrap();
+
+        let data = writer.into_inner().unwrap().data;
+        let read: Vec<TestSpec> = TagIterator::new(&data[..], &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Segment(Master::Start), cluster, TestSpec::Segment(Master::End)], read);
+    }
+
+    #[test]
+    pub fn documents_can_be_completed_on_a_new_destination() {
+        let mut writer = TagWriter::new(FlakyWriter { data: Vec::new(), budget: 200 });
+        writer.write_advanced(&TestSpec::Segment(Master::Start), WriteOptions::is_unknown_sized_element()).unwrap();
+        writer.write(&tags()[0]).unwrap();
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Count(2)).unwrap();
+        let result = writer.write(&TestSpec::Block(vec![0x02; 100]));
+        assert!(result.is_ok(), "{:?}", result);
+        let result = writer.write(&TestSpec::Cluster(Master::End));
+        assert!(matches!(result, Err(TagWriterError::WriteError { .. })), "{:?}", result);
+        assert_eq!(vec![0x18538067], writer.open_tag_ids());
+
+        // The open cluster is closed on the new destination
+        writer.write(&TestSpec::Cluster(Master::Start)).unwrap();
+        writer.write(&TestSpec::Count(3)).unwrap();
+        assert_eq!(vec![0x18538067, 0x1f43b675], writer.open_tag_ids());
+        let sent = writer.replace_destination(FlakyWriter { data: Vec::new(), budget: usize::MAX }).data;
+        writer.flush().unwrap();
+        assert!(writer.open_tag_ids().is_empty());
+
+        let document = [sent, writer.into_inner().unwrap().data].concat();
+        let read: Vec<TestSpec> = TagIterator::new(&document[..], &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            tags()[0].clone(),
+            tags()[1].clone(),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(3)])),
+            TestSpec::Segment(Master::End),
+        ], read);
+    }
+}
@@ -0,0 +1,155 @@
+mod test_spec;
+
+pub mod zero_length_tests {
+    use ebml_iterable::error::{CorruptedFileError, TagIteratorError};
+    use ebml_iterable::iterator::ZeroLengthValues;
+    use ebml_iterable::specs::ebml_header::{self, EbmlHeader};
+    use ebml_iterable::specs::Master;
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    use super::test_spec::TestSpec;
+
+    fn empty_values() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&TestSpec::Root(Master::Start)).unwrap();
+        writer.write_raw(0x4101, &[]).unwrap();
+        writer.write_raw(0x4102, &[]).unwrap();
+        writer.write(&TestSpec::Root(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn emits_default_values() {
+        let data = empty_values();
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        let tags: Vec<TestSpec> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(vec![TestSpec::Root(Master::Start), TestSpec::Int(0), TestSpec::String(String::new()), TestSpec::Root(Master::End)], tags);
+        assert_eq!(0, reader.metrics().zero_length_values);
+    }
+
+    #[test]
+    pub fn emits_declared_default_values() {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&EbmlHeader::Ebml(Master::Start)).unwrap();
+        writer.write_raw(ebml_header::EBML_VERSION_ID, &[]).unwrap();
+        writer.write_raw(ebml_header::EBML_MAX_SIZE_LENGTH_ID, &[]).unwrap();
+        writer.write_raw(ebml_header::DOC_TYPE_ID, &[]).unwrap();
+        writer.write(&EbmlHeader::Ebml(Master::End)).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut reader: TagIterator<_, EbmlHeader> = TagIterator::new(&data[..], &[]);
+        let tags: Vec<EbmlHeader> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(vec![
+            EbmlHeader::Ebml(Master::Start),
+            EbmlHeader::EbmlVersion(1),
+            EbmlHeader::EbmlMaxSizeLength(8),
+            // DocType has no default
+            EbmlHeader::DocType(String::new()),
+            EbmlHeader::Ebml(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn warns_when_configured() {
+        let data = empty_values();
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        reader.set_zero_length_values(ZeroLengthValues::Warn);
+        let tags: Vec<TestSpec> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(vec![TestSpec::Root(Master::Start), TestSpec::Int(0), TestSpec::String(String::new()), TestSpec::Root(Master::End)], tags);
+        assert_eq!(2, reader.metrics().zero_length_values);
+    }
+
+    #[test]
+    pub fn errors_when_configured() {
+        let data = empty_values();
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(&data[..], &[]);
+        reader.set_zero_length_values(ZeroLengthValues::Error);
+
+        assert_eq!(TestSpec::Root(Master::Start), reader.next().unwrap().unwrap());
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::ZeroLengthValue { position: 2, tag_id: 0x4101 })))));
+        assert!(matches!(reader.next(), Some(Err(TagIteratorError::CorruptedFileData(CorruptedFileError::ZeroLengthValue { position: 5, tag_id: 0x4102 })))));
+        assert_eq!(TestSpec::Root(Master::End), reader.next().unwrap().unwrap());
+        assert!(reader.next().is_none());
+        assert_eq!(2, reader.metrics().zero_length_values);
+    }
+}
+
+#[cfg(feature = "derive-spec")]
+pub mod zero_length_default_tests {
+    use ebml_iterable::iterator::ZeroLengthValues;
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, Master, TagDataType};
+    use ebml_iterable::{TagIterator, TagWriter};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Defaults {
+        #[id(0x81)]
+        #[data_type(TagDataType::Master)]
+        Root,
+
+        #[id(0x82)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[doc_path(Root)]
+        #[default_value(0x10)]
+        Count,
+
+        #[id(0x83)]
+        #[data_type(TagDataType::Integer)]
+        #[doc_path(Root)]
+        #[default_value(-3)]
+        Offset,
+
+        #[id(0x84)]
+        #[data_type(TagDataType::Float)]
+        #[doc_path(Root)]
+        #[default_value(8000.0)]
+        Rate,
+
+        #[id(0x85)]
+        #[data_type(TagDataType::Utf8)]
+        #[doc_path(Root)]
+        #[default_value("eng")]
+        Language,
+    }
+
+    fn empty_values() -> Vec<u8> {
+        let mut writer = TagWriter::new(Vec::new());
+        writer.write(&Defaults::Root(Master::Start)).unwrap();
+        for id in 0x82..=0x85 {
+            writer.write_raw(id, &[]).unwrap();
+        }
+        writer.write(&Defaults::Root(Master::End)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    pub fn spec_reports_default_values() {
+        assert_eq!(Some("0x10"), Defaults::get_default_value(0x82));
+        assert_eq!(Some("eng"), Defaults::get_default_value(0x85));
+        assert_eq!(None, Defaults::get_default_value(0x81));
+    }
+
+    #[test]
+    pub fn emits_declared_default_values() {
+        let expected = vec![
+            Defaults::Root(Master::Start),
+            Defaults::Count(16),
+            Defaults::Offset(-3),
+            Defaults::Rate(8000.0),
+            Defaults::Language(String::from("eng")),
+            Defaults::Root(Master::End),
+        ];
+
+        let data = empty_values();
+        let mut reader: TagIterator<_, Defaults> = TagIterator::new(&data[..], &[]);
+        assert_eq!(expected, reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap());
+        assert_eq!(0, reader.metrics().zero_length_values);
+
+        let mut reader: TagIterator<_, Defaults> = TagIterator::new(&data[..], &[]);
+        reader.set_zero_length_values(ZeroLengthValues::Warn);
+        assert_eq!(expected, reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap());
+        assert_eq!(4, reader.metrics().zero_length_values);
+    }
+}